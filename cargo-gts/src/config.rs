@@ -0,0 +1,127 @@
+//! Per-package settings from `[package.metadata.gts]`.
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::{Metadata, Package};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The `[package.metadata.gts]` table.  Paths are relative to the package
+/// directory.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GtsMetadata {
+    /// Directory scanned for `#[struct_to_gts_schema]` structs.
+    pub source: String,
+    /// Output directory overriding each struct's `dir_path`; must be inside
+    /// `source`.
+    pub output: Option<String>,
+    /// Patterns of source files to skip, as for `gts generate-from-rust`.
+    pub exclude: Vec<String>,
+    /// Schema and instance directories loaded by `validate` and `audit`.
+    /// Defaults to `output`, or `source` where the generated schemas live.
+    pub paths: Vec<String>,
+    /// GTS config JSON overriding the defaults.
+    pub config: Option<String>,
+}
+
+impl Default for GtsMetadata {
+    fn default() -> Self {
+        Self {
+            source: "src".to_owned(),
+            output: None,
+            exclude: Vec::new(),
+            paths: Vec::new(),
+            config: None,
+        }
+    }
+}
+
+/// A workspace package and its GTS settings.
+#[derive(Debug, Clone)]
+pub struct GtsPackage {
+    pub name: String,
+    /// Directory containing the package's `Cargo.toml`.
+    pub root: PathBuf,
+    pub metadata: GtsMetadata,
+}
+
+impl GtsPackage {
+    fn from_package(package: &Package) -> Result<Self> {
+        let root = package
+            .manifest_path
+            .parent()
+            .map(|dir| dir.as_std_path().to_path_buf())
+            .unwrap_or_default();
+        let metadata = match package.metadata.get("gts") {
+            Some(table) => GtsMetadata::deserialize(table)
+                .with_context(|| format!("invalid [package.metadata.gts] in {}", package.name))?,
+            None => GtsMetadata::default(),
+        };
+        Ok(Self {
+            name: package.name.to_string(),
+            root,
+            metadata,
+        })
+    }
+
+    /// `path` resolved against the package directory.
+    pub fn resolve(&self, path: &str) -> String {
+        self.root.join(path).display().to_string()
+    }
+
+    pub fn source(&self) -> String {
+        self.resolve(&self.metadata.source)
+    }
+
+    pub fn output(&self) -> Option<String> {
+        self.metadata.output.as_deref().map(|p| self.resolve(p))
+    }
+
+    /// The directories to load entities from.
+    pub fn entity_paths(&self) -> Vec<String> {
+        if self.metadata.paths.is_empty() {
+            vec![self.output().unwrap_or_else(|| self.source())]
+        } else {
+            self.metadata
+                .paths
+                .iter()
+                .map(|p| self.resolve(p))
+                .collect()
+        }
+    }
+
+    pub fn config(&self) -> Option<String> {
+        self.metadata.config.as_deref().map(|p| self.resolve(p))
+    }
+}
+
+/// The workspace packages to act on: those named in `selected`, or else
+/// every member with a `[package.metadata.gts]` table.
+///
+/// # Errors
+/// Returns an error if a selected package is not a workspace member, a
+/// table is malformed, or nothing is selected and no member has a table.
+pub fn select_packages(metadata: &Metadata, selected: &[String]) -> Result<Vec<GtsPackage>> {
+    let members = metadata.workspace_packages();
+    let packages: Vec<&Package> = if selected.is_empty() {
+        members
+            .into_iter()
+            .filter(|package| package.metadata.get("gts").is_some())
+            .collect()
+    } else {
+        selected
+            .iter()
+            .map(|name| {
+                members
+                    .iter()
+                    .find(|package| package.name.as_str() == name)
+                    .copied()
+                    .with_context(|| format!("package '{name}' is not a workspace member"))
+            })
+            .collect::<Result<_>>()?
+    };
+    if packages.is_empty() {
+        bail!("no workspace package has a [package.metadata.gts] table; add one or pass --package");
+    }
+    packages.into_iter().map(GtsPackage::from_package).collect()
+}
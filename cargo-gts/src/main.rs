@@ -0,0 +1,298 @@
+//! `cargo gts`: GTS schema tooling for the current Cargo workspace.
+//!
+//! Each subcommand runs once per selected package, with the settings from
+//! that package's `[package.metadata.gts]` table (see [`config`]), so CI
+//! and developers share one configuration instead of repeating
+//! `gts generate-from-rust` flags.
+
+// CLI binary: printing and exiting with a status are its job.
+#![allow(clippy::print_stdout, clippy::print_stderr, clippy::exit)]
+
+mod config;
+
+use anyhow::{Result, bail};
+use cargo_metadata::MetadataCommand;
+use clap::{Parser, Subcommand};
+use gts::GtsOps;
+use gts_cli::OutputFormat;
+use gts_cli::gen_schemas::{diff_schemas_from_rust, generate_schemas_from_rust};
+use gts_cli::output::render;
+use gts_cli::progress::Progress;
+use serde_json::json;
+use std::path::PathBuf;
+
+use crate::config::{GtsPackage, select_packages};
+
+#[derive(Parser)]
+#[command(name = "cargo", bin_name = "cargo")]
+enum CargoCli {
+    /// GTS schema tooling for the current workspace
+    Gts(GtsArgs),
+}
+
+#[derive(clap::Args)]
+#[command(version, about)]
+struct GtsArgs {
+    /// Path to the workspace or package Cargo.toml
+    #[arg(long)]
+    manifest_path: Option<PathBuf>,
+
+    /// Package to act on (can be used multiple times); defaults to every
+    /// member with a [package.metadata.gts] table
+    #[arg(short, long = "package")]
+    packages: Vec<String>,
+
+    /// Increase verbosity (can be used multiple times)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Format of results and progress summaries
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: GtsCommand,
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum GtsCommand {
+    /// Generate schema files from `#[struct_to_gts_schema]` structs
+    Generate {
+        /// Write nothing; fail if any schema file is missing or out of date
+        #[arg(long)]
+        check: bool,
+    },
+    /// Validate every instance against its schema
+    Validate,
+    /// Show how schema files differ from what `generate` would write
+    Diff,
+    /// Report dangling references and types with several live major versions
+    Audit,
+}
+
+fn main() {
+    let CargoCli::Gts(args) = CargoCli::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+/// Runs the command for every selected package, failing at the end if it
+/// failed for any of them.
+fn run(args: &GtsArgs) -> Result<()> {
+    let mut metadata = MetadataCommand::new();
+    metadata.no_deps();
+    if let Some(manifest_path) = &args.manifest_path {
+        metadata.manifest_path(manifest_path);
+    }
+    let packages = select_packages(&metadata.exec()?, &args.packages)?;
+
+    let mut failed = Vec::new();
+    for package in &packages {
+        if packages.len() > 1 {
+            println!("== {}", package.name);
+        }
+        if let Err(e) = run_package(package, args) {
+            eprintln!("{}: {e:#}", package.name);
+            failed.push(package.name.as_str());
+        }
+    }
+    if !failed.is_empty() {
+        bail!("cargo gts failed for: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+fn run_package(package: &GtsPackage, args: &GtsArgs) -> Result<()> {
+    let source = package.source();
+    let output = package.output();
+    let exclude = &package.metadata.exclude;
+    match args.command {
+        GtsCommand::Generate { check } => {
+            generate_schemas_from_rust(
+                &source,
+                output.as_deref(),
+                exclude,
+                check,
+                args.verbose,
+                Progress::new("generate", None, args.output),
+            )?
+            .emit(args.output)?;
+        }
+        GtsCommand::Diff => {
+            let diffs = diff_schemas_from_rust(
+                &source,
+                output.as_deref(),
+                exclude,
+                args.verbose,
+                Progress::new("diff", None, args.output),
+            )?;
+            if args.output != OutputFormat::Text {
+                print!("{}", render(&diffs, Ok(()), args.output)?);
+            } else if diffs.is_empty() {
+                println!("Schema files are up to date");
+            } else {
+                for diff in diffs {
+                    println!("--- {} ({})", diff.path.display(), diff.schema_id);
+                    print!("{}", diff.diff);
+                }
+            }
+        }
+        GtsCommand::Validate => {
+            let mut ops = ops(package, args)?;
+            let report = ops.validate_all();
+            let failure = format!(
+                "{} of {} instances failed validation",
+                report.failures.len(),
+                report.checked
+            );
+            let outcome = if report.ok() {
+                Ok(())
+            } else {
+                Err(failure.as_str())
+            };
+            print!("{}", render(&report, outcome, args.output)?);
+            if !report.ok() {
+                bail!(failure);
+            }
+        }
+        GtsCommand::Audit => {
+            let ops = ops(package, args)?;
+            let refs = ops.audit_refs();
+            let majors = ops.audit_majors();
+            let failure = format!("{} dangling references", refs.count);
+            let outcome = if refs.count == 0 {
+                Ok(())
+            } else {
+                Err(failure.as_str())
+            };
+            let result = json!({"refs": refs, "majors": majors});
+            print!("{}", render(&result, outcome, args.output)?);
+            if refs.count > 0 {
+                bail!(failure);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A store loaded from the package's entity paths.
+fn ops(package: &GtsPackage, args: &GtsArgs) -> Result<GtsOps> {
+    Ok(GtsOps::try_new(
+        Some(package.entity_paths()),
+        package.config(),
+        usize::from(args.verbose),
+    )?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A workspace with a configured `models` package and a plain `other`.
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"models\", \"other\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        for (name, metadata) in [
+            (
+                "models",
+                "\n[package.metadata.gts]\noutput = \"src/gts\"\nexclude = [\"skip/*\"]\n",
+            ),
+            ("other", ""),
+        ] {
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+            fs::write(
+                root.join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2024\"\n{metadata}"
+                ),
+            )
+            .unwrap();
+            fs::write(root.join(name).join("src/lib.rs"), "").unwrap();
+        }
+        fs::write(
+            root.join("models/src/lib.rs"),
+            r#"
+#[struct_to_gts_schema(dir_path = "events", base = true, schema_id = "gts.x.test.cargo.event.v1~")]
+pub struct Event {
+    pub id: String,
+}
+"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    fn args(root: &std::path::Path, packages: &[&str], command: GtsCommand) -> GtsArgs {
+        GtsArgs {
+            manifest_path: Some(root.join("Cargo.toml")),
+            packages: packages.iter().map(|&p| p.to_owned()).collect(),
+            verbose: 0,
+            output: OutputFormat::Json,
+            command,
+        }
+    }
+
+    #[test]
+    fn test_select_packages_reads_metadata() {
+        let dir = workspace();
+        let metadata = MetadataCommand::new()
+            .manifest_path(dir.path().join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .unwrap();
+
+        let packages = select_packages(&metadata, &[]).unwrap();
+        assert_eq!(packages.len(), 1);
+        let models = &packages[0];
+        assert_eq!(models.name, "models");
+        assert_eq!(models.metadata.exclude, ["skip/*"]);
+        assert!(models.source().ends_with("src"));
+        assert_eq!(models.entity_paths(), [models.output().unwrap()]);
+
+        let other = select_packages(&metadata, &["other".to_owned()]).unwrap();
+        assert_eq!(other[0].metadata.source, "src");
+        assert!(other[0].output().is_none());
+        let err = select_packages(&metadata, &["missing".to_owned()]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'missing' is not a workspace member")
+        );
+    }
+
+    #[test]
+    fn test_generate_check_validate_and_audit_workspace() {
+        let dir = workspace();
+        let root = dir.path().canonicalize().unwrap();
+        let schema = root.join("models/src/gts/events/gts.x.test.cargo.event.v1~.schema.json");
+
+        assert!(run(&args(&root, &[], GtsCommand::Generate { check: true })).is_err());
+        run(&args(&root, &[], GtsCommand::Generate { check: false })).unwrap();
+        assert!(schema.exists());
+        run(&args(&root, &[], GtsCommand::Generate { check: true })).unwrap();
+        run(&args(&root, &[], GtsCommand::Diff)).unwrap();
+
+        fs::write(
+            root.join("models/src/gts/item.json"),
+            r#"{"id": "gts.x.test.cargo.event.v1~x.test._.a.v1", "id_count": 1}"#,
+        )
+        .unwrap();
+        assert!(run(&args(&root, &[], GtsCommand::Validate)).is_err());
+        run(&args(&root, &[], GtsCommand::Audit)).unwrap();
+        // `other` has no annotated structs and no entities
+        run(&args(
+            &root,
+            &["other"],
+            GtsCommand::Generate { check: true },
+        ))
+        .unwrap();
+    }
+}
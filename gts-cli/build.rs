@@ -0,0 +1,16 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+/// Generates the gRPC service from `proto/gts/v1/gts.proto`.
+#[cfg(feature = "grpc")]
+fn grpc() {
+    println!("cargo:rerun-if-changed=proto");
+    let descriptors = protox::compile(["gts/v1/gts.proto"], ["proto"])
+        .unwrap_or_else(|e| panic!("cannot compile proto/gts/v1/gts.proto: {e}"));
+    tonic_build::configure()
+        .build_client(false)
+        .compile_fds(descriptors)
+        .unwrap_or_else(|e| panic!("cannot generate the gRPC service: {e}"));
+}
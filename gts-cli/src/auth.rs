@@ -0,0 +1,504 @@
+//! Authentication of requests to the HTTP server.
+//!
+//! Reads of public entities are open to everyone.  With a [`GtsAuthConfig`]
+//! (the `auth` section of the config), writes such as registering schemas
+//! and entities need either one of its static API keys or a JWT signed with
+//! its secret.  Both name a principal, which the store's namespace rules
+//! then apply to, the vendors whose IDs it may write, [`ANY_VENDOR`] for all
+//! of them, and the highest role it may read as.  Callers may ask for a
+//! lower role in [`ROLE_HEADER`]; without credentials they read as public.
+//!
+//! Write requests carry an API key, as `X-Api-Key: <key>` or
+//! `Authorization: Bearer <key>`, or a JWT as `Authorization: Bearer <jwt>`.
+//! Which keys and tokens are accepted comes from the `auth` section of the
+//! config plus the `server` command's `--api-key` and `--jwt-secret` (or
+//! `$GTS_API_KEYS` and `$GTS_JWT_SECRET`).
+
+use axum::http::{HeaderMap, HeaderName, header::AUTHORIZATION};
+use gts::{GtsID, GtsVisibility};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Header carrying a static API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Header carrying the role a caller reads as: `public`, `partner` or
+/// `internal`.
+pub const ROLE_HEADER: &str = "x-gts-role";
+
+/// Vendor entry granting writes to every vendor's IDs.
+pub const ANY_VENDOR: &str = "*";
+
+/// A static API key and whom it authenticates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsApiKey {
+    pub key: String,
+    pub principal: String,
+    /// Vendors whose IDs the key may write, [`ANY_VENDOR`] for all; empty
+    /// for none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vendors: Vec<String>,
+    /// Highest role the key may read as.
+    #[serde(default)]
+    pub role: GtsVisibility,
+}
+
+/// Validation of HS256 JSON Web Tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsJwtConfig {
+    /// Shared HMAC secret.
+    pub secret: String,
+    /// Required `iss` claim, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Required `aud` claim, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Claim listing the vendors the bearer may write, as a string or an
+    /// array, [`ANY_VENDOR`] for all; without it the token may write none.
+    #[serde(default = "default_vendors_claim")]
+    pub vendors_claim: String,
+    /// Claim naming the highest role the bearer may read as; without it
+    /// the token reads as public.
+    #[serde(default = "default_role_claim")]
+    pub role_claim: String,
+}
+
+fn default_vendors_claim() -> String {
+    "gts_vendors".to_owned()
+}
+
+fn default_role_claim() -> String {
+    "gts_role".to_owned()
+}
+
+impl GtsJwtConfig {
+    #[must_use]
+    pub fn new(secret: impl Into<String>) -> Self {
+        GtsJwtConfig {
+            secret: secret.into(),
+            issuer: None,
+            audience: None,
+            vendors_claim: default_vendors_claim(),
+            role_claim: default_role_claim(),
+        }
+    }
+}
+
+/// Credentials accepted for writes.  With neither keys nor a JWT secret,
+/// writes are open like reads.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsAuthConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<GtsApiKey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<GtsJwtConfig>,
+}
+
+impl GtsAuthConfig {
+    /// Whether writes need credentials.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some()
+    }
+}
+
+/// What an authenticated caller may do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtsWriteGrant {
+    pub principal: String,
+    /// Vendors whose IDs may be written, [`ANY_VENDOR`] for all; empty for
+    /// none.
+    pub vendors: Vec<String>,
+    /// Highest role the caller may read as.
+    pub role: GtsVisibility,
+}
+
+impl GtsWriteGrant {
+    /// Whether the grant covers `id`, judged by the vendor of the segment
+    /// the ID itself adds, so that vendors may derive from other vendors'
+    /// base types.  IDs that are not GTS IDs are covered only by grants
+    /// for any vendor.
+    #[must_use]
+    pub fn allows(&self, id: &str) -> bool {
+        if self.vendors.iter().any(|v| v == ANY_VENDOR) {
+            return true;
+        }
+        GtsID::new(id)
+            .ok()
+            .and_then(|gid| gid.gts_id_segments.last().map(|seg| seg.vendor.clone()))
+            .is_some_and(|vendor| self.vendors.contains(&vendor))
+    }
+}
+
+/// Why a request was not authenticated, or not for what it asks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    Missing,
+    Invalid(String),
+    /// The credentials do not allow the role asked for.
+    Forbidden(GtsVisibility),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Missing => write!(
+                f,
+                "writes need credentials: an `{API_KEY_HEADER}` header or a bearer token"
+            ),
+            AuthError::Invalid(reason) => write!(f, "invalid credentials: {reason}"),
+            AuthError::Forbidden(role) => {
+                write!(f, "the credentials do not allow the '{role}' role")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Adds the server command's credentials to the config's: `api_keys` as
+/// `PRINCIPAL=KEY`, allowed to write any vendor's IDs and to read as
+/// internal, and a JWT secret replacing the config's.
+///
+/// # Errors
+/// Returns an error if an API key is not of the form `PRINCIPAL=KEY`.
+pub fn with_cli_credentials(
+    config: Option<GtsAuthConfig>,
+    api_keys: &[String],
+    jwt_secret: Option<String>,
+) -> anyhow::Result<GtsAuthConfig> {
+    let mut config = config.unwrap_or_default();
+    for entry in api_keys {
+        let Some((principal, key)) = entry.split_once('=') else {
+            anyhow::bail!("API key '{entry}' is not of the form PRINCIPAL=KEY");
+        };
+        if principal.is_empty() || key.is_empty() {
+            anyhow::bail!("API key '{entry}' is not of the form PRINCIPAL=KEY");
+        }
+        config.api_keys.push(GtsApiKey {
+            key: key.to_owned(),
+            principal: principal.to_owned(),
+            vendors: vec![ANY_VENDOR.to_owned()],
+            role: GtsVisibility::Internal,
+        });
+    }
+    if let Some(secret) = jwt_secret {
+        match &mut config.jwt {
+            Some(jwt) => jwt.secret = secret,
+            None => config.jwt = Some(GtsJwtConfig::new(secret)),
+        }
+    }
+    Ok(config)
+}
+
+/// Compares in time independent of where the inputs differ.
+fn same_key(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+/// Names from a claim holding one string or an array of them.
+fn claim_strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_owned))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn verify_jwt(jwt: &GtsJwtConfig, token: &str) -> Result<GtsWriteGrant, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    if let Some(issuer) = &jwt.issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    match &jwt.audience {
+        Some(audience) => validation.set_audience(&[audience]),
+        None => validation.validate_aud = false,
+    }
+    let claims = jsonwebtoken::decode::<Map<String, Value>>(
+        token,
+        &DecodingKey::from_secret(jwt.secret.as_bytes()),
+        &validation,
+    )
+    .map_err(|e| AuthError::Invalid(e.to_string()))?
+    .claims;
+    let Some(principal) = claims.get("sub").and_then(Value::as_str) else {
+        return Err(AuthError::Invalid("token has no `sub` claim".to_owned()));
+    };
+    let role = match claims.get(&jwt.role_claim) {
+        Some(Value::String(role)) => role.parse().map_err(AuthError::Invalid)?,
+        Some(_) => return Err(AuthError::Invalid("role claim is not a string".to_owned())),
+        None => GtsVisibility::Public,
+    };
+    Ok(GtsWriteGrant {
+        principal: principal.to_owned(),
+        vendors: claim_strings(claims.get(&jwt.vendors_claim)),
+        role,
+    })
+}
+
+/// Authenticates a write request: `None` if `config` requires no
+/// credentials, otherwise what the presented key or token grants.
+///
+/// # Errors
+/// Returns an error if credentials are required but missing, unknown or
+/// invalid.
+pub fn authenticate(
+    config: &GtsAuthConfig,
+    headers: &HeaderMap,
+) -> Result<Option<GtsWriteGrant>, AuthError> {
+    if !config.is_enabled() {
+        return Ok(None);
+    }
+    let header = |name| {
+        headers
+            .get(name)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|e| AuthError::Invalid(e.to_string()))
+            })
+            .transpose()
+    };
+    let bearer = header(AUTHORIZATION)?
+        .map(|value| {
+            value
+                .strip_prefix("Bearer ")
+                .ok_or_else(|| AuthError::Invalid("expected a bearer token".to_owned()))
+        })
+        .transpose()?;
+    let Some(credential) = header(HeaderName::from_static(API_KEY_HEADER))?.or(bearer) else {
+        return Err(AuthError::Missing);
+    };
+
+    if let Some(key) = config
+        .api_keys
+        .iter()
+        .find(|key| same_key(&key.key, credential))
+    {
+        return Ok(Some(GtsWriteGrant {
+            principal: key.principal.clone(),
+            vendors: key.vendors.clone(),
+            role: key.role,
+        }));
+    }
+    match &config.jwt {
+        Some(jwt) if credential.contains('.') => verify_jwt(jwt, credential).map(Some),
+        _ => Err(AuthError::Invalid("unknown API key".to_owned())),
+    }
+}
+
+/// The role a request reads as: `requested`, from [`ROLE_HEADER`], if its
+/// credentials allow it, else the highest one they allow.  Requests without
+/// credentials, or to a server requiring none, read as public.
+///
+/// # Errors
+/// Returns an error if the credentials are invalid or do not allow the
+/// requested role.
+pub fn caller_role(
+    config: &GtsAuthConfig,
+    headers: &HeaderMap,
+    requested: Option<GtsVisibility>,
+) -> Result<GtsVisibility, AuthError> {
+    let allowed = match authenticate(config, headers) {
+        Ok(Some(grant)) => grant.role,
+        Ok(None) | Err(AuthError::Missing) => GtsVisibility::Public,
+        Err(e) => return Err(e),
+    };
+    match requested {
+        Some(role) if !allowed.can_see(role) => Err(AuthError::Forbidden(role)),
+        Some(role) => Ok(role),
+        None => Ok(allowed),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    fn token(secret: &str, claims: &Value) -> String {
+        jsonwebtoken::encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_config_defaults_and_grants() {
+        let cfg: GtsAuthConfig = serde_json::from_str(
+            r#"{"api_keys": [{"key": "k1", "principal": "ci"}], "jwt": {"secret": "s"}}"#,
+        )
+        .unwrap();
+        assert!(cfg.is_enabled());
+        assert!(cfg.api_keys[0].vendors.is_empty());
+        assert_eq!(cfg.api_keys[0].role, GtsVisibility::Public);
+        let jwt = cfg.jwt.unwrap();
+        assert_eq!(jwt.vendors_claim, "gts_vendors");
+        assert_eq!(jwt.role_claim, "gts_role");
+        assert!(!GtsAuthConfig::default().is_enabled());
+
+        let acme = GtsWriteGrant {
+            principal: "acme-ci".to_owned(),
+            vendors: vec!["acme".to_owned()],
+            role: GtsVisibility::Public,
+        };
+        assert!(acme.allows("gts.acme.billing.invoices.invoice.v1~"));
+        assert!(acme.allows("gts.x.core.events.type.v1~acme.billing.events.paid.v1~"));
+        assert!(!acme.allows("gts.x.core.events.type.v1~"));
+        assert!(!acme.allows("7f3c1a52-0000-4000-8000-000000000000"));
+        let any = GtsWriteGrant {
+            principal: "admin".to_owned(),
+            vendors: vec![ANY_VENDOR.to_owned()],
+            role: GtsVisibility::Internal,
+        };
+        assert!(any.allows("gts.x.core.events.type.v1~"));
+        let none = GtsWriteGrant {
+            principal: "nobody".to_owned(),
+            vendors: Vec::new(),
+            role: GtsVisibility::Public,
+        };
+        assert!(!none.allows("gts.acme.billing.invoices.invoice.v1~"));
+    }
+
+    #[test]
+    fn test_api_keys_and_jwts() {
+        let mut config =
+            with_cli_credentials(None, &["ci=k3y".to_owned()], Some("s3cret".to_owned())).unwrap();
+        config.jwt.as_mut().unwrap().issuer = Some("idp".to_owned());
+        assert!(with_cli_credentials(None, &["no-principal".to_owned()], None).is_err());
+        assert_eq!(
+            authenticate(&GtsAuthConfig::default(), &HeaderMap::new()),
+            Ok(None)
+        );
+
+        assert_eq!(
+            authenticate(&config, &HeaderMap::new()),
+            Err(AuthError::Missing)
+        );
+        let grant = authenticate(&config, &headers(API_KEY_HEADER, "k3y"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.principal, "ci");
+        assert_eq!(grant.vendors, [ANY_VENDOR]);
+        assert!(authenticate(&config, &headers("authorization", "Bearer nope")).is_err());
+        assert!(authenticate(&config, &headers("authorization", "Basic k3y")).is_err());
+
+        let exp = chrono::Utc::now().timestamp() + 60;
+        let jwt = token(
+            "s3cret",
+            &json!({"sub": "acme-ci", "iss": "idp", "exp": exp, "gts_vendors": ["acme"]}),
+        );
+        let grant = authenticate(&config, &headers("authorization", &format!("Bearer {jwt}")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.principal, "acme-ci");
+        assert_eq!(grant.vendors, ["acme"]);
+
+        // A missing or empty vendors claim grants no vendor, not every one
+        for claims in [
+            json!({"sub": "acme-ci", "iss": "idp", "exp": exp}),
+            json!({"sub": "acme-ci", "iss": "idp", "exp": exp, "gts_vendors": []}),
+        ] {
+            let jwt = token("s3cret", &claims);
+            let grant = authenticate(&config, &headers("authorization", &format!("Bearer {jwt}")))
+                .unwrap()
+                .unwrap();
+            assert!(grant.vendors.is_empty(), "{claims}");
+            assert!(
+                !grant.allows("gts.acme.billing.invoices.invoice.v1~"),
+                "{claims}"
+            );
+        }
+
+        for claims in [
+            json!({"sub": "acme-ci", "iss": "other", "exp": exp}),
+            json!({"sub": "acme-ci", "iss": "idp", "exp": exp - 7200}),
+            json!({"iss": "idp", "exp": exp}),
+            json!({"sub": "acme-ci", "iss": "idp", "exp": exp, "gts_role": "root"}),
+        ] {
+            let jwt = token("s3cret", &claims);
+            assert!(
+                authenticate(&config, &headers("authorization", &format!("Bearer {jwt}"))).is_err(),
+                "{claims}"
+            );
+        }
+        let forged = token("guess", &json!({"sub": "x", "iss": "idp", "exp": exp}));
+        assert!(
+            authenticate(
+                &config,
+                &headers("authorization", &format!("Bearer {forged}"))
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_roles_come_from_the_credentials() {
+        let mut config = GtsAuthConfig {
+            api_keys: vec![GtsApiKey {
+                key: "partner-key".to_owned(),
+                principal: "partner".to_owned(),
+                vendors: Vec::new(),
+                role: GtsVisibility::Partner,
+            }],
+            jwt: Some(GtsJwtConfig::new("s3cret")),
+        };
+        let key = headers(API_KEY_HEADER, "partner-key");
+        let none = HeaderMap::new();
+        let role = |headers: &HeaderMap, requested| caller_role(&config, headers, requested);
+
+        assert_eq!(role(&none, None), Ok(GtsVisibility::Public));
+        assert_eq!(
+            role(&none, Some(GtsVisibility::Internal)),
+            Err(AuthError::Forbidden(GtsVisibility::Internal))
+        );
+        assert_eq!(role(&key, None), Ok(GtsVisibility::Partner));
+        assert_eq!(
+            role(&key, Some(GtsVisibility::Public)),
+            Ok(GtsVisibility::Public)
+        );
+        assert_eq!(
+            role(&key, Some(GtsVisibility::Internal)),
+            Err(AuthError::Forbidden(GtsVisibility::Internal))
+        );
+        assert!(role(&headers(API_KEY_HEADER, "nope"), None).is_err());
+
+        let exp = chrono::Utc::now().timestamp() + 60;
+        let jwt = token(
+            "s3cret",
+            &json!({"sub": "auditor", "exp": exp, "gts_role": "internal"}),
+        );
+        let bearer = headers("authorization", &format!("Bearer {jwt}"));
+        assert_eq!(role(&bearer, None), Ok(GtsVisibility::Internal));
+
+        // A server requiring no credentials cannot tell who asks
+        config = GtsAuthConfig::default();
+        assert_eq!(
+            caller_role(&config, &key, Some(GtsVisibility::Partner)),
+            Err(AuthError::Forbidden(GtsVisibility::Partner))
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! `gts bench compare`: runs a declared workload against two store snapshots.
+//!
+//! A snapshot is any `--path` the CLI accepts (a directory or a file of
+//! schemas and instances).  The workload is a JSON file:
+//!
+//! ```json
+//! {
+//!   "iterations": 200,
+//!   "operations": [
+//!     {"op": "validate", "id": "gts.x.shop.orders.order.v1~x.shop._.o1.v1"},
+//!     {"op": "query", "expr": "gts.x.shop.*", "limit": 100},
+//!     {"op": "cast", "from_id": "...", "to_schema_id": "..."}
+//!   ]
+//! }
+//! ```
+//!
+//! Each operation runs once to warm the validator cache, then `iterations`
+//! times; the report compares median and p95 latencies, and the approximate
+//! size of each store (serialized entity content).
+
+use anyhow::{Context, Result};
+use gts::{GtsConfig, GtsFileReader, GtsStore};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+fn default_iterations() -> usize {
+    100
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workload {
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+    pub operations: Vec<WorkloadOp>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WorkloadOp {
+    Query {
+        expr: String,
+        #[serde(default = "default_limit")]
+        limit: usize,
+    },
+    Validate {
+        id: String,
+    },
+    Cast {
+        from_id: String,
+        to_schema_id: String,
+    },
+}
+
+impl WorkloadOp {
+    fn label(&self) -> String {
+        match self {
+            WorkloadOp::Query { expr, .. } => format!("query {expr}"),
+            WorkloadOp::Validate { id } => format!("validate {id}"),
+            WorkloadOp::Cast {
+                from_id,
+                to_schema_id,
+            } => format!("cast {from_id} -> {to_schema_id}"),
+        }
+    }
+
+    /// Runs the operation once; `false` if it failed.
+    fn run(&self, store: &mut GtsStore) -> bool {
+        match self {
+            WorkloadOp::Query { expr, limit } => store.query(expr, *limit).error.is_empty(),
+            WorkloadOp::Validate { id } => store.validate_instance(id).is_ok(),
+            WorkloadOp::Cast {
+                from_id,
+                to_schema_id,
+            } => store
+                .cast(from_id, to_schema_id)
+                .is_ok_and(|r| r.error.is_none()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpTiming {
+    pub label: String,
+    pub p50_us: f64,
+    pub p95_us: f64,
+    /// Runs that returned an error (timed all the same).
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotRun {
+    pub path: String,
+    pub load_ms: f64,
+    pub entities: usize,
+    /// Serialized size of all entity content, in bytes.
+    pub store_bytes: usize,
+    pub operations: Vec<OpTiming>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpDelta {
+    pub label: String,
+    pub before_p50_us: f64,
+    pub after_p50_us: f64,
+    /// Relative change of the median, in percent; positive is slower.
+    pub p50_change_pct: f64,
+    pub before_p95_us: f64,
+    pub after_p95_us: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchComparison {
+    pub iterations: usize,
+    pub before: SnapshotRun,
+    pub after: SnapshotRun,
+    pub store_bytes_change_pct: f64,
+    pub operations: Vec<OpDelta>,
+}
+
+fn micros(d: Duration) -> f64 {
+    d.as_secs_f64() * 1_000_000.0
+}
+
+fn change_pct(before: f64, after: f64) -> f64 {
+    if before == 0.0 {
+        0.0
+    } else {
+        (after - before) / before * 100.0
+    }
+}
+
+/// The `pct` percentile of sorted samples, in microseconds.
+fn percentile(sorted: &[Duration], pct: usize) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = (sorted.len() * pct).div_ceil(100).saturating_sub(1);
+    micros(sorted[idx.min(sorted.len() - 1)])
+}
+
+/// Loads the workload definition from a JSON file.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a valid workload.
+pub fn load_workload(path: &str) -> Result<Workload> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+    serde_json::from_str(&text).with_context(|| format!("parsing workload {path}"))
+}
+
+/// Loads the snapshot at `path` and times every workload operation on it.
+#[must_use]
+pub fn run_snapshot(path: &str, cfg: &GtsConfig, workload: &Workload) -> SnapshotRun {
+    let started = Instant::now();
+    let reader = GtsFileReader::new(&[path.to_owned()], Some(cfg.clone()));
+    let mut store = GtsStore::new(Some(Box::new(reader)));
+    store.apply_config(cfg);
+    let load_ms = started.elapsed().as_secs_f64() * 1_000.0;
+
+    let (entities, store_bytes) = store.items().fold((0, 0), |(n, bytes), (_, e)| {
+        (n + 1, bytes + e.content.to_string().len())
+    });
+
+    let operations = workload
+        .operations
+        .iter()
+        .map(|op| {
+            op.run(&mut store);
+            let mut errors = 0;
+            let mut samples: Vec<Duration> = (0..workload.iterations)
+                .map(|_| {
+                    let started = Instant::now();
+                    if !op.run(&mut store) {
+                        errors += 1;
+                    }
+                    started.elapsed()
+                })
+                .collect();
+            samples.sort_unstable();
+            OpTiming {
+                label: op.label(),
+                p50_us: percentile(&samples, 50),
+                p95_us: percentile(&samples, 95),
+                errors,
+            }
+        })
+        .collect();
+
+    SnapshotRun {
+        path: path.to_owned(),
+        load_ms,
+        entities,
+        store_bytes,
+        operations,
+    }
+}
+
+/// Runs `workload` against both snapshots and compares the results.
+#[must_use]
+pub fn compare(before: &str, after: &str, cfg: &GtsConfig, workload: &Workload) -> BenchComparison {
+    let before = run_snapshot(before, cfg, workload);
+    let after = run_snapshot(after, cfg, workload);
+    let operations = before
+        .operations
+        .iter()
+        .zip(&after.operations)
+        .map(|(b, a)| OpDelta {
+            label: b.label.clone(),
+            before_p50_us: b.p50_us,
+            after_p50_us: a.p50_us,
+            p50_change_pct: change_pct(b.p50_us, a.p50_us),
+            before_p95_us: b.p95_us,
+            after_p95_us: a.p95_us,
+        })
+        .collect();
+    #[allow(clippy::cast_precision_loss)]
+    let store_bytes_change_pct = change_pct(before.store_bytes as f64, after.store_bytes as f64);
+    BenchComparison {
+        iterations: workload.iterations,
+        before,
+        after,
+        store_bytes_change_pct,
+        operations,
+    }
+}
+
+impl BenchComparison {
+    /// Operations whose median got slower by more than `pct` percent.
+    #[must_use]
+    pub fn regressions(&self, pct: f64) -> Vec<&OpDelta> {
+        self.operations
+            .iter()
+            .filter(|op| op.p50_change_pct > pct)
+            .collect()
+    }
+
+    /// Renders the comparison as a Markdown table.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Benchmark comparison\n\n");
+        let _ = writeln!(
+            out,
+            "Before: `{}` ({} entities, {} bytes)  \nAfter: `{}` ({} entities, {} bytes, {:+.1}%)  \nIterations: {}\n",
+            self.before.path,
+            self.before.entities,
+            self.before.store_bytes,
+            self.after.path,
+            self.after.entities,
+            self.after.store_bytes,
+            self.store_bytes_change_pct,
+            self.iterations
+        );
+        out.push_str("| Operation | p50 before (us) | p50 after (us) | Change | p95 before (us) | p95 after (us) |\n");
+        out.push_str("|---|---:|---:|---:|---:|---:|\n");
+        for op in &self.operations {
+            let _ = writeln!(
+                out,
+                "| `{}` | {:.1} | {:.1} | {:+.1}% | {:.1} | {:.1} |",
+                op.label,
+                op.before_p50_us,
+                op.after_p50_us,
+                op.p50_change_pct,
+                op.before_p95_us,
+                op.after_p95_us
+            );
+        }
+        let errors: Vec<String> = [&self.before, &self.after]
+            .iter()
+            .flat_map(|run| {
+                run.operations.iter().filter(|op| op.errors > 0).map(|op| {
+                    format!(
+                        "- `{}` failed {} times on `{}`",
+                        op.label, op.errors, run.path
+                    )
+                })
+            })
+            .collect();
+        if !errors.is_empty() {
+            out.push_str("\n## Errors\n\n");
+            out.push_str(&errors.join("\n"));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_micros).collect();
+        assert!((percentile(&samples, 50) - 50.0).abs() < 1e-6);
+        assert!((percentile(&samples, 95) - 95.0).abs() < 1e-6);
+        assert!((percentile(&samples[..1], 95) - 1.0).abs() < 1e-6);
+        assert!(percentile(&[], 50).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_workload_parsing() {
+        let workload: Workload = serde_json::from_str(
+            r#"{"operations": [
+                {"op": "query", "expr": "gts.x.*"},
+                {"op": "validate", "id": "gts.x.a.b.c.v1~x.a._.d.v1"},
+                {"op": "cast", "from_id": "a", "to_schema_id": "b"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(workload.iterations, 100);
+        assert!(matches!(
+            &workload.operations[0],
+            WorkloadOp::Query { limit: 100, .. }
+        ));
+        assert_eq!(workload.operations[2].label(), "cast a -> b");
+    }
+}
@@ -1,10 +1,27 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use gts::GtsOps;
+use clap_complete::ArgValueCompleter;
+use gts::{
+    GtsID, GtsIdRenamer, GtsLifecycleState, GtsOps, GtsTraitVars, GtsWildcard, SortOrder,
+    rename_in_paths,
+};
+use std::collections::BTreeMap;
 use std::io::Write;
-
-use crate::gen_schemas::generate_schemas_from_rust;
+use std::sync::Arc;
+
+use crate::auth::with_cli_credentials;
+use crate::bench;
+use crate::completions::{CompletionShell, complete_ids, write_registration};
+use crate::doctor::{DoctorOptions, run_doctor};
+use crate::gen_schemas::{
+    diff_schemas_from_rust, find_declared_schemas, generate_schemas_from_rust,
+};
+use crate::output::{Reported, render, render_error};
+use crate::progress::{OutputFormat, Progress};
 use crate::server::GtsHttpServer;
+use crate::server_config::GtsServerConfig;
+use crate::tenants::GtsTenant;
+use crate::webhooks::{GtsWebhook, WebhookNotifier};
 
 #[derive(Parser)]
 #[command(name = "gts")]
@@ -14,14 +31,23 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
-    /// Path to optional GTS config JSON to override defaults
+    /// Path to optional GTS config (JSON, YAML or TOML) to override defaults
     #[arg(long)]
     pub config: Option<String>,
 
-    /// Path to json and schema files or directories (global default)
-    #[arg(long)]
+    /// Path to json and schema files, directories or .zip/.tar.gz archives (global default)
+    #[arg(long, visible_alias = "input")]
     pub path: Option<String>,
 
+    /// Principal to register entities as, for namespace ownership rules
+    #[arg(long)]
+    pub principal: Option<String>,
+
+    /// Format of results and progress summaries: text, json or yaml (versioned
+    /// envelopes for scripts), or table
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -30,61 +56,141 @@ pub struct Cli {
 pub enum Commands {
     /// Validate a GTS ID format
     ValidateId {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
     },
     /// Parse a GTS ID into its components
     ParseId {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
     },
     /// Match a GTS ID against a pattern
     MatchIdPattern {
         #[arg(long)]
         pattern: String,
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         candidate: String,
     },
     /// Generate UUID from a GTS ID
     Uuid {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
         #[arg(long, default_value = "major")]
         scope: String,
     },
     /// Validate an instance against its schema
     ValidateInstance {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
+        /// Print each error's path through the schema chain as an indented tree
+        #[arg(long)]
+        explain: bool,
     },
     /// Validate a schema against its base schema (OP#12 schema-vs-schema)
     ValidateSchema {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         schema_id: String,
     },
     /// Validate an entity (instance or schema) by GTS ID
     ValidateEntity {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
     },
+    /// Validate every instance in the store against its schema; fails if any is invalid
+    #[command(alias = "validate")]
+    ValidateAll {
+        /// Keep running and re-validate whenever files under DIR (and --path) change
+        #[arg(long, value_name = "DIR")]
+        watch: Option<String>,
+    },
     /// Resolve relationships for an entity
     ResolveRelationships {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_id: String,
     },
+    /// Print a schema's effective traits with `${name}` placeholders substituted
+    ResolveTraits {
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        schema_id: String,
+        /// Placeholder value (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE")]
+        vars: Vec<String>,
+        /// Resolve placeholders without a --var from environment variables
+        #[arg(long)]
+        env: bool,
+    },
+    /// Export Kafka topics declared by the traits of matching schemas as Terraform JSON
+    ExportTerraform {
+        /// Schema pattern, e.g. 'gts.x.core.events.topic.v1~*'
+        #[arg(long)]
+        pattern: String,
+        /// Placeholder value (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE")]
+        vars: Vec<String>,
+        /// Resolve placeholders without a --var from environment variables
+        #[arg(long)]
+        env: bool,
+        /// Write the configuration to this file (e.g. kafka.tf.json) instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Show what removing the entities matching a pattern would take, dependents first
+    PlanRemoval {
+        /// GTS wildcard or exact ID, e.g. 'gts.x.retired.*'
+        #[arg(long)]
+        pattern: String,
+    },
+    /// Export the schema dependency graph (inheritance, $ref, x-gts-ref) of the store
+    Graph {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Include instances and their instance-of edges
+        #[arg(long)]
+        instances: bool,
+        /// Write the graph to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
     /// Check compatibility between two schemas
     Compatibility {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         old_schema_id: String,
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         new_schema_id: String,
     },
+    /// Verify that a producer schema satisfies a consumer schema's expectations
+    Contract {
+        /// Schema of the payloads the producer emits
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        producer: String,
+        /// Schema the consumer reads those payloads with
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        consumer: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        /// Write the report to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
     /// Cast an instance or schema to a target schema
     Cast {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         from_id: String,
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        to_schema_id: String,
+    },
+    /// Cast every instance matching a wildcard pattern to a target schema
+    CastAll {
+        /// Instance pattern, e.g. 'gts.x.shop.orders.order.v1.0~*'
         #[arg(long)]
+        pattern: String,
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         to_schema_id: String,
+        /// Write cast instances back to their source files
+        #[arg(long)]
+        write: bool,
     },
     /// Query entities using an expression
     Query {
@@ -92,10 +198,16 @@ pub enum Commands {
         expr: String,
         #[arg(long, default_value = "100")]
         limit: usize,
+        /// Order results by this field (dotted path, e.g. `meta.created_at`)
+        #[arg(long)]
+        sort_by: Option<String>,
+        /// Sort in descending order (with --sort-by)
+        #[arg(long, requires = "sort_by")]
+        desc: bool,
     },
     /// Get attribute value from a GTS entity
     Attr {
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
         gts_with_path: String,
     },
     /// List all entities
@@ -103,12 +215,128 @@ pub enum Commands {
         #[arg(long, default_value = "100")]
         limit: usize,
     },
+    /// Rename GTS IDs across all files under --path and print a mapping manifest
+    Rename {
+        /// Source pattern, e.g. 'gts.x.oldpkg.*' or a full type ID
+        #[arg(long)]
+        from: String,
+        /// Target pattern, e.g. 'gts.x.newpkg.*'
+        #[arg(long)]
+        to: String,
+        /// Also rewrite references ($ref, x-gts-ref, plain string values), not just own IDs
+        #[arg(long)]
+        rewrite_refs: bool,
+        /// Report what would change without writing files
+        #[arg(long)]
+        dry_run: bool,
+        /// Write the mapping manifest to this file
+        #[arg(long)]
+        manifest: Option<String>,
+    },
+    /// List the entities whose $ref / x-gts-ref values point at an ID
+    Refs {
+        /// Referenced ID, e.g. a schema about to be deleted or deprecated
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        to: String,
+    },
+    /// Audit the registry for deprecation and hygiene issues
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Benchmark store snapshots against a declared workload
+    Bench {
+        #[command(subcommand)]
+        command: BenchCommand,
+    },
+    /// Generate reports about the registry
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+    /// Manage schema lifecycle states (draft -> active -> deprecated -> retired)
+    Lifecycle {
+        #[command(subcommand)]
+        command: LifecycleCommand,
+    },
+    /// Package schemas and instances into portable bundle files
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+    /// Print a shell completion script, e.g. `source <(gts completions bash)`
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+    /// Explore the store at an interactive prompt (query, get, validate, cast, graph)
+    Repl,
+    /// Browse the store in an interactive terminal UI
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Run a read-only SQL query over the `instances` and `schemas` tables
+    #[cfg(feature = "sql")]
+    Sql {
+        /// e.g. `SELECT id, content->>'name' FROM instances WHERE schema_id LIKE 'gts.x.%'`
+        query: String,
+    },
+    /// Export store data to other formats
+    Export {
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+    /// Create GTS schemas from documents in other formats
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+    /// Generate code from GTS schemas
+    Codegen {
+        #[command(subcommand)]
+        command: CodegenCommand,
+    },
+    /// Check the config, --path, server port and registry, and suggest fixes
+    Doctor {
+        /// Host `gts server` would listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Port `gts server` would listen on
+        #[arg(long, default_value = "8000")]
+        port: u16,
+        /// Remote registry to compare versions with (default: the config's `registry`)
+        #[arg(long)]
+        registry: Option<String>,
+    },
     /// Start the GTS HTTP server
     Server {
         #[arg(long, default_value = "127.0.0.1")]
         host: String,
         #[arg(long, default_value = "8000")]
         port: u16,
+        /// Require credentials for writes, accepting this API key as
+        /// `PRINCIPAL=KEY` (repeatable; also `$GTS_API_KEYS`, comma-separated)
+        #[arg(long = "api-key", value_name = "PRINCIPAL=KEY")]
+        api_keys: Vec<String>,
+        /// Require credentials for writes, accepting HS256 JWTs signed with
+        /// this secret (also `$GTS_JWT_SECRET`)
+        #[arg(long)]
+        jwt_secret: Option<String>,
+        /// `Cache-Control` header sent with schemas, overriding the config's
+        /// `cache_control` (default `no-cache`)
+        #[arg(long, value_name = "DIRECTIVES")]
+        cache_control: Option<String>,
+        /// Post store changes to this URL, in addition to the config's
+        /// `webhooks` (repeatable; also `$GTS_WEBHOOKS`, comma-separated)
+        #[arg(long = "webhook", value_name = "URL")]
+        webhooks: Vec<String>,
+        /// Also serve the gRPC interface on this port (needs the `grpc`
+        /// feature)
+        #[arg(long)]
+        grpc_port: Option<u16>,
+        /// Also serve the store read from PATH under `/t/NAME/`, in addition
+        /// to the config's `tenants` (repeatable)
+        #[arg(long = "tenant", value_name = "NAME=PATH")]
+        tenants: Vec<String>,
     },
     /// Generate `OpenAPI` specification
     OpenapiSpec {
@@ -131,8 +359,260 @@ pub enum Commands {
         /// Example: --exclude "tests/*" --exclude "examples/*"
         #[arg(long, action = clap::ArgAction::Append)]
         exclude: Vec<String>,
+        /// Write nothing; fail if any schema file is missing or out of date
+        #[arg(long)]
+        check: bool,
+        /// Write nothing; print how each out-of-date schema file would change
+        #[arg(long, conflicts_with = "check")]
+        diff: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// List types with multiple live major versions and their instance counts
+    Majors,
+    /// List $ref / x-gts-ref targets missing from the store; fails if any
+    Refs,
+}
+
+#[derive(Subcommand)]
+pub enum BenchCommand {
+    /// Run a workload against two snapshots and report latency and size deltas
+    Compare {
+        /// Snapshot (directory or file) before the change
+        #[arg(long)]
+        before: String,
+        /// Snapshot (directory or file) after the change
+        #[arg(long)]
+        after: String,
+        /// Workload JSON file listing queries, validations and casts
+        #[arg(long)]
+        workload: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        /// Write the report to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+        /// Fail if any operation's median latency grows by more than this percentage
+        #[arg(long, value_name = "PCT")]
+        max_regression: Option<f64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommand {
+    /// Check the schemas a service declares against the registry under --path
+    Conformance {
+        /// Service name shown in the report
+        #[arg(long)]
+        service: String,
+        /// Rust source directory with `#[struct_to_gts_schema]` annotations
+        #[arg(long, default_value = ".")]
+        source: String,
+        /// Only report declared schemas matching these GTS patterns (e.g. 'gts.x.pay.*')
+        #[arg(long, num_args = 1..)]
+        patterns: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        /// Write the report to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Write matching entities and the schemas they depend on to a bundle file
+    Export {
+        /// GTS wildcard or exact ID, e.g. 'gts.x.shop.*'
+        #[arg(long)]
+        pattern: String,
+        /// Bundle file to write; stdout if omitted
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Verify a bundle file and add its entities to the store
+    Import {
+        /// Bundle file written by `bundle export`
+        #[arg(long)]
+        file: String,
     },
 }
+
+#[derive(Subcommand)]
+pub enum CodegenCommand {
+    /// Write serde structs for matching schemas to one Rust source file
+    Rust {
+        /// Directory of schemas to generate from; defaults to --path
+        #[arg(long)]
+        schema_dir: Option<String>,
+        /// GTS wildcard or exact schema ID
+        #[arg(long, default_value = "gts.*")]
+        pattern: String,
+        /// Rust file to write, e.g. src/generated.rs; stdout if omitted
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Write TypeScript interfaces for matching schemas to one .d.ts file
+    Typescript {
+        /// Directory of schemas to generate from; defaults to --path
+        #[arg(long)]
+        schema_dir: Option<String>,
+        /// GTS wildcard or exact schema ID
+        #[arg(long, default_value = "gts.*")]
+        pattern: String,
+        /// Declaration file to write, e.g. src/events.d.ts; stdout if omitted
+        #[arg(long)]
+        out: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommand {
+    /// Write matching schemas and their references as `OpenAPI` 3.1 components
+    Openapi {
+        /// GTS wildcard or exact schema ID, e.g. 'gts.x.shop.*'
+        #[arg(long)]
+        pattern: String,
+        /// Document title (info.title)
+        #[arg(long, default_value = "GTS schemas")]
+        title: String,
+        /// Document version (info.version)
+        #[arg(long, default_value = "1.0.0")]
+        api_version: String,
+        /// Write the document to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Write matching schemas as Avro `.avsc` files plus a Schema Registry subject map
+    Avro {
+        /// GTS wildcard or exact schema ID
+        #[arg(long, default_value = "gts.*")]
+        pattern: String,
+        /// Output directory; subjects and compatibility levels go to `subjects.json`
+        #[arg(long)]
+        out: String,
+    },
+    /// Write matching schemas as proto3 files, one flattened message per schema
+    Proto {
+        /// GTS wildcard or exact schema ID
+        #[arg(long, default_value = "gts.*")]
+        pattern: String,
+        /// Output directory
+        #[arg(long)]
+        out: String,
+    },
+    /// Write matching instances to Parquet files partitioned by schema
+    #[cfg(feature = "parquet")]
+    Parquet {
+        /// GTS wildcard matched against instance and schema IDs, e.g. 'gts.x.*'
+        #[arg(long)]
+        pattern: String,
+        /// Output directory; files go to `<out>/schema_id=<id>/part-0.parquet`
+        #[arg(long)]
+        out: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Register the schemas of an `OpenAPI` or JSON Schema document (JSON or YAML)
+    #[command(visible_alias = "json-schema")]
+    Openapi {
+        /// Document to import
+        file: String,
+        /// Vendor of the created schema IDs
+        #[arg(long)]
+        vendor: String,
+        /// Package of the created schema IDs
+        #[arg(long)]
+        package: String,
+        /// Namespace of the created schema IDs
+        #[arg(long, default_value = "_")]
+        namespace: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LifecycleCommand {
+    /// Move a schema to a new lifecycle state and write it back to its file
+    Set {
+        #[arg(long, add = ArgValueCompleter::new(complete_ids))]
+        schema_id: String,
+        /// active, deprecated or retired
+        #[arg(long)]
+        state: GtsLifecycleState,
+        /// End of the deprecation grace period (YYYY-MM-DD)
+        #[arg(long)]
+        retire_on: Option<String>,
+        /// Schema consumers should migrate to
+        #[arg(long)]
+        replacement: Option<String>,
+    },
+    /// List the lifecycle state of every schema
+    List {
+        /// Only show schemas whose effective state matches
+        #[arg(long)]
+        state: Option<GtsLifecycleState>,
+    },
+}
+/// The stores of the config's `tenants` and of `--tenant NAME=PATH`
+/// arguments, which take precedence, with their own server config, posting
+/// changes to their own config's webhooks.
+fn tenant_ops(
+    config: &GtsServerConfig,
+    args: &[String],
+    verbose: u8,
+) -> Result<BTreeMap<String, (GtsOps, GtsServerConfig)>> {
+    let mut tenants = config.tenants.clone();
+    for arg in args {
+        let (name, path) = arg
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--tenant expects NAME=PATH, got '{arg}'"))?;
+        tenants.insert(
+            name.to_owned(),
+            GtsTenant {
+                path: vec![path.to_owned()],
+                config: None,
+            },
+        );
+    }
+    tenants
+        .iter()
+        .map(|(name, tenant)| {
+            if !GtsTenant::is_valid_name(name) {
+                anyhow::bail!("invalid tenant name '{name}': use letters, digits, '-' and '_'");
+            }
+            let mut ops = tenant.ops(verbose as usize)?;
+            let config = GtsServerConfig::load(
+                ops.cfg.clone(),
+                tenant.config.as_deref().map(std::path::Path::new),
+            )?;
+            if !config.webhooks.is_empty() {
+                let notifier = WebhookNotifier::start(config.webhooks.clone());
+                ops.store.add_change_listener(Arc::new(notifier));
+            }
+            Ok((name.clone(), (ops, config)))
+        })
+        .collect()
+}
+
 /// Run the CLI application
 ///
 /// # Errors
@@ -167,114 +647,788 @@ pub async fn run_with_cli(cli: Cli) -> Result<()> {
         .with_target(false)
         .try_init();
 
-    run_command(cli).await
+    // Scripts reading structured output get failures in the same envelope
+    let output = cli.output;
+    let result = run_command(cli).await;
+    if let Err(e) = &result
+        && !e.is::<Reported>()
+        && let Some(rendered) = render_error(&format!("{e:#}"), output)
+    {
+        print!("{rendered}");
+    }
+    result
+}
+
+/// The ops over the `--path` store with the `--config` config.
+fn load_ops(cli: &Cli) -> Result<GtsOps> {
+    let path = cli.path.clone().map(|p| vec![p]);
+    // Loading a large --path can take a while
+    let loading = path
+        .is_some()
+        .then(|| Progress::new("load", None, cli.output));
+    // `doctor` reports a bad config rather than refusing to run
+    let ops = if matches!(cli.command, Commands::Doctor { .. }) {
+        GtsOps::new(path, cli.config.clone(), cli.verbose as usize)
+    } else {
+        GtsOps::try_new(path, cli.config.clone(), cli.verbose as usize)?
+    };
+    if let Some(loading) = loading {
+        loading.clear();
+    }
+    Ok(ops)
 }
 
 /// Execute a command with the given CLI configuration
+#[allow(clippy::too_many_lines)]
 async fn run_command(cli: Cli) -> Result<()> {
-    // Parse path into Vec<String>
-    let path = cli.path.map(|p| vec![p]);
-
-    // Create GtsOps
-    let mut ops = GtsOps::new(path, cli.config, cli.verbose as usize);
-
-    match cli.command {
-        Commands::Server { host, port } => {
+    let output = cli.output;
+    let mut ops = load_ops(&cli)?;
+    ops.principal = cli.principal;
+    let mut command = cli.command;
+    resolve_ids(&ops, &mut command)?;
+
+    match command {
+        Commands::Server {
+            host,
+            port,
+            mut api_keys,
+            jwt_secret,
+            cache_control,
+            mut webhooks,
+            grpc_port,
+            tenants,
+        } => {
+            #[cfg(not(feature = "grpc"))]
+            if grpc_port.is_some() {
+                anyhow::bail!("--grpc-port needs gts built with the `grpc` feature");
+            }
+            if let Ok(keys) = std::env::var("GTS_API_KEYS") {
+                api_keys.extend(keys.split(',').filter(|k| !k.is_empty()).map(str::to_owned));
+            }
+            let jwt_secret = jwt_secret.or_else(|| std::env::var("GTS_JWT_SECRET").ok());
+            let mut config = GtsServerConfig::load(
+                ops.cfg.clone(),
+                cli.config.as_deref().map(std::path::Path::new),
+            )?;
+            let auth = with_cli_credentials(config.auth.clone(), &api_keys, jwt_secret)?;
+            let mut tenants = tenant_ops(&config, &tenants, cli.verbose)?;
+            if cache_control.is_some() {
+                for (_, tenant) in tenants.values_mut() {
+                    tenant.cache_control.clone_from(&cache_control);
+                }
+                config.cache_control = cache_control;
+            }
+            if let Ok(urls) = std::env::var("GTS_WEBHOOKS") {
+                webhooks.extend(urls.split(',').filter(|u| !u.is_empty()).map(str::to_owned));
+            }
+            let mut hooks = config.webhooks.clone();
+            hooks.extend(webhooks.into_iter().map(GtsWebhook::new));
             println!("starting the server @ http://{host}:{port}");
+            if auth.is_enabled() {
+                println!("writes require an API key or a bearer token");
+            }
+            if !hooks.is_empty() {
+                println!("posting store changes to {} webhook(s)", hooks.len());
+                let notifier = WebhookNotifier::start(hooks);
+                ops.store.add_change_listener(Arc::new(notifier));
+            }
+            for name in tenants.keys() {
+                println!("serving tenant '{name}' @ http://{host}:{port}/t/{name}");
+            }
             if cli.verbose == 0 {
                 println!("use --verbose to see server logs");
             }
-            let server = GtsHttpServer::new(ops, host.clone(), port, cli.verbose);
+            let server = tenants.into_iter().fold(
+                GtsHttpServer::new(ops, host.clone(), port, cli.verbose)
+                    .with_auth(auth)
+                    .with_cache_control(config.cache_control),
+                |server, (name, (tenant, tenant_config))| {
+                    server.with_tenant(name, tenant, tenant_config.cache_control)
+                },
+            );
+            #[cfg(feature = "grpc")]
+            let server = match grpc_port {
+                Some(grpc_port) => {
+                    println!("serving gRPC @ {host}:{grpc_port}");
+                    server.with_grpc(grpc_port)
+                }
+                None => server,
+            };
             server.run().await?;
         }
+        Commands::Repl => crate::repl::run(&mut ops)?,
+        Commands::Completions { shell } => write_registration(shell, &mut std::io::stdout())?,
+        #[cfg(feature = "tui")]
+        Commands::Tui => crate::tui::run(ops.store)?,
+        Commands::Doctor {
+            host,
+            port,
+            registry,
+        } => {
+            let report = run_doctor(
+                &mut ops,
+                &DoctorOptions {
+                    config: cli.config.as_deref(),
+                    host: &host,
+                    port,
+                    registry: registry.as_deref(),
+                },
+            );
+            print!("{}", report.render(output)?);
+            if let Some(failure) = report.failure() {
+                return Err(Reported(failure).into());
+            }
+        }
+        Commands::Export { command } => run_export(&ops, command, output)?,
+        Commands::Import { command } => run_import(&mut ops, command, output)?,
+        Commands::Codegen { command } => {
+            run_codegen(&ops, cli.config.as_deref(), cli.verbose, &command, output)?;
+        }
+        #[cfg(feature = "sql")]
+        Commands::Sql { query } => {
+            print_result(&crate::sql::run_query(&ops.store, &query)?, output)?;
+        }
         Commands::OpenapiSpec { out, host, port } => {
             let server = GtsHttpServer::new(ops, host, port, cli.verbose);
             let spec = server.openapi_spec();
-            std::fs::write(&out, serde_json::to_string_pretty(&spec)?)?;
+            std::fs::write(&out, gts::canonical_json_pretty(&spec))?;
             let result = serde_json::json!({
                 "ok": true,
                 "out": out
             });
-            println!("{}", serde_json::to_string_pretty(&result)?);
+            print_result(&result, output)?;
         }
         Commands::ValidateId { gts_id } => {
             let result = GtsOps::validate_id(&gts_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
         Commands::ParseId { gts_id } => {
             let result = GtsOps::parse_id(&gts_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
         Commands::MatchIdPattern { pattern, candidate } => {
             let result = GtsOps::match_id_pattern(&candidate, &pattern);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
         Commands::Uuid { gts_id, scope: _ } => {
             let result = GtsOps::uuid(&gts_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
-        Commands::ValidateInstance { gts_id } => {
-            let result = ops.validate_instance(&gts_id);
-            print_result(&result)?;
+        Commands::ValidateInstance { gts_id, explain } => {
+            if explain {
+                print!("{}", ops.validate_instance_explain(&gts_id).render_tree());
+            } else {
+                let result = ops.validate_instance(&gts_id);
+                print_result(&result, output)?;
+            }
         }
         Commands::ValidateSchema { schema_id } => {
             let result = ops.validate_schema(&schema_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
         Commands::ValidateEntity { gts_id } => {
             let result = ops.validate_entity(&gts_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
+        Commands::ValidateAll { watch: Some(dir) } => {
+            let mut paths = ops.path.clone().unwrap_or_default();
+            if !paths.contains(&dir) {
+                paths.push(dir);
+            }
+            crate::watch::run(&mut ops, &paths, output)?;
+        }
+        Commands::ValidateAll { watch: None } => run_validate_all(&mut ops, output)?,
         Commands::ResolveRelationships { gts_id } => {
             let result = ops.schema_graph(&gts_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
+        }
+        Commands::ResolveTraits {
+            schema_id,
+            vars,
+            env,
+        } => {
+            let trait_vars = trait_vars(&vars, env)?;
+            print_result(&ops.store.resolve_traits(&schema_id, &trait_vars)?, output)?;
+        }
+        Commands::ExportTerraform {
+            pattern,
+            vars,
+            env,
+            out,
+        } => {
+            let export = ops
+                .store
+                .export_terraform(&pattern, &trait_vars(&vars, env)?)?;
+            let rendered = gts::canonical_json_pretty(&export.to_terraform_json());
+            write_output(out, &rendered)?;
+            for problem in &export.problems {
+                eprintln!("{problem}");
+            }
+            if !export.problems.is_empty() {
+                anyhow::bail!("{} schemas could not be exported", export.problems.len());
+            }
+        }
+        Commands::PlanRemoval { pattern } => {
+            print_result(&ops.store.plan_removal(&pattern)?, output)?;
+        }
+        Commands::Graph {
+            format,
+            instances,
+            out,
+        } => {
+            let graph = ops.store.dependency_graph(instances);
+            let rendered = match format {
+                GraphFormat::Dot => graph.to_dot(),
+                GraphFormat::Mermaid => graph.to_mermaid(),
+                GraphFormat::Json => serde_json::to_string_pretty(&graph.to_json())? + "\n",
+            };
+            write_output(out, &rendered)?;
         }
         Commands::Compatibility {
             old_schema_id,
             new_schema_id,
         } => {
             let result = ops.compatibility(&old_schema_id, &new_schema_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
+        }
+        Commands::Contract {
+            producer,
+            consumer,
+            format,
+            out,
+        } => {
+            let report = ops.store.contract_report(&producer, &consumer)?;
+            let rendered = match format {
+                ReportFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+                ReportFormat::Markdown => report.to_markdown(),
+            };
+            write_output(out, &rendered)?;
         }
         Commands::Cast {
             from_id,
             to_schema_id,
         } => {
             let result = ops.cast(&from_id, &to_schema_id);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
-        Commands::Query { expr, limit } => {
-            let result = ops.query(&expr, limit);
-            print_result(&result)?;
+        Commands::CastAll {
+            pattern,
+            to_schema_id,
+            write,
+        } => {
+            let progress = Progress::new("cast", None, output);
+            let result =
+                ops.cast_all_with_progress(&pattern, &to_schema_id, write, |done, total| {
+                    progress.update(done, total);
+                });
+            let result = progress
+                .finish(result.casted, result.count - result.casted, 0)
+                .attach(&result, output)?;
+            print_result(&result, output)?;
+        }
+        Commands::Query {
+            expr,
+            limit,
+            sort_by,
+            desc,
+        } => {
+            let result = match sort_by {
+                Some(field) => {
+                    let order = if desc {
+                        SortOrder::Desc
+                    } else {
+                        SortOrder::Asc
+                    };
+                    ops.query_sorted(&expr, &field, order, limit)
+                }
+                None => ops.query(&expr, limit),
+            };
+            print_result(&result, output)?;
         }
         Commands::Attr { gts_with_path } => {
             let result = ops.attr(&gts_with_path);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
         Commands::List { limit } => {
             let result = ops.get_entities(limit);
-            print_result(&result)?;
+            print_result(&result, output)?;
         }
+        Commands::Rename {
+            from,
+            to,
+            rewrite_refs,
+            dry_run,
+            manifest,
+        } => {
+            let renamer = GtsIdRenamer::new(&from, &to)?;
+            let paths = ops
+                .path
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("rename requires --path"))?;
+            let result = rename_in_paths(paths, &renamer, rewrite_refs, dry_run, &ops.cfg)?;
+            if let Some(manifest) = manifest {
+                std::fs::write(&manifest, serde_json::to_string_pretty(&result)?)?;
+            }
+            print_result(&result, output)?;
+        }
+        Commands::Refs { to } => print_result(&ops.referrers(&to), output)?,
+        Commands::Audit { command } => run_audit(&ops, &command, output)?,
+        Commands::Bench { command } => run_bench(&ops, command)?,
+        Commands::Report { command } => run_report(&mut ops, command)?,
+        Commands::Lifecycle { command } => run_lifecycle(&mut ops, command, output)?,
+        Commands::Bundle { command } => run_bundle(&mut ops, command, output)?,
         Commands::GenerateFromRust {
             source,
-            output,
+            output: out_dir,
             exclude,
+            check,
+            diff,
+        } => {
+            if diff {
+                let diffs = diff_schemas_from_rust(
+                    &source,
+                    out_dir.as_deref(),
+                    &exclude,
+                    cli.verbose,
+                    Progress::new("diff", None, output),
+                )?;
+                if output != OutputFormat::Text {
+                    print_result(&diffs, output)?;
+                } else if diffs.is_empty() {
+                    println!("Schema files are up to date");
+                } else {
+                    for diff in diffs {
+                        println!("--- {} ({})", diff.path.display(), diff.schema_id);
+                        print!("{}", diff.diff);
+                    }
+                }
+            } else {
+                generate_schemas_from_rust(
+                    &source,
+                    out_dir.as_deref(),
+                    &exclude,
+                    check,
+                    cli.verbose,
+                    Progress::new("generate", None, output),
+                )?
+                .emit(output)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds trait placeholder values from `NAME=VALUE` arguments.
+fn trait_vars(vars: &[String], env: bool) -> Result<GtsTraitVars> {
+    let mut trait_vars = GtsTraitVars::new();
+    for var in vars {
+        let (name, value) = var
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--var must be NAME=VALUE, got '{var}'"))?;
+        trait_vars = trait_vars.with_var(name, value);
+    }
+    Ok(if env {
+        trait_vars.with_env()
+    } else {
+        trait_vars
+    })
+}
+
+/// Expands `@alias` and ID-suffix arguments to full entity IDs in place.
+fn resolve_ids(ops: &GtsOps, command: &mut Commands) -> Result<()> {
+    let resolve = |id: &mut String| -> Result<()> {
+        *id = ops.resolve_id(id)?;
+        Ok(())
+    };
+    match command {
+        Commands::ValidateId { gts_id }
+        | Commands::ParseId { gts_id }
+        | Commands::Uuid { gts_id, .. }
+        | Commands::ValidateInstance { gts_id, .. }
+        | Commands::ValidateEntity { gts_id }
+        | Commands::ResolveRelationships { gts_id } => resolve(gts_id)?,
+        Commands::Refs { to } => resolve(to)?,
+        Commands::MatchIdPattern { candidate, .. } => resolve(candidate)?,
+        Commands::ValidateSchema { schema_id }
+        | Commands::ResolveTraits { schema_id, .. }
+        | Commands::Lifecycle {
+            command: LifecycleCommand::Set { schema_id, .. },
+        } => resolve(schema_id)?,
+        Commands::Compatibility {
+            old_schema_id,
+            new_schema_id,
+        } => {
+            resolve(old_schema_id)?;
+            resolve(new_schema_id)?;
+        }
+        Commands::Contract {
+            producer, consumer, ..
+        } => {
+            resolve(producer)?;
+            resolve(consumer)?;
+        }
+        Commands::Cast {
+            from_id,
+            to_schema_id,
+        } => {
+            resolve(from_id)?;
+            resolve(to_schema_id)?;
+        }
+        Commands::CastAll { to_schema_id, .. } => resolve(to_schema_id)?,
+        Commands::Attr { gts_with_path } => {
+            // The ID ends at the first '@' that does not start an alias
+            let start = usize::from(gts_with_path.starts_with('@'));
+            let end = gts_with_path[start..]
+                .find('@')
+                .map_or(gts_with_path.len(), |i| i + start);
+            let id = ops.resolve_id(&gts_with_path[..end])?;
+            gts_with_path.replace_range(..end, &id);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn run_validate_all(ops: &mut GtsOps, output: OutputFormat) -> Result<()> {
+    let progress = Progress::new("validate", None, output);
+    let report = ops
+        .store
+        .validate_all_with_progress(|done, total| progress.update(done, total));
+    let result = progress
+        .finish(report.valid, report.failures.len(), 0)
+        .attach(&report, output)?;
+    if report.ok() {
+        print_result(&result, output)
+    } else {
+        let failure = format!("{} invalid instances", report.failures.len());
+        print_failure(&result, failure, output)
+    }
+}
+
+fn run_audit(ops: &GtsOps, command: &AuditCommand, output: OutputFormat) -> Result<()> {
+    match command {
+        AuditCommand::Majors => print_result(&ops.audit_majors(), output)?,
+        AuditCommand::Refs => {
+            let result = ops.audit_refs();
+            if result.count > 0 {
+                let failure = format!("{} dangling references", result.count);
+                print_failure(&result, failure, output)?;
+            } else {
+                print_result(&result, output)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_bench(ops: &GtsOps, command: BenchCommand) -> Result<()> {
+    let BenchCommand::Compare {
+        before,
+        after,
+        workload,
+        format,
+        out,
+        max_regression,
+    } = command;
+    let workload = bench::load_workload(&workload)?;
+    let comparison = bench::compare(&before, &after, &ops.cfg, &workload);
+    let rendered = match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&comparison)? + "\n",
+        ReportFormat::Markdown => comparison.to_markdown(),
+    };
+    write_output(out, &rendered)?;
+    if let Some(pct) = max_regression {
+        let regressions = comparison.regressions(pct);
+        if !regressions.is_empty() {
+            let labels: Vec<&str> = regressions.iter().map(|op| op.label.as_str()).collect();
+            anyhow::bail!(
+                "median latency regressed by more than {pct}%: {}",
+                labels.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_report(ops: &mut GtsOps, command: ReportCommand) -> Result<()> {
+    match command {
+        ReportCommand::Conformance {
+            service,
+            source,
+            patterns,
+            format,
+            out,
+        } => {
+            let patterns = patterns
+                .iter()
+                .map(|p| GtsWildcard::new(p))
+                .collect::<Result<Vec<_>, _>>()?;
+            let declared: Vec<_> = find_declared_schemas(&source, &[])?
+                .into_iter()
+                .filter(|d| {
+                    patterns.is_empty()
+                        || GtsID::new(&d.schema_id)
+                            .is_ok_and(|gid| patterns.iter().any(|p| gid.wildcard_match(p)))
+                })
+                .collect();
+            let report = ops.store.conformance_report(&service, declared);
+            let rendered = match format {
+                ReportFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+                ReportFormat::Markdown => report.to_markdown(),
+            };
+            write_output(out, &rendered)?;
+            Ok(())
+        }
+    }
+}
+
+fn run_bundle(ops: &mut GtsOps, command: BundleCommand, output: OutputFormat) -> Result<()> {
+    match command {
+        BundleCommand::Export { pattern, out } => {
+            let bundle = ops.store.export_bundle(&pattern)?;
+            write_output(
+                out,
+                &gts::canonical_json_pretty(&serde_json::to_value(&bundle)?),
+            )?;
+        }
+        BundleCommand::Import { file } => {
+            let text = std::fs::read_to_string(&file)?;
+            let bundle: gts::GtsBundle = serde_json::from_str(&text)?;
+            let imported = ops
+                .store
+                .import_bundle_as(ops.principal.as_deref(), &bundle)?;
+            let written = imported
+                .iter()
+                .map(|id| ops.store.persist(id))
+                .collect::<Result<Vec<_>, _>>()?;
+            print_result(
+                &serde_json::json!({
+                    "ok": true,
+                    "imported": imported,
+                    "written": written,
+                }),
+                output,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn run_codegen(
+    ops: &GtsOps,
+    config: Option<&str>,
+    verbose: u8,
+    command: &CodegenCommand,
+    output: OutputFormat,
+) -> Result<()> {
+    let (CodegenCommand::Rust {
+        schema_dir,
+        pattern,
+        out,
+    }
+    | CodegenCommand::Typescript {
+        schema_dir,
+        pattern,
+        out,
+    }) = command;
+    let loaded;
+    let store = match schema_dir {
+        Some(dir) => {
+            loaded = GtsOps::try_new(
+                Some(vec![dir.clone()]),
+                config.map(str::to_owned),
+                verbose as usize,
+            )?;
+            &loaded.store
+        }
+        None => &ops.store,
+    };
+    let code = match command {
+        CodegenCommand::Rust { .. } => store.codegen_rust(pattern)?,
+        CodegenCommand::Typescript { .. } => store.codegen_typescript(pattern)?,
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(path, &code.content)?;
+            print_result(
+                &serde_json::json!({"ok": true, "path": path, "types": code.types}),
+                output,
+            )
+        }
+        None => write_output(None, &code.content),
+    }
+}
+
+fn run_export(ops: &GtsOps, command: ExportCommand, output: OutputFormat) -> Result<()> {
+    match command {
+        ExportCommand::Openapi {
+            pattern,
+            title,
+            api_version,
+            out,
+        } => {
+            let doc = ops.store.export_openapi(&pattern, &title, &api_version)?;
+            write_output(out, &gts::canonical_json_pretty(&doc))
+        }
+        ExportCommand::Avro { pattern, out } => {
+            let dir = std::path::Path::new(&out);
+            std::fs::create_dir_all(dir)?;
+            let export = ops.store.export_avro(&pattern)?;
+            let mut written = Vec::new();
+            for schema in &export.schemas {
+                let path = dir.join(&schema.file_name);
+                std::fs::write(&path, gts::canonical_json_pretty(&schema.schema))?;
+                written.push(path.display().to_string());
+            }
+            let subjects = dir.join("subjects.json");
+            std::fs::write(
+                &subjects,
+                gts::canonical_json_pretty(&serde_json::to_value(&export.subjects)?),
+            )?;
+            print_result(
+                &serde_json::json!({
+                    "ok": true,
+                    "files": written,
+                    "subjects": subjects.display().to_string(),
+                }),
+                output,
+            )
+        }
+        ExportCommand::Proto { pattern, out } => {
+            let dir = std::path::Path::new(&out);
+            std::fs::create_dir_all(dir)?;
+            let mut written = Vec::new();
+            for file in ops.store.export_proto(&pattern)? {
+                let path = dir.join(&file.file_name);
+                std::fs::write(&path, &file.content)?;
+                written.push(serde_json::json!({
+                    "schema_id": file.schema_id,
+                    "message": format!("{}.{}", file.package, file.message),
+                    "path": path.display().to_string(),
+                }));
+            }
+            print_result(&serde_json::json!({"ok": true, "files": written}), output)
+        }
+        #[cfg(feature = "parquet")]
+        ExportCommand::Parquet { pattern, out } => print_result(
+            &crate::parquet_export::export_parquet(
+                &ops.store,
+                &pattern,
+                std::path::Path::new(&out),
+            )?,
+            output,
+        ),
+    }
+}
+
+fn run_import(ops: &mut GtsOps, command: ImportCommand, output: OutputFormat) -> Result<()> {
+    match command {
+        ImportCommand::Openapi {
+            file,
+            vendor,
+            package,
+            namespace,
         } => {
-            generate_schemas_from_rust(&source, output.as_deref(), &exclude, cli.verbose)?;
+            let text = std::fs::read_to_string(&file)?;
+            let yaml = std::path::Path::new(&file).extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml")
+            });
+            let doc: serde_json::Value = if yaml {
+                serde_saphyr::from_str(&text)?
+            } else {
+                serde_json::from_str(&text)?
+            };
+            let report = ops.store.import_json_schemas_as(
+                ops.principal.as_deref(),
+                &doc,
+                &vendor,
+                &package,
+                &namespace,
+            )?;
+            let written = report
+                .schemas
+                .iter()
+                .map(|schema| ops.store.persist(&schema.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            print_result(
+                &serde_json::json!({
+                    "ok": true,
+                    "schemas": report.schemas,
+                    "warnings": report.warnings,
+                    "written": written,
+                }),
+                output,
+            )
         }
     }
+}
 
+fn run_lifecycle(ops: &mut GtsOps, command: LifecycleCommand, output: OutputFormat) -> Result<()> {
+    match command {
+        LifecycleCommand::Set {
+            schema_id,
+            state,
+            retire_on,
+            replacement,
+        } => {
+            let lifecycle = ops.store.set_schema_lifecycle(
+                &schema_id,
+                state,
+                retire_on.as_deref(),
+                replacement.as_deref(),
+            )?;
+            let file = ops.store.persist_schema_lifecycle(&schema_id)?;
+            print_result(
+                &serde_json::json!({
+                    "schema_id": schema_id,
+                    "file": file,
+                    "lifecycle": lifecycle,
+                }),
+                output,
+            )
+        }
+        LifecycleCommand::List { state } => {
+            let entries: Vec<_> = ops
+                .store
+                .lifecycle_report()
+                .into_iter()
+                .filter(|e| state.is_none_or(|s| e.effective_state == s))
+                .collect();
+            print_result(&entries, output)
+        }
+    }
+}
+
+/// Writes rendered output to `out`, or to stdout when no file is given.
+fn write_output(out: Option<String>, rendered: &str) -> Result<()> {
+    match out {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => print!("{rendered}"),
+    }
     Ok(())
 }
 
-fn print_result<T: serde::Serialize>(value: &T) -> Result<()> {
+fn print_result<T: serde::Serialize>(value: &T, output: OutputFormat) -> Result<()> {
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
-    serde_json::to_writer_pretty(&mut handle, value)?;
-    writeln!(handle)?;
+    handle.write_all(render(value, Ok(()), output)?.as_bytes())?;
     Ok(())
 }
 
+/// Prints `value` as the result of a command failing with `failure`, and
+/// fails with it; the printed result is the only report of the failure.
+fn print_failure<T: serde::Serialize>(
+    value: &T,
+    failure: String,
+    output: OutputFormat,
+) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(render(value, Err(&failure), output)?.as_bytes())?;
+    Err(Reported(failure).into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +1488,9 @@ mod tests {
             }
             _ => panic!("Expected List command"),
         }
+
+        let cli = Cli::try_parse_from(["gts", "--input", "schemas.tar.gz", "validate"]).unwrap();
+        assert_eq!(cli.path, Some("schemas.tar.gz".to_owned()));
     }
 
     #[test]
@@ -349,6 +1506,7 @@ mod tests {
             "tests/*",
             "--exclude",
             "examples/*",
+            "--check",
         ];
         let cli = Cli::try_parse_from(args).unwrap();
 
@@ -357,10 +1515,14 @@ mod tests {
                 source,
                 output,
                 exclude,
+                check,
+                diff,
             } => {
+                assert!(!diff);
                 assert_eq!(source, "/src/path");
                 assert_eq!(output, Some("/out/path".to_owned()));
                 assert_eq!(exclude, vec!["tests/*", "examples/*"]);
+                assert!(check);
             }
             _ => panic!("Expected GenerateFromRust command"),
         }
@@ -372,7 +1534,7 @@ mod tests {
         let cli = Cli::try_parse_from(args).unwrap();
 
         match cli.command {
-            Commands::Server { host, port } => {
+            Commands::Server { host, port, .. } => {
                 assert_eq!(host, "0.0.0.0");
                 assert_eq!(port, 3000);
             }
@@ -413,7 +1575,7 @@ mod tests {
 
         // Just verify it doesn't panic
         // We can't easily capture stdout in this test, but we can verify it compiles and runs
-        let result = print_result(&test_value);
+        let result = print_result(&test_value, OutputFormat::Text);
         assert!(result.is_ok());
     }
 
@@ -463,13 +1625,127 @@ mod tests {
         let cli = Cli::try_parse_from(args).unwrap();
 
         match cli.command {
-            Commands::ValidateInstance { gts_id } => {
+            Commands::ValidateInstance { gts_id, explain } => {
                 assert_eq!(gts_id, "test:schema:instance:v1");
+                assert!(!explain);
             }
             _ => panic!("Expected ValidateInstance command"),
         }
     }
 
+    #[test]
+    fn test_cli_parse_validate_instance_explain() {
+        let args = vec![
+            "gts",
+            "validate-instance",
+            "--gts-id",
+            "test:schema:instance:v1",
+            "--explain",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::ValidateInstance { explain, .. } => assert!(explain),
+            _ => panic!("Expected ValidateInstance command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_resolve_traits() {
+        let args = vec![
+            "gts",
+            "resolve-traits",
+            "--schema-id",
+            "gts.x.test.topic.v1~",
+            "--var",
+            "env=prod",
+            "--var",
+            "region=eu",
+            "--env",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::ResolveTraits {
+                schema_id,
+                vars,
+                env,
+            } => {
+                assert_eq!(schema_id, "gts.x.test.topic.v1~");
+                assert_eq!(vars, vec!["env=prod", "region=eu"]);
+                assert!(env);
+            }
+            _ => panic!("Expected ResolveTraits command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_plan_removal() {
+        let cli = Cli::try_parse_from(["gts", "plan-removal", "--pattern", "gts.x.old.*"]).unwrap();
+        match cli.command {
+            Commands::PlanRemoval { pattern } => assert_eq!(pattern, "gts.x.old.*"),
+            _ => panic!("Expected PlanRemoval command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_graph() {
+        let cli =
+            Cli::try_parse_from(["gts", "graph", "--format", "mermaid", "--instances"]).unwrap();
+
+        match cli.command {
+            Commands::Graph {
+                format,
+                instances,
+                out,
+            } => {
+                assert_eq!(format, GraphFormat::Mermaid);
+                assert!(instances);
+                assert!(out.is_none());
+            }
+            _ => panic!("Expected Graph command"),
+        }
+
+        let cli = Cli::try_parse_from(["gts", "graph"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Graph {
+                format: GraphFormat::Dot,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_export_terraform() {
+        let args = vec![
+            "gts",
+            "export-terraform",
+            "--pattern",
+            "gts.x.core.events.topic.v1~*",
+            "--var",
+            "env=prod",
+            "--out",
+            "kafka.tf.json",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::ExportTerraform {
+                pattern,
+                vars,
+                env,
+                out,
+            } => {
+                assert_eq!(pattern, "gts.x.core.events.topic.v1~*");
+                assert_eq!(vars, vec!["env=prod"]);
+                assert!(!env);
+                assert_eq!(out, Some("kafka.tf.json".to_owned()));
+            }
+            _ => panic!("Expected ExportTerraform command"),
+        }
+    }
+
     #[test]
     fn test_cli_parse_resolve_relationships() {
         let args = vec!["gts", "resolve-relationships", "--gts-id", "test:schema:v1"];
@@ -507,6 +1783,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_cast_all() {
+        let args = vec![
+            "gts",
+            "cast-all",
+            "--pattern",
+            "gts.x.shop.orders.order.v1.0~*",
+            "--to-schema-id",
+            "gts.x.shop.orders.order.v1.1~",
+            "--write",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::CastAll {
+                pattern,
+                to_schema_id,
+                write,
+            } => {
+                assert_eq!(pattern, "gts.x.shop.orders.order.v1.0~*");
+                assert_eq!(to_schema_id, "gts.x.shop.orders.order.v1.1~");
+                assert!(write);
+            }
+            _ => panic!("Expected CastAll command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_contract() {
+        let args = vec![
+            "gts",
+            "contract",
+            "--producer",
+            "gts.x.a.ns.event.v1~",
+            "--consumer",
+            "gts.x.b.ns.view.v1~",
+            "--format",
+            "markdown",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Contract {
+                producer,
+                consumer,
+                format,
+                out,
+            } => {
+                assert_eq!(producer, "gts.x.a.ns.event.v1~");
+                assert_eq!(consumer, "gts.x.b.ns.view.v1~");
+                assert_eq!(format, ReportFormat::Markdown);
+                assert!(out.is_none());
+            }
+            _ => panic!("Expected Contract command"),
+        }
+    }
+
     #[test]
     fn test_cli_parse_cast() {
         let args = vec![
@@ -537,9 +1870,16 @@ mod tests {
         let cli = Cli::try_parse_from(args).unwrap();
 
         match cli.command {
-            Commands::Query { expr, limit } => {
+            Commands::Query {
+                expr,
+                limit,
+                sort_by,
+                desc,
+            } => {
                 assert_eq!(expr, "test:*");
                 assert_eq!(limit, 25);
+                assert!(sort_by.is_none());
+                assert!(!desc);
             }
             _ => panic!("Expected Query command"),
         }
@@ -551,7 +1891,7 @@ mod tests {
         let cli = Cli::try_parse_from(args).unwrap();
 
         match cli.command {
-            Commands::Query { expr, limit } => {
+            Commands::Query { expr, limit, .. } => {
                 assert_eq!(expr, "test:*");
                 assert_eq!(limit, 100);
             }
@@ -559,6 +1899,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_query_sorted() {
+        let args = vec![
+            "gts",
+            "query",
+            "--expr",
+            "gts.x.*",
+            "--sort-by",
+            "created_at",
+            "--desc",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Query { sort_by, desc, .. } => {
+                assert_eq!(sort_by.as_deref(), Some("created_at"));
+                assert!(desc);
+            }
+            _ => panic!("Expected Query command"),
+        }
+
+        // --desc only makes sense with --sort-by
+        assert!(Cli::try_parse_from(vec!["gts", "query", "--expr", "gts.x.*", "--desc"]).is_err());
+    }
+
     #[test]
     fn test_cli_parse_attr() {
         let args = vec![
@@ -590,6 +1955,187 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_rename() {
+        let args = vec![
+            "gts",
+            "--path",
+            "/data",
+            "rename",
+            "--from",
+            "gts.x.oldpkg.*",
+            "--to",
+            "gts.x.newpkg.*",
+            "--rewrite-refs",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Rename {
+                from,
+                to,
+                rewrite_refs,
+                dry_run,
+                manifest,
+            } => {
+                assert_eq!(from, "gts.x.oldpkg.*");
+                assert_eq!(to, "gts.x.newpkg.*");
+                assert!(rewrite_refs);
+                assert!(!dry_run);
+                assert!(manifest.is_none());
+            }
+            _ => panic!("Expected Rename command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_audit_majors() {
+        let args = vec!["gts", "audit", "majors"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Audit {
+                command: AuditCommand::Majors,
+            } => {}
+            _ => panic!("Expected Audit Majors command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_bench_compare() {
+        let args = vec![
+            "gts",
+            "bench",
+            "compare",
+            "--before",
+            "snap-a",
+            "--after",
+            "snap-b",
+            "--workload",
+            "workload.json",
+            "--max-regression",
+            "20",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Bench {
+                command:
+                    BenchCommand::Compare {
+                        before,
+                        after,
+                        workload,
+                        format,
+                        out,
+                        max_regression,
+                    },
+            } => {
+                assert_eq!(before, "snap-a");
+                assert_eq!(after, "snap-b");
+                assert_eq!(workload, "workload.json");
+                assert_eq!(format, ReportFormat::Json);
+                assert!(out.is_none());
+                assert_eq!(max_regression, Some(20.0));
+            }
+            _ => panic!("Expected Bench Compare command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_refs_to() {
+        let cli =
+            Cli::try_parse_from(["gts", "refs", "--to", "gts.x.core.events.type.v1~"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Refs { ref to } if to == "gts.x.core.events.type.v1~"
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_audit_refs() {
+        let cli = Cli::try_parse_from(["gts", "audit", "refs"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Audit {
+                command: AuditCommand::Refs
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_report_conformance() {
+        let args = vec![
+            "gts",
+            "report",
+            "conformance",
+            "--service",
+            "payments",
+            "--patterns",
+            "gts.x.pay.*",
+            "gts.x.shared.*",
+            "--format",
+            "markdown",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Report {
+                command:
+                    ReportCommand::Conformance {
+                        service,
+                        source,
+                        patterns,
+                        format,
+                        out,
+                    },
+            } => {
+                assert_eq!(service, "payments");
+                assert_eq!(source, ".");
+                assert_eq!(patterns, vec!["gts.x.pay.*", "gts.x.shared.*"]);
+                assert_eq!(format, ReportFormat::Markdown);
+                assert!(out.is_none());
+            }
+            _ => panic!("Expected Report Conformance command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_lifecycle_set() {
+        let args = vec![
+            "gts",
+            "lifecycle",
+            "set",
+            "--schema-id",
+            "gts.x.test.life.order.v1~",
+            "--state",
+            "deprecated",
+            "--retire-on",
+            "2026-01-01",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        match cli.command {
+            Commands::Lifecycle {
+                command:
+                    LifecycleCommand::Set {
+                        schema_id,
+                        state,
+                        retire_on,
+                        replacement,
+                    },
+            } => {
+                assert_eq!(schema_id, "gts.x.test.life.order.v1~");
+                assert_eq!(state, GtsLifecycleState::Deprecated);
+                assert_eq!(retire_on.as_deref(), Some("2026-01-01"));
+                assert!(replacement.is_none());
+            }
+            _ => panic!("Expected Lifecycle Set command"),
+        }
+
+        let bad = vec!["gts", "lifecycle", "list", "--state", "sunset"];
+        assert!(Cli::try_parse_from(bad).is_err());
+    }
+
     #[test]
     fn test_cli_parse_openapi_spec() {
         let args = vec![
@@ -629,6 +2175,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_codegen_rust() {
+        let cli = Cli::try_parse_from([
+            "gts",
+            "codegen",
+            "rust",
+            "--schema-dir",
+            "schemas/",
+            "--out",
+            "src/generated.rs",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Codegen {
+                command:
+                    CodegenCommand::Rust {
+                        schema_dir,
+                        pattern,
+                        out,
+                    },
+            } => {
+                assert_eq!(schema_dir.as_deref(), Some("schemas/"));
+                assert_eq!(pattern, "gts.*");
+                assert_eq!(out.as_deref(), Some("src/generated.rs"));
+            }
+            _ => panic!("Expected Codegen command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["gts", "codegen", "typescript", "--pattern", "gts.x.shop.*"])
+                .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Codegen {
+                command: CodegenCommand::Typescript { pattern, out: None, .. }
+            } if pattern == "gts.x.shop.*"
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_generate_from_rust_diff_conflicts_with_check() {
+        let cli = Cli::try_parse_from(["gts", "generate-from-rust", "--source", "src", "--diff"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::GenerateFromRust {
+                diff: true,
+                check: false,
+                ..
+            }
+        ));
+        assert!(
+            Cli::try_parse_from([
+                "gts",
+                "generate-from-rust",
+                "--source",
+                "src",
+                "--diff",
+                "--check"
+            ])
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_cli_parse_generate_from_rust_minimal() {
         let args = vec!["gts", "generate-from-rust", "--source", "/src/path"];
@@ -639,10 +2249,14 @@ mod tests {
                 source,
                 output,
                 exclude,
+                check,
+                diff,
             } => {
+                assert!(!diff);
                 assert_eq!(source, "/src/path");
                 assert_eq!(output, None);
                 assert!(exclude.is_empty());
+                assert!(!check);
             }
             _ => panic!("Expected GenerateFromRust command"),
         }
@@ -654,7 +2268,22 @@ mod tests {
         let cli = Cli::try_parse_from(args).unwrap();
 
         match cli.command {
-            Commands::Server { host, port } => {
+            Commands::Server {
+                host,
+                port,
+                api_keys,
+                jwt_secret,
+                cache_control,
+                webhooks,
+                grpc_port,
+                tenants,
+            } => {
+                assert!(api_keys.is_empty());
+                assert!(tenants.is_empty());
+                assert!(grpc_port.is_none());
+                assert!(jwt_secret.is_none());
+                assert!(cache_control.is_none());
+                assert!(webhooks.is_empty());
                 assert_eq!(host, "127.0.0.1");
                 assert_eq!(port, 8000);
             }
@@ -0,0 +1,145 @@
+//! `gts completions`: shell completion scripts.
+//!
+//! The scripts call back into `gts` with `COMPLETE=<shell>` set for every
+//! completion, so they always match the installed binary and ID arguments
+//! complete to the entities under the `--path` (and the aliases in the
+//! `--config`) on the command line being completed, or under `$GTS_PATH`.
+
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::CompletionCandidate;
+use clap_complete::env::{CompleteEnv, Shells};
+use gts::GtsOps;
+use std::ffi::OsStr;
+use std::io::Write;
+
+use crate::cli::Cli;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Answers a completion request and exits if `gts` was run by a completion
+/// script; returns otherwise.  Must run before anything is printed.
+pub fn complete_from_env() {
+    CompleteEnv::with_factory(Cli::command).complete();
+}
+
+/// Writes the script that registers `gts` completions with `shell`.
+///
+/// # Errors
+/// Returns an error if `buf` cannot be written.
+pub fn write_registration(shell: CompletionShell, buf: &mut dyn Write) -> std::io::Result<()> {
+    let name = match shell {
+        CompletionShell::Bash => "bash",
+        CompletionShell::Zsh => "zsh",
+        CompletionShell::Fish => "fish",
+    };
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(name) else {
+        return Err(std::io::Error::other(format!("unsupported shell {name}")));
+    };
+    completer.write_registration("COMPLETE", "gts", "gts", "gts", buf)
+}
+
+/// The `--path` and `--config` values in `args`, with `$GTS_PATH` as the
+/// fallback path.
+fn store_args(args: &[String]) -> (Option<String>, Option<String>) {
+    let value = |flag: &str| {
+        let prefix = format!("{flag}=");
+        args.iter().enumerate().rev().find_map(|(i, arg)| {
+            if arg == flag {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix(&prefix).map(str::to_owned)
+            }
+        })
+    };
+    let path = value("--path").or_else(|| std::env::var("GTS_PATH").ok());
+    (path, value("--config"))
+}
+
+/// Registered IDs and `@aliases` of `ops` starting with `prefix`, sorted.
+fn id_candidates(ops: &GtsOps, prefix: &str) -> Vec<String> {
+    let mut ids: Vec<String> = ops
+        .store
+        .items()
+        .map(|(id, _)| id.clone())
+        .chain(ops.cfg.aliases.keys().map(|alias| format!("@{alias}")))
+        .filter(|id| id.starts_with(prefix))
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Completes an ID argument from the store the command line points at.
+pub fn complete_ids(current: &OsStr) -> Vec<CompletionCandidate> {
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(path), config) = store_args(&args) else {
+        return Vec::new();
+    };
+    let ops = GtsOps::new(Some(vec![path]), config, 0);
+    id_candidates(&ops, &current.to_string_lossy())
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_store_args_and_id_candidates() {
+        let args: Vec<String> = [
+            "gts",
+            "--",
+            "gts",
+            "--path",
+            "schemas",
+            "--config=gts.json",
+            "cast",
+            "--from-id",
+        ]
+        .map(str::to_owned)
+        .to_vec();
+        assert_eq!(
+            store_args(&args),
+            (Some("schemas".to_owned()), Some("gts.json".to_owned()))
+        );
+
+        let mut ops = GtsOps::new(None, None, 0);
+        for id in ["gts.x.test.compl.a.v1~", "gts.x.test.compl.b.v1~"] {
+            ops.add_schema(
+                id.to_owned(),
+                &json!({"$id": format!("gts://{id}"), "type": "object"}),
+            );
+        }
+        ops.cfg
+            .aliases
+            .insert("a".to_owned(), "gts.x.test.compl.a.v1~".to_owned());
+        assert_eq!(
+            id_candidates(&ops, "gts.x.test.compl."),
+            ["gts.x.test.compl.a.v1~", "gts.x.test.compl.b.v1~"]
+        );
+        assert_eq!(id_candidates(&ops, "@"), ["@a"]);
+    }
+
+    #[test]
+    fn test_registration_calls_back_into_gts() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let mut script = Vec::new();
+            write_registration(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("COMPLETE"), "{script}");
+        }
+    }
+}
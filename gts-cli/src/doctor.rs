@@ -0,0 +1,419 @@
+//! `gts doctor`: checks the local setup and says how to fix what is wrong.
+//!
+//! Each check is independent, so one run lists every problem instead of
+//! the first: the config file, the `--path` directories, files that
+//! loading skips or that are named after the wrong ID, instances that fail
+//! validation, whether the server port is free, and whether a configured
+//! remote registry is reachable and runs the same version as this CLI.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use gts::config::{CONFIG_FILE_NAMES, find_config_file, read_config_data};
+use gts::{GtsConfig, GtsConfigError, GtsConfigFile, GtsFileIssue, GtsFileReader, GtsOps};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::progress::OutputFormat;
+
+/// Config key naming a remote registry to compare versions with.
+pub const REGISTRY_KEY: &str = "registry";
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Per-item findings, e.g. one line per problem file.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+    /// What to do about it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            message: message.into(),
+            details: Vec::new(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    fn details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// Whether no check failed; warnings do not count.
+    #[must_use]
+    pub fn ok(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    /// The error `gts doctor` fails with, if a check failed.
+    #[must_use]
+    pub fn failure(&self) -> Option<String> {
+        (!self.ok()).then(|| "gts doctor found problems".to_owned())
+    }
+
+    /// Renders the report in `output` format.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be serialized.
+    pub fn render(&self, output: OutputFormat) -> anyhow::Result<String> {
+        if output != OutputFormat::Text {
+            let failure = self.failure();
+            return crate::output::render(self, failure.as_deref().map_or(Ok(()), Err), output);
+        }
+        let mut out = String::new();
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Ok => "ok",
+                CheckStatus::Warn => "warn",
+                CheckStatus::Fail => "FAIL",
+            };
+            let _ = writeln!(out, "[{status:>4}] {}: {}", check.name, check.message);
+            for detail in &check.details {
+                let _ = writeln!(out, "         - {detail}");
+            }
+            if let Some(fix) = &check.fix {
+                let _ = writeln!(out, "         fix: {fix}");
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Where `gts doctor` looks besides the loaded store.
+pub struct DoctorOptions<'a> {
+    /// The `--config` argument, if any.
+    pub config: Option<&'a str>,
+    pub host: &'a str,
+    pub port: u16,
+    /// Registry URL; overrides the config's `registry` key.
+    pub registry: Option<&'a str>,
+}
+
+/// Runs every check against `ops`, which was loaded from `--path`.
+#[must_use]
+pub fn run_doctor(ops: &mut GtsOps, options: &DoctorOptions<'_>) -> DoctorReport {
+    let (config_check, config) = check_config(options.config);
+    let mut checks = vec![config_check, check_paths(ops.path.as_deref())];
+    if let Some(paths) = ops.path.clone() {
+        checks.push(check_files(&paths, ops));
+        checks.push(check_store(ops));
+    }
+    checks.push(check_port(options.host, options.port));
+    let registry = options.registry.map(str::to_owned).or_else(|| {
+        config
+            .as_ref()
+            .and_then(|c| c.get(REGISTRY_KEY))
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+    });
+    if let Some(url) = registry {
+        checks.push(check_registry(&url));
+    }
+    DoctorReport { checks }
+}
+
+/// Other commands refuse to run with a config that cannot be loaded;
+/// `doctor` runs with the defaults instead and says what is wrong.
+fn check_config(config: Option<&str>) -> (Check, Option<HashMap<String, Value>>) {
+    let (path, explicit) = match config.map(PathBuf::from) {
+        Some(path) => (path, true),
+        None => match find_config_file() {
+            Some(path) => (path, false),
+            None => {
+                return (
+                    Check::new(
+                        "config",
+                        CheckStatus::Ok,
+                        format!(
+                            "no --config and none of {} here; using defaults",
+                            CONFIG_FILE_NAMES.join(", ")
+                        ),
+                    ),
+                    None,
+                );
+            }
+        },
+    };
+    let shown = path.display();
+    match read_config_data(&path) {
+        Ok(config) => match GtsConfig::from_file(&path) {
+            Ok(_) => (
+                Check::new("config", CheckStatus::Ok, format!("loaded {shown}")),
+                Some(config),
+            ),
+            Err(e) => (
+                Check::new("config", CheckStatus::Fail, e.to_string())
+                    .fix(format!("fix the value in {shown}")),
+                Some(config),
+            ),
+        },
+        Err(e @ GtsConfigError::Read { .. }) => {
+            let check = Check::new("config", CheckStatus::Fail, e.to_string());
+            let fix = if explicit {
+                "check the --config path, or drop --config to use ./gts.config.json".to_owned()
+            } else {
+                format!("make ./{shown} readable")
+            };
+            (check.fix(fix), None)
+        }
+        Err(e) => (
+            Check::new("config", CheckStatus::Fail, e.to_string())
+                .fix(format!("fix the syntax of {shown}")),
+            None,
+        ),
+    }
+}
+
+fn check_paths(paths: Option<&[String]>) -> Check {
+    let Some(paths) = paths else {
+        return Check::new(
+            "paths",
+            CheckStatus::Warn,
+            "no --path given; nothing is loaded",
+        )
+        .fix("pass --path <dir> pointing at your schema and instance files");
+    };
+    let missing: Vec<String> = paths
+        .iter()
+        .filter(|p| !Path::new(p).exists())
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        Check::new(
+            "paths",
+            CheckStatus::Ok,
+            format!("{} exists", paths.join(", ")),
+        )
+    } else {
+        Check::new(
+            "paths",
+            CheckStatus::Fail,
+            format!("{} does not exist", missing.join(", ")),
+        )
+        .fix("create the directory or correct --path")
+    }
+}
+
+fn check_files(paths: &[String], ops: &GtsOps) -> Check {
+    let issues = GtsFileReader::new(paths, Some(ops.cfg.clone())).check_files();
+    if issues.is_empty() {
+        return Check::new(
+            "files",
+            CheckStatus::Ok,
+            "all files parse, and files named after an ID hold that ID",
+        );
+    }
+    let details = issues
+        .iter()
+        .map(|issue| match issue {
+            GtsFileIssue::Unparseable { path, error } => {
+                format!("{path}: not valid JSON/YAML ({error}); fix the syntax")
+            }
+            GtsFileIssue::NoId { path } => format!(
+                "{path}: entity without an ID field; add \"id\" or \"$id\" (see entity_id_fields)"
+            ),
+            GtsFileIssue::IdMismatch { path, id } => {
+                format!("{path}: holds {id}; rename the file after that ID")
+            }
+            GtsFileIssue::BadLine { path, line, error } => {
+                format!("{path}: line {line} is not valid JSON ({error}); fix or remove it")
+            }
+        })
+        .collect();
+    Check::new(
+        "files",
+        CheckStatus::Warn,
+        format!(
+            "{} files or lines are skipped, or files misnamed",
+            issues.len()
+        ),
+    )
+    .details(details)
+}
+
+fn check_store(ops: &mut GtsOps) -> Check {
+    let entities = ops.store.items().count();
+    let report = ops.store.validate_all();
+    if report.ok() {
+        return Check::new(
+            "store",
+            CheckStatus::Ok,
+            format!(
+                "loaded {entities} entities; all {} instances valid",
+                report.checked
+            ),
+        );
+    }
+    let details = report
+        .failures
+        .iter()
+        .map(|f| format!("{}: {}", f.id, f.error))
+        .collect();
+    Check::new(
+        "store",
+        CheckStatus::Fail,
+        format!(
+            "loaded {entities} entities; {} of {} instances are invalid",
+            report.failures.len(),
+            report.checked
+        ),
+    )
+    .details(details)
+    .fix("run `gts validate-instance --explain <id>` for each, and fix the instance or its schema")
+}
+
+fn check_port(host: &str, port: u16) -> Check {
+    match TcpListener::bind((host, port)) {
+        Ok(_) => Check::new(
+            "port",
+            CheckStatus::Ok,
+            format!("{host}:{port} is free for `gts server`"),
+        ),
+        Err(e) => Check::new(
+            "port",
+            CheckStatus::Fail,
+            format!("cannot listen on {host}:{port}: {e}"),
+        )
+        .fix("stop whatever is using the port, or pass another --port to `gts server`"),
+    }
+}
+
+/// Compares this CLI's version with the one a registry reports at
+/// `GET /version`.
+fn check_registry(url: &str) -> Check {
+    let base = url.trim_end_matches('/');
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REGISTRY_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut response = match agent.get(format!("{base}/version")).call() {
+        Ok(response) => response,
+        Err(e) => {
+            return Check::new(
+                "registry",
+                CheckStatus::Fail,
+                format!("cannot reach {base}: {e}"),
+            )
+            .fix("check the registry URL, or start one with `gts server`");
+        }
+    };
+    let status = response.status().as_u16();
+    let remote = response
+        .body_mut()
+        .read_to_string()
+        .ok()
+        .and_then(|body| serde_json::from_str::<Value>(&body).ok())
+        .and_then(|body| {
+            body.get("version")
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        });
+    let local = env!("CARGO_PKG_VERSION");
+    match remote {
+        Some(remote) if remote == local => Check::new(
+            "registry",
+            CheckStatus::Ok,
+            format!("{base} runs version {remote}, same as this CLI"),
+        ),
+        Some(remote) => Check::new(
+            "registry",
+            CheckStatus::Warn,
+            format!("{base} runs version {remote}, this CLI is {local}"),
+        )
+        .fix("upgrade the CLI or the registry so both run the same version"),
+        None => Check::new(
+            "registry",
+            CheckStatus::Warn,
+            format!("{base} answered {status} but reported no version"),
+        )
+        .fix("upgrade the registry to a version that serves GET /version"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_doctor_reports_each_problem() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(
+            dir.join("gts.x.test.doc.old.v1~.schema.json"),
+            r#"{"$id": "gts://gts.x.test.doc.new.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}"#,
+        )
+        .unwrap();
+        let config = dir.join("gts.config.json");
+        fs::write(&config, "{ not json").unwrap();
+        let busy = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = busy.local_addr().unwrap().port();
+
+        let mut ops = GtsOps::new(Some(vec![dir.to_string_lossy().to_string()]), None, 0);
+        let report = run_doctor(
+            &mut ops,
+            &DoctorOptions {
+                config: config.to_str(),
+                host: "127.0.0.1",
+                port,
+                registry: None,
+            },
+        );
+
+        let status = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.status)
+        };
+        assert_eq!(status("config"), Some(CheckStatus::Fail));
+        assert_eq!(status("paths"), Some(CheckStatus::Ok));
+        assert_eq!(status("files"), Some(CheckStatus::Warn));
+        assert_eq!(status("store"), Some(CheckStatus::Ok));
+        assert_eq!(status("port"), Some(CheckStatus::Fail));
+        assert_eq!(status("registry"), None);
+        assert!(!report.ok());
+
+        let text = report.render(OutputFormat::Text).unwrap();
+        assert!(text.contains("holds gts.x.test.doc.new.v1~; rename the file"));
+        assert!(text.contains("fix: stop whatever is using the port"));
+    }
+}
@@ -1,11 +1,17 @@
 use anyhow::{Result, bail};
-use gts::{GtsInstanceId, GtsSchemaId};
+use gts::{GtsDeclaredSchema, GtsInstanceId, GtsSchemaId};
+use quote::ToTokens;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Expr, Fields, Item, Lit, Meta, Token};
 use walkdir::WalkDir;
 
+use crate::progress::{Progress, Summary};
+
 /// Directories that are automatically ignored (e.g., trybuild `compile_fail` tests)
 const AUTO_IGNORE_DIRS: &[&str] = &["compile_fail"];
 
@@ -46,28 +52,165 @@ enum BaseAttr {
     Parent(String),
 }
 
+/// A `#[struct_to_gts_schema]` struct found in a source file.
+#[derive(Debug, Clone)]
+struct AnnotatedStruct {
+    attrs: MacroAttrs,
+    name: String,
+    /// Line of the attribute.
+    line: usize,
+    /// Named fields and their types, with whitespace removed
+    /// (e.g. `Option<String>`).
+    field_types: HashMap<String, String>,
+}
+
+/// A schema planned from one annotated struct, not yet written.
+#[derive(Debug, Clone)]
+struct PlannedSchema {
+    schema_id: String,
+    struct_name: String,
+    base: BaseAttr,
+    source_file: PathBuf,
+    line: usize,
+    output_path: PathBuf,
+    /// Canonical `output_path`, for detecting two structs writing one file.
+    resolved_path: PathBuf,
+    schema: serde_json::Value,
+}
+
+impl PlannedSchema {
+    fn location(&self) -> String {
+        format!(
+            "{}:{} ({})",
+            self.source_file.display(),
+            self.line,
+            self.struct_name
+        )
+    }
+}
+
+/// The schemas planned from a source tree, and how its files fared.
+struct SourcePlan {
+    planned: Vec<PlannedSchema>,
+    files_scanned: usize,
+    files_unreadable: usize,
+    files_skipped: usize,
+}
+
+/// How a schema file on disk compares with the planned schema.
+enum OnDisk {
+    Current,
+    Missing,
+    /// A different schema, or not JSON; holds the file text.
+    Stale(String),
+}
+
+/// A schema file that `generate_schemas_from_rust` would create or change.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SchemaFileDiff {
+    pub schema_id: String,
+    pub path: PathBuf,
+    /// Line diff from the file on disk (empty if missing) to the schema
+    /// that would be written.  Both sides are pretty-printed first, so
+    /// formatting differences do not show up.
+    pub diff: String,
+}
+
+/// What scanning one source file found.
+enum FileScan {
+    Unreadable,
+    Ignored,
+    Planned(Vec<PlannedSchema>),
+}
+
 /// Generate GTS schemas from Rust source code with `#[struct_to_gts_schema]` annotations
 ///
+/// Files are parsed with `syn` in parallel, then checked for two structs
+/// declaring the same `schema_id` or writing the same file, and for children
+/// whose `base = Parent` does not match the parent's `schema_id`; only if
+/// there are no problems are the schemas written, in path order, so the
+/// result does not depend on scheduling.
+///
+/// With `check`, nothing is written: the planned schemas are compared with
+/// the files on disk, and the call fails listing every missing or stale file.
+/// CI runs this to catch schemas that were not regenerated.
+///
 /// # Arguments
 /// * `source` - Source directory or file to scan
 /// * `output` - Optional output directory override
 /// * `exclude_patterns` - Patterns to exclude (supports simple glob matching)
+/// * `check` - Compare with the files on disk instead of writing them
 /// * `verbose` - Verbosity level (0 = normal, 1+ = show skipped files)
+/// * `progress` - Progress bar advanced once per `.rs` file
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - The source path does not exist
+/// - A source file is not valid Rust
 /// - The output path is outside the source repository
+/// - Two structs declare the same schema ID or output file
+/// - A child's `base` struct declares a different parent schema ID
+/// - With `check`, a schema file is missing or out of date
 /// - File I/O operations fail
 pub fn generate_schemas_from_rust(
     source: &str,
     output: Option<&str>,
     exclude_patterns: &[String],
+    check: bool,
     verbose: u8,
-) -> Result<()> {
+    progress: Progress,
+) -> Result<Summary> {
     println!("Scanning Rust source files in: {source}");
+    let SourcePlan {
+        planned,
+        files_scanned,
+        files_unreadable,
+        files_skipped,
+    } = plan_source(source, output, exclude_patterns, verbose, &progress)?;
+
+    if check {
+        let outdated = outdated_schemas(&planned)?;
+        let summary = progress.finish(files_scanned, files_unreadable, files_skipped);
+        if !outdated.is_empty() {
+            bail!(
+                "{} of {} schema files are out of date (run `gts generate-from-rust` without --check):\n  {}",
+                outdated.len(),
+                planned.len(),
+                outdated.join("\n  ")
+            );
+        }
+        println!("\nSchemas up to date: {}", planned.len());
+        return Ok(summary);
+    }
+    let results = write_schemas(&planned)?;
+    progress.suspend(|| {
+        for (schema_id, file_path) in &results {
+            println!("  Generated schema: {schema_id} @ {file_path}");
+        }
+    });
+
+    let summary = progress.finish(files_scanned, files_unreadable, files_skipped);
+    println!("\nSchemas generated: {}", results.len());
 
+    if results.is_empty() {
+        println!(
+            "\n- No schemas found. Make sure your structs are annotated with `#[struct_to_gts_schema(...)]`"
+        );
+    }
+
+    Ok(summary)
+}
+
+/// Plans the schemas declared under `source`, failing on conflicting or
+/// inconsistent declarations.
+fn plan_source(
+    source: &str,
+    output: Option<&str>,
+    exclude_patterns: &[String],
+    verbose: u8,
+    progress: &Progress,
+) -> Result<SourcePlan> {
     let source_path = Path::new(source);
     if !source_path.exists() {
         bail!("Source path does not exist: {source}");
@@ -76,13 +219,13 @@ pub fn generate_schemas_from_rust(
     // Canonicalize source path to detect path traversal attempts
     let source_canonical = source_path.canonicalize()?;
 
-    let mut schemas_generated = 0;
-    let mut files_scanned = 0;
     let mut files_skipped = 0;
+    let mut candidates = Vec::new();
 
-    // Walk through all .rs files
+    // Walk through all .rs files, in a stable order
     for entry in WalkDir::new(source_path)
         .follow_links(true)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
     {
@@ -91,70 +234,331 @@ pub fn generate_schemas_from_rust(
             continue;
         }
 
-        // Check if path should be excluded
-        if should_exclude_path(path, exclude_patterns) {
+        // Check if path should be excluded, or is in an auto-ignored
+        // directory (e.g., compile_fail)
+        let skip = if should_exclude_path(path, exclude_patterns) {
+            Some(SkipReason::ExcludePattern)
+        } else if is_in_auto_ignored_dir(path) {
+            Some(SkipReason::AutoIgnoredDir)
+        } else {
+            None
+        };
+        if let Some(reason) = skip {
+            progress.inc();
             files_skipped += 1;
             if verbose > 0 {
-                println!(
-                    "  Skipped: {} ({})",
-                    path.display(),
-                    SkipReason::ExcludePattern
-                );
+                progress.suspend(|| println!("  Skipped: {} ({reason})", path.display()));
             }
             continue;
         }
+        candidates.push(path.to_path_buf());
+    }
 
-        // Check for auto-ignored directories (e.g., compile_fail)
-        if is_in_auto_ignored_dir(path) {
-            files_skipped += 1;
-            if verbose > 0 {
-                println!(
-                    "  Skipped: {} ({})",
-                    path.display(),
-                    SkipReason::AutoIgnoredDir
-                );
-            }
-            continue;
-        }
+    let scans = scan_files(&candidates, output, &source_canonical, progress)?;
 
-        files_scanned += 1;
-        if let Ok(content) = fs::read_to_string(path) {
-            // Check for gts:ignore directive
-            if has_ignore_directive(&content) {
+    let mut files_scanned = 0;
+    let mut files_unreadable = 0;
+    let mut planned = Vec::new();
+    for (path, scan) in candidates.iter().zip(scans) {
+        match scan {
+            FileScan::Unreadable => files_unreadable += 1,
+            FileScan::Ignored => {
                 files_skipped += 1;
                 if verbose > 0 {
-                    println!(
-                        "  Skipped: {} ({})",
-                        path.display(),
-                        SkipReason::IgnoreDirective
-                    );
+                    progress.suspend(|| {
+                        println!(
+                            "  Skipped: {} ({})",
+                            path.display(),
+                            SkipReason::IgnoreDirective
+                        );
+                    });
                 }
-                continue;
             }
-
-            // Parse the file and extract schema information
-            let results = extract_and_generate_schemas(&content, output, &source_canonical, path)?;
-            schemas_generated += results.len();
-            for (schema_id, file_path) in results {
-                println!("  Generated schema: {schema_id} @ {file_path}");
+            FileScan::Planned(schemas) => {
+                files_scanned += 1;
+                planned.extend(schemas);
             }
         }
     }
 
-    println!("\nSummary:");
-    println!("  Files scanned: {files_scanned}");
-    println!("  Files skipped: {files_skipped}");
-    println!("  Schemas generated: {schemas_generated}");
+    check_conflicts(&planned)?;
+    check_inheritance(&planned)?;
+    Ok(SourcePlan {
+        planned,
+        files_scanned,
+        files_unreadable,
+        files_skipped,
+    })
+}
 
-    if schemas_generated == 0 {
-        println!(
-            "\n- No schemas found. Make sure your structs are annotated with `#[struct_to_gts_schema(...)]`"
+/// Reads and plans `files` on a few threads; results are in `files` order.
+fn scan_files(
+    files: &[PathBuf],
+    output: Option<&str>,
+    source_root: &Path,
+    progress: &Progress,
+) -> Result<Vec<FileScan>> {
+    let scan = |path: &PathBuf| -> Result<FileScan> {
+        let scan = match fs::read_to_string(path) {
+            Err(_) => FileScan::Unreadable,
+            // Check for gts:ignore directive
+            Ok(content) if has_ignore_directive(&content) => FileScan::Ignored,
+            Ok(content) => FileScan::Planned(plan_schemas(&content, output, source_root, path)?),
+        };
+        progress.inc();
+        Ok(scan)
+    };
+
+    let threads = std::thread::available_parallelism()
+        .map_or(1, std::num::NonZeroUsize::get)
+        .min(files.len().max(1));
+    let chunk = files.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = files
+            .chunks(chunk)
+            .map(|part| scope.spawn(move || part.iter().map(scan).collect::<Result<Vec<_>>>()))
+            .collect();
+        let mut scans = Vec::with_capacity(files.len());
+        for worker in workers {
+            let part = worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("schema scan thread panicked"))??;
+            scans.extend(part);
+        }
+        Ok(scans)
+    })
+}
+
+/// Fails, listing every source location involved, if two structs declare
+/// the same schema ID or would write the same file.
+fn check_conflicts(planned: &[PlannedSchema]) -> Result<()> {
+    let mut by_id: BTreeMap<&str, Vec<&PlannedSchema>> = BTreeMap::new();
+    let mut by_path: BTreeMap<&Path, Vec<&PlannedSchema>> = BTreeMap::new();
+    for schema in planned {
+        by_id.entry(&schema.schema_id).or_default().push(schema);
+        by_path
+            .entry(&schema.resolved_path)
+            .or_default()
+            .push(schema);
+    }
+
+    let locations = |schemas: &[&PlannedSchema]| {
+        schemas
+            .iter()
+            .map(|s| format!("\n    {}", s.location()))
+            .collect::<Vec<_>>()
+            .concat()
+    };
+    let mut conflicts = Vec::new();
+    for (schema_id, schemas) in &by_id {
+        if schemas.len() > 1 {
+            conflicts.push(format!(
+                "schema_id '{schema_id}' is declared by:{}",
+                locations(schemas)
+            ));
+        }
+    }
+    for (path, schemas) in &by_path {
+        // Same-ID duplicates are already reported above
+        if schemas.len() > 1 && schemas.iter().any(|s| s.schema_id != schemas[0].schema_id) {
+            conflicts.push(format!(
+                "file '{}' is written by:{}",
+                path.display(),
+                locations(schemas)
+            ));
+        }
+    }
+    if !conflicts.is_empty() {
+        bail!(
+            "Conflicting schema declarations:\n  {}",
+            conflicts.join("\n  ")
         );
     }
+    Ok(())
+}
+
+/// Fails, listing each child, if a `base = Parent` struct in the scan
+/// declares a schema ID other than the child's parent segment, or if a child
+/// schema ID has no parent segment at all.  Parents outside the scan (e.g. in
+/// another crate) are not checked.
+fn check_inheritance(planned: &[PlannedSchema]) -> Result<()> {
+    let mut by_name: HashMap<&str, Vec<&PlannedSchema>> = HashMap::new();
+    for schema in planned {
+        by_name.entry(&schema.struct_name).or_default().push(schema);
+    }
 
+    let mut problems = Vec::new();
+    for schema in planned {
+        let BaseAttr::Parent(parent) = &schema.base else {
+            continue;
+        };
+        let expected = derive_parent_schema_id(&schema.schema_id);
+        if expected == schema.schema_id {
+            problems.push(format!(
+                "{}: base = {parent}, but schema_id '{}' has no parent segment",
+                schema.location(),
+                schema.schema_id
+            ));
+        } else if let Some(parents) = by_name.get(parent.as_str())
+            && !parents.iter().any(|p| p.schema_id == expected)
+        {
+            let declared: Vec<&str> = parents.iter().map(|p| p.schema_id.as_str()).collect();
+            problems.push(format!(
+                "{}: base = {parent} declares '{}', but the parent of '{}' is '{expected}'",
+                schema.location(),
+                declared.join("', '"),
+                schema.schema_id
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        bail!(
+            "Inconsistent schema inheritance:\n  {}",
+            problems.join("\n  ")
+        );
+    }
     Ok(())
 }
 
+/// Compares the file of `schema` with its planned content.  Key order,
+/// number form and formatting are ignored, so a reformatted file is still
+/// current.
+fn on_disk(schema: &PlannedSchema) -> Result<OnDisk> {
+    match fs::read_to_string(&schema.output_path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(OnDisk::Missing),
+        Err(e) => Err(e.into()),
+        Ok(text) => {
+            if serde_json::from_str::<serde_json::Value>(&text).is_ok_and(|on_disk| {
+                gts::canonical_value(&on_disk) == gts::canonical_value(&schema.schema)
+            }) {
+                Ok(OnDisk::Current)
+            } else {
+                Ok(OnDisk::Stale(text))
+            }
+        }
+    }
+}
+
+/// The planned schemas whose file is missing or holds a different schema,
+/// as `"<missing|stale>: <schema_id> @ <path>"` lines.
+fn outdated_schemas(planned: &[PlannedSchema]) -> Result<Vec<String>> {
+    let mut outdated = Vec::new();
+    for schema in planned {
+        let state = match on_disk(schema)? {
+            OnDisk::Current => continue,
+            OnDisk::Missing => "missing",
+            OnDisk::Stale(_) => "stale",
+        };
+        outdated.push(format!(
+            "{state}: {} @ {}",
+            schema.schema_id,
+            schema.output_path.display()
+        ));
+    }
+    Ok(outdated)
+}
+
+/// Writes planned schemas; returns `(schema_id, file_path)` pairs.
+fn write_schemas(planned: &[PlannedSchema]) -> Result<Vec<(String, String)>> {
+    let mut results = Vec::with_capacity(planned.len());
+    for schema in planned {
+        // Create parent directories
+        if let Some(parent) = schema.output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            &schema.output_path,
+            gts::canonical_json_pretty(&schema.schema),
+        )?;
+        results.push((
+            schema.schema_id.clone(),
+            schema.output_path.display().to_string(),
+        ));
+    }
+    Ok(results)
+}
+
+/// Diff the schema files under `source` against what
+/// [`generate_schemas_from_rust`] would write, without writing anything.
+/// Files that are up to date are left out.
+///
+/// # Errors
+///
+/// Returns an error in the same cases as [`generate_schemas_from_rust`]
+/// with `check`, except that out-of-date files are reported, not failed on.
+pub fn diff_schemas_from_rust(
+    source: &str,
+    output: Option<&str>,
+    exclude_patterns: &[String],
+    verbose: u8,
+    progress: Progress,
+) -> Result<Vec<SchemaFileDiff>> {
+    let plan = plan_source(source, output, exclude_patterns, verbose, &progress)?;
+    progress.clear();
+
+    let mut diffs = Vec::new();
+    for schema in &plan.planned {
+        let old = match on_disk(schema)? {
+            OnDisk::Current => continue,
+            OnDisk::Missing => String::new(),
+            OnDisk::Stale(text) => serde_json::from_str::<serde_json::Value>(&text)
+                .map_or(text, |value| gts::canonical_json_pretty(&value)),
+        };
+        let new = gts::canonical_json_pretty(&schema.schema);
+        diffs.push(SchemaFileDiff {
+            schema_id: schema.schema_id.clone(),
+            path: schema.output_path.clone(),
+            diff: gts::snapshot::line_diff(&old, &new),
+        });
+    }
+    Ok(diffs)
+}
+
+/// Collect the schema IDs declared via `#[struct_to_gts_schema]` in Rust sources
+/// under `source`, without generating any files.
+///
+/// # Errors
+///
+/// Returns an error if the source path does not exist or a source file is
+/// not valid Rust.
+pub fn find_declared_schemas(
+    source: &str,
+    exclude_patterns: &[String],
+) -> Result<Vec<GtsDeclaredSchema>> {
+    let source_path = Path::new(source);
+    if !source_path.exists() {
+        bail!("Source path does not exist: {source}");
+    }
+    let mut declared = Vec::new();
+    for entry in WalkDir::new(source_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("rs")
+            || should_exclude_path(path, exclude_patterns)
+            || is_in_auto_ignored_dir(path)
+        {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        if has_ignore_directive(&content) {
+            continue;
+        }
+        for found in annotated_structs(&content, path)? {
+            declared.push(GtsDeclaredSchema {
+                schema_id: found.attrs.schema_id,
+                declared_by: Some(found.name),
+                source: Some(path.display().to_string()),
+            });
+        }
+    }
+    Ok(declared)
+}
+
 /// Check if a path matches any of the exclude patterns
 fn should_exclude_path(path: &Path, patterns: &[String]) -> bool {
     let path_str = path.to_string_lossy();
@@ -217,80 +621,164 @@ fn has_ignore_directive(content: &str) -> bool {
     false
 }
 
-/// Parse the attribute body of `#[struct_to_gts_schema(...)]` to extract individual attributes
-fn parse_macro_attrs(attr_body: &str) -> Option<MacroAttrs> {
-    // Patterns for extracting individual attributes
-    let dir_path_re = Regex::new(r#"dir_path\s*=\s*"([^"]+)""#).ok()?;
-    let schema_id_re = Regex::new(r#"schema_id\s*=\s*"([^"]+)""#).ok()?;
-    let description_re = Regex::new(r#"description\s*=\s*"([^"]+)""#).ok()?;
-    let properties_re = Regex::new(r#"properties\s*=\s*"([^"]+)""#).ok()?;
-    let base_true_re = Regex::new(r"\bbase\s*=\s*true\b").ok()?;
-    let base_parent_re = Regex::new(r"\bbase\s*=\s*([A-Z]\w*)").ok()?;
-
-    // Extract required fields
-    let dir_path = dir_path_re.captures(attr_body)?.get(1)?.as_str().to_owned();
-    let schema_id = schema_id_re
-        .captures(attr_body)?
-        .get(1)?
-        .as_str()
-        .to_owned();
-
-    // Extract optional fields
-    let description = description_re
-        .captures(attr_body)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_owned()));
-    let properties = properties_re
-        .captures(attr_body)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_owned()));
-
-    // Parse base attribute
-    let base = if base_true_re.is_match(attr_body) {
-        BaseAttr::IsBase
-    } else if let Some(cap) = base_parent_re.captures(attr_body) {
-        BaseAttr::Parent(cap.get(1)?.as_str().to_owned())
-    } else {
-        // base is required but not found
-        return None;
+/// Extracts the generator's attributes from parsed macro arguments; `None`
+/// if `dir_path`, `schema_id` or a valid `base` is missing.
+fn macro_attrs(metas: &Punctuated<Meta, Token![,]>) -> Option<MacroAttrs> {
+    let string = |expr: &Expr| match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => Some(s.value()),
+            _ => None,
+        },
+        _ => None,
     };
 
+    let mut dir_path = None;
+    let mut schema_id = None;
+    let mut description = None;
+    let mut properties = None;
+    let mut base = None;
+    for meta in metas {
+        // Flags such as `payload_must_be_gts` do not affect the schema file
+        let Meta::NameValue(pair) = meta else {
+            continue;
+        };
+        let Some(key) = pair.path.get_ident() else {
+            continue;
+        };
+        match key.to_string().as_str() {
+            "dir_path" => dir_path = string(&pair.value),
+            "schema_id" => schema_id = string(&pair.value),
+            "description" => description = string(&pair.value),
+            "properties" => properties = string(&pair.value),
+            "base" => {
+                base = match &pair.value {
+                    Expr::Lit(lit) if matches!(&lit.lit, Lit::Bool(b) if b.value) => {
+                        Some(BaseAttr::IsBase)
+                    }
+                    Expr::Path(path) => path
+                        .path
+                        .get_ident()
+                        .map(|parent| BaseAttr::Parent(parent.to_string())),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+
     Some(MacroAttrs {
-        dir_path,
-        schema_id,
+        dir_path: dir_path?,
+        schema_id: schema_id?,
         description,
         properties,
-        base,
+        base: base?,
     })
 }
 
-/// Extract schema metadata from Rust source and generate JSON files
-/// Returns a vector of (`schema_id`, `file_path`) tuples for each generated schema
-fn extract_and_generate_schemas(
+/// Parses `content` and returns its `#[struct_to_gts_schema]` structs,
+/// including those in inline modules, in source order.  Structs whose
+/// attribute the generator cannot read are skipped; the macro reports those
+/// at compile time.
+fn annotated_structs(content: &str, source_file: &Path) -> Result<Vec<AnnotatedStruct>> {
+    fn collect(items: &[Item], found: &mut Vec<AnnotatedStruct>) {
+        for item in items {
+            match item {
+                Item::Struct(item) => {
+                    let attr = item.attrs.iter().find(|attr| {
+                        attr.path()
+                            .segments
+                            .last()
+                            .is_some_and(|s| s.ident == "struct_to_gts_schema")
+                    });
+                    let Some(attr) = attr else {
+                        continue;
+                    };
+                    let Some(attrs) = attr
+                        .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                        .ok()
+                        .and_then(|metas| macro_attrs(&metas))
+                    else {
+                        continue;
+                    };
+                    let field_types = match &item.fields {
+                        Fields::Named(fields) => fields
+                            .named
+                            .iter()
+                            .filter_map(|field| {
+                                let ty = field.ty.to_token_stream().to_string();
+                                Some((
+                                    field.ident.as_ref()?.to_string(),
+                                    ty.split_whitespace().collect(),
+                                ))
+                            })
+                            .collect(),
+                        Fields::Unnamed(_) | Fields::Unit => HashMap::new(),
+                    };
+                    found.push(AnnotatedStruct {
+                        attrs,
+                        name: item.ident.to_string(),
+                        line: attr.span().start().line,
+                        field_types,
+                    });
+                }
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        collect(items, found);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let file = syn::parse_file(content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", source_file.display()))?;
+    let mut found = Vec::new();
+    collect(&file.items, &mut found);
+    Ok(found)
+}
+
+/// Canonical form of `path`, which need not exist: the deepest existing
+/// ancestor is canonicalized and the rest appended with `..` resolved.
+fn resolve_path(path: &Path) -> Result<PathBuf> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    while !existing.as_os_str().is_empty() && !existing.exists() {
+        let Some(name) = existing.components().next_back() else {
+            break;
+        };
+        missing.push(name);
+        existing = existing.parent().unwrap_or(Path::new(""));
+    }
+    let base = if existing.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        existing
+    };
+    let mut resolved = base.canonicalize()?;
+    for component in missing.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Plan the JSON schema files for the annotated structs in one source file
+fn plan_schemas(
     content: &str,
     output_override: Option<&str>,
     source_root: &Path,
     source_file: &Path,
-) -> Result<Vec<(String, String)>> {
-    // Match #[struct_to_gts_schema(...)] followed by struct definition
-    // Captures: (1) attribute body, (2) struct name, (3) optional generics, (4) struct body or semicolon for unit structs
-    let re = Regex::new(
-        r"(?s)#\[struct_to_gts_schema\(([^)]+)\)\]\s*(?:#\[[^\]]+\]\s*)*(?:pub\s+)?struct\s+(\w+)(?:<([^>]+)>)?\s*(?:\{([^}]*)\}|;)",
-    )?;
-
-    // Pre-compile field regex outside the loop
-    let field_re = Regex::new(r"(?m)^\s*(?:pub\s+)?(\w+)\s*:\s*([^,\n]+)")?;
-
+) -> Result<Vec<PlannedSchema>> {
     let mut results = Vec::new();
 
-    for cap in re.captures_iter(content) {
-        let attr_body = &cap[1];
-        let struct_name = &cap[2];
-        let _generics = cap.get(3).map(|m| m.as_str());
-        let struct_body = cap.get(4).map_or("", |m| m.as_str());
-
-        // Parse macro attributes
-        let Some(attrs) = parse_macro_attrs(attr_body) else {
-            continue;
-        };
+    for found in annotated_structs(content, source_file)? {
+        let attrs = found.attrs;
 
         // Convert schema_id to filename-safe format
         // e.g., "gts.x.core.events.type.v1~" -> "gts.x.core.events.type.v1~"
@@ -307,61 +795,39 @@ fn extract_and_generate_schemas(
         };
 
         // Security check: ensure output path doesn't escape source repository
-        let output_canonical = if output_path.exists() {
-            output_path.canonicalize()?
-        } else {
-            // For non-existent files, canonicalize the parent directory
-            let parent = output_path.parent().unwrap_or(Path::new("."));
-            fs::create_dir_all(parent)?;
-            let parent_canonical = parent.canonicalize()?;
-            let file_name = output_path
-                .file_name()
-                .ok_or_else(|| anyhow::anyhow!("Invalid output path: no file name"))?;
-            parent_canonical.join(file_name)
-        };
-
-        // Check if output path is within source repository
+        let output_canonical = resolve_path(&output_path)?;
         if !output_canonical.starts_with(source_root) {
             bail!(
                 "Security error in {}:{} - dir_path '{}' attempts to write outside source repository. \
                 Resolved to: {}, but must be within: {}",
                 source_file.display(),
-                struct_name,
+                found.name,
                 attrs.dir_path,
                 output_canonical.display(),
                 source_root.display()
             );
         }
 
-        // Parse struct fields
-        let mut field_types = HashMap::new();
-
-        for field_cap in field_re.captures_iter(struct_body) {
-            let field_name = &field_cap[1];
-            let field_type = field_cap[2].trim().trim_end_matches(',');
-            field_types.insert(field_name.to_owned(), field_type.to_owned());
-        }
-
         // Build JSON schema
         let schema = build_json_schema(
             &attrs.schema_id,
-            struct_name,
+            &found.name,
             attrs.description.as_deref(),
             attrs.properties.as_deref(),
             &attrs.base,
-            &field_types,
+            &found.field_types,
         );
 
-        // Create parent directories
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        // Write schema file
-        fs::write(&output_path, serde_json::to_string_pretty(&schema)?)?;
-
-        // Add to results (schema_id, file_path)
-        results.push((attrs.schema_id, output_path.display().to_string()));
+        results.push(PlannedSchema {
+            schema_id: attrs.schema_id,
+            struct_name: found.name,
+            base: attrs.base,
+            source_file: source_file.to_path_buf(),
+            line: found.line,
+            output_path,
+            resolved_path: output_canonical,
+            schema,
+        });
     }
 
     Ok(results)
@@ -556,6 +1022,17 @@ fn rust_type_to_json_schema(rust_type: &str) -> (bool, serde_json::Value) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::progress::OutputFormat;
+
+    /// Parse the attribute body of `#[struct_to_gts_schema(...)]`
+    fn parse_macro_attrs(attr_body: &str) -> Option<MacroAttrs> {
+        use syn::parse::Parser;
+
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated
+            .parse_str(attr_body)
+            .ok()?;
+        macro_attrs(&metas)
+    }
 
     #[test]
     fn test_matches_glob_pattern() {
@@ -789,6 +1266,40 @@ mod tests {
         assert!(attrs.description.is_none());
     }
 
+    #[test]
+    fn test_find_declared_schemas() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("model.rs"),
+            r#"
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = true,
+    schema_id = "gts.x.pay.core.payment.v1~"
+)]
+#[derive(Debug)]
+pub struct Payment {
+    pub id: String,
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("ignored.rs"),
+            "// gts:ignore\n#[struct_to_gts_schema(dir_path = \"s\", base = true, schema_id = \"gts.x.pay.core.skip.v1~\")]\npub struct Skip;\n",
+        )
+        .unwrap();
+
+        let declared = find_declared_schemas(temp_dir.path().to_str().unwrap(), &[]).unwrap();
+        assert_eq!(declared.len(), 1);
+        assert_eq!(declared[0].schema_id, "gts.x.pay.core.payment.v1~");
+        assert_eq!(declared[0].declared_by.as_deref(), Some("Payment"));
+
+        assert!(find_declared_schemas("/nonexistent/gts/source", &[]).is_err());
+    }
+
     #[test]
     fn test_extract_and_generate_schemas_single_struct() {
         use std::fs;
@@ -816,14 +1327,14 @@ pub struct Person {
 "#;
         fs::write(&test_file, content).unwrap();
 
-        // Call extract_and_generate_schemas
-        let results = extract_and_generate_schemas(
+        let planned = plan_schemas(
             content,
             Some(temp_path.to_str().unwrap()),
             &temp_path,
             &test_file,
         )
         .unwrap();
+        let results = write_schemas(&planned).unwrap();
 
         // Verify results
         assert_eq!(results.len(), 1);
@@ -883,14 +1394,14 @@ pub struct ChildEvent {
 "#;
         fs::write(&test_file, content).unwrap();
 
-        // Call extract_and_generate_schemas
-        let results = extract_and_generate_schemas(
+        let planned = plan_schemas(
             content,
             Some(temp_path.to_str().unwrap()),
             &temp_path,
             &test_file,
         )
         .unwrap();
+        let results = write_schemas(&planned).unwrap();
 
         // Verify results - should have 2 schemas
         assert_eq!(results.len(), 2);
@@ -1032,7 +1543,9 @@ pub struct ChildEvent {
             temp_path.to_str().unwrap(),
             None,
             &["test_*.rs".to_owned()],
+            false,
             1, // verbose
+            Progress::new("generate", None, OutputFormat::Json),
         );
 
         assert!(result.is_ok());
@@ -1053,7 +1566,9 @@ pub struct ChildEvent {
             temp_path.to_str().unwrap(),
             None,
             &[],
+            false,
             1, // verbose
+            Progress::new("generate", None, OutputFormat::Json),
         );
 
         assert!(result.is_ok());
@@ -1061,10 +1576,152 @@ pub struct ChildEvent {
 
     #[test]
     fn test_generate_schemas_from_rust_nonexistent_path() {
-        let result =
-            generate_schemas_from_rust("/nonexistent/path/that/does/not/exist", None, &[], 0);
+        let result = generate_schemas_from_rust(
+            "/nonexistent/path/that/does/not/exist",
+            None,
+            &[],
+            false,
+            0,
+            Progress::new("generate", None, OutputFormat::Json),
+        );
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
     }
+
+    #[test]
+    fn test_generate_schemas_from_rust_reports_conflicts() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let declare = |file: &str, name: &str| {
+            let source = format!(
+                "#[struct_to_gts_schema(\n    dir_path = \"schemas\",\n    base = true,\n    schema_id = \"gts.x.test.dup.event.v1~\",\n    description = \"Event\",\n    properties = \"id\"\n)]\npub struct {name} {{\n    pub id: String,\n}}\n"
+            );
+            fs::write(temp_path.join(file), format!("// header\n{source}")).unwrap();
+        };
+        declare("a.rs", "First");
+        declare("b.rs", "Second");
+
+        let err = generate_schemas_from_rust(
+            temp_path.to_str().unwrap(),
+            None,
+            &[],
+            false,
+            0,
+            Progress::new("generate", None, OutputFormat::Json),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("schema_id 'gts.x.test.dup.event.v1~' is declared by"));
+        assert!(err.contains("a.rs:2 (First)"));
+        assert!(err.contains("b.rs:2 (Second)"));
+        assert!(!temp_path.join("schemas").exists());
+    }
+
+    #[test]
+    fn test_check_mode_reports_missing_and_stale_files() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        // Annotated structs inside an inline module, with a qualified path
+        fs::write(
+            temp_path.join("model.rs"),
+            r#"
+pub mod events {
+    #[gts_macros::struct_to_gts_schema(
+        dir_path = "schemas",
+        base = true,
+        schema_id = "gts.x.test.chk.event.v1~"
+    )]
+    #[derive(Debug)]
+    pub struct Event<P> {
+        pub id: String,
+        pub tags: Vec<String>,
+        pub payload: P,
+    }
+}
+"#,
+        )
+        .unwrap();
+        let run = |check| {
+            generate_schemas_from_rust(
+                temp_path.to_str().unwrap(),
+                None,
+                &[],
+                check,
+                0,
+                Progress::new("generate", None, OutputFormat::Json),
+            )
+        };
+        let schema_file = temp_path.join("schemas/gts.x.test.chk.event.v1~.schema.json");
+
+        let err = run(true).unwrap_err().to_string();
+        assert!(err.contains("1 of 1 schema files are out of date"));
+        assert!(err.contains("missing: gts.x.test.chk.event.v1~"));
+        assert!(!schema_file.exists());
+
+        run(false).unwrap();
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&schema_file).unwrap()).unwrap();
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+        run(true).unwrap();
+
+        // Reformatting is not a change, editing is
+        fs::write(&schema_file, serde_json::to_string(&schema).unwrap() + "\n").unwrap();
+        run(true).unwrap();
+        fs::write(&schema_file, "{}").unwrap();
+        let err = run(true).unwrap_err().to_string();
+        assert!(err.contains("stale: gts.x.test.chk.event.v1~"));
+    }
+
+    #[test]
+    fn test_generate_schemas_rejects_mismatched_base() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        fs::write(
+            temp_path.join("model.rs"),
+            r#"
+#[struct_to_gts_schema(dir_path = "schemas", base = true, schema_id = "gts.x.test.inh.base.v1~")]
+pub struct Base {
+    pub id: String,
+}
+
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = Base,
+    schema_id = "gts.x.test.inh.other.v1~x.test.inh.child.v1~"
+)]
+pub struct Child {
+    pub name: String,
+}
+"#,
+        )
+        .unwrap();
+
+        let err = generate_schemas_from_rust(
+            temp_path.to_str().unwrap(),
+            None,
+            &[],
+            false,
+            0,
+            Progress::new("generate", None, OutputFormat::Json),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("model.rs:7 (Child): base = Base declares 'gts.x.test.inh.base.v1~'"));
+        assert!(err.contains("is 'gts.x.test.inh.other.v1~'"));
+        assert!(!temp_path.join("schemas").exists());
+
+        fs::write(temp_path.join("broken.rs"), "pub struct {").unwrap();
+        let err = find_declared_schemas(temp_path.to_str().unwrap(), &[])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Failed to parse"));
+    }
 }
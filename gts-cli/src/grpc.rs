@@ -0,0 +1,334 @@
+//! gRPC interface of the server, for platforms that standardize on gRPC.
+//!
+//! The `gts.v1.Gts` service of `proto/gts/v1/gts.proto` mirrors the REST
+//! routes for fetching schemas, validating, querying and casting, over the
+//! same store as the HTTP server.  Callers read with the role their
+//! credentials allow, as over REST, sent as the `x-api-key` or
+//! `authorization` metadata key, and may ask for a lower one in the
+//! `x-gts-role` key.
+
+// tonic's handlers return its large `Status` by value
+#![allow(clippy::result_large_err)]
+
+use gts::GtsOps;
+use std::sync::{Arc, Mutex};
+use tonic::{Request, Response, Status};
+
+use crate::auth::{AuthError, GtsAuthConfig, ROLE_HEADER, caller_role};
+
+#[allow(
+    clippy::pedantic,
+    clippy::default_trait_access,
+    clippy::doc_markdown,
+    clippy::missing_errors_doc,
+    clippy::must_use_candidate,
+    clippy::wildcard_imports
+)]
+pub mod proto {
+    tonic::include_proto!("gts.v1");
+}
+
+use proto::gts_server::{Gts, GtsServer};
+use proto::{
+    CastRequest, CastResponse, GetSchemaRequest, GetSchemaResponse, QueryRequest, QueryResponse,
+    ValidateRequest, ValidateResponse,
+};
+
+/// Results returned by `Query` when the request sets no limit, as over REST.
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// The `gts.v1.Gts` service over a store shared with the HTTP server.
+pub struct GtsGrpcService {
+    ops: Arc<Mutex<GtsOps>>,
+    auth: Arc<GtsAuthConfig>,
+}
+
+impl GtsGrpcService {
+    /// The service without credentials, so every caller reads as public.
+    #[must_use]
+    pub fn new(ops: Arc<Mutex<GtsOps>>) -> Self {
+        Self {
+            ops,
+            auth: Arc::default(),
+        }
+    }
+
+    /// Lets callers read with the roles `auth`'s credentials allow.
+    #[must_use]
+    pub fn with_auth(mut self, auth: Arc<GtsAuthConfig>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// The service ready to add to a `tonic` server.
+    #[must_use]
+    pub fn into_server(self) -> GtsServer<Self> {
+        GtsServer::new(self)
+    }
+
+    /// Runs `f` with the store showing only what `request`'s caller may
+    /// see, on tokio's blocking pool so store work does not stall the
+    /// async workers.
+    async fn with_ops<R, T: Send + 'static>(
+        &self,
+        request: &Request<R>,
+        f: impl FnOnce(&mut GtsOps) -> Result<T, Status> + Send + 'static,
+    ) -> Result<Response<T>, Status> {
+        let requested = request
+            .metadata()
+            .get(ROLE_HEADER)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|e| e.to_string())
+                    .and_then(str::parse)
+            })
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        let headers = request.metadata().clone().into_headers();
+        let role = caller_role(&self.auth, &headers, requested).map_err(|e| match e {
+            AuthError::Forbidden(_) => Status::permission_denied(e.to_string()),
+            _ => Status::unauthenticated(e.to_string()),
+        })?;
+        let ops = self.ops.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut ops = ops
+                .lock()
+                .map_err(|_| Status::internal("Server state corrupted"))?;
+            ops.store.set_audience(Some(role));
+            let result = f(&mut ops);
+            ops.store.set_audience(None);
+            result
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map(Response::new)
+    }
+}
+
+/// `NOT_FOUND` unless the caller may see `id`.
+fn visible(ops: &GtsOps, id: &str) -> Result<(), Status> {
+    if ops.store.is_visible(id) {
+        Ok(())
+    } else {
+        Err(Status::not_found(format!("Entity '{id}' not found")))
+    }
+}
+
+#[tonic::async_trait]
+impl Gts for GtsGrpcService {
+    async fn get_schema(
+        &self,
+        request: Request<GetSchemaRequest>,
+    ) -> Result<Response<GetSchemaResponse>, Status> {
+        let gts_id = request.get_ref().gts_id.clone();
+        self.with_ops(&request, move |ops| {
+            visible(ops, &gts_id)?;
+            let result = ops.get_entity(&gts_id);
+            match result.content {
+                Some(schema) if result.ok && result.is_schema => Ok(GetSchemaResponse {
+                    gts_id: result.id,
+                    schema_json: schema.to_string(),
+                }),
+                _ => Err(Status::not_found(format!("Schema '{gts_id}' not found"))),
+            }
+        })
+        .await
+    }
+
+    async fn validate(
+        &self,
+        request: Request<ValidateRequest>,
+    ) -> Result<Response<ValidateResponse>, Status> {
+        let gts_id = request.get_ref().gts_id.clone();
+        self.with_ops(&request, move |ops| {
+            visible(ops, &gts_id)?;
+            let result = ops.validate_entity(&gts_id);
+            Ok(ValidateResponse {
+                gts_id: result.id,
+                ok: result.ok,
+                entity_type: result.entity_type,
+                error: result.error,
+            })
+        })
+        .await
+    }
+
+    async fn query(
+        &self,
+        request: Request<QueryRequest>,
+    ) -> Result<Response<QueryResponse>, Status> {
+        let QueryRequest { expr, limit } = request.get_ref().clone();
+        let limit = match usize::try_from(limit) {
+            Ok(0) | Err(_) => DEFAULT_QUERY_LIMIT,
+            Ok(limit) => limit,
+        };
+        self.with_ops(&request, move |ops| {
+            let result = ops.query(&expr, limit);
+            if !result.error.is_empty() {
+                return Err(Status::invalid_argument(result.error));
+            }
+            Ok(QueryResponse {
+                count: u32::try_from(result.count).unwrap_or(u32::MAX),
+                results_json: result.results.iter().map(ToString::to_string).collect(),
+            })
+        })
+        .await
+    }
+
+    async fn cast(&self, request: Request<CastRequest>) -> Result<Response<CastResponse>, Status> {
+        let CastRequest {
+            instance_id,
+            to_schema_id,
+        } = request.get_ref().clone();
+        self.with_ops(&request, move |ops| {
+            visible(ops, &instance_id)?;
+            visible(ops, &to_schema_id)?;
+            let result = ops.cast(&instance_id, &to_schema_id);
+            Ok(CastResponse {
+                from_id: result.from_id,
+                to_id: result.to_id,
+                direction: result.direction,
+                is_fully_compatible: result.is_fully_compatible,
+                is_backward_compatible: result.is_backward_compatible,
+                is_forward_compatible: result.is_forward_compatible,
+                incompatibility_reasons: result.incompatibility_reasons,
+                casted_entity_json: result
+                    .casted_entity
+                    .map(|entity| entity.to_string())
+                    .unwrap_or_default(),
+                error: result.error.unwrap_or_default(),
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::auth::{API_KEY_HEADER, GtsApiKey};
+    use gts::GtsVisibility;
+    use serde_json::json;
+    use tonic::Code;
+
+    const SCHEMA: &str = "gts.x.test.grpc.item.v1~";
+    const HIDDEN: &str = "gts.x.test.grpc.audit.v1~";
+
+    fn service() -> GtsGrpcService {
+        let mut ops = GtsOps::new(None, None, 0);
+        for (id, visibility) in [(SCHEMA, "public"), (HIDDEN, "internal")] {
+            let schema = json!({
+                "$id": format!("gts://{id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "x-gts-visibility": visibility
+            });
+            assert!(ops.add_schema(id.to_owned(), &schema).ok);
+        }
+        let auth = GtsAuthConfig {
+            api_keys: vec![GtsApiKey {
+                key: "auditor-key".to_owned(),
+                principal: "auditor".to_owned(),
+                vendors: Vec::new(),
+                role: GtsVisibility::Internal,
+            }],
+            jwt: None,
+        };
+        GtsGrpcService::new(Arc::new(Mutex::new(ops))).with_auth(Arc::new(auth))
+    }
+
+    /// A request asking for `role` with the auditor's key.
+    fn as_role<T>(message: T, role: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        let metadata = request.metadata_mut();
+        metadata.insert(ROLE_HEADER, role.parse().unwrap());
+        metadata.insert(API_KEY_HEADER, "auditor-key".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_service_mirrors_rest_operations() {
+        let service = service();
+        let schema = service
+            .get_schema(Request::new(GetSchemaRequest {
+                gts_id: SCHEMA.to_owned(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let content: serde_json::Value = serde_json::from_str(&schema.schema_json).unwrap();
+        assert_eq!(content["type"], "object");
+
+        let hidden = GetSchemaRequest {
+            gts_id: HIDDEN.to_owned(),
+        };
+        let status = service
+            .get_schema(Request::new(hidden.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        assert!(
+            service
+                .get_schema(as_role(hidden.clone(), "internal"))
+                .await
+                .is_ok()
+        );
+        // The role comes from the credentials, not the caller's say-so
+        let mut unauthenticated = Request::new(hidden);
+        unauthenticated
+            .metadata_mut()
+            .insert(ROLE_HEADER, "internal".parse().unwrap());
+        let status = service.get_schema(unauthenticated).await.unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        let validated = service
+            .validate(Request::new(ValidateRequest {
+                gts_id: SCHEMA.to_owned(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(validated.ok, "{}", validated.error);
+        assert_eq!(validated.entity_type, "schema");
+
+        let query = |expr: &str| QueryRequest {
+            expr: expr.to_owned(),
+            limit: 0,
+        };
+        let found = service
+            .query(Request::new(query("gts.x.test.grpc.*")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(found.count, 1);
+        let found = service
+            .query(as_role(query("gts.x.test.grpc.*"), "internal"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(found.count, 2);
+        let status = service
+            .query(Request::new(query("gts.x.test.grpc")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let status = service
+            .query(as_role(query("gts.x.test.grpc.*"), "root"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let cast = service
+            .cast(Request::new(CastRequest {
+                instance_id: "gts.x.test.grpc.item.v1~x.test._.missing.v1".to_owned(),
+                to_schema_id: "gts.x.test.grpc.item.v1.1~".to_owned(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!cast.error.is_empty());
+        assert!(cast.casted_entity_json.is_empty());
+    }
+}
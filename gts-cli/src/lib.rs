@@ -1,10 +1,34 @@
 // Re-export the logging module for use in integration tests
 pub mod logging;
+pub mod metrics;
 
 // Other modules needed by CLI
+pub mod auth;
+pub mod bench;
+pub mod completions;
+pub mod doctor;
 pub mod gen_schemas;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod output;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod progress;
+pub mod repl;
 pub mod server;
+pub mod server_config;
+#[cfg(feature = "sql")]
+pub mod sql;
+pub mod tenants;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod watch;
+pub mod webhooks;
 
 // Re-export CLI types and functions for testing
 pub mod cli;
-pub use cli::{Cli, Commands, run_with_cli};
+pub use cli::{
+    AuditCommand, BenchCommand, BundleCommand, Cli, CodegenCommand, Commands, ExportCommand,
+    GraphFormat, ImportCommand, LifecycleCommand, ReportCommand, ReportFormat, run_with_cli,
+};
+pub use progress::OutputFormat;
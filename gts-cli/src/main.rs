@@ -10,13 +10,34 @@
     clippy::expect_used
 )]
 
+mod auth;
+mod bench;
 mod cli;
+mod completions;
+mod doctor;
 mod gen_schemas;
+#[cfg(feature = "grpc")]
+mod grpc;
 mod logging;
+mod metrics;
+mod output;
+#[cfg(feature = "parquet")]
+mod parquet_export;
+mod progress;
+mod repl;
 mod server;
+mod server_config;
+#[cfg(feature = "sql")]
+mod sql;
+mod tenants;
+#[cfg(feature = "tui")]
+mod tui;
+mod watch;
+mod webhooks;
 
 #[tokio::main]
 async fn main() {
+    completions::complete_from_env();
     if let Err(e) = cli::run().await {
         eprintln!("Error: {e}");
         std::process::exit(1);
@@ -0,0 +1,209 @@
+//! Prometheus metrics of the server, served at `GET /metrics`.
+//!
+//! [`ServerMetrics`] counts the requests of each route by method and status
+//! and keeps a latency histogram per route.  Installed as the store's
+//! [`GtsMetricsSink`], it also counts failed validations by schema ID.  The
+//! rendered text ends with the store's own [`gts::GtsStoreMetrics`].
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use gts::GtsMetricsSink;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Requests of one route and method.
+#[derive(Default)]
+struct RouteStats {
+    by_status: BTreeMap<u16, u64>,
+    /// Requests per latency bucket, not cumulative; the last counts those
+    /// slower than every bound.
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    seconds: f64,
+}
+
+/// Request and validation counters of one store's routes.
+#[derive(Default)]
+pub struct ServerMetrics {
+    /// By route template and method.
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+    /// Failed validations by schema ID.
+    failures: Mutex<BTreeMap<String, u64>>,
+}
+
+impl ServerMetrics {
+    /// Counts a request to `route` that answered `status` after `elapsed`.
+    pub fn record_request(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        let Ok(mut routes) = self.routes.lock() else {
+            return;
+        };
+        let route_stats = routes
+            .entry((route.to_owned(), method.to_owned()))
+            .or_default();
+        *route_stats.by_status.entry(status).or_default() += 1;
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        route_stats.buckets[bucket] += 1;
+        route_stats.seconds += seconds;
+    }
+
+    /// The counters in the Prometheus text exposition format, with the
+    /// validation failures of the schemas `shown` accepts.
+    #[must_use]
+    pub fn to_prometheus(&self, shown: impl Fn(&str) -> bool) -> String {
+        let mut out = String::new();
+        if let Ok(routes) = self.routes.lock() {
+            header(
+                &mut out,
+                "http_requests_total",
+                "counter",
+                "HTTP requests by route, method and status.",
+            );
+            for ((route, method), stats) in routes.iter() {
+                for (status, count) in &stats.by_status {
+                    let _ = writeln!(
+                        out,
+                        "gts_http_requests_total{{route=\"{}\",method=\"{method}\",status=\"{status}\"}} {count}",
+                        escape(route)
+                    );
+                }
+            }
+            header(
+                &mut out,
+                "http_request_duration_seconds",
+                "histogram",
+                "Time to answer HTTP requests by route and method.",
+            );
+            for ((route, method), stats) in routes.iter() {
+                let labels = format!("route=\"{}\",method=\"{method}\"", escape(route));
+                let mut cumulative = 0;
+                for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                    cumulative += count;
+                    let _ = writeln!(
+                        out,
+                        "gts_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {cumulative}"
+                    );
+                }
+                let total: u64 = stats.buckets.iter().sum();
+                let _ = writeln!(
+                    out,
+                    "gts_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {total}"
+                );
+                let _ = writeln!(
+                    out,
+                    "gts_http_request_duration_seconds_sum{{{labels}}} {}",
+                    stats.seconds
+                );
+                let _ = writeln!(
+                    out,
+                    "gts_http_request_duration_seconds_count{{{labels}}} {total}"
+                );
+            }
+        }
+        if let Ok(failures) = self.failures.lock() {
+            header(
+                &mut out,
+                "validation_failures_total",
+                "counter",
+                "Failed validations by schema ID.",
+            );
+            for (schema_id, count) in failures.iter().filter(|(id, _)| shown(id)) {
+                let _ = writeln!(
+                    out,
+                    "gts_validation_failures_total{{schema_id=\"{}\"}} {count}",
+                    escape(schema_id)
+                );
+            }
+        }
+        out
+    }
+}
+
+impl GtsMetricsSink for ServerMetrics {
+    fn on_validation(&self, schema_id: &str, ok: bool, _elapsed: Duration) {
+        if !ok && let Ok(mut failures) = self.failures.lock() {
+            *failures.entry(schema_id.to_owned()).or_default() += 1;
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP gts_{name} {help}");
+    let _ = writeln!(out, "# TYPE gts_{name} {kind}");
+}
+
+/// `value` escaped for a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Middleware recording each request of a matched route in `metrics`.
+pub async fn track_requests(metrics: &ServerMetrics, request: Request, next: Next) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map_or_else(
+        || request.uri().path().to_owned(),
+        |p| p.as_str().to_owned(),
+    );
+    let method = request.method().to_string();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.record_request(
+        &route,
+        &method,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_counters_histograms_and_failures() {
+        let metrics = ServerMetrics::default();
+        let route = "/schemas/{gts_id}";
+        metrics.record_request(route, "GET", 200, Duration::from_millis(3));
+        metrics.record_request(route, "GET", 404, Duration::from_secs(30));
+        metrics.on_validation("gts.x.test.metrics.item.v1~", false, Duration::ZERO);
+        metrics.on_validation("gts.x.test.metrics.item.v1~", true, Duration::ZERO);
+
+        metrics.on_validation("gts.x.test.metrics.secret.v1~", false, Duration::ZERO);
+
+        let text = metrics.to_prometheus(|id| !id.contains("secret"));
+        let labels = "route=\"/schemas/{gts_id}\",method=\"GET\"";
+        for line in [
+            format!("gts_http_requests_total{{{labels},status=\"404\"}} 1"),
+            format!("gts_http_request_duration_seconds_bucket{{{labels},le=\"0.001\"}} 0"),
+            format!("gts_http_request_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1"),
+            format!("gts_http_request_duration_seconds_bucket{{{labels},le=\"10\"}} 1"),
+            format!("gts_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 2"),
+            format!("gts_http_request_duration_seconds_count{{{labels}}} 2"),
+            "gts_validation_failures_total{schema_id=\"gts.x.test.metrics.item.v1~\"} 1".to_owned(),
+            "# TYPE gts_http_request_duration_seconds histogram".to_owned(),
+        ] {
+            assert!(
+                text.contains(&format!("{line}\n")),
+                "missing {line} in\n{text}"
+            );
+        }
+        assert!(!text.contains("secret"), "{text}");
+        assert_eq!(escape("a\"b\\"), "a\\\"b\\\\");
+    }
+}
@@ -0,0 +1,263 @@
+//! Rendering of command results in the `--output` format.
+//!
+//! `text` prints results as pretty JSON, as the CLI always has.  `json` and
+//! `yaml` wrap them in a versioned [`Envelope`] so scripts can rely on its
+//! shape: `{"version": 1, "ok": true, "result": ...}`.  A failed command
+//! prints one envelope with `"ok": false` and the `"error"`, next to the
+//! `"result"` it got as far as.  `ok` says whether the command succeeded,
+//! as its exit status does; results keep their own `ok` fields.  `table`
+//! lays out the result's fields, and each list of records in it, as
+//! aligned columns.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use serde_saphyr::{FlowMap, FlowSeq};
+use std::fmt::{self, Write as _};
+
+use crate::progress::OutputFormat;
+
+/// Version of the [`Envelope`] layout; bumped on incompatible changes.
+pub const OUTPUT_VERSION: u32 = 1;
+
+/// The document printed for `--output json` and `--output yaml`.
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a, T: Serialize> {
+    pub version: u32,
+    /// Whether the command succeeded.
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<&'a T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<&'a str>,
+}
+
+fn serialize<T: Serialize>(value: &T, output: OutputFormat) -> Result<String> {
+    Ok(match output {
+        OutputFormat::Yaml => serde_saphyr::to_string(&YamlValue(&serde_json::to_value(value)?))?,
+        _ => serde_json::to_string(value)? + "\n",
+    })
+}
+
+/// A JSON value serialized for YAML with empty lists and maps in flow
+/// style, since a block-style empty collection reads back as null.
+struct YamlValue<'a>(&'a Value);
+
+impl Serialize for YamlValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Array(items) if items.is_empty() => FlowSeq(items).serialize(s),
+            Value::Object(map) if map.is_empty() => FlowMap(map).serialize(s),
+            Value::Array(items) => s.collect_seq(items.iter().map(YamlValue)),
+            Value::Object(map) => s.collect_map(map.iter().map(|(k, v)| (k, YamlValue(v)))),
+            other => other.serialize(s),
+        }
+    }
+}
+
+/// Renders a command result in `output` format; `outcome` is the error
+/// message of a command that failed with this result.
+///
+/// # Errors
+/// Returns an error if the result cannot be serialized.
+pub fn render<T: Serialize>(
+    value: &T,
+    outcome: Result<(), &str>,
+    output: OutputFormat,
+) -> Result<String> {
+    match output {
+        OutputFormat::Text => Ok(serde_json::to_string_pretty(value)? + "\n"),
+        OutputFormat::Table => Ok(table(&serde_json::to_value(value)?)),
+        OutputFormat::Json | OutputFormat::Yaml => serialize(
+            &Envelope {
+                version: OUTPUT_VERSION,
+                ok: outcome.is_ok(),
+                result: Some(value),
+                error: outcome.err(),
+            },
+            output,
+        ),
+    }
+}
+
+/// The error of a command whose printed result already reports it, so
+/// that no second envelope is printed for it.
+#[derive(Debug)]
+pub struct Reported(pub String);
+
+impl fmt::Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Reported {}
+
+/// The envelope reporting a failed command, for the formats that have one.
+#[must_use]
+pub fn render_error(message: &str, output: OutputFormat) -> Option<String> {
+    if !matches!(output, OutputFormat::Json | OutputFormat::Yaml) {
+        return None;
+    }
+    serialize(
+        &Envelope::<Value> {
+            version: OUTPUT_VERSION,
+            ok: false,
+            result: None,
+            error: Some(message),
+        },
+        output,
+    )
+    .ok()
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.replace('\n', " "),
+        other => other.to_string(),
+    }
+}
+
+/// Whether `value` is a non-empty list of records.
+fn is_records(value: &Value) -> bool {
+    value
+        .as_array()
+        .is_some_and(|rows| !rows.is_empty() && rows.iter().all(Value::is_object))
+}
+
+/// Aligned columns under upper-cased `headers`.
+fn grid(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| h.to_uppercase()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(value, &width)| format!("{value:width$}"))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn records_table(rows: &[Value]) -> String {
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows.iter().filter_map(Value::as_object) {
+        for key in row.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| row.get(column).map(cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    grid(&columns, &cells)
+}
+
+/// `value` as tables: its scalar fields as FIELD/VALUE rows, then one
+/// table per list of records.
+fn table(value: &Value) -> String {
+    match value {
+        Value::Array(rows) if is_records(value) => records_table(rows),
+        Value::Object(map) => {
+            let fields: Vec<Vec<String>> = map
+                .iter()
+                .filter(|(_, v)| !is_records(v))
+                .map(|(k, v)| vec![k.clone(), cell(v)])
+                .collect();
+            let mut out = grid(&["field".to_owned(), "value".to_owned()], &fields);
+            for (key, rows) in map.iter().filter(|(_, v)| is_records(v)) {
+                let _ = write!(out, "\n{key}:\n");
+                out.push_str(&records_table(rows.as_array().map_or(&[], Vec::as_slice)));
+            }
+            out
+        }
+        other => cell(other) + "\n",
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result() -> Value {
+        json!({
+            "ok": false,
+            "count": 2,
+            "results": [
+                {"id": "gts.x.test.out.a.v1~", "valid": true},
+                {"id": "gts.x.test.out.b.v1~", "error": "missing n"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_json_and_yaml_envelopes() {
+        // A result's own `ok` does not decide the command's
+        let json: Value =
+            serde_json::from_str(&render(&result(), Ok(()), OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["version"], OUTPUT_VERSION);
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["result"]["ok"], false);
+        assert_eq!(json["result"]["count"], 2);
+        assert!(json.get("error").is_none());
+
+        let failed = render(&result(), Err("1 invalid"), OutputFormat::Json).unwrap();
+        assert_eq!(failed.lines().count(), 1);
+        let failed: Value = serde_json::from_str(&failed).unwrap();
+        assert_eq!(failed["ok"], false);
+        assert_eq!(failed["error"], "1 invalid");
+        assert_eq!(failed["result"]["count"], 2);
+
+        let yaml = render(&json!([1, 2]), Ok(()), OutputFormat::Yaml).unwrap();
+        let parsed: Value = serde_saphyr::from_str(&yaml).unwrap();
+        assert_eq!(parsed, json!({"version": 1, "ok": true, "result": [1, 2]}));
+
+        let empty = json!({"count": 0, "results": [], "labels": {}});
+        let yaml = render(&empty, Ok(()), OutputFormat::Yaml).unwrap();
+        let parsed: Value = serde_saphyr::from_str(&yaml).unwrap();
+        assert_eq!(parsed["result"], empty);
+
+        let error: Value =
+            serde_json::from_str(&render_error("boom", OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(error, json!({"version": 1, "ok": false, "error": "boom"}));
+        assert!(render_error("boom", OutputFormat::Text).is_none());
+    }
+
+    #[test]
+    fn test_table_lists_fields_and_records() {
+        let text = render(&result(), Ok(()), OutputFormat::Table).unwrap();
+        assert_eq!(
+            text,
+            "FIELD  VALUE\n\
+             ok     false\n\
+             count  2\n\
+             \n\
+             results:\n\
+             ID                    VALID  ERROR\n\
+             gts.x.test.out.a.v1~  true\n\
+             gts.x.test.out.b.v1~         missing n\n"
+        );
+        assert_eq!(
+            render(&json!("plain"), Ok(()), OutputFormat::Table).unwrap(),
+            "plain\n"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Parquet export of store instances for analytics (`parquet` feature).
+//!
+//! Instances matching a pattern are written to one Parquet file per schema,
+//! in Hive-style partitions (`<out>/schema_id=<id>/part-0.parquet`) that
+//! `DuckDB` and Spark read directly.  Every file starts with the `id`,
+//! `schema_id`, `file_modified` and `exported_at` columns, followed by the
+//! instance content flattened into one column per leaf field
+//! (`payload.order_id`).  Column types are inferred from the values of each
+//! schema's instances; arrays and fields with mixed types become JSON text.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use gts::{GtsEntity, GtsID, GtsStore, GtsWildcard};
+use parquet::basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::MilliSeconds;
+use parquet::schema::types::Type;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Columns every exported file starts with.
+const FIXED_COLUMNS: [&str; 4] = ["id", "schema_id", "file_modified", "exported_at"];
+
+/// One written Parquet file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParquetFile {
+    pub schema_id: String,
+    pub path: String,
+    pub rows: usize,
+    pub columns: Vec<String>,
+}
+
+/// Result of [`export_parquet`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParquetExport {
+    pub files: Vec<ParquetFile>,
+}
+
+/// Physical type chosen for a flattened content column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Bool,
+    Int,
+    Double,
+    Text,
+}
+
+impl ColumnKind {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(ColumnKind::Bool),
+            Value::Number(n) if n.is_i64() => Some(ColumnKind::Int),
+            Value::Number(_) => Some(ColumnKind::Double),
+            _ => Some(ColumnKind::Text),
+        }
+    }
+
+    /// The kind that holds values of both kinds.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnKind::Int | ColumnKind::Double, ColumnKind::Int | ColumnKind::Double) => {
+                ColumnKind::Double
+            }
+            _ => ColumnKind::Text,
+        }
+    }
+}
+
+/// One instance, ready to be written.
+struct Row {
+    id: String,
+    file_modified: Option<i64>,
+    fields: BTreeMap<String, Value>,
+}
+
+/// Flattens nested objects into dotted paths; other values are leaves.
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&path, child, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_owned(), value.clone());
+        }
+    }
+}
+
+fn millis(time: SystemTime) -> Option<i64> {
+    let elapsed = time.duration_since(UNIX_EPOCH).ok()?;
+    i64::try_from(elapsed.as_millis()).ok()
+}
+
+fn file_modified(entity: &GtsEntity) -> Option<i64> {
+    let file = entity.file.as_ref()?;
+    millis(fs::metadata(&file.path).ok()?.modified().ok()?)
+}
+
+/// Whether `id` (an instance or schema ID) matches `pattern`.
+fn matches(id: &str, pattern: &GtsWildcard) -> bool {
+    GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(pattern))
+}
+
+/// Instances matching `pattern` by their own ID or their schema ID, grouped
+/// by schema ID.
+fn collect_rows(store: &GtsStore, pattern: &GtsWildcard) -> BTreeMap<String, Vec<Row>> {
+    let mut partitions: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+    for (id, entity) in store.items() {
+        if entity.is_schema {
+            continue;
+        }
+        let Some(schema_id) = &entity.schema_id else {
+            continue;
+        };
+        if !matches(id, pattern) && !matches(schema_id, pattern) {
+            continue;
+        }
+        let mut fields = BTreeMap::new();
+        flatten("", &entity.content, &mut fields);
+        fields.retain(|name, _| !FIXED_COLUMNS.contains(&name.as_str()));
+        partitions.entry(schema_id.clone()).or_default().push(Row {
+            id: id.clone(),
+            file_modified: file_modified(entity),
+            fields,
+        });
+    }
+    for rows in partitions.values_mut() {
+        rows.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+    partitions
+}
+
+fn column_kinds(rows: &[Row]) -> BTreeMap<String, ColumnKind> {
+    let names: BTreeSet<&String> = rows.iter().flat_map(|r| r.fields.keys()).collect();
+    names
+        .into_iter()
+        .map(|name| {
+            let kind = rows
+                .iter()
+                .filter_map(|r| r.fields.get(name).and_then(ColumnKind::of))
+                .reduce(ColumnKind::merge)
+                .unwrap_or(ColumnKind::Text);
+            (name.clone(), kind)
+        })
+        .collect()
+}
+
+fn primitive(
+    name: &str,
+    physical: PhysicalType,
+    repetition: Repetition,
+    logical: Option<LogicalType>,
+) -> Result<Arc<Type>> {
+    Ok(Arc::new(
+        Type::primitive_type_builder(name, physical)
+            .with_repetition(repetition)
+            .with_logical_type(logical)
+            .build()?,
+    ))
+}
+
+fn file_schema(kinds: &BTreeMap<String, ColumnKind>) -> Result<Arc<Type>> {
+    let timestamp = || {
+        Some(LogicalType::Timestamp {
+            is_adjusted_to_u_t_c: true,
+            unit: TimeUnit::MILLIS(MilliSeconds {}),
+        })
+    };
+    let mut fields = vec![
+        primitive(
+            "id",
+            PhysicalType::BYTE_ARRAY,
+            Repetition::REQUIRED,
+            Some(LogicalType::String),
+        )?,
+        primitive(
+            "schema_id",
+            PhysicalType::BYTE_ARRAY,
+            Repetition::REQUIRED,
+            Some(LogicalType::String),
+        )?,
+        primitive(
+            "file_modified",
+            PhysicalType::INT64,
+            Repetition::OPTIONAL,
+            timestamp(),
+        )?,
+        primitive(
+            "exported_at",
+            PhysicalType::INT64,
+            Repetition::REQUIRED,
+            timestamp(),
+        )?,
+    ];
+    for (name, kind) in kinds {
+        fields.push(match kind {
+            ColumnKind::Bool => primitive(name, PhysicalType::BOOLEAN, Repetition::OPTIONAL, None)?,
+            ColumnKind::Int => primitive(name, PhysicalType::INT64, Repetition::OPTIONAL, None)?,
+            ColumnKind::Double => {
+                primitive(name, PhysicalType::DOUBLE, Repetition::OPTIONAL, None)?
+            }
+            ColumnKind::Text => primitive(
+                name,
+                PhysicalType::BYTE_ARRAY,
+                Repetition::OPTIONAL,
+                Some(LogicalType::String),
+            )?,
+        });
+    }
+    Ok(Arc::new(
+        Type::group_type_builder("instance")
+            .with_fields(fields)
+            .build()?,
+    ))
+}
+
+fn text(value: &Value) -> ByteArray {
+    match value {
+        Value::String(s) => ByteArray::from(s.as_bytes().to_vec()),
+        other => ByteArray::from(other.to_string().into_bytes()),
+    }
+}
+
+/// Present values of one optional column and the definition levels
+/// marking which rows have them.
+fn optional<T>(
+    rows: &[Row],
+    name: &str,
+    convert: impl Fn(&Value) -> Option<T>,
+) -> (Vec<T>, Vec<i16>) {
+    let mut values = Vec::new();
+    let mut levels = Vec::with_capacity(rows.len());
+    for row in rows {
+        match row.fields.get(name).and_then(&convert) {
+            Some(v) => {
+                values.push(v);
+                levels.push(1);
+            }
+            None => levels.push(0),
+        }
+    }
+    (values, levels)
+}
+
+fn write_file(path: &Path, schema_id: &str, rows: &[Row], exported_at: i64) -> Result<Vec<String>> {
+    let kinds = column_kinds(rows);
+    let file = fs::File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut writer = SerializedFileWriter::new(
+        file,
+        file_schema(&kinds)?,
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut group = writer.next_row_group()?;
+
+    let ids: Vec<ByteArray> = rows
+        .iter()
+        .map(|r| ByteArray::from(r.id.as_str()))
+        .collect();
+    let schema_ids = vec![ByteArray::from(schema_id); rows.len()];
+    let modified_levels: Vec<i16> = rows
+        .iter()
+        .map(|r| i16::from(r.file_modified.is_some()))
+        .collect();
+    let modified: Vec<i64> = rows.iter().filter_map(|r| r.file_modified).collect();
+    let exported = vec![exported_at; rows.len()];
+
+    for data in [&ids, &schema_ids] {
+        if let Some(mut column) = group.next_column()? {
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(data, None, None)?;
+            column.close()?;
+        }
+    }
+    if let Some(mut column) = group.next_column()? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&modified, Some(&modified_levels), None)?;
+        column.close()?;
+    }
+    if let Some(mut column) = group.next_column()? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&exported, None, None)?;
+        column.close()?;
+    }
+
+    for (name, kind) in &kinds {
+        let Some(mut column) = group.next_column()? else {
+            break;
+        };
+        match kind {
+            ColumnKind::Bool => {
+                let (values, levels) = optional(rows, name, Value::as_bool);
+                column
+                    .typed::<BoolType>()
+                    .write_batch(&values, Some(&levels), None)?;
+            }
+            ColumnKind::Int => {
+                let (values, levels) = optional(rows, name, Value::as_i64);
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(&values, Some(&levels), None)?;
+            }
+            ColumnKind::Double => {
+                let (values, levels) = optional(rows, name, Value::as_f64);
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&values, Some(&levels), None)?;
+            }
+            ColumnKind::Text => {
+                let (values, levels) = optional(rows, name, |v| (!v.is_null()).then(|| text(v)));
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, Some(&levels), None)?;
+            }
+        }
+        column.close()?;
+    }
+    group.close()?;
+    writer.close()?;
+
+    Ok(FIXED_COLUMNS
+        .iter()
+        .map(|c| (*c).to_owned())
+        .chain(kinds.into_keys())
+        .collect())
+}
+
+/// Writes the instances matching `pattern` (by their own ID or their
+/// schema ID) under `out`, one Parquet file per schema.
+///
+/// # Errors
+/// Returns an error if `pattern` is not a valid GTS wildcard or a file
+/// cannot be written.
+pub fn export_parquet(store: &GtsStore, pattern: &str, out: &Path) -> Result<ParquetExport> {
+    let pattern = GtsWildcard::new(pattern)?;
+    let exported_at = millis(SystemTime::now()).unwrap_or_default();
+    let mut export = ParquetExport::default();
+    for (schema_id, rows) in collect_rows(store, &pattern) {
+        let dir: PathBuf = out.join(format!("schema_id={schema_id}"));
+        fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+        let path = dir.join("part-0.parquet");
+        let columns = write_file(&path, &schema_id, &rows, exported_at)?;
+        export.files.push(ParquetFile {
+            schema_id,
+            path: path.display().to_string(),
+            rows: rows.len(),
+            columns,
+        });
+    }
+    Ok(export)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use gts::GtsConfig;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+    use serde_json::json;
+
+    const ORDER: &str = "gts.x.test.pq.order.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ORDER,
+                &json!({
+                    "$id": format!("gts://{ORDER}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        for content in [
+            json!({"id": format!("{ORDER}x.test._.o1.v1"), "total": 10, "paid": true,
+                   "customer": {"name": "Ann"}, "tags": ["a"]}),
+            json!({"id": format!("{ORDER}x.test._.o2.v1"), "total": 2.5,
+                   "customer": {"name": "Bob", "vip": true}}),
+        ] {
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &content,
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(ORDER.to_owned()),
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_export_parquet_partitions_and_flattens() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let export = export_parquet(&store(), "gts.x.test.pq.*", dir.path()).unwrap();
+        assert_eq!(export.files.len(), 1);
+        let file = &export.files[0];
+        assert_eq!(file.rows, 2);
+        assert!(file.path.contains(&format!("schema_id={ORDER}")));
+        assert_eq!(
+            file.columns,
+            vec![
+                "id",
+                "schema_id",
+                "file_modified",
+                "exported_at",
+                "customer.name",
+                "customer.vip",
+                "paid",
+                "tags",
+                "total"
+            ]
+        );
+
+        let reader = SerializedFileReader::new(fs::File::open(&file.path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let first: BTreeMap<String, Field> = rows[0]
+            .get_column_iter()
+            .map(|(name, field)| (name.clone(), field.clone()))
+            .collect();
+        assert_eq!(first["customer.name"], Field::Str("Ann".to_owned()));
+        assert_eq!(first["customer.vip"], Field::Null);
+        assert_eq!(first["total"], Field::Double(10.0));
+        assert_eq!(first["tags"], Field::Str("[\"a\"]".to_owned()));
+        assert_eq!(first["schema_id"], Field::Str(ORDER.to_owned()));
+
+        let none = export_parquet(&store(), "gts.x.other.*", dir.path()).unwrap();
+        assert!(none.files.is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! Progress bars and end-of-run summaries for long-running commands.
+//!
+//! Bars are drawn on stderr, and only when it is a terminal and the output
+//! format is for humans, so piped and scripted runs stay clean.  Each
+//! command ends with a [`Summary`]: a short block on stderr for humans.
+//! With `--output json` (`yaml`) it is nested in the command's result as
+//! `summary`, so the result stays one document, or is one JSON line (a YAML
+//! document) on stderr for commands without a result.
+
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Format of command results and summaries; see [`crate::output`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Table,
+}
+
+const BAR_TEMPLATE: &str =
+    "{msg} [{elapsed_precise}] {wide_bar} {pos}/{len} ({per_sec}, ETA {eta})";
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} [{elapsed_precise}] {pos} ({per_sec})";
+
+/// A progress bar for one operation, hidden when nobody is watching.
+pub struct Progress {
+    operation: &'static str,
+    bar: ProgressBar,
+    started: Instant,
+}
+
+impl Progress {
+    /// A bar counting up to `total` items, or a spinner if the total is not
+    /// known in advance.
+    #[must_use]
+    pub fn new(operation: &'static str, total: Option<usize>, output: OutputFormat) -> Self {
+        let visible = matches!(output, OutputFormat::Text | OutputFormat::Table)
+            && atty::is(atty::Stream::Stderr);
+        let bar = match (visible, total) {
+            (false, _) => ProgressBar::hidden(),
+            (true, Some(total)) => ProgressBar::new(total as u64).with_style(
+                ProgressStyle::with_template(BAR_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            ),
+            (true, None) => {
+                let bar = ProgressBar::new_spinner().with_style(
+                    ProgressStyle::with_template(SPINNER_TEMPLATE)
+                        .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+                );
+                bar.enable_steady_tick(Duration::from_millis(100));
+                bar
+            }
+        };
+        bar.set_message(operation);
+        Self {
+            operation,
+            bar,
+            started: Instant::now(),
+        }
+    }
+
+    /// Records that `done` of `total` items are processed.
+    pub fn update(&self, done: usize, total: usize) {
+        self.bar.set_length(total as u64);
+        self.bar.set_position(done as u64);
+    }
+
+    /// Records one more processed item.
+    pub fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    /// Runs `f`, which prints, with the bar out of the way.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.bar.suspend(f)
+    }
+
+    /// Removes the bar without a summary.
+    pub fn clear(self) {
+        self.bar.finish_and_clear();
+    }
+
+    /// Removes the bar and returns the run's summary.
+    #[must_use]
+    pub fn finish(self, passed: usize, failed: usize, skipped: usize) -> Summary {
+        self.bar.finish_and_clear();
+        Summary {
+            operation: self.operation,
+            processed: passed + failed + skipped,
+            passed,
+            failed,
+            skipped,
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        }
+    }
+}
+
+/// Counts for a finished run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub operation: &'static str,
+    pub processed: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub elapsed_secs: f64,
+}
+
+impl Summary {
+    /// Renders the summary in `output` format.
+    ///
+    /// # Errors
+    /// Returns an error if the summary cannot be serialized.
+    pub fn render(&self, output: OutputFormat) -> anyhow::Result<String> {
+        let document = serde_json::json!({ "summary": self });
+        match output {
+            OutputFormat::Json => Ok(serde_json::to_string(&document)? + "\n"),
+            OutputFormat::Yaml => Ok(serde_saphyr::to_string(&document)?),
+            OutputFormat::Text | OutputFormat::Table => Ok(format!(
+                "\n{} summary:\n  processed: {}\n  passed:    {}\n  failed:    {}\n  skipped:   {}\n  elapsed:   {:.2}s\n",
+                self.operation,
+                self.processed,
+                self.passed,
+                self.failed,
+                self.skipped,
+                self.elapsed_secs
+            )),
+        }
+    }
+
+    /// Writes the summary to stderr.
+    ///
+    /// # Errors
+    /// Returns an error if the summary cannot be serialized.
+    pub fn emit(&self, output: OutputFormat) -> anyhow::Result<()> {
+        eprint!("{}", self.render(output)?);
+        Ok(())
+    }
+
+    /// Reports the summary with the command's `result`: as its `summary`
+    /// field for `--output json` and `yaml`, else on stderr.
+    ///
+    /// # Errors
+    /// Returns an error if the result or summary cannot be serialized.
+    pub fn attach<T: Serialize>(&self, result: &T, output: OutputFormat) -> anyhow::Result<Value> {
+        let mut result = serde_json::to_value(result)?;
+        match (output, &mut result) {
+            (OutputFormat::Json | OutputFormat::Yaml, Value::Object(fields)) => {
+                fields.insert("summary".to_owned(), serde_json::to_value(self)?);
+            }
+            _ => self.emit(output)?,
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_counts_and_json() {
+        let progress = Progress::new("validate", Some(5), OutputFormat::Json);
+        progress.update(5, 5);
+        let summary = progress.finish(3, 1, 1);
+        assert_eq!(summary.processed, 5);
+
+        let line = summary.render(OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["summary"]["operation"], "validate");
+        assert_eq!(value["summary"]["failed"], 1);
+        assert!(
+            summary
+                .render(OutputFormat::Text)
+                .unwrap()
+                .contains("skipped:   1")
+        );
+        assert!(
+            summary
+                .render(OutputFormat::Yaml)
+                .unwrap()
+                .contains("operation: validate")
+        );
+
+        let result = serde_json::json!({"ok": true, "count": 5});
+        let attached = summary.attach(&result, OutputFormat::Json).unwrap();
+        assert_eq!(attached["count"], 5);
+        assert_eq!(attached["summary"]["passed"], 3);
+        let attached = summary.attach(&result, OutputFormat::Text).unwrap();
+        assert_eq!(attached, result);
+    }
+}
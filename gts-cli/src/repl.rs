@@ -0,0 +1,267 @@
+//! `gts repl`: an interactive prompt over the loaded store.
+//!
+//! Each line is one of the commands of [`ReplCommand`], split like a shell
+//! command line so quoted arguments may contain spaces.  Results are printed
+//! as pretty JSON; `Tab` completes command names and the IDs and aliases of
+//! registered entities.
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use gts::GtsOps;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+#[derive(Parser)]
+#[command(no_binary_name = true, disable_version_flag = true)]
+struct ReplLine {
+    #[command(subcommand)]
+    command: ReplCommand,
+}
+
+#[derive(Subcommand)]
+enum ReplCommand {
+    /// Query entities using an expression
+    Query {
+        expr: String,
+        #[arg(long, default_value = "100")]
+        limit: usize,
+    },
+    /// Show an entity
+    Get { gts_id: String },
+    /// Validate an entity (instance or schema)
+    Validate { gts_id: String },
+    /// Cast an instance or schema to a target schema
+    Cast {
+        from_id: String,
+        to_schema_id: String,
+    },
+    /// Show the schemas and references an entity depends on
+    Graph { gts_id: String },
+    /// Leave the REPL
+    #[command(alias = "quit")]
+    Exit,
+}
+
+const COMMANDS: &[&str] = &[
+    "query", "get", "validate", "cast", "graph", "exit", "quit", "help",
+];
+
+/// What evaluating a line produced.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Output(String),
+    Exit,
+}
+
+/// Runs one REPL line against `ops`.  Usage errors, including `help`, come
+/// back as output so the session goes on.
+fn eval(ops: &mut GtsOps, line: &str) -> Result<Outcome> {
+    let Some(words) = shlex::split(line) else {
+        anyhow::bail!("unbalanced quotes");
+    };
+    if words.is_empty() {
+        return Ok(Outcome::Output(String::new()));
+    }
+    let command = match ReplLine::try_parse_from(words) {
+        Ok(parsed) => parsed.command,
+        Err(e) => return Ok(Outcome::Output(e.render().to_string())),
+    };
+    let value = match command {
+        ReplCommand::Query { expr, limit } => serde_json::to_value(ops.query(&expr, limit))?,
+        ReplCommand::Get { gts_id } => {
+            let id = ops.resolve_id(&gts_id)?;
+            serde_json::to_value(ops.get_entity(&id))?
+        }
+        ReplCommand::Validate { gts_id } => {
+            let id = ops.resolve_id(&gts_id)?;
+            serde_json::to_value(ops.validate_entity(&id))?
+        }
+        ReplCommand::Cast {
+            from_id,
+            to_schema_id,
+        } => {
+            let from = ops.resolve_id(&from_id)?;
+            let to = ops.resolve_id(&to_schema_id)?;
+            serde_json::to_value(ops.cast(&from, &to))?
+        }
+        ReplCommand::Graph { gts_id } => {
+            let id = ops.resolve_id(&gts_id)?;
+            serde_json::to_value(ops.schema_graph(&id))?
+        }
+        ReplCommand::Exit => return Ok(Outcome::Exit),
+    };
+    Ok(Outcome::Output(
+        serde_json::to_string_pretty(&value)? + "\n",
+    ))
+}
+
+/// Completes the word before the cursor: a command name first, then entity
+/// IDs.
+struct ReplHelper {
+    ids: Vec<String>,
+}
+
+impl ReplHelper {
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+        let start = before
+            .rfind(|c: char| c.is_whitespace() || c == '\'' || c == '"')
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let first = before[..start].trim().is_empty();
+        let matches = if first {
+            COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .map(|&c| c.to_owned())
+                .collect()
+        } else {
+            self.ids
+                .iter()
+                .filter(|id| id.starts_with(word))
+                .cloned()
+                .collect()
+        };
+        (start, matches)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Reads and runs commands until `exit` or end of input.
+///
+/// # Errors
+/// Returns an error if the terminal cannot be read.
+pub fn run(ops: &mut GtsOps) -> Result<()> {
+    let mut ids: Vec<String> = ops
+        .store
+        .items()
+        .map(|(id, _)| id.clone())
+        .chain(ops.cfg.aliases.keys().map(|alias| format!("@{alias}")))
+        .collect();
+    ids.sort();
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper { ids }));
+    println!("GTS REPL: query, get, validate, cast, graph; `help` for usage, `exit` to leave");
+    loop {
+        let line = match editor.readline("gts> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+        match eval(ops, &line) {
+            Ok(Outcome::Output(text)) => print!("{text}"),
+            Ok(Outcome::Exit) => break,
+            Err(e) => eprintln!("Error: {e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    const ITEM: &str = "gts.x.test.repl.item.v1~";
+
+    fn ops() -> GtsOps {
+        let mut ops = GtsOps::new(None, None, 0);
+        ops.add_schema(
+            ITEM.to_owned(),
+            &json!({
+                "$id": format!("gts://{ITEM}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "required": ["id", "n"],
+                "properties": {"id": {"type": "string"}, "n": {"type": "integer"}}
+            }),
+        );
+        ops.add_entity(
+            &json!({"id": format!("{ITEM}x.test._.a.v1"), "n": "x"}),
+            false,
+        );
+        ops
+    }
+
+    fn output(ops: &mut GtsOps, line: &str) -> Value {
+        match eval(ops, line).unwrap() {
+            Outcome::Output(text) => serde_json::from_str(&text).unwrap(),
+            Outcome::Exit => panic!("unexpected exit"),
+        }
+    }
+
+    #[test]
+    fn test_eval_commands() {
+        let mut ops = ops();
+        let instance = format!("{ITEM}x.test._.a.v1");
+
+        assert_eq!(output(&mut ops, &format!("get {ITEM}"))["is_schema"], true);
+        assert_eq!(
+            output(&mut ops, &format!("validate '{instance}'"))["ok"],
+            false
+        );
+        let query = output(&mut ops, "query 'gts.x.test.repl.*' --limit 5");
+        assert_eq!(query["count"], 2);
+        assert!(output(&mut ops, &format!("graph {instance}")).is_object());
+
+        let Outcome::Output(help) = eval(&mut ops, "help").unwrap() else {
+            panic!("expected output");
+        };
+        assert!(help.contains("validate"));
+        let Outcome::Output(usage) = eval(&mut ops, "cast only-one").unwrap() else {
+            panic!("expected output");
+        };
+        assert!(usage.contains("<TO_SCHEMA_ID>"));
+        assert!(eval(&mut ops, "get 'unbalanced").is_err());
+        assert_eq!(
+            eval(&mut ops, "  ").unwrap(),
+            Outcome::Output(String::new())
+        );
+        assert_eq!(eval(&mut ops, "quit").unwrap(), Outcome::Exit);
+    }
+
+    #[test]
+    fn test_completion_of_commands_and_ids() {
+        let helper = ReplHelper {
+            ids: vec![ITEM.to_owned(), format!("{ITEM}x.test._.a.v1")],
+        };
+        assert_eq!(helper.candidates("va", 2), (0, vec!["validate".to_owned()]));
+        let (start, ids) = helper.candidates("get gts.x.test.repl.item.v1~x", 29);
+        assert_eq!(start, 4);
+        assert_eq!(ids, [format!("{ITEM}x.test._.a.v1")]);
+        assert_eq!(helper.candidates("cast 'gts.x", 11).1.len(), 2);
+    }
+}
@@ -1,21 +1,82 @@
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    middleware,
-    response::IntoResponse,
+    extract::{FromRequestParts, Path, Query, Request, State},
+    http::{
+        HeaderMap, StatusCode, Uri,
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, WWW_AUTHENTICATE},
+        request::Parts,
+    },
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
     routing::{get, post},
 };
-use gts::GtsOps;
+use gts::{
+    GtsChangeEvent, GtsChangeKind, GtsChangeListener, GtsID, GtsOps, GtsVisibility, SortOrder,
+};
 use serde::Deserialize;
 use serde_json::{Value, json};
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::broadcast::{self, error::RecvError};
 
+use crate::auth::{
+    API_KEY_HEADER, AuthError, GtsAuthConfig, GtsWriteGrant, ROLE_HEADER, authenticate, caller_role,
+};
 use crate::logging::LoggingMiddleware;
+use crate::metrics::{ServerMetrics, track_requests};
 
 #[derive(Clone)]
 pub struct AppState {
     pub ops: Arc<Mutex<GtsOps>>,
+    /// Credentials required for writes; none by default.
+    pub auth: Arc<GtsAuthConfig>,
+    /// Store changes, streamed to `GET /events` subscribers.
+    pub changes: broadcast::Sender<GtsChangeEvent>,
+    /// Further stores, served under `/t/{name}` or with [`TENANT_HEADER`].
+    pub tenants: Arc<BTreeMap<String, AppState>>,
+    /// Request and validation counters, served at `GET /metrics`.
+    pub metrics: Arc<ServerMetrics>,
+    /// `Cache-Control` sent with schemas; `no-cache` if unset.
+    pub cache_control: Option<Arc<str>>,
+}
+
+/// Changes buffered per `/events` subscriber; one that falls further
+/// behind is told how many it missed.
+const EVENTS_BUFFER: usize = 1024;
+
+/// Forwards store changes to the `/events` subscribers.
+struct ChangeBroadcaster(broadcast::Sender<GtsChangeEvent>);
+
+impl GtsChangeListener for ChangeBroadcaster {
+    fn on_change(&self, event: &GtsChangeEvent) {
+        // Fails only while nobody is subscribed
+        let _ = self.0.send(event.clone());
+    }
+}
+
+impl AppState {
+    /// Serves `ops` with writes open, streaming its changes at `/events`
+    /// and counting its validations at `/metrics`.
+    #[must_use]
+    pub fn new(mut ops: GtsOps) -> Self {
+        let (changes, _) = broadcast::channel(EVENTS_BUFFER);
+        ops.store
+            .add_change_listener(Arc::new(ChangeBroadcaster(changes.clone())));
+        let metrics = Arc::new(ServerMetrics::default());
+        ops.store.set_metrics_sink(Some(metrics.clone()));
+        AppState {
+            ops: Arc::new(Mutex::new(ops)),
+            auth: Arc::default(),
+            changes,
+            tenants: Arc::default(),
+            metrics,
+            cache_control: None,
+        }
+    }
 }
 
 pub struct GtsHttpServer {
@@ -23,6 +84,13 @@ pub struct GtsHttpServer {
     host: String,
     port: u16,
     verbose: u8,
+    auth: GtsAuthConfig,
+    cache_control: Option<String>,
+    /// Further stores, served under `/t/{name}`, and their `Cache-Control`.
+    tenants: BTreeMap<String, (GtsOps, Option<String>)>,
+    /// Port of the gRPC service, if it is served.
+    #[cfg(feature = "grpc")]
+    grpc_port: Option<u16>,
 }
 
 impl GtsHttpServer {
@@ -33,9 +101,50 @@ impl GtsHttpServer {
             host,
             port,
             verbose,
+            auth: GtsAuthConfig::default(),
+            cache_control: None,
+            tenants: BTreeMap::new(),
+            #[cfg(feature = "grpc")]
+            grpc_port: None,
         }
     }
 
+    /// Requires `auth`'s credentials for writes.
+    #[must_use]
+    pub fn with_auth(mut self, auth: GtsAuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Sends `cache_control` with schemas instead of `no-cache`.
+    #[must_use]
+    pub fn with_cache_control(mut self, cache_control: Option<String>) -> Self {
+        self.cache_control = cache_control;
+        self
+    }
+
+    /// Also serves `ops` as the tenant `name`, at `/t/{name}/...` or with
+    /// [`TENANT_HEADER`], sending its own `cache_control` with schemas.
+    /// Tenants share the server's write credentials.
+    #[must_use]
+    pub fn with_tenant(
+        mut self,
+        name: impl Into<String>,
+        ops: GtsOps,
+        cache_control: Option<String>,
+    ) -> Self {
+        self.tenants.insert(name.into(), (ops, cache_control));
+        self
+    }
+
+    /// Also serves the gRPC service on `port` of the same host.
+    #[cfg(feature = "grpc")]
+    #[must_use]
+    pub fn with_grpc(mut self, port: u16) -> Self {
+        self.grpc_port = Some(port);
+        self
+    }
+
     /// Run the HTTP server
     ///
     /// # Errors
@@ -45,8 +154,43 @@ impl GtsHttpServer {
     /// - The server encounters an error while serving requests
     pub async fn run(self) -> anyhow::Result<()> {
         let verbose = self.verbose;
+        let auth = Arc::new(self.auth);
+        let tenants = self
+            .tenants
+            .into_iter()
+            .map(|(name, (ops, cache_control))| {
+                let state = AppState {
+                    auth: auth.clone(),
+                    cache_control: cache_control.map(Arc::from),
+                    ..AppState::new(ops)
+                };
+                (name, state)
+            })
+            .collect();
         let state = AppState {
-            ops: Arc::new(Mutex::new(self.ops)),
+            auth,
+            tenants: Arc::new(tenants),
+            cache_control: self.cache_control.map(Arc::from),
+            ..AppState::new(self.ops)
+        };
+
+        #[cfg(feature = "grpc")]
+        let grpc = match self.grpc_port {
+            Some(port) => {
+                let addr = tokio::net::lookup_host((self.host.as_str(), port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("cannot resolve {}", self.host))?;
+                let service = crate::grpc::GtsGrpcService::new(state.ops.clone())
+                    .with_auth(state.auth.clone());
+                tracing::info!("gRPC service listening on {}", addr);
+                Some(tokio::spawn(
+                    tonic::transport::Server::builder()
+                        .add_service(service.into_server())
+                        .serve(addr),
+                ))
+            }
+            None => None,
         };
 
         let app = Self::create_router(state, verbose);
@@ -57,15 +201,53 @@ impl GtsHttpServer {
         tracing::info!("Server listening on {}", addr);
         axum::serve(listener, app).await?;
 
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = grpc {
+            grpc.await??;
+        }
         Ok(())
     }
 
     pub fn create_router(state: AppState, verbose: u8) -> Router {
-        let mut router = Router::new()
+        let tenants = state.tenants.clone();
+        let mut router = Self::store_routes(state);
+        if !tenants.is_empty() {
+            let names: Arc<BTreeSet<String>> = Arc::new(tenants.keys().cloned().collect());
+            for (name, tenant) in tenants.iter() {
+                router = router.nest(&format!("/t/{name}"), Self::store_routes(tenant.clone()));
+            }
+            // Runs before `router` routes, so it can move header-selected
+            // requests under their tenant's prefix
+            router = Router::new()
+                .fallback_service(router)
+                .layer(middleware::from_fn(move |req, next| {
+                    let names = names.clone();
+                    async move { select_tenant(&names, req, next).await }
+                }));
+        }
+
+        // Add custom logging middleware if verbose >= 1
+        if verbose >= 1 {
+            let logging = LoggingMiddleware::new(verbose);
+            router = router.layer(middleware::from_fn(move |req, next| {
+                let logging = logging.clone();
+                async move { logging.handle(req, next).await }
+            }));
+        }
+
+        router
+    }
+
+    /// The routes of one store.
+    fn store_routes(state: AppState) -> Router {
+        let metrics = state.metrics.clone();
+        Router::new()
             .route("/entities", get(get_entities).post(add_entity))
             .route("/entities/{gts_id}", get(get_entity))
+            .route("/entities/{gts_id}/labels", get(get_labels).put(set_labels))
             .route("/entities/bulk", post(add_entities))
             .route("/schemas", post(add_schema))
+            .route("/schemas/{gts_id}", get(get_schema).put(put_schema))
             .route("/validate-id", get(validate_id))
             .route("/extract-id", post(extract_id))
             .route("/parse-id", get(parse_id))
@@ -74,49 +256,317 @@ impl GtsHttpServer {
             .route("/validate-instance", post(validate_instance))
             .route("/validate-schema", post(validate_schema))
             .route("/validate-entity", post(validate_entity))
+            .route("/validate", post(validate_entity))
             .route("/resolve-relationships", get(schema_graph))
+            .route("/graph/{gts_id}", get(graph))
             .route("/compatibility", get(compatibility))
             .route("/cast", post(cast))
             .route("/query", get(query))
+            .route("/query-page", get(query_page))
             .route("/attr", get(attr))
-            .with_state(state);
-
-        // Add custom logging middleware if verbose >= 1
-        if verbose >= 1 {
-            let logging = LoggingMiddleware::new(verbose);
-            router = router.layer(middleware::from_fn(move |req, next| {
-                let logging = logging.clone();
-                async move { logging.handle(req, next).await }
-            }));
-        }
-
-        router
+            .route("/version", get(version))
+            .route("/events", get(events))
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .route("/metrics", get(get_metrics))
+            .route_layer(middleware::from_fn(move |req, next| {
+                let metrics = metrics.clone();
+                async move { track_requests(&metrics, req, next).await }
+            }))
+            .with_state(state)
     }
 
+    /// The `OpenAPI` 3.0 description of every route of [`Self::create_router`].
     #[must_use]
     pub fn openapi_spec(&self) -> Value {
+        let mut paths: BTreeMap<&str, BTreeMap<&str, Value>> = BTreeMap::new();
+        for endpoint in ENDPOINTS {
+            paths
+                .entry(endpoint.path)
+                .or_default()
+                .insert(endpoint.method, endpoint.operation());
+        }
         json!({
             "openapi": "3.0.0",
             "info": {
                 "title": "GTS Server",
-                "version": "0.1.0"
+                "version": env!("CARGO_PKG_VERSION")
             },
             "servers": [{
                 "url": format!("http://{}:{}", self.host, self.port)
             }],
-            "paths": {
-                "/entities": {
-                    "get": { "summary": "Get all entities in the registry" },
-                    "post": { "summary": "Register a single entity" }
-                },
-                "/validate-id": {
-                    "get": { "summary": "Validate GTS identifier" }
+            "paths": paths,
+            "components": {
+                "securitySchemes": {
+                    "apiKey": {"type": "apiKey", "in": "header", "name": API_KEY_HEADER},
+                    "bearer": {"type": "http", "scheme": "bearer"}
                 }
             }
         })
     }
 }
 
+/// One route, as described in the `OpenAPI` spec.
+#[allow(clippy::struct_excessive_bools)]
+struct Endpoint {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    /// Query parameters; those ending in `?` are optional.
+    query: &'static [&'static str],
+    /// Fields of the JSON body: `None` for no body, empty for any JSON value.
+    body: Option<&'static [&'static str]>,
+    /// Whether the answer depends on the caller's [`ROLE_HEADER`].
+    scoped: bool,
+    /// Whether the route writes, needing credentials when auth is on.
+    write: bool,
+    /// Whether the answer carries an `ETag` and honours `If-None-Match`.
+    cached: bool,
+}
+
+impl Endpoint {
+    const fn new(method: &'static str, path: &'static str, summary: &'static str) -> Self {
+        Self {
+            method,
+            path,
+            summary,
+            query: &[],
+            body: None,
+            scoped: false,
+            write: false,
+            cached: false,
+        }
+    }
+
+    const fn query(mut self, query: &'static [&'static str]) -> Self {
+        self.query = query;
+        self
+    }
+
+    const fn body(mut self, fields: &'static [&'static str]) -> Self {
+        self.body = Some(fields);
+        self
+    }
+
+    const fn scoped(mut self) -> Self {
+        self.scoped = true;
+        self
+    }
+
+    const fn write(mut self) -> Self {
+        self.write = true;
+        self
+    }
+
+    const fn cached(mut self) -> Self {
+        self.cached = true;
+        self
+    }
+
+    fn operation(&self) -> Value {
+        let mut parameters = Vec::new();
+        for segment in self.path.split('/') {
+            if let Some(name) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                parameters.push(json!({
+                    "name": name, "in": "path", "required": true, "schema": {"type": "string"}
+                }));
+            }
+        }
+        for param in self.query {
+            let name = param.trim_end_matches('?');
+            let kind = match name {
+                "limit" | "page_size" => "integer",
+                "validate" => "boolean",
+                _ => "string",
+            };
+            parameters.push(json!({
+                "name": name,
+                "in": "query",
+                "required": !param.ends_with('?'),
+                "schema": {"type": kind}
+            }));
+        }
+        if self.scoped {
+            parameters.push(json!({
+                "name": ROLE_HEADER,
+                "in": "header",
+                "required": false,
+                "schema": {"type": "string", "enum": ["public", "partner", "internal"]}
+            }));
+        }
+        if self.cached {
+            parameters.push(json!({
+                "name": "If-None-Match",
+                "in": "header",
+                "required": false,
+                "schema": {"type": "string"}
+            }));
+        }
+
+        let mut operation = json!({
+            "summary": self.summary,
+            "responses": {"200": {"description": "JSON result"}}
+        });
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        if self.write {
+            operation["security"] = json!([{"apiKey": []}, {"bearer": []}]);
+            operation["responses"]["401"] =
+                json!({"description": "Missing or invalid credentials"});
+            operation["responses"]["403"] =
+                json!({"description": "Credentials do not cover the ID's vendor"});
+        }
+        if self.cached {
+            operation["responses"]["304"] =
+                json!({"description": "Unchanged since the `If-None-Match` ETag"});
+        }
+        if let Some(fields) = self.body {
+            let schema = if fields.is_empty() {
+                json!({})
+            } else {
+                let properties: serde_json::Map<String, Value> = fields
+                    .iter()
+                    .map(|field| ((*field).to_owned(), json!({"type": "string"})))
+                    .collect();
+                json!({"type": "object", "required": fields, "properties": properties})
+            };
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": {"application/json": {"schema": schema}}
+            });
+        }
+        operation
+    }
+}
+
+const ENDPOINTS: &[Endpoint] = &[
+    Endpoint::new("get", "/entities", "List registered entities")
+        .query(&["limit?"])
+        .scoped(),
+    Endpoint::new("post", "/entities", "Register an entity")
+        .query(&["validate?"])
+        .body(&[])
+        .write(),
+    Endpoint::new("get", "/entities/{gts_id}", "Get an entity").scoped(),
+    Endpoint::new("get", "/entities/{gts_id}/labels", "Get an entity's labels").scoped(),
+    Endpoint::new(
+        "put",
+        "/entities/{gts_id}/labels",
+        "Replace an entity's labels",
+    )
+    .body(&[])
+    .write(),
+    Endpoint::new("post", "/entities/bulk", "Register several entities")
+        .body(&[])
+        .write(),
+    Endpoint::new("post", "/schemas", "Register a schema")
+        .body(&["type_id", "schema"])
+        .write(),
+    Endpoint::new(
+        "get",
+        "/schemas/{gts_id}",
+        "Get a schema; 404 if it is not registered",
+    )
+    .scoped()
+    .cached(),
+    Endpoint::new(
+        "put",
+        "/schemas/{gts_id}",
+        "Register or replace a schema; the body is the schema",
+    )
+    .body(&[])
+    .write(),
+    Endpoint::new("get", "/validate-id", "Validate a GTS ID").query(&["gts_id"]),
+    Endpoint::new("post", "/extract-id", "Extract the GTS ID from a document").body(&[]),
+    Endpoint::new("get", "/parse-id", "Parse a GTS ID into its segments").query(&["gts_id"]),
+    Endpoint::new(
+        "get",
+        "/match-id-pattern",
+        "Match a GTS ID against a pattern",
+    )
+    .query(&["candidate", "pattern"]),
+    Endpoint::new("get", "/uuid", "UUID of a GTS ID").query(&["gts_id"]),
+    Endpoint::new(
+        "post",
+        "/validate",
+        "Validate an entity (instance or schema)",
+    )
+    .body(&["id"])
+    .scoped(),
+    Endpoint::new(
+        "post",
+        "/validate-instance",
+        "Validate an instance against its schema",
+    )
+    .body(&["instance_id"])
+    .scoped(),
+    Endpoint::new(
+        "post",
+        "/validate-schema",
+        "Validate a schema and its chain",
+    )
+    .body(&["schema_id"])
+    .scoped(),
+    Endpoint::new(
+        "post",
+        "/validate-entity",
+        "Validate an entity (instance or schema)",
+    )
+    .body(&["entity_id"])
+    .scoped(),
+    Endpoint::new("get", "/resolve-relationships", "Schema graph of an entity")
+        .query(&["gts_id"])
+        .scoped(),
+    Endpoint::new("get", "/graph/{gts_id}", "Schema graph of an entity").scoped(),
+    Endpoint::new(
+        "get",
+        "/compatibility",
+        "Compatibility of two schema versions",
+    )
+    .query(&["old_schema_id", "new_schema_id"])
+    .scoped(),
+    Endpoint::new(
+        "post",
+        "/cast",
+        "Cast an instance to another schema version",
+    )
+    .body(&["instance_id", "to_schema_id"])
+    .scoped(),
+    Endpoint::new(
+        "get",
+        "/query",
+        "Query entities; `pattern` is an alias of `expr`",
+    )
+    .query(&["expr", "limit?", "labels?", "sort_by?", "order?"])
+    .scoped(),
+    Endpoint::new("get", "/query-page", "Query entities a page at a time")
+        .query(&["expr", "cursor?", "page_size?"])
+        .scoped(),
+    Endpoint::new("get", "/attr", "Read an attribute: `<gts_id>@<path>`")
+        .query(&["gts_with_path"])
+        .scoped(),
+    Endpoint::new("get", "/version", "Server version, checked by `gts doctor`"),
+    Endpoint::new(
+        "get",
+        "/events",
+        "Stream store changes as server-sent events",
+    )
+    .query(&["kinds?"])
+    .scoped(),
+    Endpoint::new("get", "/healthz", "Liveness; 503 once a store is broken"),
+    Endpoint::new(
+        "get",
+        "/metrics",
+        "Request, validation and store metrics in the Prometheus text format",
+    ),
+    Endpoint::new(
+        "get",
+        "/readyz",
+        "Readiness and load status of the stores; 503 while one cannot be served",
+    ),
+];
+
 // Query parameters
 #[derive(Deserialize)]
 struct GtsIdQuery {
@@ -137,9 +587,27 @@ struct CompatibilityQuery {
 
 #[derive(Deserialize)]
 struct QueryParams {
+    #[serde(alias = "pattern")]
     expr: String,
     #[serde(default = "default_limit")]
     limit: usize,
+    /// Label selector, e.g. `env=prod,tier=gold`.
+    #[serde(default)]
+    labels: Option<String>,
+    /// Field (dotted path) to order results by.
+    #[serde(default)]
+    sort_by: Option<String>,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+#[derive(Deserialize)]
+struct QueryPageParams {
+    expr: String,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    page_size: usize,
 }
 
 #[derive(Deserialize)]
@@ -153,6 +621,12 @@ struct LimitQuery {
     limit: usize,
 }
 
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Comma-separated kinds of change to stream; all if absent.
+    kinds: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct AddEntityQuery {
     #[serde(default)]
@@ -188,10 +662,247 @@ struct ValidateSchemaRequest {
 
 #[derive(Deserialize, serde::Serialize)]
 struct ValidateEntityRequest {
-    #[serde(alias = "gts_id")]
+    #[serde(alias = "gts_id", alias = "id")]
     entity_id: String,
 }
 
+/// The caller's clearance, as its credentials allow; see [`caller_role`].
+/// Requests with invalid credentials are rejected with 401, and those
+/// asking in [`ROLE_HEADER`] for a role their credentials do not allow
+/// with 403.
+#[derive(Debug, Clone, Copy)]
+pub struct CallerRole(pub GtsVisibility);
+
+impl FromRequestParts<AppState> for CallerRole {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let requested = parts
+            .headers
+            .get(ROLE_HEADER)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|e| e.to_string())
+                    .and_then(str::parse)
+            })
+            .transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response())?;
+        caller_role(&state.auth, &parts.headers, requested)
+            .map(CallerRole)
+            .map_err(|e| {
+                let body = Json(json!({"ok": false, "error": e.to_string()}));
+                match e {
+                    AuthError::Forbidden(_) => (StatusCode::FORBIDDEN, body).into_response(),
+                    _ => (
+                        StatusCode::UNAUTHORIZED,
+                        [(WWW_AUTHENTICATE, "Bearer")],
+                        body,
+                    )
+                        .into_response(),
+                }
+            })
+    }
+}
+
+/// Header naming the tenant whose store answers a request without a
+/// `/t/{name}` prefix.
+pub const TENANT_HEADER: &str = "x-gts-tenant";
+
+/// Moves a request naming a tenant in [`TENANT_HEADER`] under its
+/// `/t/{name}` prefix, which wins if both are given, and answers 404 for
+/// unknown tenants.
+async fn select_tenant(tenants: &BTreeSet<String>, mut request: Request, next: Next) -> Response {
+    let prefixed = request
+        .uri()
+        .path()
+        .strip_prefix("/t/")
+        .map(|rest| rest.split('/').next().unwrap_or_default().to_owned());
+    let name = match (prefixed, request.headers().get(TENANT_HEADER)) {
+        (Some(name), _) => name,
+        (None, Some(value)) => value.to_str().unwrap_or_default().to_owned(),
+        (None, None) => return next.run(request).await,
+    };
+    if !tenants.contains(&name) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("Unknown tenant '{name}'")})),
+        )
+            .into_response();
+    }
+    if !request.uri().path().starts_with("/t/") {
+        let target = request.uri().path_and_query().map_or("/", |pq| pq.as_str());
+        // Tenant names are URL-safe, so the prefixed URI is valid
+        if let Ok(uri) = format!("/t/{name}{target}").parse::<Uri>() {
+            *request.uri_mut() = uri;
+        }
+    }
+    next.run(request).await
+}
+
+/// The credentials of a write request: what they grant, or `None` when the
+/// server requires none.  Requests without valid credentials are rejected
+/// with 401.
+pub struct Writer(pub Option<GtsWriteGrant>);
+
+impl FromRequestParts<AppState> for Writer {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        authenticate(&state.auth, &parts.headers)
+            .map(Writer)
+            .map_err(|e| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(WWW_AUTHENTICATE, "Bearer")],
+                    Json(json!({"ok": false, "error": e.to_string()})),
+                )
+                    .into_response()
+            })
+    }
+}
+
+impl Writer {
+    /// A 403 response if the credentials do not cover `id`.
+    fn forbidden(&self, id: &str) -> Option<Response> {
+        let grant = self.0.as_ref()?;
+        (!grant.allows(id)).then(|| {
+            (
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "ok": false,
+                    "id": id,
+                    "error": format!("'{}' may not write '{id}'", grant.principal)
+                })),
+            )
+                .into_response()
+        })
+    }
+}
+
+/// The locked ops, registering on behalf of one writer until dropped.
+struct WriterOps<'a> {
+    guard: MutexGuard<'a, GtsOps>,
+    principal: Option<String>,
+}
+
+impl Deref for WriterOps<'_> {
+    type Target = GtsOps;
+
+    fn deref(&self) -> &GtsOps {
+        &self.guard
+    }
+}
+
+impl DerefMut for WriterOps<'_> {
+    fn deref_mut(&mut self) -> &mut GtsOps {
+        &mut self.guard
+    }
+}
+
+impl Drop for WriterOps<'_> {
+    fn drop(&mut self) {
+        self.guard.principal = self.principal.take();
+    }
+}
+
+/// Locks the ops and makes the writer's principal, if authenticated, the
+/// one registrations are made on behalf of.
+fn lock_ops_for<'a>(
+    mutex: &'a Arc<Mutex<GtsOps>>,
+    writer: &Writer,
+) -> Result<WriterOps<'a>, impl IntoResponse> {
+    lock_ops(mutex).map(|mut guard| {
+        let principal = match &writer.0 {
+            Some(grant) => guard.principal.replace(grant.principal.clone()),
+            None => guard.principal.clone(),
+        };
+        WriterOps { guard, principal }
+    })
+}
+
+/// The ID a write of `body` is authorized by: its own, or its schema's
+/// for anonymous instances.
+fn write_target(ops: &GtsOps, body: &Value) -> String {
+    let extracted = ops.extract_id(body);
+    match extracted.schema_id {
+        Some(schema_id) if GtsID::new(&extracted.id).is_err() => schema_id,
+        _ => extracted.id,
+    }
+}
+
+/// The locked ops, answering for one caller until dropped.
+struct CallerOps<'a>(MutexGuard<'a, GtsOps>);
+
+impl Deref for CallerOps<'_> {
+    type Target = GtsOps;
+
+    fn deref(&self) -> &GtsOps {
+        &self.0
+    }
+}
+
+impl DerefMut for CallerOps<'_> {
+    fn deref_mut(&mut self) -> &mut GtsOps {
+        &mut self.0
+    }
+}
+
+impl Drop for CallerOps<'_> {
+    fn drop(&mut self) {
+        self.0.store.set_audience(None);
+    }
+}
+
+impl CallerOps<'_> {
+    /// A 404 response if the caller may not see `id`.
+    fn hidden(&self, id: &str) -> Option<Response> {
+        (!self.store.is_visible(id)).then(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"ok": false, "id": id, "error": format!("Entity '{id}' not found")})),
+            )
+                .into_response()
+        })
+    }
+}
+
+/// Locks the ops and restricts what the store shows to `role`.
+fn lock_ops_as(
+    mutex: &Arc<Mutex<GtsOps>>,
+    role: CallerRole,
+) -> Result<CallerOps<'_>, impl IntoResponse> {
+    lock_ops(mutex).map(|mut guard| {
+        guard.store.set_audience(Some(role.0));
+        CallerOps(guard)
+    })
+}
+
+/// Runs a handler's store work on tokio's blocking pool, so waiting for
+/// the store's lock, reading files and compiling schemas do not stall the
+/// async workers serving other requests.
+async fn blocking<R>(handler: impl FnOnce() -> R + Send + 'static) -> Response
+where
+    R: IntoResponse + Send + 'static,
+{
+    tokio::task::spawn_blocking(handler).await.map_or_else(
+        |e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Request failed: {e}")})),
+            )
+                .into_response()
+        },
+        IntoResponse::into_response,
+    )
+}
+
 // Helper function to lock mutex or return error response
 fn lock_ops(
     mutex: &Arc<Mutex<GtsOps>>,
@@ -207,217 +918,659 @@ fn lock_ops(
 // Async Handlers
 async fn get_entities(
     State(state): State<AppState>,
+    role: CallerRole,
     Query(params): Query<LimitQuery>,
 ) -> impl IntoResponse {
-    let ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.get_entities(params.limit);
-    Json(result).into_response()
+    blocking(move || {
+        let ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.get_entities(params.limit);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn get_entity(
     State(state): State<AppState>,
+    role: CallerRole,
     Path(gts_id): Path<String>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.get_entity(&gts_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&gts_id) {
+            return response;
+        }
+        let result = ops.get_entity(&gts_id);
+        Json(result).into_response()
+    })
+    .await
+}
+
+async fn get_labels(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Path(gts_id): Path<String>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&gts_id) {
+            return response;
+        }
+        let result = ops.get_labels(&gts_id);
+        Json(result).into_response()
+    })
+    .await
+}
+
+async fn set_labels(
+    State(state): State<AppState>,
+    writer: Writer,
+    Path(gts_id): Path<String>,
+    Json(labels): Json<BTreeMap<String, String>>,
+) -> impl IntoResponse {
+    blocking(move || {
+        if let Some(response) = writer.forbidden(&gts_id) {
+            return response;
+        }
+        let mut ops = match lock_ops_for(&state.ops, &writer) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.set_labels(&gts_id, &labels);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn add_entity(
     State(state): State<AppState>,
+    writer: Writer,
     Query(params): Query<AddEntityQuery>,
     Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.add_entity(&body, params.validate);
-    if result.ok {
-        (StatusCode::OK, Json(result)).into_response()
-    } else {
-        (StatusCode::UNPROCESSABLE_ENTITY, Json(result)).into_response()
-    }
+    blocking(move || {
+        let mut ops = match lock_ops_for(&state.ops, &writer) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = writer.forbidden(&write_target(&ops, &body)) {
+            return response;
+        }
+        let result = ops.add_entity(&body, params.validate);
+        if result.ok {
+            (StatusCode::OK, Json(result)).into_response()
+        } else {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(result)).into_response()
+        }
+    })
+    .await
 }
 
 async fn add_entities(
     State(state): State<AppState>,
+    writer: Writer,
     Json(body): Json<Vec<Value>>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.add_entities(&body);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_for(&state.ops, &writer) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = body
+            .iter()
+            .find_map(|entity| writer.forbidden(&write_target(&ops, entity)))
+        {
+            return response;
+        }
+        let result = ops.add_entities(&body);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn add_schema(
     State(state): State<AppState>,
+    writer: Writer,
     Json(body): Json<SchemaRegister>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.add_schema(body.type_id, &body.schema_content);
-    Json(result).into_response()
+    blocking(move || {
+        if let Some(response) = writer.forbidden(&body.type_id) {
+            return response;
+        }
+        let mut ops = match lock_ops_for(&state.ops, &writer) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.add_schema(body.type_id, &body.schema_content);
+        Json(result).into_response()
+    })
+    .await
+}
+
+/// Whether an `If-None-Match` header lists `etag` or is `*`, comparing
+/// weakly as RFC 9110 asks for GET.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+async fn get_schema(
+    State(state): State<AppState>,
+    role: CallerRole,
+    headers: HeaderMap,
+    Path(gts_id): Path<String>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&gts_id) {
+            return response;
+        }
+        let result = ops.get_entity(&gts_id);
+        if !result.ok || !result.is_schema {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(
+                    json!({"ok": false, "id": gts_id, "error": format!("Schema '{gts_id}' not found")}),
+                ),
+            )
+                .into_response();
+        }
+        // Fingerprints ignore key order and formatting, so the tag is weak
+        let etag = ops
+            .store
+            .fingerprint(&gts_id)
+            .map(|fingerprint| format!("W/\"{fingerprint}\""))
+            .unwrap_or_default();
+        let cache_control = state
+            .cache_control
+            .as_deref()
+            .unwrap_or("no-cache")
+            .to_owned();
+        let caching = [(ETAG, etag.clone()), (CACHE_CONTROL, cache_control)];
+        if etag_matches(&headers, &etag) {
+            return (StatusCode::NOT_MODIFIED, caching).into_response();
+        }
+        (caching, Json(result)).into_response()
+    })
+    .await
+}
+
+async fn put_schema(
+    State(state): State<AppState>,
+    writer: Writer,
+    Path(gts_id): Path<String>,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    blocking(move || {
+        if let Some(response) = writer.forbidden(&gts_id) {
+            return response;
+        }
+        let mut ops = match lock_ops_for(&state.ops, &writer) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.add_schema(gts_id, &body);
+        if result.ok {
+            (StatusCode::OK, Json(result)).into_response()
+        } else {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(result)).into_response()
+        }
+    })
+    .await
 }
 
 async fn validate_id(
     State(state): State<AppState>,
     Query(params): Query<GtsIdQuery>,
 ) -> impl IntoResponse {
-    let _ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = GtsOps::validate_id(&params.gts_id);
-    Json(result).into_response()
+    blocking(move || {
+        let _ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = GtsOps::validate_id(&params.gts_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn extract_id(State(state): State<AppState>, Json(body): Json<Value>) -> impl IntoResponse {
-    let ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.extract_id(&body);
-    Json(result).into_response()
+    blocking(move || {
+        let ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.extract_id(&body);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn parse_id(
     State(state): State<AppState>,
     Query(params): Query<GtsIdQuery>,
 ) -> impl IntoResponse {
-    let _ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = GtsOps::parse_id(&params.gts_id);
-    Json(result).into_response()
+    blocking(move || {
+        let _ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = GtsOps::parse_id(&params.gts_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn match_id_pattern(
     State(state): State<AppState>,
     Query(params): Query<MatchIdQuery>,
 ) -> impl IntoResponse {
-    let _ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
+    blocking(move || {
+        let _ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = GtsOps::match_id_pattern(&params.candidate, &params.pattern);
+        Json(result).into_response()
+    })
+    .await
+}
+
+/// Streams store changes the caller may see as server-sent events, each
+/// with the change's sequence number as its ID.  A subscriber too slow to
+/// keep up gets a `lagged` event with the number of changes it missed, and
+/// should then refetch what it caches.
+async fn events(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Query(params): Query<EventsQuery>,
+) -> Response {
+    let kinds = match params
+        .kinds
+        .as_deref()
+        .map(|kinds| {
+            kinds
+                .split(',')
+                .map(|kind| serde_json::from_value::<GtsChangeKind>(json!(kind.trim())))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+    {
+        Ok(kinds) => kinds,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"ok": false, "error": format!("invalid kinds: {e}")})),
+            )
+                .into_response();
+        }
+    };
+    let wanted = move |change: &GtsChangeEvent| {
+        role.0.can_see(change.visibility)
+            && kinds
+                .as_ref()
+                .is_none_or(|kinds| kinds.contains(&change.kind))
+    };
+    let stream = futures_util::stream::unfold(state.changes.subscribe(), move |mut receiver| {
+        let wanted = wanted.clone();
+        async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(change) if wanted(&change) => Event::default()
+                        .id(change.sequence.to_string())
+                        .json_data(&change),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        Ok(Event::default().event("lagged").data(missed.to_string()))
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((event, receiver));
+            }
+        }
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn version() -> impl IntoResponse {
+    Json(json!({
+        "name": "gts",
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+async fn get_metrics(State(state): State<AppState>, role: CallerRole) -> impl IntoResponse {
+    blocking(move || {
+        let ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let shown = |schema_id: &str| role.0.can_see(ops.store.visibility_of(schema_id));
+        let text = state.metrics.to_prometheus(shown) + &ops.metrics().to_prometheus();
+        (
+            [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+            text,
+        )
+            .into_response()
+    })
+    .await
+}
+
+/// Liveness: whether no store was left broken by a panic.  It never waits
+/// for a store, so a long operation holding one does not fail it.
+async fn healthz(State(state): State<AppState>) -> impl IntoResponse {
+    let broken = state.ops.is_poisoned() || state.tenants.values().any(|t| t.ops.is_poisoned());
+    if broken {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"status": "broken"})),
+        )
+    } else {
+        (StatusCode::OK, Json(json!({"status": "ok"})))
+    }
+}
+
+/// Load status of one store, and whether it can be served: its reader's
+/// storage, if it has one, must be reachable.
+fn store_readiness(mutex: &Arc<Mutex<GtsOps>>) -> (bool, Value) {
+    let Ok(ops) = mutex.lock() else {
+        return (
+            false,
+            json!({"status": "unavailable", "error": "Server state corrupted"}),
+        );
     };
-    let result = GtsOps::match_id_pattern(&params.candidate, &params.pattern);
-    Json(result).into_response()
+    let metrics = ops.store.metrics();
+    let backend = ops.store.check_reader().map(|check| match check {
+        Ok(()) => json!({"reachable": true}),
+        Err(e) => json!({"reachable": false, "error": e}),
+    });
+    let ready = backend.as_ref().is_none_or(|b| b["reachable"] == true);
+    let loaded_at = ops
+        .store
+        .loaded_at()
+        .map(|at| chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339());
+    let status = if ready { "ready" } else { "unavailable" };
+    (
+        ready,
+        json!({
+            "status": status,
+            "schemas": metrics.schemas,
+            "instances": metrics.instances,
+            "loaded_at": loaded_at,
+            "backend": backend
+        }),
+    )
+}
+
+/// Readiness: the load status of the store and of each tenant's; 503 while
+/// any of them cannot be served.  The server only listens once its stores
+/// are loaded.
+async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    blocking(move || {
+        let (mut ready, mut body) = store_readiness(&state.ops);
+        if !state.tenants.is_empty() {
+            let mut tenants = serde_json::Map::new();
+            for (name, tenant) in state.tenants.iter() {
+                let (tenant_ready, status) = store_readiness(&tenant.ops);
+                ready &= tenant_ready;
+                tenants.insert(name.clone(), status);
+            }
+            body["status"] = json!(if ready { "ready" } else { "unavailable" });
+            body["tenants"] = Value::Object(tenants);
+        }
+        let code = if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (code, Json(body))
+    })
+    .await
 }
 
 async fn id_to_uuid(
     State(state): State<AppState>,
     Query(params): Query<GtsIdQuery>,
 ) -> impl IntoResponse {
-    let _ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = GtsOps::uuid(&params.gts_id);
-    Json(result).into_response()
+    blocking(move || {
+        let _ops = match lock_ops(&state.ops) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = GtsOps::uuid(&params.gts_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn validate_instance(
     State(state): State<AppState>,
+    role: CallerRole,
     Json(body): Json<ValidateInstanceRequest>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.validate_instance(&body.instance_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&body.instance_id) {
+            return response;
+        }
+        let result = ops.validate_instance(&body.instance_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn validate_schema(
     State(state): State<AppState>,
+    role: CallerRole,
     Json(body): Json<ValidateSchemaRequest>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.validate_schema(&body.schema_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&body.schema_id) {
+            return response;
+        }
+        let result = ops.validate_schema(&body.schema_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn validate_entity(
     State(state): State<AppState>,
+    role: CallerRole,
     Json(body): Json<ValidateEntityRequest>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.validate_entity(&body.entity_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&body.entity_id) {
+            return response;
+        }
+        let result = ops.validate_entity(&body.entity_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn schema_graph(
     State(state): State<AppState>,
+    role: CallerRole,
     Query(params): Query<GtsIdQuery>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.schema_graph(&params.gts_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&params.gts_id) {
+            return response;
+        }
+        let result = ops.schema_graph(&params.gts_id);
+        Json(result).into_response()
+    })
+    .await
+}
+
+async fn graph(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Path(gts_id): Path<String>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        if let Some(response) = ops.hidden(&gts_id) {
+            return response;
+        }
+        let result = ops.schema_graph(&gts_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn compatibility(
     State(state): State<AppState>,
+    role: CallerRole,
     Query(params): Query<CompatibilityQuery>,
 ) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.compatibility(&params.old_schema_id, &params.new_schema_id);
-    Json(result).into_response()
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        for id in [&params.old_schema_id, &params.new_schema_id] {
+            if let Some(response) = ops.hidden(id) {
+                return response;
+            }
+        }
+        let result = ops.compatibility(&params.old_schema_id, &params.new_schema_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
-async fn cast(State(state): State<AppState>, Json(body): Json<CastRequest>) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.cast(&body.instance_id, &body.to_schema_id);
-    Json(result).into_response()
+async fn cast(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Json(body): Json<CastRequest>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        for id in [&body.instance_id, &body.to_schema_id] {
+            if let Some(response) = ops.hidden(id) {
+                return response;
+            }
+        }
+        let result = ops.cast(&body.instance_id, &body.to_schema_id);
+        Json(result).into_response()
+    })
+    .await
 }
 
 async fn query(
     State(state): State<AppState>,
+    role: CallerRole,
     Query(params): Query<QueryParams>,
 ) -> impl IntoResponse {
-    let ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.query(&params.expr, params.limit);
-    Json(result).into_response()
+    blocking(move || {
+        let ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = if let Some(field) = &params.sort_by {
+            if params.labels.is_some() {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "labels cannot be combined with sort_by"})),
+                )
+                    .into_response();
+            }
+            ops.query_sorted(&params.expr, field, params.order, params.limit)
+        } else {
+            let labels = params
+                .labels
+                .as_deref()
+                .map(GtsOps::parse_label_selector)
+                .unwrap_or_default();
+            ops.query_with_labels(&params.expr, &labels, params.limit)
+        };
+        Json(result).into_response()
+    })
+    .await
 }
 
-async fn attr(State(state): State<AppState>, Query(params): Query<AttrQuery>) -> impl IntoResponse {
-    let mut ops = match lock_ops(&state.ops) {
-        Ok(guard) => guard,
-        Err(response) => return response.into_response(),
-    };
-    let result = ops.attr(&params.gts_with_path);
-    Json(result).into_response()
+async fn query_page(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Query(params): Query<QueryPageParams>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let result = ops.query_page(&params.expr, params.cursor.as_deref(), params.page_size);
+        Json(result).into_response()
+    })
+    .await
+}
+
+async fn attr(
+    State(state): State<AppState>,
+    role: CallerRole,
+    Query(params): Query<AttrQuery>,
+) -> impl IntoResponse {
+    blocking(move || {
+        let mut ops = match lock_ops_as(&state.ops, role) {
+            Ok(guard) => guard,
+            Err(response) => return response.into_response(),
+        };
+        let id = params
+            .gts_with_path
+            .split_once('@')
+            .map_or(params.gts_with_path.as_str(), |(id, _)| id);
+        if let Some(response) = ops.hidden(id) {
+            return response;
+        }
+        let result = ops.attr(&params.gts_with_path);
+        Json(result).into_response()
+    })
+    .await
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -438,9 +1591,7 @@ mod tests {
     #[test]
     fn test_app_state_creation() {
         let ops = GtsOps::new(None, None, 0);
-        let _state = AppState {
-            ops: Arc::new(Mutex::new(ops)),
-        };
+        let _state = AppState::new(ops);
 
         // AppState is Clone, verified by compilation
     }
@@ -455,4 +1606,17 @@ mod tests {
         assert_eq!(server.port, 8080);
         assert_eq!(server.verbose, 0);
     }
+
+    #[test]
+    fn test_lock_ops_as_scopes_audience_to_guard() {
+        let state = Arc::new(Mutex::new(GtsOps::new(None, None, 0)));
+        {
+            let Ok(ops) = lock_ops_as(&state, CallerRole(GtsVisibility::Partner)) else {
+                panic!("lock failed");
+            };
+            assert_eq!(ops.store.audience(), Some(GtsVisibility::Partner));
+            assert!(ops.hidden("gts.x.test.unknown.v1~").is_none());
+        }
+        assert_eq!(state.lock().unwrap().store.audience(), None);
+    }
 }
@@ -0,0 +1,136 @@
+//! Settings only the `server` command uses.
+//!
+//! They sit in the same config file as the store's [`GtsConfig`], which
+//! `gts` itself reads; [`GtsServerConfig`] adds the server's own sections to
+//! it:
+//!
+//! | Key             | Holds                                             |
+//! |-----------------|---------------------------------------------------|
+//! | `auth`          | the credentials writes need, [`GtsAuthConfig`]    |
+//! | `cache_control` | the `Cache-Control` sent with schemas             |
+//! | `tenants`       | further stores to serve, by [`GtsTenant`] name    |
+//! | `webhooks`      | endpoints store changes go to, [`GtsWebhook`]s    |
+
+use gts::GtsConfig;
+use gts::config::{GtsConfigError, find_config_file, read_config_data};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+use crate::auth::GtsAuthConfig;
+use crate::tenants::GtsTenant;
+use crate::webhooks::GtsWebhook;
+
+/// The store's config and the server's own settings.
+#[derive(Debug, Clone, Default)]
+pub struct GtsServerConfig {
+    /// The store settings, loaded as for every command.
+    pub gts: GtsConfig,
+    /// Credentials the server requires for writes.
+    pub auth: Option<GtsAuthConfig>,
+    /// `Cache-Control` the server sends with schemas; `no-cache` if unset,
+    /// so clients revalidate with the schema's `ETag`.
+    pub cache_control: Option<String>,
+    /// Further stores the server serves, by tenant name.
+    pub tenants: BTreeMap<String, GtsTenant>,
+    /// Endpoints the server posts store changes to.
+    pub webhooks: Vec<GtsWebhook>,
+}
+
+impl GtsServerConfig {
+    /// `gts` with the server sections of the config file at `path`, else of
+    /// the first config file in the current directory; without a file the
+    /// sections keep their defaults.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the file cannot be read or one of its
+    /// server sections is invalid.
+    pub fn load(gts: GtsConfig, path: Option<&Path>) -> Result<Self, GtsConfigError> {
+        match path.map(Path::to_path_buf).or_else(find_config_file) {
+            Some(path) => Self::from_data(gts, &path, &read_config_data(&path)?),
+            None => Ok(GtsServerConfig {
+                gts,
+                ..Self::default()
+            }),
+        }
+    }
+
+    fn from_data(
+        gts: GtsConfig,
+        path: &Path,
+        data: &HashMap<String, Value>,
+    ) -> Result<Self, GtsConfigError> {
+        Ok(GtsServerConfig {
+            gts,
+            auth: section(path, data, "auth")?,
+            cache_control: section(path, data, "cache_control")?,
+            tenants: section(path, data, "tenants")?.unwrap_or_default(),
+            webhooks: section(path, data, "webhooks")?.unwrap_or_default(),
+        })
+    }
+}
+
+impl Deref for GtsServerConfig {
+    type Target = GtsConfig;
+
+    fn deref(&self) -> &GtsConfig {
+        &self.gts
+    }
+}
+
+impl DerefMut for GtsServerConfig {
+    fn deref_mut(&mut self) -> &mut GtsConfig {
+        &mut self.gts
+    }
+}
+
+/// The section `key` of the config file at `path`, if present.
+fn section<T: DeserializeOwned>(
+    path: &Path,
+    data: &HashMap<String, Value>,
+    key: &str,
+) -> Result<Option<T>, GtsConfigError> {
+    data.get(key)
+        .map(|value| {
+            serde_json::from_value(value.clone()).map_err(|e| GtsConfigError::Parse {
+                path: PathBuf::from(path),
+                message: format!("{key}: {e}"),
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use gts::{GtsChangeKind, GtsConfigFile};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_server_sections_are_read_next_to_the_store_config() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("gts.toml");
+        fs::write(
+            &path,
+            "strict = true\ncache_control = \"max-age=60\"\n\n[auth]\napi_keys = [{ key = \"k\", principal = \"ci\" }]\n\n\
+             [tenants.billing]\npath = [\"./billing\"]\n\n\
+             [[webhooks]]\nurl = \"http://hooks.example/gts\"\nevents = [\"retired\"]\n",
+        )
+        .unwrap();
+        let store_cfg = GtsConfig::from_file(&path).unwrap();
+        let cfg = GtsServerConfig::load(store_cfg, Some(&path)).unwrap();
+        assert!(cfg.strict);
+        assert_eq!(cfg.auth.unwrap().api_keys[0].principal, "ci");
+        assert_eq!(cfg.cache_control.as_deref(), Some("max-age=60"));
+        assert_eq!(cfg.tenants["billing"].path, ["./billing"]);
+        assert_eq!(cfg.webhooks[0].events, [GtsChangeKind::Retired]);
+
+        fs::write(&path, "[tenants.billing]\npath = \"./billing\"\n").unwrap();
+        let err = GtsServerConfig::load(GtsConfig::default(), Some(&path)).unwrap_err();
+        assert!(err.to_string().contains("tenants"), "{err}");
+    }
+}
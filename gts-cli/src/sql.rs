@@ -0,0 +1,149 @@
+//! `gts sql`: ad-hoc SQL over the store (`sql` feature).
+//!
+//! The store is loaded into an in-memory `SQLite` database with two tables:
+//!
+//! - `instances(id, schema_id, content)`
+//! - `schemas(id, content)`
+//!
+//! `content` holds the entity as JSON text, so `SQLite`'s JSON operators
+//! reach into it: `content->>'name'`, `json_extract(content, '$.a.b')`.
+//! The database is read-only once loaded.
+
+use anyhow::{Context, Result};
+use gts::GtsStore;
+use rusqlite::Connection;
+use rusqlite::types::ValueRef;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Rows returned by [`run_query`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SqlResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE instances (id TEXT PRIMARY KEY, schema_id TEXT, content TEXT NOT NULL);
+    CREATE INDEX instances_schema_id ON instances (schema_id);
+    CREATE TABLE schemas (id TEXT PRIMARY KEY, content TEXT NOT NULL);
+";
+
+/// An in-memory database holding the entities of `store`.
+fn load(store: &GtsStore) -> Result<Connection> {
+    let mut conn = Connection::open_in_memory()?;
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    {
+        let mut instance =
+            tx.prepare("INSERT INTO instances (id, schema_id, content) VALUES (?1, ?2, ?3)")?;
+        let mut schema = tx.prepare("INSERT INTO schemas (id, content) VALUES (?1, ?2)")?;
+        for (id, entity) in store.items() {
+            let content = entity.content.to_string();
+            if entity.is_schema {
+                schema.execute((id, content))?;
+            } else {
+                instance.execute((id, entity.schema_id.as_deref(), content))?;
+            }
+        }
+    }
+    tx.commit()?;
+    conn.pragma_update(None, "query_only", true)?;
+    Ok(conn)
+}
+
+fn json_value(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(n) => Value::from(n),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+            Value::String(String::from_utf8_lossy(bytes).into_owned())
+        }
+    }
+}
+
+/// Runs `query` against the entities of `store`.
+///
+/// # Errors
+/// Returns an error if the query is invalid or tries to modify the database.
+pub fn run_query(store: &GtsStore, query: &str) -> Result<SqlResult> {
+    let conn = load(store).context("loading the store into SQLite")?;
+    let mut stmt = conn.prepare(query)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_owned).collect();
+    let width = columns.len();
+    let rows = stmt
+        .query_map([], |row| {
+            (0..width)
+                .map(|i| row.get_ref(i).map(json_value))
+                .collect::<rusqlite::Result<Vec<Value>>>()
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(SqlResult { columns, rows })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use gts::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    const ITEM: &str = "gts.x.marketplace.catalog.item.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ITEM,
+                &json!({
+                    "$id": format!("gts://{ITEM}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        for (name, price) in [("lamp", 30), ("desk", 120)] {
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &json!({"id": format!("{ITEM}x.test._.{name}.v1"), "name": name, "price": price}),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(ITEM.to_owned()),
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_run_query_over_instances() {
+        let result = run_query(
+            &store(),
+            "SELECT content->>'name' AS name, content->>'price' AS price FROM instances \
+             WHERE schema_id LIKE 'gts.x.marketplace.%' ORDER BY price DESC",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["name", "price"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![json!("desk"), json!(120)],
+                vec![json!("lamp"), json!(30)]
+            ]
+        );
+
+        let schemas = run_query(&store(), "SELECT count(*) FROM schemas").unwrap();
+        assert_eq!(schemas.rows, vec![vec![json!(1)]]);
+    }
+
+    #[test]
+    fn test_run_query_is_read_only() {
+        assert!(run_query(&store(), "DELETE FROM instances").is_err());
+        assert!(run_query(&store(), "SELECT * FROM nowhere").is_err());
+    }
+}
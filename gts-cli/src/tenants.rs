@@ -0,0 +1,67 @@
+//! Isolated stores served side by side by one `gts` server.
+//!
+//! Each entry of the `tenants` config section names a [`GtsTenant`]: a store
+//! read from its own paths with its own config, reachable under
+//! `/t/{name}/...` or with the `x-gts-tenant` header.
+
+use gts::{GtsConfigError, GtsOps};
+use serde::{Deserialize, Serialize};
+
+/// Where a tenant's store is read from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsTenant {
+    /// Files and directories of the tenant's entities; empty for a store
+    /// that starts empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
+    /// The tenant's own config file; without one it is looked up as for
+    /// the server's store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+}
+
+impl GtsTenant {
+    /// Whether `name` may name a tenant: letters, digits, `-` and `_`, so it
+    /// fits in a URL path segment unescaped.
+    #[must_use]
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    /// Ops over the tenant's store, read from its own paths with its own
+    /// config.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the tenant's config cannot be loaded.
+    pub fn ops(&self, verbose: usize) -> Result<GtsOps, GtsConfigError> {
+        let path = (!self.path.is_empty()).then(|| self.path.clone());
+        GtsOps::try_new(path, self.config.clone(), verbose)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_config_and_names() {
+        let tenant: GtsTenant =
+            serde_json::from_str(r#"{"path": ["./teams/billing"], "config": "billing.json"}"#)
+                .unwrap();
+        assert_eq!(tenant.path, ["./teams/billing"]);
+        assert_eq!(tenant.config.as_deref(), Some("billing.json"));
+        assert_eq!(
+            serde_json::from_str::<GtsTenant>("{}").unwrap(),
+            GtsTenant::default()
+        );
+
+        assert!(GtsTenant::is_valid_name("team-a_2"));
+        assert!(!GtsTenant::is_valid_name(""));
+        assert!(!GtsTenant::is_valid_name("a/b"));
+        assert!(!GtsTenant::is_valid_name("team a"));
+    }
+}
@@ -0,0 +1,466 @@
+//! `gts tui`: terminal browser for the store (`tui` feature).
+//!
+//! The left pane lists schemas grouped by `vendor.package.namespace`; the
+//! right side previews the selected schema above the list of its instances.
+//! `/` filters both incrementally, `v` validates, `Tab` switches panes.
+
+use anyhow::Result;
+use gts::{GtsID, GtsStore};
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use std::collections::{BTreeMap, HashMap};
+
+/// Schemas sharing a `vendor.package.namespace` prefix.
+struct Group {
+    name: String,
+    schemas: Vec<String>,
+    expanded: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Group(usize),
+    Schema(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Tree,
+    Instances,
+}
+
+pub struct TuiApp {
+    store: GtsStore,
+    groups: Vec<Group>,
+    /// Instance IDs by schema ID, sorted.
+    instances: HashMap<String, Vec<String>>,
+    /// Validation outcome per instance ID.
+    status: HashMap<String, Result<(), String>>,
+    focus: Focus,
+    tree_cursor: usize,
+    instance_cursor: usize,
+    search: String,
+    searching: bool,
+    quit: bool,
+}
+
+fn namespace_of(schema_id: &str) -> String {
+    GtsID::new(schema_id)
+        .ok()
+        .and_then(|gid| {
+            gid.gts_id_segments
+                .first()
+                .map(|s| format!("{}.{}.{}", s.vendor, s.package, s.namespace))
+        })
+        .unwrap_or_else(|| "(other)".to_owned())
+}
+
+impl TuiApp {
+    #[must_use]
+    pub fn new(store: GtsStore) -> Self {
+        let mut by_namespace: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut instances: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, entity) in store.items() {
+            if entity.is_schema {
+                by_namespace
+                    .entry(namespace_of(id))
+                    .or_default()
+                    .push(id.clone());
+            } else if let Some(schema_id) = &entity.schema_id {
+                instances
+                    .entry(schema_id.clone())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+        for ids in by_namespace.values_mut().chain(instances.values_mut()) {
+            ids.sort();
+        }
+
+        let groups = by_namespace
+            .into_iter()
+            .map(|(name, schemas)| Group {
+                name,
+                schemas,
+                expanded: false,
+            })
+            .collect();
+        Self {
+            store,
+            groups,
+            instances,
+            status: HashMap::new(),
+            focus: Focus::Tree,
+            tree_cursor: 0,
+            instance_cursor: 0,
+            search: String::new(),
+            searching: false,
+            quit: false,
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        self.search.is_empty() || text.to_lowercase().contains(&self.search.to_lowercase())
+    }
+
+    /// Visible tree rows. While a search is active, groups with matching
+    /// schemas are shown expanded.
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        for (g, group) in self.groups.iter().enumerate() {
+            let schemas: Vec<usize> = (0..group.schemas.len())
+                .filter(|&s| self.search.is_empty() || self.matches(&group.schemas[s]))
+                .collect();
+            if !self.search.is_empty() && schemas.is_empty() {
+                continue;
+            }
+            rows.push(Row::Group(g));
+            if group.expanded || !self.search.is_empty() {
+                rows.extend(schemas.into_iter().map(|s| Row::Schema(g, s)));
+            }
+        }
+        rows
+    }
+
+    fn selected_schema(&self) -> Option<&str> {
+        match self.rows().get(self.tree_cursor)? {
+            Row::Schema(g, s) => Some(&self.groups[*g].schemas[*s]),
+            Row::Group(_) => None,
+        }
+    }
+
+    fn visible_instances(&self) -> Vec<&String> {
+        self.selected_schema()
+            .and_then(|schema_id| self.instances.get(schema_id))
+            .map(|ids| ids.iter().filter(|id| self.matches(id)).collect())
+            .unwrap_or_default()
+    }
+
+    fn move_cursor(&mut self, down: bool) {
+        let len = match self.focus {
+            Focus::Tree => self.rows().len(),
+            Focus::Instances => self.visible_instances().len(),
+        };
+        let cursor = match self.focus {
+            Focus::Tree => &mut self.tree_cursor,
+            Focus::Instances => &mut self.instance_cursor,
+        };
+        *cursor = if down {
+            (*cursor + 1).min(len.saturating_sub(1))
+        } else {
+            cursor.saturating_sub(1)
+        };
+        if self.focus == Focus::Tree {
+            self.instance_cursor = 0;
+        }
+    }
+
+    fn toggle_group(&mut self) {
+        if let Some(Row::Group(g)) = self.rows().get(self.tree_cursor).copied() {
+            self.groups[g].expanded = !self.groups[g].expanded;
+        }
+    }
+
+    /// Validates the selected instance, or every instance of the selected
+    /// schema when the tree has focus.
+    fn validate(&mut self) {
+        let ids: Vec<String> = match self.focus {
+            Focus::Tree => self.visible_instances().into_iter().cloned().collect(),
+            Focus::Instances => self
+                .visible_instances()
+                .get(self.instance_cursor)
+                .map(|id| vec![(*id).clone()])
+                .unwrap_or_default(),
+        };
+        for id in ids {
+            let result = self.store.validate_instance(&id).map_err(|e| e.to_string());
+            self.status.insert(id, result);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.search.clear();
+                    self.searching = false;
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Char(c) => self.search.push(c),
+                _ => return,
+            }
+            self.tree_cursor = 0;
+            self.instance_cursor = 0;
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Esc => self.search.clear(),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Tree => Focus::Instances,
+                    Focus::Instances => Focus::Tree,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(false),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(true),
+            KeyCode::Enter | KeyCode::Left | KeyCode::Right => self.toggle_group(),
+            KeyCode::Char('v') => self.validate(),
+            _ => {}
+        }
+    }
+
+    #[must_use]
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    fn pane(&self, title: &str, focus: Option<Focus>) -> Block<'static> {
+        let block = Block::bordered().title(format!(" {title} "));
+        if focus.is_some_and(|f| f == self.focus) {
+            block.border_style(Style::default().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [tree, right] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+        let [preview, instances] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(right);
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let items: Vec<ListItem> = self
+            .rows()
+            .into_iter()
+            .map(|row| match row {
+                Row::Group(g) => {
+                    let group = &self.groups[g];
+                    let marker = if group.expanded || !self.search.is_empty() {
+                        "v"
+                    } else {
+                        ">"
+                    };
+                    ListItem::new(format!("{marker} {} ({})", group.name, group.schemas.len()))
+                }
+                Row::Schema(g, s) => ListItem::new(format!("    {}", self.groups[g].schemas[s])),
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.tree_cursor));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(self.pane("Namespaces", Some(Focus::Tree)))
+                .highlight_style(highlight),
+            tree,
+            &mut state,
+        );
+
+        let schema_text = self
+            .selected_schema()
+            .and_then(|id| self.store.items().find(|(key, _)| key.as_str() == id))
+            .and_then(|(_, e)| serde_json::to_string_pretty(&e.content).ok())
+            .unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(schema_text).block(self.pane("Schema", None)),
+            preview,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible_instances()
+            .into_iter()
+            .map(|id| {
+                let (mark, color) = match self.status.get(id) {
+                    Some(Ok(())) => ("ok", Color::Green),
+                    Some(Err(_)) => ("!!", Color::Red),
+                    None => ("  ", Color::Reset),
+                };
+                ListItem::new(Line::styled(
+                    format!("{mark} {id}"),
+                    Style::default().fg(color),
+                ))
+            })
+            .collect();
+        let mut state = ListState::default()
+            .with_selected((self.focus == Focus::Instances).then_some(self.instance_cursor));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(self.pane("Instances", Some(Focus::Instances)))
+                .highlight_style(highlight),
+            instances,
+            &mut state,
+        );
+
+        frame.render_widget(Paragraph::new(self.status_line()), status);
+    }
+
+    fn status_line(&self) -> String {
+        if self.searching {
+            return format!("/{}", self.search);
+        }
+        if self.focus == Focus::Instances
+            && let Some(id) = self.visible_instances().get(self.instance_cursor)
+            && let Some(Err(error)) = self.status.get(*id)
+        {
+            return error.clone();
+        }
+        let filter = if self.search.is_empty() {
+            String::new()
+        } else {
+            format!("  filter: {}", self.search)
+        };
+        format!("q quit  / search  tab switch pane  enter expand  v validate{filter}")
+    }
+}
+
+/// Runs the browser until the user quits.
+///
+/// # Errors
+/// Returns an error if the terminal cannot be drawn to or read from.
+pub fn run(store: GtsStore) -> Result<()> {
+    let mut app = TuiApp::new(store);
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<()> {
+        while !app.should_quit() {
+            terminal.draw(|frame| app.render(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                app.handle_key(key);
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use gts::{GtsConfig, GtsEntity};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use serde_json::json;
+
+    const ORDER: &str = "gts.x.shop.orders.order.v1~";
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn app() -> TuiApp {
+        let mut store = GtsStore::new(None);
+        for id in [
+            ORDER,
+            "gts.x.shop.orders.refund.v1~",
+            "gts.x.crm.core.customer.v1~",
+        ] {
+            let schema = json!({
+                "$id": format!("gts://{id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {"qty": {"type": "integer", "minimum": 1}}
+            });
+            store.register_schema(id, &schema).unwrap();
+        }
+        for (name, qty) in [("first", 1), ("second", 0)] {
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &json!({"id": format!("{ORDER}x.shop._.{name}.v1"), "qty": qty}),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(ORDER.to_owned()),
+            );
+            store.register(entity).unwrap();
+        }
+        TuiApp::new(store)
+    }
+
+    #[test]
+    fn test_tree_navigation_and_validation() {
+        let mut app = app();
+        assert_eq!(app.rows().len(), 2);
+
+        // Expand x.shop.orders and select the order schema
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Enter));
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.selected_schema(), Some(ORDER));
+        assert_eq!(app.visible_instances().len(), 2);
+
+        app.handle_key(key(KeyCode::Char('v')));
+        let first = format!("{ORDER}x.shop._.first.v1");
+        let second = format!("{ORDER}x.shop._.second.v1");
+        assert!(app.status[&first].is_ok());
+        assert!(app.status[&second].is_err());
+
+        app.handle_key(key(KeyCode::Tab));
+        app.handle_key(key(KeyCode::Down));
+        assert!(app.status_line().contains("Validation failed"));
+
+        app.handle_key(key(KeyCode::Char('q')));
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let mut app = app();
+        for c in "/refund".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            app.rows(),
+            vec![Row::Group(1), Row::Schema(1, 1)],
+            "only the matching schema and its group remain"
+        );
+        // 'q' is part of the query while typing
+        app.handle_key(key(KeyCode::Char('q')));
+        assert!(!app.should_quit());
+        assert!(app.rows().is_empty());
+
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.rows().len(), 2);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Enter));
+        app.handle_key(key(KeyCode::Down));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(screen.contains("x.shop.orders (2)"));
+        assert!(screen.contains("\"minimum\": 1"));
+        assert!(screen.contains("x.shop._.first.v1"));
+    }
+}
@@ -0,0 +1,196 @@
+//! `gts validate-all --watch`: re-validation whenever files change.
+//!
+//! The store is validated once, then again after every burst of file
+//! changes under the watched directories.  Each round reports only what
+//! changed since the previous one: instances that started failing (or fail
+//! differently) and instances that no longer fail.
+
+use anyhow::Result;
+use gts::{GtsFileWatcher, GtsOps, GtsValidateAllReport, GtsValidationFailure, GtsWatchEvent};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::output::render;
+use crate::progress::OutputFormat;
+
+/// How long to wait for more changes before validating, so that saving
+/// several files at once triggers a single round.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// The outcome of one validation round.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WatchRound {
+    /// Files changed since the previous round.
+    pub files: Vec<String>,
+    pub checked: usize,
+    pub failing: usize,
+    /// Instances that fail now but did not, or failed differently, before.
+    pub new_failures: Vec<GtsValidationFailure>,
+    /// Instances that failed before and now pass or are gone.
+    pub resolved: Vec<String>,
+}
+
+impl WatchRound {
+    /// Compares `report` with the failures of the previous round, which it
+    /// replaces.
+    fn new(
+        files: Vec<String>,
+        report: GtsValidateAllReport,
+        failures: &mut BTreeMap<String, String>,
+    ) -> Self {
+        let current: BTreeMap<String, String> = report
+            .failures
+            .iter()
+            .map(|f| (f.id.clone(), f.error.clone()))
+            .collect();
+        let resolved = failures
+            .keys()
+            .filter(|id| !current.contains_key(*id))
+            .cloned()
+            .collect();
+        let new_failures = report
+            .failures
+            .into_iter()
+            .filter(|f| failures.get(&f.id) != Some(&f.error))
+            .collect();
+        *failures = current;
+        Self {
+            files,
+            checked: report.checked,
+            failing: failures.len(),
+            new_failures,
+            resolved,
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        if !self.files.is_empty() {
+            let now = chrono::Local::now().format("%H:%M:%S");
+            let _ = writeln!(out, "[{now}] changed: {}", self.files.join(", "));
+        }
+        for failure in &self.new_failures {
+            let _ = writeln!(out, "  fail   {}: {}", failure.id, failure.error);
+        }
+        for id in &self.resolved {
+            let _ = writeln!(out, "  fixed  {id}");
+        }
+        let status = if self.failing == 0 { "ok" } else { "FAILED" };
+        let _ = writeln!(
+            out,
+            "  {status}: {} of {} instances invalid",
+            self.failing, self.checked
+        );
+        out
+    }
+
+    fn render(&self, output: OutputFormat) -> Result<String> {
+        match output {
+            OutputFormat::Text | OutputFormat::Table => Ok(self.render_text()),
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let failing = format!("{} invalid instances", self.failing);
+                let outcome = if self.failing == 0 {
+                    Ok(())
+                } else {
+                    Err(failing.as_str())
+                };
+                render(self, outcome, output)
+            }
+        }
+    }
+}
+
+/// Validates the store loaded from `paths`, then watches them and
+/// re-validates after every change until interrupted.
+///
+/// # Errors
+/// Returns an error if the directories cannot be watched.
+pub fn run(ops: &mut GtsOps, paths: &[String], output: OutputFormat) -> Result<()> {
+    ops.reload_from_path(paths);
+    let watcher = GtsFileWatcher::new(paths, Some(ops.cfg.clone()))?;
+    if output == OutputFormat::Text {
+        eprintln!("Watching {} for changes (Ctrl-C to stop)", paths.join(", "));
+    }
+
+    let mut failures = BTreeMap::new();
+    let mut files = Vec::new();
+    loop {
+        let round = WatchRound::new(files, ops.store.validate_all(), &mut failures);
+        print!("{}", round.render(output)?);
+
+        // Block for the next change, then gather the rest of the burst
+        let Ok(first) = watcher.events().recv() else {
+            return Ok(());
+        };
+        let mut changed = BTreeSet::new();
+        let mut event = Some(first);
+        while let Some(next) = event {
+            match &next {
+                GtsWatchEvent::FileChanged { path, .. } | GtsWatchEvent::FileRemoved { path } => {
+                    changed.insert(path.display().to_string());
+                }
+                GtsWatchEvent::Error(e) => eprintln!("Watch error: {e}"),
+            }
+            ops.store.apply_watch_event(next);
+            event = watcher.events().recv_timeout(SETTLE).ok();
+        }
+        files = changed.into_iter().collect();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn report(failures: &[(&str, &str)]) -> GtsValidateAllReport {
+        GtsValidateAllReport {
+            checked: 3,
+            valid: 3 - failures.len(),
+            failures: failures
+                .iter()
+                .map(|(id, error)| GtsValidationFailure {
+                    id: (*id).to_owned(),
+                    schema_id: "gts.x.test.watch.item.v1~".to_owned(),
+                    error: (*error).to_owned(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_rounds_report_only_changes() {
+        let mut failures = BTreeMap::new();
+        let first = WatchRound::new(
+            Vec::new(),
+            report(&[("a", "bad n"), ("b", "bad m")]),
+            &mut failures,
+        );
+        assert_eq!(first.new_failures.len(), 2);
+        assert_eq!(first.failing, 2);
+        assert!(first.render_text().contains("  fail   a: bad n\n"));
+
+        let second = WatchRound::new(
+            vec!["items/a.json".to_owned()],
+            report(&[("b", "bad m"), ("c", "missing id")]),
+            &mut failures,
+        );
+        let new: Vec<&str> = second.new_failures.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(new, ["c"]);
+        assert_eq!(second.resolved, ["a"]);
+        let text = second.render_text();
+        assert!(text.contains("changed: items/a.json\n"));
+        assert!(text.contains("  fixed  a\n"));
+        assert!(text.ends_with("  FAILED: 2 of 3 instances invalid\n"));
+
+        let third = WatchRound::new(Vec::new(), report(&[("b", "bad k")]), &mut failures);
+        assert_eq!(third.new_failures[0].error, "bad k");
+        assert_eq!(third.resolved, ["c"]);
+        let json: serde_json::Value =
+            serde_json::from_str(&third.render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["result"]["failing"], 1);
+        assert_eq!(json["ok"], false);
+    }
+}
@@ -0,0 +1,232 @@
+//! Webhook notifications of store changes.
+//!
+//! [`WebhookNotifier`] listens to the server's store and posts every change
+//! a webhook asks for as a JSON body:
+//!
+//! ```json
+//! {"sequence": 7, "kind": "deprecated", "id": "gts.x.core.events.type.v1~",
+//!  "is_schema": true, "visibility": "public", "timestamp": "2026-01-31T12:00:00Z"}
+//! ```
+//!
+//! Each webhook has its own delivery thread, so a slow endpoint delays only
+//! its own notifications, which it receives in order.  Failed deliveries
+//! (network errors and non-2xx answers) are retried with doubling delays and
+//! then dropped with an error log.
+
+use gts::{GtsChangeEvent, GtsChangeKind, GtsChangeListener};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Time allowed for one delivery attempt.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before the first retry; each further retry waits twice as long.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// An HTTP endpoint that store changes are posted to, from the `webhooks`
+/// config section or `--webhook`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsWebhook {
+    pub url: String,
+    /// Kinds of change to post; empty for all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<GtsChangeKind>,
+    /// Extra request headers, e.g. `Authorization`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Further attempts after a failed delivery, with doubling delays.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+impl GtsWebhook {
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        GtsWebhook {
+            url: url.into(),
+            events: Vec::new(),
+            headers: BTreeMap::new(),
+            retries: default_retries(),
+        }
+    }
+
+    /// Whether changes of `kind` are posted to this webhook.
+    #[must_use]
+    pub fn wants(&self, kind: GtsChangeKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Posts store changes to webhooks from background threads.
+pub struct WebhookNotifier {
+    queues: Vec<(GtsWebhook, Sender<Value>)>,
+}
+
+impl WebhookNotifier {
+    /// Starts a delivery thread for each webhook.
+    #[must_use]
+    pub fn start(webhooks: Vec<GtsWebhook>) -> Self {
+        Self::with_backoff(webhooks, RETRY_BACKOFF)
+    }
+
+    fn with_backoff(webhooks: Vec<GtsWebhook>, backoff: Duration) -> Self {
+        let queues = webhooks
+            .into_iter()
+            .map(|webhook| {
+                let (sender, receiver) = mpsc::channel::<Value>();
+                let worker = webhook.clone();
+                thread::spawn(move || {
+                    let agent: ureq::Agent = ureq::Agent::config_builder()
+                        .timeout_global(Some(DELIVERY_TIMEOUT))
+                        .http_status_as_error(false)
+                        .build()
+                        .into();
+                    for payload in receiver {
+                        deliver(&agent, &worker, &payload, backoff);
+                    }
+                });
+                (webhook, sender)
+            })
+            .collect();
+        WebhookNotifier { queues }
+    }
+}
+
+impl GtsChangeListener for WebhookNotifier {
+    fn on_change(&self, event: &GtsChangeEvent) {
+        let mut payload = json!(event);
+        payload["timestamp"] = json!(chrono::Utc::now().to_rfc3339());
+        for (webhook, queue) in &self.queues {
+            if webhook.wants(event.kind) {
+                // The worker only stops when the notifier is dropped
+                let _ = queue.send(payload.clone());
+            }
+        }
+    }
+}
+
+/// Posts `body` to `webhook` once.
+fn post(agent: &ureq::Agent, webhook: &GtsWebhook, body: &str) -> Result<(), String> {
+    let mut request = agent
+        .post(&webhook.url)
+        .header("content-type", "application/json");
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send(body.as_bytes()).map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("answered {}", response.status()))
+    }
+}
+
+/// Posts `payload` to `webhook`, retrying failures; whether it was accepted.
+fn deliver(agent: &ureq::Agent, webhook: &GtsWebhook, payload: &Value, backoff: Duration) -> bool {
+    let body = payload.to_string();
+    let mut delay = backoff;
+    for attempt in 0..=webhook.retries {
+        if attempt > 0 {
+            thread::sleep(delay);
+            delay *= 2;
+        }
+        match post(agent, webhook, &body) {
+            Ok(()) => return true,
+            Err(e) => tracing::warn!("webhook {} {e} (attempt {})", webhook.url, attempt + 1),
+        }
+    }
+    tracing::error!(
+        "dropping change {} to {} after {} attempts",
+        payload["sequence"],
+        webhook.url,
+        webhook.retries + 1
+    );
+    false
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use gts::GtsVisibility;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers each request with the next status and sends its body back.
+    fn endpoint(statuses: &'static [u16]) -> (String, mpsc::Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (stream, status) in listener.incoming().zip(statuses) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                )
+                .unwrap();
+                sender
+                    .send(serde_json::from_slice::<Value>(&body).unwrap())
+                    .unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    fn event(sequence: u64, kind: GtsChangeKind) -> GtsChangeEvent {
+        GtsChangeEvent {
+            sequence,
+            kind,
+            id: "gts.x.test.hooks.item.v1~".to_owned(),
+            is_schema: true,
+            visibility: GtsVisibility::Public,
+        }
+    }
+
+    #[test]
+    fn test_notifier_filters_and_retries() {
+        let (url, received) = endpoint(&[503, 200, 200]);
+        let mut webhook = GtsWebhook::new(url);
+        webhook.events = vec![GtsChangeKind::Deprecated, GtsChangeKind::Removed];
+        let notifier = WebhookNotifier::with_backoff(vec![webhook], Duration::from_millis(10));
+
+        notifier.on_change(&event(1, GtsChangeKind::Registered));
+        notifier.on_change(&event(2, GtsChangeKind::Deprecated));
+        notifier.on_change(&event(3, GtsChangeKind::Removed));
+
+        let wait = Duration::from_secs(5);
+        let bodies: Vec<Value> = (0..3)
+            .map(|_| received.recv_timeout(wait).unwrap())
+            .collect();
+        // The first delivery failed with 503 and was retried
+        assert_eq!(bodies[0]["sequence"], 2);
+        assert_eq!(bodies[1]["sequence"], 2);
+        assert_eq!(bodies[1]["kind"], "deprecated");
+        assert_eq!(bodies[2]["kind"], "removed");
+        assert!(bodies[2]["timestamp"].is_string());
+    }
+}
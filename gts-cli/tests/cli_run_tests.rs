@@ -1,5 +1,8 @@
 use anyhow::Result;
-use gts_cli::{Cli, Commands, run_with_cli};
+use gts_cli::{
+    AuditCommand, BenchCommand, Cli, Commands, GraphFormat, LifecycleCommand, OutputFormat,
+    ReportFormat, run_with_cli,
+};
 use std::fs;
 use tempfile::TempDir;
 
@@ -9,6 +12,8 @@ async fn test_run_validate_id_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::ValidateId {
             gts_id: "test:schema:v1".to_owned(),
         },
@@ -27,6 +32,8 @@ async fn test_run_parse_id_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::ParseId {
             gts_id: "test:schema:v1".to_owned(),
         },
@@ -42,6 +49,8 @@ async fn test_run_match_id_pattern_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::MatchIdPattern {
             pattern: "test:*:v1".to_owned(),
             candidate: "test:schema:v1".to_owned(),
@@ -58,6 +67,8 @@ async fn test_run_uuid_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::Uuid {
             gts_id: "test:schema:v1".to_owned(),
             scope: "major".to_owned(),
@@ -74,8 +85,11 @@ async fn test_run_validate_instance_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::ValidateInstance {
             gts_id: "test:instance:v1".to_owned(),
+            explain: false,
         },
     };
 
@@ -91,6 +105,8 @@ async fn test_run_resolve_relationships_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::ResolveRelationships {
             gts_id: "test:schema:v1".to_owned(),
         },
@@ -100,12 +116,133 @@ async fn test_run_resolve_relationships_command() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_run_resolve_traits_rejects_malformed_var() -> Result<()> {
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: None,
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::ResolveTraits {
+            schema_id: "gts.x.test.topic.v1~".to_owned(),
+            vars: vec!["env".to_owned()],
+            env: false,
+        },
+    };
+
+    let err = run_with_cli(cli).await.unwrap_err();
+    assert!(err.to_string().contains("NAME=VALUE"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_graph_command() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let base = "gts.x.test.graph.event.v1~";
+    fs::write(
+        temp_dir.path().join("schemas.json"),
+        serde_json::json!([
+            {
+                "$id": format!("gts://{base}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            },
+            {
+                "$id": format!("gts://{base}x.test.graph.placed.v1~"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "allOf": [{"$ref": format!("gts://{base}")}]
+            }
+        ])
+        .to_string(),
+    )?;
+    let out_dir = TempDir::new()?;
+    let out = out_dir.path().join("graph.json");
+
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Graph {
+            format: GraphFormat::Json,
+            instances: false,
+            out: Some(out.to_str().unwrap().to_owned()),
+        },
+    };
+    run_with_cli(cli).await?;
+
+    let graph: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out)?)?;
+    assert_eq!(
+        graph["nodes"][format!("{base}x.test.graph.placed.v1~")]["edges"],
+        serde_json::json!([{"to": base, "kind": "inherits"}])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_export_terraform_command() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let base = "gts.x.test.iac.topic.v1~";
+    let leaf = "gts.x.test.iac.topic.v1~x.test.iac.orders.v1~";
+    fs::write(
+        temp_dir.path().join("topic.schema.json"),
+        serde_json::json!({
+            "$id": format!("gts://{base}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "x-gts-traits-schema": {
+                "type": "object",
+                "properties": {"topicRef": {"type": "string"}}
+            }
+        })
+        .to_string(),
+    )?;
+    fs::write(
+        temp_dir.path().join("orders.schema.json"),
+        serde_json::json!({
+            "$id": format!("gts://{leaf}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "allOf": [{"$ref": format!("gts://{base}")}],
+            "x-gts-traits": {"topicRef": "${env}.orders"}
+        })
+        .to_string(),
+    )?;
+    let out_dir = TempDir::new()?;
+    let out = out_dir.path().join("kafka.tf.json");
+
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::ExportTerraform {
+            pattern: format!("{base}*"),
+            vars: vec!["env=dev".to_owned()],
+            env: false,
+            out: Some(out.to_str().unwrap().to_owned()),
+        },
+    };
+    run_with_cli(cli).await?;
+
+    let tf: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out)?)?;
+    assert_eq!(
+        tf["resource"]["kafka_topic"]["dev_orders"]["name"],
+        "dev.orders"
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_run_compatibility_command() -> Result<()> {
     let cli = Cli {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::Compatibility {
             old_schema_id: "test:schema:v1".to_owned(),
             new_schema_id: "test:schema:v2".to_owned(),
@@ -116,12 +253,53 @@ async fn test_run_compatibility_command() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_run_cast_all_command() -> Result<()> {
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: None,
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::CastAll {
+            pattern: "gts.x.test.ns.item.v1.0~*".to_owned(),
+            to_schema_id: "gts.x.test.ns.item.v1.1~".to_owned(),
+            write: false,
+        },
+    };
+
+    run_with_cli(cli).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_contract_command_missing_schema() -> Result<()> {
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: None,
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Contract {
+            producer: "gts.x.a.ns.event.v1~".to_owned(),
+            consumer: "gts.x.b.ns.view.v1~".to_owned(),
+            format: ReportFormat::Json,
+            out: None,
+        },
+    };
+
+    assert!(run_with_cli(cli).await.is_err());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_run_cast_command() -> Result<()> {
     let cli = Cli {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::Cast {
             from_id: "test:instance:v1".to_owned(),
             to_schema_id: "test:schema:v2".to_owned(),
@@ -138,9 +316,13 @@ async fn test_run_query_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::Query {
             expr: "test:*".to_owned(),
             limit: 10,
+            sort_by: None,
+            desc: false,
         },
     };
 
@@ -154,6 +336,8 @@ async fn test_run_attr_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::Attr {
             gts_with_path: "test:instance:v1@field.nested".to_owned(),
         },
@@ -169,6 +353,8 @@ async fn test_run_list_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::List { limit: 50 },
     };
 
@@ -185,6 +371,8 @@ async fn test_run_openapi_spec_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::OpenapiSpec {
             out: output_path.to_str().unwrap().to_owned(),
             host: "127.0.0.1".to_owned(),
@@ -216,10 +404,14 @@ async fn test_run_generate_from_rust_command() -> Result<()> {
         verbose: 0,
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::GenerateFromRust {
             source: source_path.to_str().unwrap().to_owned(),
             output: Some(output_path.to_str().unwrap().to_owned()),
             exclude: vec![],
+            check: false,
+            diff: false,
         },
     };
 
@@ -234,6 +426,8 @@ async fn test_run_with_verbose_flag() -> Result<()> {
         verbose: 2, // DEBUG level
         config: None,
         path: None,
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::ValidateId {
             gts_id: "test:schema:v1".to_owned(),
         },
@@ -256,6 +450,8 @@ async fn test_run_with_config_and_path() -> Result<()> {
         verbose: 0,
         config: Some(config_path.to_str().unwrap().to_owned()),
         path: Some(data_path.to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
         command: Commands::List { limit: 100 },
     };
 
@@ -263,8 +459,298 @@ async fn test_run_with_config_and_path() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_run_audit_majors_command() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join("schemas.json"),
+        r#"[
+            {"$id": "gts://gts.x.test.audit.order.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"},
+            {"$id": "gts://gts.x.test.audit.order.v2~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}
+        ]"#,
+    )?;
+
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Audit {
+            command: AuditCommand::Majors,
+        },
+    };
+
+    run_with_cli(cli).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_bench_compare_command() -> Result<()> {
+    let base = "gts.x.test.bench.order.v1~";
+    let snapshot = |extra: serde_json::Value| -> Result<TempDir> {
+        let dir = TempDir::new()?;
+        let mut schema = serde_json::json!({
+            "$id": format!("gts://{base}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {"id": {"type": "string"}}
+        });
+        schema["properties"]
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        fs::write(dir.path().join("order.schema.json"), schema.to_string())?;
+        fs::write(
+            dir.path().join("o1.json"),
+            serde_json::json!({"id": format!("{base}x.test._.o1.v1")}).to_string(),
+        )?;
+        Ok(dir)
+    };
+    let before = snapshot(serde_json::json!({}))?;
+    let after = snapshot(serde_json::json!({"note": {"type": "string"}}))?;
+    let work_dir = TempDir::new()?;
+    let workload = work_dir.path().join("workload.json");
+    fs::write(
+        &workload,
+        serde_json::json!({
+            "iterations": 5,
+            "operations": [
+                {"op": "validate", "id": format!("{base}x.test._.o1.v1")},
+                {"op": "query", "expr": "gts.x.test.bench.*"}
+            ]
+        })
+        .to_string(),
+    )?;
+    let out = work_dir.path().join("bench.json");
+
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: None,
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Bench {
+            command: BenchCommand::Compare {
+                before: before.path().to_str().unwrap().to_owned(),
+                after: after.path().to_str().unwrap().to_owned(),
+                workload: workload.to_str().unwrap().to_owned(),
+                format: ReportFormat::Json,
+                out: Some(out.to_str().unwrap().to_owned()),
+                max_regression: None,
+            },
+        },
+    };
+    run_with_cli(cli).await?;
+
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out)?)?;
+    assert_eq!(report["iterations"], 5);
+    assert_eq!(report["operations"].as_array().unwrap().len(), 2);
+    assert_eq!(report["before"]["operations"][0]["errors"], 0);
+    assert!(report["store_bytes_change_pct"].as_f64().unwrap() > 0.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_audit_refs_fails_on_dangling_refs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schemas = temp_dir.path().join("schemas.json");
+    fs::write(
+        &schemas,
+        r#"[
+            {"$id": "gts://gts.x.test.audit.order.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}
+        ]"#,
+    )?;
+    let audit = |path: &str| Cli {
+        verbose: 0,
+        config: None,
+        path: Some(path.to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Audit {
+            command: AuditCommand::Refs,
+        },
+    };
+    run_with_cli(audit(temp_dir.path().to_str().unwrap())).await?;
+
+    fs::write(
+        &schemas,
+        r#"[
+            {"$id": "gts://gts.x.test.audit.order.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object",
+             "properties": {"customer": {"$ref": "gts://gts.x.test.audit.customer.v1~"}}}
+        ]"#,
+    )?;
+    let err = run_with_cli(audit(temp_dir.path().to_str().unwrap()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "1 dangling references");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_lifecycle_set_writes_schema_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("order.json");
+    fs::write(
+        &schema_path,
+        r#"{"$id": "gts://gts.x.test.life.order.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}"#,
+    )?;
+
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Lifecycle {
+            command: LifecycleCommand::Set {
+                schema_id: "gts.x.test.life.order.v1~".to_owned(),
+                state: gts::GtsLifecycleState::Retired,
+                retire_on: None,
+                replacement: Some("gts.x.test.life.order.v2~".to_owned()),
+            },
+        },
+    };
+    run_with_cli(cli).await?;
+
+    let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&schema_path)?)?;
+    assert_eq!(written["x-gts-lifecycle"]["state"], "retired");
+    assert_eq!(
+        written["x-gts-lifecycle"]["replacement"],
+        "gts.x.test.life.order.v2~"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_resolves_aliases_and_id_suffixes() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    for name in ["order", "refund"] {
+        fs::write(
+            temp_dir.path().join(format!("{name}.json")),
+            format!(
+                r#"{{"$id": "gts://gts.x.test.alias.{name}.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}}"#
+            ),
+        )?;
+    }
+    let config_dir = TempDir::new()?;
+    let config_path = config_dir.path().join("gts.config.json");
+    fs::write(
+        &config_path,
+        r#"{"aliases": {"order": "gts.x.test.alias.order.v1~"}}"#,
+    )?;
+    let cli = |schema_id: &str| Cli {
+        verbose: 0,
+        config: Some(config_path.to_str().unwrap().to_owned()),
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Text,
+        command: Commands::Lifecycle {
+            command: LifecycleCommand::Set {
+                schema_id: schema_id.to_owned(),
+                state: gts::GtsLifecycleState::Deprecated,
+                retire_on: None,
+                replacement: None,
+            },
+        },
+    };
+
+    run_with_cli(cli("@order")).await?;
+    run_with_cli(cli("refund.v1~")).await?;
+    for name in ["order", "refund"] {
+        let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+            temp_dir.path().join(format!("{name}.json")),
+        )?)?;
+        assert_eq!(written["x-gts-lifecycle"]["state"], "deprecated");
+    }
+
+    let err = run_with_cli(cli("v1~")).await.unwrap_err();
+    assert!(err.to_string().contains("ambiguous"), "{err}");
+    let err = run_with_cli(cli("@missing")).await.unwrap_err();
+    assert!(err.to_string().contains("Unknown ID alias"), "{err}");
+    Ok(())
+}
+
 // Note: Server command test is omitted because it runs indefinitely
 // To test the server command, you would need to:
 // - Spawn it in a background task with a timeout
 // - Make HTTP requests to verify it's responding
 // - Gracefully shutdown the server
+
+/// A store with one valid and one invalid instance.
+fn store_with_invalid_instance() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    let schema = "gts.x.test.vall.item.v1~";
+    fs::write(
+        temp_dir.path().join("item.schema.json"),
+        serde_json::json!({
+            "$id": format!("gts://{schema}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "qty": {"type": "integer"}}
+        })
+        .to_string(),
+    )?;
+    for (name, qty) in [
+        ("a", serde_json::json!(1)),
+        ("b", serde_json::json!("many")),
+    ] {
+        fs::write(
+            temp_dir.path().join(format!("{name}.json")),
+            serde_json::json!({"id": format!("{schema}x.test._.{name}.v1"), "qty": qty})
+                .to_string(),
+        )?;
+    }
+    Ok(temp_dir)
+}
+
+#[tokio::test]
+async fn test_run_validate_all_command_fails_on_invalid_instance() -> Result<()> {
+    let temp_dir = store_with_invalid_instance()?;
+    let cli = Cli {
+        verbose: 0,
+        config: None,
+        path: Some(temp_dir.path().to_str().unwrap().to_owned()),
+        principal: None,
+        output: OutputFormat::Json,
+        command: Commands::ValidateAll { watch: None },
+    };
+    let err = run_with_cli(cli).await.unwrap_err();
+    assert_eq!(err.to_string(), "1 invalid instances");
+    Ok(())
+}
+
+#[test]
+fn test_failed_command_prints_one_envelope() -> Result<()> {
+    let temp_dir = store_with_invalid_instance()?;
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_gts"))
+        .args(["--output", "json", "--path"])
+        .arg(temp_dir.path())
+        .arg("validate-all")
+        .output()?;
+    assert!(!run.status.success());
+    let stdout = String::from_utf8(run.stdout)?;
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let envelope: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(envelope["ok"], false);
+    assert_eq!(envelope["error"], "1 invalid instances");
+    assert_eq!(envelope["result"]["failures"].as_array().unwrap().len(), 1);
+    assert_eq!(envelope["result"]["summary"]["failed"], 1);
+    assert!(!String::from_utf8(run.stderr)?.contains("summary"));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_config_fails_the_command() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let config = temp_dir.path().join("gts.toml");
+    fs::write(&config, "strict = \"sometimes\"\n")?;
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_gts"))
+        .arg("--config")
+        .arg(&config)
+        .arg("list")
+        .output()?;
+    assert!(!run.status.success());
+    assert!(String::from_utf8(run.stderr)?.contains("'strict'"));
+    Ok(())
+}
@@ -3,7 +3,7 @@ use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use gts::GtsOps;
 use gts_cli::server::{AppState, GtsHttpServer};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tower::ServiceExt;
 
 fn create_test_ops() -> GtsOps {
@@ -11,10 +11,30 @@ fn create_test_ops() -> GtsOps {
 }
 
 fn create_test_router(ops: GtsOps, verbose: u8) -> Router {
-    let state = AppState {
-        ops: Arc::new(Mutex::new(ops)),
+    GtsHttpServer::create_router(AppState::new(ops), verbose)
+}
+
+/// Credentials of the auditor of [`create_audited_router`], who may write
+/// every vendor's IDs and read as internal.
+const AUDITOR: &[(&str, &str)] = &[("x-api-key", "auditor-key")];
+
+fn create_audited_router(ops: GtsOps) -> Router {
+    let auth = gts_cli::auth::GtsAuthConfig {
+        api_keys: vec![gts_cli::auth::GtsApiKey {
+            key: "auditor-key".to_owned(),
+            principal: "auditor".to_owned(),
+            vendors: vec![gts_cli::auth::ANY_VENDOR.to_owned()],
+            role: gts::GtsVisibility::Internal,
+        }],
+        jwt: None,
     };
-    GtsHttpServer::create_router(state, verbose)
+    GtsHttpServer::create_router(
+        AppState {
+            auth: Arc::new(auth),
+            ..AppState::new(ops)
+        },
+        0,
+    )
 }
 
 #[tokio::test]
@@ -516,3 +536,663 @@ async fn test_attr_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
 }
+
+#[tokio::test]
+async fn test_labels_endpoints_and_label_query() {
+    let mut ops = create_test_ops();
+    ops.add_schema(
+        "gts.x.test.labels.item.v1~".to_owned(),
+        &serde_json::json!({
+            "$id": "gts.x.test.labels.item.v1~",
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        }),
+    );
+    let app = create_test_router(ops, 0);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/entities/gts.x.test.labels.item.v1~/labels")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"env": "prod"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["labels"]["env"], "prod");
+
+    for (selector, expected) in [("env=prod", 1), ("env=dev", 0)] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/query?expr=gts.x.*&labels={selector}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["count"], expected, "selector {selector}");
+    }
+}
+
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    send_with(app, &[], method, uri, body).await
+}
+
+#[allow(clippy::unwrap_used)]
+async fn send_with(
+    app: &Router,
+    headers: &[(&str, &str)],
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = headers
+        .iter()
+        .fold(Request::builder().method(method).uri(uri), |r, (k, v)| {
+            r.header(*k, *v)
+        });
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap())),
+        None => request.body(Body::empty()),
+    };
+    let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_rest_schema_entity_and_graph_routes() {
+    let schema_id = "gts.x.test.rest.item.v1~";
+    let instance_id = "gts.x.test.rest.item.v1~x.test._.a.v1";
+    let app = create_test_router(create_test_ops(), 0);
+
+    let (status, _) = send(&app, "GET", &format!("/schemas/{schema_id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["id", "n"],
+        "properties": {"id": {"type": "string"}, "n": {"type": "integer"}}
+    });
+    let (status, json) = send(&app, "PUT", &format!("/schemas/{schema_id}"), Some(schema)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["ok"], true);
+
+    let (status, json) = send(&app, "GET", &format!("/schemas/{schema_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["is_schema"], true);
+
+    let instance = serde_json::json!({"id": instance_id, "n": "x"});
+    send(&app, "POST", "/entities", Some(instance)).await;
+    let (status, _) = send(&app, "GET", &format!("/schemas/{instance_id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, json) = send(&app, "GET", &format!("/entities/{instance_id}"), None).await;
+    assert_eq!(json["content"]["n"], "x");
+
+    let (_, json) = send(
+        &app,
+        "POST",
+        "/validate",
+        Some(serde_json::json!({"id": instance_id})),
+    )
+    .await;
+    assert_eq!(json["ok"], false);
+
+    let (_, json) = send(
+        &app,
+        "GET",
+        "/query?pattern=gts.x.test.rest.*&limit=1",
+        None,
+    )
+    .await;
+    assert_eq!(json["count"], 1);
+
+    let (status, json) = send(&app, "GET", &format!("/graph/{instance_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["id"], instance_id);
+}
+
+#[tokio::test]
+async fn test_openapi_spec_describes_rest_routes() {
+    let server = GtsHttpServer::new(create_test_ops(), "127.0.0.1".to_owned(), 8000, 0);
+    let spec = server.openapi_spec();
+    let paths = &spec["paths"];
+
+    assert!(paths["/schemas/{gts_id}"]["get"].is_object());
+    assert!(paths["/schemas/{gts_id}"]["put"]["requestBody"].is_object());
+    assert_eq!(
+        paths["/graph/{gts_id}"]["get"]["parameters"][0]["in"],
+        "path"
+    );
+    assert_eq!(
+        paths["/validate"]["post"]["requestBody"]["content"]["application/json"]["schema"]["required"]
+            [0],
+        "id"
+    );
+    let query = paths["/query"]["get"]["parameters"].as_array().unwrap();
+    assert!(
+        query
+            .iter()
+            .any(|p| p["name"] == "limit" && p["required"] == false)
+    );
+    assert!(query.iter().any(|p| p["in"] == "header"));
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn test_writes_need_credentials_when_auth_is_on() {
+    let auth = gts_cli::auth::GtsAuthConfig {
+        api_keys: vec![
+            gts_cli::auth::GtsApiKey {
+                key: "acme-key".to_owned(),
+                principal: "acme-ci".to_owned(),
+                vendors: vec!["acme".to_owned()],
+                role: gts::GtsVisibility::Public,
+            },
+            gts_cli::auth::GtsApiKey {
+                key: "admin-key".to_owned(),
+                principal: "admin".to_owned(),
+                vendors: vec![gts_cli::auth::ANY_VENDOR.to_owned()],
+                role: gts::GtsVisibility::Public,
+            },
+        ],
+        jwt: None,
+    };
+    let app = GtsHttpServer::create_router(
+        AppState {
+            auth: Arc::new(auth),
+            ..AppState::new(create_test_ops())
+        },
+        0,
+    );
+    let schema_id = "gts.acme.billing.invoices.invoice.v1~";
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object"
+    });
+    let put = |key: Option<&str>, id: &str| {
+        let mut request = Request::builder()
+            .method("PUT")
+            .uri(format!("/schemas/{id}"))
+            .header("content-type", "application/json");
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        request
+            .body(Body::from(serde_json::to_vec(&schema).unwrap()))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(put(None, schema_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(response.headers().contains_key("www-authenticate"));
+    let response = app
+        .clone()
+        .oneshot(put(Some("wrong"), schema_id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = app
+        .clone()
+        .oneshot(put(Some("acme-key"), "gts.x.core.events.type.v1~"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = app
+        .clone()
+        .oneshot(put(Some("acme-key"), schema_id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Reads stay open
+    let (status, json) = send(&app, "GET", &format!("/schemas/{schema_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["is_schema"], true);
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/validate",
+        Some(serde_json::json!({"id": schema_id})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_schema_etags_and_conditional_get() {
+    let schema_id = "gts.x.test.cache.item.v1~";
+    let app = GtsHttpServer::create_router(
+        AppState {
+            cache_control: Some("public, max-age=300".into()),
+            ..AppState::new(create_test_ops())
+        },
+        0,
+    );
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object"
+    });
+    send(&app, "PUT", &format!("/schemas/{schema_id}"), Some(schema)).await;
+
+    let get = |if_none_match: Option<&str>| {
+        let mut request = Request::builder()
+            .method("GET")
+            .uri(format!("/schemas/{schema_id}"));
+        if let Some(tag) = if_none_match {
+            request = request.header("if-none-match", tag);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+    let response = app.clone().oneshot(get(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "public, max-age=300");
+    let etag = response.headers()["etag"].to_str().unwrap().to_owned();
+    assert!(etag.starts_with("W/\""));
+
+    let response = app.clone().oneshot(get(Some(&etag))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    let strong = etag.trim_start_matches("W/");
+    let response = app
+        .clone()
+        .oneshot(get(Some(&format!("\"stale\", {strong}"))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // A changed schema gets a new tag
+    let changed = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["id"]
+    });
+    send(&app, "PUT", &format!("/schemas/{schema_id}"), Some(changed)).await;
+    let response = app.clone().oneshot(get(Some(&etag))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()["etag"], etag.as_str());
+}
+
+#[tokio::test]
+async fn test_events_stream_changes_the_caller_may_see() {
+    use futures_util::StreamExt;
+
+    let app = create_audited_router(create_test_ops());
+    let subscribe = |uri: &str, role: &str| {
+        Request::builder()
+            .uri(uri)
+            .header("x-gts-role", role)
+            .header(AUDITOR[0].0, AUDITOR[0].1)
+            .body(Body::empty())
+            .unwrap()
+    };
+    let public = app
+        .clone()
+        .oneshot(subscribe("/events", "public"))
+        .await
+        .unwrap();
+    assert_eq!(public.status(), StatusCode::OK);
+    assert_eq!(public.headers()["content-type"], "text/event-stream");
+    let internal = app
+        .clone()
+        .oneshot(subscribe("/events?kinds=registered", "internal"))
+        .await
+        .unwrap();
+    let (status, _) = send(&app, "GET", "/events?kinds=renamed", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let schema = |id: &str, visibility: &str| {
+        serde_json::json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "x-gts-visibility": visibility
+        })
+    };
+    let hidden = "gts.x.test.events.audit.v1~";
+    let open = "gts.x.test.events.order.v1~";
+    send_with(
+        &app,
+        AUDITOR,
+        "PUT",
+        &format!("/schemas/{hidden}"),
+        Some(schema(hidden, "internal")),
+    )
+    .await;
+    send_with(
+        &app,
+        AUDITOR,
+        "PUT",
+        &format!("/schemas/{open}"),
+        Some(schema(open, "public")),
+    )
+    .await;
+
+    let next = |response: axum::response::Response| async move {
+        let mut stream = response.into_body().into_data_stream();
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        String::from_utf8(frame.to_vec()).unwrap()
+    };
+    let frame = next(public).await;
+    assert!(frame.starts_with("id: 2\n"), "{frame}");
+    let data: serde_json::Value =
+        serde_json::from_str(frame.lines().nth(1).unwrap().trim_start_matches("data: ")).unwrap();
+    assert_eq!(data["id"], open);
+    assert_eq!(data["kind"], "registered");
+    let frame = next(internal).await;
+    assert!(frame.contains(hidden), "{frame}");
+}
+
+#[tokio::test]
+async fn test_tenants_are_isolated_and_selected_by_prefix_or_header() {
+    let schema_id = "gts.x.test.tenants.item.v1~";
+    let tenant = |name: &str| (name.to_owned(), AppState::new(create_test_ops()));
+    let state = AppState {
+        tenants: Arc::new([tenant("billing"), tenant("search")].into_iter().collect()),
+        ..AppState::new(create_test_ops())
+    };
+    let app = GtsHttpServer::create_router(state, 0);
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object"
+    });
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &format!("/t/billing/schemas/{schema_id}"),
+        Some(schema),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let get = |uri: String, tenant: Option<&str>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some(tenant) = tenant {
+            request = request.header("x-gts-tenant", tenant);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+    let path = format!("/schemas/{schema_id}");
+    let status = |uri: &str, tenant| {
+        let response = get(uri.to_owned(), tenant);
+        async move { response.await.unwrap().status() }
+    };
+    assert_eq!(
+        status(&format!("/t/billing{path}"), None).await,
+        StatusCode::OK
+    );
+    assert_eq!(status(&path, Some("billing")).await, StatusCode::OK);
+    // Neither the default store nor another tenant sees it
+    assert_eq!(status(&path, None).await, StatusCode::NOT_FOUND);
+    assert_eq!(status(&path, Some("search")).await, StatusCode::NOT_FOUND);
+    assert_eq!(
+        status(&format!("/t/search{path}"), None).await,
+        StatusCode::NOT_FOUND
+    );
+    // The prefix wins over the header
+    assert_eq!(
+        status(&format!("/t/billing{path}"), Some("search")).await,
+        StatusCode::OK
+    );
+
+    let (status, body) = send(&app, "GET", &format!("/t/payroll{path}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Unknown tenant 'payroll'");
+    let response = get(path.clone(), Some("payroll")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_health_and_readiness_report_store_status() {
+    let dir = tempfile::tempdir().unwrap();
+    let schema_id = "gts.x.test.health.item.v1~";
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object"
+    });
+    std::fs::write(dir.path().join("item.schema.json"), schema.to_string()).unwrap();
+    let path = dir.path().to_string_lossy().into_owned();
+    let state = AppState {
+        tenants: Arc::new(
+            [("billing".to_owned(), AppState::new(create_test_ops()))]
+                .into_iter()
+                .collect(),
+        ),
+        ..AppState::new(GtsOps::new(Some(vec![path]), None, 0))
+    };
+    let app = GtsHttpServer::create_router(state, 0);
+
+    let (status, body) = send(&app, "GET", "/healthz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+
+    let (status, body) = send(&app, "GET", "/readyz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["schemas"], 1);
+    assert!(body["loaded_at"].is_string());
+    assert_eq!(body["backend"]["reachable"], true);
+    // A store without a reader has nothing to load or reach
+    assert_eq!(body["tenants"]["billing"]["status"], "ready");
+    assert!(body["tenants"]["billing"]["backend"].is_null());
+
+    drop(dir);
+    let (status, body) = send(&app, "GET", "/readyz", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["backend"]["reachable"], false);
+    assert_eq!(body["schemas"], 1);
+}
+
+#[tokio::test]
+async fn test_metrics_count_requests_and_validation_failures() {
+    let schema_id = "gts.x.test.metrics.item.v1~";
+    let instance_id = "gts.x.test.metrics.item.v1~x.test._.a.v1";
+    let app = create_test_router(create_test_ops(), 0);
+    let schema = serde_json::json!({
+        "$id": format!("gts://{schema_id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "properties": {"n": {"type": "integer"}}
+    });
+    send(
+        &app,
+        "PUT",
+        &format!("/schemas/{schema_id}"),
+        Some(schema.clone()),
+    )
+    .await;
+    let instance = serde_json::json!({"id": instance_id, "n": "x"});
+    send(&app, "POST", "/entities", Some(instance)).await;
+    let (_, body) = send(
+        &app,
+        "POST",
+        "/validate-instance",
+        Some(serde_json::json!({"instance_id": instance_id})),
+    )
+    .await;
+    assert_eq!(body["ok"], false);
+    send(&app, "GET", "/schemas/gts.x.test.metrics.missing.v1~", None).await;
+
+    // Failures of schemas the caller may not see stay out of the metrics
+    let internal_id = "gts.x.test.metrics.internal.v1~";
+    let mut internal = schema.clone();
+    internal["$id"] = format!("gts://{internal_id}").into();
+    internal["x-gts-visibility"] = "internal".into();
+    send(
+        &app,
+        "PUT",
+        &format!("/schemas/{internal_id}"),
+        Some(internal),
+    )
+    .await;
+    let hidden_id = format!("{internal_id}x.test._.a.v1");
+    let instance = serde_json::json!({"id": hidden_id, "n": "x"});
+    send(&app, "POST", "/entities", Some(instance)).await;
+    send(
+        &app,
+        "POST",
+        "/validate-instance",
+        Some(serde_json::json!({"instance_id": hidden_id})),
+    )
+    .await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain")
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    for line in [
+        "gts_http_requests_total{route=\"/schemas/{gts_id}\",method=\"PUT\",status=\"200\"} 2",
+        "gts_http_requests_total{route=\"/schemas/{gts_id}\",method=\"GET\",status=\"404\"} 1",
+        "gts_http_request_duration_seconds_count{route=\"/validate-instance\",method=\"POST\"} 2",
+        "gts_validation_failures_total{schema_id=\"gts.x.test.metrics.item.v1~\"} 1",
+        "gts_entities{kind=\"schema\"} 2",
+    ] {
+        assert!(
+            text.contains(&format!("{line}\n")),
+            "missing {line} in\n{text}"
+        );
+    }
+    assert!(!text.contains(internal_id), "{text}");
+}
+
+#[tokio::test]
+async fn test_routes_taking_ids_hide_entities_above_the_callers_role() {
+    let app = create_audited_router(create_test_ops());
+    let schema = |id: &str, visibility: &str| {
+        serde_json::json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "x-gts-visibility": visibility
+        })
+    };
+    let hidden = "gts.x.test.hide.audit.v1~";
+    let open = "gts.x.test.hide.order.v1~";
+    for (id, visibility) in [(hidden, "internal"), (open, "public")] {
+        let (status, _) = send_with(
+            &app,
+            AUDITOR,
+            "PUT",
+            &format!("/schemas/{id}"),
+            Some(schema(id, visibility)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let cast = serde_json::json!({"instance_id": open, "to_schema_id": hidden});
+    let routes = [
+        (
+            "GET",
+            format!("/resolve-relationships?gts_id={hidden}"),
+            None,
+        ),
+        ("GET", format!("/graph/{hidden}"), None),
+        (
+            "GET",
+            format!("/compatibility?old_schema_id={open}&new_schema_id={hidden}"),
+            None,
+        ),
+        ("POST", "/cast".to_owned(), Some(cast)),
+    ];
+    for (method, uri, body) in &routes {
+        let (status, json) = send(&app, method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{method} {uri}: {json}");
+        assert_eq!(json["id"], hidden, "{method} {uri}");
+    }
+
+    // Asking for a role takes credentials that allow it
+    let internal = [("x-gts-role", "internal")];
+    for (method, uri, body) in &routes {
+        let (status, _) = send_with(&app, &internal, method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+    for (method, uri, body) in &routes {
+        let (status, json) = send_with(&app, AUDITOR, method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::OK, "{method} {uri}: {json}");
+    }
+}
+
+#[tokio::test]
+async fn test_store_work_does_not_stall_other_requests() {
+    let state = AppState::new(create_test_ops());
+    let ops = state.ops.clone();
+    let app = GtsHttpServer::create_router(state, 0);
+
+    // Hold the store while a request waits for it
+    let (locked, holding) = std::sync::mpsc::channel();
+    let holder = std::thread::spawn(move || {
+        let _guard = ops.lock().unwrap();
+        locked.send(()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    });
+    holding.recv().unwrap();
+    let started = std::time::Instant::now();
+    let waiting = {
+        let app = app.clone();
+        tokio::spawn(async move { send(&app, "GET", "/entities", None).await })
+    };
+    tokio::task::yield_now().await;
+
+    // The single runtime thread of this test still serves other routes
+    let (status, _) = send(&app, "GET", "/healthz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() < std::time::Duration::from_millis(300));
+
+    holder.join().unwrap();
+    let (status, _) = waiting.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,19 @@
+//! Changes to the registered entities of a store.
+//!
+//! The `gts` store reports each change with its [`GtsChangeKind`].
+
+use serde::{Deserialize, Serialize};
+
+/// What happened to an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsChangeKind {
+    /// The entity was registered, or its content replaced.
+    Registered,
+    /// The schema moved to the deprecated lifecycle state.
+    Deprecated,
+    /// The schema moved to the retired lifecycle state.
+    Retired,
+    /// The entity was removed from the store.
+    Removed,
+}
@@ -0,0 +1,324 @@
+//! Stable diagnostic codes.
+//!
+//! Every error the GTS crates report maps to a [`GtsCode`] such as
+//! `GTS0012` (a derived schema loosens `maxLength`).  Messages may be
+//! reworded between releases; codes never change meaning and are never
+//! reused, so CI tooling can suppress or route failures by code.
+//!
+//! Codes are grouped by range:
+//!
+//! | Range     | Area                                            |
+//! |-----------|-------------------------------------------------|
+//! | `GTS00xx` | Schema chain compatibility (derived vs. base)   |
+//! | `GTS01xx` | Store operations                                |
+//! | `GTS02xx` | Instance casting                                |
+//! | `GTS03xx` | `x-gts-ref` constraints                         |
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GtsCode {
+    /// A derived schema sets an inherited property to `false`.
+    PropertyDisabled,
+    /// A derived schema adds a property its base closes with
+    /// `additionalProperties: false`.
+    PropertyAdded,
+    /// A derived schema drops the base's `additionalProperties: false`.
+    AdditionalPropertiesLoosened,
+    /// A derived schema no longer requires a field the base requires.
+    RequiredRemoved,
+    /// A derived schema replaces a property schema with `true` or another
+    /// non-object value.
+    PropertyUnconstrained,
+    TypeChanged,
+    ConstChanged,
+    ConstOmitted,
+    PatternChanged,
+    PatternOmitted,
+    /// A derived schema omits the base `enum` or allows values outside it.
+    EnumLoosened,
+    MaxLengthLoosened,
+    MaximumLoosened,
+    MaxItemsLoosened,
+    MinLengthLoosened,
+    MinimumLoosened,
+    MinItemsLoosened,
+    ItemsOmitted,
+
+    ObjectNotFound,
+    SchemaNotFound,
+    EntityNotFound,
+    SchemaForInstanceNotFound,
+    CastFromSchemaNotAllowed,
+    InvalidEntity,
+    InvalidSchemaId,
+    /// Validation failed; the message says why.
+    ValidationFailed,
+    InvalidRef,
+    AsyncTask,
+    SchemaRetired,
+    NoWriter,
+    WriteFailed,
+    UnresolvedReferences,
+    PolicyViolation,
+    RegistrationDenied,
+    InvalidBundle,
+    InvalidSignature,
+    UnknownAlias,
+    AmbiguousId,
+    /// A schema is not compatible with its base; the `GTS00xx` codes say how.
+    IncompatibleSchema,
+    /// `x-gts-ref` values point at missing or mistyped entities; the
+    /// `GTS030x` codes say which.
+    BrokenReference,
+    /// An entity cannot be removed while others still reference it.
+    RemovalBlocked,
+
+    CastInternal,
+    CastTargetNotSchema,
+    CastSourceNotSchema,
+    CastInstanceNotObject,
+    CastFailed,
+
+    /// A value does not match the `x-gts-ref` pattern of its field.
+    XGtsRefMismatch,
+    /// A value in an `x-gts-ref` field is not a GTS ID.
+    XGtsRefInvalidValue,
+    /// An `x-gts-ref` in a schema is not a valid GTS ID, pattern or pointer.
+    XGtsRefInvalidPattern,
+    /// An `x-gts-ref` pointer does not resolve within the schema.
+    XGtsRefUnresolved,
+    /// No (or more than one) `oneOf` / `anyOf` branch satisfies the
+    /// `x-gts-ref` constraints.
+    XGtsRefBranch,
+    /// An `x-gts-ref` value names an entity that is not in the store.
+    XGtsRefMissingTarget,
+    /// An `x-gts-ref` value names an instance that does not validate
+    /// against its schema.
+    XGtsRefInvalidTarget,
+}
+
+impl GtsCode {
+    /// Every code, in code order.
+    pub const ALL: &[GtsCode] = &[
+        GtsCode::PropertyDisabled,
+        GtsCode::PropertyAdded,
+        GtsCode::AdditionalPropertiesLoosened,
+        GtsCode::RequiredRemoved,
+        GtsCode::PropertyUnconstrained,
+        GtsCode::TypeChanged,
+        GtsCode::ConstChanged,
+        GtsCode::ConstOmitted,
+        GtsCode::PatternChanged,
+        GtsCode::PatternOmitted,
+        GtsCode::EnumLoosened,
+        GtsCode::MaxLengthLoosened,
+        GtsCode::MaximumLoosened,
+        GtsCode::MaxItemsLoosened,
+        GtsCode::MinLengthLoosened,
+        GtsCode::MinimumLoosened,
+        GtsCode::MinItemsLoosened,
+        GtsCode::ItemsOmitted,
+        GtsCode::ObjectNotFound,
+        GtsCode::SchemaNotFound,
+        GtsCode::EntityNotFound,
+        GtsCode::SchemaForInstanceNotFound,
+        GtsCode::CastFromSchemaNotAllowed,
+        GtsCode::InvalidEntity,
+        GtsCode::InvalidSchemaId,
+        GtsCode::ValidationFailed,
+        GtsCode::InvalidRef,
+        GtsCode::AsyncTask,
+        GtsCode::SchemaRetired,
+        GtsCode::NoWriter,
+        GtsCode::WriteFailed,
+        GtsCode::UnresolvedReferences,
+        GtsCode::PolicyViolation,
+        GtsCode::RegistrationDenied,
+        GtsCode::InvalidBundle,
+        GtsCode::InvalidSignature,
+        GtsCode::UnknownAlias,
+        GtsCode::AmbiguousId,
+        GtsCode::IncompatibleSchema,
+        GtsCode::BrokenReference,
+        GtsCode::RemovalBlocked,
+        GtsCode::CastInternal,
+        GtsCode::CastTargetNotSchema,
+        GtsCode::CastSourceNotSchema,
+        GtsCode::CastInstanceNotObject,
+        GtsCode::CastFailed,
+        GtsCode::XGtsRefMismatch,
+        GtsCode::XGtsRefInvalidValue,
+        GtsCode::XGtsRefInvalidPattern,
+        GtsCode::XGtsRefUnresolved,
+        GtsCode::XGtsRefBranch,
+        GtsCode::XGtsRefMissingTarget,
+        GtsCode::XGtsRefInvalidTarget,
+    ];
+
+    /// The code as printed, e.g. `GTS0012`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            GtsCode::PropertyDisabled => "GTS0001",
+            GtsCode::PropertyAdded => "GTS0002",
+            GtsCode::AdditionalPropertiesLoosened => "GTS0003",
+            GtsCode::RequiredRemoved => "GTS0004",
+            GtsCode::PropertyUnconstrained => "GTS0005",
+            GtsCode::TypeChanged => "GTS0006",
+            GtsCode::ConstChanged => "GTS0007",
+            GtsCode::ConstOmitted => "GTS0008",
+            GtsCode::PatternChanged => "GTS0009",
+            GtsCode::PatternOmitted => "GTS0010",
+            GtsCode::EnumLoosened => "GTS0011",
+            GtsCode::MaxLengthLoosened => "GTS0012",
+            GtsCode::MaximumLoosened => "GTS0013",
+            GtsCode::MaxItemsLoosened => "GTS0014",
+            GtsCode::MinLengthLoosened => "GTS0015",
+            GtsCode::MinimumLoosened => "GTS0016",
+            GtsCode::MinItemsLoosened => "GTS0017",
+            GtsCode::ItemsOmitted => "GTS0018",
+            GtsCode::ObjectNotFound => "GTS0101",
+            GtsCode::SchemaNotFound => "GTS0102",
+            GtsCode::EntityNotFound => "GTS0103",
+            GtsCode::SchemaForInstanceNotFound => "GTS0104",
+            GtsCode::CastFromSchemaNotAllowed => "GTS0105",
+            GtsCode::InvalidEntity => "GTS0106",
+            GtsCode::InvalidSchemaId => "GTS0107",
+            GtsCode::ValidationFailed => "GTS0108",
+            GtsCode::InvalidRef => "GTS0109",
+            GtsCode::AsyncTask => "GTS0110",
+            GtsCode::SchemaRetired => "GTS0111",
+            GtsCode::NoWriter => "GTS0112",
+            GtsCode::WriteFailed => "GTS0113",
+            GtsCode::UnresolvedReferences => "GTS0114",
+            GtsCode::PolicyViolation => "GTS0115",
+            GtsCode::RegistrationDenied => "GTS0116",
+            GtsCode::InvalidBundle => "GTS0117",
+            GtsCode::InvalidSignature => "GTS0118",
+            GtsCode::UnknownAlias => "GTS0119",
+            GtsCode::AmbiguousId => "GTS0120",
+            GtsCode::IncompatibleSchema => "GTS0121",
+            GtsCode::BrokenReference => "GTS0122",
+            GtsCode::RemovalBlocked => "GTS0123",
+            GtsCode::CastInternal => "GTS0201",
+            GtsCode::CastTargetNotSchema => "GTS0202",
+            GtsCode::CastSourceNotSchema => "GTS0203",
+            GtsCode::CastInstanceNotObject => "GTS0204",
+            GtsCode::CastFailed => "GTS0205",
+            GtsCode::XGtsRefMismatch => "GTS0301",
+            GtsCode::XGtsRefInvalidValue => "GTS0302",
+            GtsCode::XGtsRefInvalidPattern => "GTS0303",
+            GtsCode::XGtsRefUnresolved => "GTS0304",
+            GtsCode::XGtsRefBranch => "GTS0305",
+            GtsCode::XGtsRefMissingTarget => "GTS0306",
+            GtsCode::XGtsRefInvalidTarget => "GTS0307",
+        }
+    }
+
+    /// The code for a derived schema loosening bound `keyword` of its base,
+    /// e.g. `maxLength` → `GTS0012`.
+    #[must_use]
+    pub fn loosened_bound(keyword: &str) -> Option<GtsCode> {
+        match keyword {
+            "maxLength" => Some(GtsCode::MaxLengthLoosened),
+            "maximum" => Some(GtsCode::MaximumLoosened),
+            "maxItems" => Some(GtsCode::MaxItemsLoosened),
+            "minLength" => Some(GtsCode::MinLengthLoosened),
+            "minimum" => Some(GtsCode::MinimumLoosened),
+            "minItems" => Some(GtsCode::MinItemsLoosened),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GtsCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown diagnostic code '{0}'")]
+pub struct UnknownGtsCode(pub String);
+
+impl FromStr for GtsCode {
+    type Err = UnknownGtsCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GtsCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownGtsCode(s.to_owned()))
+    }
+}
+
+impl Serialize for GtsCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for GtsCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// One coded, machine-readable problem.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsDiagnostic {
+    pub code: GtsCode,
+    pub message: String,
+}
+
+impl GtsDiagnostic {
+    #[must_use]
+    pub fn new(code: GtsCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GtsDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_codes_are_unique_and_round_trip() {
+        let printed: HashSet<&str> = GtsCode::ALL.iter().map(|c| c.as_str()).collect();
+        assert_eq!(printed.len(), GtsCode::ALL.len());
+        for code in GtsCode::ALL {
+            assert_eq!(code.as_str().parse::<GtsCode>().unwrap(), *code);
+            let json = serde_json::to_string(code).unwrap();
+            assert_eq!(serde_json::from_str::<GtsCode>(&json).unwrap(), *code);
+        }
+        assert_eq!(GtsCode::MaxLengthLoosened.as_str(), "GTS0012");
+        assert!("GTS9999".parse::<GtsCode>().is_err());
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = GtsDiagnostic::new(GtsCode::MaxLengthLoosened, "maxLength grew");
+        assert_eq!(diagnostic.to_string(), "GTS0012: maxLength grew");
+        assert_eq!(
+            serde_json::to_value(&diagnostic).unwrap(),
+            serde_json::json!({"code": "GTS0012", "message": "maxLength grew"})
+        );
+    }
+}
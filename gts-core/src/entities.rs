@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::gts::{GTS_URI_PREFIX, GtsID};
+use crate::lifecycle::GtsRetiredSchemaPolicy;
+use crate::numbers::GtsNumberPolicy;
+use crate::ownership::GtsNamespaceRule;
 use crate::path_resolver::JsonPathResolver;
+use crate::ref_integrity::GtsRefIntegrity;
+use crate::sandbox::GtsSandboxLimits;
 use crate::schema_cast::{GtsEntityCastResult, SchemaCastError};
+use crate::schema_resolution::GtsSchemaResolution;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
@@ -58,10 +64,80 @@ impl GtsFile {
     }
 }
 
+/// Where IDs live in documents of an existing format, tried before
+/// `entity_id_fields` and `schema_id_fields`.
+///
+/// Entries are top-level field names (`"entityId"`) or JSON Pointers into
+/// the document (`"/meta/kind"`); the first with a non-empty string wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GtsIdFields {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entity_id: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schema_id: Vec<String>,
+}
+
+impl GtsIdFields {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entity_id.is_empty() && self.schema_id.is_empty()
+    }
+}
+
+/// How entities are read and validated.
+///
+/// New settings are added over time, so the struct is `#[non_exhaustive]`:
+/// start from [`GtsConfig::default`] and set the fields you need.
+///
+/// ```
+/// let mut cfg = gts_core::GtsConfig::default();
+/// cfg.entity_id_fields = vec!["entityId".to_owned()];
+/// cfg.strict = true;
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct GtsConfig {
+    /// Fields holding an entity's ID, in order; entries starting with `/`
+    /// are JSON Pointers.
     pub entity_id_fields: Vec<String>,
+    /// Fields holding an instance's schema ID, in order; entries starting
+    /// with `/` are JSON Pointers.
     pub schema_id_fields: Vec<String>,
+    /// Custom ID locations tried before the two lists above.
+    #[serde(default, skip_serializing_if = "GtsIdFields::is_empty")]
+    pub id_fields: GtsIdFields,
+    #[serde(default)]
+    pub numbers: GtsNumberPolicy,
+    /// Short names accepted as `@name` wherever an entity ID is expected.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Resource limits for validating against untrusted schemas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<GtsSandboxLimits>,
+    /// How instances find their schema when the declared minor version is
+    /// not registered.
+    #[serde(default)]
+    pub schema_resolution: GtsSchemaResolution,
+    /// Whether `x-gts-ref` values must point at entities in the store.
+    #[serde(default)]
+    pub ref_integrity: GtsRefIntegrity,
+    /// Whether validating against a retired schema fails or only warns.
+    #[serde(default)]
+    pub retired_schemas: GtsRetiredSchemaPolicy,
+    /// Namespaces that only their owners may register entities in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<GtsNamespaceRule>,
+    /// Where entities are read from when no path is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_dir: Option<String>,
+    /// `$schema` URI assumed for schemas that declare none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<String>,
+    /// Tightens validation: integers must be integral and `x-gts-ref`
+    /// targets must exist, whatever `numbers` and `ref_integrity` say.
+    #[serde(default)]
+    pub strict: bool,
 }
 
 impl Default for GtsConfig {
@@ -88,6 +164,39 @@ impl Default for GtsConfig {
                 "type".to_owned(),
                 "schema".to_owned(),
             ],
+            id_fields: GtsIdFields::default(),
+            numbers: GtsNumberPolicy::default(),
+            aliases: BTreeMap::new(),
+            sandbox: None,
+            schema_resolution: GtsSchemaResolution::default(),
+            ref_integrity: GtsRefIntegrity::default(),
+            retired_schemas: GtsRetiredSchemaPolicy::default(),
+            namespaces: Vec::new(),
+            schema_dir: None,
+            dialect: None,
+            strict: false,
+        }
+    }
+}
+
+impl GtsConfig {
+    /// The number policy in effect: `numbers`, with strict integers under
+    /// `strict`.
+    #[must_use]
+    pub fn number_policy(&self) -> GtsNumberPolicy {
+        GtsNumberPolicy {
+            strict_integers: self.numbers.strict_integers || self.strict,
+            ..self.numbers
+        }
+    }
+
+    /// The ref integrity in effect: `ref_integrity`, at least `exists` under
+    /// `strict`.
+    #[must_use]
+    pub fn ref_integrity_policy(&self) -> GtsRefIntegrity {
+        match self.ref_integrity {
+            GtsRefIntegrity::Off if self.strict => GtsRefIntegrity::Exists,
+            integrity => integrity,
         }
     }
 }
@@ -370,7 +479,7 @@ impl GtsEntity {
 
     /// Get the id field value from `entity_id_fields` config
     fn get_id_field_value(&mut self, cfg: &GtsConfig) -> Option<String> {
-        for f in &cfg.entity_id_fields {
+        for f in cfg.id_fields.entity_id.iter().chain(&cfg.entity_id_fields) {
             // Skip $schema and type fields - they're not entity IDs
             if f == "$schema" || f == "type" {
                 continue;
@@ -385,7 +494,7 @@ impl GtsEntity {
 
     /// Get the type/schema field value from `schema_id_fields` config
     fn get_type_field_value(&mut self, cfg: &GtsConfig) -> Option<String> {
-        for f in &cfg.schema_id_fields {
+        for f in cfg.id_fields.schema_id.iter().chain(&cfg.schema_id_fields) {
             // Skip $schema for instances - it's not a valid field for instances
             if f == "$schema" {
                 continue;
@@ -404,7 +513,8 @@ impl GtsEntity {
 
     /// Legacy method for backwards compatibility
     fn calc_json_entity_id_legacy(&mut self, cfg: &GtsConfig) -> Option<String> {
-        self.first_non_empty_field(&cfg.entity_id_fields)
+        let fields = cfg.id_fields.entity_id.iter().chain(&cfg.entity_id_fields);
+        self.first_non_empty_field(fields)
     }
 
     #[must_use]
@@ -573,9 +683,14 @@ impl GtsEntity {
         Self::deduplicate_by_id_and_path(refs)
     }
 
+    /// The trimmed string at `field`, a top-level key or a JSON Pointer.
     fn get_field_value(&self, field: &str) -> Option<String> {
         if let Some(obj) = self.content.as_object()
-            && let Some(v) = obj.get(field)
+            && let Some(v) = if field.starts_with('/') {
+                self.content.pointer(field)
+            } else {
+                obj.get(field)
+            }
             && let Some(s) = v.as_str()
         {
             let trimmed = s.trim();
@@ -600,9 +715,12 @@ impl GtsEntity {
         None
     }
 
-    fn first_non_empty_field(&mut self, fields: &[String]) -> Option<String> {
+    fn first_non_empty_field<'a>(
+        &mut self,
+        fields: impl Iterator<Item = &'a String> + Clone,
+    ) -> Option<String> {
         // First pass: look for valid GTS IDs
-        for f in fields {
+        for f in fields.clone() {
             if let Some(v) = self.get_field_value(f)
                 && GtsID::is_valid(&v)
             {
@@ -622,6 +740,26 @@ impl GtsEntity {
         None
     }
 
+    /// Field paths a schema asks stores to index, from its `x-gts-index`
+    /// annotation (e.g. `["payload.user_id", "payload.order_id"]`).  Paths are
+    /// dotted and relative to the instance root.  Always empty for instances.
+    #[must_use]
+    pub fn index_hints(&self) -> Vec<String> {
+        if !self.is_schema {
+            return Vec::new();
+        }
+        self.content
+            .get("x-gts-index")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
     /// Returns the effective ID for this entity (for store indexing and CLI output).
     /// - For schemas: the GTS ID from `$id` field
     /// - For well-known instances: the GTS ID from `id` field
@@ -919,6 +1057,63 @@ mod tests {
         assert!(cfg.schema_id_fields.contains(&"gtsTid".to_owned()));
     }
 
+    #[test]
+    fn test_id_fields_map_custom_names_and_pointers() {
+        let cfg: GtsConfig = serde_json::from_value(json!({
+            "entity_id_fields": ["id"],
+            "schema_id_fields": ["type"],
+            "id_fields": {"entity_id": ["entityId"], "schema_id": ["/meta/kind"]}
+        }))
+        .unwrap();
+        let content = json!({
+            "entityId": "7a1d2f34-5678-4abc-9def-0123456789ab",
+            "id": "ignored-because-entityId-comes-first",
+            "meta": {"kind": "gts.x.test.mapping.order.v1~"}
+        });
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        assert_eq!(
+            entity.instance_id.as_deref(),
+            Some("7a1d2f34-5678-4abc-9def-0123456789ab")
+        );
+        assert_eq!(entity.selected_entity_field.as_deref(), Some("entityId"));
+        assert_eq!(
+            entity.schema_id.as_deref(),
+            Some("gts.x.test.mapping.order.v1~")
+        );
+        assert_eq!(
+            entity.selected_schema_id_field.as_deref(),
+            Some("/meta/kind")
+        );
+
+        // Without a match the configured lists still apply
+        let content = json!({"id": "gts.x.test.mapping.order.v1~x.test._.one.v1"});
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        assert_eq!(
+            entity.gts_id.map(|id| id.id).as_deref(),
+            Some("gts.x.test.mapping.order.v1~x.test._.one.v1")
+        );
+    }
+
     #[test]
     fn test_json_entity_with_validation_result() {
         let content = json!({"id": "gts.vendor.package.namespace.type.v1.0"});
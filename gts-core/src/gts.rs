@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -91,6 +92,47 @@ impl GtsIdSegment {
         self.is_uuid_tail = parsed.is_uuid_tail;
         Ok(())
     }
+
+    /// Whether both segments name the same type at the same major version.
+    #[must_use]
+    pub fn same_type_as(&self, other: &GtsIdSegment) -> bool {
+        self.vendor == other.vendor
+            && self.package == other.package
+            && self.namespace == other.namespace
+            && self.type_name == other.type_name
+            && self.ver_major == other.ver_major
+    }
+}
+
+/// Orders by vendor, package, namespace and type name, then semantically by
+/// version (`v1` < `v1.0` < `v1.2` < `v1.10` < `v2`).
+impl Ord for GtsIdSegment {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            &self.vendor,
+            &self.package,
+            &self.namespace,
+            &self.type_name,
+            self.ver_major,
+            self.ver_minor,
+        )
+            .cmp(&(
+                &other.vendor,
+                &other.package,
+                &other.namespace,
+                &other.type_name,
+                other.ver_major,
+                other.ver_minor,
+            ))
+            .then_with(|| self.segment.cmp(&other.segment))
+            .then_with(|| (self.num, self.offset).cmp(&(other.num, other.offset)))
+    }
+}
+
+impl PartialOrd for GtsIdSegment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 /// GTS ID - a validated Global Type System identifier.
@@ -189,6 +231,36 @@ impl GtsID {
         Some(format!("{GTS_PREFIX}{segments}"))
     }
 
+    /// Whether `other` is the same type at the same major version, possibly
+    /// at another minor: all segments but the last are identical, and the
+    /// last names the same type and major.  Schemas and instances never
+    /// match each other.
+    #[must_use]
+    pub fn same_type_as(&self, other: &GtsID) -> bool {
+        let (Some((last, prefix)), Some((other_last, other_prefix))) = (
+            self.gts_id_segments.split_last(),
+            other.gts_id_segments.split_last(),
+        ) else {
+            return false;
+        };
+        prefix == other_prefix
+            && last.is_type == other_last.is_type
+            && last.same_type_as(other_last)
+    }
+
+    /// Whether this is a newer minor version of the same type as `other`.
+    #[must_use]
+    pub fn is_newer_than(&self, other: &GtsID) -> bool {
+        self.same_type_as(other) && self > other
+    }
+
+    /// The newest of `ids` by version, e.g. the latest minor among the
+    /// versions of one type (see [`GtsID::same_type_as`]).  IDs of different
+    /// types compare by name first, so filter before mixing them.
+    pub fn latest_of<'a>(ids: impl IntoIterator<Item = &'a GtsID>) -> Option<&'a GtsID> {
+        ids.into_iter().max()
+    }
+
     /// Generate a deterministic UUID v5 from this GTS ID.
     #[must_use]
     pub fn to_uuid(&self) -> Uuid {
@@ -326,6 +398,22 @@ impl GtsID {
     }
 }
 
+/// Orders segment by segment (see [`GtsIdSegment`]), so the versions of one
+/// type sort oldest to newest.
+impl Ord for GtsID {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.gts_id_segments
+            .cmp(&other.gts_id_segments)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for GtsID {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Display for GtsID {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.id)
@@ -346,6 +434,156 @@ impl AsRef<str> for GtsID {
     }
 }
 
+impl serde::Serialize for GtsID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.id)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for GtsID {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        GtsID::new(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Builds a [`GtsID`] from its parts instead of by string formatting.
+///
+/// The vendor / package / namespace / type / version setters describe the
+/// last segment; [`GtsIdBuilder::chain`] adds a segment before it, such as the
+/// base type of a derived schema.  The result is validated like any parsed ID.
+///
+/// ```
+/// use gts_core::GtsIdBuilder;
+///
+/// let id = GtsIdBuilder::new()
+///     .chain("x.core.events.type.v1")
+///     .vendor("x")
+///     .package("shop")
+///     .namespace("orders")
+///     .type_name("placed")
+///     .version(1, Some(2))
+///     .schema()
+///     .unwrap();
+/// assert_eq!(id.id, "gts.x.core.events.type.v1~x.shop.orders.placed.v1.2~");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GtsIdBuilder {
+    chain: Vec<String>,
+    vendor: String,
+    package: String,
+    namespace: String,
+    type_name: String,
+    version: Option<(u32, Option<u32>)>,
+}
+
+impl GtsIdBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn vendor(mut self, vendor: &str) -> Self {
+        vendor.clone_into(&mut self.vendor);
+        self
+    }
+
+    #[must_use]
+    pub fn package(mut self, package: &str) -> Self {
+        package.clone_into(&mut self.package);
+        self
+    }
+
+    #[must_use]
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        namespace.clone_into(&mut self.namespace);
+        self
+    }
+
+    #[must_use]
+    pub fn type_name(mut self, type_name: &str) -> Self {
+        type_name.clone_into(&mut self.type_name);
+        self
+    }
+
+    #[must_use]
+    pub fn version(mut self, major: u32, minor: Option<u32>) -> Self {
+        self.version = Some((major, minor));
+        self
+    }
+
+    /// Adds a segment (without the `gts.` prefix or trailing `~`, e.g.
+    /// `x.core.events.type.v1`) in front of the one being built.
+    #[must_use]
+    pub fn chain(mut self, segment: &str) -> Self {
+        self.chain.push(
+            segment
+                .trim_start_matches(GTS_PREFIX)
+                .trim_end_matches('~')
+                .to_owned(),
+        );
+        self
+    }
+
+    /// Builds a schema (type) ID, ending in `~`.
+    ///
+    /// # Errors
+    /// Returns `GtsError::Id` if a part is missing, or the usual parse errors
+    /// if a part is invalid.
+    pub fn schema(&self) -> Result<GtsID, GtsError> {
+        GtsID::new(&format!("{}~", self.render()?))
+    }
+
+    /// Builds a well-known instance ID, without the trailing `~`.
+    ///
+    /// # Errors
+    /// Returns `GtsError::Id` if a part is missing, or the usual parse errors
+    /// if a part is invalid.
+    pub fn instance(&self) -> Result<GtsID, GtsError> {
+        GtsID::new(&self.render()?)
+    }
+
+    fn render(&self) -> Result<String, GtsError> {
+        let missing = [
+            ("vendor", self.vendor.is_empty()),
+            ("package", self.package.is_empty()),
+            ("namespace", self.namespace.is_empty()),
+            ("type_name", self.type_name.is_empty()),
+            ("version", self.version.is_none()),
+        ]
+        .iter()
+        .filter(|(_, is_missing)| *is_missing)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+        let last = match self.version {
+            Some((major, minor)) if missing.is_empty() => {
+                let version =
+                    minor.map_or_else(|| format!("v{major}"), |m| format!("v{major}.{m}"));
+                format!(
+                    "{}.{}.{}.{}.{version}",
+                    self.vendor, self.package, self.namespace, self.type_name
+                )
+            }
+            _ => {
+                return Err(GtsError::Id {
+                    id: format!("{GTS_PREFIX}{}", self.chain.join("~")),
+                    cause: format!("Missing {}", missing.join(", ")),
+                });
+            }
+        };
+        let mut segments = self.chain.clone();
+        segments.push(last);
+        Ok(format!("{GTS_PREFIX}{}", segments.join("~")))
+    }
+}
+
 /// GTS Wildcard pattern
 #[derive(Debug, Clone, PartialEq)]
 pub struct GtsWildcard {
@@ -466,7 +704,7 @@ impl From<GtsEntityId> for String {
 /// # Example
 ///
 /// ```
-/// use gts::GtsInstanceId;
+/// use gts_core::GtsInstanceId;
 ///
 /// let id = GtsInstanceId::new("gts.x.core.events.topic.v1~", "vendor.app.orders.v1.0");
 /// assert_eq!(id.as_ref(), "gts.x.core.events.topic.v1~vendor.app.orders.v1.0");
@@ -533,7 +771,7 @@ impl GtsInstanceId {
     ///
     /// # Example
     /// ```
-    /// use gts::gts::GtsInstanceId;
+    /// use gts_core::gts::GtsInstanceId;
     ///
     /// let schema = GtsInstanceId::json_schema_value();
     /// assert_eq!(schema["type"], "string");
@@ -626,7 +864,7 @@ impl PartialEq<String> for GtsInstanceId {
 /// # Example
 ///
 /// ```
-/// use gts::gts::GtsSchemaId;
+/// use gts_core::gts::GtsSchemaId;
 ///
 /// let id = GtsSchemaId::new("gts.x.core.events.topic.v1~vendor.app.orders.v1.0~");
 /// assert_eq!(id.as_ref(), "gts.x.core.events.topic.v1~vendor.app.orders.v1.0~");
@@ -693,7 +931,7 @@ impl GtsSchemaId {
     ///
     /// # Example
     /// ```
-    /// use gts::gts::GtsSchemaId;
+    /// use gts_core::gts::GtsSchemaId;
     ///
     /// let schema = GtsSchemaId::json_schema_value();
     /// assert_eq!(schema["type"], "string");
@@ -781,6 +1019,123 @@ impl PartialEq<String> for GtsSchemaId {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_gts_id_builder() {
+        let base = GtsIdBuilder::new()
+            .vendor("x")
+            .package("core")
+            .namespace("events")
+            .type_name("type")
+            .version(1, None);
+        assert_eq!(
+            base.schema().expect("test").id,
+            "gts.x.core.events.type.v1~"
+        );
+
+        let derived = GtsIdBuilder::new()
+            .chain("gts.x.core.events.type.v1~")
+            .vendor("x")
+            .package("shop")
+            .namespace("orders")
+            .type_name("placed")
+            .version(1, Some(2));
+        let id = derived.schema().expect("test");
+        assert_eq!(id.gts_id_segments.len(), 2);
+        assert_eq!(id.gts_id_segments[1].ver_minor, Some(2));
+
+        let instance = GtsIdBuilder::new()
+            .chain(&id.id)
+            .vendor("x")
+            .package("shop")
+            .namespace("_")
+            .type_name("o1")
+            .version(1, None)
+            .instance()
+            .expect("test");
+        assert_eq!(
+            instance.to_string(),
+            "gts.x.core.events.type.v1~x.shop.orders.placed.v1.2~x.shop._.o1.v1"
+        );
+
+        let err = GtsIdBuilder::new().vendor("x").schema().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Missing package, namespace, type_name, version")
+        );
+        assert!(
+            GtsIdBuilder::new()
+                .vendor("X")
+                .package("core")
+                .namespace("events")
+                .type_name("type")
+                .version(1, None)
+                .schema()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_gts_id_version_ordering() {
+        let ids: Vec<GtsID> = [
+            "gts.x.shop.orders.order.v1.10~",
+            "gts.x.shop.orders.order.v1~",
+            "gts.x.shop.orders.order.v1.2~",
+            "gts.x.shop.orders.order.v2.0~",
+            "gts.x.shop.orders.order.v1.0~",
+        ]
+        .iter()
+        .map(|id| GtsID::new(id).expect("test"))
+        .collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        let order: Vec<&str> = sorted.iter().map(|id| id.id.as_str()).collect();
+        assert_eq!(
+            order,
+            [
+                "gts.x.shop.orders.order.v1~",
+                "gts.x.shop.orders.order.v1.0~",
+                "gts.x.shop.orders.order.v1.2~",
+                "gts.x.shop.orders.order.v1.10~",
+                "gts.x.shop.orders.order.v2.0~",
+            ]
+        );
+
+        let v1_2 = &ids[2];
+        assert!(ids[0].is_newer_than(v1_2));
+        assert!(!v1_2.is_newer_than(&ids[0]));
+        // Another major is another type
+        assert!(!ids[3].same_type_as(v1_2));
+        assert!(!ids[3].is_newer_than(v1_2));
+
+        let latest_v1 = GtsID::latest_of(ids.iter().filter(|id| id.same_type_as(v1_2)));
+        assert_eq!(
+            latest_v1.map(|id| id.id.as_str()),
+            Some("gts.x.shop.orders.order.v1.10~")
+        );
+        assert!(GtsID::latest_of(&[]).is_none());
+
+        let instance = GtsID::new("gts.x.shop.orders.order.v1.2~x.shop._.o1.v1").expect("test");
+        let other = GtsID::new("gts.x.shop.orders.order.v1.3~x.shop._.o1.v1").expect("test");
+        assert!(!instance.same_type_as(&other));
+        assert!(!instance.same_type_as(v1_2));
+    }
+
+    #[test]
+    fn test_gts_id_serde_roundtrip() {
+        let id = GtsID::new("gts.x.core.events.event.v1~").expect("test");
+        let json = serde_json::to_string(&id).expect("test");
+        assert_eq!(json, "\"gts.x.core.events.event.v1~\"");
+        let back: GtsID = serde_json::from_str(&json).expect("test");
+        assert_eq!(back, id);
+        assert!(serde_json::from_str::<GtsID>("\"not-an-id\"").is_err());
+        assert_eq!(
+            "gts.x.core.events.event.v1~"
+                .parse::<GtsID>()
+                .expect("test"),
+            id
+        );
+    }
+
     #[test]
     fn test_gts_id_valid() {
         let id = GtsID::new("gts.x.core.events.event.v1~").expect("test");
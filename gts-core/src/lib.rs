@@ -0,0 +1,35 @@
+//! GTS identifiers, wildcard patterns and entities.
+//!
+//! The parts of GTS that need no schema store: parsing and building IDs,
+//! matching patterns, reading entities out of JSON and casting them between
+//! schema versions.  Crates that only handle IDs depend on this instead of
+//! `gts`, which re-exports all of it and adds the store, JSON Schema
+//! validation and file IO.
+
+pub mod changes;
+pub mod diagnostic;
+pub mod entities;
+pub mod gts;
+pub mod lifecycle;
+pub mod numbers;
+pub mod ownership;
+pub mod path_resolver;
+pub mod ref_integrity;
+pub mod sandbox;
+pub mod schema_cast;
+pub mod schema_resolution;
+
+pub use changes::GtsChangeKind;
+pub use diagnostic::{GtsCode, GtsDiagnostic};
+pub use entities::{GtsConfig, GtsEntity, GtsFile, GtsIdFields, ValidationError, ValidationResult};
+pub use gts::{
+    GtsError, GtsID, GtsIdBuilder, GtsIdSegment, GtsInstanceId, GtsSchemaId, GtsWildcard,
+};
+pub use lifecycle::GtsRetiredSchemaPolicy;
+pub use numbers::{GtsNumberError, GtsNumberPolicy};
+pub use ownership::GtsNamespaceRule;
+pub use path_resolver::JsonPathResolver;
+pub use ref_integrity::GtsRefIntegrity;
+pub use sandbox::GtsSandboxLimits;
+pub use schema_cast::{GtsEntityCastResult, SchemaCastError};
+pub use schema_resolution::{GtsSchemaResolution, GtsSchemaSubstitution};
@@ -0,0 +1,15 @@
+//! What validation does with schemas past the end of their lifecycle.  The
+//! `gts` store reads schema lifecycles and applies this policy.
+
+use serde::{Deserialize, Serialize};
+
+/// How validation against a retired schema is handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsRetiredSchemaPolicy {
+    /// Validation fails with a pointer to the replacement.
+    #[default]
+    Fail,
+    /// Validation proceeds with a warning.
+    Warn,
+}
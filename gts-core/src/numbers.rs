@@ -0,0 +1,294 @@
+//! Number handling beyond default JSON semantics.
+//!
+//! JSON Schema treats `1.0` as a valid `integer`, and `serde_json` silently
+//! turns integers wider than 64 bits into lossy floats.  [`GtsNumberPolicy`]
+//! makes both configurable:
+//!
+//! - `strict_integers` rejects floats (`1.0`, `1e3`, or integers too large to
+//!   parse exactly) wherever a schema expects an `integer`.
+//! - `bignum_as_string` checks strings annotated with `"format": "bigint"` or
+//!   `"format": "decimal"`, the recommended way to carry large sequence IDs and
+//!   exact decimal amounts.
+//!
+//! Casting converts between the two representations when the target schema
+//! asks for it, and [`canonicalize_numbers`] gives integral floats a single
+//! form so `1.0` and `1` hash identically when the policy accepts both.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+/// `format` of a string holding an arbitrary-size integer.
+pub const BIGINT_FORMAT: &str = "bigint";
+/// `format` of a string holding an exact decimal number.
+pub const DECIMAL_FORMAT: &str = "decimal";
+
+/// Largest integer an `f64` represents exactly (2^53).
+const MAX_EXACT_F64: f64 = 9_007_199_254_740_992.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GtsNumberPolicy {
+    /// Reject non-integral JSON numbers (including `1.0`) for `integer` schemas.
+    pub strict_integers: bool,
+    /// Validate `bigint` / `decimal` formatted strings.
+    pub bignum_as_string: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsNumberError {
+    /// JSON pointer of the offending value.
+    pub path: String,
+    /// The schema keyword the rule applies to: `type` or `format`.
+    pub keyword: String,
+    pub reason: String,
+}
+
+fn is_bigint(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+fn is_decimal(s: &str) -> bool {
+    match s.split_once('.') {
+        Some((int, frac)) => {
+            is_bigint(int) && !frac.is_empty() && frac.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => is_bigint(s),
+    }
+}
+
+fn schema_types(schema: &Map<String, Value>) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Checks `instance` against the number rules of `policy`, following
+/// `properties`, `additionalProperties`, `items` and `allOf` of an already
+/// `$ref`-resolved schema.
+#[must_use]
+pub fn check_numbers(
+    instance: &Value,
+    schema: &Value,
+    policy: GtsNumberPolicy,
+) -> Vec<GtsNumberError> {
+    let mut errors = Vec::new();
+    if policy != GtsNumberPolicy::default() {
+        walk(instance, schema, policy, "", &mut errors);
+    }
+    errors
+}
+
+fn walk(
+    instance: &Value,
+    schema: &Value,
+    policy: GtsNumberPolicy,
+    path: &str,
+    errors: &mut Vec<GtsNumberError>,
+) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let types = schema_types(schema);
+    let mut fail = |keyword: &str, reason: String| {
+        errors.push(GtsNumberError {
+            path: path.to_owned(),
+            keyword: keyword.to_owned(),
+            reason,
+        });
+    };
+
+    if policy.strict_integers
+        && types.contains(&"integer")
+        && !types.contains(&"number")
+        && let Value::Number(n) = instance
+        && n.is_f64()
+    {
+        fail(
+            "type",
+            format!("{n} is not an integer (strict integer mode)"),
+        );
+    }
+    if policy.bignum_as_string
+        && let Value::String(s) = instance
+    {
+        match schema.get("format").and_then(Value::as_str) {
+            Some(BIGINT_FORMAT) if !is_bigint(s) => {
+                fail(
+                    "format",
+                    format!("'{s}' is not a valid {BIGINT_FORMAT} string"),
+                );
+            }
+            Some(DECIMAL_FORMAT) if !is_decimal(s) => {
+                fail(
+                    "format",
+                    format!("'{s}' is not a valid {DECIMAL_FORMAT} string"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(branches) = schema.get("allOf").and_then(Value::as_array) {
+        for branch in branches {
+            walk(instance, branch, policy, path, errors);
+        }
+    }
+    match instance {
+        Value::Object(obj) => {
+            let props = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties");
+            for (key, value) in obj {
+                let sub = props.and_then(|p| p.get(key)).or(additional);
+                if let Some(sub) = sub {
+                    walk(value, sub, policy, &format!("{path}/{key}"), errors);
+                }
+            }
+        }
+        Value::Array(items) => match schema.get("items") {
+            Some(Value::Array(tuple)) => {
+                for (i, (item, sub)) in items.iter().zip(tuple).enumerate() {
+                    walk(item, sub, policy, &format!("{path}/{i}"), errors);
+                }
+            }
+            Some(sub) => {
+                for (i, item) in items.iter().enumerate() {
+                    walk(item, sub, policy, &format!("{path}/{i}"), errors);
+                }
+            }
+            None => {}
+        },
+        _ => {}
+    }
+}
+
+/// Converts a value to the numeric representation `schema` asks for:
+/// numbers become strings for `bigint` / `decimal` formatted strings, and
+/// `bigint` strings or integral floats become integers for `integer` schemas
+/// when that is lossless. Returns the converted value, if any.
+#[must_use]
+pub fn coerce_number(value: &Value, schema: &Value) -> Option<Value> {
+    let schema = schema.as_object()?;
+    let types = schema_types(schema);
+    let format = schema.get("format").and_then(Value::as_str);
+
+    match value {
+        Value::Number(n)
+            if types == ["string"] && matches!(format, Some(BIGINT_FORMAT | DECIMAL_FORMAT)) =>
+        {
+            Some(Value::String(n.to_string()))
+        }
+        Value::String(s) if types == ["integer"] && is_bigint(s) => s
+            .parse::<i64>()
+            .map(Number::from)
+            .or_else(|_| s.parse::<u64>().map(Number::from))
+            .ok()
+            .map(Value::Number),
+        Value::Number(n) if types == ["integer"] && n.is_f64() => {
+            integral(n.as_f64()?).map(Value::Number)
+        }
+        _ => None,
+    }
+}
+
+/// The integer equal to `f`, if `f` is integral and exactly representable.
+#[allow(clippy::cast_possible_truncation)]
+fn integral(f: f64) -> Option<Number> {
+    (f.fract() == 0.0 && f.abs() < MAX_EXACT_F64).then(|| Number::from(f as i64))
+}
+
+/// Rewrites integral floats (`1.0`) as integers (`1`), recursively, so that
+/// equal numbers serialize — and hash — identically.
+pub fn canonicalize_numbers(value: &mut Value) {
+    match value {
+        Value::Number(n) => {
+            if let Some(i) = n.as_f64().filter(|_| n.is_f64()).and_then(integral) {
+                *n = i;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(canonicalize_numbers),
+        Value::Object(obj) => obj.values_mut().for_each(canonicalize_numbers),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer"},
+                "ratio": {"type": "number"},
+                "sequence_id": {"type": "string", "format": "bigint"},
+                "amount": {"type": "string", "format": "decimal"},
+                "items": {"type": "array", "items": {"type": "integer"}}
+            }
+        })
+    }
+
+    #[test]
+    fn test_default_policy_accepts_everything() {
+        let instance = json!({"count": 1.0, "sequence_id": "abc"});
+        assert!(check_numbers(&instance, &schema(), GtsNumberPolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_strict_integers() {
+        let policy = GtsNumberPolicy {
+            strict_integers: true,
+            ..Default::default()
+        };
+        let instance =
+            json!({"count": 1.0, "ratio": 1.5, "items": [1, 2.0, 18_446_744_073_709_551_615_u64]});
+        let errors = check_numbers(&instance, &schema(), policy);
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/count", "/items/1"]);
+
+        // Integers wider than u64 only parse as lossy floats
+        let instance: Value = serde_json::from_str(r#"{"count": 36893488147419103232}"#).unwrap();
+        assert_eq!(check_numbers(&instance, &schema(), policy).len(), 1);
+    }
+
+    #[test]
+    fn test_bignum_strings() {
+        let policy = GtsNumberPolicy {
+            bignum_as_string: true,
+            ..Default::default()
+        };
+        let ok = json!({"sequence_id": "36893488147419103232", "amount": "-12.50"});
+        assert!(check_numbers(&ok, &schema(), policy).is_empty());
+
+        let bad = json!({"sequence_id": "0012", "amount": "1."});
+        let errors = check_numbers(&bad, &schema(), policy);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].reason, "'0012' is not a valid bigint string");
+    }
+
+    #[test]
+    fn test_coerce_and_canonicalize() {
+        let bigint = json!({"type": "string", "format": "bigint"});
+        let integer = json!({"type": "integer"});
+        assert_eq!(coerce_number(&json!(42), &bigint), Some(json!("42")));
+        assert_eq!(coerce_number(&json!("42"), &integer), Some(json!(42)));
+        assert_eq!(coerce_number(&json!(3.0), &integer), Some(json!(3)));
+        assert_eq!(coerce_number(&json!(3.5), &integer), None);
+        assert_eq!(
+            coerce_number(&json!("36893488147419103232"), &integer),
+            None
+        );
+
+        let mut value = json!({"a": 1.0, "b": [2.0, 2.5], "c": "1.0"});
+        canonicalize_numbers(&mut value);
+        assert_eq!(value, json!({"a": 1, "b": [2, 2.5], "c": "1.0"}));
+        assert_eq!(value.to_string(), r#"{"a":1,"b":[2,2.5],"c":"1.0"}"#);
+    }
+}
@@ -0,0 +1,53 @@
+//! Vendor namespace ownership.
+//!
+//! A [`GtsNamespaceRule`] reserves a part of the ID space, such as
+//! `gts.acme.*`, for a set of principals.  The `gts` store consults these
+//! rules (or a custom policy provider) before registering an entity.
+
+use serde::{Deserialize, Serialize};
+
+use crate::gts::{GTS_PREFIX, GtsID, GtsWildcard};
+
+/// Principals allowed to register entities whose IDs match `pattern`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsNamespaceRule {
+    /// GTS wildcard or exact ID, e.g. `gts.acme.*`.
+    pub pattern: String,
+    /// Principals (signers, service accounts, ...) that own the namespace.
+    pub owners: Vec<String>,
+}
+
+impl GtsNamespaceRule {
+    /// Whether `id` falls in this rule's namespace: the pattern matches the
+    /// ID itself or the segment it adds to its parent, so vendors also own
+    /// the types they derive from other vendors' base types.  IDs that are
+    /// not GTS IDs (e.g. UUIDs of anonymous instances) are never covered.
+    #[must_use]
+    pub fn covers(&self, id: &str) -> bool {
+        let Ok(pattern) = GtsWildcard::new(&self.pattern) else {
+            return false;
+        };
+        let Ok(gid) = GtsID::new(id) else {
+            return false;
+        };
+        if gid.wildcard_match(&pattern) {
+            return true;
+        }
+        gid.gts_id_segments.len() > 1
+            && gid
+                .gts_id_segments
+                .last()
+                .and_then(|seg| {
+                    // Parsed as a type: a lone instance segment is not a valid ID
+                    let own = seg.segment.trim_end_matches('~');
+                    GtsID::new(&format!("{GTS_PREFIX}{own}~")).ok()
+                })
+                .is_some_and(|own| own.wildcard_match(&pattern))
+    }
+
+    /// Whether `principal` is one of the owners.
+    #[must_use]
+    pub fn is_owner(&self, principal: &str) -> bool {
+        self.owners.iter().any(|o| o == principal)
+    }
+}
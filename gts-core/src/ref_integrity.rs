@@ -0,0 +1,19 @@
+//! Whether `x-gts-ref` values must point at entities that exist.  The `gts`
+//! store implements the check; this is the policy.
+
+use serde::{Deserialize, Serialize};
+
+/// How far the store checks the targets of `x-gts-ref` values when
+/// validating an instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsRefIntegrity {
+    /// Only the syntax of the ID and its match against the pattern.
+    #[default]
+    Off,
+    /// The referenced entity must also be in the store.
+    Exists,
+    /// The referenced entity must be in the store and be of its type: a
+    /// schema, or an instance that validates against its schema.
+    Typed,
+}
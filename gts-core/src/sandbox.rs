@@ -0,0 +1,164 @@
+//! Resource limits for validating against untrusted schemas.
+//!
+//! A registry that accepts schemas from many teams must not let one schema
+//! stall validation for everybody.  With [`GtsSandboxLimits`] set on the store
+//! (or `"sandbox": {}` in the config for the defaults):
+//!
+//! - `pattern` and `patternProperties` regexes are compiled with the `regex`
+//!   crate instead of a backtracking engine, so matching time is linear in the
+//!   input; the compiled program size is capped, which bounds compile time.
+//!   Patterns needing backtracking (look-around, back-references) are
+//!   rejected.
+//! - `enum` lists and pattern sources are capped in length.
+//! - Each validation gets a step budget, estimated as the number of values in
+//!   the instance times the number of keywords in the resolved schema.
+//!
+//! The `gts` store enforces these limits and reports violations as
+//! `StoreError::PolicyViolation`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GtsSandboxLimits {
+    /// Longest accepted regex source, in bytes.
+    pub max_pattern_length: usize,
+    /// Largest compiled regex program, in bytes.
+    pub regex_size_limit: usize,
+    /// Largest lazy DFA cache per regex, in bytes.
+    pub regex_dfa_size_limit: usize,
+    /// Most values accepted in one `enum`.
+    pub max_enum_values: usize,
+    /// Step budget of one validation.
+    pub max_validation_steps: u64,
+}
+
+impl Default for GtsSandboxLimits {
+    fn default() -> Self {
+        GtsSandboxLimits {
+            max_pattern_length: 1_000,
+            regex_size_limit: 256 * 1024,
+            regex_dfa_size_limit: 1024 * 1024,
+            max_enum_values: 1_000,
+            max_validation_steps: 10_000_000,
+        }
+    }
+}
+
+impl GtsSandboxLimits {
+    /// Policy violations in a schema, one message per offending keyword.
+    #[must_use]
+    pub fn check_schema(&self, schema: &Value) -> Vec<String> {
+        let mut problems = Vec::new();
+        self.walk(schema, "", &mut problems);
+        problems
+    }
+
+    fn check_pattern(&self, pattern: &str, path: &str, problems: &mut Vec<String>) {
+        if pattern.len() > self.max_pattern_length {
+            problems.push(format!(
+                "{path}: pattern is {} bytes long, the limit is {}",
+                pattern.len(),
+                self.max_pattern_length
+            ));
+            return;
+        }
+        let compiled = regex::RegexBuilder::new(pattern)
+            .size_limit(self.regex_size_limit)
+            .dfa_size_limit(self.regex_dfa_size_limit)
+            .build();
+        match compiled {
+            Ok(_) => {}
+            Err(regex::Error::CompiledTooBig(limit)) => problems.push(format!(
+                "{path}: pattern compiles beyond the {limit} byte regex size limit"
+            )),
+            Err(e) => problems.push(format!(
+                "{path}: pattern is not supported without backtracking: {}",
+                e.to_string().lines().last().unwrap_or_default()
+            )),
+        }
+    }
+
+    fn walk(&self, value: &Value, path: &str, problems: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(pattern)) = map.get("pattern") {
+                    self.check_pattern(pattern, &format!("{path}/pattern"), problems);
+                }
+                if let Some(Value::Object(props)) = map.get("patternProperties") {
+                    for pattern in props.keys() {
+                        self.check_pattern(pattern, &format!("{path}/patternProperties"), problems);
+                    }
+                }
+                if let Some(Value::Array(values)) = map.get("enum")
+                    && values.len() > self.max_enum_values
+                {
+                    problems.push(format!(
+                        "{path}/enum: {} values, the limit is {}",
+                        values.len(),
+                        self.max_enum_values
+                    ));
+                }
+                for (key, v) in map {
+                    let escaped = key.replace('~', "~0").replace('/', "~1");
+                    self.walk(v, &format!("{path}/{escaped}"), problems);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.walk(item, &format!("{path}/{idx}"), problems);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ITEM: &str = "gts.x.test.sandbox.item.v1~";
+
+    fn schema(properties: &Value) -> Value {
+        json!({
+            "$id": format!("gts://{ITEM}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": properties
+        })
+    }
+
+    #[test]
+    fn test_check_schema_reports_each_violation() {
+        let limits = GtsSandboxLimits {
+            max_pattern_length: 20,
+            max_enum_values: 3,
+            ..GtsSandboxLimits::default()
+        };
+        let problems = limits.check_schema(&schema(&json!({
+            "a": {"type": "string", "pattern": "^(a+)+$"},
+            "b": {"type": "string", "pattern": "^(?=x)y"},
+            "c": {"type": "string", "pattern": "x".repeat(21)},
+            "d": {"enum": [1, 2, 3, 4]},
+            "e": {"type": "object", "patternProperties": {"(\\w)\\1": {}}},
+            // A property named like a keyword is still only a property
+            "pattern": {"type": "string"}
+        })));
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[0].starts_with("/properties/b/pattern: pattern is not supported"));
+        assert!(problems[1].starts_with("/properties/c/pattern: pattern is 21 bytes"));
+        assert!(problems[2].starts_with("/properties/d/enum: 4 values"));
+        assert!(problems[3].starts_with("/properties/e/patternProperties: pattern is not"));
+
+        let tiny = GtsSandboxLimits {
+            regex_size_limit: 100,
+            ..GtsSandboxLimits::default()
+        };
+        let problems = tiny.check_schema(&json!({"pattern": "\\w{100}"}));
+        assert!(problems[0].contains("regex size limit"), "{problems:?}");
+    }
+}
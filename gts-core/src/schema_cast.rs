@@ -3,7 +3,9 @@ use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
+use crate::diagnostic::{GtsCode, GtsDiagnostic};
 use crate::gts::GtsID;
+use crate::numbers;
 
 #[derive(Debug, Error)]
 pub enum SchemaCastError {
@@ -19,6 +21,25 @@ pub enum SchemaCastError {
     CastError(String),
 }
 
+impl SchemaCastError {
+    /// The stable diagnostic code of this error.
+    #[must_use]
+    pub fn code(&self) -> GtsCode {
+        match self {
+            SchemaCastError::InternalError(_) => GtsCode::CastInternal,
+            SchemaCastError::TargetMustBeSchema => GtsCode::CastTargetNotSchema,
+            SchemaCastError::SourceMustBeSchema => GtsCode::CastSourceNotSchema,
+            SchemaCastError::InstanceMustBeObject => GtsCode::CastInstanceNotObject,
+            SchemaCastError::CastError(_) => GtsCode::CastFailed,
+        }
+    }
+
+    #[must_use]
+    pub fn diagnostic(&self) -> GtsDiagnostic {
+        GtsDiagnostic::new(self.code(), self.to_string())
+    }
+}
+
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GtsEntityCastResult {
@@ -101,6 +122,10 @@ impl GtsEntityCastResult {
                 }
             };
 
+        let mut casted = casted;
+        let mut changed = Vec::new();
+        Self::coerce_numbers(&mut casted, &target_schema, "", &mut changed);
+
         // Validate the transformed instance against the FULL target schema
         let is_fully_compatible = true; // Simplified for now
         let reasons = incompatibility_reasons;
@@ -123,7 +148,7 @@ impl GtsEntityCastResult {
             direction,
             added_properties: added_sorted,
             removed_properties: removed_sorted,
-            changed_properties: Vec::new(),
+            changed_properties: changed,
             is_fully_compatible,
             is_backward_compatible: is_backward,
             is_forward_compatible: is_forward,
@@ -135,6 +160,40 @@ impl GtsEntityCastResult {
         })
     }
 
+    /// Converts numbers to the representation the target schema asks for
+    /// (e.g. a number into a `bigint` string), recording each change.
+    fn coerce_numbers(
+        instance: &mut Map<String, Value>,
+        schema: &Value,
+        base_path: &str,
+        changed: &mut Vec<HashMap<String, String>>,
+    ) {
+        let Some(props) = schema.get("properties").and_then(Value::as_object) else {
+            return;
+        };
+        for (prop, p_schema) in props {
+            let Some(value) = instance.get_mut(prop) else {
+                continue;
+            };
+            let path = if base_path.is_empty() {
+                prop.clone()
+            } else {
+                format!("{base_path}.{prop}")
+            };
+            if let Some(coerced) = numbers::coerce_number(value, p_schema) {
+                changed.push(HashMap::from([
+                    ("path".to_owned(), path),
+                    ("from".to_owned(), value.to_string()),
+                    ("to".to_owned(), coerced.to_string()),
+                ]));
+                *value = coerced;
+            } else if let Some(nested) = value.as_object_mut() {
+                let nested_schema = Self::effective_object_schema(p_schema);
+                Self::coerce_numbers(nested, &nested_schema, &path, changed);
+            }
+        }
+    }
+
     #[must_use]
     pub fn infer_direction(from_id: &str, to_id: &str) -> String {
         if let (Ok(gid_from), Ok(gid_to)) = (GtsID::new(from_id), GtsID::new(to_id))
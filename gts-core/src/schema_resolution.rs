@@ -0,0 +1,24 @@
+//! How instances find their schema when the declared minor version is not
+//! registered.  The `gts` store implements the lookup; these are the policy
+//! and the record of a substitution it returns.
+
+use serde::{Deserialize, Serialize};
+
+/// How the store finds the schema an instance declares.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsSchemaResolution {
+    /// Only the exact declared schema ID.
+    #[default]
+    Exact,
+    /// The exact ID, or else the nearest newer registered minor version of
+    /// the same major.
+    NearestMinor,
+}
+
+/// A schema used in place of the one an instance declared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsSchemaSubstitution {
+    pub requested: String,
+    pub resolved: String,
+}
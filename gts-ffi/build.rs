@@ -0,0 +1,17 @@
+//! Regenerates `include/gts.h` from the `extern "C"` functions in `src/lib.rs`.
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = cbindgen::Config::from_file("cbindgen.toml").unwrap_or_default();
+    match cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/lib.rs")
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file("include/gts.h");
+        }
+        Err(e) => println!("cargo:warning=cannot generate include/gts.h: {e}"),
+    }
+}
@@ -0,0 +1,68 @@
+#ifndef GTS_H
+#define GTS_H
+
+/* Generated by cbindgen from gts-ffi/src/lib.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An opaque GTS store.
+typedef struct GtsFfiStore GtsFfiStore;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a store, loading the entities under directory `path` and the
+// settings in file `config`.  Either may be null.  Returns null if an
+// argument is not UTF-8.
+//
+// # Safety
+// `path` and `config` must be null or NUL-terminated strings.
+struct GtsFfiStore *gts_store_new(const char *path, const char *config);
+
+// Frees a store.  Null is ignored.
+//
+// # Safety
+// `store` must be null or a pointer from [`gts_store_new`] that has not
+// been freed.
+void gts_store_free(struct GtsFfiStore *store);
+
+// Registers the JSON schema `schema_json` under `type_id`.
+//
+// # Safety
+// `store` must be null or a live store; the strings must be null or
+// NUL-terminated.
+char *gts_register_schema_json(struct GtsFfiStore *store,
+                               const char *type_id,
+                               const char *schema_json);
+
+// Registers the JSON entity `content_json`, validating it first when
+// `validate` is non-zero.
+//
+// # Safety
+// `store` must be null or a live store; `content_json` must be null or
+// NUL-terminated.
+char *gts_add_entity_json(struct GtsFfiStore *store, const char *content_json, int32_t validate);
+
+// Validates registered instance `gts_id` against its schema.
+//
+// # Safety
+// `store` must be null or a live store; `gts_id` must be null or
+// NUL-terminated.
+char *gts_validate_instance(struct GtsFfiStore *store, const char *gts_id);
+
+// Frees a result string.  Null is ignored.
+//
+// # Safety
+// `result` must be null or a string returned by this library that has not
+// been freed.
+void gts_free_result(char *result);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GTS_H */
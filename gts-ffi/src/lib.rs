@@ -0,0 +1,250 @@
+//! C API for embedding GTS validation.
+//!
+//! A store is an opaque `GtsFfiStore` pointer from [`gts_store_new`], freed
+//! with [`gts_store_free`].  Operations take NUL-terminated UTF-8 strings and
+//! return a newly allocated JSON string with the same shape the CLI prints
+//! (`{"ok": true, ...}` or `{"ok": false, "error": "..."}`), which the caller
+//! frees with [`gts_free_result`].  Functions never unwind into the caller: a
+//! panic is reported as an error result.  The header is `include/gts.h`,
+//! regenerated by cbindgen on every build.
+
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use gts::GtsOps;
+use gts::ops::GtsOpsResult;
+use serde_json::{Value, json};
+
+/// An opaque GTS store.
+pub struct GtsFfiStore {
+    ops: GtsOps,
+}
+
+/// Borrows a NUL-terminated UTF-8 argument.
+///
+/// # Safety
+/// `ptr` must be null or point to a NUL-terminated string that outlives the
+/// returned reference.
+unsafe fn arg<'a>(name: &str, ptr: *const c_char) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{name} is null"));
+    }
+    // SAFETY: non-null and NUL-terminated per the caller's contract.
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|e| format!("{name} is not UTF-8: {e}"))
+}
+
+fn error(message: &str) -> Value {
+    json!({"ok": false, "error": message})
+}
+
+/// Hands `value` to the caller as a JSON C string.
+fn into_result(value: &Value) -> *mut c_char {
+    // serde_json escapes NUL, so the text never contains one.
+    CString::new(value.to_string()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Runs `op` on the store, turning null pointers, bad arguments and panics
+/// into error results.
+///
+/// # Safety
+/// `store` must be null or a live pointer from [`gts_store_new`].
+unsafe fn with_store(
+    store: *mut GtsFfiStore,
+    op: impl FnOnce(&mut GtsOps) -> Result<Value, String>,
+) -> *mut c_char {
+    // SAFETY: null or live and not aliased, per the caller's contract.
+    let Some(store) = (unsafe { store.as_mut() }) else {
+        return into_result(&error("store is null"));
+    };
+    let result = catch_unwind(AssertUnwindSafe(|| op(&mut store.ops)))
+        .unwrap_or_else(|_| Err("internal error: operation panicked".to_owned()));
+    into_result(&result.unwrap_or_else(|e| error(&e)))
+}
+
+/// Creates a store, loading the entities under directory `path` and the
+/// settings in file `config`.  Either may be null.  Returns null if an
+/// argument is not UTF-8.
+///
+/// # Safety
+/// `path` and `config` must be null or NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_store_new(
+    path: *const c_char,
+    config: *const c_char,
+) -> *mut GtsFfiStore {
+    let optional = |name, ptr: *const c_char| {
+        if ptr.is_null() {
+            Ok(None)
+        } else {
+            // SAFETY: non-null and NUL-terminated per this function's contract.
+            unsafe { arg(name, ptr) }.map(|s| Some(s.to_owned()))
+        }
+    };
+    let (Ok(path), Ok(config)) = (optional("path", path), optional("config", config)) else {
+        return ptr::null_mut();
+    };
+    catch_unwind(|| GtsOps::new(path.map(|p| vec![p]), config, 0)).map_or(ptr::null_mut(), |ops| {
+        Box::into_raw(Box::new(GtsFfiStore { ops }))
+    })
+}
+
+/// Frees a store.  Null is ignored.
+///
+/// # Safety
+/// `store` must be null or a pointer from [`gts_store_new`] that has not
+/// been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_store_free(store: *mut GtsFfiStore) {
+    if !store.is_null() {
+        // SAFETY: allocated by gts_store_new and not freed, per the contract.
+        drop(unsafe { Box::from_raw(store) });
+    }
+}
+
+/// Registers the JSON schema `schema_json` under `type_id`.
+///
+/// # Safety
+/// `store` must be null or a live store; the strings must be null or
+/// NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_register_schema_json(
+    store: *mut GtsFfiStore,
+    type_id: *const c_char,
+    schema_json: *const c_char,
+) -> *mut c_char {
+    // SAFETY: forwarded from this function's contract.
+    unsafe {
+        with_store(store, |ops| {
+            let type_id = arg("type_id", type_id)?;
+            let schema: Value = serde_json::from_str(arg("schema_json", schema_json)?)
+                .map_err(|e| format!("schema_json is not JSON: {e}"))?;
+            Ok(ops.add_schema(type_id.to_owned(), &schema).to_dict())
+        })
+    }
+}
+
+/// Registers the JSON entity `content_json`, validating it first when
+/// `validate` is non-zero.
+///
+/// # Safety
+/// `store` must be null or a live store; `content_json` must be null or
+/// NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_add_entity_json(
+    store: *mut GtsFfiStore,
+    content_json: *const c_char,
+    validate: i32,
+) -> *mut c_char {
+    // SAFETY: forwarded from this function's contract.
+    unsafe {
+        with_store(store, |ops| {
+            let content: Value = serde_json::from_str(arg("content_json", content_json)?)
+                .map_err(|e| format!("content_json is not JSON: {e}"))?;
+            Ok(ops.add_entity(&content, validate != 0).to_dict())
+        })
+    }
+}
+
+/// Validates registered instance `gts_id` against its schema.
+///
+/// # Safety
+/// `store` must be null or a live store; `gts_id` must be null or
+/// NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_validate_instance(
+    store: *mut GtsFfiStore,
+    gts_id: *const c_char,
+) -> *mut c_char {
+    // SAFETY: forwarded from this function's contract.
+    unsafe {
+        with_store(store, |ops| {
+            Ok(ops.validate_instance(arg("gts_id", gts_id)?).to_dict())
+        })
+    }
+}
+
+/// Frees a result string.  Null is ignored.
+///
+/// # Safety
+/// `result` must be null or a string returned by this library that has not
+/// been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gts_free_result(result: *mut c_char) {
+    if !result.is_null() {
+        // SAFETY: allocated by CString::into_raw and not freed, per the contract.
+        drop(unsafe { CString::from_raw(result) });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn take(result: *mut c_char) -> Value {
+        assert!(!result.is_null());
+        // SAFETY: a live result of this library.
+        let value = serde_json::from_str(unsafe { CStr::from_ptr(result) }.to_str().unwrap());
+        unsafe { gts_free_result(result) };
+        value.unwrap()
+    }
+
+    #[test]
+    fn test_register_and_validate_through_c_api() {
+        let type_id = CString::new("gts.x.test.ffi.item.v1~").unwrap();
+        let schema = CString::new(
+            json!({
+                "$id": "gts://gts.x.test.ffi.item.v1~",
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {"n": {"type": "integer"}}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let id = "gts.x.test.ffi.item.v1~x.test._.a.v1";
+        let instance = CString::new(json!({"id": id, "n": "x"}).to_string()).unwrap();
+        let gts_id = CString::new(id).unwrap();
+
+        unsafe {
+            let store = gts_store_new(ptr::null(), ptr::null());
+            assert!(!store.is_null());
+            let added = take(gts_register_schema_json(
+                store,
+                type_id.as_ptr(),
+                schema.as_ptr(),
+            ));
+            assert_eq!(added["ok"], true);
+            assert_eq!(
+                take(gts_add_entity_json(store, instance.as_ptr(), 0))["ok"],
+                true
+            );
+            let validated = take(gts_validate_instance(store, gts_id.as_ptr()));
+            assert_eq!(validated["ok"], false);
+            assert_eq!(validated["id"], id);
+
+            let bad = take(gts_validate_instance(store, ptr::null()));
+            assert_eq!(bad["error"], "gts_id is null");
+            let not_json = CString::new("{").unwrap();
+            let bad = take(gts_register_schema_json(
+                store,
+                type_id.as_ptr(),
+                not_json.as_ptr(),
+            ));
+            assert!(
+                bad["error"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("schema_json is not JSON")
+            );
+            gts_store_free(store);
+        }
+        assert_eq!(
+            take(unsafe { gts_validate_instance(ptr::null_mut(), gts_id.as_ptr()) })["error"],
+            "store is null"
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Validation bundles for arbitrary `#[struct_to_gts_schema]` chains.
+//!
+//! Scans a crate for annotated structs, picks up the schema files that
+//! `gts generate-from-rust` wrote next to them (`<dir_path>/<schema_id>.schema.json`
+//! relative to the declaring file), and for every chain found emits all of
+//! its schemas, a sample instance of the most derived type, and a
+//! `validate.sh` that checks each sample with `ajv`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use gts::GtsStore;
+use regex::Regex;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+/// A struct annotated with `#[struct_to_gts_schema]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredType {
+    pub struct_name: String,
+    pub schema_id: String,
+    /// Where `gts generate-from-rust` writes the schema for this type.
+    pub schema_path: PathBuf,
+}
+
+/// Collects the annotated structs in the `.rs` files under `source`.
+///
+/// # Errors
+/// Returns an error if `source` does not exist.
+pub fn scan_crate(source: &Path) -> anyhow::Result<Vec<DeclaredType>> {
+    if !source.exists() {
+        bail!("Source path does not exist: {}", source.display());
+    }
+    let annotation = Regex::new(
+        r"(?s)#\[struct_to_gts_schema\(([^)]+)\)\]\s*(?:#\[[^\]]+\]\s*)*(?:pub\s+)?struct\s+(\w+)",
+    )?;
+    let dir_path_re = Regex::new(r#"dir_path\s*=\s*"([^"]+)""#)?;
+    let schema_id_re = Regex::new(r#"schema_id\s*=\s*"([^"]+)""#)?;
+
+    let mut declared = Vec::new();
+    for entry in WalkDir::new(source)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("rs")
+            || path.components().any(|c| c.as_os_str() == "target")
+        {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        for cap in annotation.captures_iter(&content) {
+            let attrs = &cap[1];
+            let (Some(dir_path), Some(schema_id)) =
+                (dir_path_re.captures(attrs), schema_id_re.captures(attrs))
+            else {
+                continue;
+            };
+            let source_dir = path.parent().unwrap_or(source);
+            declared.push(DeclaredType {
+                struct_name: cap[2].to_owned(),
+                schema_id: schema_id[1].to_owned(),
+                schema_path: source_dir
+                    .join(&dir_path[1])
+                    .join(format!("{}.schema.json", &schema_id[1])),
+            });
+        }
+    }
+    Ok(declared)
+}
+
+/// Type names mentioned in a `--types` value such as
+/// `BaseEventV1<AuditPayloadV1<()>>,PlaceOrderDataV1`.
+#[must_use]
+pub fn parse_type_names(types: &str) -> BTreeSet<String> {
+    types
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|name| name.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Schema IDs of `schema_id` and all its ancestors, base first.
+fn chain_of(schema_id: &str) -> Vec<String> {
+    schema_id
+        .match_indices('~')
+        .map(|(pos, _)| schema_id[..=pos].to_owned())
+        .collect()
+}
+
+/// One chain of the bundle: its schemas and the sample instance.
+#[derive(Debug, Clone)]
+pub struct BundleChain {
+    /// Schema IDs from the most derived type down to the base.
+    pub schema_ids: Vec<String>,
+    pub instance_file: String,
+    pub instance: Value,
+    /// Required fields of the sample that hold placeholder values.
+    pub zero_filled: Vec<String>,
+}
+
+/// Everything a bundle directory holds.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// Every schema of every chain, by schema ID.
+    pub schemas: BTreeMap<String, Value>,
+    pub chains: Vec<BundleChain>,
+}
+
+/// Builds a chain for every selected type that no other selected type
+/// derives from. With `types` empty, every declared type is selected.
+///
+/// # Errors
+/// Returns an error if a chain member is not declared or its schema file is
+/// missing or invalid, or if no declared type matches `types`.
+pub fn build_chains(declared: &[DeclaredType], types: &BTreeSet<String>) -> anyhow::Result<Bundle> {
+    let by_id: BTreeMap<&str, &DeclaredType> =
+        declared.iter().map(|d| (d.schema_id.as_str(), d)).collect();
+    let selected: BTreeSet<&str> = declared
+        .iter()
+        .filter(|d| types.is_empty() || types.contains(&d.struct_name))
+        .map(|d| d.schema_id.as_str())
+        .collect();
+    if selected.is_empty() {
+        bail!("No annotated type matches {types:?}");
+    }
+    let leaves: Vec<&str> = selected
+        .iter()
+        .copied()
+        .filter(|id| {
+            !selected
+                .iter()
+                .any(|other| other != id && other.starts_with(id))
+        })
+        .collect();
+
+    let mut schemas = BTreeMap::new();
+    let mut store = GtsStore::new(None);
+    for leaf in &leaves {
+        for schema_id in chain_of(leaf) {
+            if schemas.contains_key(&schema_id) {
+                continue;
+            }
+            let Some(decl) = by_id.get(schema_id.as_str()) else {
+                bail!("Schema {schema_id} in the chain of {leaf} is not declared by any struct");
+            };
+            let text = std::fs::read_to_string(&decl.schema_path).with_context(|| {
+                format!(
+                    "reading {} (run `gts generate-from-rust` first)",
+                    decl.schema_path.display()
+                )
+            })?;
+            let schema: Value = serde_json::from_str(&text)
+                .with_context(|| format!("parsing {}", decl.schema_path.display()))?;
+            store.register_schema(&schema_id, &schema)?;
+            schemas.insert(schema_id, schema);
+        }
+    }
+
+    let mut chains = Vec::new();
+    for leaf in leaves {
+        let sample = store.default_instance(leaf)?;
+        let mut instance = sample.instance;
+        // Type references can only hold GTS IDs; point them at the chain itself.
+        if let (Some(obj), Ok(resolved)) =
+            (instance.as_object_mut(), store.get_schema_content(leaf))
+        {
+            let resolved = store.resolve_schema_refs(&resolved);
+            for path in &sample.zero_filled {
+                let refs_gts = resolved["properties"][path.as_str()]
+                    .get("x-gts-ref")
+                    .is_some();
+                if refs_gts && obj.get(path).is_some_and(Value::is_string) {
+                    obj.insert(path.clone(), Value::String(leaf.to_owned()));
+                }
+            }
+        }
+        let mut schema_ids = chain_of(leaf);
+        schema_ids.reverse();
+        chains.push(BundleChain {
+            schema_ids,
+            instance_file: format!("{leaf}.example.json"),
+            instance,
+            zero_filled: sample.zero_filled,
+        });
+    }
+    Ok(Bundle { schemas, chains })
+}
+
+/// Appends the `ajv` command validating `instance_file` against a chain.
+///
+/// # Errors
+/// Only fails if writing to the string fails.
+pub fn push_ajv_command(
+    script: &mut String,
+    schema_ids: &[impl AsRef<str>],
+    instance_file: &str,
+) -> std::fmt::Result {
+    script.push_str("npx ajv-cli validate \\\n");
+    script.push_str("  --spec=draft7 \\\n");
+    script.push_str("  -c ajv-formats \\\n");
+    script.push_str("  --strict=false \\\n");
+
+    // Main schema (-s): the innermost/most derived schema
+    if let Some((main, refs)) = schema_ids.split_first() {
+        writeln!(
+            script,
+            "  -s \"$SCRIPT_DIR/{}.schema.json\" \\",
+            main.as_ref()
+        )?;
+        // Referenced schemas (-r): from most derived to base, excluding the main schema
+        for schema_id in refs {
+            writeln!(
+                script,
+                "  -r \"$SCRIPT_DIR/{}.schema.json\" \\",
+                schema_id.as_ref()
+            )?;
+        }
+    }
+
+    // Data file (-d): the instance
+    writeln!(script, "  -d \"$SCRIPT_DIR/{instance_file}\"")
+}
+
+/// Writes `script` to `dir/validate.sh` and makes it executable.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_validate_script(dir: &Path, script: &str) -> anyhow::Result<PathBuf> {
+    let validate_path = dir.join("validate.sh");
+    std::fs::write(&validate_path, script)?;
+
+    // Make the script executable on Unix
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(&validate_path)?.permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&validate_path, perms)?;
+    }
+    Ok(validate_path)
+}
+
+/// Script header that resolves paths relative to the script's directory.
+pub const SCRIPT_HEADER: &str =
+    "#!/bin/bash\n\nSCRIPT_DIR=\"$(cd \"$(dirname \"$0\")\" && pwd)\"\n\n";
+
+/// Scans `source`, builds the chains of the types named in `types` (all
+/// annotated types if empty) and writes the bundle to `dir`.
+///
+/// # Errors
+/// Returns an error if scanning, building a chain or writing fails.
+pub fn dump_bundle(source: &Path, types: &BTreeSet<String>, dir: &Path) -> anyhow::Result<()> {
+    let declared = scan_crate(source)?;
+    let Bundle { schemas, chains } = build_chains(&declared, types)?;
+
+    std::fs::create_dir_all(dir)?;
+    for (schema_id, schema) in &schemas {
+        crate::save_schema(dir, schema, schema_id)?;
+    }
+
+    let mut script = String::from(SCRIPT_HEADER);
+    script.push_str("set -e\n\n");
+    for chain in &chains {
+        let instance_path = dir.join(&chain.instance_file);
+        std::fs::write(
+            &instance_path,
+            serde_json::to_string_pretty(&chain.instance)? + "\n",
+        )?;
+        println!("Saved instance: {}", instance_path.display());
+        if !chain.zero_filled.is_empty() {
+            println!("  placeholder values: {}", chain.zero_filled.join(", "));
+        }
+        push_ajv_command(&mut script, &chain.schema_ids, &chain.instance_file)?;
+        script.push('\n');
+    }
+    let validate_path = write_validate_script(dir, &script)?;
+    println!("Saved validate script: {}", validate_path.display());
+
+    println!(
+        "\nDone! {} chain(s), {} schema(s) saved to: {}",
+        chains.len(),
+        schemas.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const BASE: &str = "gts.x.test.bundle.event.v1~";
+    const CHILD: &str = "gts.x.test.bundle.event.v1~x.test.bundle.created.v1~";
+    const OTHER: &str = "gts.x.test.bundle.note.v1~";
+
+    const SOURCE: &str = r#"
+#[struct_to_gts_schema(dir_path = "schemas", base = true, schema_id = "gts.x.test.bundle.event.v1~")]
+pub struct EventV1<P> {
+    pub kind: GtsSchemaId,
+    pub payload: P,
+}
+
+#[struct_to_gts_schema(dir_path = "schemas", base = EventV1, schema_id = "gts.x.test.bundle.event.v1~x.test.bundle.created.v1~")]
+#[derive(Debug)]
+pub struct CreatedV1 {
+    pub name: String,
+}
+
+#[struct_to_gts_schema(dir_path = "schemas", base = true, schema_id = "gts.x.test.bundle.note.v1~")]
+pub struct NoteV1 {
+    pub text: String,
+}
+"#;
+
+    fn write_schema(dir: &Path, id: &str, body: &Value) {
+        let mut schema = json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+        });
+        schema
+            .as_object_mut()
+            .unwrap()
+            .extend(body.as_object().unwrap().clone());
+        fs::write(
+            dir.join(format!("{id}.schema.json")),
+            serde_json::to_string(&schema).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn crate_dir() -> TempDir {
+        let root = TempDir::new().unwrap();
+        let src = root.path().join("src");
+        let schemas = src.join("schemas");
+        fs::create_dir_all(&schemas).unwrap();
+        fs::write(src.join("events.rs"), SOURCE).unwrap();
+        write_schema(
+            &schemas,
+            BASE,
+            &json!({
+                "type": "object",
+                "properties": {
+                    "kind": {"type": "string", "x-gts-ref": "gts.*"},
+                    "payload": {"type": "object"}
+                },
+                "required": ["kind", "payload"]
+            }),
+        );
+        write_schema(
+            &schemas,
+            CHILD,
+            &json!({
+                "type": "object",
+                "allOf": [
+                    {"$ref": format!("gts://{BASE}")},
+                    {"properties": {"payload": {
+                        "type": "object",
+                        "properties": {"name": {"type": "string"}},
+                        "required": ["name"]
+                    }}}
+                ]
+            }),
+        );
+        write_schema(
+            &schemas,
+            OTHER,
+            &json!({
+                "type": "object",
+                "properties": {"text": {"type": "string"}},
+                "required": ["text"]
+            }),
+        );
+        root
+    }
+
+    #[test]
+    fn test_parse_type_names() {
+        let names = parse_type_names("EventV1<CreatedV1<()>>, NoteV1");
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec!["CreatedV1", "EventV1", "NoteV1"]
+        );
+    }
+
+    #[test]
+    fn test_build_chains_for_leaves() {
+        let root = crate_dir();
+        let declared = scan_crate(root.path()).unwrap();
+        assert_eq!(declared.len(), 3);
+
+        let Bundle { schemas, chains } = build_chains(&declared, &BTreeSet::new()).unwrap();
+        assert_eq!(schemas.len(), 3);
+        let leaves: Vec<&str> = chains.iter().map(|c| c.schema_ids[0].as_str()).collect();
+        assert_eq!(leaves, vec![CHILD, OTHER]);
+        assert_eq!(chains[0].schema_ids, vec![CHILD, BASE]);
+        assert_eq!(chains[0].instance["kind"], CHILD);
+        assert_eq!(chains[0].instance["payload"]["name"], "");
+
+        let only_base = parse_type_names("EventV1");
+        let Bundle { schemas, chains } = build_chains(&declared, &only_base).unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(chains[0].schema_ids, vec![BASE]);
+
+        assert!(build_chains(&declared, &parse_type_names("Missing")).is_err());
+    }
+
+    #[test]
+    fn test_dump_bundle_writes_every_chain() {
+        let root = crate_dir();
+        let out = root.path().join("bundle");
+        dump_bundle(root.path(), &parse_type_names("CreatedV1,NoteV1"), &out).unwrap();
+
+        for id in [BASE, CHILD, OTHER] {
+            assert!(out.join(format!("{id}.schema.json")).exists(), "{id}");
+        }
+        assert!(out.join(format!("{CHILD}.example.json")).exists());
+        assert!(out.join(format!("{OTHER}.example.json")).exists());
+
+        let script = fs::read_to_string(out.join("validate.sh")).unwrap();
+        assert_eq!(script.matches("npx ajv-cli validate").count(), 2);
+        assert!(script.contains(&format!("-s \"$SCRIPT_DIR/{CHILD}.schema.json\"")));
+        assert!(script.contains(&format!("-r \"$SCRIPT_DIR/{BASE}.schema.json\"")));
+    }
+
+    #[test]
+    fn test_missing_schema_file_names_the_generator() {
+        let root = crate_dir();
+        fs::remove_file(
+            root.path()
+                .join("src/schemas")
+                .join(format!("{BASE}.schema.json")),
+        )
+        .unwrap();
+        let declared = scan_crate(root.path()).unwrap();
+        let err = build_chains(&declared, &BTreeSet::new()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("gts generate-from-rust"),
+            "{err:#}"
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use gts::gts::GtsSchemaId;
-use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
 use clap::Parser;
 use gts::gts_schema_for;
 use serde::{Deserialize, Serialize};
 
+mod bundle;
+
 const SEPARATOR: &str =
     "================================================================================";
 
@@ -83,15 +84,30 @@ struct Args {
     /// Instances are saved as `{instance_id}.json`.
     #[arg(long, value_name = "DIR")]
     dump: Option<PathBuf>,
+
+    /// Crate to scan for `#[struct_to_gts_schema]` types instead of dumping
+    /// the built-in demo chain. Reads the schemas `gts generate-from-rust`
+    /// wrote and emits one sample instance per chain.
+    #[arg(long, value_name = "DIR", requires = "dump")]
+    source: Option<PathBuf>,
+
+    /// Only dump the chains of these types, e.g.
+    /// `BaseEventV1<AuditPayloadV1<()>>,OrderV1` (implies `--source .`).
+    #[arg(long, value_name = "TYPES", requires = "dump")]
+    types: Option<String>,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    if let Some(dir) = args.dump {
-        dump_to_directory(&dir)?;
-    } else {
-        run_demo()?;
+    match (args.dump, args.source, args.types) {
+        (Some(dir), None, None) => dump_to_directory(&dir)?,
+        (Some(dir), source, types) => {
+            let source = source.unwrap_or_else(|| PathBuf::from("."));
+            let types = types.as_deref().map(bundle::parse_type_names);
+            bundle::dump_bundle(&source, &types.unwrap_or_default(), &dir)?;
+        }
+        (None, ..) => run_demo()?,
     }
 
     Ok(())
@@ -196,43 +212,13 @@ fn dump_to_directory(dir: &Path) -> anyhow::Result<()> {
     let schema4_id = test_structs::PlaceOrderDataPayloadV1::SCHEMA_ID;
     let schema_ids = [schema4_id, schema3_id, schema2_id, schema1_id];
 
-    let mut validate_script = String::from("#!/bin/bash\n\n");
-    // Get the directory where this script is located, so it works from any location
-    validate_script.push_str("SCRIPT_DIR=\"$(cd \"$(dirname \"$0\")\" && pwd)\"\n\n");
-    validate_script.push_str("npx ajv-cli validate \\\n");
-    validate_script.push_str("  --spec=draft7 \\\n");
-    validate_script.push_str("  -c ajv-formats \\\n");
-    validate_script.push_str("  --strict=false \\\n");
-
-    // Main schema (-s): the innermost/most derived schema
-    let main_schema_id = schema_ids[0];
-    writeln!(
-        validate_script,
-        "  -s \"$SCRIPT_DIR/{main_schema_id}.schema.json\" \\"
+    let mut validate_script = String::from(bundle::SCRIPT_HEADER);
+    bundle::push_ajv_command(
+        &mut validate_script,
+        &schema_ids,
+        &format!("{instance_id}.json"),
     )?;
-
-    // Referenced schemas (-r): from most derived to base, excluding the main schema
-    for schema_id in &schema_ids[1..] {
-        writeln!(
-            validate_script,
-            "  -r \"$SCRIPT_DIR/{schema_id}.schema.json\" \\"
-        )?;
-    }
-
-    // Data file (-d): the instance
-    writeln!(validate_script, "  -d \"$SCRIPT_DIR/{instance_id}.json\"")?;
-
-    let validate_path = dir.join("validate.sh");
-    std::fs::write(&validate_path, validate_script)?;
-
-    // Make the script executable on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&validate_path)?.permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(&validate_path, perms)?;
-    }
+    let validate_path = bundle::write_validate_script(dir, &validate_script)?;
 
     println!("Saved validate script: {}", validate_path.display());
 
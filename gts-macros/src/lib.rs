@@ -626,6 +626,7 @@ struct GtsSchemaArgs {
     description: String,
     properties: String,
     base: BaseAttr,
+    payload_must_be_gts: bool,
 }
 
 impl Parse for GtsSchemaArgs {
@@ -635,6 +636,7 @@ impl Parse for GtsSchemaArgs {
         let mut description: Option<String> = None;
         let mut properties: Option<String> = None;
         let mut base: Option<BaseAttr> = None;
+        let mut payload_must_be_gts = false;
 
         while !input.is_empty() {
             let key: syn::Ident = input.parse()?;
@@ -706,10 +708,14 @@ impl Parse for GtsSchemaArgs {
                         ));
                     }
                 }
+                "payload_must_be_gts" => {
+                    let value: syn::LitBool = input.parse()?;
+                    payload_must_be_gts = value.value;
+                }
                 _ => {
                     return Err(syn::Error::new_spanned(
                         key,
-                        "Unknown attribute. Expected: dir_path, schema_id, description, properties, or base",
+                        "Unknown attribute. Expected: dir_path, schema_id, description, properties, base, or payload_must_be_gts",
                     ));
                 }
             }
@@ -730,6 +736,7 @@ impl Parse for GtsSchemaArgs {
                 .ok_or_else(|| input.error("Missing required attribute: properties"))?,
             base: base
                 .ok_or_else(|| input.error("Missing required attribute: base (use 'base = true' for base types or 'base = ParentStruct' for child types)"))?,
+            payload_must_be_gts,
         })
     }
 }
@@ -752,7 +759,7 @@ impl Parse for GtsSchemaArgs {
 ///
 /// After annotating your structs, run:
 /// ```bash
-/// cargo gts generate --source src/
+/// cargo gts generate
 /// ```
 ///
 /// Or use the GTS CLI directly:
@@ -788,6 +795,9 @@ impl Parse for GtsSchemaArgs {
 /// * `base` - Explicit base/parent struct declaration (required):
 ///   - `base = true`: Marks this struct as a base type (must have single-segment `schema_id`)
 ///   - `base = ParentStruct`: Parent struct name (macro automatically uses `ParentStruct<()>`)
+/// * `payload_must_be_gts` - Optional. With `payload_must_be_gts = true` the generic parameter
+///   must implement `gts::GtsTyped`, i.e. be declared with `struct_to_gts_schema` itself (or be
+///   `()`). Hand-written `GtsSchema` impls are rejected at compile time.
 ///
 /// # Memory Efficiency
 ///
@@ -840,6 +850,15 @@ pub fn struct_to_gts_schema(attr: TokenStream, item: TokenStream) -> TokenStream
         .into();
     }
 
+    if args.payload_must_be_gts && generic_count == 0 {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "struct_to_gts_schema: payload_must_be_gts requires a generic payload parameter (e.g., `struct BaseEventV1<P>`)",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     // base = true can have 0 or 1 generic field:
     // - 0 generics: This is a leaf/terminal type, no derived structs can extend it
     // - 1 generic: Derived structs can extend via the generic field
@@ -929,6 +948,11 @@ pub fn struct_to_gts_schema(attr: TokenStream, item: TokenStream) -> TokenStream
     let mut modified_input = input.clone();
     for param in modified_input.generics.type_params_mut() {
         param.bounds.push(syn::parse_quote!(::gts::GtsSchema));
+        // With payload_must_be_gts, hand-written GtsSchema impls are not enough:
+        // the payload must itself be declared with struct_to_gts_schema.
+        if args.payload_must_be_gts {
+            param.bounds.push(syn::parse_quote!(::gts::GtsTyped));
+        }
     }
 
     // For nested structs, remove Serialize/Deserialize to prevent direct serialization
@@ -1813,6 +1837,9 @@ pub fn struct_to_gts_schema(attr: TokenStream, item: TokenStream) -> TokenStream
             #gts_schema_impl
         }
 
+        // Mark this struct as declared with struct_to_gts_schema
+        impl #impl_generics ::gts::GtsTyped for #struct_name #ty_generics #gts_schema_where_clause {}
+
         // Public API methods for schema serialization
         impl #impl_generics #struct_name #ty_generics #gts_schema_where_clause {
             /// Get the JSON Schema with `allOf` + `$ref` for inheritance as a JSON string.
@@ -0,0 +1,20 @@
+//! Test: payload_must_be_gts = true on a struct without a generic payload
+//! parameter should fail
+
+use gts_macros::struct_to_gts_schema;
+
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = true,
+    schema_id = "gts.x.core.events.topic.v1~",
+    description = "Event topic",
+    properties = "id,name",
+    payload_must_be_gts = true
+)]
+#[derive(Debug)]
+pub struct EventTopicV1 {
+    pub id: gts::GtsInstanceId,
+    pub name: String,
+}
+
+fn main() {}
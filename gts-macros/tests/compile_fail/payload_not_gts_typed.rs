@@ -0,0 +1,43 @@
+//! Test: With payload_must_be_gts = true, a payload that only implements
+//! GtsSchema by hand (not declared with struct_to_gts_schema) should fail
+
+use gts::{GtsInstanceId, GtsSchema};
+use gts_macros::struct_to_gts_schema;
+
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = true,
+    schema_id = "gts.x.core.events.type.v1~",
+    description = "Base event type",
+    properties = "id,payload",
+    payload_must_be_gts = true
+)]
+#[derive(Debug)]
+pub struct BaseEventV1<P> {
+    pub id: GtsInstanceId,
+    pub payload: P,
+}
+
+// An ad-hoc payload with a hand-written GtsSchema impl
+#[derive(Debug, schemars::JsonSchema)]
+pub struct AdHocPayload {
+    pub note: String,
+}
+
+impl GtsSchema for AdHocPayload {
+    const SCHEMA_ID: &'static str = "gts.x.core.events.type.v1~x.adhoc.note.v1~";
+
+    fn gts_schema_with_refs() -> serde_json::Value {
+        serde_json::json!({"type": "object"})
+    }
+}
+
+fn main() {
+    // This should fail: AdHocPayload does not implement GtsTyped
+    let _event: BaseEventV1<AdHocPayload> = BaseEventV1 {
+        id: GtsInstanceId::new("gts.x.core.events.type.v1~", "x.adhoc.note.v1"),
+        payload: AdHocPayload {
+            note: "hello".to_owned(),
+        },
+    };
+}
@@ -218,6 +218,36 @@ pub struct ContentV1 {
     pub content_value: String,
 }
 
+/* ============================================================
+Envelope that only accepts GTS payloads (payload_must_be_gts)
+============================================================ */
+
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = true,
+    schema_id = "gts.x.core.events.envelope.v1~",
+    description = "Envelope restricted to GTS payloads",
+    properties = "id,payload",
+    payload_must_be_gts = true
+)]
+#[derive(Debug)]
+pub struct EnvelopeV1<P> {
+    pub id: GtsInstanceId,
+    pub payload: P,
+}
+
+#[struct_to_gts_schema(
+    dir_path = "schemas",
+    base = EnvelopeV1,
+    schema_id = "gts.x.core.events.envelope.v1~x.app.notes.note.v1~",
+    description = "Note carried in a strict envelope",
+    properties = "text"
+)]
+#[derive(Debug)]
+pub struct NoteV1 {
+    pub text: String,
+}
+
 /* ============================================================
 The macro automatically generates:
 - GTS_SCHEMA_JSON constants with proper allOf inheritance
@@ -2754,4 +2784,25 @@ mod tests {
             "inner_data.properties should have 'content_value'"
         );
     }
+
+    #[test]
+    fn test_payload_must_be_gts_accepts_gts_payloads() {
+        fn assert_gts_typed<T: gts::GtsTyped>() {}
+        assert_gts_typed::<NoteV1>();
+        assert_gts_typed::<EnvelopeV1<NoteV1>>();
+        assert_gts_typed::<EnvelopeV1<()>>();
+
+        let event = EnvelopeV1 {
+            id: EnvelopeV1::<()>::gts_make_instance_id("x.app.notes.n1.v1"),
+            payload: NoteV1 {
+                text: "hello".to_owned(),
+            },
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["payload"]["text"], "hello");
+        assert_eq!(
+            <EnvelopeV1<NoteV1> as GtsSchema>::innermost_schema_id(),
+            "gts.x.core.events.envelope.v1~x.app.notes.note.v1~"
+        );
+    }
 }
@@ -1316,3 +1316,8 @@ fn test_make_instance_id_with_complex_segment() {
         "gts.x.core.events.topic.v1~vendor.marketplace.orders.v1"
     );
 }
+
+#[test]
+fn test_schema_snapshot() {
+    gts::gts_schema_snapshot!(ProductV1);
+}
@@ -0,0 +1,118 @@
+//! Node.js bindings for GTS.
+//!
+//! A thin N-API layer over [`GtsOps`]: every method returns the same JSON
+//! object the CLI prints (see [`GtsOpsResult::to_dict`]), so JavaScript
+//! services get the validation and casting semantics of the Rust services
+//! without reimplementing them.  Build with `napi build --release` from this
+//! directory; the generated `index.js` and `index.d.ts` load the addon.
+
+use gts::GtsOps;
+use gts::ops::GtsOpsResult;
+use napi_derive::napi;
+use serde_json::Value;
+
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+
+/// Parses a GTS ID into its segments.
+#[napi]
+#[must_use]
+pub fn parse_id(gts_id: String) -> Value {
+    GtsOps::parse_id(&gts_id).to_dict()
+}
+
+/// Checks whether a string is a valid GTS ID.
+#[napi]
+#[must_use]
+pub fn validate_id(gts_id: String) -> Value {
+    GtsOps::validate_id(&gts_id).to_dict()
+}
+
+/// A GTS store, exposed to JavaScript as `GtsStore`.
+#[napi(js_name = "GtsStore")]
+pub struct JsGtsStore {
+    ops: GtsOps,
+}
+
+#[napi]
+impl JsGtsStore {
+    /// Loads the entities under `paths`, reading settings from the optional
+    /// `config` file.
+    #[napi(constructor)]
+    #[must_use]
+    pub fn new(paths: Option<Vec<String>>, config: Option<String>) -> Self {
+        Self {
+            ops: GtsOps::new(paths, config, 0),
+        }
+    }
+
+    #[napi]
+    pub fn register_schema(&mut self, type_id: String, schema: Value) -> Value {
+        self.ops.add_schema(type_id, &schema).to_dict()
+    }
+
+    /// Registers a schema or instance, validating instances unless
+    /// `validate` is `false`.
+    #[napi]
+    pub fn add_entity(&mut self, content: Value, validate: Option<bool>) -> Value {
+        self.ops
+            .add_entity(&content, validate.unwrap_or(true))
+            .to_dict()
+    }
+
+    #[napi]
+    pub fn get_entity(&mut self, gts_id: String) -> Value {
+        self.ops.get_entity(&gts_id).to_dict()
+    }
+
+    #[napi]
+    pub fn validate_instance(&mut self, gts_id: String) -> Value {
+        self.ops.validate_instance(&gts_id).to_dict()
+    }
+
+    /// Entities matching `expr`, at most `limit` (100 by default).
+    #[napi]
+    #[must_use]
+    pub fn query(&self, expr: String, limit: Option<u32>) -> Value {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT) as usize;
+        self.ops.query(&expr, limit).to_dict()
+    }
+
+    #[napi]
+    pub fn cast(&mut self, from_id: String, to_schema_id: String) -> Value {
+        self.ops.cast(&from_id, &to_schema_id).to_dict()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_store_methods_return_ops_dicts() {
+        let mut store = JsGtsStore::new(None, None);
+        let schema_id = "gts.x.test.node.item.v1~";
+        let added = store.register_schema(
+            schema_id.to_owned(),
+            json!({
+                "$id": format!("gts://{schema_id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {"id": {"type": "string"}, "n": {"type": "integer"}}
+            }),
+        );
+        assert_eq!(added["ok"], true);
+
+        let id = format!("{schema_id}x.test._.a.v1");
+        assert_eq!(
+            store.add_entity(json!({"id": id, "n": 1}), None)["ok"],
+            true
+        );
+        assert_eq!(store.validate_instance(id.clone())["ok"], true);
+        assert_eq!(
+            store.query("gts.x.test.node.*".to_owned(), Some(1))["count"],
+            1
+        );
+        assert_eq!(parse_id(schema_id.to_owned())["ok"], true);
+    }
+}
@@ -0,0 +1,133 @@
+//! WebAssembly bindings for GTS.
+//!
+//! Exposes an in-memory store, instance validation, cast previews and ID
+//! parsing to JavaScript, e.g. for a browser-based schema editor.  There is
+//! no filesystem: schemas and instances are registered from JS values.
+//! Every method returns the same JSON object the CLI prints (see
+//! [`GtsOpsResult::to_dict`]).  Build with
+//! `wasm-pack build gts-wasm --target web`.
+
+use gts::GtsOps;
+use gts::ops::GtsOpsResult;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+fn from_js(value: JsValue) -> Result<Value, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn to_js(result: &impl GtsOpsResult) -> Result<JsValue, JsError> {
+    result
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Parses a GTS ID into its segments.
+///
+/// # Errors
+/// Fails only if the result cannot be converted to a JS value.
+#[wasm_bindgen(js_name = parseId)]
+pub fn parse_id(gts_id: &str) -> Result<JsValue, JsError> {
+    to_js(&GtsOps::parse_id(gts_id))
+}
+
+/// Checks whether a string is a valid GTS ID.
+///
+/// # Errors
+/// Fails only if the result cannot be converted to a JS value.
+#[wasm_bindgen(js_name = validateId)]
+pub fn validate_id(gts_id: &str) -> Result<JsValue, JsError> {
+    to_js(&GtsOps::validate_id(gts_id))
+}
+
+/// An in-memory GTS store, exposed to JavaScript as `GtsStore`.
+#[wasm_bindgen(js_name = GtsStore)]
+pub struct WasmGtsStore {
+    ops: GtsOps,
+}
+
+impl Default for WasmGtsStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(js_class = GtsStore)]
+impl WasmGtsStore {
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ops: GtsOps::new(None, None, 0),
+        }
+    }
+
+    /// # Errors
+    /// Fails if `schema` or the result cannot be converted.
+    #[wasm_bindgen(js_name = registerSchema)]
+    pub fn register_schema(
+        &mut self,
+        type_id: String,
+        schema: JsValue,
+    ) -> Result<JsValue, JsError> {
+        to_js(&self.ops.add_schema(type_id, &from_js(schema)?))
+    }
+
+    /// Registers a schema or instance, validating instances unless
+    /// `validate` is `false`.
+    ///
+    /// # Errors
+    /// Fails if `content` or the result cannot be converted.
+    #[wasm_bindgen(js_name = addEntity)]
+    pub fn add_entity(
+        &mut self,
+        content: JsValue,
+        validate: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        to_js(
+            &self
+                .ops
+                .add_entity(&from_js(content)?, validate.unwrap_or(true)),
+        )
+    }
+
+    /// # Errors
+    /// Fails only if the result cannot be converted.
+    #[wasm_bindgen(js_name = validateInstance)]
+    pub fn validate_instance(&mut self, gts_id: &str) -> Result<JsValue, JsError> {
+        to_js(&self.ops.validate_instance(gts_id))
+    }
+
+    /// Validates an unregistered instance, e.g. the one in the editor,
+    /// against a registered schema.
+    ///
+    /// # Errors
+    /// Fails if `content` or the result cannot be converted.
+    #[wasm_bindgen(js_name = validateValue)]
+    pub fn validate_value(
+        &mut self,
+        schema_id: &str,
+        content: JsValue,
+    ) -> Result<JsValue, JsError> {
+        to_js(&self.ops.validate_value(schema_id, &from_js(content)?))
+    }
+
+    /// Previews casting a registered instance to another schema; nothing is
+    /// changed in the store.
+    ///
+    /// # Errors
+    /// Fails only if the result cannot be converted.
+    pub fn cast(&mut self, from_id: &str, to_schema_id: &str) -> Result<JsValue, JsError> {
+        to_js(&self.ops.cast(from_id, to_schema_id))
+    }
+
+    /// Entities matching `expr`, at most `limit` (100 by default).
+    ///
+    /// # Errors
+    /// Fails only if the result cannot be converted.
+    pub fn query(&self, expr: &str, limit: Option<usize>) -> Result<JsValue, JsError> {
+        to_js(&self.ops.query(expr, limit.unwrap_or(DEFAULT_QUERY_LIMIT)))
+    }
+}
@@ -0,0 +1,184 @@
+//! Schema bundles read straight from `.zip`, `.tar`, `.tar.gz` and `.tgz`
+//! archives.
+//!
+//! [`GtsFileReader`](crate::GtsFileReader) accepts archives wherever it
+//! accepts files: the GTS files inside are read into memory, without
+//! extracting them to disk, and their entities are keyed by paths such as
+//! `bundle.tar.gz/schemas/order.v1~.schema.json`.
+
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::files_reader::GtsFileReader;
+
+const ARCHIVE_SUFFIXES: &[&str] = &[".zip", ".tar", ".tar.gz", ".tgz"];
+
+/// A GTS file inside an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtsArchiveEntry {
+    /// Path of the entry within the archive.
+    pub name: String,
+    pub content: String,
+}
+
+/// Returns true if `path` names an archive by its extension.
+#[must_use]
+pub fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// The GTS files in the archive at `path`, in archive order.  Directories,
+/// files of other types and entries that are not UTF-8 are skipped.
+///
+/// # Errors
+/// Returns an error if the archive cannot be opened or is corrupt.
+pub fn read_archive(path: &Path) -> io::Result<Vec<GtsArchiveEntry>> {
+    let file = File::open(path)?;
+    let extension = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    match extension.as_str() {
+        "zip" => read_zip(file),
+        "tar" => read_tar(file),
+        _ => read_tar(GzDecoder::new(file)),
+    }
+}
+
+/// `content` if it is text, else `None` with a warning.
+fn text_entry(name: String, content: Vec<u8>) -> Option<GtsArchiveEntry> {
+    if let Ok(content) = String::from_utf8(content) {
+        Some(GtsArchiveEntry { name, content })
+    } else {
+        tracing::warn!("Skipping archive entry {name}: not UTF-8");
+        None
+    }
+}
+
+fn read_zip(file: File) -> io::Result<Vec<GtsArchiveEntry>> {
+    let mut archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+    let mut entries = Vec::new();
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx).map_err(io::Error::other)?;
+        let name = entry.name().to_owned();
+        if !entry.is_file() || !GtsFileReader::is_gts_file(Path::new(&name)) {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        entries.extend(text_entry(name, content));
+    }
+    Ok(entries)
+}
+
+fn read_tar(reader: impl Read) -> io::Result<Vec<GtsArchiveEntry>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        if !entry.header().entry_type().is_file() || !GtsFileReader::is_gts_file(Path::new(&name)) {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        entries.extend(text_entry(name, content));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::store::GtsReader;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const FILES: [(&str, &str); 3] = [
+        (
+            "schemas/gts.x.test.archive.item.v1~.schema.json",
+            r#"{"$id": "gts://gts.x.test.archive.item.v1~", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}"#,
+        ),
+        (
+            "instances/items.yaml",
+            "- id: gts.x.test.archive.item.v1~x.test._.one.v1\n- id: gts.x.test.archive.item.v1~x.test._.two.v1\n",
+        ),
+        ("README.md", "not a GTS file"),
+    ];
+
+    fn write_tar_gz(path: &Path) {
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(path).unwrap(),
+            Compression::default(),
+        ));
+        for (name, content) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn write_zip(path: &Path) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        zip.add_directory("schemas/", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        for (name, content) in FILES {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_reads_gts_files_from_zip_and_tar_gz() {
+        let dir = TempDir::new().unwrap();
+        let tgz = dir.path().join("bundle.tar.gz");
+        let zip = dir.path().join("bundle.ZIP");
+        write_tar_gz(&tgz);
+        write_zip(&zip);
+        assert!(is_archive(&tgz) && is_archive(&zip));
+        assert!(!is_archive(Path::new("bundle.json")));
+
+        for archive in [&tgz, &zip] {
+            let names: Vec<String> = read_archive(archive)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect();
+            assert_eq!(names, [FILES[0].0, FILES[1].0]);
+
+            let mut reader = GtsFileReader::new(&[archive.to_string_lossy().to_string()], None);
+            let mut ids: Vec<String> = reader.iter().filter_map(|e| e.effective_id()).collect();
+            ids.sort();
+            assert_eq!(
+                ids,
+                [
+                    "gts.x.test.archive.item.v1~",
+                    "gts.x.test.archive.item.v1~x.test._.one.v1",
+                    "gts.x.test.archive.item.v1~x.test._.two.v1",
+                ]
+            );
+        }
+
+        let broken = dir.path().join("broken.tgz");
+        std::fs::write(&broken, "not gzip").unwrap();
+        assert!(read_archive(&broken).is_err());
+    }
+}
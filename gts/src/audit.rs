@@ -0,0 +1,480 @@
+//! Store-wide audit reports.
+//!
+//! **Majors:** groups every registered schema by its type family (the GTS ID
+//! with the version of the last segment stripped) and reports families where
+//! more than one major version is live at the same time, together with the
+//! number of instances bound to each major.  The report is meant to drive
+//! deprecation decisions for old majors.
+//!
+//! **Refs:** collects the references of every entity, as
+//! [`GtsStore::references`] finds them, except IDs merely named in instance
+//! content, and reports the targets missing from the store, grouped by the
+//! entity that holds them.
+//!
+//! **Referrers:** the reverse lookup — every entity holding a reference to
+//! a given ID, for impact analysis before a schema is deleted or deprecated.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use web_time::UNIX_EPOCH;
+
+use crate::gts::{GTS_PREFIX, GtsID};
+use crate::references::GtsReferenceKind;
+use crate::store::GtsStore;
+
+/// Usage summary for one major version of a type family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMajorVersionUsage {
+    pub major: u32,
+    /// All registered schemas belonging to this major (e.g. `v2.0~`, `v2.1~`).
+    pub schema_ids: Vec<String>,
+    pub instance_count: usize,
+    /// Unix timestamp (seconds) of the last validation against any schema of
+    /// this major. Only present when usage tracking is enabled on the store.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<u64>,
+}
+
+/// A type family with more than one live major version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMajorTypeReport {
+    /// Type family key, e.g. `gts.x.core.events.event`.
+    pub type_family: String,
+    /// Majors in ascending order.
+    pub majors: Vec<GtsMajorVersionUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMajorsAuditResult {
+    pub count: usize,
+    pub usage_tracking: bool,
+    pub types: Vec<GtsMajorTypeReport>,
+}
+
+/// A reference whose target is not in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsDanglingRef {
+    /// JSON pointer of the reference within the entity.
+    pub path: String,
+    /// `parent`, `$ref`, `x-gts-ref` or `schema`.
+    pub kind: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsEntityDanglingRefs {
+    pub id: String,
+    pub refs: Vec<GtsDanglingRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsRefsAuditResult {
+    /// Total number of dangling references.
+    pub count: usize,
+    /// Entities holding dangling references, ordered by ID.
+    pub entities: Vec<GtsEntityDanglingRefs>,
+}
+
+/// An entity holding a reference to a given ID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsReferrer {
+    pub id: String,
+    pub is_schema: bool,
+    /// JSON pointer of the reference within the referrer.
+    pub path: String,
+    /// `parent`, `$ref`, `x-gts-ref`, `schema` or `content`.
+    pub kind: String,
+}
+
+/// Splits a GTS ID into its type family key and the major version of its
+/// last segment.
+pub(crate) fn type_family(gid: &GtsID) -> Option<(String, u32)> {
+    let (last, prefix) = gid.gts_id_segments.split_last()?;
+    let prefix: String = prefix.iter().map(|s| s.segment.as_str()).collect();
+    let family = format!(
+        "{GTS_PREFIX}{prefix}{}.{}.{}.{}",
+        last.vendor, last.package, last.namespace, last.type_name
+    );
+    Some((family, last.ver_major))
+}
+
+impl GtsStore {
+    /// Reports type families that have more than one live major version.
+    #[must_use]
+    pub fn audit_majors(&self) -> GtsMajorsAuditResult {
+        let mut families: BTreeMap<String, BTreeMap<u32, GtsMajorVersionUsage>> = BTreeMap::new();
+
+        for (id, entity) in self.items() {
+            if !entity.is_schema {
+                continue;
+            }
+            let Some((family, major)) = GtsID::new(id).ok().as_ref().and_then(type_family) else {
+                continue;
+            };
+            let usage = families
+                .entry(family)
+                .or_default()
+                .entry(major)
+                .or_insert_with(|| GtsMajorVersionUsage {
+                    major,
+                    schema_ids: Vec::new(),
+                    instance_count: 0,
+                    last_seen: None,
+                });
+            usage.schema_ids.push(id.clone());
+            let seen = self
+                .last_used(id)
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            usage.last_seen = usage.last_seen.max(seen);
+        }
+
+        for (_, entity) in self.items() {
+            if entity.is_schema {
+                continue;
+            }
+            let Some((family, major)) = entity
+                .schema_id
+                .as_deref()
+                .and_then(|s| GtsID::new(s).ok())
+                .as_ref()
+                .and_then(type_family)
+            else {
+                continue;
+            };
+            if let Some(usage) = families.get_mut(&family).and_then(|m| m.get_mut(&major)) {
+                usage.instance_count += 1;
+            }
+        }
+
+        let types: Vec<GtsMajorTypeReport> = families
+            .into_iter()
+            .filter(|(_, majors)| majors.len() > 1)
+            .map(|(type_family, majors)| GtsMajorTypeReport {
+                type_family,
+                majors: majors
+                    .into_values()
+                    .map(|mut usage| {
+                        usage.schema_ids.sort();
+                        usage
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        GtsMajorsAuditResult {
+            count: types.len(),
+            usage_tracking: self.usage_tracking(),
+            types,
+        }
+    }
+
+    /// Whether a reference target is satisfied by the store. Wildcard
+    /// `x-gts-ref` targets need at least one matching entity.
+    fn ref_target_exists(&self, target: &str) -> bool {
+        match target.strip_suffix('*') {
+            Some(prefix) => self.items().any(|(id, _)| id.starts_with(prefix)),
+            None => self.entities().contains_key(target),
+        }
+    }
+
+    /// Every entity holding a reference to `id`, as
+    /// [`GtsStore::references`] finds them, ordered by ID and path.  A
+    /// wildcard `x-gts-ref` in a schema points at every ID it matches.
+    #[must_use]
+    pub fn referrers(&self, id: &str) -> Vec<GtsReferrer> {
+        let mut referrers = Vec::new();
+        for (entity_id, refs) in self.all_references() {
+            let is_schema = self.entities()[entity_id].is_schema;
+            referrers.extend(
+                refs.into_iter()
+                    .filter(|r| r.points_at(id))
+                    .map(|r| GtsReferrer {
+                        id: entity_id.to_owned(),
+                        is_schema,
+                        path: r.path,
+                        kind: r.kind.as_str().to_owned(),
+                    }),
+            );
+        }
+        referrers.sort_by(|a, b| (&a.id, &a.path).cmp(&(&b.id, &b.path)));
+        referrers.dedup();
+        referrers
+    }
+
+    /// Reports the `$ref`, `x-gts-ref` and schema references of every entity
+    /// whose target is missing from the store.
+    #[must_use]
+    pub fn audit_refs(&self) -> GtsRefsAuditResult {
+        let mut entities = Vec::new();
+        for (id, refs) in self.all_references() {
+            let mut refs: Vec<GtsDanglingRef> = refs
+                .into_iter()
+                .filter(|r| r.kind != GtsReferenceKind::Content)
+                .filter(|r| !self.ref_target_exists(&r.target))
+                .map(|r| GtsDanglingRef {
+                    path: r.path,
+                    kind: r.kind.as_str().to_owned(),
+                    target: r.target,
+                })
+                .collect();
+            refs.dedup();
+            if !refs.is_empty() {
+                entities.push(GtsEntityDanglingRefs {
+                    id: id.to_owned(),
+                    refs,
+                });
+            }
+        }
+
+        GtsRefsAuditResult {
+            count: entities.iter().map(|e| e.refs.len()).sum(),
+            entities,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    fn schema(id: &str) -> serde_json::Value {
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$id": format!("gts://{id}"),
+            "type": "object"
+        })
+    }
+
+    fn register_instance(store: &mut GtsStore, id: &str, schema_id: &str) {
+        let content = json!({"id": id});
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        );
+        store.register(entity).unwrap();
+    }
+
+    fn store_with_two_majors() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in [
+            "gts.x.test.audit.order.v1~",
+            "gts.x.test.audit.order.v1.1~",
+            "gts.x.test.audit.order.v2~",
+            "gts.x.test.audit.single.v1~",
+        ] {
+            store.register_schema(id, &schema(id)).unwrap();
+        }
+        register_instance(
+            &mut store,
+            "gts.x.test.audit.order.v1~x.test.audit.a.v1",
+            "gts.x.test.audit.order.v1~",
+        );
+        register_instance(
+            &mut store,
+            "gts.x.test.audit.order.v1.1~x.test.audit.b.v1",
+            "gts.x.test.audit.order.v1.1~",
+        );
+        register_instance(
+            &mut store,
+            "gts.x.test.audit.order.v2~x.test.audit.c.v1",
+            "gts.x.test.audit.order.v2~",
+        );
+        store
+    }
+
+    #[test]
+    fn test_type_family_strips_last_version() {
+        let gid = GtsID::new("gts.x.core.events.type.v1~x.shop.orders.placed.v2.3~").unwrap();
+        let (family, major) = type_family(&gid).unwrap();
+        assert_eq!(family, "gts.x.core.events.type.v1~x.shop.orders.placed");
+        assert_eq!(major, 2);
+    }
+
+    #[test]
+    fn test_audit_majors_reports_only_multi_major_families() {
+        let store = store_with_two_majors();
+        let report = store.audit_majors();
+
+        assert_eq!(report.count, 1);
+        assert!(!report.usage_tracking);
+        let family = &report.types[0];
+        assert_eq!(family.type_family, "gts.x.test.audit.order");
+        assert_eq!(family.majors.len(), 2);
+
+        let v1 = &family.majors[0];
+        assert_eq!(v1.major, 1);
+        assert_eq!(
+            v1.schema_ids,
+            vec!["gts.x.test.audit.order.v1.1~", "gts.x.test.audit.order.v1~"]
+        );
+        assert_eq!(v1.instance_count, 2);
+        assert!(v1.last_seen.is_none());
+
+        let v2 = &family.majors[1];
+        assert_eq!(v2.major, 2);
+        assert_eq!(v2.instance_count, 1);
+    }
+
+    #[test]
+    fn test_audit_majors_last_seen_with_usage_tracking() {
+        let mut store = store_with_two_majors();
+        store.set_usage_tracking(true);
+        store
+            .validate_instance("gts.x.test.audit.order.v2~x.test.audit.c.v1")
+            .unwrap();
+
+        let report = store.audit_majors();
+        assert!(report.usage_tracking);
+        let majors = &report.types[0].majors;
+        assert!(majors[0].last_seen.is_none());
+        assert!(majors[1].last_seen.is_some());
+    }
+
+    #[test]
+    fn test_audit_majors_empty_store() {
+        let report = GtsStore::new(None).audit_majors();
+        assert_eq!(report.count, 0);
+        assert!(report.types.is_empty());
+    }
+
+    #[test]
+    fn test_audit_refs_groups_dangling_targets_by_entity() {
+        let mut store = GtsStore::new(None);
+        let base = "gts.x.test.refs.event.v1~";
+        let derived = "gts.x.test.refs.event.v1~x.test.refs.placed.v1~";
+        store.register_schema(base, &schema(base)).unwrap();
+        let mut content = schema(derived);
+        content["allOf"] = json!([
+            {"$ref": format!("gts://{base}")},
+            {"properties": {
+                "user": {"$ref": "gts://gts.x.test.refs.user.v1~#/definitions/id"},
+                "kind": {"type": "string", "x-gts-ref": "/$id"},
+                "actor": {"type": "string", "x-gts-ref": "gts.x.test.refs.actor.*"},
+                "source": {"type": "string", "x-gts-ref": "gts.x.test.refs.*"}
+            }}
+        ]);
+        store.register_schema(derived, &content).unwrap();
+        register_instance(
+            &mut store,
+            "gts.x.test.refs.event.v1~x.test.refs.placed.v1~x.test._.a.v1",
+            derived,
+        );
+        register_instance(
+            &mut store,
+            "gts.x.test.refs.gone.v1~x.test._.b.v1",
+            "gts.x.test.refs.gone.v1~",
+        );
+
+        let report = store.audit_refs();
+        assert_eq!(report.count, 3, "{report:?}");
+        assert_eq!(report.entities.len(), 2);
+
+        let derived_refs = &report.entities[0];
+        assert_eq!(derived_refs.id, derived);
+        let targets: Vec<(&str, &str)> = derived_refs
+            .refs
+            .iter()
+            .map(|r| (r.kind.as_str(), r.target.as_str()))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("$ref", "gts.x.test.refs.user.v1~"),
+                ("x-gts-ref", "gts.x.test.refs.actor.*"),
+            ]
+        );
+        assert_eq!(derived_refs.refs[0].path, "/allOf/1/properties/user/$ref");
+
+        assert_eq!(report.entities[1].refs[0].kind, "schema");
+    }
+
+    #[test]
+    fn test_referrers_lists_schemas_and_instances_pointing_at_an_id() {
+        let mut store = GtsStore::new(None);
+        let user = "gts.x.test.refs.user.v1~";
+        let order = "gts.x.test.refs.order.v1~";
+        let profile = "gts.x.test.refs.profile.v1~";
+        let alice = "gts.x.test.refs.user.v1~x.test._.alice.v1";
+        store.register_schema(user, &schema(user)).unwrap();
+        let mut content = schema(order);
+        content["properties"] = json!({
+            "owner": {"type": "string", "x-gts-ref": user},
+            "any": {"type": "string", "x-gts-ref": "gts.x.test.refs.*"},
+            "self": {"type": "string", "x-gts-ref": "/$id"}
+        });
+        store.register_schema(order, &content).unwrap();
+        let mut content = schema(profile);
+        content["allOf"] = json!([{"$ref": format!("gts://{user}")}]);
+        store.register_schema(profile, &content).unwrap();
+        register_instance(&mut store, alice, user);
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": "gts.x.test.refs.order.v1~x.test._.o1.v1", "owner": alice}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(order.to_owned()),
+        );
+        store.register(entity).unwrap();
+
+        let found = |id: &str| -> Vec<(String, String, String)> {
+            store
+                .referrers(id)
+                .into_iter()
+                .map(|r| (r.id, r.path, r.kind))
+                .collect()
+        };
+        let owned =
+            |id: &str, path: &str, kind: &str| (id.to_owned(), path.to_owned(), kind.to_owned());
+
+        assert_eq!(
+            found(user),
+            vec![
+                owned(order, "/properties/any/x-gts-ref", "x-gts-ref"),
+                owned(order, "/properties/owner/x-gts-ref", "x-gts-ref"),
+                owned(profile, "/allOf/0/$ref", "$ref"),
+                owned(alice, "", "schema"),
+            ]
+        );
+        assert_eq!(
+            found(alice),
+            vec![
+                owned(order, "/properties/any/x-gts-ref", "x-gts-ref"),
+                owned(
+                    "gts.x.test.refs.order.v1~x.test._.o1.v1",
+                    "/owner",
+                    "x-gts-ref"
+                ),
+            ]
+        );
+        // Only the wildcard covers the profile schema
+        assert_eq!(
+            found(profile),
+            vec![owned(order, "/properties/any/x-gts-ref", "x-gts-ref")]
+        );
+        assert!(!store.referrers(user)[3].is_schema);
+        // Chained schema IDs refer to their parent
+        let placed = "gts.x.test.refs.user.v1~x.test.refs.admin.v1~";
+        store.register_schema(placed, &schema(placed)).unwrap();
+        assert!(
+            store
+                .referrers(user)
+                .iter()
+                .any(|r| r.id == placed && r.kind == "parent")
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Avro export of GTS schemas for Kafka Schema Registry.
+//!
+//! [`GtsStore::export_avro`] converts each schema matching a pattern into an
+//! Avro record schema (`.avsc`), with the inheritance chain flattened the
+//! same way as the proto export.  Schemas are grouped into registry subjects,
+//! one per type and major version: the minor versions of a type are
+//! successive versions of one subject, and a new major starts a new subject.
+//!
+//! GTS promises that minor versions of a type stay compatible, so each
+//! subject gets the strongest registry compatibility level its registered
+//! minors actually satisfy: `FULL_TRANSITIVE` if every minor is backward and
+//! forward compatible with the earlier ones, `BACKWARD_TRANSITIVE` or
+//! `FORWARD_TRANSITIVE` if only one direction holds, and `NONE`, with the
+//! reasons, if neither does.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::gts::{GtsID, GtsWildcard};
+use crate::openapi::component_name;
+use crate::proto::pascal_case;
+use crate::schema_cast::GtsEntityCastResult;
+use crate::store::{GtsStore, StoreError};
+
+/// Attribute keeping the JSON name of a field whose name had to change to
+/// be a valid Avro name.
+pub const AVRO_JSON_NAME_KEY: &str = "x-gts-json-name";
+
+/// Schema Registry compatibility levels a subject can be mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GtsAvroCompatibility {
+    FullTransitive,
+    BackwardTransitive,
+    ForwardTransitive,
+    None,
+}
+
+/// One exported `.avsc` schema.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsAvroSchema {
+    pub schema_id: String,
+    /// Registry subject the schema is a version of.
+    pub subject: String,
+    /// File name, e.g. `x.shop.orders.order.v1.2.avsc`.
+    pub file_name: String,
+    pub schema: Value,
+}
+
+/// A registry subject: the minor versions of one type and major version.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsAvroSubject {
+    /// The type's schema ID without the minor version, e.g.
+    /// `gts.x.shop.orders.order.v1~`.
+    pub subject: String,
+    pub compatibility: GtsAvroCompatibility,
+    /// Schema IDs of the subject's versions, oldest first.
+    pub versions: Vec<String>,
+    /// Why minors are not compatible in one or both directions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+/// Result of [`GtsStore::export_avro`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GtsAvroExport {
+    pub schemas: Vec<GtsAvroSchema>,
+    pub subjects: Vec<GtsAvroSubject>,
+}
+
+/// An Avro name for a JSON property: `[A-Za-z_][A-Za-z0-9_]*`.
+fn avro_name(property: &str) -> String {
+    let mut name: String = property
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name.is_empty() {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Any JSON scalar, for values a schema leaves untyped.
+fn any_scalar() -> Value {
+    json!(["null", "boolean", "long", "double", "string"])
+}
+
+/// The registry subject of a schema: its ID without the minor version of
+/// the last chain segment.
+fn subject(id: &GtsID) -> String {
+    match id.gts_id_segments.last() {
+        Some(seg) if seg.ver_minor.is_some() => format!(
+            "{}{}.{}.{}.{}.v{}~",
+            &id.id[..seg.offset],
+            seg.vendor,
+            seg.package,
+            seg.namespace,
+            seg.type_name,
+            seg.ver_major
+        ),
+        _ => id.id.clone(),
+    }
+}
+
+/// Converts `schema`, resolved and possibly chained, to an Avro type;
+/// `name` is used for the records and enums it introduces.
+fn avro_type(schema: &Value, name: &str) -> Value {
+    let ty = match schema.get("type") {
+        Some(Value::String(ty)) => Some(ty.as_str()),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ if schema.get("properties").is_some() || schema.get("allOf").is_some() => Some("object"),
+        _ => None,
+    };
+    match ty {
+        Some("string") => {
+            if let Some(symbols) = schema.get("enum").and_then(Value::as_array)
+                && !symbols.is_empty()
+                && symbols
+                    .iter()
+                    .all(|s| s.as_str().is_some_and(|s| avro_name(s) == s))
+            {
+                return json!({"type": "enum", "name": name, "symbols": symbols});
+            }
+            match schema.get("format").and_then(Value::as_str) {
+                Some("uuid") => json!({"type": "string", "logicalType": "uuid"}),
+                _ => json!("string"),
+            }
+        }
+        Some("integer") => json!("long"),
+        Some("number") => json!("double"),
+        Some("boolean") => json!("boolean"),
+        Some("null") => json!("null"),
+        Some("array") => json!({
+            "type": "array",
+            "items": avro_type(schema.get("items").unwrap_or(&Value::Null), &format!("{name}Item"))
+        }),
+        Some("object") => {
+            let flat = GtsEntityCastResult::flatten_schema(schema);
+            if flat["properties"]
+                .as_object()
+                .is_some_and(|p| !p.is_empty())
+            {
+                record(schema, name, None)
+            } else {
+                let values = match schema.get("additionalProperties") {
+                    Some(additional @ Value::Object(_)) => {
+                        avro_type(additional, &format!("{name}Value"))
+                    }
+                    _ => any_scalar(),
+                };
+                json!({"type": "map", "values": values})
+            }
+        }
+        _ => any_scalar(),
+    }
+}
+
+/// Converts an object schema to an Avro record named `name`.
+fn record(schema: &Value, name: &str, namespace: Option<&str>) -> Value {
+    let flat = GtsEntityCastResult::flatten_schema(schema);
+    let required: BTreeSet<&str> = flat["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut fields = Vec::new();
+    if let Some(properties) = flat["properties"].as_object() {
+        for (property, prop_schema) in properties {
+            let field_name = avro_name(property);
+            let ty = avro_type(prop_schema, &format!("{name}{}", pascal_case(property)));
+            let nullable = match prop_schema.get("type") {
+                Some(Value::Array(types)) => types.iter().any(|t| t == "null"),
+                _ => false,
+            };
+            let default = prop_schema.get("default");
+
+            let mut field = Map::new();
+            field.insert("name".to_owned(), json!(field_name));
+            if let Some(doc) = prop_schema.get("description") {
+                field.insert("doc".to_owned(), doc.clone());
+            }
+            if required.contains(property.as_str()) && !nullable {
+                field.insert("type".to_owned(), ty);
+                if let Some(default) = default {
+                    field.insert("default".to_owned(), default.clone());
+                }
+            } else {
+                // A union's default must match its first branch
+                match default {
+                    // Already a union with `null` first
+                    _ if ty.is_array() => {
+                        field.insert("type".to_owned(), ty);
+                        field.insert("default".to_owned(), Value::Null);
+                    }
+                    Some(default) if !default.is_null() => {
+                        field.insert("type".to_owned(), json!([ty, "null"]));
+                        field.insert("default".to_owned(), default.clone());
+                    }
+                    _ => {
+                        field.insert("type".to_owned(), json!(["null", ty]));
+                        field.insert("default".to_owned(), Value::Null);
+                    }
+                }
+            }
+            if field_name != *property {
+                field.insert(AVRO_JSON_NAME_KEY.to_owned(), json!(property));
+            }
+            fields.push(Value::Object(field));
+        }
+    }
+
+    let mut out = Map::new();
+    out.insert("type".to_owned(), json!("record"));
+    out.insert("name".to_owned(), json!(name));
+    if let Some(namespace) = namespace {
+        out.insert("namespace".to_owned(), json!(namespace));
+    }
+    if let Some(doc) = schema.get("description") {
+        out.insert("doc".to_owned(), doc.clone());
+    }
+    out.insert("fields".to_owned(), Value::Array(fields));
+    Value::Object(out)
+}
+
+/// Maps the compatibility of consecutive minors to a registry level.
+fn compatibility(resolved: &[(String, Value)]) -> (GtsAvroCompatibility, Vec<String>) {
+    let mut backward = true;
+    let mut forward = true;
+    let mut reasons = Vec::new();
+    for pair in resolved.windows(2) {
+        let ((old_id, old), (new_id, new)) = (&pair[0], &pair[1]);
+        let (is_backward, backward_errors) =
+            GtsEntityCastResult::check_backward_compatibility(old, new);
+        let (is_forward, forward_errors) =
+            GtsEntityCastResult::check_forward_compatibility(old, new);
+        backward &= is_backward;
+        forward &= is_forward;
+        reasons.extend(
+            backward_errors
+                .into_iter()
+                .map(|e| format!("{old_id} -> {new_id}: backward: {e}")),
+        );
+        reasons.extend(
+            forward_errors
+                .into_iter()
+                .map(|e| format!("{old_id} -> {new_id}: forward: {e}")),
+        );
+    }
+    let level = match (backward, forward) {
+        (true, true) => GtsAvroCompatibility::FullTransitive,
+        (true, false) => GtsAvroCompatibility::BackwardTransitive,
+        (false, true) => GtsAvroCompatibility::ForwardTransitive,
+        (false, false) => GtsAvroCompatibility::None,
+    };
+    (level, reasons)
+}
+
+impl GtsStore {
+    /// Converts the schemas matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact schema ID, to Avro, and maps the minor
+    /// versions of each type onto a registry subject and compatibility level.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn export_avro(&self, pattern: &str) -> Result<GtsAvroExport, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let mut selected: Vec<(GtsID, Value)> = self
+            .entities()
+            .iter()
+            .filter(|(_, entity)| entity.is_schema)
+            .filter_map(|(id, entity)| {
+                let gid = GtsID::new(id).ok()?;
+                let matched = if pattern.contains('*') {
+                    gid.wildcard_match(&wildcard)
+                } else {
+                    *id == wildcard.id
+                };
+                matched.then(|| (gid, self.resolve_schema_refs(&entity.content)))
+            })
+            .collect();
+        selected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut export = GtsAvroExport::default();
+        let mut subjects: BTreeMap<String, Vec<(String, Value)>> = BTreeMap::new();
+        for (gid, resolved) in selected {
+            let subject = subject(&gid);
+            let (name, namespace) = gid.gts_id_segments.last().map_or_else(
+                || ("Schema".to_owned(), "gts".to_owned()),
+                |seg| {
+                    (
+                        format!("{}V{}", pascal_case(&seg.type_name), seg.ver_major),
+                        format!("{}.{}.{}", seg.vendor, seg.package, seg.namespace),
+                    )
+                },
+            );
+            export.schemas.push(GtsAvroSchema {
+                schema_id: gid.id.clone(),
+                subject: subject.clone(),
+                file_name: format!("{}.avsc", component_name(&gid.id)),
+                schema: record(&resolved, &name, Some(&namespace)),
+            });
+            subjects
+                .entry(subject)
+                .or_default()
+                .push((gid.id, resolved));
+        }
+        for (subject, versions) in subjects {
+            let (compatibility, reasons) = compatibility(&versions);
+            export.subjects.push(GtsAvroSubject {
+                subject,
+                compatibility,
+                versions: versions.into_iter().map(|(id, _)| id).collect(),
+                reasons,
+            });
+        }
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn schema(id: &str, properties: &Value, required: &[&str]) -> Value {
+        json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "required": required,
+            "properties": properties
+        })
+    }
+
+    #[test]
+    fn test_record_mapping() {
+        let avsc = record(
+            &json!({
+                "type": "object",
+                "required": ["id", "status"],
+                "properties": {
+                    "id": {"type": "string", "format": "uuid"},
+                    "status": {"type": "string", "enum": ["NEW", "PAID"]},
+                    "line-items": {
+                        "type": "array",
+                        "items": {"type": "object", "properties": {"qty": {"type": "integer"}}}
+                    },
+                    "note": {"type": ["string", "null"], "default": "none"},
+                    "extra": {}
+                }
+            }),
+            "OrderV1",
+            Some("x.shop.orders"),
+        );
+        let fields = avsc["fields"].as_array().unwrap();
+        assert_eq!(avsc["namespace"], "x.shop.orders");
+        assert_eq!(fields[0]["type"]["logicalType"], "uuid");
+        assert_eq!(fields[1]["type"]["type"], "enum");
+        assert_eq!(fields[2]["name"], "line_items");
+        assert_eq!(fields[2][AVRO_JSON_NAME_KEY], "line-items");
+        assert_eq!(fields[2]["type"][0], "null");
+        assert_eq!(
+            fields[2]["type"][1]["items"]["name"],
+            "OrderV1LineItemsItem"
+        );
+        assert_eq!(fields[3]["type"], json!(["string", "null"]));
+        assert_eq!(fields[3]["default"], "none");
+        assert_eq!(fields[4]["type"], any_scalar());
+    }
+
+    #[test]
+    fn test_export_avro_maps_minor_versions_to_subjects() {
+        let v10 = "gts.x.test.avro.order.v1.0~";
+        let v11 = "gts.x.test.avro.order.v1.1~";
+        let v20 = "gts.x.test.avro.order.v2.0~";
+        let mut store = GtsStore::new(None);
+        let id = json!({"id": {"type": "string"}});
+        store
+            .register_schema(v10, &schema(v10, &id, &["id"]))
+            .unwrap();
+        let with_note = json!({"id": {"type": "string"}, "note": {"type": "string"}});
+        store
+            .register_schema(v11, &schema(v11, &with_note, &["id"]))
+            .unwrap();
+        store
+            .register_schema(
+                v20,
+                &schema(v20, &json!({"n": {"type": "integer"}}), &["n"]),
+            )
+            .unwrap();
+
+        let export = store.export_avro("gts.x.test.avro.*").unwrap();
+        assert_eq!(export.schemas.len(), 3);
+        assert_eq!(export.schemas[0].schema["name"], "OrderV1");
+        assert_eq!(export.schemas[0].subject, "gts.x.test.avro.order.v1~");
+
+        assert_eq!(export.subjects.len(), 2);
+        let v1 = &export.subjects[0];
+        assert_eq!(v1.subject, "gts.x.test.avro.order.v1~");
+        assert_eq!(v1.versions, [v10, v11]);
+        assert_ne!(v1.compatibility, GtsAvroCompatibility::None);
+        assert_eq!(
+            export.subjects[1].compatibility,
+            GtsAvroCompatibility::FullTransitive
+        );
+    }
+}
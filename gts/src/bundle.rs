@@ -0,0 +1,325 @@
+//! Portable bundles of schemas and instances.
+//!
+//! [`GtsStore::export_bundle`] packages the entities matching a pattern,
+//! plus every schema they depend on, into one [`GtsBundle`] document.  Its
+//! manifest lists each entity with a SHA-256 checksum of its content, so
+//! [`GtsStore::import_bundle`] can detect a damaged or edited file before it
+//! registers anything.  See the `signing` feature for bundles that also
+//! prove who published them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::entities::{GtsConfig, GtsEntity};
+use crate::gts::{GtsID, GtsWildcard};
+use crate::store::{GtsStore, StoreError};
+use crate::validator_cache::schema_dependencies;
+
+/// Value of [`GtsBundleManifest::format`] written by this version.
+pub const BUNDLE_FORMAT: &str = "gts-bundle/v1";
+
+/// One bundled entity, as listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsBundleEntry {
+    pub id: String,
+    pub is_schema: bool,
+    /// Schema of an instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_id: Option<String>,
+    /// Whether the entity is only included because a matched one needs it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dependency: bool,
+    /// Hex SHA-256 of the entity's JSON content.
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsBundleManifest {
+    pub format: String,
+    /// Pattern the bundle was exported with.
+    pub pattern: String,
+    /// Entries in the order of [`GtsBundle::entities`].
+    pub entries: Vec<GtsBundleEntry>,
+}
+
+/// Schemas and instances with a manifest, ready to be written to a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GtsBundle {
+    pub manifest: GtsBundleManifest,
+    /// Entity contents: schemas first, then instances, each sorted by ID.
+    pub entities: Vec<Value>,
+}
+
+/// Hex SHA-256 of `content` as compact JSON.
+#[must_use]
+pub fn content_checksum(content: &Value) -> String {
+    let digest = Sha256::digest(content.to_string().as_bytes());
+    digest.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+impl GtsBundle {
+    /// Checks the manifest format and every entry's checksum.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidBundle` describing the first problem.
+    pub fn verify(&self) -> Result<(), StoreError> {
+        if self.manifest.format != BUNDLE_FORMAT {
+            return Err(StoreError::InvalidBundle(format!(
+                "unsupported bundle format '{}'",
+                self.manifest.format
+            )));
+        }
+        if self.manifest.entries.len() != self.entities.len() {
+            return Err(StoreError::InvalidBundle(format!(
+                "manifest lists {} entities but the bundle holds {}",
+                self.manifest.entries.len(),
+                self.entities.len()
+            )));
+        }
+        for (entry, content) in self.manifest.entries.iter().zip(&self.entities) {
+            if content_checksum(content) != entry.sha256 {
+                return Err(StoreError::InvalidBundle(format!(
+                    "checksum mismatch for '{}'",
+                    entry.id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GtsStore {
+    /// Bundles the entities matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact ID, together with the schemas they
+    /// reference and the schemas of matched instances.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard, or `StoreError::UnresolvedReferences` if a dependency is not
+    /// in the store.
+    pub fn export_bundle(&self, pattern: &str) -> Result<GtsBundle, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let selected = |id: &str| {
+            if pattern.contains('*') {
+                GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(&wildcard))
+            } else {
+                id == wildcard.id
+            }
+        };
+        let entities = self.entities();
+
+        // ID -> whether it was matched (rather than pulled in)
+        let mut included: BTreeMap<&str, bool> = BTreeMap::new();
+        let mut missing = BTreeSet::new();
+        for (id, entity) in entities.iter().filter(|(id, _)| selected(id)) {
+            included.insert(id, true);
+            let deps = if entity.is_schema {
+                schema_dependencies(entities, id, &entity.content)
+            } else if let Some(schema_id) = &entity.schema_id {
+                let schema = entities.get(schema_id).map(|s| &s.content);
+                schema.map_or_else(
+                    || [schema_id.clone()].into(),
+                    |schema| schema_dependencies(entities, schema_id, schema),
+                )
+            } else {
+                continue;
+            };
+            for dep in deps {
+                match entities.get_key_value(&dep) {
+                    Some((key, _)) => {
+                        included.entry(key).or_insert(false);
+                    }
+                    None => {
+                        missing.insert(format!("'{id}' depends on missing '{dep}'"));
+                    }
+                }
+            }
+        }
+        if !missing.is_empty() {
+            return Err(StoreError::UnresolvedReferences(
+                missing.into_iter().collect(),
+            ));
+        }
+
+        let mut ordered: Vec<(&str, bool, &GtsEntity)> = included
+            .into_iter()
+            .filter_map(|(id, matched)| entities.get(id).map(|e| (id, matched, e)))
+            .collect();
+        ordered.sort_by_key(|(id, _, entity)| (!entity.is_schema, *id));
+        let (entries, contents) = ordered
+            .into_iter()
+            .map(|(id, matched, entity)| {
+                let entry = GtsBundleEntry {
+                    id: id.to_owned(),
+                    is_schema: entity.is_schema,
+                    schema_id: entity.schema_id.clone().filter(|_| !entity.is_schema),
+                    dependency: !matched,
+                    sha256: content_checksum(&entity.content),
+                };
+                (entry, entity.content.clone())
+            })
+            .unzip();
+        Ok(GtsBundle {
+            manifest: GtsBundleManifest {
+                format: BUNDLE_FORMAT.to_owned(),
+                pattern: pattern.to_owned(),
+                entries,
+            },
+            entities: contents,
+        })
+    }
+
+    /// Verifies `bundle` and registers its entities in one transaction,
+    /// returning their IDs.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidBundle` if verification fails, before
+    /// anything is registered, or the registration error, after which the
+    /// store is unchanged.
+    pub fn import_bundle(&mut self, bundle: &GtsBundle) -> Result<Vec<String>, StoreError> {
+        self.import_bundle_as(None, bundle)
+    }
+
+    /// Imports `bundle` on behalf of `principal`; see
+    /// [`GtsStore::import_bundle`].
+    ///
+    /// # Errors
+    /// As [`GtsStore::import_bundle`], including
+    /// `StoreError::RegistrationDenied` if the policy provider refuses an
+    /// entity.
+    pub fn import_bundle_as(
+        &mut self,
+        principal: Option<&str>,
+        bundle: &GtsBundle,
+    ) -> Result<Vec<String>, StoreError> {
+        bundle.verify()?;
+        let cfg = GtsConfig::default();
+        self.transaction(|tx| {
+            let mut ids = Vec::with_capacity(bundle.entities.len());
+            for (entry, content) in bundle.manifest.entries.iter().zip(&bundle.entities) {
+                if entry.is_schema {
+                    tx.register_schema_as(principal, &entry.id, content)?;
+                } else {
+                    tx.register_as(
+                        principal,
+                        GtsEntity::new(
+                            None,
+                            None,
+                            content,
+                            Some(&cfg),
+                            None,
+                            false,
+                            String::new(),
+                            None,
+                            entry.schema_id.clone(),
+                        ),
+                    )?;
+                }
+                ids.push(entry.id.clone());
+            }
+            Ok(ids)
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.bundle.event.v1~";
+    const CHILD: &str = "gts.x.test.bundle.event.v1~x.test.bundle.paid.v1~";
+    const OTHER: &str = "gts.x.test.other.thing.v1~";
+
+    fn schema(id: &str, parent: Option<&str>) -> Value {
+        let mut schema = json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        if let Some(parent) = parent {
+            schema["allOf"] = json!([{"$ref": format!("gts://{parent}")}]);
+        }
+        schema
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store.register_schema(BASE, &schema(BASE, None)).unwrap();
+        store
+            .register_schema(CHILD, &schema(CHILD, Some(BASE)))
+            .unwrap();
+        store.register_schema(OTHER, &schema(OTHER, None)).unwrap();
+        let instance = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{CHILD}x.test._.p1.v1"), "amount": 3}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(CHILD.to_owned()),
+        );
+        store.register(instance).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_bundle_includes_dependency_closure() {
+        let instance_id = format!("{CHILD}x.test._.p1.v1");
+        let bundle = store().export_bundle(&instance_id).unwrap();
+        let ids: Vec<(&str, bool)> = bundle
+            .manifest
+            .entries
+            .iter()
+            .map(|e| (e.id.as_str(), e.dependency))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![(BASE, true), (CHILD, true), (instance_id.as_str(), false)]
+        );
+        assert_eq!(bundle.manifest.entries[2].schema_id.as_deref(), Some(CHILD));
+
+        // Survives a trip through JSON text
+        let bundle: GtsBundle =
+            serde_json::from_str(&serde_json::to_string_pretty(&bundle).unwrap()).unwrap();
+        let mut target = GtsStore::new(None);
+        let imported = target.import_bundle(&bundle).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert!(target.entities().contains_key(&instance_id));
+        assert!(!target.entities().contains_key(OTHER));
+    }
+
+    #[test]
+    fn test_import_bundle_rejects_damaged_bundles() {
+        let bundle = store().export_bundle("gts.x.test.bundle.*").unwrap();
+        let mut target = GtsStore::new(None);
+
+        let mut edited = bundle.clone();
+        edited.entities[0]["type"] = json!("string");
+        let err = target.import_bundle(&edited).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+        let mut truncated = bundle.clone();
+        truncated.entities.pop();
+        assert!(target.import_bundle(&truncated).is_err());
+
+        // A bundle missing a dependency rolls back entirely
+        let mut partial = bundle;
+        partial.manifest.entries.remove(0);
+        partial.entities.remove(0);
+        let err = target.import_bundle(&partial).unwrap_err();
+        assert!(matches!(err, StoreError::UnresolvedReferences(_)), "{err}");
+        assert!(target.entities().is_empty());
+    }
+}
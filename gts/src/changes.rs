@@ -0,0 +1,201 @@
+//! Notifications of changes to the registered entities.
+//!
+//! Add a [`GtsChangeListener`] with [`GtsStore::add_change_listener`] to be
+//! told about every registration, lifecycle change and removal as a
+//! [`GtsChangeEvent`], e.g. to post webhooks or invalidate caches.  Events
+//! are numbered in the order they happen.  Wholesale replacements of the
+//! entities, such as [`GtsStore::restore`] and ID renames, are not reported
+//! entity by entity.  Changes made inside a [`GtsStore::transaction`] are
+//! reported once it commits, and not at all if it rolls back.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub use gts_core::changes::GtsChangeKind;
+
+use crate::entities::GtsEntity;
+use crate::lifecycle::{GtsLifecycle, GtsLifecycleState};
+use crate::store::GtsStore;
+use crate::visibility::GtsVisibility;
+
+/// One change to the store's registered entities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsChangeEvent {
+    /// Position of the change in the store's history, starting at 1.
+    pub sequence: u64,
+    pub kind: GtsChangeKind,
+    pub id: String,
+    pub is_schema: bool,
+    /// Who may see the entity: after the change, or before a removal.
+    pub visibility: GtsVisibility,
+}
+
+/// Receives store changes as they happen, while the store is borrowed, so
+/// implementations should hand slow work such as HTTP calls to another
+/// thread.
+pub trait GtsChangeListener: Send + Sync {
+    fn on_change(&self, event: &GtsChangeEvent);
+}
+
+/// A change waiting for its transaction to commit.
+type HeldChange = (GtsChangeKind, String, bool, GtsVisibility);
+
+/// The installed listeners and the number of the last change.
+#[derive(Default)]
+pub(crate) struct GtsChangeFeed {
+    listeners: Vec<Arc<dyn GtsChangeListener>>,
+    sequence: u64,
+    /// Changes of the open transaction; `None` outside of one.
+    held: Option<Vec<HeldChange>>,
+}
+
+impl GtsChangeFeed {
+    pub(crate) fn is_listened(&self) -> bool {
+        !self.listeners.is_empty()
+    }
+
+    /// Holds back changes until [`Self::release`] or [`Self::discard`];
+    /// returns `false` if they are already held.
+    pub(crate) fn hold(&mut self) -> bool {
+        if self.held.is_some() {
+            return false;
+        }
+        self.held = Some(Vec::new());
+        true
+    }
+
+    /// Tells the listeners about the held changes and stops holding them.
+    pub(crate) fn release(&mut self) {
+        for (kind, id, is_schema, visibility) in self.held.take().unwrap_or_default() {
+            self.publish(kind, &id, is_schema, visibility);
+        }
+    }
+
+    /// Drops the held changes and stops holding them.
+    pub(crate) fn discard(&mut self) {
+        self.held = None;
+    }
+
+    pub(crate) fn publish(
+        &mut self,
+        kind: GtsChangeKind,
+        id: &str,
+        is_schema: bool,
+        visibility: GtsVisibility,
+    ) {
+        if self.listeners.is_empty() {
+            return;
+        }
+        if let Some(held) = &mut self.held {
+            held.push((kind, id.to_owned(), is_schema, visibility));
+            return;
+        }
+        self.sequence += 1;
+        let event = GtsChangeEvent {
+            sequence: self.sequence,
+            kind,
+            id: id.to_owned(),
+            is_schema,
+            visibility,
+        };
+        for listener in &self.listeners {
+            listener.on_change(&event);
+        }
+    }
+}
+
+/// How replacing `previous` by `entity` is reported: as a lifecycle change
+/// if a schema just became deprecated or retired, otherwise as a
+/// registration.
+pub(crate) fn registration_kind(previous: Option<&GtsEntity>, entity: &GtsEntity) -> GtsChangeKind {
+    let state = |e: &GtsEntity| e.is_schema.then(|| GtsLifecycle::of(&e.content).state);
+    let before = previous.and_then(state);
+    match state(entity) {
+        Some(GtsLifecycleState::Deprecated) if before != Some(GtsLifecycleState::Deprecated) => {
+            GtsChangeKind::Deprecated
+        }
+        Some(GtsLifecycleState::Retired) if before != Some(GtsLifecycleState::Retired) => {
+            GtsChangeKind::Retired
+        }
+        _ => GtsChangeKind::Registered,
+    }
+}
+
+impl GtsStore {
+    /// Adds a listener told about every later change to the registered
+    /// entities, after the listeners added before it.
+    pub fn add_change_listener(&mut self, listener: Arc<dyn GtsChangeListener>) {
+        self.change_feed_mut().listeners.push(listener);
+    }
+
+    /// Removes all change listeners.
+    pub fn clear_change_listeners(&mut self) {
+        self.change_feed_mut().listeners.clear();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const OLD: &str = "gts.x.test.changes.order.v1~";
+    const NEW: &str = "gts.x.test.changes.order.v2~";
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<GtsChangeEvent>>);
+
+    impl GtsChangeListener for Recorder {
+        fn on_change(&self, event: &GtsChangeEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn schema(id: &str) -> serde_json::Value {
+        json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        })
+    }
+
+    #[test]
+    fn test_listener_sees_registrations_lifecycle_and_removals() {
+        let mut store = GtsStore::new(None);
+        store.register_schema(OLD, &schema(OLD)).unwrap();
+        let recorder = Arc::new(Recorder::default());
+        store.add_change_listener(recorder.clone());
+
+        store.register_schema(NEW, &schema(NEW)).unwrap();
+        store.deprecate_schema(OLD, NEW).unwrap();
+        store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Retired, None, None)
+            .unwrap();
+        store.register_schema(NEW, &schema(NEW)).unwrap();
+        store.remove(OLD, false).unwrap();
+
+        let events: Vec<_> = recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| (e.sequence, e.kind, e.id.clone()))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (1, GtsChangeKind::Registered, NEW.to_owned()),
+                (2, GtsChangeKind::Deprecated, OLD.to_owned()),
+                (3, GtsChangeKind::Retired, OLD.to_owned()),
+                (4, GtsChangeKind::Registered, NEW.to_owned()),
+                (5, GtsChangeKind::Removed, OLD.to_owned()),
+            ]
+        );
+
+        store.clear_change_listeners();
+        store.register_schema(OLD, &schema(OLD)).unwrap();
+        assert_eq!(recorder.0.lock().unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,831 @@
+//! Rust and TypeScript code generation from GTS schemas.
+//!
+//! [`GtsStore::codegen_rust`] renders the schemas matching a pattern as one
+//! Rust source file of serde structs, the reverse of
+//! `#[struct_to_gts_schema]` for consumers who only have the schemas.
+//! Properties that are not required, or allow `null`, become `Option`s;
+//! string `enum`s become Rust enums; nested objects become structs of their
+//! own; free-form objects become `serde_json::Map`s and untyped values
+//! `serde_json::Value`.  [`GtsStore::codegen_typescript`] renders the same
+//! types as a `.d.ts` file of interfaces, with optional (`?`) properties,
+//! `| null` for nullable ones and literal unions for enums.
+//!
+//! Chains follow the macro's payload convention.  When a derived type is
+//! generated together with its base, the base gets a generic parameter for
+//! its free-form payload property and the derived type holds only its own
+//! fields, so `gts.x.core.events.type.v1~x.core.audit.event.v1~` becomes
+//! `BaseEventV1<AuditEventV1>`.  A base without exactly one free-form
+//! object property has no payload slot; its derived types are then
+//! generated standalone with the base's fields merged in.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::gts::{GtsID, GtsWildcard};
+use crate::proto::{pascal_case, schema_type};
+use crate::schema_cast::GtsEntityCastResult;
+use crate::store::{GtsStore, StoreError};
+
+const MAP_TYPE: &str = "serde_json::Map<String, serde_json::Value>";
+const VALUE_TYPE: &str = "serde_json::Value";
+const TS_MAP_TYPE: &str = "Record<string, unknown>";
+
+/// One generated top-level type.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsGeneratedType {
+    pub schema_id: String,
+    pub name: String,
+    /// Property that holds derived types, if the type is generic over it.
+    pub payload_field: Option<String>,
+}
+
+/// A generated source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsGeneratedCode {
+    /// Types generated for schemas, ordered by schema ID.
+    pub types: Vec<GtsGeneratedType>,
+    pub content: String,
+}
+
+/// A schema and what it becomes.
+struct TypePlan {
+    schema_id: String,
+    name: String,
+    description: Option<String>,
+    /// The type's own fields: `properties`, `required` and
+    /// `additionalProperties` of a flat object schema.
+    fields: Value,
+    /// Index of the base type whose payload this type is.
+    parent: Option<usize>,
+    /// Index of a generated base with no payload slot, whose fields this
+    /// type repeats.
+    merged_base: Option<usize>,
+    payload_field: Option<String>,
+}
+
+/// `createdAt` → `created_at`, `type` → `type_`.
+fn field_ident(property: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+        "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+        "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+        "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type",
+        "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    let mut ident = String::new();
+    let mut previous_lower = false;
+    for c in property.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                ident.push('_');
+            }
+            ident.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            ident.push(c);
+            previous_lower = true;
+        } else {
+            ident.push('_');
+            previous_lower = false;
+        }
+    }
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') || ident == "_" {
+        ident.insert_str(0, "f_");
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// An enum variant for `value`: `in-progress` → `InProgress`.
+fn variant_ident(value: &str) -> String {
+    let name = pascal_case(value);
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("V{name}")
+    }
+}
+
+/// The type name declared by a macro-generated title such as
+/// `AuditEventV1 (extends BaseEventV1)`, if the title is one.
+fn title_name(schema: &Value) -> Option<&str> {
+    let title = schema.get("title")?.as_str()?;
+    let name = title.split(" (extends ").next()?;
+    let is_type_name = name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    is_type_name.then_some(name)
+}
+
+/// `OrderPlacedV1_2` for the last segment of `id`.
+fn id_name(id: &GtsID) -> String {
+    id.gts_id_segments.last().map_or_else(
+        || "Schema".to_owned(),
+        |seg| {
+            let version = match seg.ver_minor {
+                Some(minor) => format!("V{}_{minor}", seg.ver_major),
+                None => format!("V{}", seg.ver_major),
+            };
+            pascal_case(&seg.type_name) + &version
+        },
+    )
+}
+
+/// Whether `schema` is an object with no declared properties.
+fn is_free_form(schema: &Value) -> bool {
+    schema_type(schema).0 == Some("object")
+        && schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_none_or(Map::is_empty)
+        && schema.get("allOf").is_none()
+}
+
+/// The fields a derived schema adds: its `allOf` branches other than the
+/// `$ref` to its base, plus any top-level properties.
+fn own_fields(schema: &Value) -> Value {
+    let mut own = schema.clone();
+    if let Some(all_of) = own.get_mut("allOf").and_then(Value::as_array_mut) {
+        all_of.retain(|branch| branch.get("$ref").is_none());
+    }
+    GtsEntityCastResult::flatten_schema(&own)
+}
+
+fn properties(fields: &Value) -> Map<String, Value> {
+    fields["properties"]
+        .as_object()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Picks the payload property of base `fields`.  Derived schemas written by
+/// the macro at runtime nest their fields under it, so a derived type whose
+/// only field is a free-form property of the base names it; otherwise the
+/// base's only free-form object property is used.
+fn payload_field(fields: &Value, children: &[&Value]) -> Option<String> {
+    let base = properties(fields);
+    let nested = children.iter().find_map(|child| {
+        let own = properties(child);
+        let (name, _) = own.iter().next().filter(|_| own.len() == 1)?;
+        base.get(name)
+            .is_some_and(is_free_form)
+            .then(|| name.clone())
+    });
+    nested.or_else(|| {
+        let mut free_form = base.iter().filter(|(_, schema)| is_free_form(schema));
+        let (name, _) = free_form.next()?;
+        free_form.next().is_none().then(|| name.clone())
+    })
+}
+
+/// `value` as a Rust string literal.
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.escape_default())
+}
+
+/// `key` as a TypeScript property name, quoted unless it is an identifier.
+fn ts_property(key: &str) -> String {
+    let is_ident = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_ident {
+        key.to_owned()
+    } else {
+        Value::String(key.to_owned()).to_string()
+    }
+}
+
+/// `lines` as a `/** ... */` block indented by `indent`.
+fn ts_doc(lines: &[String], indent: &str) -> String {
+    let mut doc = format!("{indent}/**\n");
+    for line in lines {
+        let line = line.replace("*/", "*\\/");
+        if line.is_empty() {
+            let _ = writeln!(doc, "{indent} *");
+        } else {
+            let _ = writeln!(doc, "{indent} * {line}");
+        }
+    }
+    let _ = writeln!(doc, "{indent} */");
+    doc
+}
+
+fn description_lines(schema: &Value) -> Vec<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(|d| d.lines().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+/// Doc lines for the top-level type `plans[i]`, with `link` rendering a
+/// reference to another generated type.
+fn type_docs(plans: &[TypePlan], i: usize, link: fn(&str) -> String) -> Vec<String> {
+    let plan = &plans[i];
+    let mut docs: Vec<String> = plan
+        .description
+        .as_deref()
+        .map(|d| d.lines().map(str::to_owned).collect())
+        .unwrap_or_default();
+    if !docs.is_empty() {
+        docs.push(String::new());
+    }
+    docs.push(format!("GTS schema `{}`.", plan.schema_id));
+    if let Some(parent) = plan.parent {
+        let mut full = plan.name.clone();
+        let mut base = Some(parent);
+        while let Some(i) = base {
+            full = format!("{}<{full}>", plans[i].name);
+            base = plans[i].parent;
+        }
+        docs.push(format!(
+            "Stored in the `{}` field of {}; a complete instance is a `{full}`.",
+            plans[parent].payload_field.as_deref().unwrap_or_default(),
+            link(&plans[parent].name)
+        ));
+    }
+    if let Some(base) = plan.merged_base {
+        docs.push(format!(
+            "Extends {}, which has no payload field, so its fields are repeated here.",
+            link(&plans[base].name)
+        ));
+    }
+    docs
+}
+
+struct Renderer {
+    /// Names already taken by generated items.
+    names: BTreeSet<String>,
+    items: String,
+}
+
+impl Renderer {
+    /// `name`, or `name2`, `name3`... if taken.
+    fn claim(&mut self, name: &str) -> String {
+        let mut candidate = name.to_owned();
+        let mut n = 1;
+        while !self.names.insert(candidate.clone()) {
+            n += 1;
+            candidate = format!("{name}{n}");
+        }
+        candidate
+    }
+
+    /// Renders a struct for object schema `fields`; `payload` names the
+    /// property typed by generic parameter `P`.
+    fn struct_item(
+        &mut self,
+        name: &str,
+        docs: &[String],
+        fields: &Value,
+        payload: Option<&str>,
+        schema_id: Option<&str>,
+    ) {
+        let flat = GtsEntityCastResult::flatten_schema(fields);
+        let required: BTreeSet<&str> = flat["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut body = String::new();
+        for (property, schema) in properties(&flat) {
+            let ident = field_ident(&property);
+            let mut ty = if payload == Some(property.as_str()) {
+                "P".to_owned()
+            } else {
+                self.field_type(name, &property, &schema)
+            };
+            let optional = !required.contains(property.as_str()) || schema_type(&schema).1;
+            if let Some(description) = schema.get("description").and_then(Value::as_str) {
+                for line in description.lines() {
+                    let _ = writeln!(body, "    /// {line}");
+                }
+            }
+            let mut serde = Vec::new();
+            if ident != property {
+                serde.push(format!("rename = {}", string_literal(&property)));
+            }
+            if optional {
+                ty = format!("Option<{ty}>");
+                serde.push("default, skip_serializing_if = \"Option::is_none\"".to_owned());
+            }
+            if !serde.is_empty() {
+                let _ = writeln!(body, "    #[serde({})]", serde.join(", "));
+            }
+            let _ = writeln!(body, "    pub {ident}: {ty},");
+        }
+
+        let mut item = String::new();
+        for line in docs {
+            if line.is_empty() {
+                item.push_str("///\n");
+            } else {
+                let _ = writeln!(item, "/// {line}");
+            }
+        }
+        item.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+        if flat.get("additionalProperties") == Some(&Value::Bool(false)) {
+            item.push_str("#[serde(deny_unknown_fields)]\n");
+        }
+        let (params, args) = if payload.is_some() {
+            (format!("<P = {VALUE_TYPE}>"), "<P>")
+        } else {
+            (String::new(), "")
+        };
+        let _ = writeln!(item, "pub struct {name}{params} {{\n{body}}}");
+        if let Some(schema_id) = schema_id {
+            let _ = writeln!(
+                item,
+                "\nimpl{args} {name}{args} {{\n    pub const SCHEMA_ID: &'static str = {};\n}}",
+                string_literal(schema_id)
+            );
+        }
+        self.items.push('\n');
+        self.items.push_str(&item);
+    }
+
+    /// The Rust type of property `property` of struct `owner`, rendering the
+    /// nested structs and enums it needs.
+    fn field_type(&mut self, owner: &str, property: &str, schema: &Value) -> String {
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let strings: Vec<&str> = values.iter().filter_map(Value::as_str).collect();
+            if !strings.is_empty() && strings.len() == values.len() {
+                let name = self.claim(&(owner.to_owned() + &pascal_case(property)));
+                self.enum_item(&name, schema, &strings);
+                return name;
+            }
+        }
+        match schema_type(schema).0 {
+            Some("string") => "String".to_owned(),
+            Some("integer") => "i64".to_owned(),
+            Some("number") => "f64".to_owned(),
+            Some("boolean") => "bool".to_owned(),
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                format!(
+                    "Vec<{}>",
+                    self.field_type(owner, &format!("{property}_item"), items)
+                )
+            }
+            Some("object") => {
+                let flat = GtsEntityCastResult::flatten_schema(schema);
+                if properties(&flat).is_empty() {
+                    MAP_TYPE.to_owned()
+                } else {
+                    let name = self.claim(&(owner.to_owned() + &pascal_case(property)));
+                    self.struct_item(&name, &description_lines(schema), schema, None, None);
+                    name
+                }
+            }
+            _ => VALUE_TYPE.to_owned(),
+        }
+    }
+
+    fn enum_item(&mut self, name: &str, schema: &Value, values: &[&str]) {
+        let mut item = String::new();
+        if let Some(description) = schema.get("description").and_then(Value::as_str) {
+            for line in description.lines() {
+                let _ = writeln!(item, "/// {line}");
+            }
+        }
+        item.push_str(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n",
+        );
+        let _ = writeln!(item, "pub enum {name} {{");
+        let mut variants = BTreeSet::new();
+        for value in values {
+            let base = variant_ident(value);
+            let mut variant = base.clone();
+            let mut n = 1;
+            while !variants.insert(variant.clone()) {
+                n += 1;
+                variant = format!("{base}{n}");
+            }
+            if variant != *value {
+                let _ = writeln!(item, "    #[serde(rename = {})]", string_literal(value));
+            }
+            let _ = writeln!(item, "    {variant},");
+        }
+        item.push_str("}\n");
+        self.items.push('\n');
+        self.items.push_str(&item);
+    }
+
+    /// Renders a TypeScript interface for object schema `fields`; `payload`
+    /// names the property typed by generic parameter `P`.
+    fn interface_item(
+        &mut self,
+        name: &str,
+        docs: &[String],
+        fields: &Value,
+        payload: Option<&str>,
+    ) {
+        let flat = GtsEntityCastResult::flatten_schema(fields);
+        let required: BTreeSet<&str> = flat["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut body = String::new();
+        for (property, schema) in properties(&flat) {
+            let mut ty = if payload == Some(property.as_str()) {
+                "P".to_owned()
+            } else {
+                self.ts_type(name, &property, &schema)
+            };
+            if schema_type(&schema).1 {
+                ty.push_str(" | null");
+            }
+            let description = description_lines(&schema);
+            if !description.is_empty() {
+                body.push_str(&ts_doc(&description, "  "));
+            }
+            let optional = if required.contains(property.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            let _ = writeln!(body, "  {}{optional}: {ty};", ts_property(&property));
+        }
+
+        let mut item = String::new();
+        if !docs.is_empty() {
+            item.push_str(&ts_doc(docs, ""));
+        }
+        let params = if payload.is_some() {
+            format!("<P = {TS_MAP_TYPE}>")
+        } else {
+            String::new()
+        };
+        let _ = writeln!(item, "export interface {name}{params} {{\n{body}}}");
+        self.items.push('\n');
+        self.items.push_str(&item);
+    }
+
+    /// The TypeScript type of property `property` of interface `owner`,
+    /// rendering the nested interfaces and unions it needs.
+    fn ts_type(&mut self, owner: &str, property: &str, schema: &Value) -> String {
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let scalar = |v: &Value| !v.is_array() && !v.is_object();
+            if !values.is_empty() && values.iter().all(scalar) {
+                let name = self.claim(&(owner.to_owned() + &pascal_case(property)));
+                let mut item = String::new();
+                let description = description_lines(schema);
+                if !description.is_empty() {
+                    item.push_str(&ts_doc(&description, ""));
+                }
+                let union: Vec<String> = values.iter().map(Value::to_string).collect();
+                let _ = writeln!(item, "export type {name} = {};", union.join(" | "));
+                self.items.push('\n');
+                self.items.push_str(&item);
+                return name;
+            }
+        }
+        match schema_type(schema).0 {
+            Some("string") => "string".to_owned(),
+            Some("integer" | "number") => "number".to_owned(),
+            Some("boolean") => "boolean".to_owned(),
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                let item = self.ts_type(owner, &format!("{property}_item"), items);
+                format!("{item}[]")
+            }
+            Some("object") => {
+                let flat = GtsEntityCastResult::flatten_schema(schema);
+                if properties(&flat).is_empty() {
+                    TS_MAP_TYPE.to_owned()
+                } else {
+                    let name = self.claim(&(owner.to_owned() + &pascal_case(property)));
+                    self.interface_item(&name, &description_lines(schema), schema, None);
+                    name
+                }
+            }
+            _ => "unknown".to_owned(),
+        }
+    }
+}
+
+impl GtsStore {
+    /// Registered schemas matching `pattern`, a GTS wildcard or one exact
+    /// schema ID, ordered by ID.
+    fn codegen_schemas(&self, pattern: &str) -> Result<Vec<(GtsID, &Value)>, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let mut schemas: Vec<(GtsID, &Value)> = self
+            .entities()
+            .iter()
+            .filter(|(_, entity)| entity.is_schema)
+            .filter_map(|(id, entity)| {
+                let gid = GtsID::new(id).ok()?;
+                let selected = if pattern.contains('*') {
+                    gid.wildcard_match(&wildcard)
+                } else {
+                    *id == wildcard.id
+                };
+                selected.then_some((gid, &entity.content))
+            })
+            .collect();
+        schemas.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+        Ok(schemas)
+    }
+
+    /// Plans the types for the schemas matching `pattern`: names, fields and
+    /// payload links between base and derived types.
+    fn codegen_plan(&self, pattern: &str) -> Result<Vec<TypePlan>, StoreError> {
+        let schemas = self.codegen_schemas(pattern)?;
+        let index: HashMap<&str, usize> = schemas
+            .iter()
+            .enumerate()
+            .map(|(i, (gid, _))| (gid.id.as_str(), i))
+            .collect();
+        // `a~b~` is derived from `a~`
+        let parent_of = |gid: &GtsID| {
+            let end = gid.id.trim_end_matches('~').rfind('~')?;
+            index.get(&gid.id[..=end]).copied()
+        };
+
+        let mut names = BTreeSet::new();
+        let mut plans: Vec<TypePlan> = schemas
+            .iter()
+            .map(|(gid, content)| {
+                let mut name = title_name(content).map_or_else(|| id_name(gid), str::to_owned);
+                if !names.insert(name.clone()) {
+                    // Same type name from another vendor or package
+                    let seg = gid.gts_id_segments.last();
+                    name = seg.map_or(String::new(), |s| {
+                        pascal_case(&format!("{}_{}", s.vendor, s.package))
+                    }) + &name;
+                    names.insert(name.clone());
+                }
+                let parent = parent_of(gid);
+                TypePlan {
+                    schema_id: gid.id.clone(),
+                    name,
+                    description: content
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .map(str::to_owned),
+                    fields: if parent.is_some() {
+                        own_fields(content)
+                    } else {
+                        GtsEntityCastResult::flatten_schema(&self.resolve_schema_refs(content))
+                    },
+                    parent,
+                    merged_base: None,
+                    payload_field: None,
+                }
+            })
+            .collect();
+
+        for base in 0..plans.len() {
+            let children: Vec<usize> = (0..plans.len())
+                .filter(|&i| plans[i].parent == Some(base))
+                .collect();
+            if children.is_empty() {
+                continue;
+            }
+            let child_fields: Vec<&Value> = children.iter().map(|&i| &plans[i].fields).collect();
+            let payload = payload_field(&plans[base].fields, &child_fields);
+            for &child in &children {
+                if let Some(field) = &payload {
+                    // Unwrap fields the macro nested under the payload
+                    let own = properties(&plans[child].fields);
+                    if own.len() == 1
+                        && let Some(inner) = own.get(field)
+                    {
+                        plans[child].fields = GtsEntityCastResult::flatten_schema(inner);
+                    }
+                } else {
+                    plans[child].parent = None;
+                    plans[child].merged_base = Some(base);
+                    plans[child].fields = GtsEntityCastResult::flatten_schema(
+                        &self.resolve_schema_refs(schemas[child].1),
+                    );
+                }
+            }
+            plans[base].payload_field = payload;
+        }
+        Ok(plans)
+    }
+
+    /// Renders the schemas matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact schema ID, as one Rust source file of serde
+    /// structs.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn codegen_rust(&self, pattern: &str) -> Result<GtsGeneratedCode, StoreError> {
+        let plans = self.codegen_plan(pattern)?;
+        let mut renderer = Renderer {
+            names: plans.iter().map(|plan| plan.name.clone()).collect(),
+            items: String::new(),
+        };
+        for (i, plan) in plans.iter().enumerate() {
+            renderer.struct_item(
+                &plan.name,
+                &type_docs(&plans, i, |name| format!("[`{name}`]")),
+                &plan.fields,
+                plan.payload_field.as_deref(),
+                Some(&plan.schema_id),
+            );
+        }
+
+        let content = format!(
+            "// Generated by `gts codegen rust` from GTS schemas; do not edit.\n\nuse serde::{{Deserialize, Serialize}};\n{}",
+            renderer.items
+        );
+        Ok(generated(plans, content))
+    }
+
+    /// Renders the schemas matching `pattern` as one TypeScript declaration
+    /// file of interfaces, with the same names and payload generics as
+    /// [`GtsStore::codegen_rust`].
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn codegen_typescript(&self, pattern: &str) -> Result<GtsGeneratedCode, StoreError> {
+        let plans = self.codegen_plan(pattern)?;
+        let mut renderer = Renderer {
+            names: plans.iter().map(|plan| plan.name.clone()).collect(),
+            items: String::new(),
+        };
+        for (i, plan) in plans.iter().enumerate() {
+            renderer.interface_item(
+                &plan.name,
+                &type_docs(&plans, i, |name| format!("{{@link {name}}}")),
+                &plan.fields,
+                plan.payload_field.as_deref(),
+            );
+        }
+
+        let content = format!(
+            "// Generated by `gts codegen typescript` from GTS schemas; do not edit.\n{}",
+            renderer.items
+        );
+        Ok(generated(plans, content))
+    }
+}
+
+fn generated(plans: Vec<TypePlan>, content: String) -> GtsGeneratedCode {
+    GtsGeneratedCode {
+        types: plans
+            .into_iter()
+            .map(|plan| GtsGeneratedType {
+                schema_id: plan.schema_id,
+                name: plan.name,
+                payload_field: plan.payload_field,
+            })
+            .collect(),
+        content,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.codegen.event.v1~";
+    const CHILD: &str = "gts.x.test.codegen.event.v1~x.test.codegen.order_placed.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "title": "BaseEventV1",
+                    "description": "Base event",
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["type", "id", "payload"],
+                    "properties": {
+                        "type": {"type": "string"},
+                        "id": {"type": "string", "format": "uuid"},
+                        "createdAt": {"type": "string", "description": "When it happened"},
+                        "payload": {"type": "object"}
+                    }
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                CHILD,
+                &json!({
+                    "$id": format!("gts://{CHILD}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "allOf": [
+                        {"$ref": format!("gts://{BASE}")},
+                        {
+                            "required": ["status", "lines"],
+                            "properties": {
+                                "status": {"enum": ["new", "in-progress"]},
+                                "lines": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {"sku": {"type": "string"}, "qty": {"type": "integer"}},
+                                        "required": ["sku"]
+                                    }
+                                },
+                                "total": {"type": ["number", "null"]}
+                            }
+                        }
+                    ]
+                }),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_field_ident() {
+        assert_eq!(field_ident("createdAt"), "created_at");
+        assert_eq!(field_ident("type"), "type_");
+        assert_eq!(field_ident("x-gts-ref"), "x_gts_ref");
+        assert_eq!(field_ident("2fa"), "f_2fa");
+        assert_eq!(variant_ident("in-progress"), "InProgress");
+        assert_eq!(variant_ident("1"), "V1");
+    }
+
+    #[test]
+    fn test_codegen_rust_chain_as_generic_payload() {
+        let code = store().codegen_rust("gts.x.test.codegen.*").unwrap();
+        let names: Vec<(&str, Option<&str>)> = code
+            .types
+            .iter()
+            .map(|t| (t.name.as_str(), t.payload_field.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [("BaseEventV1", Some("payload")), ("OrderPlacedV1", None)]
+        );
+
+        let rust = &code.content;
+        assert!(rust.contains("use serde::{Deserialize, Serialize};"));
+        assert!(rust.contains(
+            "#[serde(deny_unknown_fields)]\npub struct BaseEventV1<P = serde_json::Value> {"
+        ));
+        assert!(rust.contains("    #[serde(rename = \"type\")]\n    pub type_: String,"));
+        assert!(rust.contains(
+            "    /// When it happened\n    #[serde(rename = \"createdAt\", default, skip_serializing_if = \"Option::is_none\")]\n    pub created_at: Option<String>,"
+        ));
+        assert!(rust.contains("    pub payload: P,"));
+        assert!(rust.contains("impl<P> BaseEventV1<P> {\n    pub const SCHEMA_ID: &'static str = \"gts.x.test.codegen.event.v1~\";"));
+
+        // The derived type holds only its own fields
+        assert!(rust.contains(
+            "/// Stored in the `payload` field of [`BaseEventV1`]; a complete instance is a `BaseEventV1<OrderPlacedV1>`."
+        ));
+        assert!(rust.contains("pub struct OrderPlacedV1 {\n    pub status: OrderPlacedV1Status,"));
+        assert!(rust.contains("    #[serde(rename = \"in-progress\")]\n    InProgress,"));
+        assert!(rust.contains("    pub lines: Vec<OrderPlacedV1LinesItem>,"));
+        assert!(rust.contains("    pub total: Option<f64>,"));
+        assert!(rust.contains("pub struct OrderPlacedV1LinesItem {\n    pub sku: String,"));
+        assert!(!rust.contains("OrderPlacedV1 {\n    #[serde(rename = \"type\")]"));
+
+        // Without its base, the derived type is standalone
+        let alone = store().codegen_rust(CHILD).unwrap().content;
+        assert!(alone.contains("pub struct OrderPlacedV1 {"));
+        assert!(alone.contains("pub payload: serde_json::Map<String, serde_json::Value>,"));
+        assert!(!alone.contains("a complete instance"));
+    }
+
+    #[test]
+    fn test_codegen_typescript_interfaces() {
+        let code = store().codegen_typescript("gts.x.test.codegen.*").unwrap();
+        assert_eq!(code.types[0].payload_field.as_deref(), Some("payload"));
+
+        let ts = &code.content;
+        assert!(ts.starts_with("// Generated by `gts codegen typescript`"));
+        assert!(ts.contains(
+            "/**\n * Base event\n *\n * GTS schema `gts.x.test.codegen.event.v1~`.\n */\nexport interface BaseEventV1<P = Record<string, unknown>> {"
+        ));
+        assert!(ts.contains("  type: string;\n"));
+        assert!(ts.contains("  /**\n   * When it happened\n   */\n  createdAt?: string;\n"));
+        assert!(ts.contains("  payload: P;\n"));
+
+        assert!(ts.contains(
+            " * Stored in the `payload` field of {@link BaseEventV1}; a complete instance is a `BaseEventV1<OrderPlacedV1>`."
+        ));
+        assert!(ts.contains("export type OrderPlacedV1Status = \"new\" | \"in-progress\";"));
+        assert!(ts.contains("  status: OrderPlacedV1Status;\n"));
+        assert!(ts.contains("  lines: OrderPlacedV1LinesItem[];\n"));
+        assert!(ts.contains("  total?: number | null;\n"));
+        assert!(ts.contains(
+            "export interface OrderPlacedV1LinesItem {\n  sku: string;\n  qty?: number;\n}"
+        ));
+        assert_eq!(ts_property("x-gts-ref"), "\"x-gts-ref\"");
+    }
+}
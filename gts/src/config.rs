@@ -0,0 +1,331 @@
+//! Loading [`GtsConfig`] from files and the environment.
+//!
+//! Settings are layered: the defaults, then a config file, then `GTS_*`
+//! environment variables.  The file is the one given, else the first of
+//! [`CONFIG_FILE_NAMES`] in the current directory; JSON, YAML and TOML
+//! share the same keys:
+//!
+//! ```toml
+//! schema_dir = "./schemas"
+//! dialect = "2020-12"
+//! strict = true
+//!
+//! [numbers]
+//! bignum_as_string = true
+//! ```
+//!
+//! | Variable         | Overrides    | Values                                    |
+//! |------------------|--------------|-------------------------------------------|
+//! | `GTS_SCHEMA_DIR` | `schema_dir` | a directory                               |
+//! | `GTS_DIALECT`    | `dialect`    | `draft4` to `2020-12`, or a `$schema` URI |
+//! | `GTS_STRICT`     | `strict`     | `1`/`true`/`yes` or `0`/`false`/`no`      |
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::entities::GtsConfig;
+use crate::ops::GtsOps;
+
+/// Config files looked up in the current directory, in order.
+pub const CONFIG_FILE_NAMES: [&str; 4] = ["gts.config.json", "gts.toml", "gts.yaml", "gts.yml"];
+
+/// Short names of the JSON Schema dialects and their `$schema` URIs.
+const DIALECTS: [(&str, &str); 5] = [
+    ("draft4", "http://json-schema.org/draft-04/schema#"),
+    ("draft6", "http://json-schema.org/draft-06/schema#"),
+    ("draft7", "http://json-schema.org/draft-07/schema#"),
+    ("2019-09", "https://json-schema.org/draft/2019-09/schema"),
+    ("2020-12", "https://json-schema.org/draft/2020-12/schema"),
+];
+
+#[derive(Debug, Error)]
+pub enum GtsConfigError {
+    #[error("Cannot read config {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid config {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+    #[error("Config {0} is not JSON, YAML or TOML")]
+    UnsupportedFormat(PathBuf),
+    #[error("Invalid {var}: {message}")]
+    Env { var: &'static str, message: String },
+}
+
+/// Loading a [`GtsConfig`] from files and the environment.
+pub trait GtsConfigFile: Sized {
+    /// The config in the JSON, YAML or TOML file at `path`, by extension;
+    /// keys it leaves out keep their defaults.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the file cannot be read or parsed.
+    fn from_file(path: impl AsRef<Path>) -> Result<Self, GtsConfigError>;
+
+    /// The config in `path`, else in the first of [`CONFIG_FILE_NAMES`] in
+    /// the current directory, else the defaults.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the chosen file cannot be read or parsed.
+    fn find(path: Option<&Path>) -> Result<Self, GtsConfigError>;
+
+    /// Overrides settings with the `GTS_SCHEMA_DIR`, `GTS_DIALECT` and
+    /// `GTS_STRICT` environment variables that are set.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError::Env` for a value that cannot be used, leaving
+    /// the config unchanged.
+    fn apply_env_overrides(&mut self) -> Result<(), GtsConfigError>;
+
+    /// [`find`](Self::find) with the environment overrides applied.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the file or an override is invalid.
+    fn load(path: Option<&Path>) -> Result<Self, GtsConfigError> {
+        let mut cfg = Self::find(path)?;
+        cfg.apply_env_overrides()?;
+        Ok(cfg)
+    }
+}
+
+impl GtsConfigFile for GtsConfig {
+    fn from_file(path: impl AsRef<Path>) -> Result<Self, GtsConfigError> {
+        let data = read_config_data(path.as_ref())?;
+        GtsOps::create_config_from_data(&data).map_err(|message| GtsConfigError::Parse {
+            path: path.as_ref().to_path_buf(),
+            message,
+        })
+    }
+
+    fn find(path: Option<&Path>) -> Result<Self, GtsConfigError> {
+        match path.map(Path::to_path_buf).or_else(find_config_file) {
+            Some(path) => Self::from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), GtsConfigError> {
+        apply_overrides(self, |var| std::env::var(var).ok())
+    }
+}
+
+/// The first of [`CONFIG_FILE_NAMES`] present in the current directory.
+#[must_use]
+pub fn find_config_file() -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+}
+
+/// The top-level keys of the config file at `path`.
+///
+/// # Errors
+/// Returns `GtsConfigError` if the file cannot be read, has an unknown
+/// extension, or is not a map.
+pub fn read_config_data(path: &Path) -> Result<HashMap<String, Value>, GtsConfigError> {
+    let content = fs::read_to_string(path).map_err(|source| GtsConfigError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let parsed = match extension.as_deref() {
+        Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Some("yaml" | "yml") => serde_saphyr::from_str(&content).map_err(|e| e.to_string()),
+        Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+        _ => return Err(GtsConfigError::UnsupportedFormat(path.to_path_buf())),
+    };
+    parsed.map_err(|message| GtsConfigError::Parse {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+/// The `$schema` URI of a dialect short name; URIs are kept as given.
+///
+/// # Errors
+/// Returns the message for a value that is neither.
+pub fn dialect_uri(dialect: &str) -> Result<String, String> {
+    if dialect.contains("://") {
+        return Ok(dialect.to_owned());
+    }
+    DIALECTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(dialect))
+        .map(|(_, uri)| (*uri).to_owned())
+        .ok_or_else(|| {
+            let names: Vec<&str> = DIALECTS.iter().map(|(name, _)| *name).collect();
+            format!(
+                "unknown dialect '{dialect}', expected one of {} or a $schema URI",
+                names.join(", ")
+            )
+        })
+}
+
+/// Applies the overrides `lookup` finds, once all of them are known valid.
+fn apply_overrides(
+    cfg: &mut GtsConfig,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), GtsConfigError> {
+    let schema_dir = lookup("GTS_SCHEMA_DIR").filter(|dir| !dir.is_empty());
+    let dialect = lookup("GTS_DIALECT")
+        .map(|d| dialect_uri(&d))
+        .transpose()
+        .map_err(|message| GtsConfigError::Env {
+            var: "GTS_DIALECT",
+            message,
+        })?;
+    let strict = lookup("GTS_STRICT")
+        .map(|value| match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" => Ok(true),
+            "0" | "false" | "no" => Ok(false),
+            _ => Err(GtsConfigError::Env {
+                var: "GTS_STRICT",
+                message: format!("expected true or false, got '{value}'"),
+            }),
+        })
+        .transpose()?;
+
+    if schema_dir.is_some() {
+        cfg.schema_dir = schema_dir;
+    }
+    if dialect.is_some() {
+        cfg.dialect = dialect;
+    }
+    if let Some(strict) = strict {
+        cfg.strict = strict;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::numbers::GtsNumberPolicy;
+    use crate::ref_integrity::GtsRefIntegrity;
+
+    fn env(vars: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars.to_vec();
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn test_from_file_reads_toml_and_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("gts.toml");
+        fs::write(
+            &toml_path,
+            "schema_dir = \"./schemas\"\ndialect = \"draft7\"\nstrict = true\n\n\
+             [numbers]\nbignum_as_string = true\n",
+        )
+        .unwrap();
+        let cfg = GtsConfig::from_file(&toml_path).unwrap();
+        assert_eq!(cfg.schema_dir.as_deref(), Some("./schemas"));
+        assert_eq!(
+            cfg.dialect.as_deref(),
+            Some("http://json-schema.org/draft-07/schema#")
+        );
+        assert!(cfg.strict);
+        assert!(cfg.numbers.bignum_as_string);
+        assert_eq!(cfg.entity_id_fields, GtsConfig::default().entity_id_fields);
+
+        let yaml_path = dir.path().join("gts.yaml");
+        fs::write(&yaml_path, "schema_dir: ./yaml\nref_integrity: typed\n").unwrap();
+        let cfg = GtsConfig::from_file(&yaml_path).unwrap();
+        assert_eq!(cfg.schema_dir.as_deref(), Some("./yaml"));
+        assert_eq!(cfg.ref_integrity, GtsRefIntegrity::Typed);
+
+        let bad = dir.path().join("gts.toml");
+        fs::write(&bad, "strict = ").unwrap();
+        assert!(matches!(
+            GtsConfig::from_file(&bad),
+            Err(GtsConfigError::Parse { .. })
+        ));
+        assert!(matches!(
+            GtsConfig::from_file(dir.path().join("gts.ini")),
+            Err(GtsConfigError::Read { .. })
+        ));
+        let ini = dir.path().join("gts.ini");
+        fs::write(&ini, "strict=1").unwrap();
+        assert!(matches!(
+            GtsConfig::from_file(&ini),
+            Err(GtsConfigError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_file_rejects_invalid_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gts.toml");
+        fs::write(&path, "dialect = \"draft99\"\n").unwrap();
+        let err = GtsConfig::from_file(&path).unwrap_err();
+        assert!(matches!(err, GtsConfigError::Parse { .. }));
+        assert!(err.to_string().contains("unknown dialect"), "{err}");
+
+        fs::write(&path, "strict = \"sometimes\"\n").unwrap();
+        assert!(GtsConfig::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_layer_over_the_file() {
+        let mut cfg = GtsConfig::default();
+        cfg.schema_dir = Some("./from-file".to_owned());
+        cfg.dialect = Some("http://json-schema.org/draft-07/schema#".to_owned());
+        apply_overrides(&mut cfg, env(&[])).unwrap();
+        assert_eq!(cfg.schema_dir.as_deref(), Some("./from-file"));
+
+        apply_overrides(
+            &mut cfg,
+            env(&[("GTS_SCHEMA_DIR", "./from-env"), ("GTS_STRICT", "yes")]),
+        )
+        .unwrap();
+        assert_eq!(cfg.schema_dir.as_deref(), Some("./from-env"));
+        assert_eq!(
+            cfg.dialect.as_deref(),
+            Some("http://json-schema.org/draft-07/schema#")
+        );
+        assert!(cfg.strict);
+
+        let err = apply_overrides(
+            &mut cfg,
+            env(&[("GTS_DIALECT", "2020-12"), ("GTS_STRICT", "maybe")]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("GTS_STRICT"), "{err}");
+        // Nothing is applied when an override is invalid
+        assert_eq!(
+            cfg.dialect.as_deref(),
+            Some("http://json-schema.org/draft-07/schema#")
+        );
+        assert!(dialect_uri("draft3").is_err());
+        assert_eq!(
+            dialect_uri("https://example.com/meta").unwrap(),
+            "https://example.com/meta"
+        );
+    }
+
+    #[test]
+    fn test_strict_tightens_numbers_and_ref_integrity() {
+        let mut cfg = GtsConfig::default();
+        assert_eq!(cfg.number_policy(), GtsNumberPolicy::default());
+        assert_eq!(cfg.ref_integrity_policy(), GtsRefIntegrity::Off);
+
+        cfg.strict = true;
+        cfg.ref_integrity = GtsRefIntegrity::Typed;
+        assert!(cfg.number_policy().strict_integers);
+        assert_eq!(cfg.ref_integrity_policy(), GtsRefIntegrity::Typed);
+        cfg.ref_integrity = GtsRefIntegrity::Off;
+        assert_eq!(cfg.ref_integrity_policy(), GtsRefIntegrity::Exists);
+    }
+}
@@ -0,0 +1,335 @@
+//! Service conformance reports.
+//!
+//! Given the schema IDs a service declares (typically collected from its
+//! `#[struct_to_gts_schema]` annotations), [`GtsStore::conformance_report`]
+//! checks each one against the registry: is it registered, is it the latest
+//! version of its type, and is it still active.  The result renders as JSON or
+//! as Markdown suitable for release notes.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::audit::type_family;
+use crate::gts::GtsID;
+use crate::lifecycle::{self, GtsLifecycle, GtsLifecycleState};
+use crate::store::GtsStore;
+
+/// A schema declared by a service, with where it was declared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsDeclaredSchema {
+    pub schema_id: String,
+    /// Name of the declaring struct, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_by: Option<String>,
+    /// Source file of the declaration, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsConformanceStatus {
+    /// Registered, active and the latest version of its type.
+    Current,
+    /// Registered, but a newer version of the same type exists.
+    Outdated,
+    /// Registered, but deprecated.
+    Deprecated,
+    /// Registered, but retired; instances will fail validation.
+    Retired,
+    /// Not registered.
+    Missing,
+}
+
+impl GtsConformanceStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            GtsConformanceStatus::Current => "current",
+            GtsConformanceStatus::Outdated => "outdated",
+            GtsConformanceStatus::Deprecated => "deprecated",
+            GtsConformanceStatus::Retired => "retired",
+            GtsConformanceStatus::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsConformanceEntry {
+    #[serde(flatten)]
+    pub declared: GtsDeclaredSchema,
+    pub status: GtsConformanceStatus,
+    /// Latest registered version of the same type, when newer than the declared one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    /// Replacement named by the schema's lifecycle, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GtsConformanceSummary {
+    pub declared: usize,
+    pub current: usize,
+    pub outdated: usize,
+    pub deprecated: usize,
+    pub retired: usize,
+    pub missing: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsConformanceReport {
+    pub service: String,
+    /// Report date (`YYYY-MM-DD`, UTC).
+    pub generated_on: String,
+    /// True when every declared schema is current.
+    pub conformant: bool,
+    pub summary: GtsConformanceSummary,
+    pub schemas: Vec<GtsConformanceEntry>,
+}
+
+impl GtsConformanceReport {
+    /// Renders the report as Markdown with a status badge.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let (label, color) = if self.conformant {
+            ("conformant", "brightgreen")
+        } else {
+            ("non--conformant", "red")
+        };
+        let s = &self.summary;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# GTS conformance: {}\n", self.service);
+        let _ = writeln!(
+            out,
+            "![GTS conformance](https://img.shields.io/badge/GTS-{label}-{color})\n"
+        );
+        let _ = writeln!(
+            out,
+            "Generated on {}: {} declared, {} current, {} outdated, {} deprecated, {} retired, {} missing.\n",
+            self.generated_on,
+            s.declared,
+            s.current,
+            s.outdated,
+            s.deprecated,
+            s.retired,
+            s.missing
+        );
+        out.push_str("| Schema | Declared by | Status | Notes |\n");
+        out.push_str("|---|---|---|---|\n");
+        for entry in &self.schemas {
+            let mut notes = Vec::new();
+            if let Some(latest) = &entry.latest {
+                notes.push(format!("latest: `{latest}`"));
+            }
+            if let Some(replacement) = &entry.replacement {
+                notes.push(format!("replacement: `{replacement}`"));
+            }
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} |",
+                entry.declared.schema_id,
+                entry.declared.declared_by.as_deref().unwrap_or(""),
+                entry.status.as_str(),
+                notes.join(", ")
+            );
+        }
+        out
+    }
+}
+
+impl GtsStore {
+    /// Checks the schemas declared by `service` against the store.
+    #[must_use]
+    pub fn conformance_report(
+        &mut self,
+        service: &str,
+        declared: Vec<GtsDeclaredSchema>,
+    ) -> GtsConformanceReport {
+        let today = lifecycle::today();
+        let mut summary = GtsConformanceSummary::default();
+        let mut schemas = Vec::with_capacity(declared.len());
+
+        for decl in declared {
+            let entry = self.conformance_entry(decl, &today);
+            summary.declared += 1;
+            match entry.status {
+                GtsConformanceStatus::Current => summary.current += 1,
+                GtsConformanceStatus::Outdated => summary.outdated += 1,
+                GtsConformanceStatus::Deprecated => summary.deprecated += 1,
+                GtsConformanceStatus::Retired => summary.retired += 1,
+                GtsConformanceStatus::Missing => summary.missing += 1,
+            }
+            schemas.push(entry);
+        }
+        schemas.sort_by(|a, b| a.declared.schema_id.cmp(&b.declared.schema_id));
+
+        GtsConformanceReport {
+            service: service.to_owned(),
+            generated_on: today,
+            conformant: summary.current == summary.declared,
+            summary,
+            schemas,
+        }
+    }
+
+    fn conformance_entry(
+        &mut self,
+        declared: GtsDeclaredSchema,
+        today: &str,
+    ) -> GtsConformanceEntry {
+        let Some(lifecycle) = self
+            .get(&declared.schema_id)
+            .filter(|e| e.is_schema)
+            .map(|e| GtsLifecycle::of(&e.content))
+        else {
+            return GtsConformanceEntry {
+                declared,
+                status: GtsConformanceStatus::Missing,
+                latest: None,
+                replacement: None,
+            };
+        };
+
+        let latest = self.latest_version_of(&declared.schema_id);
+        let status = match lifecycle.effective_state(today) {
+            GtsLifecycleState::Retired => GtsConformanceStatus::Retired,
+            GtsLifecycleState::Deprecated => GtsConformanceStatus::Deprecated,
+            GtsLifecycleState::Draft | GtsLifecycleState::Active if latest.is_some() => {
+                GtsConformanceStatus::Outdated
+            }
+            GtsLifecycleState::Draft | GtsLifecycleState::Active => GtsConformanceStatus::Current,
+        };
+        GtsConformanceEntry {
+            declared,
+            status,
+            latest,
+            replacement: lifecycle.replacement,
+        }
+    }
+
+    /// The highest registered version of `schema_id`'s type family, if it is
+    /// newer than `schema_id` itself.
+    fn latest_version_of(&self, schema_id: &str) -> Option<String> {
+        let version = |gid: &GtsID| {
+            gid.gts_id_segments
+                .last()
+                .map(|s| (s.ver_major, s.ver_minor.unwrap_or(0)))
+        };
+        let gid = GtsID::new(schema_id).ok()?;
+        let (family, _) = type_family(&gid)?;
+        let own = version(&gid)?;
+
+        self.items()
+            .filter(|(_, e)| e.is_schema)
+            .filter_map(|(id, _)| {
+                let other = GtsID::new(id).ok()?;
+                let (other_family, _) = type_family(&other)?;
+                (other_family == family).then(|| (version(&other), id))
+            })
+            .filter_map(|(v, id)| Some((v?, id)))
+            .filter(|(v, _)| *v > own)
+            .max()
+            .map(|(_, id)| id.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(id: &str) -> serde_json::Value {
+        json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        })
+    }
+
+    fn declared(id: &str) -> GtsDeclaredSchema {
+        GtsDeclaredSchema {
+            schema_id: id.to_owned(),
+            declared_by: Some("Payment".to_owned()),
+            source: None,
+        }
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in [
+            "gts.x.pay.core.payment.v1~",
+            "gts.x.pay.core.payment.v1.1~",
+            "gts.x.pay.core.refund.v1~",
+            "gts.x.pay.core.card.v1~",
+        ] {
+            store.register_schema(id, &schema(id)).unwrap();
+        }
+        store
+            .set_schema_lifecycle(
+                "gts.x.pay.core.card.v1~",
+                GtsLifecycleState::Retired,
+                None,
+                Some("gts.x.pay.core.card.v2~"),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_conformance_statuses() {
+        let mut store = store();
+        let report = store.conformance_report(
+            "payments",
+            vec![
+                declared("gts.x.pay.core.payment.v1~"),
+                declared("gts.x.pay.core.refund.v1~"),
+                declared("gts.x.pay.core.card.v1~"),
+                declared("gts.x.pay.core.payout.v1~"),
+            ],
+        );
+
+        assert!(!report.conformant);
+        assert_eq!(report.summary.declared, 4);
+        assert_eq!(report.summary.current, 1);
+        assert_eq!(report.summary.outdated, 1);
+        assert_eq!(report.summary.retired, 1);
+        assert_eq!(report.summary.missing, 1);
+
+        let by_id = |id: &str| {
+            report
+                .schemas
+                .iter()
+                .find(|e| e.declared.schema_id == id)
+                .unwrap()
+        };
+        let payment = by_id("gts.x.pay.core.payment.v1~");
+        assert_eq!(payment.status, GtsConformanceStatus::Outdated);
+        assert_eq!(
+            payment.latest.as_deref(),
+            Some("gts.x.pay.core.payment.v1.1~")
+        );
+        let card = by_id("gts.x.pay.core.card.v1~");
+        assert_eq!(card.status, GtsConformanceStatus::Retired);
+        assert_eq!(card.replacement.as_deref(), Some("gts.x.pay.core.card.v2~"));
+    }
+
+    #[test]
+    fn test_conformant_report_markdown() {
+        let mut store = store();
+        let report = store.conformance_report(
+            "payments",
+            vec![
+                declared("gts.x.pay.core.payment.v1.1~"),
+                declared("gts.x.pay.core.refund.v1~"),
+            ],
+        );
+        assert!(report.conformant);
+
+        let md = report.to_markdown();
+        assert!(md.starts_with("# GTS conformance: payments"));
+        assert!(md.contains("badge/GTS-conformant-brightgreen"));
+        assert!(md.contains("| `gts.x.pay.core.refund.v1~` | Payment | current |  |"));
+    }
+}
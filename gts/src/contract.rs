@@ -0,0 +1,301 @@
+//! Producer/consumer contract checks.
+//!
+//! A consumer schema describes what a consuming service expects to read; the
+//! producer schema describes what another service emits.  The contract holds
+//! when every instance the producer may emit is accepted by the consumer:
+//! the consumer may read a projection of the producer's fields, but each field
+//! it reads must be sent whenever the consumer requires it, and the
+//! producer's constraints on it must be at least as tight as the consumer's.
+//!
+//! [`GtsStore::contract_report`] lists each consumer expectation with its
+//! verification status, in the spirit of a Pact verification report.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::schema_compat::{self, EffectiveSchema};
+use crate::store::{GtsStore, StoreError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsContractStatus {
+    /// The producer always meets the expectation.
+    Satisfied,
+    /// The producer may emit instances the consumer rejects.
+    Violated,
+    /// The consumer reads an optional field the producer never declares.
+    NotProvided,
+}
+
+impl GtsContractStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            GtsContractStatus::Satisfied => "satisfied",
+            GtsContractStatus::Violated => "violated",
+            GtsContractStatus::NotProvided => "not provided",
+        }
+    }
+}
+
+/// One consumer expectation, usually about a single top-level field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsContractExpectation {
+    pub field: String,
+    /// Whether the consumer requires the field.
+    pub required: bool,
+    pub status: GtsContractStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsContractReport {
+    pub producer: String,
+    pub consumer: String,
+    /// True when no expectation is violated.
+    pub verified: bool,
+    pub expectations: Vec<GtsContractExpectation>,
+}
+
+impl GtsContractReport {
+    /// Renders the report as Markdown.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# GTS contract\n");
+        let _ = writeln!(out, "- Producer: `{}`", self.producer);
+        let _ = writeln!(out, "- Consumer: `{}`", self.consumer);
+        let _ = writeln!(
+            out,
+            "- Result: **{}**\n",
+            if self.verified { "verified" } else { "failed" }
+        );
+        out.push_str("| Field | Required | Status | Problems |\n");
+        out.push_str("|---|---|---|---|\n");
+        for e in &self.expectations {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} |",
+                e.field,
+                if e.required { "yes" } else { "no" },
+                e.status.as_str(),
+                e.problems.join("; ")
+            );
+        }
+        out
+    }
+}
+
+fn expectation(field: &str, required: bool, problems: Vec<String>) -> GtsContractExpectation {
+    GtsContractExpectation {
+        field: field.to_owned(),
+        required,
+        status: if problems.is_empty() {
+            GtsContractStatus::Satisfied
+        } else {
+            GtsContractStatus::Violated
+        },
+        problems,
+    }
+}
+
+fn check_contract(
+    producer: &EffectiveSchema,
+    consumer: &EffectiveSchema,
+) -> Vec<GtsContractExpectation> {
+    let mut fields: Vec<&String> = consumer.properties.keys().collect();
+    fields.sort_unstable();
+
+    let mut expectations = Vec::new();
+    for field in fields {
+        let required = consumer.required.contains(field);
+        let mut problems = Vec::new();
+        if required && !producer.required.contains(field) {
+            problems.push("producer does not always send this field".to_owned());
+        }
+        match producer.properties.get(field) {
+            Some(Value::Bool(false)) => {
+                problems.push("producer never sends this field".to_owned());
+            }
+            Some(sent) => problems.extend(
+                schema_compat::property_compatibility(&consumer.properties[field], sent, field)
+                    .into_iter()
+                    .map(|d| d.message),
+            ),
+            None if !required => {
+                expectations.push(GtsContractExpectation {
+                    field: field.clone(),
+                    required,
+                    status: GtsContractStatus::NotProvided,
+                    problems,
+                });
+                continue;
+            }
+            None => {}
+        }
+        expectations.push(expectation(field, required, problems));
+    }
+
+    // Fields the consumer requires but does not describe
+    let mut undescribed: Vec<&String> = consumer
+        .required
+        .iter()
+        .filter(|f| !consumer.properties.contains_key(*f))
+        .collect();
+    undescribed.sort_unstable();
+    for field in undescribed {
+        let problems = if producer.required.contains(field) {
+            Vec::new()
+        } else {
+            vec!["producer does not always send this field".to_owned()]
+        };
+        expectations.push(expectation(field, true, problems));
+    }
+
+    // A closed consumer rejects any field it does not list
+    if matches!(consumer.additional_properties, Some(Value::Bool(false))) {
+        let mut extra: Vec<&String> = producer
+            .properties
+            .keys()
+            .filter(|f| !consumer.properties.contains_key(*f))
+            .collect();
+        extra.sort_unstable();
+        let mut problems: Vec<String> = extra
+            .iter()
+            .map(|f| format!("producer sends '{f}', which the consumer does not allow"))
+            .collect();
+        if !matches!(producer.additional_properties, Some(Value::Bool(false))) {
+            problems.push(
+                "producer allows additional properties, which the consumer does not".to_owned(),
+            );
+        }
+        expectations.push(expectation("additionalProperties", false, problems));
+    }
+
+    expectations
+}
+
+impl GtsStore {
+    /// Checks that every instance of the `producer` schema is accepted by the
+    /// `consumer` schema.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if either schema is missing or has unresolvable references.
+    pub fn contract_report(
+        &mut self,
+        producer: &str,
+        consumer: &str,
+    ) -> Result<GtsContractReport, StoreError> {
+        let [producer_eff, consumer_eff] = [producer, consumer].map(|id| {
+            let content = self.get_schema_content(id)?;
+            let resolved = self
+                .resolve_schema_refs_checked(&content)
+                .map_err(|e| StoreError::ValidationError(format!("Schema '{id}' has {e}")))?;
+            Ok::<_, StoreError>(schema_compat::extract_effective_schema(&resolved))
+        });
+        let expectations = check_contract(&producer_eff?, &consumer_eff?);
+
+        Ok(GtsContractReport {
+            producer: producer.to_owned(),
+            consumer: consumer.to_owned(),
+            verified: expectations
+                .iter()
+                .all(|e| e.status != GtsContractStatus::Violated),
+            expectations,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PRODUCER: &str = "gts.x.test.contract.order_event.v1~";
+    const CONSUMER: &str = "gts.x.test.contract.billing_view.v1~";
+
+    fn store(consumer_body: &Value) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        let producer = json!({
+            "$id": format!("gts://{PRODUCER}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "required": ["id", "amount"],
+            "properties": {
+                "id": {"type": "string"},
+                "amount": {"type": "integer", "minimum": 0, "maximum": 1000},
+                "note": {"type": "string"}
+            }
+        });
+        let mut consumer = json!({
+            "$id": format!("gts://{CONSUMER}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+        });
+        consumer
+            .as_object_mut()
+            .unwrap()
+            .extend(consumer_body.as_object().unwrap().clone());
+        store.register_schema(PRODUCER, &producer).unwrap();
+        store.register_schema(CONSUMER, &consumer).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_projection_is_verified() {
+        let mut store = store(&json!({
+            "required": ["amount"],
+            "properties": {
+                "amount": {"type": "integer", "minimum": 0},
+                "currency": {"type": "string"}
+            }
+        }));
+        let report = store.contract_report(PRODUCER, CONSUMER).unwrap();
+        assert!(report.verified, "{report:?}");
+        let statuses: Vec<_> = report
+            .expectations
+            .iter()
+            .map(|e| (e.field.as_str(), e.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("amount", GtsContractStatus::Satisfied),
+                ("currency", GtsContractStatus::NotProvided),
+            ]
+        );
+        assert!(
+            report
+                .to_markdown()
+                .contains("| `currency` | no | not provided |")
+        );
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let mut store = store(&json!({
+            "required": ["amount", "note"],
+            "additionalProperties": false,
+            "properties": {
+                "amount": {"type": "integer", "maximum": 100},
+                "note": {"type": "string"}
+            }
+        }));
+        let report = store.contract_report(PRODUCER, CONSUMER).unwrap();
+        assert!(!report.verified);
+        let violated: Vec<&str> = report
+            .expectations
+            .iter()
+            .filter(|e| e.status == GtsContractStatus::Violated)
+            .map(|e| e.field.as_str())
+            .collect();
+        assert_eq!(violated, vec!["amount", "note", "additionalProperties"]);
+
+        assert!(matches!(
+            store.contract_report(PRODUCER, "gts.x.test.contract.missing.v1~"),
+            Err(StoreError::SchemaNotFound(_))
+        ));
+    }
+}
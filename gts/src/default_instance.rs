@@ -0,0 +1,298 @@
+//! Default-instance ("zero value") synthesis for a schema.
+//!
+//! Builds the minimal instance a schema accepts: declared `default`/`const`
+//! values are used as-is, required properties without a default get a
+//! type-appropriate zero value, and optional properties without a default are
+//! omitted.  Every zero-filled location is reported back so callers (tests,
+//! admin tools) know which fields still need a real value.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::store::{GtsStore, StoreError};
+
+/// Maximum nesting depth for synthesis; guards against recursive schemas.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsDefaultInstance {
+    pub schema_id: String,
+    pub instance: Value,
+    /// Dotted paths (e.g. `address.city`) of required fields that had no
+    /// declared default and were filled with a zero value.
+    pub zero_filled: Vec<String>,
+}
+
+impl GtsStore {
+    /// Synthesizes the minimal valid instance for a schema.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSchemaId` if `schema_id` is not a type ID, or
+    /// `StoreError::SchemaNotFound` if the schema is not in the store.
+    pub fn default_instance(&mut self, schema_id: &str) -> Result<GtsDefaultInstance, StoreError> {
+        if !schema_id.ends_with('~') {
+            return Err(StoreError::InvalidSchemaId);
+        }
+        let schema = self.get_schema_content(schema_id)?;
+        let resolved = self.resolve_schema_refs(&schema);
+
+        let mut zero_filled = Vec::new();
+        let instance = synthesize(&resolved, "", &mut zero_filled, 0);
+        Ok(GtsDefaultInstance {
+            schema_id: schema_id.to_owned(),
+            instance,
+            zero_filled,
+        })
+    }
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn synthesize(schema: &Value, path: &str, zero_filled: &mut Vec<String>, depth: usize) -> Value {
+    if let Some(value) = schema.get("default").or_else(|| schema.get("const")) {
+        return value.clone();
+    }
+    if depth >= MAX_DEPTH {
+        zero_filled.push(path.to_owned());
+        return Value::Null;
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|v| v.first())
+    {
+        zero_filled.push(path.to_owned());
+        return first.clone();
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|v| v.first())
+        {
+            return synthesize(first, path, zero_filled, depth + 1);
+        }
+    }
+
+    match schema_type(schema) {
+        Some("object") => synthesize_object(schema, path, zero_filled, depth),
+        Some("array") => {
+            let min_items = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let items = schema.get("items").unwrap_or(&Value::Null);
+            let values = (0..min_items)
+                .map(|i| synthesize(items, &format!("{path}[{i}]"), zero_filled, depth + 1))
+                .collect();
+            Value::Array(values)
+        }
+        other => {
+            zero_filled.push(path.to_owned());
+            zero_scalar(schema, other)
+        }
+    }
+}
+
+fn synthesize_object(
+    schema: &Value,
+    path: &str,
+    zero_filled: &mut Vec<String>,
+    depth: usize,
+) -> Value {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut obj = Map::new();
+    if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+        for (name, prop) in props {
+            let has_default = prop.get("default").is_some() || prop.get("const").is_some();
+            if has_default || required.contains(&name.as_str()) {
+                let value = synthesize(prop, &join_path(path, name), zero_filled, depth + 1);
+                obj.insert(name.clone(), value);
+            }
+        }
+    }
+    // Required fields that are not described under `properties` still need a value.
+    for name in required {
+        if !obj.contains_key(name) {
+            zero_filled.push(join_path(path, name));
+            obj.insert(name.to_owned(), Value::Null);
+        }
+    }
+    Value::Object(obj)
+}
+
+/// Picks the effective type: the declared one, `null` when a type union allows
+/// it, otherwise the first listed type.  Untyped schemas with `properties` are
+/// treated as objects.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => Some(t.as_str()),
+        Some(Value::Array(types)) => {
+            let types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            if types.contains(&"null") {
+                Some("null")
+            } else {
+                types.first().copied()
+            }
+        }
+        _ if schema.get("properties").is_some() => Some("object"),
+        _ => None,
+    }
+}
+
+fn zero_scalar(schema: &Value, ty: Option<&str>) -> Value {
+    match ty {
+        Some("string") => Value::String(zero_string(schema)),
+        Some("integer") => Value::from(zero_integer(schema)),
+        Some("number") => schema
+            .get("minimum")
+            .filter(|m| m.as_f64().is_some_and(|m| m > 0.0))
+            .cloned()
+            .unwrap_or_else(|| Value::from(0)),
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+fn zero_string(schema: &Value) -> String {
+    match schema.get("format").and_then(Value::as_str) {
+        Some("uuid") => return uuid::Uuid::nil().to_string(),
+        Some("date-time") => return "1970-01-01T00:00:00Z".to_owned(),
+        Some("date") => return "1970-01-01".to_owned(),
+        _ => {}
+    }
+    let min_len = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+    (0..min_len).map(|_| ' ').collect()
+}
+
+fn zero_integer(schema: &Value) -> i64 {
+    let min = schema.get("minimum").and_then(Value::as_i64);
+    let exclusive_min = schema
+        .get("exclusiveMinimum")
+        .and_then(Value::as_i64)
+        .map(|m| m + 1);
+    match min.into_iter().chain(exclusive_min).max() {
+        Some(lower) if lower > 0 => lower,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA_ID: &str = "gts.x.test.defaults.order.v1~";
+
+    fn store_with(schema: &Value) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store.register_schema(SCHEMA_ID, schema).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_defaults_and_zero_values() {
+        let mut store = store_with(&json!({
+            "$id": format!("gts://{SCHEMA_ID}"),
+            "type": "object",
+            "required": ["name", "count", "active", "tags", "status"],
+            "properties": {
+                "name": {"type": "string"},
+                "count": {"type": "integer", "minimum": 1},
+                "active": {"type": "boolean"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "status": {"type": "string", "default": "draft"},
+                "priority": {"type": "integer", "default": 5},
+                "note": {"type": "string"}
+            }
+        }));
+
+        let result = store.default_instance(SCHEMA_ID).unwrap();
+        assert_eq!(
+            result.instance,
+            json!({
+                "name": "",
+                "count": 1,
+                "active": false,
+                "tags": [],
+                "status": "draft",
+                "priority": 5
+            })
+        );
+        let mut flagged = result.zero_filled;
+        flagged.sort();
+        assert_eq!(flagged, vec!["active", "count", "name"]);
+    }
+
+    #[test]
+    fn test_nested_objects_and_formats() {
+        let mut store = store_with(&json!({
+            "$id": format!("gts://{SCHEMA_ID}"),
+            "type": "object",
+            "required": ["id", "address"],
+            "properties": {
+                "id": {"type": "string", "format": "uuid"},
+                "address": {
+                    "type": "object",
+                    "required": ["city"],
+                    "properties": {
+                        "city": {"type": "string"},
+                        "zip": {"type": ["string", "null"]}
+                    }
+                }
+            }
+        }));
+
+        let result = store.default_instance(SCHEMA_ID).unwrap();
+        assert_eq!(
+            result.instance,
+            json!({
+                "id": "00000000-0000-0000-0000-000000000000",
+                "address": {"city": ""}
+            })
+        );
+        assert!(result.zero_filled.contains(&"address.city".to_owned()));
+    }
+
+    #[test]
+    fn test_enum_const_and_nullable() {
+        let mut store = store_with(&json!({
+            "$id": format!("gts://{SCHEMA_ID}"),
+            "type": "object",
+            "required": ["kind", "type", "parent"],
+            "properties": {
+                "kind": {"enum": ["a", "b"]},
+                "type": {"const": SCHEMA_ID},
+                "parent": {"type": ["string", "null"]}
+            }
+        }));
+
+        let result = store.default_instance(SCHEMA_ID).unwrap();
+        assert_eq!(result.instance["kind"], json!("a"));
+        assert_eq!(result.instance["type"], json!(SCHEMA_ID));
+        assert_eq!(result.instance["parent"], Value::Null);
+        assert!(!result.zero_filled.contains(&"type".to_owned()));
+    }
+
+    #[test]
+    fn test_unknown_schema_and_instance_id() {
+        let mut store = GtsStore::new(None);
+        assert!(matches!(
+            store.default_instance(SCHEMA_ID),
+            Err(StoreError::SchemaNotFound(_))
+        ));
+        assert!(matches!(
+            store.default_instance("gts.x.test.defaults.order.v1~x.test.a.b.v1"),
+            Err(StoreError::InvalidSchemaId)
+        ));
+    }
+}
@@ -0,0 +1,507 @@
+//! Encryption at rest for instance files (`encryption` feature).
+//!
+//! With a [`GtsContentCipher`] attached, [`GtsFileWriter`](crate::GtsFileWriter)
+//! writes each instance as its ID and type fields in clear text plus an
+//! AES-256-GCM sealed copy of the whole content under `x-gts-encrypted`;
+//! [`GtsFileReader`](crate::GtsFileReader) opens them again on load.  The
+//! clear fields are bound to the ciphertext, so moving an encrypted payload
+//! under another ID fails to decrypt.  Schemas are never encrypted.
+//!
+//! Keys come from a [`GtsKeyProvider`]: [`GtsEnvKeyProvider`] reads base64
+//! keys from environment variables, and a KMS integration implements the
+//! trait itself.  Each envelope records the ID of the key it was sealed
+//! with, so keys can be rotated while old files stay readable.
+
+use std::fmt;
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom as _, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::entities::GtsConfig;
+
+/// Key of the sealed content in an encrypted document.
+pub const ENCRYPTED_KEY: &str = "x-gts-encrypted";
+
+/// Environment variable [`GtsContentCipher::from_env`] reads the key from.
+pub const ENCRYPTION_KEY_ENV: &str = "GTS_ENCRYPTION_KEY";
+
+const ALGORITHM: &str = "A256GCM";
+
+#[derive(Debug, Error)]
+pub enum GtsEncryptionError {
+    #[error("Encryption key '{key_id}' unavailable: {reason}")]
+    Key { key_id: String, reason: String },
+    #[error("Malformed encrypted content: {0}")]
+    Malformed(String),
+    #[error("Encrypted content could not be decrypted; wrong key or tampered data")]
+    Decrypt,
+    #[error("Encryption failed")]
+    Encrypt,
+}
+
+/// Supplies 256-bit data keys, e.g. from a KMS.
+pub trait GtsKeyProvider: Send + Sync {
+    /// ID of the key new content is encrypted with.
+    fn current_key_id(&self) -> String;
+
+    /// The key with `key_id`.
+    ///
+    /// # Errors
+    /// Returns the reason if the key is unknown or cannot be fetched.
+    fn key(&self, key_id: &str) -> Result<[u8; 32], String>;
+}
+
+/// Base64 keys read from environment variables on each use.
+///
+/// The current key is in `var`.  If `<var>_ID` names it, say `2`, content
+/// is sealed with key ID `env:<var>_2`; once the key is rotated, the old one
+/// is read from `<var>_2`.  Without `<var>_ID` the key ID is `env:<var>`.
+#[derive(Debug, Clone)]
+pub struct GtsEnvKeyProvider {
+    pub var: String,
+}
+
+impl GtsEnvKeyProvider {
+    #[must_use]
+    pub fn new(var: &str) -> Self {
+        GtsEnvKeyProvider {
+            var: var.to_owned(),
+        }
+    }
+
+    fn current_key_id_with(&self, lookup: impl Fn(&str) -> Option<String>) -> String {
+        match lookup(&format!("{}_ID", self.var)) {
+            Some(id) if !id.trim().is_empty() => format!("env:{}_{}", self.var, id.trim()),
+            _ => format!("env:{}", self.var),
+        }
+    }
+
+    fn key_with(
+        &self,
+        key_id: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<[u8; 32], String> {
+        let var = if key_id == self.current_key_id_with(&lookup) {
+            self.var.clone()
+        } else {
+            match key_id.strip_prefix("env:") {
+                Some(var)
+                    if var.starts_with(&format!("{}_", self.var))
+                        && var != format!("{}_ID", self.var) =>
+                {
+                    var.to_owned()
+                }
+                _ => return Err(format!("not a key in {} or {}_<id>", self.var, self.var)),
+            }
+        };
+        let encoded = lookup(&var).ok_or_else(|| format!("{var} is not set"))?;
+        BASE64
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| format!("{var} is not a base64 256-bit key"))
+    }
+}
+
+impl GtsKeyProvider for GtsEnvKeyProvider {
+    fn current_key_id(&self) -> String {
+        self.current_key_id_with(|var| std::env::var(var).ok())
+    }
+
+    fn key(&self, key_id: &str) -> Result<[u8; 32], String> {
+        self.key_with(key_id, |var| std::env::var(var).ok())
+    }
+}
+
+/// The sealed part of an encrypted document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsEncryptedEnvelope {
+    pub alg: String,
+    /// Key ID, as given by the [`GtsKeyProvider`].
+    pub kid: String,
+    /// Base64 nonce.
+    pub nonce: String,
+    /// Base64 ciphertext and tag of the compact JSON content.
+    pub ciphertext: String,
+}
+
+/// Encrypts and decrypts instance content.
+#[derive(Clone)]
+pub struct GtsContentCipher {
+    provider: Arc<dyn GtsKeyProvider>,
+    /// Top-level fields kept in clear text.
+    clear_fields: Vec<String>,
+}
+
+impl fmt::Debug for GtsContentCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GtsContentCipher")
+            .field("key_id", &self.provider.current_key_id())
+            .field("clear_fields", &self.clear_fields)
+            .finish()
+    }
+}
+
+impl GtsContentCipher {
+    /// A cipher keeping the ID and type fields of `cfg` in clear text, so
+    /// encrypted instances keep their IDs.  A field given as a JSON Pointer
+    /// keeps its whole top-level field in clear text.
+    #[must_use]
+    pub fn new(provider: Arc<dyn GtsKeyProvider>, cfg: &GtsConfig) -> Self {
+        let mut clear_fields: Vec<String> = Vec::new();
+        let fields = cfg
+            .id_fields
+            .entity_id
+            .iter()
+            .chain(&cfg.id_fields.schema_id)
+            .chain(&cfg.entity_id_fields)
+            .chain(&cfg.schema_id_fields);
+        for field in fields {
+            let field = match field.strip_prefix('/') {
+                Some(pointer) => {
+                    let top = pointer.split('/').next().unwrap_or_default();
+                    top.replace("~1", "/").replace("~0", "~")
+                }
+                None => field.clone(),
+            };
+            if !clear_fields.contains(&field) {
+                clear_fields.push(field);
+            }
+        }
+        GtsContentCipher {
+            provider,
+            clear_fields,
+        }
+    }
+
+    /// A cipher keyed by [`ENCRYPTION_KEY_ENV`], if it is set, keeping the
+    /// ID and type fields of `cfg` in clear text.
+    #[must_use]
+    pub fn from_env(cfg: &GtsConfig) -> Option<Self> {
+        std::env::var_os(ENCRYPTION_KEY_ENV)
+            .map(|_| Self::new(Arc::new(GtsEnvKeyProvider::new(ENCRYPTION_KEY_ENV)), cfg))
+    }
+
+    /// Replaces the fields kept in clear text.
+    #[must_use]
+    pub fn with_clear_fields(mut self, fields: Vec<String>) -> Self {
+        self.clear_fields = fields;
+        self
+    }
+
+    fn sealing_key(&self, key_id: &str) -> Result<LessSafeKey, GtsEncryptionError> {
+        let key = self
+            .provider
+            .key(key_id)
+            .map_err(|reason| GtsEncryptionError::Key {
+                key_id: key_id.to_owned(),
+                reason,
+            })?;
+        let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| GtsEncryptionError::Encrypt)?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    /// Encrypts `content`, keeping its clear fields readable.  Non-object
+    /// content and content that is already encrypted is returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `GtsEncryptionError` if the key is unavailable.
+    pub fn encrypt(&self, content: &Value) -> Result<Value, GtsEncryptionError> {
+        let Some(map) = content.as_object() else {
+            return Ok(content.clone());
+        };
+        if map.contains_key(ENCRYPTED_KEY) {
+            return Ok(content.clone());
+        }
+        let clear: Map<String, Value> = self
+            .clear_fields
+            .iter()
+            .filter_map(|f| map.get(f).map(|v| (f.clone(), v.clone())))
+            .collect();
+
+        let kid = self.provider.current_key_id();
+        let key = self.sealing_key(&kid)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| GtsEncryptionError::Encrypt)?;
+        let mut sealed = content.to_string().into_bytes();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad(&clear)),
+            &mut sealed,
+        )
+        .map_err(|_| GtsEncryptionError::Encrypt)?;
+
+        let envelope = GtsEncryptedEnvelope {
+            alg: ALGORITHM.to_owned(),
+            kid,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(sealed),
+        };
+        let mut doc = clear;
+        doc.insert(
+            ENCRYPTED_KEY.to_owned(),
+            serde_json::to_value(envelope).map_err(|_| GtsEncryptionError::Encrypt)?,
+        );
+        Ok(Value::Object(doc))
+    }
+
+    /// Decrypts a document written by [`GtsContentCipher::encrypt`].
+    /// Documents without `x-gts-encrypted` are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `GtsEncryptionError` if the envelope is malformed, the key is
+    /// unavailable, or the content or its clear fields were changed.
+    pub fn decrypt(&self, doc: &Value) -> Result<Value, GtsEncryptionError> {
+        let Some(envelope) = doc.get(ENCRYPTED_KEY) else {
+            return Ok(doc.clone());
+        };
+        let envelope: GtsEncryptedEnvelope = serde_json::from_value(envelope.clone())
+            .map_err(|e| GtsEncryptionError::Malformed(e.to_string()))?;
+        if envelope.alg != ALGORITHM {
+            return Err(GtsEncryptionError::Malformed(format!(
+                "unsupported algorithm '{}'",
+                envelope.alg
+            )));
+        }
+        let nonce = BASE64
+            .decode(&envelope.nonce)
+            .ok()
+            .and_then(|n| <[u8; NONCE_LEN]>::try_from(n).ok())
+            .ok_or_else(|| GtsEncryptionError::Malformed("invalid nonce".to_owned()))?;
+        let mut sealed = BASE64
+            .decode(&envelope.ciphertext)
+            .map_err(|e| GtsEncryptionError::Malformed(e.to_string()))?;
+
+        let mut clear = doc.as_object().cloned().unwrap_or_default();
+        clear.remove(ENCRYPTED_KEY);
+        let key = self.sealing_key(&envelope.kid)?;
+        let plain = key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad(&clear)),
+                &mut sealed,
+            )
+            .map_err(|_| GtsEncryptionError::Decrypt)?;
+        serde_json::from_slice(plain).map_err(|e| GtsEncryptionError::Malformed(e.to_string()))
+    }
+}
+
+/// Whether `doc` holds encrypted content.
+#[must_use]
+pub fn is_encrypted(doc: &Value) -> bool {
+    doc.get(ENCRYPTED_KEY).is_some()
+}
+
+/// Associated data binding the clear fields to the ciphertext.
+fn aad(clear: &Map<String, Value>) -> Vec<u8> {
+    let mut fields: Vec<(&String, &Value)> = clear.iter().collect();
+    fields.sort_by_key(|(name, _)| *name);
+    serde_json::to_vec(&fields).unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsEntity;
+    use crate::files_reader::GtsFileReader;
+    use crate::files_writer::GtsFileWriter;
+    use crate::store::GtsStore;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const ORDER: &str = "gts.x.test.enc.order.v1~";
+
+    struct FixedKeys;
+    impl GtsKeyProvider for FixedKeys {
+        fn current_key_id(&self) -> String {
+            "k2".to_owned()
+        }
+        fn key(&self, key_id: &str) -> Result<[u8; 32], String> {
+            match key_id {
+                "k1" => Ok([1; 32]),
+                "k2" => Ok([2; 32]),
+                other => Err(format!("unknown key {other}")),
+            }
+        }
+    }
+
+    fn cipher() -> GtsContentCipher {
+        GtsContentCipher::new(Arc::new(FixedKeys), &GtsConfig::default())
+    }
+
+    fn order() -> Value {
+        json!({"id": format!("{ORDER}x.test._.o1.v1"), "customer": {"email": "ann@example.com"}})
+    }
+
+    #[test]
+    fn test_encrypt_keeps_ids_in_clear_and_round_trips() {
+        let doc = cipher().encrypt(&order()).unwrap();
+        assert!(is_encrypted(&doc));
+        assert_eq!(doc["id"], order()["id"]);
+        assert!(doc.get("customer").is_none());
+        assert!(!doc.to_string().contains("ann@example.com"));
+        assert_eq!(doc[ENCRYPTED_KEY]["kid"], "k2");
+        assert_eq!(cipher().decrypt(&doc).unwrap(), order());
+
+        // The clear ID is bound to the ciphertext
+        let mut moved = doc.clone();
+        moved["id"] = json!(format!("{ORDER}x.test._.o2.v1"));
+        assert!(matches!(
+            cipher().decrypt(&moved),
+            Err(GtsEncryptionError::Decrypt)
+        ));
+
+        let mut unknown = doc;
+        unknown[ENCRYPTED_KEY]["kid"] = json!("k9");
+        assert!(matches!(
+            cipher().decrypt(&unknown),
+            Err(GtsEncryptionError::Key { .. })
+        ));
+    }
+
+    #[test]
+    fn test_file_store_encrypts_instances_at_rest() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_string_lossy().into_owned();
+        let mut store = GtsStore::new(None);
+        store.set_writer(Box::new(GtsFileWriter::new(&root).with_cipher(cipher())));
+        store
+            .register_schema(
+                ORDER,
+                &json!({
+                    "$id": format!("gts://{ORDER}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &order(),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(ORDER.to_owned()),
+        );
+        store.register(entity).unwrap();
+        let instance_id = format!("{ORDER}x.test._.o1.v1");
+        let path = store.persist(&instance_id).unwrap();
+        let schema_path = store.persist(ORDER).unwrap();
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("ann@example.com"));
+        assert!(on_disk.contains(&instance_id));
+        assert!(
+            !std::fs::read_to_string(schema_path)
+                .unwrap()
+                .contains(ENCRYPTED_KEY)
+        );
+
+        let reader = GtsFileReader::new(std::slice::from_ref(&root), None).with_cipher(cipher());
+        let mut reloaded = GtsStore::new(Some(Box::new(reader)));
+        assert_eq!(reloaded.get(&instance_id).unwrap().content, order());
+
+        // Without the key the instance is skipped rather than loaded sealed
+        let plain = GtsFileReader::new(std::slice::from_ref(&root), None).with_cipher(
+            GtsContentCipher::new(
+                Arc::new(GtsEnvKeyProvider::new("GTS_TEST_ENC_UNSET_KEY")),
+                &GtsConfig::default(),
+            ),
+        );
+        let mut locked = GtsStore::new(Some(Box::new(plain)));
+        assert!(locked.get(&instance_id).is_none());
+        assert!(locked.get(ORDER).is_some());
+    }
+
+    #[test]
+    fn test_array_items_that_fail_to_decrypt_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("orders.json");
+        let first = cipher().encrypt(&order()).unwrap();
+        let mut second = order();
+        second["id"] = json!(format!("{ORDER}x.test._.o2.v1"));
+        let mut tampered = cipher().encrypt(&second).unwrap();
+        tampered["id"] = json!(format!("{ORDER}x.test._.o3.v1"));
+        std::fs::write(&path, json!([first, tampered]).to_string()).unwrap();
+
+        let reader =
+            GtsFileReader::new(&[path.to_string_lossy().into_owned()], None).with_cipher(cipher());
+        let entities = reader.process_file(&path);
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].content, order());
+        assert_eq!(entities[0].list_sequence, Some(0));
+        // The failed item is kept sealed in the file content, not nulled
+        let file = entities[0].file.as_ref().unwrap();
+        assert!(is_encrypted(&file.content[1]));
+    }
+
+    #[test]
+    fn test_clear_fields_follow_the_config() {
+        let mut cfg = GtsConfig::default();
+        cfg.entity_id_fields = vec!["entityId".to_owned()];
+        cfg.schema_id_fields = vec!["kind".to_owned()];
+        cfg.id_fields.schema_id = vec!["/meta/type".to_owned()];
+        let cipher = GtsContentCipher::new(Arc::new(FixedKeys), &cfg);
+        let content = json!({
+            "entityId": format!("{ORDER}x.test._.o1.v1"),
+            "meta": {"type": ORDER},
+            "secret": "s3cr3t"
+        });
+        let doc = cipher.encrypt(&content).unwrap();
+        assert_eq!(doc["entityId"], content["entityId"]);
+        assert_eq!(doc["meta"], content["meta"]);
+        assert!(doc.get("secret").is_none());
+
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &doc,
+            Some(&cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        assert_eq!(
+            entity.effective_id().as_deref(),
+            content["entityId"].as_str()
+        );
+    }
+
+    #[test]
+    fn test_env_keys_rotate() {
+        let env: std::collections::HashMap<&str, String> = [
+            ("GTS_KEY", BASE64.encode([2u8; 32])),
+            ("GTS_KEY_ID", "2".to_owned()),
+            ("GTS_KEY_1", BASE64.encode([1u8; 32])),
+        ]
+        .into();
+        let lookup = |var: &str| env.get(var).cloned();
+        let provider = GtsEnvKeyProvider::new("GTS_KEY");
+
+        assert_eq!(provider.current_key_id_with(lookup), "env:GTS_KEY_2");
+        assert_eq!(provider.key_with("env:GTS_KEY_2", lookup), Ok([2; 32]));
+        assert_eq!(provider.key_with("env:GTS_KEY_1", lookup), Ok([1; 32]));
+        assert!(provider.key_with("env:GTS_KEY_3", lookup).is_err());
+        assert!(provider.key_with("env:GTS_KEY_ID", lookup).is_err());
+        assert!(provider.key_with("env:OTHER", lookup).is_err());
+
+        // Without an ID the key ID is the variable itself
+        let unnamed = |var: &str| (var == "GTS_KEY").then(|| BASE64.encode([2u8; 32]));
+        assert_eq!(provider.current_key_id_with(unnamed), "env:GTS_KEY");
+        assert_eq!(provider.key_with("env:GTS_KEY", unnamed), Ok([2; 32]));
+    }
+}
@@ -0,0 +1,391 @@
+//! Validation explain mode.
+//!
+//! [`GtsStore::validate_instance_explain`] validates an instance exactly like
+//! [`GtsStore::validate_instance`], and when it fails re-evaluates the instance
+//! with `gts://` references kept as references instead of inlined.  That keeps
+//! the full evaluation path of every error, so each one can be traced through
+//! the schema chain: which `allOf` branch, which chain segment and which
+//! keyword rejected the value.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::entities::GtsEntity;
+use crate::gts::{GTS_URI_PREFIX, GtsID};
+use crate::sandbox;
+use crate::store::{GtsRetriever, GtsStore, StoreError};
+
+/// Applicators followed by an array index in an evaluation path.
+const INDEXED_KEYWORDS: &[&str] = &["allOf", "anyOf", "oneOf", "items", "prefixItems"];
+/// Applicators followed by a property or definition name in an evaluation path.
+const NAMED_KEYWORDS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "dependencies",
+    "dependentSchemas",
+    "$defs",
+    "definitions",
+];
+
+/// One step of an error's path through the schema chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsTraceStep {
+    /// Schema (chain segment) the step is evaluated in.
+    pub schema_id: String,
+    /// JSON pointer of the step within that schema.
+    pub location: String,
+    /// What was evaluated, e.g. `allOf[1]`, `properties.amount`, `$ref -> gts://...`
+    /// or the failing keyword.
+    pub step: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidationTrace {
+    /// JSON pointer of the rejected value in the instance.
+    pub instance_path: String,
+    /// The keyword that rejected the value.
+    pub keyword: String,
+    pub message: String,
+    pub steps: Vec<GtsTraceStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidationExplain {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_id: Option<String>,
+    pub ok: bool,
+    /// Traced schema errors. Failures outside JSON Schema (lifecycle,
+    /// `x-gts-ref`) only show up in `error`.
+    pub errors: Vec<GtsValidationTrace>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+impl GtsValidationExplain {
+    /// Renders the result as an indented tree, one branch per error.
+    #[must_use]
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        let schema = self.schema_id.as_deref().unwrap_or("?");
+        if self.ok {
+            let _ = writeln!(out, "{}: valid against {schema}", self.id);
+            return out;
+        }
+        let _ = writeln!(out, "{}: invalid against {schema}", self.id);
+        if self.errors.is_empty() {
+            let _ = writeln!(out, "  {}", self.error);
+        }
+        for trace in &self.errors {
+            let at = if trace.instance_path.is_empty() {
+                "/"
+            } else {
+                trace.instance_path.as_str()
+            };
+            let _ = writeln!(out, "  {at}: {}", trace.message);
+            let mut current_schema = None;
+            for (depth, step) in trace.steps.iter().enumerate() {
+                let indent = "  ".repeat(depth + 2);
+                if current_schema == Some(&step.schema_id) {
+                    let _ = writeln!(out, "{indent}{}", step.step);
+                } else {
+                    let _ = writeln!(out, "{indent}{}  [{}]", step.step, step.schema_id);
+                    current_schema = Some(&step.schema_id);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Splits a JSON pointer into unescaped tokens.
+fn pointer_tokens(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+fn push_pointer(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+fn child<'a>(node: &'a Value, token: &str) -> Option<&'a Value> {
+    match node {
+        Value::Array(items) => token.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => node.get(token),
+    }
+}
+
+/// Walks an evaluation path through the schema chain, following `$ref`s into
+/// the referenced schemas.
+fn trace_steps(
+    entities: &HashMap<String, GtsEntity>,
+    schema_id: &str,
+    schema: &Value,
+    tokens: &[String],
+) -> Vec<GtsTraceStep> {
+    let mut steps = Vec::new();
+    let mut doc_id = schema_id.to_owned();
+    let mut root = Some(schema);
+    let mut node = Some(schema);
+    let mut location = String::new();
+    let mut i = 0;
+
+    while let Some(token) = tokens.get(i).map(String::as_str) {
+        if token == "$ref" {
+            let target = node
+                .and_then(|n| n.get("$ref"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            push_pointer(&mut location, token);
+            steps.push(GtsTraceStep {
+                schema_id: doc_id.clone(),
+                location: location.clone(),
+                step: format!("$ref -> {target}"),
+            });
+
+            let (doc, fragment) = target.split_once('#').unwrap_or((target, ""));
+            if let Some(id) = doc.strip_prefix(GTS_URI_PREFIX) {
+                id.clone_into(&mut doc_id);
+                root = entities.get(id).map(|e| &e.content);
+            } else if !doc.is_empty() {
+                root = None;
+            }
+            node = root.and_then(|r| r.pointer(fragment));
+            fragment.clone_into(&mut location);
+            i += 1;
+            continue;
+        }
+
+        let name = tokens.get(i + 1).filter(|next| {
+            NAMED_KEYWORDS.contains(&token)
+                || (INDEXED_KEYWORDS.contains(&token) && next.parse::<usize>().is_ok())
+        });
+        let step = if let Some(name) = name {
+            node = node.and_then(|n| n.get(token)).and_then(|n| child(n, name));
+            push_pointer(&mut location, token);
+            push_pointer(&mut location, name);
+            i += 2;
+            if NAMED_KEYWORDS.contains(&token) {
+                format!("{token}.{name}")
+            } else {
+                format!("{token}[{name}]")
+            }
+        } else {
+            node = node.and_then(|n| n.get(token));
+            push_pointer(&mut location, token);
+            i += 1;
+            token.to_owned()
+        };
+        steps.push(GtsTraceStep {
+            schema_id: doc_id.clone(),
+            location: location.clone(),
+            step,
+        });
+    }
+    steps
+}
+
+impl GtsStore {
+    /// Validates an instance like [`GtsStore::validate_instance`]; on failure
+    /// every schema error carries its evaluation trace through the schema chain.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the instance or its schema cannot be found.
+    pub fn validate_instance_explain(
+        &mut self,
+        gts_id: &str,
+    ) -> Result<GtsValidationExplain, StoreError> {
+        let gid = GtsID::new(gts_id).map_err(|_| StoreError::ObjectNotFound(gts_id.to_owned()))?;
+        let obj = self
+            .get(&gid.id)
+            .ok_or_else(|| StoreError::ObjectNotFound(gts_id.to_owned()))?;
+        let instance = obj.content.clone();
+        let schema_id = obj
+            .schema_id
+            .clone()
+            .ok_or_else(|| StoreError::SchemaForInstanceNotFound(gid.id.clone()))?;
+        let schema = self.get_schema_content(&schema_id)?;
+
+        let (ok, error, errors) = match self.validate_instance(gts_id) {
+            Ok(()) => (true, String::new(), Vec::new()),
+            Err(e) => (
+                false,
+                e.to_string(),
+                self.trace_errors(&schema_id, &schema, &instance),
+            ),
+        };
+        Ok(GtsValidationExplain {
+            id: gts_id.to_owned(),
+            schema_id: Some(schema_id),
+            ok,
+            errors,
+            error,
+        })
+    }
+
+    fn trace_errors(
+        &self,
+        schema_id: &str,
+        schema: &Value,
+        instance: &Value,
+    ) -> Vec<GtsValidationTrace> {
+        let mut options =
+            jsonschema::options().with_retriever(GtsRetriever::stripped(self.entities()));
+        if let Some(limits) = self.sandbox_limits() {
+            options = options.with_pattern_options(sandbox::pattern_options(&limits));
+        }
+        let validator = match options.build(&Self::remove_x_gts_ref_fields(schema)) {
+            Ok(validator) => validator,
+            Err(e) => {
+                tracing::debug!("Cannot compile schema {schema_id} for explain: {e}");
+                return Vec::new();
+            }
+        };
+
+        validator
+            .iter_errors(instance)
+            .map(|err| {
+                let tokens = pointer_tokens(err.evaluation_path().as_str());
+                GtsValidationTrace {
+                    instance_path: err.instance_path().as_str().to_owned(),
+                    keyword: tokens.last().cloned().unwrap_or_default(),
+                    message: err.to_string(),
+                    steps: trace_steps(self.entities(), schema_id, schema, &tokens),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.pay.core.order.v1~";
+    const REFUND: &str = "gts.x.pay.core.order.v1~x.pay.core.refund.v1~";
+
+    fn store_with_instance(content: &Value) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "amount": {"type": "number", "minimum": 0}
+                    }
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                REFUND,
+                &json!({
+                    "$id": format!("gts://{REFUND}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [
+                        {"$ref": format!("gts://{BASE}")},
+                        {
+                            "type": "object",
+                            "properties": {"reason": {"type": "string"}},
+                            "required": ["reason"]
+                        }
+                    ]
+                }),
+            )
+            .unwrap();
+        let entity = GtsEntity::new(
+            None,
+            None,
+            content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(REFUND.to_owned()),
+        );
+        store.register(entity).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_pointer_tokens() {
+        assert_eq!(pointer_tokens(""), Vec::<String>::new());
+        assert_eq!(pointer_tokens("/allOf/0/$ref"), vec!["allOf", "0", "$ref"]);
+        assert_eq!(
+            pointer_tokens("/properties/a~1b~0c"),
+            vec!["properties", "a/b~c"]
+        );
+    }
+
+    #[test]
+    fn test_explain_traces_error_into_base_segment() {
+        let id = "gts.x.pay.core.order.v1~x.pay.core.refund.v1~x.pay._.r1.v1";
+        let mut store = store_with_instance(&json!({"id": id, "amount": -5, "reason": "dup"}));
+
+        let explain = store.validate_instance_explain(id).unwrap();
+        assert!(!explain.ok);
+        assert_eq!(explain.errors.len(), 1);
+
+        let trace = &explain.errors[0];
+        assert_eq!(trace.instance_path, "/amount");
+        assert_eq!(trace.keyword, "minimum");
+        let steps: Vec<(&str, &str)> = trace
+            .steps
+            .iter()
+            .map(|s| (s.schema_id.as_str(), s.step.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (REFUND, "allOf[0]"),
+                (REFUND, "$ref -> gts://gts.x.pay.core.order.v1~"),
+                (BASE, "properties.amount"),
+                (BASE, "minimum"),
+            ]
+        );
+        assert_eq!(trace.steps[2].location, "/properties/amount");
+
+        let tree = explain.render_tree();
+        assert!(tree.contains("  /amount: -5 is less than the minimum of 0"));
+        assert!(tree.contains(&format!("      properties.amount  [{BASE}]")));
+    }
+
+    #[test]
+    fn test_explain_valid_instance_and_derived_segment_error() {
+        let id = "gts.x.pay.core.order.v1~x.pay.core.refund.v1~x.pay._.r2.v1";
+        let mut store = store_with_instance(&json!({"id": id, "amount": 5}));
+
+        let explain = store.validate_instance_explain(id).unwrap();
+        assert!(!explain.ok);
+        let trace = &explain.errors[0];
+        assert_eq!(trace.keyword, "required");
+        assert_eq!(trace.steps[0].step, "allOf[1]");
+        assert!(trace.steps.iter().all(|s| s.schema_id == REFUND));
+
+        assert!(
+            store
+                .validate_instance_explain("gts.x.pay.core.order.v1~x.pay._.missing.v1")
+                .is_err()
+        );
+
+        let id = "gts.x.pay.core.order.v1~x.pay.core.refund.v1~x.pay._.r3.v1";
+        let mut store = store_with_instance(&json!({"id": id, "amount": 5, "reason": "dup"}));
+        let explain = store.validate_instance_explain(id).unwrap();
+        assert!(explain.ok);
+        assert!(explain.render_tree().contains("valid against"));
+    }
+}
@@ -0,0 +1,400 @@
+//! axum integration: schema enforcement at API boundaries.
+//!
+//! [`GtsValidated<T>`] is a request body extractor that validates the JSON
+//! body against `T`'s GTS schema (its innermost schema ID for generic chains
+//! such as `BaseEventV1<AuditPayloadV1>`) in a shared [`AsyncGtsStore`] before
+//! deserializing it into `T`:
+//!
+//! ```ignore
+//! async fn create_order(GtsValidated(order): GtsValidated<OrderV1>) -> StatusCode {
+//!     // `order` has passed full GTS validation
+//!     StatusCode::CREATED
+//! }
+//!
+//! let app = Router::new()
+//!     .route("/orders", post(create_order))
+//!     .with_state(store); // or any state with `FromRef<S> for AsyncGtsStore`
+//! ```
+//!
+//! In the other direction, handlers that return [`GtsJson<T>`] declare the
+//! schema of their response body, and the [`validate_response`] middleware
+//! checks those bodies before they leave the service.  It is meant for
+//! development and staging, to catch producer drift before consumers do:
+//!
+//! ```ignore
+//! let mode = if profile == "production" {
+//!     GtsResponseValidationMode::Off
+//! } else {
+//!     GtsResponseValidationMode::Reject
+//! };
+//! let app = Router::new()
+//!     .route("/orders/{id}", get(get_order)) // returns GtsJson<OrderV1>
+//!     .layer(middleware::from_fn_with_state(
+//!         GtsResponseValidation::new(store, mode),
+//!         validate_response,
+//!     ));
+//! ```
+
+use axum::Json;
+use axum::body::Body;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRef, FromRequest, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::schema::GtsSchema;
+use crate::store::StoreError;
+use crate::store_async::AsyncGtsStore;
+
+/// Request body validated against `T`'s GTS schema, then deserialized.
+#[derive(Debug, Clone)]
+pub struct GtsValidated<T>(pub T);
+
+/// Why a [`GtsValidated`] body was rejected.
+#[derive(Debug)]
+pub enum GtsValidatedRejection {
+    /// The body is not JSON (wrong content type, syntax error, ...).
+    Json(JsonRejection),
+    /// The body violates the schema.
+    Invalid {
+        schema_id: &'static str,
+        error: StoreError,
+    },
+    /// The schema is not registered, or the store failed.
+    Store {
+        schema_id: &'static str,
+        error: StoreError,
+    },
+    /// The body passed validation but does not deserialize into `T`.
+    Deserialize(serde_json::Error),
+}
+
+impl IntoResponse for GtsValidatedRejection {
+    fn into_response(self) -> Response {
+        let (status, schema_id, error) = match self {
+            GtsValidatedRejection::Json(rejection) => return rejection.into_response(),
+            GtsValidatedRejection::Invalid { schema_id, error } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Some(schema_id),
+                error.to_string(),
+            ),
+            GtsValidatedRejection::Store { schema_id, error } => {
+                tracing::error!("Cannot validate request against {schema_id}: {error}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Some(schema_id),
+                    error.to_string(),
+                )
+            }
+            GtsValidatedRejection::Deserialize(e) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, None, e.to_string())
+            }
+        };
+        let body = json!({"ok": false, "schema_id": schema_id, "error": error});
+        (status, Json(body)).into_response()
+    }
+}
+
+impl<T, S> FromRequest<S> for GtsValidated<T>
+where
+    T: GtsSchema + DeserializeOwned,
+    S: Send + Sync,
+    AsyncGtsStore: FromRef<S>,
+{
+    type Rejection = GtsValidatedRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(body) = Json::<Value>::from_request(req, state)
+            .await
+            .map_err(GtsValidatedRejection::Json)?;
+
+        let schema_id = T::innermost_schema_id();
+        let store = AsyncGtsStore::from_ref(state);
+        match store.validate_value(schema_id, body.clone()).await {
+            Ok(()) => {}
+            Err(error @ (StoreError::ValidationError(_) | StoreError::SchemaRetired { .. })) => {
+                return Err(GtsValidatedRejection::Invalid { schema_id, error });
+            }
+            Err(error) => return Err(GtsValidatedRejection::Store { schema_id, error }),
+        }
+
+        serde_json::from_value(body)
+            .map(GtsValidated)
+            .map_err(GtsValidatedRejection::Deserialize)
+    }
+}
+
+/// Response extension naming the GTS schema of the response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GtsResponseSchema(pub &'static str);
+
+/// JSON response body declared to conform to `T`'s GTS schema.
+///
+/// Serializes like [`Json<T>`] and attaches a [`GtsResponseSchema`] extension
+/// for [`validate_response`].
+#[derive(Debug, Clone)]
+pub struct GtsJson<T>(pub T);
+
+impl<T> IntoResponse for GtsJson<T>
+where
+    T: GtsSchema + Serialize,
+{
+    fn into_response(self) -> Response {
+        let mut response = Json(self.0).into_response();
+        response
+            .extensions_mut()
+            .insert(GtsResponseSchema(T::innermost_schema_id()));
+        response
+    }
+}
+
+/// What [`validate_response`] does with a body that does not match its schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsResponseValidationMode {
+    /// Skip response validation (the production setting).
+    #[default]
+    Off,
+    /// Log a warning and send the response unchanged.
+    Log,
+    /// Replace the response with `500 Internal Server Error`.
+    Reject,
+}
+
+/// State of the [`validate_response`] middleware.
+#[derive(Clone)]
+pub struct GtsResponseValidation {
+    store: AsyncGtsStore,
+    mode: GtsResponseValidationMode,
+}
+
+impl GtsResponseValidation {
+    #[must_use]
+    pub fn new(store: AsyncGtsStore, mode: GtsResponseValidationMode) -> Self {
+        Self { store, mode }
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> GtsResponseValidationMode {
+        self.mode
+    }
+
+    /// Reports a mismatching body; returns the replacement response in
+    /// `Reject` mode.
+    fn mismatch(&self, schema_id: &str, error: &StoreError) -> Option<Response> {
+        if self.mode != GtsResponseValidationMode::Reject {
+            tracing::warn!("Response does not match {schema_id}: {error}");
+            return None;
+        }
+        tracing::error!("Response does not match {schema_id}: {error}");
+        let body = json!({
+            "ok": false,
+            "schema_id": schema_id,
+            "error": format!("Response does not match its schema: {error}"),
+        });
+        Some((StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response())
+    }
+}
+
+/// Middleware validating response bodies tagged with [`GtsResponseSchema`]
+/// (as set by [`GtsJson`]); untagged responses pass through untouched.
+///
+/// Use with `axum::middleware::from_fn_with_state`.
+pub async fn validate_response(
+    State(validation): State<GtsResponseValidation>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    if validation.mode == GtsResponseValidationMode::Off {
+        return response;
+    }
+    let Some(&GtsResponseSchema(schema_id)) = response.extensions().get() else {
+        return response;
+    };
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Cannot read response body declared as {schema_id}: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let result = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(content) => validation.store.validate_value(schema_id, content).await,
+        Err(e) => Err(StoreError::ValidationError(format!(
+            "Response body is not JSON: {e}"
+        ))),
+    };
+    if let Err(error) = result
+        && let Some(rejection) = validation.mismatch(schema_id, &error)
+    {
+        return rejection;
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::store::GtsStore;
+    use axum::Router;
+    use axum::middleware;
+    use axum::routing::{get, post};
+    use tower::ServiceExt;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Order {
+        quantity: u32,
+    }
+
+    impl GtsSchema for Order {
+        const SCHEMA_ID: &'static str = "gts.x.test.web.order.v1~";
+
+        fn gts_schema_with_refs() -> Value {
+            json!({})
+        }
+    }
+
+    async fn app() -> Router {
+        let store = AsyncGtsStore::from_store(GtsStore::new(None));
+        store
+            .register_schema(
+                Order::SCHEMA_ID,
+                json!({
+                    "$id": format!("gts://{}", Order::SCHEMA_ID),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"quantity": {"type": "integer", "minimum": 1}},
+                    "required": ["quantity"]
+                }),
+            )
+            .await
+            .unwrap();
+        Router::new()
+            .route(
+                "/orders",
+                post(|GtsValidated(order): GtsValidated<Order>| async move {
+                    order.quantity.to_string()
+                }),
+            )
+            .with_state(store)
+    }
+
+    async fn post_body(app: Router, body: &str) -> (StatusCode, String) {
+        let request = Request::post("/orders")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_valid_body_is_deserialized() {
+        let (status, body) = post_body(app().await, r#"{"quantity": 3}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "3");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_body_is_rejected_with_422() {
+        let (status, body) = post_body(app().await, r#"{"quantity": 0}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["schema_id"], Order::SCHEMA_ID);
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .contains("Validation failed")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_schema_and_bad_json() {
+        let empty = Router::new()
+            .route(
+                "/orders",
+                post(|GtsValidated(_): GtsValidated<Order>| async {}),
+            )
+            .with_state(AsyncGtsStore::from_store(GtsStore::new(None)));
+        let (status, _) = post_body(empty, r#"{"quantity": 3}"#).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let (status, _) = post_body(app().await, "{not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    async fn response_app(mode: GtsResponseValidationMode) -> Router {
+        let store = AsyncGtsStore::from_store(GtsStore::new(None));
+        store
+            .register_schema(
+                Order::SCHEMA_ID,
+                json!({
+                    "$id": format!("gts://{}", Order::SCHEMA_ID),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"quantity": {"type": "integer", "minimum": 1}},
+                    "required": ["quantity"]
+                }),
+            )
+            .await
+            .unwrap();
+        Router::new()
+            .route("/good", get(|| async { GtsJson(Order { quantity: 2 }) }))
+            .route("/bad", get(|| async { GtsJson(Order { quantity: 0 }) }))
+            .route("/untagged", get(|| async { Json(json!({"quantity": 0})) }))
+            .layer(middleware::from_fn_with_state(
+                GtsResponseValidation::new(store, mode),
+                validate_response,
+            ))
+    }
+
+    async fn get_status(app: Router, uri: &str) -> (StatusCode, Value) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_response_validation_modes() {
+        let app = response_app(GtsResponseValidationMode::Reject).await;
+        let (status, body) = get_status(app.clone(), "/good").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({"quantity": 2}));
+
+        let (status, body) = get_status(app.clone(), "/bad").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["schema_id"], Order::SCHEMA_ID);
+
+        // Responses without a declared schema are not checked
+        let (status, _) = get_status(app, "/untagged").await;
+        assert_eq!(status, StatusCode::OK);
+
+        for mode in [
+            GtsResponseValidationMode::Log,
+            GtsResponseValidationMode::Off,
+        ] {
+            let (status, body) = get_status(response_app(mode).await, "/bad").await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, json!({"quantity": 0}));
+        }
+    }
+}
@@ -1,19 +1,65 @@
+use glob::{MatchOptions, Pattern};
+use serde::Serialize;
 use serde_json::Value;
 use std::fs;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+#[cfg(feature = "encryption")]
+use crate::encryption::GtsContentCipher;
 use crate::entities::{GtsConfig, GtsEntity, GtsFile};
+use crate::gtsignore::GtsIgnore;
 use crate::store::GtsReader;
 
+/// Directories never scanned, wherever they are under a scanned root.
 const EXCLUDE_LIST: &[&str] = &["node_modules", "dist", "build"];
-const VALID_EXTENSIONS: &[&str] = &[".json", ".jsonc", ".gts", ".yaml", ".yml"];
+/// `*` and `?` stay within a directory; `**` crosses them.
+const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+const VALID_EXTENSIONS: &[&str] = &[
+    ".json", ".jsonc", ".gts", ".yaml", ".yml", ".ndjson", ".jsonl",
+];
+
+/// A problem with one file under a reader's paths; the reader skips or
+/// misfiles such files without complaint.  See [`GtsFileReader::check_files`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GtsFileIssue {
+    /// Not valid JSON or YAML.
+    Unparseable { path: String, error: String },
+    /// An entity in the file has no ID field, so it is keyed by the file path.
+    NoId { path: String },
+    /// Named after a GTS ID other than the one it holds.
+    IdMismatch { path: String, id: String },
+    /// A line of a JSON Lines file that is not valid JSON; the file's other
+    /// lines still load.
+    BadLine {
+        path: String,
+        line: usize,
+        error: String,
+    },
+}
+
+/// A JSON Lines line that is not valid JSON, by 1-based line number.
+struct BadLine {
+    line: usize,
+    error: String,
+}
+
+/// A file's content with its bad JSON Lines lines.
+type LoadedLines = (Value, Vec<BadLine>);
 
 pub struct GtsFileReader {
     paths: Vec<PathBuf>,
     cfg: GtsConfig,
     files: Vec<PathBuf>,
     initialized: bool,
+    #[cfg(feature = "encryption")]
+    cipher: Option<GtsContentCipher>,
 }
 
 impl GtsFileReader {
@@ -29,62 +75,83 @@ impl GtsFileReader {
             cfg: cfg.unwrap_or_default(),
             files: Vec::new(),
             initialized: false,
+            #[cfg(feature = "encryption")]
+            cipher: None,
         }
     }
 
-    #[allow(clippy::cognitive_complexity)]
+    /// Decrypts instances written with `cipher`; encrypted instances that
+    /// cannot be decrypted are skipped.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn with_cipher(mut self, cipher: GtsContentCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Opens encrypted documents in `content`.  A document that fails to
+    /// open is dropped with a warning; in an array it is left sealed, so the
+    /// other items keep their positions, and [`Self::is_sealed`] skips it.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn decrypt(&self, file_path: &Path, content: Value) -> Option<Value> {
+        let Some(cipher) = &self.cipher else {
+            return Some(content);
+        };
+        let open = |doc: &Value| match cipher.decrypt(doc) {
+            Ok(plain) => Some(plain),
+            Err(e) => {
+                tracing::warn!("Skipping encrypted entity in {:?}: {}", file_path, e);
+                None
+            }
+        };
+        match content {
+            Value::Array(items) => Some(Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| open(&item).unwrap_or(item))
+                    .collect(),
+            )),
+            doc => open(&doc),
+        }
+    }
+
+    /// Whether `item` is still encrypted after [`Self::decrypt`], i.e. it
+    /// could not be opened.
+    #[cfg(feature = "encryption")]
+    fn is_sealed(&self, item: &Value) -> bool {
+        self.cipher.is_some() && crate::encryption::is_encrypted(item)
+    }
+
     fn collect_files(&mut self) {
         let mut seen = std::collections::HashSet::new();
         let mut collected = Vec::new();
+        let mut add = |path: &Path| {
+            let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            if seen.insert(resolved.clone()) {
+                tracing::debug!("- discovered file: {:?}", resolved);
+                collected.push(resolved);
+            }
+        };
 
         for path in &self.paths {
-            let resolved_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if let Some((base, pattern)) = Self::split_glob(path) {
+                for file in Self::walk(&base) {
+                    let relative = file.strip_prefix(&base).unwrap_or(&file);
+                    if pattern.matches_path_with(relative, GLOB_OPTIONS) {
+                        add(&file);
+                    }
+                }
+                continue;
+            }
 
+            let resolved_path = path.canonicalize().unwrap_or_else(|_| path.clone());
             if resolved_path.is_file() {
-                if let Some(ext) = resolved_path.extension() {
-                    let ext_str = ext.to_string_lossy().to_lowercase();
-                    if VALID_EXTENSIONS.contains(&format!(".{ext_str}").as_str()) {
-                        let rp = resolved_path.to_string_lossy().to_string();
-                        if !seen.contains(&rp) {
-                            seen.insert(rp.clone());
-                            tracing::debug!("- discovered file: {:?}", resolved_path);
-                            collected.push(resolved_path.clone());
-                        }
-                    }
+                if Self::is_readable(&resolved_path) {
+                    add(&resolved_path);
                 }
             } else if resolved_path.is_dir() {
-                for entry in WalkDir::new(&resolved_path)
-                    .follow_links(true)
-                    .into_iter()
-                    .flatten()
-                {
-                    let path = entry.path();
-
-                    // Skip excluded directories
-                    if path.is_dir()
-                        && let Some(name) = path.file_name()
-                        && EXCLUDE_LIST.contains(&name.to_string_lossy().as_ref())
-                    {
-                        continue;
-                    }
-
-                    if path.is_file()
-                        && let Some(ext) = path.extension()
-                    {
-                        let ext_str = ext.to_string_lossy().to_lowercase();
-                        if VALID_EXTENSIONS.contains(&format!(".{ext_str}").as_str()) {
-                            let rp = path
-                                .canonicalize()
-                                .unwrap_or_else(|_| path.to_path_buf())
-                                .to_string_lossy()
-                                .to_string();
-                            if !seen.contains(&rp) {
-                                seen.insert(rp.clone());
-                                tracing::debug!("- discovered file: {:?}", path);
-                                collected.push(PathBuf::from(rp));
-                            }
-                        }
-                    }
+                for file in Self::walk(&resolved_path) {
+                    add(&file);
                 }
             }
         }
@@ -92,9 +159,92 @@ impl GtsFileReader {
         self.files = collected;
     }
 
-    fn load_json_file(file_path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+    /// The readable files under `root`, leaving out excluded directories and
+    /// what its `.gtsignore` excludes.
+    fn walk(root: &Path) -> Vec<PathBuf> {
+        let ignore = GtsIgnore::load(root);
+        WalkDir::new(root)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|entry| {
+                let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                !Self::is_excluded(&ignore, relative, entry.file_type().is_dir())
+            })
+            .flatten()
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| path.is_file() && Self::is_readable(path))
+            .collect()
+    }
+
+    /// Whether a scan skips `relative`, a path under a scanned root whose
+    /// `.gtsignore` is `ignore`: it is in one of the [`EXCLUDE_LIST`]
+    /// directories or ignored.
+    pub(crate) fn is_excluded(ignore: &GtsIgnore, relative: &Path, is_dir: bool) -> bool {
+        let mut dirs = relative.components().map(std::path::Component::as_os_str);
+        if !is_dir {
+            dirs.next_back();
+        }
+        dirs.any(|dir| EXCLUDE_LIST.contains(&dir.to_string_lossy().as_ref()))
+            || ignore.is_ignored(relative, is_dir)
+    }
+
+    /// For a glob such as `schemas/**/*.schema.json`, the directory before
+    /// its first wildcard and the pattern for paths under it; `None` for a
+    /// plain path.
+    pub(crate) fn split_glob(path: &Path) -> Option<(PathBuf, Pattern)> {
+        let is_wild = |part: &str| part.contains(['*', '?', '[']);
+        if !is_wild(&path.to_string_lossy()) {
+            return None;
+        }
+        let mut base = PathBuf::new();
+        let mut rest = Vec::new();
+        for component in path.components() {
+            let part = component.as_os_str().to_string_lossy();
+            if rest.is_empty() && !is_wild(&part) {
+                base.push(component);
+            } else {
+                rest.push(part.to_string());
+            }
+        }
+        if base.as_os_str().is_empty() {
+            base.push(".");
+        }
+        match Pattern::new(&rest.join("/")) {
+            Ok(pattern) => Some((base, pattern)),
+            Err(e) => {
+                tracing::warn!("Invalid glob {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Returns the files discovered under the configured paths, scanning on first use.
+    pub(crate) fn discovered_files(&mut self) -> &[PathBuf] {
+        if !self.initialized {
+            self.collect_files();
+            self.initialized = true;
+        }
+        &self.files
+    }
+
+    pub(crate) fn load_json_file(file_path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+        let (value, bad_lines) = Self::load_file_lines(file_path)?;
+        Self::warn_bad_lines(file_path, &bad_lines);
+        Ok(value)
+    }
+
+    /// Loads `file_path` like [`Self::load_json_file`], returning the lines
+    /// of a JSON Lines file that do not parse instead of logging them.
+    fn load_file_lines(file_path: &Path) -> Result<LoadedLines, Box<dyn std::error::Error>> {
+        if Self::is_json_lines(file_path) {
+            return Self::read_json_lines(BufReader::new(fs::File::open(file_path)?));
+        }
         let content = fs::read_to_string(file_path)?;
+        Ok((Self::parse_content(file_path, &content)?, Vec::new()))
+    }
 
+    /// Parses `content` as the format `file_path`'s extension names.
+    fn parse_content(file_path: &Path, content: &str) -> Result<Value, Box<dyn std::error::Error>> {
         // Determine file type by extension
         let extension = file_path
             .extension()
@@ -105,23 +255,128 @@ impl GtsFileReader {
         let value: Value = match extension.as_str() {
             "yaml" | "yml" => {
                 // Parse YAML and convert to JSON
-                serde_saphyr::from_str(&content)?
+                serde_saphyr::from_str(content)?
+            }
+            "ndjson" | "jsonl" => {
+                let (value, bad_lines) = Self::read_json_lines(content.as_bytes())?;
+                Self::warn_bad_lines(file_path, &bad_lines);
+                value
             }
             _ => {
                 // Default: parse as JSON
-                serde_json::from_str(&content)?
+                serde_json::from_str(content)?
             }
         };
 
         Ok(value)
     }
 
+    fn is_json_lines(file_path: &Path) -> bool {
+        file_path.extension().is_some_and(|ext| {
+            ext.eq_ignore_ascii_case("ndjson") || ext.eq_ignore_ascii_case("jsonl")
+        })
+    }
+
+    /// The documents of a JSON Lines file as an array, reading one line at a
+    /// time, with the lines that are not valid JSON.  Blank and bad lines
+    /// become `null`, so each document's sequence number is its zero-based
+    /// line number.
+    fn read_json_lines(reader: impl BufRead) -> Result<LoadedLines, Box<dyn std::error::Error>> {
+        let mut documents = Vec::new();
+        let mut bad_lines = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                documents.push(Value::Null);
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(document) => documents.push(document),
+                Err(e) => {
+                    bad_lines.push(BadLine {
+                        line: idx + 1,
+                        error: e.to_string(),
+                    });
+                    documents.push(Value::Null);
+                }
+            }
+        }
+        Ok((Value::Array(documents), bad_lines))
+    }
+
+    fn warn_bad_lines(file_path: &Path, bad_lines: &[BadLine]) {
+        for bad in bad_lines {
+            tracing::warn!(
+                "Skipping line {} of {:?}: {}",
+                bad.line,
+                file_path,
+                bad.error
+            );
+        }
+    }
+
+    /// Returns true if `path` has one of the supported GTS file extensions.
+    pub(crate) fn is_gts_file(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| {
+            let ext_str = ext.to_string_lossy().to_lowercase();
+            VALID_EXTENSIONS.contains(&format!(".{ext_str}").as_str())
+        })
+    }
+
+    /// Returns true if the reader loads `path`: a GTS file, or an archive
+    /// of them with the `archive` feature.
+    fn is_readable(path: &Path) -> bool {
+        #[cfg(feature = "archive")]
+        if crate::archive::is_archive(path) {
+            return true;
+        }
+        Self::is_gts_file(path)
+    }
+
+    pub(crate) fn process_file(&self, file_path: &Path) -> Vec<GtsEntity> {
+        #[cfg(feature = "archive")]
+        if crate::archive::is_archive(file_path) {
+            return self.process_archive(file_path);
+        }
+        self.process_loaded(file_path, Self::load_json_file(file_path))
+    }
+
+    /// The entities of every GTS file in an archive, keyed by paths under
+    /// the archive's own.
+    #[cfg(feature = "archive")]
+    fn process_archive(&self, archive_path: &Path) -> Vec<GtsEntity> {
+        match crate::archive::read_archive(archive_path) {
+            Ok(entries) => entries
+                .into_iter()
+                .flat_map(|entry| {
+                    let path = archive_path.join(&entry.name);
+                    let loaded = Self::parse_content(&path, &entry.content);
+                    self.process_loaded(&path, loaded)
+                })
+                .collect(),
+            Err(e) => {
+                tracing::debug!("Failed to read archive {:?}: {}", archive_path, e);
+                Vec::new()
+            }
+        }
+    }
+
     #[allow(clippy::cognitive_complexity)]
-    fn process_file(&self, file_path: &Path) -> Vec<GtsEntity> {
+    fn process_loaded(
+        &self,
+        file_path: &Path,
+        loaded: Result<Value, Box<dyn std::error::Error>>,
+    ) -> Vec<GtsEntity> {
         let mut entities = Vec::new();
 
-        match Self::load_json_file(file_path) {
-            Ok(content) => {
+        #[cfg(feature = "encryption")]
+        let loaded = loaded.map(|content| self.decrypt(file_path, content));
+        #[cfg(not(feature = "encryption"))]
+        let loaded = loaded.map(Some);
+        match loaded {
+            Ok(None) => {}
+            Ok(Some(content)) => {
                 let json_file = GtsFile::new(
                     file_path.to_string_lossy().to_string(),
                     file_path
@@ -135,6 +390,10 @@ impl GtsFileReader {
                 // Handle both single objects and arrays
                 if let Some(arr) = content.as_array() {
                     for (idx, item) in arr.iter().enumerate() {
+                        #[cfg(feature = "encryption")]
+                        if self.is_sealed(item) {
+                            continue;
+                        }
                         let entity = GtsEntity::new(
                             Some(json_file.clone()),
                             Some(idx),
@@ -189,6 +448,75 @@ impl GtsFileReader {
     }
 }
 
+impl GtsFileReader {
+    /// Checks every discovered file for problems that make loading skip it
+    /// or key its entities by file path, or for a file name (e.g. `gts.x.a.b.c.v1~.schema.json`) that names a
+    /// different ID than the single entity inside.  Files not named after a
+    /// GTS ID are not name-checked.
+    pub fn check_files(&mut self) -> Vec<GtsFileIssue> {
+        let files = self.discovered_files().to_vec();
+        let mut issues = Vec::new();
+        for file_path in files {
+            let path = file_path.to_string_lossy().to_string();
+            #[cfg(feature = "archive")]
+            if crate::archive::is_archive(&file_path) {
+                // The entries of archives are not checked
+                continue;
+            }
+            let content = match Self::load_file_lines(&file_path) {
+                Ok((content, bad_lines)) => {
+                    issues.extend(bad_lines.into_iter().map(|bad| GtsFileIssue::BadLine {
+                        path: path.clone(),
+                        line: bad.line,
+                        error: bad.error,
+                    }));
+                    content
+                }
+                Err(e) => {
+                    issues.push(GtsFileIssue::Unparseable {
+                        path,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let entities = self.process_file(&file_path);
+            let keyed_by_path = |id: &str| {
+                id == path
+                    || id
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|s| s.starts_with('#'))
+            };
+            if entities
+                .iter()
+                .any(|e| e.effective_id().is_none_or(|id| keyed_by_path(&id)))
+            {
+                issues.push(GtsFileIssue::NoId { path });
+                continue;
+            }
+            let [entity] = entities.as_slice() else {
+                continue;
+            };
+            if content.is_array() {
+                continue;
+            }
+            let stem = file_path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let stem = stem.strip_suffix(".schema").unwrap_or(&stem);
+            if let Some(id) = entity.effective_id()
+                && stem.starts_with("gts.")
+                && stem != id
+            {
+                issues.push(GtsFileIssue::IdMismatch { path, id });
+            }
+        }
+        issues
+    }
+}
+
 impl GtsReader for GtsFileReader {
     fn iter(&mut self) -> Box<dyn Iterator<Item = GtsEntity> + '_> {
         if !self.initialized {
@@ -220,6 +548,15 @@ impl GtsReader for GtsFileReader {
     fn reset(&mut self) {
         self.initialized = false;
     }
+
+    fn check(&self) -> Result<(), String> {
+        let root =
+            |path: &PathBuf| Self::split_glob(path).map_or_else(|| path.clone(), |(base, _)| base);
+        match self.paths.iter().map(root).find(|path| !path.exists()) {
+            Some(missing) => Err(format!("{} does not exist", missing.display())),
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -339,20 +676,65 @@ mod tests {
         let mut reader = GtsFileReader::new(&paths, None);
         reader.collect_files();
 
-        // Should find the main file
-        assert!(
-            !reader.files.is_empty(),
-            "Should find at least the main file"
-        );
+        // Only the main file; the excluded directories are not descended into
+        assert_eq!(reader.files.len(), 1, "found {:?}", reader.files);
+        assert!(reader.files[0].ends_with("file1.json"));
+    }
 
-        // Count files in excluded directories - the current implementation
-        // still collects files from these directories but we're verifying
-        // that the main file is collected. This test verifies the basic behavior.
-        let main_file_found = reader.files.iter().any(|f| {
-            let path_str = f.to_string_lossy();
-            path_str.ends_with("file1.json")
-        });
-        assert!(main_file_found, "Should find the main file");
+    #[test]
+    fn test_collect_files_expands_globs() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("schemas/orders");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("order.schema.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("schemas/top.schema.json"), "{}").unwrap();
+        fs::write(nested.join("order.json"), "{}").unwrap();
+
+        let glob = temp_dir.path().join("schemas/**/*.schema.json");
+        let mut reader = GtsFileReader::new(&[glob.to_string_lossy().to_string()], None);
+        let mut names: Vec<String> = reader
+            .discovered_files()
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["order.schema.json", "top.schema.json"]);
+        assert!(reader.check().is_ok());
+
+        let glob = temp_dir.path().join("schemas/*.schema.json");
+        let mut reader = GtsFileReader::new(&[glob.to_string_lossy().to_string()], None);
+        assert_eq!(reader.discovered_files().len(), 1);
+
+        let missing = temp_dir.path().join("missing/*.json");
+        let reader = GtsFileReader::new(&[missing.to_string_lossy().to_string()], None);
+        assert!(reader.check().is_err());
+    }
+
+    #[test]
+    fn test_collect_files_honors_gtsignore() {
+        let temp_dir = TempDir::new().unwrap();
+        for dir in ["generated", "vendor/acme", "types"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        for file in [
+            "generated/a.json",
+            "vendor/acme/b.json",
+            "types/c.json",
+            "types/d.gen.json",
+        ] {
+            fs::write(temp_dir.path().join(file), "{}").unwrap();
+        }
+        fs::write(
+            temp_dir.path().join(".gtsignore"),
+            "generated/\n/vendor\n*.gen.json\n",
+        )
+        .unwrap();
+
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut reader = GtsFileReader::new(&paths, None);
+        let files = reader.discovered_files();
+        assert_eq!(files.len(), 1, "{files:?}");
+        assert!(files[0].ends_with("types/c.json"));
     }
 
     #[test]
@@ -449,6 +831,67 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_process_file_json_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("events.ndjson");
+        fs::write(
+            &file_path,
+            concat!(
+                "{\"id\": \"gts.x.test.lines.event.v1~x.test._.first.v1\"}\n",
+                "\n",
+                "{\"type\": \"gts.x.test.lines.event.v1~\", \"note\": \"anonymous\"}\n",
+            ),
+        )
+        .unwrap();
+
+        let reader = GtsFileReader::new(&[], None);
+        let entities = reader.process_file(&file_path);
+        let ids: Vec<String> = entities
+            .iter()
+            .filter_map(GtsEntity::effective_id)
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "gts.x.test.lines.event.v1~x.test._.first.v1".to_owned(),
+                format!("{}#2", file_path.display()),
+            ]
+        );
+        assert!(GtsFileReader::is_gts_file(Path::new("dump.JSONL")));
+
+        fs::write(
+            &file_path,
+            concat!(
+                "{\"id\": \"gts.x.test.lines.event.v1~x.test._.first.v1\"}\n",
+                "{broken\n",
+                "{\"id\": \"gts.x.test.lines.event.v1~x.test._.third.v1\"}\n",
+            ),
+        )
+        .unwrap();
+        let ids: Vec<String> = reader
+            .process_file(&file_path)
+            .iter()
+            .filter_map(GtsEntity::effective_id)
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "gts.x.test.lines.event.v1~x.test._.first.v1",
+                "gts.x.test.lines.event.v1~x.test._.third.v1",
+            ]
+        );
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let issues = GtsFileReader::new(&paths, None).check_files();
+        assert!(
+            matches!(
+                issues.as_slice(),
+                [GtsFileIssue::BadLine { path, line: 2, .. }] if path.ends_with("events.ndjson")
+            ),
+            "{issues:?}"
+        );
+    }
+
     #[test]
     fn test_load_json_file_nonexistent() {
         let result = GtsFileReader::load_json_file(Path::new("/nonexistent/file.json"));
@@ -625,4 +1068,47 @@ mod tests {
         assert_eq!(entities.len(), 1);
         assert!(reader.initialized);
     }
+
+    #[test]
+    fn test_check_files_reports_skipped_and_misnamed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let schema = |id: &str| {
+            format!(
+                r#"{{"$id": "gts://{id}", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}}"#
+            )
+        };
+        fs::write(
+            dir.join("gts.x.test.doc.good.v1~.schema.json"),
+            schema("gts.x.test.doc.good.v1~"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("gts.x.test.doc.old.v1~.schema.json"),
+            schema("gts.x.test.doc.new.v1~"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("anything.json"),
+            schema("gts.x.test.doc.other.v1~"),
+        )
+        .unwrap();
+        fs::write(dir.join("broken.json"), "{not json").unwrap();
+        fs::write(dir.join("empty.json"), r#"{"title": "no id"}"#).unwrap();
+
+        let paths = vec![dir.to_string_lossy().to_string()];
+        let mut issues = GtsFileReader::new(&paths, None).check_files();
+        issues.sort_by_key(|issue| format!("{issue:?}"));
+
+        assert_eq!(issues.len(), 3, "{issues:?}");
+        assert!(matches!(
+            &issues[0],
+            GtsFileIssue::IdMismatch { path, id }
+                if path.ends_with("gts.x.test.doc.old.v1~.schema.json") && id == "gts.x.test.doc.new.v1~"
+        ));
+        assert!(matches!(&issues[1], GtsFileIssue::NoId { path } if path.ends_with("empty.json")));
+        assert!(
+            matches!(&issues[2], GtsFileIssue::Unparseable { path, .. } if path.ends_with("broken.json"))
+        );
+    }
 }
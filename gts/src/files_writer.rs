@@ -0,0 +1,294 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "encryption")]
+use crate::encryption::GtsContentCipher;
+use crate::entities::GtsEntity;
+use crate::fingerprint::canonical_json_pretty;
+use crate::store::{GtsWriteError, GtsWriter};
+
+/// Writes entities back to the JSON files they were read from.
+///
+/// Entities loaded from a file are updated in place, including entries of a
+/// file holding a list of entities. Entities registered in memory are written
+/// to a new file under `root`: `{schema_id}.schema.json` for schemas and
+/// `{id}.json` for instances.
+pub struct GtsFileWriter {
+    root: PathBuf,
+    #[cfg(feature = "encryption")]
+    cipher: Option<GtsContentCipher>,
+}
+
+fn io_error(path: &Path, e: &impl ToString) -> GtsWriteError {
+    GtsWriteError::Io {
+        path: path.to_string_lossy().into_owned(),
+        cause: e.to_string(),
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, GtsWriteError> {
+    if !path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    {
+        return Err(GtsWriteError::UnsupportedFormat(
+            path.to_string_lossy().into_owned(),
+        ));
+    }
+    let text = fs::read_to_string(path).map_err(|e| io_error(path, &e))?;
+    serde_json::from_str(&text).map_err(|e| io_error(path, &e))
+}
+
+fn write_json(path: &Path, doc: &Value) -> Result<(), GtsWriteError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| io_error(dir, &e))?;
+    }
+    fs::write(path, canonical_json_pretty(doc)).map_err(|e| io_error(path, &e))
+}
+
+fn not_in_file(path: &Path, entity: &GtsEntity) -> GtsWriteError {
+    GtsWriteError::NotInFile {
+        path: path.to_string_lossy().into_owned(),
+        id: entity.effective_id().unwrap_or_default(),
+    }
+}
+
+impl GtsFileWriter {
+    #[must_use]
+    pub fn new(root: &str) -> Self {
+        GtsFileWriter {
+            root: PathBuf::from(shellexpand::tilde(root).to_string()),
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    /// Encrypts instance content with `cipher` before writing it.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn with_cipher(mut self, cipher: GtsContentCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// The content as written to `path`: encrypted for instances when a
+    /// cipher is set.
+    #[cfg(feature = "encryption")]
+    fn stored_content(&self, path: &Path, entity: &GtsEntity) -> Result<Value, GtsWriteError> {
+        match &self.cipher {
+            Some(cipher) if !entity.is_schema => cipher
+                .encrypt(&entity.content)
+                .map_err(|e| io_error(path, &e)),
+            _ => Ok(entity.content.clone()),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[allow(clippy::unused_self, clippy::unnecessary_wraps)]
+    fn stored_content(&self, _path: &Path, entity: &GtsEntity) -> Result<Value, GtsWriteError> {
+        Ok(entity.content.clone())
+    }
+
+    /// The file `entity` is stored in: its source file, or a new one under
+    /// the root.
+    fn path_of(&self, entity: &GtsEntity) -> Result<PathBuf, GtsWriteError> {
+        if let Some(file) = &entity.file {
+            return Ok(PathBuf::from(&file.path));
+        }
+        let id = entity.effective_id().ok_or(GtsWriteError::MissingId)?;
+        let name = if entity.is_schema {
+            format!("{id}.schema.json")
+        } else {
+            format!("{id}.json")
+        };
+        Ok(self.root.join(name))
+    }
+
+    fn write(&self, entity: &GtsEntity) -> Result<String, GtsWriteError> {
+        let path = self.path_of(entity)?;
+        let content = self.stored_content(&path, entity)?;
+        if entity.file.is_none() {
+            write_json(&path, &content)?;
+            return Ok(path.to_string_lossy().into_owned());
+        }
+
+        let mut doc = read_json(&path)?;
+        let target = match entity.list_sequence {
+            Some(idx) => doc.get_mut(idx),
+            None => Some(&mut doc),
+        };
+        let Some(target) = target.filter(|t| t.is_object()) else {
+            return Err(not_in_file(&path, entity));
+        };
+        *target = content;
+        write_json(&path, &doc)?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+}
+
+impl GtsWriter for GtsFileWriter {
+    fn write_entity(&mut self, entity: &GtsEntity) -> Result<String, GtsWriteError> {
+        self.write(entity)
+    }
+
+    fn write_schema(&mut self, entity: &GtsEntity) -> Result<String, GtsWriteError> {
+        self.write(entity)
+    }
+
+    fn delete(&mut self, entity: &GtsEntity) -> Result<(), GtsWriteError> {
+        let path = self.path_of(entity)?;
+        let Some(idx) = entity.list_sequence else {
+            return fs::remove_file(&path).map_err(|e| io_error(&path, &e));
+        };
+
+        let mut doc = read_json(&path)?;
+        match doc.as_array_mut() {
+            Some(items) if idx < items.len() => {
+                items.remove(idx);
+            }
+            _ => return Err(not_in_file(&path, entity)),
+        }
+        write_json(&path, &doc)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use crate::files_reader::GtsFileReader;
+    use crate::store::{GtsStore, StoreError};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const ITEM: &str = "gts.x.test.writer.item.v1~";
+
+    fn read(path: &Path) -> Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn file_store(dir: &TempDir) -> GtsStore {
+        let root = dir.path().to_string_lossy().into_owned();
+        let reader = GtsFileReader::new(std::slice::from_ref(&root), None);
+        let mut store = GtsStore::new(Some(Box::new(reader)));
+        store.set_writer(Box::new(GtsFileWriter::new(&root)));
+        store
+    }
+
+    #[test]
+    fn test_persist_in_memory_registrations() {
+        let dir = TempDir::new().unwrap();
+        let mut store = file_store(&dir);
+        let schema = json!({
+            "$id": format!("gts://{ITEM}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        store.register_schema(ITEM, &schema).unwrap();
+
+        let path = store.persist(ITEM).unwrap();
+        assert!(path.ends_with(&format!("{ITEM}.schema.json")));
+        assert_eq!(read(Path::new(&path)), schema);
+
+        // The store now knows where the schema lives; a reload finds it
+        assert_eq!(store.get(ITEM).unwrap().file.as_ref().unwrap().path, path);
+        assert!(file_store(&dir).get(ITEM).is_some());
+
+        store.delete_persisted(ITEM).unwrap();
+        assert!(!Path::new(&path).exists());
+        assert!(store.get(ITEM).is_some());
+    }
+
+    #[test]
+    fn test_persisted_files_are_canonical() {
+        let dir = TempDir::new().unwrap();
+        let mut store = file_store(&dir);
+        let schema = json!({
+            "type": "object",
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"n": {"maximum": 10.0, "type": "number"}},
+            "$id": format!("gts://{ITEM}")
+        });
+        store.register_schema(ITEM, &schema).unwrap();
+        let path = store.persist(ITEM).unwrap();
+        let first = fs::read(&path).unwrap();
+        assert_eq!(first, canonical_json_pretty(&schema).into_bytes());
+
+        // Writing the same content again, reordered, leaves the file as it was
+        let mut reordered = store.get(ITEM).cloned().unwrap();
+        reordered.content = json!({
+            "$id": format!("gts://{ITEM}"),
+            "properties": {"n": {"type": "number", "maximum": 10}},
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        store.register(reordered).unwrap();
+        store.persist(ITEM).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn test_update_and_delete_in_list_file() {
+        let dir = TempDir::new().unwrap();
+        let items = dir.path().join("items.json");
+        let ids: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|n| format!("{ITEM}x.test._.{n}.v1"))
+            .collect();
+        let list: Vec<Value> = ids.iter().map(|id| json!({"id": id, "n": 1})).collect();
+        fs::write(&items, serde_json::to_string(&list).unwrap()).unwrap();
+        let mut store = file_store(&dir);
+
+        // Update the last entry in place
+        let mut entity = store.get(&ids[2]).cloned().unwrap();
+        entity.content["n"] = json!(3);
+        store.register(entity).unwrap();
+        assert_eq!(store.persist(&ids[2]).unwrap(), items.to_string_lossy());
+        assert_eq!(read(&items)[2]["n"], 3);
+
+        // Deleting the first entry keeps the others addressable
+        store.delete_persisted(&ids[0]).unwrap();
+        assert_eq!(read(&items).as_array().unwrap().len(), 2);
+        assert_eq!(store.get(&ids[2]).unwrap().list_sequence, Some(1));
+        store.delete_persisted(&ids[2]).unwrap();
+        assert_eq!(read(&items), json!([{"id": ids[1], "n": 1}]));
+    }
+
+    #[test]
+    fn test_persist_errors() {
+        let mut store = GtsStore::new(None);
+        assert!(matches!(store.persist(ITEM), Err(StoreError::NoWriter)));
+
+        let dir = TempDir::new().unwrap();
+        let yaml = dir.path().join("item.yaml");
+        fs::write(&yaml, format!("id: {ITEM}x.test._.y.v1\n")).unwrap();
+        let mut store = file_store(&dir);
+        let id = format!("{ITEM}x.test._.y.v1");
+        assert!(matches!(
+            store.persist(&id),
+            Err(StoreError::Write(GtsWriteError::UnsupportedFormat(_)))
+        ));
+        assert!(matches!(
+            store.persist("gts.x.test.writer.missing.v1~"),
+            Err(StoreError::EntityNotFound(_))
+        ));
+
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &json!({"n": 1}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        assert!(matches!(
+            GtsFileWriter::new("/tmp").write_entity(&entity),
+            Err(GtsWriteError::MissingId)
+        ));
+    }
+}
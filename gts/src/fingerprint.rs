@@ -0,0 +1,148 @@
+//! Content-addressable fingerprints of entities.
+//!
+//! [`schema_fingerprint`] hashes the canonical JSON form of a document:
+//! object keys sorted, no whitespace, integral floats written as integers.
+//! Two documents that differ only in key order, formatting or `1.0` vs `1`
+//! get the same fingerprint.  The store keeps the fingerprint of every
+//! registered entity, so importers can ask [`GtsStore::has_changed`] before
+//! re-registering a schema, and tooling can spot files whose content has
+//! drifted from what is registered.  [`canonical_json_pretty`] is the same
+//! form indented, which every JSON file the store and the CLI write is in,
+//! so that writing the same content again leaves no diff.
+
+use serde_json::{Map, Value};
+
+use crate::bundle::content_checksum;
+use crate::numbers::canonicalize_numbers;
+use crate::store::GtsStore;
+
+fn sorted_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sorted_keys(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// `value` with object keys sorted and integral floats written as integers.
+#[must_use]
+pub fn canonical_value(value: &Value) -> Value {
+    let mut canonical = sorted_keys(value);
+    canonicalize_numbers(&mut canonical);
+    canonical
+}
+
+/// Compact canonical JSON text of `value`.
+#[must_use]
+pub fn canonical_json(value: &Value) -> String {
+    canonical_value(value).to_string()
+}
+
+/// Canonical JSON text of `value` for files checked into version control:
+/// sorted keys, normalized numbers, two-space indentation and a trailing
+/// newline.  Writing the same schema twice gives byte-identical files.
+#[must_use]
+pub fn canonical_json_pretty(value: &Value) -> String {
+    let mut text = serde_json::to_string_pretty(&canonical_value(value)).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Hex SHA-256 of the canonical JSON of `content`.
+#[must_use]
+pub fn schema_fingerprint(content: &Value) -> String {
+    content_checksum(&canonical_value(content))
+}
+
+impl GtsStore {
+    /// Fingerprint of the registered content of entity `id`, computed once
+    /// per registration.
+    pub fn fingerprint(&mut self, id: &str) -> Option<String> {
+        if let Some(fingerprint) = self.fingerprints().get(id) {
+            return Some(fingerprint.clone());
+        }
+        let fingerprint = schema_fingerprint(&self.get(id)?.content);
+        self.fingerprints_mut()
+            .insert(id.to_owned(), fingerprint.clone());
+        Some(fingerprint)
+    }
+
+    /// Whether `content` differs from the registered content of `id`,
+    /// ignoring key order and formatting.  `true` if `id` is not registered.
+    pub fn has_changed(&mut self, id: &str, content: &Value) -> bool {
+        self.fingerprint(id)
+            .is_none_or(|fingerprint| fingerprint != schema_fingerprint(content))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = "gts.x.test.fp.item.v1~";
+
+    #[test]
+    fn test_canonical_json_ignores_key_order_and_number_form() {
+        let a = json!({"b": [1.0, {"y": 2, "x": 1}], "a": "s"});
+        let b = json!({"a": "s", "b": [1, {"x": 1, "y": 2.0}]});
+        assert_eq!(canonical_json(&a), r#"{"a":"s","b":[1,{"x":1,"y":2}]}"#);
+        assert_eq!(schema_fingerprint(&a), schema_fingerprint(&b));
+        assert_ne!(
+            schema_fingerprint(&a),
+            schema_fingerprint(&json!({"a": "s", "b": [1]}))
+        );
+        assert_eq!(schema_fingerprint(&a).len(), 64);
+    }
+
+    #[test]
+    fn test_canonical_json_pretty_is_stable() {
+        let a = json!({"b": [1.0, {"y": 2, "x": 1}], "a": "s"});
+        let b = json!({"a": "s", "b": [1, {"x": 1, "y": 2.0}]});
+        assert_eq!(canonical_json_pretty(&a), canonical_json_pretty(&b));
+        assert_eq!(
+            canonical_json_pretty(&a),
+            "{\n  \"a\": \"s\",\n  \"b\": [\n    1,\n    {\n      \"x\": 1,\n      \"y\": 2\n    }\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_has_changed_tracks_registrations() {
+        let mut store = GtsStore::new(None);
+        let schema = json!({
+            "$id": format!("gts://{SCHEMA}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        assert!(store.has_changed(SCHEMA, &schema));
+        assert!(store.fingerprint(SCHEMA).is_none());
+
+        store.register_schema(SCHEMA, &schema).unwrap();
+        let reordered = json!({
+            "type": "object",
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$id": format!("gts://{SCHEMA}")
+        });
+        assert!(!store.has_changed(SCHEMA, &reordered));
+        let first = store.fingerprint(SCHEMA).unwrap();
+
+        let mut edited = schema.clone();
+        edited["required"] = json!(["id"]);
+        assert!(store.has_changed(SCHEMA, &edited));
+
+        // Re-registering drops the remembered fingerprint
+        store.register_schema(SCHEMA, &edited).unwrap();
+        assert!(!store.has_changed(SCHEMA, &edited));
+        assert_ne!(store.fingerprint(SCHEMA).unwrap(), first);
+    }
+}
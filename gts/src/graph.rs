@@ -0,0 +1,341 @@
+//! Store-wide dependency graph export.
+//!
+//! [`GtsStore::build_schema_graph`] walks the references of one entity into a
+//! nested JSON tree.  [`GtsStore::dependency_graph`] instead collects every
+//! schema of the store with three kinds of edges — chain inheritance,
+//! `gts://` `$ref`s and `x-gts-ref` constraints, as [`GtsStore::references`]
+//! finds them — and renders the result as
+//! Graphviz DOT, Mermaid or a normalized adjacency JSON.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+use serde_json::{Map, Value, json};
+
+use crate::gts::GtsID;
+use crate::references::{GtsReferenceKind, chain_parent};
+use crate::store::GtsStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GtsGraphNodeKind {
+    Schema,
+    Instance,
+    /// Wildcard `x-gts-ref` target, e.g. `gts.x.core.*`.
+    Pattern,
+    /// Referenced but not in the store.
+    Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GtsGraphEdgeKind {
+    /// Derived schema to the parent schema of its chained ID.
+    Inherits,
+    /// `$ref` to another GTS schema.
+    Ref,
+    /// `x-gts-ref` constraint on a property.
+    XGtsRef,
+    /// Instance to its schema.
+    InstanceOf,
+}
+
+impl GtsGraphNodeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            GtsGraphNodeKind::Schema => "schema",
+            GtsGraphNodeKind::Instance => "instance",
+            GtsGraphNodeKind::Pattern => "pattern",
+            GtsGraphNodeKind::Missing => "missing",
+        }
+    }
+}
+
+impl GtsGraphEdgeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            GtsGraphEdgeKind::Inherits => "inherits",
+            GtsGraphEdgeKind::Ref => "$ref",
+            GtsGraphEdgeKind::XGtsRef => "x-gts-ref",
+            GtsGraphEdgeKind::InstanceOf => "instance-of",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GtsGraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: GtsGraphEdgeKind,
+}
+
+/// Entities and their relations, ordered by ID for stable output.
+#[derive(Debug, Clone, Default)]
+pub struct GtsGraph {
+    pub nodes: BTreeMap<String, GtsGraphNodeKind>,
+    pub edges: BTreeSet<GtsGraphEdge>,
+}
+
+/// Quotes `s` as a DOT or Mermaid string.
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+impl GtsGraph {
+    fn add_edge(&mut self, from: &str, to: &str, kind: GtsGraphEdgeKind) {
+        if from != to {
+            self.edges.insert(GtsGraphEdge {
+                from: from.to_owned(),
+                to: to.to_owned(),
+                kind,
+            });
+        }
+    }
+
+    /// Renders the graph in Graphviz DOT syntax.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph gts {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (id, kind) in &self.nodes {
+            let attrs = match kind {
+                GtsGraphNodeKind::Schema => "",
+                GtsGraphNodeKind::Instance => " [shape=ellipse]",
+                GtsGraphNodeKind::Pattern => " [shape=note]",
+                GtsGraphNodeKind::Missing => " [color=red, fontcolor=red]",
+            };
+            let _ = writeln!(out, "    {}{attrs};", quoted(id));
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                GtsGraphEdgeKind::Inherits => ", arrowhead=empty",
+                GtsGraphEdgeKind::Ref => "",
+                GtsGraphEdgeKind::XGtsRef => ", style=dashed",
+                GtsGraphEdgeKind::InstanceOf => ", style=dotted",
+            };
+            let _ = writeln!(
+                out,
+                "    {} -> {} [label={}{style}];",
+                quoted(&edge.from),
+                quoted(&edge.to),
+                quoted(edge.kind.as_str())
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as a Mermaid flowchart. Nodes are named `n0`,
+    /// `n1`, ... in ID order and labelled with their IDs.
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let names: BTreeMap<&str, String> = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), format!("n{i}")))
+            .collect();
+        let mut out = String::from("graph LR\n");
+        for (id, kind) in &self.nodes {
+            let name = &names[id.as_str()];
+            let label = quoted(id);
+            let _ = match kind {
+                GtsGraphNodeKind::Instance => writeln!(out, "    {name}({label})"),
+                GtsGraphNodeKind::Pattern => writeln!(out, "    {name}[/{label}/]"),
+                GtsGraphNodeKind::Schema | GtsGraphNodeKind::Missing => {
+                    writeln!(out, "    {name}[{label}]")
+                }
+            };
+        }
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                GtsGraphEdgeKind::XGtsRef | GtsGraphEdgeKind::InstanceOf => "-.->",
+                GtsGraphEdgeKind::Inherits | GtsGraphEdgeKind::Ref => "-->",
+            };
+            let _ = writeln!(
+                out,
+                "    {} {arrow}|{}| {}",
+                names[edge.from.as_str()],
+                quoted(edge.kind.as_str()),
+                names[edge.to.as_str()]
+            );
+        }
+        let missing: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|(_, kind)| **kind == GtsGraphNodeKind::Missing)
+            .map(|(id, _)| names[id.as_str()].as_str())
+            .collect();
+        if !missing.is_empty() {
+            out.push_str("    classDef missing stroke:#d00,color:#d00\n");
+            let _ = writeln!(out, "    class {} missing", missing.join(","));
+        }
+        out
+    }
+
+    /// Renders the graph as adjacency JSON:
+    /// `{"nodes": {id: {"kind": ..., "edges": [{"to": ..., "kind": ...}]}}}`.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let mut nodes: Map<String, Value> = self
+            .nodes
+            .iter()
+            .map(|(id, kind)| (id.clone(), json!({"kind": kind.as_str(), "edges": []})))
+            .collect();
+        for edge in &self.edges {
+            if let Some(Value::Array(edges)) =
+                nodes.get_mut(&edge.from).and_then(|n| n.get_mut("edges"))
+            {
+                edges.push(json!({"to": edge.to, "kind": edge.kind.as_str()}));
+            }
+        }
+        json!({ "nodes": nodes })
+    }
+}
+
+impl GtsStore {
+    /// Builds the dependency graph of every schema in the store, and of
+    /// every instance when `include_instances` is set.
+    ///
+    /// Targets that are not in the store appear as `Missing` nodes (or
+    /// `Pattern` nodes for wildcard `x-gts-ref`s). A `$ref` to the parent of
+    /// a chained schema ID is reported as inheritance only.
+    #[must_use]
+    pub fn dependency_graph(&self, include_instances: bool) -> GtsGraph {
+        let mut graph = GtsGraph::default();
+        let mut targets = Vec::new();
+        let mut resolved = HashMap::new();
+        for (id, entity) in self.items() {
+            let is_schema = entity.is_schema;
+            if is_schema {
+                graph.nodes.insert(id.clone(), GtsGraphNodeKind::Schema);
+            } else if include_instances {
+                graph.nodes.insert(id.clone(), GtsGraphNodeKind::Instance);
+            } else {
+                continue;
+            }
+            let parent = chain_parent(id);
+            for r in self.entity_references(id, entity, &mut resolved) {
+                let kind = match r.kind {
+                    GtsReferenceKind::Parent => GtsGraphEdgeKind::Inherits,
+                    GtsReferenceKind::Ref if Some(r.target.as_str()) == parent => continue,
+                    GtsReferenceKind::Ref => GtsGraphEdgeKind::Ref,
+                    GtsReferenceKind::XGtsRef if is_schema => GtsGraphEdgeKind::XGtsRef,
+                    GtsReferenceKind::Schema => GtsGraphEdgeKind::InstanceOf,
+                    GtsReferenceKind::XGtsRef | GtsReferenceKind::Content => continue,
+                };
+                graph.add_edge(id, &r.target, kind);
+            }
+        }
+
+        for edge in &graph.edges {
+            if !graph.nodes.contains_key(&edge.to) {
+                let kind = if self.entities().contains_key(&edge.to) {
+                    // An instance referenced while instances are left out
+                    GtsGraphNodeKind::Instance
+                } else if edge.to.contains('*') || !GtsID::is_valid(&edge.to) {
+                    GtsGraphNodeKind::Pattern
+                } else {
+                    GtsGraphNodeKind::Missing
+                };
+                targets.push((edge.to.clone(), kind));
+            }
+        }
+        graph.nodes.extend(targets);
+        graph
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "gts.x.test.graph.event.v1~";
+    const DERIVED: &str = "gts.x.test.graph.event.v1~x.test.graph.placed.v1~";
+    const USER: &str = "gts.x.test.graph.user.v1~";
+    const GHOST: &str = "gts.x.test.graph.ghost.v1~";
+
+    fn graph_store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        let schemas = [
+            (
+                BASE,
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "x-gts-ref": "/$id"},
+                        "source": {"type": "string", "x-gts-ref": "gts.x.test.graph.*"}
+                    }
+                }),
+            ),
+            (
+                DERIVED,
+                json!({"allOf": [
+                    {"$ref": format!("gts://{BASE}")},
+                    {"properties": {
+                        "user": {"$ref": format!("gts://{USER}")},
+                        "audit": {"$ref": format!("gts://{GHOST}#/definitions/x")}
+                    }}
+                ]}),
+            ),
+            (USER, json!({"type": "object"})),
+        ];
+        for (id, body) in schemas {
+            let mut schema = json!({
+                "$id": format!("gts://{id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+            });
+            schema
+                .as_object_mut()
+                .unwrap()
+                .extend(body.as_object().unwrap().clone());
+            store.register_schema(id, &schema).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_dependency_graph_edges() {
+        let graph = graph_store().dependency_graph(false);
+        let edges: Vec<(&str, &str, GtsGraphEdgeKind)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                (BASE, "gts.x.test.graph.*", GtsGraphEdgeKind::XGtsRef),
+                (DERIVED, BASE, GtsGraphEdgeKind::Inherits),
+                (DERIVED, GHOST, GtsGraphEdgeKind::Ref),
+                (DERIVED, USER, GtsGraphEdgeKind::Ref),
+            ]
+        );
+        assert_eq!(graph.nodes[GHOST], GtsGraphNodeKind::Missing);
+        assert_eq!(graph.nodes["gts.x.test.graph.*"], GtsGraphNodeKind::Pattern);
+        assert_eq!(graph.nodes[USER], GtsGraphNodeKind::Schema);
+    }
+
+    #[test]
+    fn test_dependency_graph_renderings() {
+        let graph = graph_store().dependency_graph(false);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph gts {"));
+        assert!(dot.contains(&format!(
+            "\"{DERIVED}\" -> \"{BASE}\" [label=\"inherits\", arrowhead=empty];"
+        )));
+        assert!(dot.contains(&format!("\"{GHOST}\" [color=red, fontcolor=red];")));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("graph LR\n"));
+        // Nodes are numbered in ID order: pattern, BASE, DERIVED, ghost, user
+        assert!(mermaid.contains("    n2 -->|\"inherits\"| n1\n"));
+        assert!(mermaid.contains("    class n3 missing\n"));
+
+        let json = graph.to_json();
+        assert_eq!(json["nodes"][USER], json!({"kind": "schema", "edges": []}));
+        assert_eq!(
+            json["nodes"][DERIVED]["edges"][0],
+            json!({"to": BASE, "kind": "inherits"})
+        );
+    }
+}
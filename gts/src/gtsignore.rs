@@ -0,0 +1,169 @@
+//! `.gtsignore` files: what [`GtsFileReader`](crate::GtsFileReader) skips
+//! when scanning a directory.
+//!
+//! A `.gtsignore` at the root of a scanned directory (or of a glob's fixed
+//! part) lists glob patterns in the style of `.gitignore`, one per line:
+//!
+//! ```text
+//! # generated code and vendored bundles
+//! generated/
+//! /vendor/**/legacy
+//! *.gen.json
+//! !keep.gen.json
+//! ```
+//!
+//! - A pattern without a `/` matches a file or directory name at any depth;
+//!   one with a `/` matches paths from the `.gtsignore`'s directory.
+//! - A trailing `/` matches directories only.
+//! - A leading `!` includes again what an earlier pattern ignored; the last
+//!   matching pattern wins.
+//! - Everything under an ignored directory is ignored.
+
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::Path;
+
+/// Name of the ignore file looked up at the root of scanned directories.
+pub const GTS_IGNORE_FILE: &str = ".gtsignore";
+
+/// `*` and `?` never match a `/`; only `**` crosses directories.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// What a pattern is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// A single file or directory name, at any depth.
+    Name,
+    /// A path from the `.gtsignore`'s directory.
+    Path,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    scope: Scope,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl IgnoreRule {
+    /// Whether the rule matches the path of `components`, or one of the
+    /// directories it is in.
+    fn matches(&self, components: &[String], is_dir: bool) -> bool {
+        let dirs = if is_dir {
+            components.len()
+        } else {
+            components.len().saturating_sub(1)
+        };
+        (1..=components.len()).any(|len| {
+            if self.dir_only && len > dirs {
+                return false;
+            }
+            if self.scope == Scope::Path {
+                self.pattern
+                    .matches_with(&components[..len].join("/"), MATCH_OPTIONS)
+            } else {
+                self.pattern
+                    .matches_with(&components[len - 1], MATCH_OPTIONS)
+            }
+        })
+    }
+}
+
+/// The patterns of one `.gtsignore` file.
+#[derive(Debug, Clone, Default)]
+pub struct GtsIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+impl GtsIgnore {
+    /// The patterns of `dir/.gtsignore`; none if there is no such file.
+    #[must_use]
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(GTS_IGNORE_FILE))
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    /// The patterns of a `.gtsignore` file's `text`; invalid ones are
+    /// skipped with a warning.
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let rules = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let scope = if line.contains('/') {
+                    Scope::Path
+                } else {
+                    Scope::Name
+                };
+                let line = line.trim_start_matches('/');
+                match Pattern::new(line) {
+                    Ok(pattern) => Some(IgnoreRule {
+                        pattern,
+                        scope,
+                        dir_only,
+                        negated,
+                    }),
+                    Err(e) => {
+                        tracing::warn!("Skipping {GTS_IGNORE_FILE} pattern '{line}': {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        GtsIgnore { rules }
+    }
+
+    /// Whether `path`, relative to the `.gtsignore`'s directory, is ignored.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let components: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&components, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_names_paths_and_directories() {
+        let ignore = GtsIgnore::parse(
+            "# comment\n\ngenerated/\n/vendor/**/legacy\n*.gen.json\n!keep.gen.json\n[\n",
+        );
+        let ignored = |path: &str, is_dir: bool| ignore.is_ignored(Path::new(path), is_dir);
+
+        assert!(ignored("generated", true));
+        assert!(ignored("a/generated/x.json", false));
+        assert!(!ignored("generated", false));
+        assert!(ignored("vendor/legacy", true));
+        assert!(ignored("vendor/acme/v1/legacy/x.json", false));
+        assert!(!ignored("src/vendor/legacy/x.json", false));
+        assert!(ignored("deep/dir/types.gen.json", false));
+        assert!(!ignored("deep/dir/keep.gen.json", false));
+        assert!(!ignored("schemas/order.v1~.schema.json", false));
+        assert!(!GtsIgnore::default().is_ignored(Path::new("x.json"), false));
+    }
+}
@@ -0,0 +1,372 @@
+//! [`GtsReader`] backed by a remote GTS registry over HTTP.
+//!
+//! The registry is expected to speak the `gts server` API:
+//!
+//! - `GET {base}/entities?limit=N` lists entity IDs
+//! - `GET {base}/entities/{id}` returns `{"ok": true, "content": {...}}`
+//!
+//! Transport errors, `429` and `5xx` responses are retried with exponential
+//! backoff; any other non-success status is treated as "not found".
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::thread;
+use std::time::Duration;
+
+use crate::entities::{GtsConfig, GtsEntity};
+use crate::store::GtsReader;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(200);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_LIST_LIMIT: usize = 100_000;
+
+#[derive(Deserialize)]
+struct ListResponse {
+    entities: Vec<ListEntry>,
+}
+
+#[derive(Deserialize)]
+struct ListEntry {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct EntityResponse {
+    #[serde(default)]
+    ok: bool,
+    content: Option<Value>,
+}
+
+enum Attempt {
+    Retry,
+    Done(Option<String>),
+}
+
+/// Reads schemas and instances from a remote GTS registry.
+pub struct HttpGtsReader {
+    base_url: String,
+    auth_header: Option<(String, String)>,
+    max_retries: u32,
+    backoff: Duration,
+    list_limit: usize,
+    cfg: GtsConfig,
+    agent: ureq::Agent,
+}
+
+impl HttpGtsReader {
+    /// Creates a reader for the registry at `base_url` (e.g. `https://registry.example.com`).
+    #[must_use]
+    pub fn new(base_url: &str) -> Self {
+        HttpGtsReader {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            auth_header: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            list_limit: DEFAULT_LIST_LIMIT,
+            cfg: GtsConfig::default(),
+            agent: build_agent(DEFAULT_TIMEOUT),
+        }
+    }
+
+    /// Sends `name: value` with every request, e.g. `("Authorization", "Bearer ...")`.
+    #[must_use]
+    pub fn with_auth_header(mut self, name: &str, value: &str) -> Self {
+        self.auth_header = Some((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Retries failed requests up to `max_retries` times, waiting
+    /// `backoff * 2^attempt` between attempts.
+    #[must_use]
+    pub fn with_retry(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the overall per-request timeout.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.agent = build_agent(timeout);
+        self
+    }
+
+    /// Sets the maximum number of entities requested when listing the registry.
+    #[must_use]
+    pub fn with_list_limit(mut self, limit: usize) -> Self {
+        self.list_limit = limit;
+        self
+    }
+
+    /// Sets the config used to extract IDs from fetched entities.
+    #[must_use]
+    pub fn with_config(mut self, cfg: GtsConfig) -> Self {
+        self.cfg = cfg;
+        self
+    }
+
+    #[must_use]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// GETs `path` and parses the body as JSON. Returns `None` once retries are
+    /// exhausted or the registry answers with a non-retryable status.
+    fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Option<T> {
+        let url = format!("{}{path}", self.base_url);
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                let delay = self
+                    .backoff
+                    .saturating_mul(2u32.saturating_pow(attempt - 1));
+                tracing::debug!("HttpGtsReader: retry {attempt} for {url} in {delay:?}");
+                thread::sleep(delay);
+            }
+            match self.try_get(&url) {
+                Attempt::Retry => {}
+                Attempt::Done(body) => {
+                    return serde_json::from_str(&body?)
+                        .map_err(|e| tracing::warn!("HttpGtsReader: invalid JSON from {url}: {e}"))
+                        .ok();
+                }
+            }
+        }
+        None
+    }
+
+    fn try_get(&self, url: &str) -> Attempt {
+        let mut request = self.agent.get(url);
+        if let Some((name, value)) = &self.auth_header {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let mut response = match request.call() {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("HttpGtsReader: request to {url} failed: {e}");
+                return Attempt::Retry;
+            }
+        };
+        let status = response.status().as_u16();
+        if status == 429 || status >= 500 {
+            tracing::warn!("HttpGtsReader: {url} returned {status}");
+            return Attempt::Retry;
+        }
+        if !(200..300).contains(&status) {
+            tracing::debug!("HttpGtsReader: {url} returned {status}");
+            return Attempt::Done(None);
+        }
+        Attempt::Done(response.body_mut().read_to_string().ok())
+    }
+
+    fn fetch_entity(&self, entity_id: &str) -> Option<GtsEntity> {
+        let response: EntityResponse = self.get_json(&format!("/entities/{entity_id}"))?;
+        if !response.ok {
+            return None;
+        }
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &response.content?,
+            Some(&self.cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        entity.effective_id().is_some().then_some(entity)
+    }
+}
+
+fn build_agent(timeout: Duration) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+impl GtsReader for HttpGtsReader {
+    fn iter(&mut self) -> Box<dyn Iterator<Item = GtsEntity> + '_> {
+        let Some(list) =
+            self.get_json::<ListResponse>(&format!("/entities?limit={}", self.list_limit))
+        else {
+            tracing::warn!(
+                "HttpGtsReader: could not list entities at {}",
+                self.base_url
+            );
+            return Box::new(std::iter::empty());
+        };
+        tracing::debug!(
+            "HttpGtsReader: fetching {} entities from {}",
+            list.entities.len(),
+            self.base_url
+        );
+        Box::new(
+            list.entities
+                .into_iter()
+                .filter_map(move |entry| self.fetch_entity(&entry.id)),
+        )
+    }
+
+    fn read_by_id(&self, entity_id: &str) -> Option<GtsEntity> {
+        self.fetch_entity(entity_id)
+    }
+
+    fn reset(&mut self) {
+        // Stateless: every iteration lists the registry again.
+    }
+
+    /// One listing request, without retries.
+    fn check(&self) -> Result<(), String> {
+        match self.try_get(&format!("{}/entities?limit=1", self.base_url)) {
+            Attempt::Done(Some(_)) => Ok(()),
+            Attempt::Done(None) | Attempt::Retry => {
+                Err(format!("registry at {} is not answering", self.base_url))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::store::GtsStore;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    type Handler = dyn Fn(&str, &[String]) -> (u16, String) + Send + Sync;
+
+    /// Minimal HTTP/1.1 server answering each request via `handler(path, headers)`.
+    fn serve(handler: Box<Handler>) -> (String, Arc<Mutex<usize>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&hits);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(line.trim().to_lowercase());
+                }
+                *counter.lock().unwrap() += 1;
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = handler(path, &headers);
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, hits)
+    }
+
+    const SCHEMA_ID: &str = "gts.x.test.http.item.v1~";
+
+    fn schema_body() -> String {
+        json!({
+            "ok": true,
+            "id": SCHEMA_ID,
+            "is_schema": true,
+            "content": {
+                "$id": format!("gts://{SCHEMA_ID}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            }
+        })
+        .to_string()
+    }
+
+    fn registry(path: &str, _headers: &[String]) -> (u16, String) {
+        if path.starts_with("/entities?") {
+            let list = json!({"entities": [{"id": SCHEMA_ID, "schema_id": null, "is_schema": true}], "count": 1, "total": 1});
+            (200, list.to_string())
+        } else if path == format!("/entities/{SCHEMA_ID}") {
+            (200, schema_body())
+        } else {
+            (200, json!({"ok": false, "error": "not found"}).to_string())
+        }
+    }
+
+    #[test]
+    fn test_read_by_id() {
+        let (url, _) = serve(Box::new(registry));
+        let reader = HttpGtsReader::new(&format!("{url}/"));
+        assert_eq!(reader.base_url(), url);
+
+        let entity = reader.read_by_id(SCHEMA_ID).expect("entity");
+        assert!(entity.is_schema);
+        assert_eq!(entity.effective_id().as_deref(), Some(SCHEMA_ID));
+        assert!(reader.read_by_id("gts.x.test.http.missing.v1~").is_none());
+    }
+
+    #[test]
+    fn test_store_populates_from_registry() {
+        let (url, _) = serve(Box::new(registry));
+        let reader = HttpGtsReader::new(&url);
+        let mut store = GtsStore::new(Some(Box::new(reader)));
+        assert_eq!(store.items().count(), 1);
+        assert!(store.get(SCHEMA_ID).is_some());
+        assert!(store.loaded_at().is_some());
+        assert_eq!(store.check_reader(), Some(Ok(())));
+    }
+
+    #[test]
+    fn test_auth_header_is_sent() {
+        let (url, _) = serve(Box::new(|path, headers| {
+            if headers.iter().any(|h| h == "authorization: bearer secret") {
+                registry(path, headers)
+            } else {
+                (401, "{}".to_owned())
+            }
+        }));
+        let anonymous = HttpGtsReader::new(&url).with_retry(0, Duration::ZERO);
+        assert!(anonymous.read_by_id(SCHEMA_ID).is_none());
+
+        let authed = HttpGtsReader::new(&url).with_auth_header("Authorization", "Bearer secret");
+        assert!(authed.read_by_id(SCHEMA_ID).is_some());
+    }
+
+    #[test]
+    fn test_retries_server_errors() {
+        let failures = Arc::new(Mutex::new(2));
+        let remaining = Arc::clone(&failures);
+        let (url, hits) = serve(Box::new(move |path, headers| {
+            let mut remaining = remaining.lock().unwrap();
+            if *remaining > 0 {
+                *remaining -= 1;
+                return (503, "{}".to_owned());
+            }
+            registry(path, headers)
+        }));
+
+        let reader = HttpGtsReader::new(&url).with_retry(3, Duration::from_millis(1));
+        assert!(reader.read_by_id(SCHEMA_ID).is_some());
+        assert_eq!(*hits.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_retries() {
+        let (url, hits) = serve(Box::new(|_, _| (500, "{}".to_owned())));
+        let reader = HttpGtsReader::new(&url).with_retry(2, Duration::from_millis(1));
+        assert!(reader.read_by_id(SCHEMA_ID).is_none());
+        assert_eq!(*hits.lock().unwrap(), 3);
+        // Health checks are not retried
+        assert!(reader.check().is_err());
+        assert_eq!(*hits.lock().unwrap(), 4);
+    }
+}
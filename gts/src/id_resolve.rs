@@ -0,0 +1,140 @@
+//! Short forms of entity IDs for interactive input.
+//!
+//! Chained IDs are long and easy to mistype, so user-facing tools accept two
+//! abbreviations and expand them with [`GtsStore::resolve_id`]:
+//!
+//! - `@name` looks `name` up in a configured alias table
+//!   (`"aliases": {"order-placed": "gts.x.shop.orders.order_placed.v1~"}`);
+//! - any other input that is not a stored ID is matched as a suffix of the
+//!   stored IDs, at a segment boundary: `orders.order_placed.v1~` expands to
+//!   `gts.x.shop.orders.order_placed.v1~` when no other ID ends that way.
+
+use std::collections::BTreeMap;
+
+use crate::gts::GTS_PREFIX;
+use crate::store::{GtsStore, StoreError};
+
+/// Whether `id` ends with `suffix` and the suffix starts a segment or token.
+fn has_suffix(id: &str, suffix: &str) -> bool {
+    id.len() > suffix.len()
+        && id.ends_with(suffix)
+        && id[..id.len() - suffix.len()].ends_with(['.', '~'])
+}
+
+impl GtsStore {
+    /// Expands an alias or an ID suffix to a full entity ID.
+    ///
+    /// Stored IDs and full GTS IDs are returned unchanged, as is input that
+    /// matches nothing, so that callers report their usual "not found" errors.
+    ///
+    /// # Errors
+    /// Returns `StoreError::UnknownAlias` for an `@name` missing from
+    /// `aliases`, or `StoreError::AmbiguousId` with the sorted candidates when
+    /// several stored IDs end with `input`.
+    pub fn resolve_id(
+        &self,
+        input: &str,
+        aliases: &BTreeMap<String, String>,
+    ) -> Result<String, StoreError> {
+        let input = match input.strip_prefix('@') {
+            Some(name) => aliases
+                .get(name)
+                .ok_or_else(|| StoreError::UnknownAlias(name.to_owned()))?,
+            None => input,
+        };
+        if input.starts_with(GTS_PREFIX) || self.entities().contains_key(input) {
+            return Ok(input.to_owned());
+        }
+
+        let mut candidates: Vec<String> = self
+            .entities()
+            .keys()
+            .filter(|id| has_suffix(id, input))
+            .cloned()
+            .collect();
+        match candidates.len() {
+            0 => Ok(input.to_owned()),
+            1 => Ok(candidates.remove(0)),
+            _ => {
+                candidates.sort_unstable();
+                Err(StoreError::AmbiguousId {
+                    input: input.to_owned(),
+                    candidates,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PLACED: &str = "gts.x.core.events.event.v1~x.shop.orders.order_placed.v1~";
+    const SHIPPED: &str = "gts.x.core.events.event.v1~x.shop.orders.order_shipped.v1~";
+    const LEGACY: &str = "gts.x.legacy.orders.order_placed.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in [PLACED, SHIPPED, LEGACY] {
+            let schema = json!({
+                "$id": format!("gts://{id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            });
+            store.register_schema(id, &schema).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_suffix_resolution() {
+        let store = store();
+        let aliases = BTreeMap::new();
+        assert_eq!(
+            store
+                .resolve_id("x.shop.orders.order_placed.v1~", &aliases)
+                .unwrap(),
+            PLACED
+        );
+        assert_eq!(
+            store.resolve_id("order_shipped.v1~", &aliases).unwrap(),
+            SHIPPED
+        );
+        // Suffixes must start at a segment boundary
+        assert_eq!(
+            store.resolve_id("der_shipped.v1~", &aliases).unwrap(),
+            "der_shipped.v1~"
+        );
+        // Full IDs pass through, even when not stored
+        let unknown = "gts.x.shop.orders.unknown.v1~";
+        assert_eq!(store.resolve_id(unknown, &aliases).unwrap(), unknown);
+
+        match store.resolve_id("orders.order_placed.v1~", &aliases) {
+            Err(StoreError::AmbiguousId { candidates, .. }) => {
+                assert_eq!(candidates, vec![PLACED.to_owned(), LEGACY.to_owned()]);
+            }
+            other => panic!("expected an ambiguity error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_alias_resolution() {
+        let store = store();
+        let aliases = BTreeMap::from([
+            ("order-placed".to_owned(), PLACED.to_owned()),
+            (
+                "legacy".to_owned(),
+                "legacy.orders.order_placed.v1~".to_owned(),
+            ),
+        ]);
+        assert_eq!(store.resolve_id("@order-placed", &aliases).unwrap(), PLACED);
+        assert_eq!(store.resolve_id("@legacy", &aliases).unwrap(), LEGACY);
+
+        let err = store.resolve_id("@nope", &aliases).unwrap_err();
+        assert!(matches!(err, StoreError::UnknownAlias(ref name) if name == "nope"));
+        assert_eq!(err.to_string(), "Unknown ID alias '@nope'");
+    }
+}
@@ -0,0 +1,437 @@
+//! Import of `OpenAPI` and plain JSON Schema documents.
+//!
+//! [`GtsStore::import_json_schemas`] is the reverse of
+//! [`GtsStore::export_openapi`]: every named schema of the document becomes a
+//! GTS schema `gts.<vendor>.<package>.<namespace>.<name>.v1~`, local `$ref`s
+//! between them are rewritten to `gts://` references, and the result is
+//! registered all-or-nothing.
+//!
+//! The named schemas are `components/schemas` of an `OpenAPI` 3 document,
+//! `definitions` of a Swagger 2 one, and `$defs`/`definitions` of a plain
+//! JSON Schema, whose root is imported too, named after its `title`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::gts::{GTS_URI_PREFIX, GtsIdBuilder};
+use crate::store::{GtsStore, StoreError};
+
+/// `$schema` given to schemas imported from documents that do not name one.
+const DEFAULT_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+/// `$schema` of schemas imported from `OpenAPI` 3.1 documents.
+const OPENAPI_31_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Name the root of a plain JSON Schema is imported under if it has no
+/// `title`.
+const ROOT_NAME: &str = "root";
+
+/// One schema created by an import.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsImportedSchema {
+    /// Name of the schema in the source document.
+    pub name: String,
+    pub id: String,
+}
+
+/// Outcome of [`GtsStore::import_json_schemas`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GtsImportReport {
+    pub schemas: Vec<GtsImportedSchema>,
+    /// References left as they were because they point outside the
+    /// imported schemas.
+    pub warnings: Vec<String>,
+}
+
+/// The GTS type name for a source schema name: `OrderItem` and
+/// `order-item` both become `order_item`.
+fn type_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+            prev_lower = true;
+        } else {
+            out.push('_');
+            prev_lower = false;
+        }
+    }
+    let mut collapsed = String::with_capacity(out.len());
+    for c in out.chars() {
+        if !(c == '_' && collapsed.ends_with('_')) {
+            collapsed.push(c);
+        }
+    }
+    let token = collapsed.trim_matches('_');
+    if token.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{token}")
+    } else {
+        token.to_owned()
+    }
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// The named schemas of a document, where local references to them point,
+/// and whether `OpenAPI` 3.0 `nullable` needs translating.
+struct Source {
+    schemas: Vec<(String, Value)>,
+    /// Pointer prefixes, such as `#/components/schemas/`, followed by a
+    /// schema name.
+    prefixes: &'static [&'static str],
+    dialect: String,
+    /// Name of the plain JSON Schema root, which `#` refers to.
+    root: Option<String>,
+    openapi_30: bool,
+}
+
+fn named(section: Option<&Value>) -> Vec<(String, Value)> {
+    section
+        .and_then(Value::as_object)
+        .map(|map| map.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default()
+}
+
+fn source(doc: &Value) -> Source {
+    if let Some(version) = doc.get("openapi").and_then(Value::as_str) {
+        let openapi_30 = !version.starts_with("3.1");
+        return Source {
+            schemas: named(doc.pointer("/components/schemas")),
+            prefixes: &["#/components/schemas/"],
+            dialect: if openapi_30 {
+                DEFAULT_DIALECT
+            } else {
+                OPENAPI_31_DIALECT
+            }
+            .to_owned(),
+            root: None,
+            openapi_30,
+        };
+    }
+    if doc.get("swagger").is_some() {
+        return Source {
+            schemas: named(doc.get("definitions")),
+            prefixes: &["#/definitions/"],
+            dialect: DEFAULT_DIALECT.to_owned(),
+            root: None,
+            openapi_30: true,
+        };
+    }
+
+    let mut schemas = named(doc.get("$defs"));
+    schemas.extend(named(doc.get("definitions")));
+    let mut root = doc.clone();
+    let mut root_name = None;
+    if let Some(map) = root.as_object_mut() {
+        map.remove("$defs");
+        map.remove("definitions");
+        map.remove("$id");
+        map.remove("$schema");
+        if !map.is_empty() {
+            let name = doc
+                .get("title")
+                .and_then(Value::as_str)
+                .unwrap_or(ROOT_NAME)
+                .to_owned();
+            schemas.push((name.clone(), root));
+            root_name = Some(name);
+        }
+    }
+    Source {
+        schemas,
+        prefixes: &["#/$defs/", "#/definitions/"],
+        dialect: doc
+            .get("$schema")
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_DIALECT)
+            .to_owned(),
+        root: root_name,
+        openapi_30: false,
+    }
+}
+
+struct Rewriter<'a> {
+    source: &'a Source,
+    ids: &'a BTreeMap<String, String>,
+    warnings: BTreeSet<String>,
+}
+
+impl Rewriter<'_> {
+    /// The `gts://` form of a local reference, if it points into an
+    /// imported schema. `current` is the name of the schema holding it.
+    fn map_ref(&self, target: &str, current: &str) -> Option<String> {
+        for prefix in self.source.prefixes {
+            if let Some(rest) = target.strip_prefix(prefix) {
+                let (name, pointer) = rest
+                    .split_once('/')
+                    .map_or((rest, None), |(n, p)| (n, Some(p)));
+                let id = self.ids.get(&unescape_pointer_token(name))?;
+                return Some(match pointer {
+                    Some(pointer) => format!("{GTS_URI_PREFIX}{id}#/{pointer}"),
+                    None => format!("{GTS_URI_PREFIX}{id}"),
+                });
+            }
+        }
+        // Anything else local points into the plain JSON Schema root
+        let root = self.source.root.as_deref()?;
+        let pointer = target.strip_prefix('#')?;
+        if current == root && !pointer.is_empty() {
+            return Some(target.to_owned());
+        }
+        let id = self.ids.get(root)?;
+        Some(format!(
+            "{GTS_URI_PREFIX}{id}{}",
+            if pointer.is_empty() { "" } else { target }
+        ))
+    }
+
+    fn rewrite(&mut self, value: &mut Value, current: &str) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get_mut("$ref") {
+                    if let Some(mapped) = self.map_ref(target, current) {
+                        *target = mapped;
+                    } else {
+                        self.warnings
+                            .insert(format!("schema '{current}': unresolved $ref '{target}'"));
+                    }
+                }
+                if self.source.openapi_30 {
+                    translate_nullable(map);
+                }
+                for v in map.values_mut() {
+                    self.rewrite(v, current);
+                }
+            }
+            Value::Array(items) => {
+                for v in items {
+                    self.rewrite(v, current);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `OpenAPI` 3.0 `nullable: true` becomes a `"null"` type.
+fn translate_nullable(map: &mut Map<String, Value>) {
+    if map.get("nullable") != Some(&Value::Bool(true)) {
+        return;
+    }
+    map.remove("nullable");
+    match map.get_mut("type") {
+        Some(Value::String(ty)) => {
+            let ty = std::mem::take(ty);
+            map.insert("type".to_owned(), Value::from(vec![ty, "null".to_owned()]));
+        }
+        Some(Value::Array(types)) if !types.contains(&Value::from("null")) => {
+            types.push(Value::from("null"));
+        }
+        _ => {}
+    }
+}
+
+impl GtsStore {
+    /// Imports the named schemas of an `OpenAPI` or plain JSON Schema
+    /// document as `gts.<vendor>.<package>.<namespace>.<name>.v1~`.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if the document has no schemas,
+    /// a part would make an invalid GTS ID, or two schema names map to the
+    /// same ID; or any registration error, in which case nothing is
+    /// registered.
+    pub fn import_json_schemas(
+        &mut self,
+        doc: &Value,
+        vendor: &str,
+        package: &str,
+        namespace: &str,
+    ) -> Result<GtsImportReport, StoreError> {
+        self.import_json_schemas_as(None, doc, vendor, package, namespace)
+    }
+
+    /// [`GtsStore::import_json_schemas`] on behalf of `principal`.
+    ///
+    /// # Errors
+    /// As [`GtsStore::import_json_schemas`].
+    pub fn import_json_schemas_as(
+        &mut self,
+        principal: Option<&str>,
+        doc: &Value,
+        vendor: &str,
+        package: &str,
+        namespace: &str,
+    ) -> Result<GtsImportReport, StoreError> {
+        let source = source(doc);
+        if source.schemas.is_empty() {
+            return Err(StoreError::ValidationError(
+                "document has no schemas to import".to_owned(),
+            ));
+        }
+
+        let mut ids = BTreeMap::new();
+        let mut taken = BTreeMap::new();
+        for (name, _) in &source.schemas {
+            let id = GtsIdBuilder::new()
+                .vendor(vendor)
+                .package(package)
+                .namespace(namespace)
+                .type_name(&type_name(name))
+                .version(1, None)
+                .schema()
+                .map_err(|e| StoreError::ValidationError(format!("schema '{name}': {e}")))?
+                .id;
+            if let Some(other) = taken.insert(id.clone(), name.clone()) {
+                return Err(StoreError::ValidationError(format!(
+                    "schemas '{other}' and '{name}' both map to '{id}'"
+                )));
+            }
+            ids.insert(name.clone(), id);
+        }
+
+        let mut rewriter = Rewriter {
+            source: &source,
+            ids: &ids,
+            warnings: BTreeSet::new(),
+        };
+        let mut schemas = Vec::with_capacity(source.schemas.len());
+        for (name, schema) in &source.schemas {
+            let id = &ids[name];
+            let mut schema = schema.clone();
+            rewriter.rewrite(&mut schema, name);
+            if let Some(map) = schema.as_object_mut() {
+                map.insert(
+                    "$id".to_owned(),
+                    Value::from(format!("{GTS_URI_PREFIX}{id}")),
+                );
+                map.insert("$schema".to_owned(), Value::from(source.dialect.clone()));
+            }
+            schemas.push((name, id, schema));
+        }
+        let warnings = rewriter.warnings.into_iter().collect();
+
+        self.transaction(|tx| {
+            for (_, id, schema) in &schemas {
+                tx.register_schema_as(principal, id, schema)?;
+            }
+            Ok(())
+        })?;
+        Ok(GtsImportReport {
+            schemas: schemas
+                .into_iter()
+                .map(|(name, id, _)| GtsImportedSchema {
+                    name: name.clone(),
+                    id: id.clone(),
+                })
+                .collect(),
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("OrderItem"), "order_item");
+        assert_eq!(type_name("order-item"), "order_item");
+        assert_eq!(type_name("HTTPError"), "httperror");
+        assert_eq!(type_name("2fa Code"), "_2fa_code");
+    }
+
+    #[test]
+    fn test_import_openapi_components() {
+        let doc = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Shop", "version": "1.0.0"},
+            "paths": {},
+            "components": {"schemas": {
+                "Money": {"type": "number"},
+                "OrderItem": {
+                    "type": "object",
+                    "properties": {
+                        "price": {"$ref": "#/components/schemas/Money"},
+                        "note": {"type": "string", "nullable": true},
+                        "tax": {"$ref": "#/components/parameters/Tax"}
+                    }
+                }
+            }}
+        });
+        let mut store = GtsStore::new(None);
+        let report = store.import_json_schemas(&doc, "x", "shop", "_").unwrap();
+        let ids: Vec<_> = report.schemas.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["gts.x.shop._.money.v1~", "gts.x.shop._.order_item.v1~"]
+        );
+        assert_eq!(report.warnings.len(), 1);
+
+        let item = &store.entities()["gts.x.shop._.order_item.v1~"].content;
+        assert_eq!(item["$id"], "gts://gts.x.shop._.order_item.v1~");
+        assert_eq!(
+            item["properties"]["price"]["$ref"],
+            "gts://gts.x.shop._.money.v1~"
+        );
+        assert_eq!(
+            item["properties"]["note"]["type"],
+            json!(["string", "null"])
+        );
+    }
+
+    #[test]
+    fn test_import_plain_json_schema() {
+        let doc = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Customer",
+            "type": "object",
+            "properties": {
+                "address": {"$ref": "#/definitions/Address"},
+                "billing": {"$ref": "#/properties/address"}
+            },
+            "definitions": {
+                "Address": {
+                    "type": "object",
+                    "properties": {"owner": {"$ref": "#"}}
+                }
+            }
+        });
+        let mut store = GtsStore::new(None);
+        store.import_json_schemas(&doc, "x", "crm", "_").unwrap();
+
+        let entities = store.entities();
+        let customer = &entities["gts.x.crm._.customer.v1~"].content;
+        assert!(customer.get("definitions").is_none());
+        assert_eq!(
+            customer["properties"]["address"]["$ref"],
+            "gts://gts.x.crm._.address.v1~"
+        );
+        assert_eq!(
+            customer["properties"]["billing"]["$ref"],
+            "#/properties/address"
+        );
+        let address = &entities["gts.x.crm._.address.v1~"].content;
+        assert_eq!(
+            address["properties"]["owner"]["$ref"],
+            "gts://gts.x.crm._.customer.v1~"
+        );
+
+        assert!(matches!(
+            GtsStore::new(None).import_json_schemas(&doc, "X", "crm", "_"),
+            Err(StoreError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,451 @@
+//! Secondary indexes for [`GtsStore`] queries.
+//!
+//! Two kinds of index narrow the set of entities a query has to look at:
+//!
+//! - **Segment index** (always on): the vendor, vendor.package and
+//!   vendor.package.namespace prefixes of each entity's first ID segment, so
+//!   `gts.vendor.package.*` only visits that package.
+//! - **Field indexes** (opt-in via [`GtsStore::index_field`], or declared by a
+//!   schema's `x-gts-index` hints): JSON fields or dotted paths by value, so
+//!   `[status=active]` only visits matching entities.
+//!
+//! Indexes only ever narrow the candidate set; every candidate is still run
+//! through the full pattern and filter match.  Code that edits the entity map
+//! directly marks the indexes stale, and queries fall back to a full scan
+//! until [`GtsStore::rebuild_indexes`] is called.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::entities::GtsEntity;
+use crate::gts::GTS_PREFIX;
+use crate::store::{FilterCondition, FilterOp, GtsStore, field_value};
+
+/// Number of leading first-segment tokens indexed: vendor, package, namespace.
+const SEGMENT_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsFieldIndexStats {
+    pub field: String,
+    pub distinct_values: usize,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsIndexStats {
+    pub entities: usize,
+    /// Distinct vendor / package / namespace prefixes in the segment index.
+    pub segment_keys: usize,
+    pub fields: Vec<GtsFieldIndexStats>,
+    /// True when the entity map was edited directly and queries are scanning
+    /// until the next rebuild.
+    pub stale: bool,
+    /// Queries answered from index candidates.
+    pub index_hits: u64,
+    /// Queries that had to scan every entity.
+    pub full_scans: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct GtsStoreIndex {
+    segments: HashMap<String, BTreeSet<String>>,
+    fields: BTreeMap<String, HashMap<String, BTreeSet<String>>>,
+    stale: bool,
+    hits: AtomicU64,
+    scans: AtomicU64,
+}
+
+/// Segment index keys of an entity: `vendor`, `vendor.package`, `vendor.package.namespace`.
+fn segment_keys(entity: &GtsEntity) -> Vec<String> {
+    let Some(first) = entity
+        .gts_id
+        .as_ref()
+        .and_then(|g| g.gts_id_segments.first())
+    else {
+        return Vec::new();
+    };
+    let parts = [&first.vendor, &first.package, &first.namespace];
+    (1..=SEGMENT_DEPTH)
+        .map(|n| {
+            parts[..n]
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+        .collect()
+}
+
+/// The most specific segment key implied by a query's base pattern, if any.
+fn pattern_segment_key(base_pattern: &str) -> Option<String> {
+    let rest = base_pattern.strip_prefix(GTS_PREFIX)?;
+    let first_segment = rest.split('~').next()?;
+    let tokens: Vec<&str> = first_segment
+        .split('.')
+        .take_while(|t| !t.is_empty() && !t.contains('*'))
+        .take(SEGMENT_DEPTH)
+        .collect();
+    (!tokens.is_empty()).then(|| tokens.join("."))
+}
+
+impl GtsStoreIndex {
+    pub(crate) fn insert(&mut self, id: &str, entity: &GtsEntity) {
+        for key in segment_keys(entity) {
+            self.segments.entry(key).or_default().insert(id.to_owned());
+        }
+        let Some(obj) = entity.content.as_object() else {
+            return;
+        };
+        for (field, values) in &mut self.fields {
+            if let Some(value) = field_value(obj, field) {
+                values
+                    .entry(value.to_string())
+                    .or_default()
+                    .insert(id.to_owned());
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: &str, entity: &GtsEntity) {
+        for key in segment_keys(entity) {
+            if let Some(ids) = self.segments.get_mut(&key) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.segments.remove(&key);
+                }
+            }
+        }
+        let Some(obj) = entity.content.as_object() else {
+            return;
+        };
+        for (field, values) in &mut self.fields {
+            let Some(key) = field_value(obj, field).map(Value::to_string) else {
+                continue;
+            };
+            if let Some(ids) = values.get_mut(&key) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    values.remove(&key);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    fn rebuild<'a>(&mut self, entities: impl Iterator<Item = (&'a String, &'a GtsEntity)>) {
+        self.segments.clear();
+        for values in self.fields.values_mut() {
+            values.clear();
+        }
+        for (id, entity) in entities {
+            self.insert(id, entity);
+        }
+        self.stale = false;
+    }
+
+    /// IDs of entities an equality condition on an indexed field can match.
+    fn field_candidates(&self, condition: &FilterCondition) -> Option<BTreeSet<String>> {
+        if condition.op != FilterOp::Eq || condition.value == "*" {
+            return None;
+        }
+        let values = self.fields.get(&condition.key)?;
+        // Mirrors the query's equality rule: a string field matches `"value"`,
+        // anything else matches its plain JSON rendering.
+        let quoted = Value::String(condition.value.clone()).to_string();
+        Some(
+            [quoted.as_str(), condition.value.as_str()]
+                .iter()
+                .filter_map(|key| values.get(*key))
+                .flatten()
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Candidate IDs for a query, or `None` when it needs a full scan.
+    pub(crate) fn candidates(
+        &self,
+        base_pattern: &str,
+        filters: &[Vec<FilterCondition>],
+    ) -> Option<BTreeSet<String>> {
+        let by_segment = if self.stale {
+            None
+        } else {
+            pattern_segment_key(base_pattern)
+                .map(|key| self.segments.get(&key).cloned().unwrap_or_default())
+        };
+
+        // Every alternative must be narrowed by some indexed equality, otherwise
+        // the alternative could match anything.
+        let by_field = if self.stale || filters.is_empty() {
+            None
+        } else {
+            filters
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .filter_map(|c| self.field_candidates(c))
+                        .min_by_key(BTreeSet::len)
+                })
+                .try_fold(BTreeSet::new(), |mut acc, group| {
+                    acc.extend(group?);
+                    Some(acc)
+                })
+        };
+
+        let candidates = match (by_segment, by_field) {
+            (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+            (a, b) => a.or(b),
+        };
+        let counter = if candidates.is_some() {
+            &self.hits
+        } else {
+            &self.scans
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        candidates
+    }
+}
+
+impl GtsStore {
+    /// Adds a field index on a JSON field or dotted path (e.g. `status` or
+    /// `payload.user_id`) and builds it.
+    pub fn index_field(&mut self, field: &str) {
+        if self.index().fields.contains_key(field) {
+            return;
+        }
+        self.index_mut()
+            .fields
+            .insert(field.to_owned(), HashMap::new());
+        self.rebuild_indexes();
+    }
+
+    /// Removes a field index. Returns false if the field was not indexed.
+    pub fn drop_field_index(&mut self, field: &str) -> bool {
+        self.index_mut().fields.remove(field).is_some()
+    }
+
+    #[must_use]
+    pub fn indexed_fields(&self) -> Vec<String> {
+        self.index().fields.keys().cloned().collect()
+    }
+
+    /// Adds a field index for every `x-gts-index` hint of the registered
+    /// schemas that is not indexed yet, rebuilding once if any was added.
+    /// Indexes are never dropped here, so an explicit [`GtsStore::index_field`]
+    /// or an earlier schema version's hint stays in place.
+    pub(crate) fn apply_index_hints(&mut self) {
+        let hints: BTreeSet<String> = self
+            .entities()
+            .values()
+            .filter(|e| e.is_schema)
+            .flat_map(GtsEntity::index_hints)
+            .filter(|hint| !self.index().fields.contains_key(hint))
+            .collect();
+        if hints.is_empty() {
+            return;
+        }
+        tracing::debug!("Creating field indexes from x-gts-index hints: {hints:?}");
+        let fields = &mut self.index_mut().fields;
+        for hint in hints {
+            fields.insert(hint, HashMap::new());
+        }
+        self.rebuild_indexes();
+    }
+
+    /// Rebuilds all indexes from the entity map.
+    pub fn rebuild_indexes(&mut self) {
+        let (entities, index) = self.entities_and_index_mut();
+        index.rebuild(entities.iter());
+        tracing::debug!("Rebuilt GtsStore indexes over {} entities", entities.len());
+    }
+
+    #[must_use]
+    pub fn index_stats(&self) -> GtsIndexStats {
+        let index = self.index();
+        GtsIndexStats {
+            entities: self.items().count(),
+            segment_keys: index.segments.len(),
+            fields: index
+                .fields
+                .iter()
+                .map(|(field, values)| GtsFieldIndexStats {
+                    field: field.clone(),
+                    distinct_values: values.len(),
+                    entries: values.values().map(BTreeSet::len).sum(),
+                })
+                .collect(),
+            stale: index.stale,
+            index_hits: index.hits.load(Ordering::Relaxed),
+            full_scans: index.scans.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    fn instance(id: &str, status: &str) -> GtsEntity {
+        GtsEntity::new(
+            None,
+            None,
+            &json!({"id": id, "status": status}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        )
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        let entities = [
+            ("gts.x.shop.orders.order.v1~x.shop._.o1.v1", "active"),
+            ("gts.x.shop.orders.order.v1~x.shop._.o2.v1", "archived"),
+            ("gts.x.shop.billing.invoice.v1~x.shop._.i1.v1", "active"),
+            ("gts.y.crm.leads.lead.v1~y.crm._.l1.v1", "active"),
+        ];
+        for (id, status) in entities {
+            store.register(instance(id, status)).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_pattern_segment_key() {
+        assert_eq!(pattern_segment_key("gts.x.*").as_deref(), Some("x"));
+        assert_eq!(
+            pattern_segment_key("gts.x.shop.*").as_deref(),
+            Some("x.shop")
+        );
+        assert_eq!(
+            pattern_segment_key("gts.x.shop.orders.order.v1~*").as_deref(),
+            Some("x.shop.orders")
+        );
+        assert_eq!(pattern_segment_key("gts.*"), None);
+    }
+
+    #[test]
+    fn test_segment_index_narrows_wildcard_queries() {
+        let store = store();
+        assert_eq!(store.query("gts.x.shop.*", 10).count, 3);
+        assert_eq!(store.query("gts.x.shop.orders.*", 10).count, 2);
+        assert_eq!(store.query("gts.z.*", 10).count, 0);
+
+        let stats = store.index_stats();
+        assert_eq!(stats.index_hits, 3);
+        assert_eq!(stats.full_scans, 0);
+        // x, x.shop, x.shop.orders, x.shop.billing, y, y.crm, y.crm.leads
+        assert_eq!(stats.segment_keys, 7);
+    }
+
+    #[test]
+    fn test_field_index_serves_equality_filters() {
+        let mut store = store();
+        store.index_field("status");
+        assert_eq!(store.indexed_fields(), vec!["status"]);
+
+        assert_eq!(store.query("gts.*[status=active]", 10).count, 3);
+        assert_eq!(
+            store
+                .query("gts.*[status=archived|status=active]", 10)
+                .count,
+            4
+        );
+        assert_eq!(store.index_stats().index_hits, 2);
+
+        // `!=` cannot use the index
+        assert_eq!(store.query("gts.*[status!=active]", 10).count, 1);
+        assert_eq!(store.index_stats().full_scans, 1);
+
+        let stats = store.index_stats();
+        assert_eq!(stats.fields[0].distinct_values, 2);
+        assert_eq!(stats.fields[0].entries, 4);
+    }
+
+    #[test]
+    fn test_index_follows_registration_and_rebuild() {
+        let mut store = store();
+        store.index_field("status");
+        // Re-registering moves the entity to its new value
+        store
+            .register(instance(
+                "gts.x.shop.orders.order.v1~x.shop._.o2.v1",
+                "active",
+            ))
+            .unwrap();
+        assert_eq!(store.query("gts.x.*[status=archived]", 10).count, 0);
+        assert_eq!(store.query("gts.x.*[status=active]", 10).count, 3);
+
+        store.entities_mut().clear();
+        assert!(store.index_stats().stale);
+        // Stale indexes are bypassed, so results stay correct
+        assert_eq!(store.query("gts.x.*[status=active]", 10).count, 0);
+
+        store.rebuild_indexes();
+        let stats = store.index_stats();
+        assert!(!stats.stale);
+        assert_eq!(stats.segment_keys, 0);
+        assert!(store.drop_field_index("status"));
+        assert!(!store.drop_field_index("status"));
+    }
+
+    #[test]
+    fn test_schema_index_hints_create_path_indexes() {
+        const EVENT: &str = "gts.x.shop.events.event.v1~";
+        let mut store = store();
+        store
+            .register_schema(
+                EVENT,
+                &json!({
+                    "$id": format!("gts://{EVENT}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "x-gts-index": ["payload.user_id", "payload.order_id", ""]
+                }),
+            )
+            .unwrap();
+        assert_eq!(
+            store.get(EVENT).unwrap().index_hints(),
+            vec!["payload.user_id", "payload.order_id"]
+        );
+        assert_eq!(
+            store.indexed_fields(),
+            vec!["payload.order_id", "payload.user_id"]
+        );
+
+        for (name, user) in [("e1", "u1"), ("e2", "u2"), ("e3", "u1")] {
+            let content = json!({
+                "id": format!("{EVENT}x.shop._.{name}.v1"),
+                "payload": {"user_id": user}
+            });
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &content,
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                None,
+            );
+            store.register(entity).unwrap();
+        }
+        assert_eq!(store.query("gts.*[payload.user_id=u1]", 10).count, 2);
+        assert_eq!(store.index_stats().index_hits, 1);
+        assert_eq!(store.index_stats().fields[1].entries, 3);
+    }
+}
@@ -0,0 +1,188 @@
+//! "Latest version" lookups.
+//!
+//! Consumers that always want the newest registered version of a type ask
+//! with a version wildcard: `gts.x.shop.orders.order.v*` resolves to the
+//! highest registered `major.minor` of `gts.x.shop.orders.order`, optionally
+//! constrained to one major version.  Chained IDs work the same way, e.g.
+//! `gts.x.core.events.type.v1~x.shop.orders.placed.v*~`.
+
+use crate::entities::GtsEntity;
+use crate::gts::GtsID;
+use crate::store::{GtsStore, StoreError};
+
+/// Version wildcard that ends a "latest" pattern.
+const VERSION_WILDCARD: &str = "v*";
+
+/// The part of `pattern` before its version wildcard, and whether the
+/// pattern only asks for schemas (ends in `~`).
+fn pattern_prefix(pattern: &str) -> Result<(&str, bool), StoreError> {
+    let (trimmed, schemas_only) = match pattern.strip_suffix('~') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    trimmed
+        .strip_suffix(VERSION_WILDCARD)
+        .filter(|prefix| prefix.ends_with('.') && GtsID::is_valid(&format!("{prefix}v1~")))
+        .map(|prefix| (prefix, schemas_only))
+        .ok_or_else(|| {
+            StoreError::ValidationError(format!(
+                "Invalid pattern '{pattern}': expected a GTS ID ending in '.{VERSION_WILDCARD}'"
+            ))
+        })
+}
+
+/// Whether `rest` is a bare version such as `v2` or `v2.1`, optionally of
+/// the given major.
+fn is_version(rest: &str, major: Option<u32>) -> bool {
+    let Some(version) = rest.strip_prefix('v') else {
+        return false;
+    };
+    let mut parts = version.splitn(2, '.');
+    let parsed_major = parts.next().and_then(|m| m.parse::<u32>().ok());
+    let minor_ok = parts.next().is_none_or(|m| m.parse::<u32>().is_ok());
+    parsed_major.is_some_and(|m| major.is_none_or(|want| want == m)) && minor_ok
+}
+
+impl GtsStore {
+    /// The entity with the highest `major.minor` version among those matching
+    /// a version wildcard such as `gts.x.shop.orders.order.v*`, or `None` if
+    /// nothing matches.  `major` restricts the search to one major version.
+    /// A pattern ending in `~` only matches schemas.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` does not end in a
+    /// `v*` version wildcard.
+    pub fn get_latest(
+        &self,
+        pattern: &str,
+        major: Option<u32>,
+    ) -> Result<Option<&GtsEntity>, StoreError> {
+        let (prefix, schemas_only) = pattern_prefix(pattern)?;
+        let matches: Vec<GtsID> = self
+            .entities()
+            .iter()
+            .filter(|(_, entity)| entity.is_schema || !schemas_only)
+            .filter(|(id, _)| {
+                id.trim_end_matches('~')
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| is_version(rest, major))
+            })
+            .filter_map(|(id, _)| GtsID::new(id).ok())
+            .collect();
+        Ok(GtsID::latest_of(&matches).and_then(|latest| self.entities().get(&latest.id)))
+    }
+
+    /// Like [`GtsStore::get_latest`], but only considers schemas.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` does not end in a
+    /// `v*` version wildcard.
+    pub fn get_latest_schema(
+        &self,
+        pattern: &str,
+        major: Option<u32>,
+    ) -> Result<Option<&GtsEntity>, StoreError> {
+        let pattern = if pattern.ends_with('~') {
+            pattern.to_owned()
+        } else {
+            format!("{pattern}~")
+        };
+        self.get_latest(&pattern, major)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    fn store_with(schemas: &[&str], instances: &[&str]) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in schemas {
+            store
+                .register_schema(
+                    id,
+                    &json!({
+                        "$id": format!("gts://{id}"),
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "type": "object"
+                    }),
+                )
+                .unwrap();
+        }
+        for id in instances {
+            let schema_id = format!("{}~", id.rsplit_once('~').unwrap().0);
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &json!({"id": id}),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(schema_id),
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    fn latest_id(result: Result<Option<&GtsEntity>, StoreError>) -> Option<String> {
+        result.unwrap().and_then(GtsEntity::effective_id)
+    }
+
+    #[test]
+    fn test_get_latest_schema_picks_highest_version() {
+        let store = store_with(
+            &[
+                "gts.x.test.latest.order.v1~",
+                "gts.x.test.latest.order.v1.10~",
+                "gts.x.test.latest.order.v1.2~",
+                "gts.x.test.latest.order.v2~",
+                "gts.x.test.latest.order_line.v9~",
+            ],
+            &[],
+        );
+        assert_eq!(
+            latest_id(store.get_latest_schema("gts.x.test.latest.order.v*", None)).as_deref(),
+            Some("gts.x.test.latest.order.v2~")
+        );
+        assert_eq!(
+            latest_id(store.get_latest_schema("gts.x.test.latest.order.v*", Some(1))).as_deref(),
+            Some("gts.x.test.latest.order.v1.10~")
+        );
+        assert_eq!(
+            latest_id(store.get_latest_schema("gts.x.test.latest.order.v*", Some(3))),
+            None
+        );
+        assert!(matches!(
+            store.get_latest_schema("gts.x.test.latest.order.*", None),
+            Err(StoreError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_latest_instance_in_chain() {
+        let schema = "gts.x.test.latest.config.v1~";
+        let store = store_with(
+            &[schema],
+            &[
+                "gts.x.test.latest.config.v1~x.test._.main.v1",
+                "gts.x.test.latest.config.v1~x.test._.main.v1.3",
+                "gts.x.test.latest.config.v1~x.test._.other.v4",
+            ],
+        );
+        assert_eq!(
+            latest_id(store.get_latest("gts.x.test.latest.config.v1~x.test._.main.v*", None))
+                .as_deref(),
+            Some("gts.x.test.latest.config.v1~x.test._.main.v1.3")
+        );
+        assert_eq!(
+            latest_id(store.get_latest("gts.x.test.latest.config.v*", None)).as_deref(),
+            Some(schema)
+        );
+    }
+}
@@ -1,25 +1,156 @@
-pub mod entities;
+// IDs, patterns and entities live in `gts-core`; the modules keep their paths here.
+pub use gts_core::{diagnostic, entities, gts, numbers, path_resolver, schema_cast};
+
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod audit;
+pub mod avro;
+pub mod bundle;
+pub mod changes;
+pub mod codegen;
+pub mod config;
+pub mod conformance;
+pub mod contract;
+pub mod default_instance;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod explain;
+#[cfg(feature = "axum")]
+pub mod extract;
 pub mod files_reader;
-pub mod gts;
+pub mod files_writer;
+pub mod fingerprint;
+pub mod graph;
+pub mod gtsignore;
+#[cfg(feature = "http-reader")]
+pub mod http_reader;
+pub mod id_resolve;
+pub mod import;
+pub mod index;
+pub mod latest;
+pub mod lifecycle;
+pub mod metrics;
+pub mod migration;
+pub mod openapi;
 pub mod ops;
-pub mod path_resolver;
+pub mod ownership;
+pub mod prelude;
+pub mod proto;
+pub mod ref_integrity;
+pub mod references;
+pub mod removal;
+pub mod rename;
+pub mod sandbox;
 pub mod schema;
-pub mod schema_cast;
 pub mod schema_compat;
+pub mod schema_resolution;
 pub mod schema_traits;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod snapshot;
 pub mod store;
+#[cfg(feature = "async")]
+pub mod store_async;
+pub mod store_snapshot;
+pub mod stream_reader;
+pub mod terraform;
+#[cfg(test)]
+mod test_fixtures;
+pub mod transaction;
+pub mod typed;
+pub mod validate_all;
+pub mod validation_report;
+pub mod validator_cache;
+pub mod visibility;
+#[cfg(feature = "watch")]
+pub mod watch;
 pub mod x_gts_ref;
 
 // Re-export commonly used types
-pub use entities::{GtsConfig, GtsEntity, GtsFile, ValidationError, ValidationResult};
-pub use files_reader::GtsFileReader;
-pub use gts::{GtsError, GtsID, GtsIdSegment, GtsInstanceId, GtsSchemaId, GtsWildcard};
+pub use audit::{
+    GtsDanglingRef, GtsEntityDanglingRefs, GtsMajorTypeReport, GtsMajorVersionUsage,
+    GtsMajorsAuditResult, GtsReferrer, GtsRefsAuditResult,
+};
+pub use avro::{
+    AVRO_JSON_NAME_KEY, GtsAvroCompatibility, GtsAvroExport, GtsAvroSchema, GtsAvroSubject,
+};
+pub use bundle::{GtsBundle, GtsBundleEntry, GtsBundleManifest};
+pub use changes::{GtsChangeEvent, GtsChangeKind, GtsChangeListener};
+pub use codegen::{GtsGeneratedCode, GtsGeneratedType};
+pub use config::{GtsConfigError, GtsConfigFile};
+pub use conformance::{
+    GtsConformanceEntry, GtsConformanceReport, GtsConformanceStatus, GtsConformanceSummary,
+    GtsDeclaredSchema,
+};
+pub use contract::{GtsContractExpectation, GtsContractReport, GtsContractStatus};
+pub use default_instance::GtsDefaultInstance;
+pub use diagnostic::{GtsCode, GtsDiagnostic};
+#[cfg(feature = "encryption")]
+pub use encryption::{GtsContentCipher, GtsEnvKeyProvider, GtsKeyProvider};
+pub use entities::{GtsConfig, GtsEntity, GtsFile, GtsIdFields, ValidationError, ValidationResult};
+pub use explain::{GtsTraceStep, GtsValidationExplain, GtsValidationTrace};
+#[cfg(feature = "axum")]
+pub use extract::{
+    GtsJson, GtsResponseSchema, GtsResponseValidation, GtsResponseValidationMode, GtsValidated,
+    GtsValidatedRejection, validate_response,
+};
+pub use files_reader::{GtsFileIssue, GtsFileReader};
+pub use files_writer::GtsFileWriter;
+pub use fingerprint::{canonical_json, canonical_json_pretty, canonical_value, schema_fingerprint};
+pub use graph::{GtsGraph, GtsGraphEdge, GtsGraphEdgeKind, GtsGraphNodeKind};
+pub use gts::{
+    GtsError, GtsID, GtsIdBuilder, GtsIdSegment, GtsInstanceId, GtsSchemaId, GtsWildcard,
+};
+pub use gtsignore::{GTS_IGNORE_FILE, GtsIgnore};
+#[cfg(feature = "http-reader")]
+pub use http_reader::HttpGtsReader;
+pub use import::{GtsImportReport, GtsImportedSchema};
+pub use index::{GtsFieldIndexStats, GtsIndexStats};
+pub use lifecycle::{
+    GtsLifecycle, GtsLifecycleEntry, GtsLifecycleState, GtsRetiredSchemaPolicy, LIFECYCLE_KEY,
+    LifecycleError, STATUS_KEY,
+};
+pub use metrics::{GtsMetricsSink, GtsStoreMetrics};
+pub use migration::{
+    GtsMigratedInstance, GtsMigrationPlan, GtsMigrationReport, GtsMigrationStep, RENAMED_FROM_KEY,
+};
+pub use numbers::{GtsNumberError, GtsNumberPolicy};
 pub use ops::GtsOps;
+pub use ownership::{GtsNamespacePolicy, GtsNamespaceRule, GtsRegistration, PolicyProvider};
 pub use path_resolver::JsonPathResolver;
+pub use proto::GtsProtoFile;
+pub use ref_integrity::{GtsBrokenRef, GtsBrokenRefReason, GtsRefIntegrity};
+pub use removal::{GtsRemovalBlocker, GtsRemovalPlan, GtsRemovalStep};
+pub use rename::{
+    GtsIdRenamer, GtsRenameManifest, GtsRenameMapping, GtsRenamedFile, RenameError, rename_in_paths,
+};
+pub use sandbox::GtsSandboxLimits;
 pub use schema::{
     GtsDeserialize, GtsDeserializeWrapper, GtsNoDirectDeserialize, GtsNoDirectSerialize, GtsSchema,
-    GtsSerialize, GtsSerializeWrapper, deserialize_gts, serialize_gts, strip_schema_metadata,
+    GtsSerialize, GtsSerializeWrapper, GtsTyped, deserialize_gts, serialize_gts,
+    strip_schema_metadata,
 };
 pub use schema_cast::{GtsEntityCastResult, SchemaCastError};
-pub use store::{GtsReader, GtsStore, GtsStoreQueryResult, StoreError};
+pub use schema_resolution::{GtsSchemaResolution, GtsSchemaSubstitution};
+pub use schema_traits::GtsTraitVars;
+#[cfg(feature = "signing")]
+pub use signing::GtsSignedBundle;
+pub use snapshot::{GtsSnapshotError, GtsSnapshotOutcome};
+pub use store::{
+    GtsReader, GtsStore, GtsStoreQueryPage, GtsStoreQueryResult, GtsWriteError, GtsWriter,
+    RefSiblingMerge, RefSiblingWarning, SortOrder, StoreError,
+};
+#[cfg(feature = "async")]
+pub use store_async::AsyncGtsStore;
+pub use store_snapshot::GtsStoreSnapshot;
+pub use stream_reader::GtsStreamReader;
+pub use terraform::{GtsKafkaTopic, GtsTerraformExport};
+pub use transaction::GtsTransaction;
+pub use typed::GtsTypedError;
+pub use validate_all::{GtsValidateAllReport, GtsValidationFailure};
+pub use validation_report::{GtsValidationIssue, GtsValidationReport};
+pub use validator_cache::GtsValidatorCacheStats;
+pub use visibility::{GtsVisibility, VISIBILITY_KEY};
+#[cfg(feature = "watch")]
+pub use watch::{GtsFileWatcher, GtsStoreChange, GtsWatchEvent, WatchError};
 pub use x_gts_ref::{XGtsRefValidationError, XGtsRefValidator};
@@ -0,0 +1,566 @@
+//! Schema lifecycle: `draft` → `active` → `deprecated` → `retired`.
+//!
+//! The lifecycle lives in the schema itself under `x-gts-lifecycle`:
+//!
+//! ```json
+//! "x-gts-lifecycle": {
+//!   "state": "deprecated",
+//!   "since": "2025-01-15",
+//!   "retire_on": "2025-07-01",
+//!   "replacement": "gts.x.shop.orders.order.v2~"
+//! }
+//! ```
+//!
+//! A deprecated schema with a `retire_on` date is a grace period: validation
+//! against it keeps working (with a warning) until that date, after which it
+//! is treated as retired and validation fails with a pointer to the
+//! replacement (or only warns under [`GtsRetiredSchemaPolicy::Warn`]).
+//! States only move forward.
+//!
+//! A bare `"x-gts-status": "deprecated"` annotation sets the state on its own
+//! and takes precedence over `x-gts-lifecycle.state`; the store writes both.
+//! Queries leave deprecated and retired schemas out unless they filter on
+//! `x-gts-status`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::fingerprint::canonical_json_pretty;
+use crate::store::GtsStore;
+
+pub use gts_core::lifecycle::GtsRetiredSchemaPolicy;
+
+/// Schema keyword holding the lifecycle.
+pub const LIFECYCLE_KEY: &str = "x-gts-lifecycle";
+
+/// Schema keyword holding just the lifecycle state.
+pub const STATUS_KEY: &str = "x-gts-status";
+
+#[derive(Debug, Error)]
+pub enum LifecycleError {
+    #[error("Schema '{0}' not found in store")]
+    SchemaNotFound(String),
+    #[error("Cannot move schema '{schema_id}' from {from} to {to}")]
+    InvalidTransition {
+        schema_id: String,
+        from: GtsLifecycleState,
+        to: GtsLifecycleState,
+    },
+    #[error("Invalid date '{0}', expected YYYY-MM-DD")]
+    InvalidDate(String),
+    #[error("Schema '{0}' was not loaded from a file")]
+    NoSourceFile(String),
+    #[error("Cannot update {0}: only JSON schema files can be rewritten")]
+    UnsupportedFormat(String),
+    #[error("Failed to update {path}: {cause}")]
+    Io { path: String, cause: String },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsLifecycleState {
+    Draft,
+    #[default]
+    Active,
+    Deprecated,
+    Retired,
+}
+
+impl fmt::Display for GtsLifecycleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GtsLifecycleState::Draft => "draft",
+            GtsLifecycleState::Active => "active",
+            GtsLifecycleState::Deprecated => "deprecated",
+            GtsLifecycleState::Retired => "retired",
+        })
+    }
+}
+
+impl FromStr for GtsLifecycleState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "draft" => Ok(GtsLifecycleState::Draft),
+            "active" => Ok(GtsLifecycleState::Active),
+            "deprecated" => Ok(GtsLifecycleState::Deprecated),
+            "retired" => Ok(GtsLifecycleState::Retired),
+            other => Err(format!(
+                "unknown lifecycle state '{other}' (expected draft, active, deprecated or retired)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsLifecycle {
+    #[serde(default)]
+    pub state: GtsLifecycleState,
+    /// Date (`YYYY-MM-DD`) the current state was entered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// End of the deprecation grace period; from this date on the schema is
+    /// treated as retired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retire_on: Option<String>,
+    /// Schema consumers should migrate to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// Lifecycle of one schema as reported by [`GtsStore::lifecycle_report`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsLifecycleEntry {
+    pub schema_id: String,
+    /// State after applying the grace period to today's date.
+    pub effective_state: GtsLifecycleState,
+    pub lifecycle: GtsLifecycle,
+}
+
+impl GtsLifecycle {
+    /// Reads the lifecycle of a schema; schemas without one are active.
+    #[must_use]
+    pub fn of(schema: &Value) -> Self {
+        let mut lifecycle: Self = schema
+            .get(LIFECYCLE_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        if let Some(state) = schema
+            .get(STATUS_KEY)
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+        {
+            lifecycle.state = state;
+        }
+        lifecycle
+    }
+
+    /// The state on `today` (`YYYY-MM-DD`), taking the grace period into account.
+    #[must_use]
+    pub fn effective_state(&self, today: &str) -> GtsLifecycleState {
+        match (&self.state, &self.retire_on) {
+            (GtsLifecycleState::Deprecated, Some(retire_on)) if retire_on.as_str() <= today => {
+                GtsLifecycleState::Retired
+            }
+            (state, _) => *state,
+        }
+    }
+
+    /// Moves to `to`, keeping existing details unless new ones are given.
+    /// Re-applying the current state only updates `retire_on`/`replacement`.
+    ///
+    /// # Errors
+    /// Returns `LifecycleError::InvalidTransition` for backward moves and
+    /// `LifecycleError::InvalidDate` if `retire_on` is not `YYYY-MM-DD`.
+    pub fn transition(
+        &self,
+        schema_id: &str,
+        to: GtsLifecycleState,
+        retire_on: Option<&str>,
+        replacement: Option<&str>,
+    ) -> Result<Self, LifecycleError> {
+        if to < self.state {
+            return Err(LifecycleError::InvalidTransition {
+                schema_id: schema_id.to_owned(),
+                from: self.state,
+                to,
+            });
+        }
+        if let Some(date) = retire_on
+            && !is_date(date)
+        {
+            return Err(LifecycleError::InvalidDate(date.to_owned()));
+        }
+
+        let mut next = self.clone();
+        if to != self.state {
+            next.state = to;
+            next.since = Some(today());
+        }
+        if let Some(date) = retire_on {
+            next.retire_on = Some(date.to_owned());
+        }
+        if let Some(replacement) = replacement {
+            next.replacement = Some(replacement.to_owned());
+        }
+        Ok(next)
+    }
+}
+
+impl GtsStore {
+    /// Returns the lifecycle of a schema, or `None` if the schema is unknown.
+    pub fn schema_lifecycle(&mut self, schema_id: &str) -> Option<GtsLifecycle> {
+        self.get(schema_id)
+            .filter(|e| e.is_schema)
+            .map(|e| GtsLifecycle::of(&e.content))
+    }
+
+    /// Moves a schema to a new lifecycle state and records it in the schema
+    /// content held by the store.
+    ///
+    /// # Errors
+    /// Returns `LifecycleError::SchemaNotFound` if the schema is unknown, or the
+    /// errors of [`GtsLifecycle::transition`].
+    pub fn set_schema_lifecycle(
+        &mut self,
+        schema_id: &str,
+        to: GtsLifecycleState,
+        retire_on: Option<&str>,
+        replacement: Option<&str>,
+    ) -> Result<GtsLifecycle, LifecycleError> {
+        let current = self
+            .schema_lifecycle(schema_id)
+            .ok_or_else(|| LifecycleError::SchemaNotFound(schema_id.to_owned()))?;
+        let next = current.transition(schema_id, to, retire_on, replacement)?;
+
+        let mut entity = self
+            .get(schema_id)
+            .cloned()
+            .ok_or_else(|| LifecycleError::SchemaNotFound(schema_id.to_owned()))?;
+        if let Some(obj) = entity.content.as_object_mut() {
+            write_lifecycle(obj, &next);
+        }
+        self.insert_entity(schema_id.to_owned(), entity);
+        Ok(next)
+    }
+
+    /// Deprecates a schema in favour of `successor_id`, which must be a
+    /// registered schema.
+    ///
+    /// # Errors
+    /// Returns `LifecycleError::SchemaNotFound` if either schema is unknown,
+    /// or the errors of [`GtsLifecycle::transition`].
+    pub fn deprecate_schema(
+        &mut self,
+        schema_id: &str,
+        successor_id: &str,
+    ) -> Result<GtsLifecycle, LifecycleError> {
+        if !self
+            .entities()
+            .get(successor_id)
+            .is_some_and(|e| e.is_schema)
+        {
+            return Err(LifecycleError::SchemaNotFound(successor_id.to_owned()));
+        }
+        self.set_schema_lifecycle(
+            schema_id,
+            GtsLifecycleState::Deprecated,
+            None,
+            Some(successor_id),
+        )
+    }
+
+    /// Writes a schema's lifecycle back into the file it was loaded from and
+    /// returns that file's path.
+    ///
+    /// # Errors
+    /// Returns `LifecycleError::NoSourceFile` for schemas registered in memory,
+    /// `LifecycleError::UnsupportedFormat` for non-JSON files, and
+    /// `LifecycleError::Io` if the file cannot be read or written.
+    pub fn persist_schema_lifecycle(&mut self, schema_id: &str) -> Result<String, LifecycleError> {
+        let entity = self
+            .get(schema_id)
+            .ok_or_else(|| LifecycleError::SchemaNotFound(schema_id.to_owned()))?;
+        let file = entity
+            .file
+            .as_ref()
+            .ok_or_else(|| LifecycleError::NoSourceFile(schema_id.to_owned()))?;
+        let path = file.path.clone();
+        if !Path::new(&path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        {
+            return Err(LifecycleError::UnsupportedFormat(path));
+        }
+        let lifecycle = GtsLifecycle::of(&entity.content);
+        let list_sequence = entity.list_sequence;
+
+        let io_error = |cause: String| LifecycleError::Io {
+            path: path.clone(),
+            cause,
+        };
+        let text = fs::read_to_string(&path).map_err(|e| io_error(e.to_string()))?;
+        let mut doc: Value = serde_json::from_str(&text).map_err(|e| io_error(e.to_string()))?;
+        let target = match list_sequence {
+            Some(idx) => doc.get_mut(idx),
+            None => Some(&mut doc),
+        };
+        let Some(Value::Object(obj)) = target else {
+            return Err(io_error(format!("schema '{schema_id}' not found in file")));
+        };
+        write_lifecycle(obj, &lifecycle);
+
+        fs::write(&path, canonical_json_pretty(&doc)).map_err(|e| io_error(e.to_string()))?;
+        Ok(path)
+    }
+
+    /// Lifecycle of every schema in the store, sorted by schema ID.
+    #[must_use]
+    pub fn lifecycle_report(&self) -> Vec<GtsLifecycleEntry> {
+        let today = today();
+        let mut entries: Vec<GtsLifecycleEntry> = self
+            .items()
+            .filter(|(_, e)| e.is_schema)
+            .map(|(id, e)| {
+                let lifecycle = GtsLifecycle::of(&e.content);
+                GtsLifecycleEntry {
+                    schema_id: id.clone(),
+                    effective_state: lifecycle.effective_state(&today),
+                    lifecycle,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.schema_id.cmp(&b.schema_id));
+        entries
+    }
+}
+
+fn write_lifecycle(schema: &mut serde_json::Map<String, Value>, lifecycle: &GtsLifecycle) {
+    schema.insert(
+        LIFECYCLE_KEY.to_owned(),
+        serde_json::to_value(lifecycle).unwrap_or(Value::Null),
+    );
+    schema.insert(
+        STATUS_KEY.to_owned(),
+        Value::String(lifecycle.state.to_string()),
+    );
+}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+#[must_use]
+pub(crate) fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs().div_euclid(86_400));
+    let (y, m, d) = civil_from_days(i64::try_from(days).unwrap_or(0));
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's algorithm, valid for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe.div_euclid(1460) + doe.div_euclid(36_524) - doe.div_euclid(146_096))
+        .div_euclid(365);
+    let doy = doe - (365 * yoe + yoe.div_euclid(4) - yoe.div_euclid(100));
+    let mp = (5 * doy + 2).div_euclid(153);
+    let d = doy - (153 * mp + 2).div_euclid(5) + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (
+        y,
+        u32::try_from(m).unwrap_or(1),
+        u32::try_from(d).unwrap_or(1),
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::files_reader::GtsFileReader;
+    use crate::store::StoreError;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const OLD: &str = "gts.x.test.life.order.v1~";
+    const NEW: &str = "gts.x.test.life.order.v2~";
+    const INSTANCE: &str = "gts.x.test.life.order.v1~x.test._.o1.v1";
+
+    fn schema(id: &str) -> Value {
+        json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        })
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store.register_schema(OLD, &schema(OLD)).unwrap();
+        store.register_schema(NEW, &schema(NEW)).unwrap();
+        let instance = crate::entities::GtsEntity::new(
+            None,
+            None,
+            &json!({"id": INSTANCE}),
+            Some(&crate::entities::GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        store.register(instance).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert!(is_date(&today()));
+    }
+
+    #[test]
+    fn test_transitions_only_move_forward() {
+        let mut store = store();
+        let deprecated = store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Deprecated, None, Some(NEW))
+            .unwrap();
+        assert_eq!(deprecated.state, GtsLifecycleState::Deprecated);
+        assert_eq!(deprecated.since, Some(today()));
+        assert_eq!(deprecated.replacement.as_deref(), Some(NEW));
+
+        let err = store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Active, None, None)
+            .unwrap_err();
+        assert!(matches!(err, LifecycleError::InvalidTransition { .. }));
+
+        let err = store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Deprecated, Some("soon"), None)
+            .unwrap_err();
+        assert!(matches!(err, LifecycleError::InvalidDate(_)));
+    }
+
+    #[test]
+    fn test_grace_period_and_retired_validation() {
+        let mut store = store();
+        store
+            .set_schema_lifecycle(
+                OLD,
+                GtsLifecycleState::Deprecated,
+                Some("9999-12-31"),
+                Some(NEW),
+            )
+            .unwrap();
+        // Still inside the grace period
+        store.validate_instance(INSTANCE).unwrap();
+
+        store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Deprecated, Some("2000-01-01"), None)
+            .unwrap();
+        let err = store.validate_instance(INSTANCE).unwrap_err();
+        assert!(matches!(err, StoreError::SchemaRetired { .. }));
+        assert!(err.to_string().contains(NEW), "{err}");
+
+        let report = store.lifecycle_report();
+        let old = report.iter().find(|e| e.schema_id == OLD).unwrap();
+        assert_eq!(old.lifecycle.state, GtsLifecycleState::Deprecated);
+        assert_eq!(old.effective_state, GtsLifecycleState::Retired);
+        let new = report.iter().find(|e| e.schema_id == NEW).unwrap();
+        assert_eq!(new.effective_state, GtsLifecycleState::Active);
+    }
+
+    #[test]
+    fn test_deprecate_schema_hides_it_from_queries() {
+        let mut store = store();
+        assert!(matches!(
+            store.deprecate_schema(OLD, "gts.x.test.life.order.v3~"),
+            Err(LifecycleError::SchemaNotFound(_))
+        ));
+        let lifecycle = store.deprecate_schema(OLD, NEW).unwrap();
+        assert_eq!(lifecycle.replacement.as_deref(), Some(NEW));
+        assert_eq!(store.get(OLD).unwrap().content[STATUS_KEY], "deprecated");
+
+        let ids = |result: crate::store::GtsStoreQueryResult| -> Vec<String> {
+            let mut ids: Vec<String> = result
+                .results
+                .iter()
+                .filter_map(|r| r.get("$id").or_else(|| r.get("id")))
+                .map(|id| id.as_str().unwrap_or_default().to_owned())
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(
+            ids(store.query("gts.x.test.life.*", 10)),
+            vec![INSTANCE.to_owned(), format!("gts://{NEW}")]
+        );
+        assert_eq!(
+            ids(store.query("gts.x.test.life.*[x-gts-status=deprecated]", 10)),
+            vec![format!("gts://{OLD}")]
+        );
+        assert_eq!(
+            ids(store.query("gts.x.test.life.*[x-gts-status=*]", 10)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_status_annotation_and_retired_policy() {
+        let mut store = store();
+        let mut retired = schema(OLD);
+        retired[STATUS_KEY] = json!("retired");
+        store.register_schema(OLD, &retired).unwrap();
+        assert_eq!(
+            store.schema_lifecycle(OLD).unwrap().state,
+            GtsLifecycleState::Retired
+        );
+        assert!(matches!(
+            store.validate_instance(INSTANCE),
+            Err(StoreError::SchemaRetired { .. })
+        ));
+        store.set_retired_schema_policy(GtsRetiredSchemaPolicy::Warn);
+        store.validate_instance(INSTANCE).unwrap();
+
+        let mut draft = schema(NEW);
+        draft[STATUS_KEY] = json!("draft");
+        store.register_schema(NEW, &draft).unwrap();
+        assert_eq!(store.query(NEW, 10).count, 1);
+        let active = store
+            .set_schema_lifecycle(NEW, GtsLifecycleState::Active, None, None)
+            .unwrap();
+        assert_eq!(active.state, GtsLifecycleState::Active);
+        assert_eq!(store.get(NEW).unwrap().content[STATUS_KEY], "active");
+    }
+
+    #[test]
+    fn test_persist_lifecycle_to_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("schemas.json");
+        fs::write(
+            &path,
+            serde_json::to_string(&json!([schema(NEW), schema(OLD)])).unwrap(),
+        )
+        .unwrap();
+
+        let reader = GtsFileReader::new(&[dir.path().to_string_lossy().to_string()], None);
+        let mut store = GtsStore::new(Some(Box::new(reader)));
+        store
+            .set_schema_lifecycle(OLD, GtsLifecycleState::Retired, None, Some(NEW))
+            .unwrap();
+        let written = store.persist_schema_lifecycle(OLD).unwrap();
+
+        let doc: Value = serde_json::from_str(&fs::read_to_string(written).unwrap()).unwrap();
+        assert_eq!(doc[1][LIFECYCLE_KEY]["state"], "retired");
+        assert_eq!(doc[1][LIFECYCLE_KEY]["replacement"], NEW);
+        assert!(doc[0].get(LIFECYCLE_KEY).is_none());
+
+        let mut in_memory = GtsStore::new(None);
+        in_memory.register_schema(OLD, &schema(OLD)).unwrap();
+        assert!(matches!(
+            in_memory.persist_schema_lifecycle(OLD),
+            Err(LifecycleError::NoSourceFile(_))
+        ));
+    }
+}
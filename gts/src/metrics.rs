@@ -0,0 +1,273 @@
+//! Store metrics and instrumentation hooks.
+//!
+//! The store counts schema compilations and instance validations as they
+//! happen; [`GtsStore::metrics`] returns those counters together with the
+//! number of registered entities and the validator cache hit rate.  Services
+//! that export metrics elsewhere install a [`GtsMetricsSink`] with
+//! [`GtsStore::set_metrics_sink`] to be told about each event as well, and
+//! [`GtsStoreMetrics::to_prometheus`] renders a snapshot in the Prometheus
+//! text format.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::store::GtsStore;
+
+/// Receives store events as they happen, e.g. to feed Prometheus counters.
+/// All methods default to doing nothing.
+pub trait GtsMetricsSink: Send + Sync {
+    /// A schema was compiled for validation (a validator cache miss).
+    fn on_compile(&self, _schema_id: &str, _elapsed: Duration) {}
+
+    /// Content was validated against `schema_id`.
+    fn on_validation(&self, _schema_id: &str, _ok: bool, _elapsed: Duration) {}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GtsStoreMetrics {
+    pub schemas: usize,
+    pub instances: usize,
+    pub validations_ok: u64,
+    pub validations_failed: u64,
+    /// Compiled-validator lookups answered from the cache, from 0 to 1; 0
+    /// before the first lookup.
+    pub cache_hit_rate: f64,
+    pub compilations: u64,
+    pub avg_compile_micros: u64,
+    pub avg_validate_micros: u64,
+}
+
+impl GtsStoreMetrics {
+    /// The metrics in the Prometheus text exposition format.
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            let _ = writeln!(out, "# HELP gts_{name} {help}");
+            let _ = writeln!(out, "# TYPE gts_{name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "gts_{name}{labels} {value}");
+            }
+        };
+        metric(
+            "entities",
+            "gauge",
+            "Registered entities by kind.",
+            &[
+                ("{kind=\"schema\"}", self.schemas.to_string()),
+                ("{kind=\"instance\"}", self.instances.to_string()),
+            ],
+        );
+        metric(
+            "validations_total",
+            "counter",
+            "Instance validations by outcome.",
+            &[
+                ("{result=\"ok\"}", self.validations_ok.to_string()),
+                ("{result=\"failed\"}", self.validations_failed.to_string()),
+            ],
+        );
+        metric(
+            "compilations_total",
+            "counter",
+            "Schemas compiled for validation.",
+            &[("", self.compilations.to_string())],
+        );
+        metric(
+            "validator_cache_hit_rate",
+            "gauge",
+            "Share of validator lookups answered from the cache.",
+            &[("", self.cache_hit_rate.to_string())],
+        );
+        metric(
+            "compile_duration_avg_microseconds",
+            "gauge",
+            "Average time to compile a schema.",
+            &[("", self.avg_compile_micros.to_string())],
+        );
+        metric(
+            "validate_duration_avg_microseconds",
+            "gauge",
+            "Average time to validate an instance.",
+            &[("", self.avg_validate_micros.to_string())],
+        );
+        out
+    }
+}
+
+/// Running totals behind [`GtsStoreMetrics`].
+#[derive(Default)]
+pub(crate) struct GtsMetricCounters {
+    validations_ok: u64,
+    validations_failed: u64,
+    validate_time: Duration,
+    compilations: u64,
+    compile_time: Duration,
+    sink: Option<Arc<dyn GtsMetricsSink>>,
+}
+
+impl GtsMetricCounters {
+    pub(crate) fn record_compile(&mut self, schema_id: &str, elapsed: Duration) {
+        self.compilations += 1;
+        self.compile_time += elapsed;
+        if let Some(sink) = &self.sink {
+            sink.on_compile(schema_id, elapsed);
+        }
+    }
+
+    pub(crate) fn record_validation(&mut self, schema_id: &str, ok: bool, elapsed: Duration) {
+        if ok {
+            self.validations_ok += 1;
+        } else {
+            self.validations_failed += 1;
+        }
+        self.validate_time += elapsed;
+        if let Some(sink) = &self.sink {
+            sink.on_validation(schema_id, ok, elapsed);
+        }
+    }
+}
+
+fn average_micros(total: Duration, count: u64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    let average = total / u32::try_from(count).unwrap_or(u32::MAX);
+    u64::try_from(average.as_micros()).unwrap_or(u64::MAX)
+}
+
+impl GtsStore {
+    /// Entity counts and validation counters since the store was created or
+    /// [`GtsStore::reset_metrics`] was last called.  Validations include
+    /// those run by [`GtsStore::validate_all`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn metrics(&self) -> GtsStoreMetrics {
+        let counters = self.metric_counters();
+        let cache = self.validator_cache_stats();
+        let lookups = cache.hits + cache.misses;
+        let schemas = self.entities().values().filter(|e| e.is_schema).count();
+        let validations = counters.validations_ok + counters.validations_failed;
+        GtsStoreMetrics {
+            schemas,
+            instances: self.entities().len() - schemas,
+            validations_ok: counters.validations_ok,
+            validations_failed: counters.validations_failed,
+            cache_hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                cache.hits as f64 / lookups as f64
+            },
+            compilations: counters.compilations,
+            avg_compile_micros: average_micros(counters.compile_time, counters.compilations),
+            avg_validate_micros: average_micros(counters.validate_time, validations),
+        }
+    }
+
+    /// Zeroes the validation and compilation counters.  The sink stays
+    /// installed.
+    pub fn reset_metrics(&mut self) {
+        let sink = self.metric_counters_mut().sink.take();
+        *self.metric_counters_mut() = GtsMetricCounters {
+            sink,
+            ..GtsMetricCounters::default()
+        };
+    }
+
+    /// Installs a sink told about every compilation and validation, or
+    /// removes it with `None`.
+    pub fn set_metrics_sink(&mut self, sink: Option<Arc<dyn GtsMetricsSink>>) {
+        self.metric_counters_mut().sink = sink;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const ITEM: &str = "gts.x.test.metrics.item.v1~";
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl GtsMetricsSink for Recorder {
+        fn on_compile(&self, schema_id: &str, _elapsed: Duration) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("compile {schema_id}"));
+        }
+
+        fn on_validation(&self, schema_id: &str, ok: bool, _elapsed: Duration) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("validate {schema_id} {ok}"));
+        }
+    }
+
+    #[test]
+    fn test_metrics_count_validations_and_notify_sink() {
+        let mut store = GtsStore::new(None);
+        let recorder = Arc::new(Recorder::default());
+        store.set_metrics_sink(Some(recorder.clone()));
+        store
+            .register_schema(
+                ITEM,
+                &json!({
+                    "$id": format!("gts://{ITEM}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"n": {"type": "integer"}}
+                }),
+            )
+            .unwrap();
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{ITEM}x.test._.a.v1"), "n": 1}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(ITEM.to_owned()),
+        );
+        let id = entity.gts_id.as_ref().unwrap().id.clone();
+        store.register(entity).unwrap();
+
+        store.validate_instance(&id).unwrap();
+        assert!(store.validate_value(ITEM, &json!({"n": "x"})).is_err());
+
+        let metrics = store.metrics();
+        assert_eq!((metrics.schemas, metrics.instances), (1, 1));
+        assert_eq!((metrics.validations_ok, metrics.validations_failed), (1, 1));
+        assert_eq!(metrics.compilations, 1);
+        assert!((metrics.cache_hit_rate - 0.5).abs() < f64::EPSILON);
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            [
+                format!("compile {ITEM}"),
+                format!("validate {ITEM} true"),
+                format!("validate {ITEM} false"),
+            ]
+        );
+        let text = metrics.to_prometheus();
+        assert!(text.contains("gts_validations_total{result=\"failed\"} 1\n"));
+        assert!(text.contains("# TYPE gts_entities gauge\n"));
+
+        store.reset_metrics();
+        assert_eq!(store.metrics().validations_ok, 0);
+        assert_eq!(store.validate_all().checked, 1);
+        assert_eq!(store.metrics().validations_ok, 1);
+        assert_eq!(recorder.events.lock().unwrap().len(), 4);
+    }
+}
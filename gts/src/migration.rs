@@ -0,0 +1,505 @@
+//! Bulk instance migration between schema versions.
+//!
+//! [`GtsStore::plan_migration`] compares two schemas and derives an ordered
+//! list of [`GtsMigrationStep`]s: renames first, then type coercions, added
+//! defaults, dropped properties and updated GTS ID constants.  A plan is plain
+//! data: it can be reviewed, stored as JSON, and then applied to any number of
+//! instances with [`GtsMigrationPlan::apply`] or
+//! [`GtsStore::apply_migration`].
+//!
+//! Renames cannot be inferred reliably, so the target schema declares them:
+//! a property annotated with `"x-gts-renamed-from": "old_name"` takes over
+//! the value of `old_name`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+
+use crate::gts::GtsID;
+use crate::numbers;
+use crate::schema_cast::GtsEntityCastResult;
+use crate::store::{GtsStore, StoreError};
+use crate::validate_all::GtsValidationFailure;
+
+/// Schema keyword naming the property a renamed property was called before.
+pub const RENAMED_FROM_KEY: &str = "x-gts-renamed-from";
+
+/// One transformation of an instance. Paths are dotted property paths
+/// (`customer.address`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GtsMigrationStep {
+    /// Move the value at `from` to `to`.
+    Rename { from: String, to: String },
+    /// Convert the value at `path` to the JSON type `to`.
+    Coerce { path: String, to: String },
+    /// Set `path` to `value` when it is missing.
+    AddDefault { path: String, value: Value },
+    /// Remove `path`.
+    Drop { path: String },
+    /// Replace a GTS ID constant at `path`, when present.
+    SetConst { path: String, value: Value },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMigrationPlan {
+    pub from_schema: String,
+    pub to_schema: String,
+    pub steps: Vec<GtsMigrationStep>,
+    /// Differences the plan cannot resolve, e.g. a new required property
+    /// without a default.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMigratedInstance {
+    pub id: String,
+    pub content: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsMigrationReport {
+    pub from_schema: String,
+    pub to_schema: String,
+    /// Migrated instances that validate against the target schema, by ID.
+    pub migrated: Vec<GtsMigratedInstance>,
+    /// Instances that could not be migrated or fail validation afterwards.
+    pub failures: Vec<GtsValidationFailure>,
+}
+
+fn join(base: &str, prop: &str) -> String {
+    if base.is_empty() {
+        prop.to_owned()
+    } else {
+        format!("{base}.{prop}")
+    }
+}
+
+fn simple_type(schema: &Value) -> Option<&str> {
+    schema.get("type").and_then(Value::as_str)
+}
+
+fn properties(schema: &Value) -> Map<String, Value> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Steps of a plan, collected by kind so they can be emitted in order.
+#[derive(Default)]
+struct PlanBuilder {
+    renames: Vec<GtsMigrationStep>,
+    coercions: Vec<GtsMigrationStep>,
+    defaults: Vec<GtsMigrationStep>,
+    drops: Vec<GtsMigrationStep>,
+    consts: Vec<GtsMigrationStep>,
+    warnings: Vec<String>,
+}
+
+impl PlanBuilder {
+    fn compare(&mut self, old: &Value, new: &Value, base: &str) {
+        let old = GtsEntityCastResult::flatten_schema(old);
+        let new = GtsEntityCastResult::flatten_schema(new);
+        let old_props = properties(&old);
+        let new_props = properties(&new);
+        let required: HashSet<&str> = new
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let closed = new.get("additionalProperties") == Some(&Value::Bool(false));
+
+        let mut renamed_from = HashSet::new();
+        for (prop, new_schema) in &new_props {
+            let path = join(base, prop);
+            let previous = new_schema
+                .get(RENAMED_FROM_KEY)
+                .and_then(Value::as_str)
+                .filter(|from| old_props.contains_key(*from) && !new_props.contains_key(*from));
+
+            let old_schema = if let Some(from) = previous {
+                renamed_from.insert(from.to_owned());
+                self.renames.push(GtsMigrationStep::Rename {
+                    from: join(base, from),
+                    to: path.clone(),
+                });
+                old_props.get(from)
+            } else {
+                old_props.get(prop)
+            };
+
+            match old_schema {
+                Some(old_schema) => self.compare_property(old_schema, new_schema, &path),
+                None => match new_schema.get("default") {
+                    Some(value) => self.defaults.push(GtsMigrationStep::AddDefault {
+                        path,
+                        value: value.clone(),
+                    }),
+                    None if required.contains(prop.as_str()) => self.warnings.push(format!(
+                        "required property '{path}' is new and has no default"
+                    )),
+                    None => {}
+                },
+            }
+        }
+
+        if closed {
+            for prop in old_props.keys() {
+                if !new_props.contains_key(prop) && !renamed_from.contains(prop) {
+                    self.drops.push(GtsMigrationStep::Drop {
+                        path: join(base, prop),
+                    });
+                }
+            }
+        }
+    }
+
+    fn compare_property(&mut self, old: &Value, new: &Value, path: &str) {
+        if let Some(value) = new.get("const")
+            && let (Some(new_id), Some(old_id)) =
+                (value.as_str(), old.get("const").and_then(Value::as_str))
+            && new_id != old_id
+            && GtsID::is_valid(new_id)
+        {
+            self.consts.push(GtsMigrationStep::SetConst {
+                path: path.to_owned(),
+                value: value.clone(),
+            });
+        }
+        match (simple_type(old), simple_type(new)) {
+            (Some("object"), Some("object")) => self.compare(old, new, path),
+            (Some(from), Some(to)) if from != to && !(from == "integer" && to == "number") => {
+                self.coercions.push(GtsMigrationStep::Coerce {
+                    path: path.to_owned(),
+                    to: to.to_owned(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The object holding the last segment of `path`, and that segment.
+fn parent_mut<'a>(
+    root: &'a mut Value,
+    path: &'a str,
+) -> Option<(&'a mut Map<String, Value>, &'a str)> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (Some(parent), key),
+        None => (None, path),
+    };
+    let mut node = root;
+    if let Some(parent) = parent {
+        for segment in parent.split('.') {
+            node = node.get_mut(segment)?;
+        }
+    }
+    Some((node.as_object_mut()?, key))
+}
+
+fn coerce(value: &Value, to: &str) -> Option<Value> {
+    if let Some(number) = numbers::coerce_number(value, &json!({"type": to})) {
+        return Some(number);
+    }
+    match (value, to) {
+        (Value::Null, _) => Some(Value::Null),
+        (Value::Number(n), "integer") if n.is_i64() || n.is_u64() => Some(value.clone()),
+        (Value::Number(_) | Value::Bool(_), "string") => Some(Value::String(value.to_string())),
+        (Value::String(s), "integer") => s.trim().parse::<i64>().ok().map(Value::from),
+        (Value::String(s), "number") => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        (Value::String(s), "boolean") => match s.trim() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (Value::Bool(b), "integer" | "number") => Some(Value::from(u8::from(*b))),
+        (Value::Number(n), "boolean") => Some(Value::Bool(n.as_f64() != Some(0.0))),
+        (v, "array") => Some(Value::Array(vec![v.clone()])),
+        _ => None,
+    }
+}
+
+impl GtsMigrationPlan {
+    /// Applies the plan to one instance.
+    ///
+    /// # Errors
+    /// Returns a description of the first step that cannot be applied.
+    pub fn apply(&self, instance: &Value) -> Result<Value, String> {
+        if !instance.is_object() {
+            return Err("instance must be an object".to_owned());
+        }
+        let mut out = instance.clone();
+        for step in &self.steps {
+            match step {
+                GtsMigrationStep::Rename { from, to } => {
+                    let value = parent_mut(&mut out, from).and_then(|(obj, key)| obj.remove(key));
+                    if let Some(value) = value {
+                        let (obj, key) = parent_mut(&mut out, to)
+                            .ok_or_else(|| format!("cannot rename '{from}' to '{to}'"))?;
+                        obj.insert(key.to_owned(), value);
+                    }
+                }
+                GtsMigrationStep::Coerce { path, to } => {
+                    if let Some((obj, key)) = parent_mut(&mut out, path)
+                        && let Some(value) = obj.get_mut(key)
+                    {
+                        *value = coerce(value, to)
+                            .ok_or_else(|| format!("cannot convert '{path}' ({value}) to {to}"))?;
+                    }
+                }
+                GtsMigrationStep::AddDefault { path, value } => {
+                    if let Some((obj, key)) = parent_mut(&mut out, path) {
+                        obj.entry(key.to_owned()).or_insert_with(|| value.clone());
+                    }
+                }
+                GtsMigrationStep::Drop { path } => {
+                    if let Some((obj, key)) = parent_mut(&mut out, path) {
+                        obj.remove(key);
+                    }
+                }
+                GtsMigrationStep::SetConst { path, value } => {
+                    if let Some((obj, key)) = parent_mut(&mut out, path)
+                        && let Some(current) = obj.get_mut(key)
+                    {
+                        value.clone_into(current);
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl GtsStore {
+    /// Plans the migration of instances from `from_schema` to `to_schema`.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if either schema is missing or has unresolvable references.
+    pub fn plan_migration(
+        &mut self,
+        from_schema: &str,
+        to_schema: &str,
+    ) -> Result<GtsMigrationPlan, StoreError> {
+        let [old, new] = [from_schema, to_schema].map(|id| {
+            let content = self.get_schema_content(id)?;
+            self.resolve_schema_refs_checked(&content)
+                .map_err(|e| StoreError::ValidationError(format!("Schema '{id}' has {e}")))
+        });
+        let mut builder = PlanBuilder::default();
+        builder.compare(&old?, &new?, "");
+
+        let PlanBuilder {
+            renames,
+            coercions,
+            defaults,
+            drops,
+            consts,
+            warnings,
+        } = builder;
+        let mut steps = renames;
+        steps.extend(coercions);
+        steps.extend(defaults);
+        steps.extend(drops);
+        steps.extend(consts);
+
+        Ok(GtsMigrationPlan {
+            from_schema: from_schema.to_owned(),
+            to_schema: to_schema.to_owned(),
+            steps,
+            warnings,
+        })
+    }
+
+    /// Applies `plan` to every instance of `plan.from_schema` in the store and
+    /// validates the results against `plan.to_schema`.
+    ///
+    /// The store is left unchanged; migrated content is returned for the
+    /// caller to persist. A top-level field holding the source schema ID
+    /// (typically `type`) is pointed at the target schema.
+    pub fn apply_migration(&mut self, plan: &GtsMigrationPlan) -> GtsMigrationReport {
+        let mut instances: Vec<(String, Value, Option<String>)> = self
+            .items()
+            .filter(|(_, e)| !e.is_schema && e.schema_id.as_deref() == Some(&plan.from_schema))
+            .map(|(id, e)| {
+                (
+                    id.clone(),
+                    e.content.clone(),
+                    e.selected_schema_id_field.clone(),
+                )
+            })
+            .collect();
+        instances.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut report = GtsMigrationReport {
+            from_schema: plan.from_schema.clone(),
+            to_schema: plan.to_schema.clone(),
+            migrated: Vec::new(),
+            failures: Vec::new(),
+        };
+        for (id, content, type_field) in instances {
+            let migrated = plan.apply(&content).and_then(|mut content| {
+                if let Some(field) = type_field
+                    && content.get(&field).and_then(Value::as_str) == Some(&plan.from_schema)
+                {
+                    content[field] = Value::String(plan.to_schema.clone());
+                }
+                self.validate_value(&plan.to_schema, &content)
+                    .map(|()| content)
+                    .map_err(|e| e.to_string())
+            });
+            match migrated {
+                Ok(content) => report.migrated.push(GtsMigratedInstance { id, content }),
+                Err(error) => report.failures.push(GtsValidationFailure {
+                    id,
+                    schema_id: plan.to_schema.clone(),
+                    error,
+                }),
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use crate::test_fixtures::schema;
+
+    const V1: &str = "gts.x.test.migrate.customer.v1~";
+    const V2: &str = "gts.x.test.migrate.customer.v2~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        let v1 = schema(
+            V1,
+            &json!({
+                "properties": {
+                    "id": {"type": "string"},
+                    "type": {"type": "string"},
+                    "name": {"type": "string"},
+                    "age": {"type": "string"},
+                    "fax": {"type": "string"},
+                    "address": {"type": "object", "properties": {"zip": {"type": "integer"}}}
+                }
+            }),
+        );
+        let v2 = schema(
+            V2,
+            &json!({
+                "required": ["id", "full_name", "tier"],
+                "additionalProperties": false,
+                "properties": {
+                    "id": {"type": "string"},
+                    "type": {"type": "string"},
+                    "full_name": {"type": "string", "x-gts-renamed-from": "name"},
+                    "age": {"type": "integer"},
+                    "tier": {"type": "string", "default": "basic"},
+                    "address": {"type": "object", "properties": {"zip": {"type": "string"}}}
+                }
+            }),
+        );
+        store.register_schema(V1, &v1).unwrap();
+        store.register_schema(V2, &v2).unwrap();
+        for (uuid, age) in [("a", "42"), ("b", "n/a")] {
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &json!({
+                    "id": format!("7a1d2f34-5678-49ab-9012-abcdef01234{uuid}"),
+                    "type": V1,
+                    "name": "Ada",
+                    "age": age,
+                    "fax": "555",
+                    "address": {"zip": 12345}
+                }),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                None,
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_plan_orders_steps() {
+        let mut store = store();
+        let plan = store.plan_migration(V1, V2).unwrap();
+        let mut coercions = plan.steps[1..3].to_vec();
+        coercions.sort_by_key(|s| format!("{s:?}"));
+        assert_eq!(
+            plan.steps[0],
+            GtsMigrationStep::Rename {
+                from: "name".to_owned(),
+                to: "full_name".to_owned()
+            }
+        );
+        assert_eq!(
+            coercions,
+            vec![
+                GtsMigrationStep::Coerce {
+                    path: "address.zip".to_owned(),
+                    to: "string".to_owned()
+                },
+                GtsMigrationStep::Coerce {
+                    path: "age".to_owned(),
+                    to: "integer".to_owned()
+                },
+            ]
+        );
+        assert_eq!(
+            plan.steps[3..],
+            [
+                GtsMigrationStep::AddDefault {
+                    path: "tier".to_owned(),
+                    value: json!("basic")
+                },
+                GtsMigrationStep::Drop {
+                    path: "fax".to_owned()
+                },
+            ]
+        );
+        assert!(plan.warnings.is_empty());
+
+        // Plans round-trip through JSON for review
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["steps"][0]["op"], "rename");
+        let parsed: GtsMigrationPlan = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.steps, plan.steps);
+    }
+
+    #[test]
+    fn test_apply_migration_to_store() {
+        let mut store = store();
+        let plan = store.plan_migration(V1, V2).unwrap();
+        let report = store.apply_migration(&plan);
+
+        assert_eq!(report.migrated.len(), 1);
+        assert_eq!(
+            report.migrated[0].content,
+            json!({
+                "id": "7a1d2f34-5678-49ab-9012-abcdef01234a",
+                "type": V2,
+                "full_name": "Ada",
+                "age": 42,
+                "tier": "basic",
+                "address": {"zip": "12345"}
+            })
+        );
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].error.contains("cannot convert 'age'"));
+    }
+}
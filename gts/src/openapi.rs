@@ -0,0 +1,204 @@
+//! `OpenAPI` 3.1 export of GTS schemas.
+//!
+//! [`GtsStore::export_openapi`] turns the schemas matching a pattern, plus
+//! the schemas they reference, into an `OpenAPI` document whose
+//! `components/schemas` API gateways and code generators can consume.  Each
+//! schema becomes a component named after its ID ([`component_name`]), and
+//! `gts://` references are rewritten to point at those components.
+
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value, json};
+
+use crate::gts::{GTS_PREFIX, GTS_URI_PREFIX, GtsID, GtsWildcard};
+use crate::store::{GtsStore, StoreError};
+use crate::validator_cache::schema_dependencies;
+
+/// `OpenAPI` version of exported documents.
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+
+/// The component name of a schema: its ID without the `gts.` prefix and
+/// trailing `~`, with segment separators turned into `-`, which GTS IDs
+/// never contain. E.g. `gts.x.core.events.type.v1~x.shop.order.v1~` becomes
+/// `x.core.events.type.v1-x.shop.order.v1`.
+#[must_use]
+pub(crate) fn component_name(schema_id: &str) -> String {
+    schema_id
+        .strip_prefix(GTS_PREFIX)
+        .unwrap_or(schema_id)
+        .trim_end_matches('~')
+        .replace('~', "-")
+}
+
+/// Rewrites the references in `value`, a schema exported as component
+/// `name`, to component references.
+fn rewrite_refs(value: &mut Value, name: &str) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(target)) = map.get_mut("$ref") {
+                if let Some(gts_ref) = target.strip_prefix(GTS_URI_PREFIX) {
+                    let (id, fragment) = gts_ref.split_once('#').unwrap_or((gts_ref, ""));
+                    *target = format!("{COMPONENTS_PREFIX}{}{fragment}", component_name(id));
+                } else if let Some(pointer) = target.strip_prefix('#') {
+                    // Local pointers lose their base along with `$id`
+                    *target = format!("{COMPONENTS_PREFIX}{name}{pointer}");
+                }
+            }
+            map.values_mut().for_each(|v| rewrite_refs(v, name));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rewrite_refs(v, name)),
+        _ => {}
+    }
+}
+
+impl GtsStore {
+    /// Exports the schemas matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact schema ID, and the schemas they reference,
+    /// as an `OpenAPI` 3.1 document with the given `info` title and version.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard, or `StoreError::UnresolvedReferences` if a referenced schema
+    /// is not in the store.
+    pub fn export_openapi(
+        &self,
+        pattern: &str,
+        title: &str,
+        version: &str,
+    ) -> Result<Value, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let selected = |id: &str| {
+            if pattern.contains('*') {
+                GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(&wildcard))
+            } else {
+                id == wildcard.id
+            }
+        };
+        let entities = self.entities();
+
+        let mut ids = BTreeSet::new();
+        let mut missing = BTreeSet::new();
+        for (id, entity) in entities {
+            if !entity.is_schema || !selected(id) {
+                continue;
+            }
+            for dep in schema_dependencies(entities, id, &entity.content) {
+                if entities.contains_key(&dep) {
+                    ids.insert(dep);
+                } else {
+                    missing.insert(format!("schema '{id}' references missing '{dep}'"));
+                }
+            }
+        }
+        if !missing.is_empty() {
+            return Err(StoreError::UnresolvedReferences(
+                missing.into_iter().collect(),
+            ));
+        }
+
+        let mut schemas = Map::new();
+        for id in ids {
+            let Some(entity) = entities.get(&id) else {
+                continue;
+            };
+            let name = component_name(&id);
+            let mut schema = entity.content.clone();
+            if let Some(map) = schema.as_object_mut() {
+                map.remove("$id");
+                map.remove("$schema");
+            }
+            rewrite_refs(&mut schema, &name);
+            schemas.insert(name, schema);
+        }
+        Ok(json!({
+            "openapi": OPENAPI_VERSION,
+            "info": {"title": title, "version": version},
+            "paths": {},
+            "components": {"schemas": schemas}
+        }))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "gts.x.test.oapi.event.v1~";
+    const CHILD: &str = "gts.x.test.oapi.event.v1~x.test.oapi.paid.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "definitions": {"money": {"type": "number"}},
+                    "properties": {"amount": {"$ref": "#/definitions/money"}}
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                CHILD,
+                &json!({
+                    "$id": format!("gts://{CHILD}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [
+                        {"$ref": format!("gts://{BASE}")},
+                        {"properties": {"fee": {"$ref": format!("gts://{BASE}#/definitions/money")}}}
+                    ]
+                }),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_component_name() {
+        assert_eq!(component_name(BASE), "x.test.oapi.event.v1");
+        assert_eq!(
+            component_name(CHILD),
+            "x.test.oapi.event.v1-x.test.oapi.paid.v1"
+        );
+    }
+
+    #[test]
+    fn test_export_openapi_rewrites_refs_and_pulls_in_dependencies() {
+        let doc = store().export_openapi(CHILD, "Events", "1.0.0").unwrap();
+        assert_eq!(doc["openapi"], OPENAPI_VERSION);
+        assert_eq!(doc["info"]["title"], "Events");
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        assert_eq!(schemas.len(), 2);
+
+        let base = &schemas["x.test.oapi.event.v1"];
+        assert!(base.get("$id").is_none() && base.get("$schema").is_none());
+        assert_eq!(
+            base["properties"]["amount"]["$ref"],
+            "#/components/schemas/x.test.oapi.event.v1/definitions/money"
+        );
+        let child = &schemas["x.test.oapi.event.v1-x.test.oapi.paid.v1"];
+        assert_eq!(
+            child["allOf"][0]["$ref"],
+            "#/components/schemas/x.test.oapi.event.v1"
+        );
+        assert_eq!(
+            child["allOf"][1]["properties"]["fee"]["$ref"],
+            "#/components/schemas/x.test.oapi.event.v1/definitions/money"
+        );
+
+        assert!(!doc.to_string().contains("gts://"));
+        assert!(
+            store().export_openapi("gts.x.none.*", "t", "1").unwrap()["components"]["schemas"]
+                .as_object()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
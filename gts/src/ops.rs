@@ -1,15 +1,26 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
+use crate::audit::{GtsMajorsAuditResult, GtsReferrer, GtsRefsAuditResult};
+use crate::config::{GtsConfigError, GtsConfigFile};
+use crate::diagnostic::GtsDiagnostic;
 use crate::entities::{GtsConfig, GtsEntity};
+use crate::explain::GtsValidationExplain;
 use crate::files_reader::GtsFileReader;
+use crate::files_writer::GtsFileWriter;
+use crate::fingerprint::canonical_value;
 use crate::gts::{GtsID, GtsWildcard};
+use crate::metrics::GtsStoreMetrics;
 use crate::path_resolver::JsonPathResolver;
+use crate::removal::{GtsRemovalBlocker, GtsRemovalStep};
 use crate::schema_cast::GtsEntityCastResult;
-use crate::store::{GtsStore, GtsStoreQueryResult};
+use crate::schema_resolution::GtsSchemaSubstitution;
+use crate::snapshot::line_diff;
+use crate::store::{GtsStore, GtsStoreQueryPage, GtsStoreQueryResult, SortOrder, StoreError};
+use crate::validate_all::GtsValidateAllReport;
+use crate::validation_report::GtsValidationIssue;
 
 /// `is_schema` is `Some(true)` for schema/type IDs (ending with `~`),
 /// `Some(false)` for instance IDs, and `None` when the input couldn't be
@@ -87,6 +98,17 @@ pub struct GtsValidationResult {
     pub ok: bool,
     #[serde(skip_serializing_if = "String::is_empty")]
     pub error: String,
+    /// Set when the instance was validated against a newer minor version of
+    /// its declared schema. See [`GtsStore::validate_instance_resolved`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub substitution: Option<GtsSchemaSubstitution>,
+    /// Each instance error with its instance and schema path, when validation
+    /// failed. See [`GtsStore::validate_instance_report`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<GtsValidationIssue>,
+    /// The failure as coded diagnostics, for tooling that filters by code.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<GtsDiagnostic>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +127,22 @@ pub struct GtsSchemaGraphResult {
     pub graph: Value,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsCastAllResult {
+    pub pattern: String,
+    pub to_schema_id: String,
+    /// Number of matching instances.
+    pub count: usize,
+    /// Instances cast without errors or incompatibilities.
+    pub casted: usize,
+    pub results: Vec<GtsEntityCastResult>,
+    /// Files the casted instances were written back to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub written: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GtsEntityInfo {
     pub id: String,
@@ -167,88 +205,266 @@ pub struct GtsExtractIdResult {
     pub is_schema: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsLabelsResult {
+    pub ok: bool,
+    pub id: String,
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// Entities referencing an ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsReferrersResult {
+    pub id: String,
+    pub count: usize,
+    pub referrers: Vec<GtsReferrer>,
+}
+
+/// Line diff of two registered entities, compared in canonical form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsSchemaDiffResult {
+    pub ok: bool,
+    pub old_id: String,
+    pub new_id: String,
+    /// Whether the canonical contents are equal.
+    pub identical: bool,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub diff: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsRemovalPlanResult {
+    pub ok: bool,
+    pub pattern: String,
+    /// Entities in safe removal order: dependents first.
+    pub steps: Vec<GtsRemovalStep>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsRemoveResult {
+    pub ok: bool,
+    pub pattern: String,
+    pub removed: Vec<String>,
+    /// Referrers that kept the removal from happening.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blockers: Vec<GtsRemovalBlocker>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A result of a [`GtsOps`] operation.  Bindings hand results to their host
+/// language as the JSON object returned by `to_dict`, which has the same
+/// shape as the CLI and HTTP server output.
+pub trait GtsOpsResult: Serialize {
+    fn to_dict(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+macro_rules! impl_ops_result {
+    ($($ty:ty),* $(,)?) => {
+        $(impl GtsOpsResult for $ty {})*
+    };
+}
+
+impl_ops_result!(
+    GtsIdValidationResult,
+    GtsIdParseResult,
+    GtsIdMatchResult,
+    GtsUuidResult,
+    GtsValidationResult,
+    GtsEntityValidationResult,
+    GtsSchemaGraphResult,
+    GtsCastAllResult,
+    GtsGetEntityResult,
+    GtsEntitiesListResult,
+    GtsAddEntityResult,
+    GtsAddEntitiesResult,
+    GtsAddSchemaResult,
+    GtsExtractIdResult,
+    GtsLabelsResult,
+    GtsReferrersResult,
+    GtsSchemaDiffResult,
+    GtsRemovalPlanResult,
+    GtsRemoveResult,
+    GtsEntityCastResult,
+    GtsValidationExplain,
+    GtsStoreQueryResult,
+    GtsStoreQueryPage,
+    JsonPathResolver,
+    GtsMajorsAuditResult,
+    GtsRefsAuditResult,
+    GtsValidateAllReport,
+    GtsStoreMetrics,
+);
+
 pub struct GtsOps {
     pub verbose: usize,
     pub cfg: GtsConfig,
     pub path: Option<Vec<String>>,
     pub store: GtsStore,
+    /// Principal that registrations are made on behalf of, checked against
+    /// the store's policy provider.
+    pub principal: Option<String>,
 }
 
 impl GtsOps {
+    /// Ops over the store at `path` with the config of `config_path`; see
+    /// [`GtsOps::try_new`].  A config that cannot be loaded is logged and
+    /// replaced by the defaults.
     #[must_use]
     pub fn new(path: Option<Vec<String>>, config: Option<String>, verbose: usize) -> Self {
-        let cfg = Self::load_config(config);
-        let reader: Option<Box<dyn crate::store::GtsReader>> = path.as_ref().map(|p| {
-            Box::new(GtsFileReader::new(p, Some(cfg.clone()))) as Box<dyn crate::store::GtsReader>
+        let cfg = Self::load_config(config).unwrap_or_else(|e| {
+            tracing::warn!("{e}; using the default config");
+            GtsConfig::default()
         });
-        let store = GtsStore::new(reader);
+        Self::with_config(path, cfg, verbose)
+    }
+
+    /// Ops over the store at `path`, else at the config's `schema_dir`.  The
+    /// config is that of `config_path`, else of the first config file in the
+    /// current directory, else the defaults; then `GTS_*` environment
+    /// overrides.
+    ///
+    /// # Errors
+    /// Returns `GtsConfigError` if the config file cannot be read or holds an
+    /// invalid value, or an override is invalid.
+    pub fn try_new(
+        path: Option<Vec<String>>,
+        config: Option<String>,
+        verbose: usize,
+    ) -> Result<Self, GtsConfigError> {
+        Ok(Self::with_config(path, Self::load_config(config)?, verbose))
+    }
+
+    fn with_config(path: Option<Vec<String>>, cfg: GtsConfig, verbose: usize) -> Self {
+        let path = path.or_else(|| cfg.schema_dir.clone().map(|dir| vec![dir]));
+        let store = if let Some(p) = &path {
+            Self::open_store(p, &cfg)
+        } else {
+            let mut store = GtsStore::new(None);
+            store.apply_config(&cfg);
+            store
+        };
 
         GtsOps {
             verbose,
             cfg,
             path,
             store,
+            principal: None,
         }
     }
 
-    fn load_config(config_path: Option<String>) -> GtsConfig {
-        // Try user-provided path
-        if let Some(path) = config_path
-            && let Ok(cfg) = Self::load_config_from_path(&PathBuf::from(path))
-        {
-            return cfg;
+    fn load_config(config_path: Option<String>) -> Result<GtsConfig, GtsConfigError> {
+        GtsConfig::load(config_path.map(PathBuf::from).as_deref())
+    }
+
+    /// The config holding the settings of `data`, the top-level keys of a
+    /// config file; keys it leaves out keep their defaults.
+    ///
+    /// # Errors
+    /// Returns a message naming the first key whose value cannot be used.
+    pub(crate) fn create_config_from_data(
+        data: &HashMap<String, Value>,
+    ) -> Result<GtsConfig, String> {
+        fn setting<T: serde::de::DeserializeOwned>(
+            data: &HashMap<String, Value>,
+            key: &str,
+        ) -> Result<Option<T>, String> {
+            data.get(key)
+                .map(|v| serde_json::from_value(v.clone()).map_err(|e| format!("'{key}': {e}")))
+                .transpose()
         }
 
-        // Try default path (relative to current directory)
-        let default_path = PathBuf::from("gts.config.json");
-        if let Ok(cfg) = Self::load_config_from_path(&default_path) {
-            return cfg;
+        let mut cfg = GtsConfig::default();
+        if let Some(fields) = setting(data, "entity_id_fields")? {
+            cfg.entity_id_fields = fields;
         }
-
-        // Fall back to defaults
-        GtsConfig::default()
-    }
-
-    fn load_config_from_path(path: &PathBuf) -> Result<GtsConfig, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let data: HashMap<String, Value> = serde_json::from_str(&content)?;
-        Ok(Self::create_config_from_data(&data))
+        if let Some(fields) = setting(data, "schema_id_fields")? {
+            cfg.schema_id_fields = fields;
+        }
+        if let Some(id_fields) = setting(data, "id_fields")? {
+            cfg.id_fields = id_fields;
+        }
+        if let Some(numbers) = setting(data, "numbers")? {
+            cfg.numbers = numbers;
+        }
+        if let Some(aliases) = setting(data, "aliases")? {
+            cfg.aliases = aliases;
+        }
+        cfg.sandbox = setting(data, "sandbox")?;
+        if let Some(schema_resolution) = setting(data, "schema_resolution")? {
+            cfg.schema_resolution = schema_resolution;
+        }
+        if let Some(ref_integrity) = setting(data, "ref_integrity")? {
+            cfg.ref_integrity = ref_integrity;
+        }
+        if let Some(retired_schemas) = setting(data, "retired_schemas")? {
+            cfg.retired_schemas = retired_schemas;
+        }
+        if let Some(namespaces) = setting(data, "namespaces")? {
+            cfg.namespaces = namespaces;
+        }
+        cfg.schema_dir = setting(data, "schema_dir")?;
+        cfg.dialect = setting::<String>(data, "dialect")?
+            .map(|d| crate::config::dialect_uri(&d).map_err(|e| format!("'dialect': {e}")))
+            .transpose()?;
+        if let Some(strict) = setting(data, "strict")? {
+            cfg.strict = strict;
+        }
+        Ok(cfg)
     }
 
-    fn create_config_from_data(data: &HashMap<String, Value>) -> GtsConfig {
-        let default_cfg = GtsConfig::default();
-
-        let entity_id_fields = data
-            .get("entity_id_fields")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(ToString::to_string))
-                    .collect()
-            })
-            .unwrap_or(default_cfg.entity_id_fields);
-
-        let schema_id_fields = data
-            .get("schema_id_fields")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(ToString::to_string))
-                    .collect()
-            })
-            .unwrap_or(default_cfg.schema_id_fields);
-
-        GtsConfig {
-            entity_id_fields,
-            schema_id_fields,
-        }
+    /// Expands an `@alias` from the config, or an unambiguous ID suffix,
+    /// to a full entity ID. See [`GtsStore::resolve_id`].
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the alias is unknown or the suffix is ambiguous.
+    pub fn resolve_id(&self, input: &str) -> Result<String, StoreError> {
+        self.store.resolve_id(input, &self.cfg.aliases)
     }
 
     pub fn reload_from_path(&mut self, path: &[String]) {
         self.path = Some(path.to_vec());
-        let reader = Box::new(GtsFileReader::new(path, Some(self.cfg.clone())))
-            as Box<dyn crate::store::GtsReader>;
-        self.store = GtsStore::new(Some(reader));
+        self.store = Self::open_store(path, &self.cfg);
+    }
+
+    /// A store reading from `path` and writing new files to its first entry.
+    fn open_store(path: &[String], cfg: &GtsConfig) -> GtsStore {
+        let reader = GtsFileReader::new(path, Some(cfg.clone()));
+        #[cfg(feature = "encryption")]
+        let cipher = crate::encryption::GtsContentCipher::from_env(cfg);
+        #[cfg(feature = "encryption")]
+        let reader = match &cipher {
+            Some(cipher) => reader.with_cipher(cipher.clone()),
+            None => reader,
+        };
+        let mut store = GtsStore::new(Some(Box::new(reader)));
+        store.apply_config(cfg);
+        if let Some(root) = path.first() {
+            let root = match Path::new(root).parent() {
+                Some(parent) if Path::new(root).is_file() => parent.to_string_lossy(),
+                _ => match GtsFileReader::split_glob(Path::new(root)) {
+                    Some((base, _)) => base.to_string_lossy().to_string().into(),
+                    None => root.into(),
+                },
+            };
+            let writer = GtsFileWriter::new(&root);
+            #[cfg(feature = "encryption")]
+            let writer = match cipher {
+                Some(cipher) => writer.with_cipher(cipher),
+                None => writer,
+            };
+            store.set_writer(Box::new(writer));
+        }
+        store
     }
 
     fn get_details(&mut self, entity: &GtsEntity) -> String {
@@ -307,7 +523,10 @@ impl GtsOps {
         };
 
         // Register the entity first
-        if let Err(e) = self.store.register(entity.clone()) {
+        if let Err(e) = self
+            .store
+            .register_as(self.principal.as_deref(), entity.clone())
+        {
             return GtsAddEntityResult {
                 ok: false,
                 id: String::new(),
@@ -372,7 +591,10 @@ impl GtsOps {
     }
 
     pub fn add_schema(&mut self, type_id: String, schema: &Value) -> GtsAddSchemaResult {
-        match self.store.register_schema(&type_id, schema) {
+        match self
+            .store
+            .register_schema_as(self.principal.as_deref(), &type_id, schema)
+        {
             Ok(()) => GtsAddSchemaResult {
                 ok: true,
                 id: type_id,
@@ -569,20 +791,71 @@ impl GtsOps {
     }
 
     pub fn validate_instance(&mut self, gts_id: &str) -> GtsValidationResult {
-        match self.store.validate_instance(gts_id) {
-            Ok(()) => GtsValidationResult {
+        match self.store.validate_instance_resolved(gts_id) {
+            Ok(substitution) => GtsValidationResult {
                 id: gts_id.to_owned(),
                 ok: true,
                 error: String::new(),
+                substitution,
+                errors: Vec::new(),
+                diagnostics: Vec::new(),
             },
             Err(e) => GtsValidationResult {
                 id: gts_id.to_owned(),
                 ok: false,
                 error: e.to_string(),
+                substitution: None,
+                errors: self
+                    .store
+                    .validate_instance_report(gts_id)
+                    .map(|report| report.errors)
+                    .unwrap_or_default(),
+                diagnostics: e.diagnostics(),
+            },
+        }
+    }
+
+    /// Validates content that is not registered, e.g. an instance still being
+    /// edited, against a registered schema.  `id` is the ID found in the
+    /// content, if any.
+    pub fn validate_value(&mut self, schema_id: &str, content: &Value) -> GtsValidationResult {
+        let id = self.extract_id(content).id;
+        match self.store.validate_value(schema_id, content) {
+            Ok(()) => GtsValidationResult {
+                id,
+                ok: true,
+                error: String::new(),
+                substitution: None,
+                errors: Vec::new(),
+                diagnostics: Vec::new(),
+            },
+            Err(e) => GtsValidationResult {
+                id,
+                ok: false,
+                error: e.to_string(),
+                substitution: None,
+                errors: self
+                    .store
+                    .validate_value_report(schema_id, content)
+                    .map(|report| report.errors)
+                    .unwrap_or_default(),
+                diagnostics: e.diagnostics(),
             },
         }
     }
 
+    pub fn validate_instance_explain(&mut self, gts_id: &str) -> GtsValidationExplain {
+        self.store
+            .validate_instance_explain(gts_id)
+            .unwrap_or_else(|e| GtsValidationExplain {
+                id: gts_id.to_owned(),
+                schema_id: None,
+                ok: false,
+                errors: Vec::new(),
+                error: e.to_string(),
+            })
+    }
+
     pub fn validate_schema(&mut self, gts_id: &str) -> GtsValidationResult {
         // First run basic schema validation (meta-schema, refs, etc.)
         if let Err(e) = self.store.validate_schema(gts_id) {
@@ -590,6 +863,9 @@ impl GtsOps {
                 id: gts_id.to_owned(),
                 ok: false,
                 error: e.to_string(),
+                substitution: None,
+                errors: Vec::new(),
+                diagnostics: e.diagnostics(),
             };
         }
 
@@ -599,6 +875,9 @@ impl GtsOps {
                 id: gts_id.to_owned(),
                 ok: false,
                 error: e.to_string(),
+                substitution: None,
+                errors: Vec::new(),
+                diagnostics: e.diagnostics(),
             };
         }
 
@@ -608,11 +887,17 @@ impl GtsOps {
                 id: gts_id.to_owned(),
                 ok: true,
                 error: String::new(),
+                substitution: None,
+                errors: Vec::new(),
+                diagnostics: Vec::new(),
             },
             Err(e) => GtsValidationResult {
                 id: gts_id.to_owned(),
                 ok: false,
                 error: e.to_string(),
+                substitution: None,
+                errors: Vec::new(),
+                diagnostics: e.diagnostics(),
             },
         }
     }
@@ -696,11 +981,191 @@ impl GtsOps {
         }
     }
 
+    /// Casts every instance whose ID (or, for anonymous instances, schema ID)
+    /// matches `pattern` to `to_schema_id`. With `write_back`, successfully
+    /// cast instances replace their originals in the store and are persisted
+    /// through the store's writer.
+    pub fn cast_all(
+        &mut self,
+        pattern: &str,
+        to_schema_id: &str,
+        write_back: bool,
+    ) -> GtsCastAllResult {
+        self.cast_all_with_progress(pattern, to_schema_id, write_back, |_, _| {})
+    }
+
+    /// Like [`GtsOps::cast_all`], calling `on_cast(done, total)` after each
+    /// instance.
+    pub fn cast_all_with_progress(
+        &mut self,
+        pattern: &str,
+        to_schema_id: &str,
+        write_back: bool,
+        mut on_cast: impl FnMut(usize, usize),
+    ) -> GtsCastAllResult {
+        let mut result = GtsCastAllResult {
+            pattern: pattern.to_owned(),
+            to_schema_id: to_schema_id.to_owned(),
+            count: 0,
+            casted: 0,
+            results: Vec::new(),
+            written: Vec::new(),
+            error: String::new(),
+        };
+        let wildcard = match GtsWildcard::new(pattern) {
+            Ok(wildcard) => wildcard,
+            Err(e) => {
+                result.error = format!("Invalid pattern: {e}");
+                return result;
+            }
+        };
+
+        let mut ids: Vec<String> = self
+            .store
+            .items()
+            .filter(|(_, e)| !e.is_schema)
+            .filter(|(_, e)| match &e.gts_id {
+                Some(gts_id) => gts_id.wildcard_match(&wildcard),
+                None => e
+                    .schema_id
+                    .as_deref()
+                    .and_then(|s| GtsID::new(s).ok())
+                    .is_some_and(|s| s.wildcard_match(&wildcard)),
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+
+        let mut failed_writes = Vec::new();
+        let total = ids.len();
+        for (done, id) in ids.into_iter().enumerate() {
+            let cast = self.cast(&id, to_schema_id);
+            let ok = cast.error.is_none() && cast.incompatibility_reasons.is_empty();
+            if ok {
+                result.casted += 1;
+                if write_back && let Some(content) = &cast.casted_entity {
+                    match self.write_back(&id, content) {
+                        Ok(path) if !result.written.contains(&path) => result.written.push(path),
+                        Ok(_) => {}
+                        Err(e) => failed_writes.push(format!("{id}: {e}")),
+                    }
+                }
+            }
+            result.results.push(cast);
+            on_cast(done + 1, total);
+        }
+        result.count = result.results.len();
+        if !failed_writes.is_empty() {
+            result.error = format!("Failed to write: {}", failed_writes.join("; "));
+        }
+        result
+    }
+
+    /// Replaces the content of `id` in the store and persists it.
+    fn write_back(&mut self, id: &str, content: &Value) -> Result<String, StoreError> {
+        let mut entity = self
+            .store
+            .get(id)
+            .cloned()
+            .ok_or_else(|| StoreError::EntityNotFound(id.to_owned()))?;
+        content.clone_into(&mut entity.content);
+        self.store.register_as(self.principal.as_deref(), entity)?;
+        self.store.persist(id)
+    }
+
     #[must_use]
     pub fn query(&self, expr: &str, limit: usize) -> GtsStoreQueryResult {
         self.store.query(expr, limit)
     }
 
+    #[must_use]
+    pub fn query_with_labels(
+        &self,
+        expr: &str,
+        labels: &BTreeMap<String, String>,
+        limit: usize,
+    ) -> GtsStoreQueryResult {
+        self.store.query_with_labels(expr, labels, limit)
+    }
+
+    #[must_use]
+    pub fn query_sorted(
+        &self,
+        expr: &str,
+        field: &str,
+        order: SortOrder,
+        limit: usize,
+    ) -> GtsStoreQueryResult {
+        self.store.query_sorted(expr, field, order, limit)
+    }
+
+    #[must_use]
+    pub fn query_page(
+        &self,
+        expr: &str,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> GtsStoreQueryPage {
+        self.store.query_page(expr, cursor, page_size)
+    }
+
+    /// Parses a label selector such as `env=prod,tier=gold`. Entries without
+    /// `=` are ignored.
+    #[must_use]
+    pub fn parse_label_selector(selector: &str) -> BTreeMap<String, String> {
+        selector
+            .split(',')
+            .filter_map(|part| part.split_once('='))
+            .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+            .filter(|(k, _)| !k.is_empty())
+            .collect()
+    }
+
+    /// Replaces the labels of an entity with the given ones; an empty map
+    /// clears them.
+    pub fn set_labels(
+        &mut self,
+        gts_id: &str,
+        labels: &BTreeMap<String, String>,
+    ) -> GtsLabelsResult {
+        if self.store.get(gts_id).is_none() {
+            return self.get_labels(gts_id);
+        }
+        for key in self.store.labels(gts_id).into_keys() {
+            if !labels.contains_key(&key) {
+                self.store.unlabel(gts_id, &key);
+            }
+        }
+        for (key, value) in labels {
+            if let Err(e) = self.store.label(gts_id, key, value) {
+                return GtsLabelsResult {
+                    ok: false,
+                    id: gts_id.to_owned(),
+                    labels: BTreeMap::new(),
+                    error: e.to_string(),
+                };
+            }
+        }
+        self.get_labels(gts_id)
+    }
+
+    pub fn get_labels(&mut self, gts_id: &str) -> GtsLabelsResult {
+        if self.store.get(gts_id).is_none() {
+            return GtsLabelsResult {
+                ok: false,
+                id: gts_id.to_owned(),
+                labels: BTreeMap::new(),
+                error: format!("Entity '{gts_id}' not found"),
+            };
+        }
+        GtsLabelsResult {
+            ok: true,
+            id: gts_id.to_owned(),
+            labels: self.store.labels(gts_id),
+            error: String::new(),
+        }
+    }
+
     pub fn attr(&mut self, gts_with_path: &str) -> JsonPathResolver {
         match GtsID::split_at_path(gts_with_path) {
             Ok((gts, Some(path))) => {
@@ -766,7 +1231,11 @@ impl GtsOps {
 
     #[must_use]
     pub fn get_entities(&self, limit: usize) -> GtsEntitiesListResult {
-        let all_entities: Vec<_> = self.store.items().collect();
+        let all_entities: Vec<_> = self
+            .store
+            .items()
+            .filter(|(id, _)| self.store.is_visible(id))
+            .collect();
         let total = all_entities.len();
 
         let entities: Vec<GtsEntityInfo> = all_entities
@@ -792,6 +1261,115 @@ impl GtsOps {
     pub fn list(&self, limit: usize) -> GtsEntitiesListResult {
         self.get_entities(limit)
     }
+
+    #[must_use]
+    pub fn audit_majors(&self) -> GtsMajorsAuditResult {
+        self.store.audit_majors()
+    }
+
+    #[must_use]
+    pub fn audit_refs(&self) -> GtsRefsAuditResult {
+        self.store.audit_refs()
+    }
+
+    #[must_use]
+    pub fn referrers(&self, gts_id: &str) -> GtsReferrersResult {
+        let referrers = self.store.referrers(gts_id);
+        GtsReferrersResult {
+            id: gts_id.to_owned(),
+            count: referrers.len(),
+            referrers,
+        }
+    }
+
+    /// Diffs the registered content of `old_id` against `new_id`.
+    pub fn diff(&mut self, old_id: &str, new_id: &str) -> GtsSchemaDiffResult {
+        let mut result = GtsSchemaDiffResult {
+            ok: false,
+            old_id: old_id.to_owned(),
+            new_id: new_id.to_owned(),
+            identical: false,
+            diff: String::new(),
+            error: String::new(),
+        };
+        let mut render = |id: &str| {
+            let entity = self.store.get(id)?;
+            serde_json::to_string_pretty(&canonical_value(&entity.content)).ok()
+        };
+        match (render(old_id), render(new_id)) {
+            (Some(old), Some(new)) => {
+                result.ok = true;
+                result.identical = old == new;
+                if !result.identical {
+                    result.diff = line_diff(&(old + "\n"), &(new + "\n"));
+                }
+            }
+            (None, _) => result.error = format!("Entity not found: {old_id}"),
+            (_, None) => result.error = format!("Entity not found: {new_id}"),
+        }
+        result
+    }
+
+    /// The dependency graph of all schemas, and of instances too when
+    /// `include_instances` is set.
+    #[must_use]
+    pub fn dependency_graph(&self, include_instances: bool) -> GtsSchemaGraphResult {
+        GtsSchemaGraphResult {
+            graph: self.store.dependency_graph(include_instances).to_json(),
+        }
+    }
+
+    pub fn validate_all(&mut self) -> GtsValidateAllReport {
+        self.store.validate_all()
+    }
+
+    #[must_use]
+    pub fn plan_removal(&self, pattern: &str) -> GtsRemovalPlanResult {
+        match self.store.plan_removal(pattern) {
+            Ok(plan) => GtsRemovalPlanResult {
+                ok: true,
+                pattern: plan.pattern,
+                steps: plan.steps,
+                error: String::new(),
+            },
+            Err(e) => GtsRemovalPlanResult {
+                ok: false,
+                pattern: pattern.to_owned(),
+                steps: Vec::new(),
+                error: e.to_string(),
+            },
+        }
+    }
+
+    /// Removes the entities matching `pattern` from the store; see
+    /// [`GtsStore::remove_matching`].
+    pub fn remove(&mut self, pattern: &str, force: bool) -> GtsRemoveResult {
+        let mut result = GtsRemoveResult {
+            ok: false,
+            pattern: pattern.to_owned(),
+            removed: Vec::new(),
+            blockers: Vec::new(),
+            error: String::new(),
+        };
+        match self.store.remove_matching(pattern, force) {
+            Ok(removed) => {
+                result.ok = true;
+                result.removed = removed;
+            }
+            Err(e) => {
+                result.error = e.to_string();
+                if let StoreError::RemovalBlocked(blockers) = e {
+                    result.blockers = blockers;
+                }
+            }
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn metrics(&self) -> GtsStoreMetrics {
+        self.store.metrics()
+    }
 }
 
 #[cfg(test)]
@@ -800,6 +1378,7 @@ mod tests {
     use super::*;
     use crate::gts::GtsID;
     use serde_json::json;
+    use std::fs;
 
     #[test]
     fn test_validate_id_valid() {
@@ -887,6 +1466,83 @@ mod tests {
         assert!(result.results.is_empty());
     }
 
+    #[test]
+    fn test_remove_blockers_match_referrers() {
+        let mut ops = GtsOps::new(None, None, 0);
+        let base = "gts.x.test.agree.event.v1~";
+        let schemas = [
+            (base, json!({"type": "object"})),
+            (
+                "gts.x.test.agree.event.v1~x.test.agree.placed.v1~",
+                json!({"type": "object"}),
+            ),
+            (
+                "gts.x.test.agree.audit.v1~",
+                json!({"properties": {"event": {"type": "string", "x-gts-ref": "gts.x.test.agree.event.*"}}}),
+            ),
+            (
+                "gts.x.test.agree.log.v1~",
+                json!({"allOf": [{"$ref": format!("gts://{base}")}]}),
+            ),
+        ];
+        for (id, body) in schemas {
+            let schema = crate::test_fixtures::schema(id, &body);
+            assert!(ops.add_schema(id.to_owned(), &schema).ok, "{id}");
+        }
+        let instance = json!({"id": format!("{base}x.test._.e1.v1"), "type": base});
+        assert!(ops.add_entity(&instance, false).ok);
+
+        let mut referrers: Vec<String> = ops
+            .referrers(base)
+            .referrers
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        referrers.dedup();
+        let Err(StoreError::RemovalBlocked(blockers)) = ops.store.remove(base, false) else {
+            panic!("expected RemovalBlocked");
+        };
+        let mut blockers: Vec<String> = blockers.into_iter().map(|b| b.id).collect();
+        blockers.dedup();
+        assert_eq!(blockers, referrers);
+        assert_eq!(blockers.len(), 4, "{blockers:?}");
+    }
+
+    #[test]
+    fn test_labels_roundtrip() {
+        let mut ops = GtsOps::new(None, None, 0);
+        let schema_id = "gts.x.test.labels.item.v1~";
+        ops.add_schema(
+            schema_id.to_owned(),
+            &json!({
+                "$id": schema_id,
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            }),
+        );
+
+        let selector = GtsOps::parse_label_selector("env=prod, tier = gold,bogus");
+        assert_eq!(selector.len(), 2);
+        let result = ops.set_labels(schema_id, &selector);
+        assert!(result.ok);
+        assert_eq!(result.labels, selector);
+
+        assert_eq!(ops.query_with_labels("gts.x.*", &selector, 10).count, 1);
+
+        let replaced = GtsOps::parse_label_selector("env=dev");
+        assert_eq!(ops.set_labels(schema_id, &replaced).labels, replaced);
+        assert!(
+            ops.set_labels(schema_id, &BTreeMap::new())
+                .labels
+                .is_empty()
+        );
+        assert!(
+            !ops.set_labels("gts.x.test.labels.missing.v1~", &selector)
+                .ok
+        );
+        assert!(!ops.get_labels("gts.x.test.labels.missing.v1~").ok);
+    }
+
     #[test]
     fn test_gts_id_validation() {
         assert!(!GtsID::is_valid("gts.vendor.package.namespace.type.v1.0")); // Single-segment instance - should be invalid
@@ -1166,6 +1822,63 @@ mod tests {
         assert_eq!(result.to_id, "gts.test.compat.v1.1~");
     }
 
+    #[test]
+    fn test_cast_all_writes_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = |minor: u32, props: Value| {
+            json!({
+                "$id": format!("gts://gts.x.test.bulk.item.v1.{minor}~"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": props
+            })
+        };
+        fs::write(
+            dir.path().join("v1_0.schema.json"),
+            schema(0, json!({"id": {"type": "string"}})).to_string(),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("v1_1.schema.json"),
+            schema(
+                1,
+                json!({"id": {"type": "string"}, "color": {"type": "string", "default": "red"}}),
+            )
+            .to_string(),
+        )
+        .unwrap();
+        let items = dir.path().join("items.json");
+        fs::write(
+            &items,
+            json!([
+                {"id": "gts.x.test.bulk.item.v1.0~x.test._.one.v1"},
+                {"id": "gts.x.test.bulk.item.v1.0~x.test._.two.v1", "color": "blue"}
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let path = dir.path().to_string_lossy().into_owned();
+        let mut ops = GtsOps::new(Some(vec![path]), None, 0);
+        let result = ops.cast_all(
+            "gts.x.test.bulk.item.v1.0~*",
+            "gts.x.test.bulk.item.v1.1~",
+            true,
+        );
+        assert!(result.error.is_empty(), "{}", result.error);
+        assert_eq!(result.count, 2);
+        assert_eq!(result.casted, 2);
+        assert_eq!(result.results[0].added_properties, vec!["color"]);
+        assert_eq!(result.written, vec![items.to_string_lossy().into_owned()]);
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&items).unwrap()).unwrap();
+        assert_eq!(written[0]["color"], "red");
+        assert_eq!(written[1]["color"], "blue");
+
+        let invalid = ops.cast_all("gts.x.[", "gts.x.test.bulk.item.v1.1~", false);
+        assert!(invalid.error.starts_with("Invalid pattern"));
+    }
+
     /// Helper to convert a serializable value to a JSON object for testing
     fn to_json_obj<T: serde::Serialize>(value: &T) -> serde_json::Map<String, Value> {
         match serde_json::to_value(value).expect("test") {
@@ -1320,6 +2033,9 @@ mod tests {
             id: "gts.vendor.package.namespace.type.v1.0".to_owned(),
             ok: true,
             error: String::new(),
+            substitution: None,
+            errors: Vec::new(),
+            diagnostics: Vec::new(),
         };
 
         let json = to_json_obj(&result);
@@ -3137,15 +3853,112 @@ mod tests {
             json!(["$schema", "$id", "schemaId"]),
         );
 
-        let config = GtsOps::create_config_from_data(&data);
+        let config = GtsOps::create_config_from_data(&data).unwrap();
         assert_eq!(config.entity_id_fields, vec!["customId", "uuid", "id"]);
         assert_eq!(config.schema_id_fields, vec!["$schema", "$id", "schemaId"]);
     }
 
+    #[test]
+    fn test_create_config_from_data_with_aliases() {
+        let mut data = HashMap::new();
+        data.insert(
+            "aliases".to_owned(),
+            json!({"order": "gts.x.shop.orders.order.v1~"}),
+        );
+
+        let mut ops = GtsOps::new(None, None, 0);
+        ops.cfg = GtsOps::create_config_from_data(&data).unwrap();
+        assert_eq!(
+            ops.resolve_id("@order").unwrap(),
+            "gts.x.shop.orders.order.v1~"
+        );
+        assert!(ops.resolve_id("@missing").is_err());
+    }
+
+    #[test]
+    fn test_create_config_from_data_with_retired_schemas() {
+        use crate::lifecycle::GtsRetiredSchemaPolicy;
+
+        let mut data = HashMap::new();
+        data.insert("retired_schemas".to_owned(), json!("warn"));
+        assert_eq!(
+            GtsOps::create_config_from_data(&data)
+                .unwrap()
+                .retired_schemas,
+            GtsRetiredSchemaPolicy::Warn
+        );
+    }
+
+    #[test]
+    fn test_create_config_from_data_with_namespaces() {
+        let mut data = HashMap::new();
+        data.insert(
+            "namespaces".to_owned(),
+            json!([{"pattern": "gts.acme.*", "owners": ["acme-signer"]}]),
+        );
+        let mut ops = GtsOps::new(None, None, 0);
+        ops.cfg = GtsOps::create_config_from_data(&data).unwrap();
+        ops.store.set_namespace_rules(ops.cfg.namespaces.clone());
+
+        let schema = json!({
+            "$id": "gts://gts.acme.billing.invoices.invoice.v1~",
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        let denied = ops.add_entity(&schema, false);
+        assert!(!denied.ok);
+        assert!(denied.error.contains("denied"), "{}", denied.error);
+
+        ops.principal = Some("acme-signer".to_owned());
+        assert!(ops.add_entity(&schema, false).ok);
+    }
+
+    #[test]
+    fn test_create_config_from_data_with_schema_resolution() {
+        use crate::schema_resolution::GtsSchemaResolution;
+
+        let mut data = HashMap::new();
+        assert_eq!(
+            GtsOps::create_config_from_data(&data)
+                .unwrap()
+                .schema_resolution,
+            GtsSchemaResolution::Exact
+        );
+        data.insert("schema_resolution".to_owned(), json!("nearest_minor"));
+        assert_eq!(
+            GtsOps::create_config_from_data(&data)
+                .unwrap()
+                .schema_resolution,
+            GtsSchemaResolution::NearestMinor
+        );
+    }
+
+    #[test]
+    fn test_create_config_from_data_with_sandbox() {
+        let mut data = HashMap::new();
+        assert!(
+            GtsOps::create_config_from_data(&data)
+                .unwrap()
+                .sandbox
+                .is_none()
+        );
+
+        data.insert("sandbox".to_owned(), json!({"max_enum_values": 10}));
+        let sandbox = GtsOps::create_config_from_data(&data)
+            .unwrap()
+            .sandbox
+            .unwrap();
+        assert_eq!(sandbox.max_enum_values, 10);
+        assert_eq!(
+            sandbox.max_validation_steps,
+            crate::GtsSandboxLimits::default().max_validation_steps
+        );
+    }
+
     #[test]
     fn test_create_config_from_data_with_empty_data() {
         let data = HashMap::new();
-        let config = GtsOps::create_config_from_data(&data);
+        let config = GtsOps::create_config_from_data(&data).unwrap();
 
         // Should use default config values
         let default_cfg = GtsConfig::default();
@@ -3155,17 +3968,19 @@ mod tests {
 
     #[test]
     fn test_create_config_from_data_with_invalid_types() {
-        let mut data = HashMap::new();
-        // Non-array value should be ignored
-        data.insert("entity_id_fields".to_owned(), json!("not-an-array"));
-        data.insert("schema_id_fields".to_owned(), json!(123));
-
-        let config = GtsOps::create_config_from_data(&data);
-
-        // Should fall back to default values
-        let default_cfg = GtsConfig::default();
-        assert_eq!(config.entity_id_fields, default_cfg.entity_id_fields);
-        assert_eq!(config.schema_id_fields, default_cfg.schema_id_fields);
+        let invalid = [
+            ("entity_id_fields", json!("not-an-array")),
+            ("schema_id_fields", json!(123)),
+            ("dialect", json!("draft99")),
+            ("strict", json!("sometimes")),
+            ("sandbox", json!({"max_enum_values": "many"})),
+            ("numbers", json!(true)),
+        ];
+        for (key, value) in invalid {
+            let data = HashMap::from([(key.to_owned(), value)]);
+            let err = GtsOps::create_config_from_data(&data).unwrap_err();
+            assert!(err.contains(key), "{err}");
+        }
     }
 
     #[test]
@@ -3437,4 +4252,68 @@ mod tests {
         assert_eq!(result.id, "gts.test.get.entity.success.v1~");
         assert!(result.is_schema);
     }
+
+    #[test]
+    fn test_validate_value_reports_unregistered_content() {
+        let mut ops = GtsOps::new(None, None, 0);
+        let schema_id = "gts.test.ops.value.item.v1~";
+        let schema = json!({
+            "$id": format!("gts://{schema_id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {"n": {"type": "integer"}}
+        });
+        assert!(ops.add_entity(&schema, false).ok);
+
+        let draft = json!({"id": format!("{schema_id}x.test._.draft.v1"), "n": "x"});
+        let result = ops.validate_value(schema_id, &draft);
+        assert!(!result.ok);
+        assert_eq!(result.id, format!("{schema_id}x.test._.draft.v1"));
+        assert_eq!(result.errors.len(), 1);
+        assert!(ops.validate_value(schema_id, &json!({"n": 1})).ok);
+        assert!(ops.get_entity(&result.id).content.is_none());
+    }
+
+    #[test]
+    fn test_diff_remove_and_to_dict() {
+        let mut ops = GtsOps::new(None, None, 0);
+        for (version, extra) in [("v1", json!({})), ("v2", json!({"required": ["id"]}))] {
+            let mut schema = json!({
+                "$id": format!("gts://gts.test.ops.facade.item.{version}~"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            });
+            schema
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            assert!(ops.add_entity(&schema, false).ok);
+        }
+
+        let diff = ops.diff(
+            "gts.test.ops.facade.item.v1~",
+            "gts.test.ops.facade.item.v2~",
+        );
+        assert!(diff.ok && !diff.identical);
+        assert!(diff.diff.contains("+  \"required\": ["), "{}", diff.diff);
+        let missing = ops.diff(
+            "gts.test.ops.facade.item.v1~",
+            "gts.test.ops.missing.item.v1~",
+        );
+        assert_eq!(
+            missing.to_dict()["error"],
+            "Entity not found: gts.test.ops.missing.item.v1~"
+        );
+
+        let plan = ops.plan_removal("gts.test.ops.facade.*");
+        assert!(plan.ok);
+        assert_eq!(plan.steps.len(), 2);
+        let removed = ops.remove("gts.test.ops.facade.item.v1~", false);
+        assert_eq!(
+            removed.to_dict()["removed"],
+            json!(["gts.test.ops.facade.item.v1~"])
+        );
+        assert_eq!(ops.metrics().schemas, 1);
+        assert_eq!(ops.validate_all().to_dict()["checked"], 0);
+    }
 }
@@ -0,0 +1,218 @@
+//! Authorization of registrations.
+//!
+//! Before the store registers an entity it asks its [`PolicyProvider`], if
+//! any, whether the registering principal may do so.  The built-in
+//! [`GtsNamespacePolicy`] enforces the `namespaces` rules from the config,
+//! e.g. that only `acme-signer` may register `gts.acme.*`; enterprises with
+//! their own authorization service plug in a provider of their own.
+//!
+//! Entities loaded through the store's reader are trusted and not checked.
+
+pub use gts_core::ownership::GtsNamespaceRule;
+
+use crate::entities::GtsEntity;
+use crate::store::{GtsStore, StoreError};
+
+/// A registration awaiting authorization.
+#[derive(Debug, Clone, Copy)]
+pub struct GtsRegistration<'a> {
+    /// Who is registering; `None` for anonymous callers.
+    pub principal: Option<&'a str>,
+    /// ID the entity will be registered under.
+    pub id: &'a str,
+    pub entity: &'a GtsEntity,
+}
+
+/// Decides whether a registration may proceed.
+pub trait PolicyProvider: Send + Sync {
+    /// Allows the registration, or denies it with a reason.
+    ///
+    /// # Errors
+    /// Returns the reason if the registration is not allowed.
+    fn authorize(&self, registration: &GtsRegistration<'_>) -> Result<(), String>;
+}
+
+/// Restricts namespaces to their owners; IDs outside every namespace are
+/// open to anyone.  A principal may register an ID if it owns any rule
+/// covering it.
+#[derive(Debug, Clone, Default)]
+pub struct GtsNamespacePolicy {
+    pub rules: Vec<GtsNamespaceRule>,
+}
+
+impl GtsNamespacePolicy {
+    #[must_use]
+    pub fn new(rules: Vec<GtsNamespaceRule>) -> Self {
+        GtsNamespacePolicy { rules }
+    }
+}
+
+impl PolicyProvider for GtsNamespacePolicy {
+    fn authorize(&self, registration: &GtsRegistration<'_>) -> Result<(), String> {
+        let covering: Vec<&GtsNamespaceRule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.covers(registration.id))
+            .collect();
+        if covering.is_empty()
+            || registration
+                .principal
+                .is_some_and(|p| covering.iter().any(|rule| rule.is_owner(p)))
+        {
+            return Ok(());
+        }
+        let owners: Vec<&str> = covering
+            .iter()
+            .flat_map(|rule| rule.owners.iter().map(String::as_str))
+            .collect();
+        Err(format!(
+            "namespace '{}' is owned by {}; {} is not an owner",
+            covering[0].pattern,
+            owners.join(", "),
+            registration
+                .principal
+                .map_or_else(|| "an anonymous caller".to_owned(), |p| format!("'{p}'"))
+        ))
+    }
+}
+
+impl GtsStore {
+    /// Sets the provider that authorizes registrations, or removes it.
+    pub fn set_policy_provider(&mut self, provider: Option<Box<dyn PolicyProvider>>) {
+        self.policy = provider;
+    }
+
+    /// Enforces namespace ownership `rules` through a [`GtsNamespacePolicy`],
+    /// replacing any other provider.  Empty rules remove the provider.
+    pub fn set_namespace_rules(&mut self, rules: Vec<GtsNamespaceRule>) {
+        self.policy = if rules.is_empty() {
+            None
+        } else {
+            Some(Box::new(GtsNamespacePolicy::new(rules)))
+        };
+    }
+
+    /// Asks the policy provider, if any, whether `principal` may register
+    /// `entity` as `id`.
+    pub(crate) fn authorize(
+        &self,
+        principal: Option<&str>,
+        id: &str,
+        entity: &GtsEntity,
+    ) -> Result<(), StoreError> {
+        let Some(policy) = &self.policy else {
+            return Ok(());
+        };
+        policy
+            .authorize(&GtsRegistration {
+                principal,
+                id,
+                entity,
+            })
+            .map_err(|reason| StoreError::RegistrationDenied {
+                id: id.to_owned(),
+                reason,
+            })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.own.event.v1~";
+    const ACME: &str = "gts.acme.billing.invoices.invoice.v1~";
+    const DERIVED: &str = "gts.x.test.own.event.v1~acme.billing.events.paid.v1~";
+
+    fn schema(id: &str) -> serde_json::Value {
+        json!({
+            "$id": format!("gts://{id}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        })
+    }
+
+    fn acme_store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store.set_namespace_rules(vec![GtsNamespaceRule {
+            pattern: "gts.acme.*".to_owned(),
+            owners: vec!["acme-signer".to_owned()],
+        }]);
+        store
+    }
+
+    #[test]
+    fn test_namespace_rules_restrict_registration() {
+        let mut store = acme_store();
+        store.register_schema(BASE, &schema(BASE)).unwrap();
+
+        let err = store.register_schema(ACME, &schema(ACME)).unwrap_err();
+        assert!(
+            matches!(&err, StoreError::RegistrationDenied { id, .. } if id == ACME),
+            "{err}"
+        );
+        assert!(err.to_string().contains("acme-signer"), "{err}");
+        assert!(
+            store
+                .register_schema_as(Some("mallory"), DERIVED, &schema(DERIVED))
+                .is_err()
+        );
+        assert!(!store.entities().contains_key(ACME));
+
+        store
+            .register_schema_as(Some("acme-signer"), ACME, &schema(ACME))
+            .unwrap();
+        store
+            .register_schema_as(Some("acme-signer"), DERIVED, &schema(DERIVED))
+            .unwrap();
+
+        let instance = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{BASE}acme.billing.events.e1.v1")}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(BASE.to_owned()),
+        );
+        assert!(store.register(instance.clone()).is_err());
+        store.register_as(Some("acme-signer"), instance).unwrap();
+        assert_eq!(store.entities().len(), 4);
+    }
+
+    #[test]
+    fn test_custom_policy_provider() {
+        struct SchemasOnly;
+        impl PolicyProvider for SchemasOnly {
+            fn authorize(&self, registration: &GtsRegistration<'_>) -> Result<(), String> {
+                if registration.entity.is_schema {
+                    Ok(())
+                } else {
+                    Err("instances are read-only".to_owned())
+                }
+            }
+        }
+
+        let mut store = GtsStore::new(None);
+        store.set_policy_provider(Some(Box::new(SchemasOnly)));
+        store.register_schema(BASE, &schema(BASE)).unwrap();
+        let instance = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{BASE}x.test._.e1.v1")}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(BASE.to_owned()),
+        );
+        let err = store.register(instance).unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{err}");
+    }
+}
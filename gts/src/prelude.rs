@@ -0,0 +1,60 @@
+//! The stable public surface of `gts`.
+//!
+//! Everything here follows semver: it only changes incompatibly with a
+//! major version bump (a minor one before 1.0), and CI checks that with
+//! `cargo semver-checks`.  Items reachable only through their module path
+//! (`gts::store::...`, `gts::openapi::...`) are public for advanced use but
+//! may still move between minor releases.
+//!
+//! ```
+//! use gts::prelude::*;
+//! use serde_json::json;
+//!
+//! let mut store = GtsStore::new(None);
+//! store.register_schema(
+//!     "gts.x.shop.orders.order.v1~",
+//!     &json!({
+//!         "$id": "gts://gts.x.shop.orders.order.v1~",
+//!         "$schema": "http://json-schema.org/draft-07/schema#",
+//!         "type": "object",
+//!         "required": ["id"],
+//!         "properties": {"id": {"type": "string"}}
+//!     }),
+//! )?;
+//!
+//! let id = GtsID::new("gts.x.shop.orders.order.v1~")?;
+//! assert!(id.wildcard_match(&GtsWildcard::new("gts.x.shop.*")?));
+//!
+//! let result: GtsStoreQueryResult = store.query("gts.x.shop.*", 10);
+//! assert_eq!(result.count, 1);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use crate::diagnostic::{GtsCode, GtsDiagnostic};
+pub use crate::entities::{GtsConfig, GtsEntity, ValidationError, ValidationResult};
+pub use crate::files_reader::GtsFileReader;
+pub use crate::files_writer::GtsFileWriter;
+pub use crate::gts::{
+    GtsError, GtsID, GtsIdBuilder, GtsIdSegment, GtsInstanceId, GtsSchemaId, GtsWildcard,
+};
+pub use crate::ops::{
+    GtsAddEntitiesResult, GtsAddEntityResult, GtsAddSchemaResult, GtsCastAllResult,
+    GtsEntitiesListResult, GtsEntityInfo, GtsEntityValidationResult, GtsExtractIdResult,
+    GtsGetEntityResult, GtsIdMatchResult, GtsIdParseResult, GtsIdSegmentInfo,
+    GtsIdValidationResult, GtsLabelsResult, GtsOps, GtsOpsResult, GtsReferrersResult,
+    GtsRemovalPlanResult, GtsRemoveResult, GtsSchemaDiffResult, GtsSchemaGraphResult,
+    GtsUuidResult, GtsValidationResult,
+};
+pub use crate::ref_integrity::{GtsBrokenRef, GtsBrokenRefReason, GtsRefIntegrity};
+pub use crate::removal::GtsRemovalBlocker;
+pub use crate::schema::{GtsSchema, GtsTyped};
+pub use crate::schema_cast::{GtsEntityCastResult, SchemaCastError};
+pub use crate::store::{
+    GtsReader, GtsStore, GtsStoreQueryPage, GtsStoreQueryResult, GtsWriteError, GtsWriter,
+    SortOrder, StoreError,
+};
+pub use crate::store_snapshot::GtsStoreSnapshot;
+pub use crate::transaction::GtsTransaction;
+pub use crate::typed::GtsTypedError;
+pub use crate::validate_all::{GtsValidateAllReport, GtsValidationFailure};
+pub use crate::validation_report::{GtsValidationIssue, GtsValidationReport};
@@ -0,0 +1,370 @@
+//! Protocol Buffers export of GTS schemas.
+//!
+//! [`GtsStore::export_proto`] turns each schema matching a pattern into a
+//! self-contained proto3 file holding one message.  The schema's inheritance
+//! chain is flattened: `$ref`s are inlined and `allOf` branches merged, so a
+//! derived type's message carries the fields of all its base types.  Nested
+//! objects become nested messages; free-form objects and untyped values map
+//! to the `google.protobuf` well-known types.
+//!
+//! Field numbers follow property order, so reordering or removing
+//! properties in a schema renumbers the exported fields; regenerate and
+//! version `.proto` files together with their schemas.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::gts::{GtsID, GtsWildcard};
+use crate::openapi::component_name;
+use crate::schema_cast::GtsEntityCastResult;
+use crate::store::{GtsStore, StoreError};
+
+const STRUCT_IMPORT: &str = "google/protobuf/struct.proto";
+
+/// One generated `.proto` file.
+#[derive(Debug, Clone, Serialize)]
+pub struct GtsProtoFile {
+    pub schema_id: String,
+    /// File name, e.g. `x.shop.orders.order.v1.proto`.
+    pub file_name: String,
+    pub package: String,
+    pub message: String,
+    pub content: String,
+}
+
+/// `order_item` → `OrderItem`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+/// A proto field name for a JSON property: `createdAt` stays, `created-at`
+/// becomes `created_at`.
+fn field_name(property: &str) -> String {
+    let mut name: String = property
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'f');
+    }
+    name
+}
+
+/// The first non-`null` type of a schema, and whether `null` is allowed.
+pub(crate) fn schema_type(schema: &Value) -> (Option<&str>, bool) {
+    match schema.get("type") {
+        Some(Value::String(ty)) => (Some(ty.as_str()), false),
+        Some(Value::Array(types)) => {
+            let nullable = types.iter().any(|t| t == "null");
+            let ty = types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null");
+            (ty, nullable)
+        }
+        _ => match schema.get("const").or_else(|| {
+            schema
+                .get("enum")
+                .and_then(Value::as_array)
+                .and_then(|v| v.first())
+        }) {
+            Some(Value::String(_)) => (Some("string"), false),
+            Some(Value::Bool(_)) => (Some("boolean"), false),
+            Some(Value::Number(n)) if n.is_f64() => (Some("number"), false),
+            Some(Value::Number(_)) => (Some("integer"), false),
+            _ if schema.get("properties").is_some() || schema.get("allOf").is_some() => {
+                (Some("object"), false)
+            }
+            _ => (None, false),
+        },
+    }
+}
+
+struct Renderer {
+    imports: BTreeSet<&'static str>,
+}
+
+impl Renderer {
+    /// Renders `schema`, an object schema, as message `name` at `depth`.
+    fn message(&mut self, out: &mut String, name: &str, schema: &Value, depth: usize) {
+        let flat = GtsEntityCastResult::flatten_schema(schema);
+        let indent = "  ".repeat(depth);
+        let required: BTreeSet<&str> = flat["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let _ = writeln!(out, "{indent}message {name} {{");
+        let mut nested = String::new();
+        let properties = flat["properties"].as_object().cloned().unwrap_or_default();
+        for (number, (property, prop_schema)) in properties.iter().enumerate() {
+            let field = field_name(property);
+            let ty = self.field_type(&mut nested, &field, prop_schema, depth + 1);
+            let label = match ty {
+                FieldType::Repeated(_) => "repeated ",
+                FieldType::Single(_)
+                    if !required.contains(property.as_str()) || schema_type(prop_schema).1 =>
+                {
+                    "optional "
+                }
+                FieldType::Single(_) => "",
+            };
+            let json_name = if field == *property {
+                String::new()
+            } else {
+                format!(" [json_name = \"{property}\"]")
+            };
+            if let Some(description) = prop_schema.get("description").and_then(Value::as_str) {
+                for line in description.lines() {
+                    let _ = writeln!(out, "{indent}  // {line}");
+                }
+            }
+            let _ = writeln!(
+                out,
+                "{indent}  {label}{} {field} = {}{json_name};",
+                ty.name(),
+                number + 1
+            );
+        }
+        out.push_str(&nested);
+        let _ = writeln!(out, "{indent}}}");
+    }
+
+    fn field_type(
+        &mut self,
+        nested: &mut String,
+        field: &str,
+        schema: &Value,
+        depth: usize,
+    ) -> FieldType {
+        let (ty, _) = schema_type(schema);
+        match ty {
+            Some("string") => FieldType::Single("string".to_owned()),
+            Some("integer") => FieldType::Single("int64".to_owned()),
+            Some("number") => FieldType::Single("double".to_owned()),
+            Some("boolean") => FieldType::Single("bool".to_owned()),
+            Some("object") => {
+                let flat = GtsEntityCastResult::flatten_schema(schema);
+                if flat["properties"]
+                    .as_object()
+                    .is_some_and(|p| !p.is_empty())
+                {
+                    let name = pascal_case(field);
+                    self.message(nested, &name, schema, depth);
+                    FieldType::Single(name)
+                } else {
+                    self.imports.insert(STRUCT_IMPORT);
+                    FieldType::Single("google.protobuf.Struct".to_owned())
+                }
+            }
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                match self.field_type(nested, field, items, depth) {
+                    FieldType::Single(name) => FieldType::Repeated(name),
+                    // Proto has no nested repeated fields
+                    FieldType::Repeated(_) => {
+                        self.imports.insert(STRUCT_IMPORT);
+                        FieldType::Repeated("google.protobuf.ListValue".to_owned())
+                    }
+                }
+            }
+            _ => {
+                self.imports.insert(STRUCT_IMPORT);
+                FieldType::Single("google.protobuf.Value".to_owned())
+            }
+        }
+    }
+}
+
+enum FieldType {
+    Single(String),
+    Repeated(String),
+}
+
+impl FieldType {
+    fn name(&self) -> &str {
+        match self {
+            FieldType::Single(name) | FieldType::Repeated(name) => name,
+        }
+    }
+}
+
+/// Renders the proto file for schema `id` with resolved content `schema`.
+fn render(id: &GtsID, schema: &Value) -> GtsProtoFile {
+    let leaf = id.gts_id_segments.last();
+    let (package, message) = leaf.map_or_else(
+        || ("gts".to_owned(), "Schema".to_owned()),
+        |seg| {
+            let version = match seg.ver_minor {
+                Some(minor) => format!("V{}_{minor}", seg.ver_major),
+                None => format!("V{}", seg.ver_major),
+            };
+            (
+                format!("{}.{}.{}", seg.vendor, seg.package, seg.namespace),
+                pascal_case(&seg.type_name) + &version,
+            )
+        },
+    );
+
+    let mut renderer = Renderer {
+        imports: BTreeSet::new(),
+    };
+    let mut body = String::new();
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        for line in description.lines() {
+            let _ = writeln!(body, "// {line}");
+        }
+    }
+    renderer.message(&mut body, &message, schema, 0);
+
+    let mut content = format!(
+        "// Generated from GTS schema {}; do not edit.\nsyntax = \"proto3\";\n\npackage {package};\n",
+        id.id
+    );
+    if !renderer.imports.is_empty() {
+        content.push('\n');
+        for import in &renderer.imports {
+            let _ = writeln!(content, "import \"{import}\";");
+        }
+    }
+    content.push('\n');
+    content.push_str(&body);
+
+    GtsProtoFile {
+        schema_id: id.id.clone(),
+        file_name: format!("{}.proto", component_name(&id.id)),
+        package,
+        message,
+        content,
+    }
+}
+
+impl GtsStore {
+    /// Renders each schema matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact schema ID, as a proto3 file with its
+    /// inheritance chain flattened into one message.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn export_proto(&self, pattern: &str) -> Result<Vec<GtsProtoFile>, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let mut files: Vec<GtsProtoFile> = self
+            .entities()
+            .iter()
+            .filter(|(_, entity)| entity.is_schema)
+            .filter_map(|(id, entity)| {
+                let gid = GtsID::new(id).ok()?;
+                let selected = if pattern.contains('*') {
+                    gid.wildcard_match(&wildcard)
+                } else {
+                    *id == wildcard.id
+                };
+                selected.then(|| render(&gid, &self.resolve_schema_refs(&entity.content)))
+            })
+            .collect();
+        files.sort_by(|a, b| a.schema_id.cmp(&b.schema_id));
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.proto.event.v1~";
+    const CHILD: &str = "gts.x.test.proto.event.v1~x.test.proto.order_placed.v1.2~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "required": ["id"],
+                    "properties": {
+                        "id": {"type": "string"},
+                        "created-at": {"type": "string", "format": "date-time"}
+                    }
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                CHILD,
+                &json!({
+                    "$id": format!("gts://{CHILD}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [
+                        {"$ref": format!("gts://{BASE}")},
+                        {
+                            "type": "object",
+                            "required": ["items"],
+                            "properties": {
+                                "items": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {"sku": {"type": "string"}, "qty": {"type": "integer"}}
+                                    }
+                                },
+                                "total": {"type": ["number", "null"], "description": "Order total"},
+                                "meta": {"type": "object"}
+                            }
+                        }
+                    ]
+                }),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_pascal_case_and_field_name() {
+        assert_eq!(pascal_case("order_placed"), "OrderPlaced");
+        assert_eq!(field_name("created-at"), "created_at");
+        assert_eq!(field_name("$schema"), "f_schema");
+    }
+
+    #[test]
+    fn test_export_proto_flattens_chain() {
+        let files = store().export_proto(CHILD).unwrap();
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.package, "x.test.proto");
+        assert_eq!(file.message, "OrderPlacedV1_2");
+        assert_eq!(
+            file.file_name,
+            "x.test.proto.event.v1-x.test.proto.order_placed.v1.2.proto"
+        );
+
+        let proto = &file.content;
+        assert!(proto.contains("syntax = \"proto3\";"));
+        assert!(proto.contains("import \"google/protobuf/struct.proto\";"));
+        assert!(proto.contains("  string id = 1;"));
+        assert!(proto.contains("  optional string created_at = 2 [json_name = \"created-at\"];"));
+        assert!(proto.contains("  repeated Items items = 3;"));
+        assert!(proto.contains("  // Order total\n  optional double total = 4;"));
+        assert!(proto.contains("  optional google.protobuf.Struct meta = 5;"));
+        assert!(proto.contains("  message Items {\n    optional string sku = 1;"));
+
+        assert_eq!(store().export_proto("gts.x.test.proto.*").unwrap().len(), 2);
+    }
+}
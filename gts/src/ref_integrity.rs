@@ -0,0 +1,374 @@
+//! Referential integrity of `x-gts-ref` values.
+//!
+//! Plain `x-gts-ref` validation only checks that a value is a GTS ID matching
+//! the pattern of its field.  With [`GtsRefIntegrity::Exists`] the store also
+//! requires the referenced entity to be registered (or fetchable through the
+//! reader), and with [`GtsRefIntegrity::Typed`] that it really is of its
+//! type: a schema, or an instance that validates against its own schema.  Every miss of an instance is reported together in one
+//! [`StoreError::BrokenReference`].
+
+pub use gts_core::ref_integrity::GtsRefIntegrity;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::diagnostic::{GtsCode, GtsDiagnostic};
+use crate::store::{GtsStore, StoreError};
+use crate::x_gts_ref::XGtsRefValidator;
+
+/// Why an `x-gts-ref` target fails the integrity check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtsBrokenRefReason {
+    /// The target is not in the store.
+    Missing,
+    /// The target is an instance that does not validate against its schema,
+    /// or whose schema is missing.
+    Invalid { error: String },
+}
+
+/// An `x-gts-ref` value whose target fails the integrity check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsBrokenRef {
+    /// JSON pointer of the value in the instance.
+    pub path: String,
+    pub target: String,
+    /// The `x-gts-ref` pattern of the field, with JSON pointers resolved.
+    pub pattern: String,
+    pub reason: GtsBrokenRefReason,
+}
+
+impl GtsBrokenRef {
+    #[must_use]
+    pub fn code(&self) -> GtsCode {
+        match self.reason {
+            GtsBrokenRefReason::Missing => GtsCode::XGtsRefMissingTarget,
+            GtsBrokenRefReason::Invalid { .. } => GtsCode::XGtsRefInvalidTarget,
+        }
+    }
+
+    #[must_use]
+    pub fn diagnostic(&self) -> GtsDiagnostic {
+        GtsDiagnostic::new(self.code(), self.to_string())
+    }
+}
+
+impl fmt::Display for GtsBrokenRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = if self.path.is_empty() {
+            "/"
+        } else {
+            self.path.as_str()
+        };
+        match &self.reason {
+            GtsBrokenRefReason::Missing => write!(f, "{at}: '{}' not found", self.target),
+            GtsBrokenRefReason::Invalid { error } => {
+                write!(
+                    f,
+                    "{at}: '{}' is not a valid instance: {error}",
+                    self.target
+                )
+            }
+        }
+    }
+}
+
+/// An `x-gts-ref` value of an instance and the resolved pattern of its field.
+struct RefValue {
+    path: String,
+    target: String,
+    pattern: String,
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Collects the values of `instance` held in `x-gts-ref` fields of `schema`
+/// that pass the syntax check.  Combinator branches are all visited; a value
+/// only counts for a branch whose pattern it matches.
+fn ref_values(instance: &Value, schema: &Value, root: &Value, path: &str, out: &mut Vec<RefValue>) {
+    let Some(sch) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(pattern) = sch.get("x-gts-ref").and_then(Value::as_str) {
+        let pattern = if pattern.starts_with('/') {
+            XGtsRefValidator::resolve_pointer(root, pattern)
+        } else {
+            Some(pattern.to_owned())
+        };
+        let mut push = |value: &Value, path: String| {
+            if let (Some(target), Some(pattern)) = (value.as_str(), &pattern)
+                && XGtsRefValidator::new()
+                    .validate_gts_pattern(target, pattern, "")
+                    .is_none()
+            {
+                out.push(RefValue {
+                    path,
+                    target: target.to_owned(),
+                    pattern: pattern.clone(),
+                });
+            }
+        };
+        match instance {
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    push(item, format!("{path}/{idx}"));
+                }
+            }
+            value => push(value, path.to_owned()),
+        }
+    }
+
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = sch.get(combinator) {
+            for branch in branches {
+                ref_values(instance, branch, root, path, out);
+            }
+        }
+    }
+
+    match instance {
+        Value::Object(fields) => {
+            let properties = sch.get("properties").and_then(Value::as_object);
+            let additional = sch.get("additionalProperties").filter(|a| a.is_object());
+            for (name, value) in fields {
+                if let Some(prop_schema) = properties.and_then(|p| p.get(name)).or(additional) {
+                    let prop_path = format!("{path}/{}", escape_pointer(name));
+                    ref_values(value, prop_schema, root, &prop_path, out);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let (tuple, rest) = match (sch.get("prefixItems"), sch.get("items")) {
+                (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+                (_, Some(Value::Array(tuple))) => (tuple.as_slice(), sch.get("additionalItems")),
+                (_, rest) => (&[][..], rest),
+            };
+            for (idx, item) in items.iter().enumerate() {
+                if let Some(item_schema) = tuple.get(idx).or(rest) {
+                    ref_values(item, item_schema, root, &format!("{path}/{idx}"), out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl GtsStore {
+    /// The `x-gts-ref` values of `content` whose targets fail the store's
+    /// [`GtsRefIntegrity`] check against `schema`; empty while it is off.
+    pub(crate) fn broken_refs(&mut self, schema: &Value, content: &Value) -> Vec<GtsBrokenRef> {
+        let mode = self.ref_integrity();
+        if mode == GtsRefIntegrity::Off {
+            return Vec::new();
+        }
+
+        let mut values = Vec::new();
+        ref_values(content, schema, schema, "", &mut values);
+
+        let mut broken: Vec<GtsBrokenRef> = Vec::new();
+        for value in values {
+            if broken
+                .iter()
+                .any(|b| b.path == value.path && b.target == value.target)
+            {
+                continue;
+            }
+            let reason = match self.get(&value.target).map(|entity| entity.is_schema) {
+                None => Some(GtsBrokenRefReason::Missing),
+                Some(false) if mode == GtsRefIntegrity::Typed => self
+                    .check_target(&value.target)
+                    .err()
+                    .map(|e| GtsBrokenRefReason::Invalid {
+                        error: e.to_string(),
+                    }),
+                Some(_) => None,
+            };
+            if let Some(reason) = reason {
+                broken.push(GtsBrokenRef {
+                    path: value.path,
+                    target: value.target,
+                    pattern: value.pattern,
+                    reason,
+                });
+            }
+        }
+        broken
+    }
+
+    /// Validates a referenced instance against its schema, without following
+    /// its own references.
+    fn check_target(&mut self, target: &str) -> Result<(), StoreError> {
+        let (content, schema_id, _) = self.instance_schema(target)?;
+        let compiled = self.prepare_schema(&schema_id, &content)?;
+        Self::check_compiled(&compiled, &content, self.number_policy())
+    }
+
+    /// Fails with [`StoreError::BrokenReference`] if any `x-gts-ref` target of
+    /// `content` fails the store's [`GtsRefIntegrity`] check.
+    pub(crate) fn check_ref_integrity(
+        &mut self,
+        schema: &Value,
+        content: &Value,
+    ) -> Result<(), StoreError> {
+        let broken = self.broken_refs(schema, content);
+        if broken.is_empty() {
+            Ok(())
+        } else {
+            Err(StoreError::BrokenReference(broken))
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    const USER: &str = "gts.x.test.integrity.user.v1~";
+    const TEAM: &str = "gts.x.test.integrity.team.v1~";
+    const ORDER: &str = "gts.x.test.integrity.order.v1~";
+
+    fn register_instance(store: &mut GtsStore, content: &Value, schema_id: &str) -> String {
+        let entity = GtsEntity::new(
+            None,
+            None,
+            content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        );
+        let id = entity.gts_id.as_ref().unwrap().id.clone();
+        store.register(entity).unwrap();
+        id
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in [USER, TEAM] {
+            store
+                .register_schema(
+                    id,
+                    &json!({
+                        "$id": format!("gts://{id}"),
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "type": "object",
+                        "properties": {"name": {"type": "string"}}
+                    }),
+                )
+                .unwrap();
+        }
+        store
+            .register_schema(
+                ORDER,
+                &json!({
+                    "$id": format!("gts://{ORDER}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {
+                        "owner": {"type": "string", "x-gts-ref": USER},
+                        "watchers": {
+                            "type": "array",
+                            "items": {"type": "string", "x-gts-ref": "gts.x.test.integrity.*"}
+                        }
+                    }
+                }),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_exists_lists_every_missing_target() {
+        let mut store = store();
+        let alice = register_instance(
+            &mut store,
+            &json!({"id": format!("{USER}x.test._.alice.v1")}),
+            USER,
+        );
+        let order = json!({
+            "owner": format!("{USER}x.test._.bob.v1"),
+            "watchers": [alice, format!("{TEAM}x.test._.ops.v1")]
+        });
+
+        // Off by default: only the syntax is checked
+        store.validate_value(ORDER, &order).unwrap();
+
+        store.set_ref_integrity(GtsRefIntegrity::Exists);
+        let Err(StoreError::BrokenReference(broken)) = store.validate_value(ORDER, &order) else {
+            panic!("expected BrokenReference");
+        };
+        let paths: Vec<&str> = broken.iter().map(|b| b.path.as_str()).collect();
+        assert_eq!(paths, ["/owner", "/watchers/1"]);
+        assert!(
+            broken
+                .iter()
+                .all(|b| b.reason == GtsBrokenRefReason::Missing)
+        );
+        assert_eq!(broken[0].code(), GtsCode::XGtsRefMissingTarget);
+
+        let err = store.validate_value(ORDER, &order).unwrap_err();
+        assert_eq!(err.code(), GtsCode::BrokenReference);
+        assert_eq!(err.diagnostics().len(), 2);
+        assert!(
+            err.to_string()
+                .contains("/owner: 'gts.x.test.integrity.user.v1~x.test._.bob.v1' not found")
+        );
+
+        register_instance(
+            &mut store,
+            &json!({"id": format!("{USER}x.test._.bob.v1")}),
+            USER,
+        );
+        register_instance(
+            &mut store,
+            &json!({"id": format!("{TEAM}x.test._.ops.v1")}),
+            TEAM,
+        );
+        store.validate_value(ORDER, &order).unwrap();
+    }
+
+    #[test]
+    fn test_typed_validates_the_target() {
+        let mut store = store();
+        let nameless = register_instance(
+            &mut store,
+            &json!({"id": format!("{USER}x.test._.eve.v1"), "name": 7}),
+            USER,
+        );
+        // Of a derived type that is not registered
+        let admin = register_instance(
+            &mut store,
+            &json!({"id": format!("{USER}x.test.integrity.admin.v1~x.test._.root.v1")}),
+            USER,
+        );
+        let order = json!({"owner": nameless, "watchers": [admin]});
+
+        store.set_ref_integrity(GtsRefIntegrity::Exists);
+        store.validate_value(ORDER, &order).unwrap();
+
+        store.set_ref_integrity(GtsRefIntegrity::Typed);
+        let Err(StoreError::BrokenReference(broken)) = store.validate_value(ORDER, &order) else {
+            panic!("expected BrokenReference");
+        };
+        assert_eq!(broken.len(), 2);
+        assert!(broken[0].to_string().contains("is not a valid instance"));
+        assert!(
+            matches!(&broken[1].reason, GtsBrokenRefReason::Invalid { error } if error.contains("not found"))
+        );
+        assert_eq!(broken[0].code(), GtsCode::XGtsRefInvalidTarget);
+
+        // Schemas are always of their own type
+        let order = json!({"watchers": [TEAM]});
+        store.validate_value(ORDER, &order).unwrap();
+    }
+}
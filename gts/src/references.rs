@@ -0,0 +1,358 @@
+//! The references an entity makes to other entities.
+//!
+//! [`GtsStore::references`] is the one walker behind removal, the referrers
+//! lookup, the refs audit and the dependency graph, so they agree on what
+//! counts as a reference:
+//!
+//! - a chained schema ID's parent schema (`parent`);
+//! - a schema's `gts://` `$ref`s (`$ref`) and `x-gts-ref` targets
+//!   (`x-gts-ref`), which may be wildcards such as `gts.x.core.*`; JSON
+//!   pointer targets are looked up in the schema itself;
+//! - an instance's schema (`schema`);
+//! - the GTS IDs an instance holds, in `x-gts-ref` constrained fields of its
+//!   schema (`x-gts-ref`) or anywhere else in its content (`content`).
+//!
+//! An entity's references to itself are left out.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::entities::GtsEntity;
+use crate::gts::{GTS_URI_PREFIX, GtsID};
+use crate::store::GtsStore;
+
+/// How an entity refers to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GtsReferenceKind {
+    #[serde(rename = "parent")]
+    Parent,
+    #[serde(rename = "$ref")]
+    Ref,
+    #[serde(rename = "x-gts-ref")]
+    XGtsRef,
+    #[serde(rename = "schema")]
+    Schema,
+    #[serde(rename = "content")]
+    Content,
+}
+
+impl GtsReferenceKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GtsReferenceKind::Parent => "parent",
+            GtsReferenceKind::Ref => "$ref",
+            GtsReferenceKind::XGtsRef => "x-gts-ref",
+            GtsReferenceKind::Schema => "schema",
+            GtsReferenceKind::Content => "content",
+        }
+    }
+}
+
+/// One reference held by an entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsReference {
+    /// JSON pointer of the reference within the entity; empty for the
+    /// `parent` and `schema` references.
+    pub path: String,
+    pub kind: GtsReferenceKind,
+    /// The referenced ID, or a wildcard ending in `*`.
+    pub target: String,
+}
+
+impl GtsReference {
+    /// Whether the reference points at `id`, directly or by wildcard.
+    #[must_use]
+    pub fn points_at(&self, id: &str) -> bool {
+        match self.target.strip_suffix('*') {
+            Some(prefix) => id.starts_with(prefix),
+            None => self.target == id,
+        }
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// The schema a chained schema ID derives from.
+pub(crate) fn chain_parent(schema_id: &str) -> Option<&str> {
+    let trimmed = schema_id.strip_suffix('~')?;
+    trimmed.rfind('~').map(|pos| &schema_id[..=pos])
+}
+
+/// Collects the `gts://` `$ref` and `x-gts-ref` targets in a schema.
+fn schema_refs(value: &Value, schema: &Value, path: &str, out: &mut Vec<GtsReference>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(target) = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| r.strip_prefix(GTS_URI_PREFIX))
+            {
+                out.push(GtsReference {
+                    path: format!("{path}/$ref"),
+                    kind: GtsReferenceKind::Ref,
+                    target: target.split('#').next().unwrap_or(target).to_owned(),
+                });
+            }
+            if let Some(target) = map.get("x-gts-ref").and_then(Value::as_str) {
+                let target = if target.starts_with('/') {
+                    schema.pointer(target).and_then(Value::as_str)
+                } else {
+                    Some(target)
+                };
+                if let Some(target) = target {
+                    out.push(GtsReference {
+                        path: format!("{path}/x-gts-ref"),
+                        kind: GtsReferenceKind::XGtsRef,
+                        target: target
+                            .strip_prefix(GTS_URI_PREFIX)
+                            .unwrap_or(target)
+                            .to_owned(),
+                    });
+                }
+            }
+            for (key, v) in map {
+                schema_refs(v, schema, &format!("{path}/{}", escape_pointer(key)), out);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                schema_refs(item, schema, &format!("{path}/{idx}"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The schemas a schema's `gts://` `$ref`s point at.
+pub(crate) fn ref_targets(schema: &Value) -> Vec<String> {
+    let mut refs = Vec::new();
+    schema_refs(schema, schema, "", &mut refs);
+    refs.into_iter()
+        .filter(|r| r.kind == GtsReferenceKind::Ref)
+        .map(|r| r.target)
+        .collect()
+}
+
+/// `schemas` with their `allOf` branches, recursively.
+fn with_branches<'a>(schemas: &[&'a Value]) -> Vec<&'a Value> {
+    let mut out = Vec::new();
+    let mut pending: Vec<&Value> = schemas.to_vec();
+    while let Some(schema) = pending.pop() {
+        if let Some(Value::Array(branches)) = schema.get("allOf") {
+            pending.extend(branches);
+        }
+        out.push(schema);
+    }
+    out
+}
+
+/// Collects the GTS IDs held in an instance, as `x-gts-ref` references where
+/// one of the `$ref`-resolved `schemas` of their position constrains them.
+fn instance_refs(instance: &Value, schemas: &[&Value], path: &str, out: &mut Vec<GtsReference>) {
+    let schemas = with_branches(schemas);
+    match instance {
+        Value::String(value) if GtsID::is_valid(value) => {
+            let kind = if schemas.iter().any(|s| s.get("x-gts-ref").is_some()) {
+                GtsReferenceKind::XGtsRef
+            } else {
+                GtsReferenceKind::Content
+            };
+            out.push(GtsReference {
+                path: path.to_owned(),
+                kind,
+                target: value.clone(),
+            });
+        }
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let props: Vec<&Value> = schemas
+                    .iter()
+                    .filter_map(|s| s.get("properties")?.get(name))
+                    .collect();
+                let field_path = format!("{path}/{}", escape_pointer(name));
+                instance_refs(value, &props, &field_path, out);
+            }
+        }
+        Value::Array(values) => {
+            let items: Vec<&Value> = schemas.iter().filter_map(|s| s.get("items")).collect();
+            for (idx, value) in values.iter().enumerate() {
+                instance_refs(value, &items, &format!("{path}/{idx}"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl GtsStore {
+    /// The references entity `id` holds, in the order they appear; empty
+    /// for an unknown ID.
+    #[must_use]
+    pub fn references(&self, id: &str) -> Vec<GtsReference> {
+        self.entities()
+            .get(id)
+            .map(|entity| self.entity_references(id, entity, &mut HashMap::new()))
+            .unwrap_or_default()
+    }
+
+    /// The references of every entity, by ID.
+    pub(crate) fn all_references(&self) -> BTreeMap<&str, Vec<GtsReference>> {
+        let mut resolved = HashMap::new();
+        self.entities()
+            .iter()
+            .map(|(id, entity)| {
+                (
+                    id.as_str(),
+                    self.entity_references(id, entity, &mut resolved),
+                )
+            })
+            .collect()
+    }
+
+    /// The references of `entity`, resolving instance schemas through
+    /// `resolved`, a cache of `$ref`-resolved schemas by ID.
+    pub(crate) fn entity_references(
+        &self,
+        id: &str,
+        entity: &GtsEntity,
+        resolved: &mut HashMap<String, Value>,
+    ) -> Vec<GtsReference> {
+        let mut refs = Vec::new();
+        if entity.is_schema {
+            if let Some(parent) = chain_parent(id) {
+                refs.push(GtsReference {
+                    path: String::new(),
+                    kind: GtsReferenceKind::Parent,
+                    target: parent.to_owned(),
+                });
+            }
+            schema_refs(&entity.content, &entity.content, "", &mut refs);
+        } else {
+            let schema_id = entity.schema_id.as_deref().filter(|s| GtsID::is_valid(s));
+            if let Some(schema_id) = schema_id {
+                refs.push(GtsReference {
+                    path: String::new(),
+                    kind: GtsReferenceKind::Schema,
+                    target: schema_id.to_owned(),
+                });
+            }
+            let schema = schema_id.and_then(|schema_id| {
+                let schema = self.entities().get(schema_id)?;
+                Some(
+                    &*resolved
+                        .entry(schema_id.to_owned())
+                        .or_insert_with(|| self.resolve_schema_refs(&schema.content)),
+                )
+            });
+            instance_refs(
+                &entity.content,
+                &schema.into_iter().collect::<Vec<_>>(),
+                "",
+                &mut refs,
+            );
+        }
+        refs.retain(|r| r.target != id);
+        refs
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_references_of_schemas_and_instances() {
+        let mut store = GtsStore::new(None);
+        let base = "gts.x.test.walk.event.v1~";
+        let derived = "gts.x.test.walk.event.v1~x.test.walk.placed.v1~";
+        store
+            .register_schema(
+                base,
+                &json!({
+                    "$id": format!("gts://{base}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "x-gts-ref": "/$id"},
+                        "source": {"type": "string", "x-gts-ref": "gts.x.test.*"}
+                    }
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                derived,
+                &json!({
+                    "$id": format!("gts://{derived}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [{"$ref": format!("gts://{base}")}]
+                }),
+            )
+            .unwrap();
+        let instance = format!("{derived}x.test._.e1.v1");
+        store
+            .register(GtsEntity::new(
+                None,
+                None,
+                &json!({"id": instance, "source": base, "note": derived}),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(derived.to_owned()),
+            ))
+            .unwrap();
+
+        let refs = |id: &str| -> Vec<(GtsReferenceKind, String, String)> {
+            store
+                .references(id)
+                .into_iter()
+                .map(|r| (r.kind, r.path, r.target))
+                .collect()
+        };
+        assert_eq!(
+            refs(base),
+            [(
+                GtsReferenceKind::XGtsRef,
+                "/properties/source/x-gts-ref".to_owned(),
+                "gts.x.test.*".to_owned()
+            )]
+        );
+        assert_eq!(
+            refs(derived),
+            [
+                (GtsReferenceKind::Parent, String::new(), base.to_owned()),
+                (
+                    GtsReferenceKind::Ref,
+                    "/allOf/0/$ref".to_owned(),
+                    base.to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            refs(&instance),
+            [
+                (GtsReferenceKind::Schema, String::new(), derived.to_owned()),
+                (
+                    GtsReferenceKind::XGtsRef,
+                    "/source".to_owned(),
+                    base.to_owned()
+                ),
+                (
+                    GtsReferenceKind::Content,
+                    "/note".to_owned(),
+                    derived.to_owned()
+                ),
+            ]
+        );
+        assert!(store.references("gts.x.test.walk.missing.v1~").is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+//! Graph-aware removal of entities.
+//!
+//! Removing a schema strands whatever still references it, as
+//! [`GtsStore::references`] finds: derived schemas (chained IDs and `$ref`s),
+//! `x-gts-ref` constraints, wildcard ones included, instances of the schema,
+//! and instances whose content names a removed entity.
+//! [`GtsStore::plan_removal`] expands a pattern to that full dependent set and
+//! orders it so every entity is removed before the ones it references;
+//! [`GtsStore::apply_plan`] carries the plan out.
+//!
+//! [`GtsStore::remove`] and [`GtsStore::remove_matching`] remove just the
+//! given entities, and refuse with [`StoreError::RemovalBlocked`] while
+//! entities outside that set still reference them, unless forced.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+use crate::entities::GtsEntity;
+use crate::gts::{GtsID, GtsWildcard};
+use crate::store::{GtsStore, StoreError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsRemovalStep {
+    pub id: String,
+    pub is_schema: bool,
+    /// Whether the ID matched the pattern itself, rather than being pulled in
+    /// as a dependent.
+    pub matched: bool,
+    /// Entities of the plan this one references, removed after it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsRemovalPlan {
+    pub pattern: String,
+    /// Entities in safe removal order: dependents first.
+    pub steps: Vec<GtsRemovalStep>,
+}
+
+/// An entity that still references one being removed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GtsRemovalBlocker {
+    pub id: String,
+    /// The referenced entity.
+    pub target: String,
+}
+
+impl fmt::Display for GtsRemovalBlocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' references '{}'", self.id, self.target)
+    }
+}
+
+impl GtsStore {
+    /// IDs each entity references, with wildcard references expanded to the
+    /// IDs they match.
+    fn referenced_ids(&self) -> BTreeMap<&str, BTreeSet<String>> {
+        self.all_references()
+            .into_iter()
+            .map(|(id, refs)| {
+                let mut targets = BTreeSet::new();
+                for r in refs {
+                    if r.target.ends_with('*') {
+                        targets.extend(
+                            self.entities()
+                                .keys()
+                                .filter(|other| other.as_str() != id && r.points_at(other))
+                                .cloned(),
+                        );
+                    } else {
+                        targets.insert(r.target);
+                    }
+                }
+                (id, targets)
+            })
+            .collect()
+    }
+
+    /// IDs matching `pattern`, a GTS wildcard or an exact ID.
+    fn matching_ids(&self, pattern: &str) -> Result<BTreeSet<&str>, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        Ok(self
+            .entities()
+            .keys()
+            .filter(|id| {
+                id.as_str() == pattern
+                    || GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(&wildcard))
+            })
+            .map(String::as_str)
+            .collect())
+    }
+
+    /// Entities outside `targets` that reference one of them.
+    fn removal_blockers(&self, targets: &BTreeSet<&str>) -> Vec<GtsRemovalBlocker> {
+        let mut blockers: Vec<GtsRemovalBlocker> = self
+            .referenced_ids()
+            .into_iter()
+            .filter(|(id, _)| !targets.contains(id))
+            .flat_map(|(id, refs)| {
+                refs.into_iter()
+                    .filter(|target| targets.contains(target.as_str()))
+                    .map(move |target| GtsRemovalBlocker {
+                        id: id.to_owned(),
+                        target,
+                    })
+            })
+            .collect();
+        blockers.sort_unstable();
+        blockers
+    }
+
+    /// Removes entity `id` and returns it.  Unless `force` is set, fails while
+    /// other entities still reference it.  Only the in-memory registration
+    /// goes; call [`GtsStore::delete_persisted`] first to delete its file.
+    ///
+    /// # Errors
+    /// Returns `StoreError::EntityNotFound` for an unknown ID, or
+    /// `StoreError::RemovalBlocked` listing the entities referencing it.
+    pub fn remove(&mut self, id: &str, force: bool) -> Result<GtsEntity, StoreError> {
+        if !self.entities().contains_key(id) {
+            return Err(StoreError::EntityNotFound(id.to_owned()));
+        }
+        if !force {
+            let blockers = self.removal_blockers(&BTreeSet::from([id]));
+            if !blockers.is_empty() {
+                return Err(StoreError::RemovalBlocked(blockers));
+            }
+        }
+        tracing::info!("Removing {id}");
+        self.remove_entity(id)
+            .ok_or_else(|| StoreError::EntityNotFound(id.to_owned()))
+    }
+
+    /// Removes every entity matching `pattern` (a GTS wildcard or an exact
+    /// ID) and returns their IDs, sorted.  Unless `force` is set, fails
+    /// without removing anything while entities that do not match still
+    /// reference one that does; [`GtsStore::plan_removal`] covers those too.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard, or `StoreError::RemovalBlocked` listing the blockers.
+    pub fn remove_matching(
+        &mut self,
+        pattern: &str,
+        force: bool,
+    ) -> Result<Vec<String>, StoreError> {
+        let matched = self.matching_ids(pattern)?;
+        if !force {
+            let blockers = self.removal_blockers(&matched);
+            if !blockers.is_empty() {
+                return Err(StoreError::RemovalBlocked(blockers));
+            }
+        }
+        let ids: Vec<String> = matched.into_iter().map(str::to_owned).collect();
+        for id in &ids {
+            tracing::info!("Removing {id}");
+            self.remove_entity(id);
+        }
+        Ok(ids)
+    }
+
+    /// Plans the removal of every entity matching `pattern` (a GTS wildcard
+    /// or an exact ID) together with everything that depends on them.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn plan_removal(&self, pattern: &str) -> Result<GtsRemovalPlan, StoreError> {
+        let matched = self.matching_ids(pattern)?;
+        let entities = self.entities();
+
+        let refs = self.referenced_ids();
+        let mut dependents: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (id, targets) in &refs {
+            for target in targets {
+                dependents.entry(target.as_str()).or_default().push(id);
+            }
+        }
+
+        let mut removed = matched.clone();
+        let mut queue: VecDeque<&str> = matched.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            for dependent in dependents.get(id).into_iter().flatten() {
+                if removed.insert(dependent) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        // Kahn's algorithm: an entity is ready once nothing left in the plan
+        // references it. Reference cycles are broken in ID order.
+        let depends_on: BTreeMap<&str, Vec<String>> = removed
+            .iter()
+            .map(|id| {
+                let targets = refs[id]
+                    .iter()
+                    .filter(|t| removed.contains(t.as_str()))
+                    .cloned()
+                    .collect();
+                (*id, targets)
+            })
+            .collect();
+        let mut pending: BTreeMap<&str, usize> = removed.iter().map(|id| (*id, 0)).collect();
+        for targets in depends_on.values() {
+            for target in targets {
+                if let Some(count) = pending.get_mut(target.as_str()) {
+                    *count += 1;
+                }
+            }
+        }
+        let mut order = Vec::with_capacity(removed.len());
+        while !pending.is_empty() {
+            let ready: Vec<&str> = pending
+                .iter()
+                .filter(|(_, count)| **count == 0)
+                .map(|(id, _)| *id)
+                .collect();
+            let batch = if ready.is_empty() {
+                pending.keys().take(1).copied().collect()
+            } else {
+                ready
+            };
+            for id in batch {
+                pending.remove(id);
+                for target in &depends_on[id] {
+                    if let Some(count) = pending.get_mut(target.as_str()) {
+                        *count = count.saturating_sub(1);
+                    }
+                }
+                order.push(id);
+            }
+        }
+
+        let steps = order
+            .into_iter()
+            .map(|id| GtsRemovalStep {
+                id: id.to_owned(),
+                is_schema: entities[id].is_schema,
+                matched: matched.contains(id),
+                depends_on: depends_on[id].clone(),
+            })
+            .collect();
+        Ok(GtsRemovalPlan {
+            pattern: pattern.to_owned(),
+            steps,
+        })
+    }
+
+    /// Removes the entities of `plan` in order and returns how many were
+    /// removed.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` without removing anything if the
+    /// store changed since the plan was made, so that the plan no longer
+    /// covers every dependent.
+    pub fn apply_plan(&mut self, plan: &GtsRemovalPlan) -> Result<usize, StoreError> {
+        let current = self.plan_removal(&plan.pattern)?;
+        let ids = |p: &GtsRemovalPlan| -> BTreeSet<String> {
+            p.steps.iter().map(|s| s.id.clone()).collect()
+        };
+        if ids(&current) != ids(plan) {
+            return Err(StoreError::ValidationError(format!(
+                "Removal plan for '{}' is out of date; plan it again",
+                plan.pattern
+            )));
+        }
+        for step in &plan.steps {
+            tracing::info!("Removing {}", step.id);
+            self.remove_entity(&step.id);
+        }
+        Ok(plan.steps.len())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use crate::test_fixtures::schema;
+    use serde_json::{Value, json};
+
+    const OLD: &str = "gts.x.test.old.event.v1~";
+    const OLD_DERIVED: &str = "gts.x.test.old.event.v1~x.test.old.placed.v1~";
+    const USES_OLD: &str = "gts.x.test.keep.audit.v1~";
+    const UNRELATED: &str = "gts.x.test.keep.other.v1~";
+
+    fn instance(content: &Value, schema_id: &str) -> GtsEntity {
+        GtsEntity::new(
+            None,
+            None,
+            content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        )
+    }
+
+    fn removal_store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(OLD, &schema(OLD, &json!({})))
+            .unwrap();
+        store
+            .register_schema(
+                OLD_DERIVED,
+                &schema(
+                    OLD_DERIVED,
+                    &json!({"allOf": [{"$ref": format!("gts://{OLD}")}]}),
+                ),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                USES_OLD,
+                &schema(
+                    USES_OLD,
+                    &json!({"properties": {"event": {"type": "string", "x-gts-ref": OLD}}}),
+                ),
+            )
+            .unwrap();
+        store
+            .register_schema(UNRELATED, &schema(UNRELATED, &json!({})))
+            .unwrap();
+        store
+            .register(instance(
+                &json!({"id": format!("{OLD_DERIVED}x.test._.e1.v1")}),
+                OLD_DERIVED,
+            ))
+            .unwrap();
+        // Names the derived event type in its content
+        store
+            .register(instance(
+                &json!({"id": format!("{UNRELATED}x.test._.o1.v1"), "about": OLD_DERIVED}),
+                UNRELATED,
+            ))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_plan_removal_collects_dependents_in_safe_order() {
+        let store = removal_store();
+        let plan = store.plan_removal("gts.x.test.old.*").unwrap();
+        let order: Vec<&str> = plan.steps.iter().map(|s| s.id.as_str()).collect();
+
+        let e1 = format!("{OLD_DERIVED}x.test._.e1.v1");
+        let o1 = format!("{UNRELATED}x.test._.o1.v1");
+        assert_eq!(order.len(), 5, "{order:?}");
+        assert!(!order.contains(&UNRELATED));
+        let pos = |id: &str| order.iter().position(|o| *o == id).unwrap();
+        assert!(pos(&e1) < pos(OLD_DERIVED));
+        assert!(pos(&o1) < pos(OLD_DERIVED));
+        assert!(pos(OLD_DERIVED) < pos(OLD));
+        assert!(pos(USES_OLD) < pos(OLD));
+
+        let step = &plan.steps[pos(USES_OLD)];
+        assert!(!step.matched);
+        assert_eq!(step.depends_on, vec![OLD.to_owned()]);
+        assert!(plan.steps[pos(OLD)].matched);
+    }
+
+    #[test]
+    fn test_apply_plan_removes_and_rejects_stale_plans() {
+        let mut store = removal_store();
+        let plan = store.plan_removal(OLD_DERIVED).unwrap();
+        assert_eq!(plan.steps.len(), 3);
+
+        let mut changed = removal_store();
+        changed
+            .register(instance(
+                &json!({"id": format!("{OLD_DERIVED}x.test._.e2.v1")}),
+                OLD_DERIVED,
+            ))
+            .unwrap();
+        assert!(changed.apply_plan(&plan).is_err());
+        assert_eq!(changed.items().count(), 7);
+
+        assert_eq!(store.apply_plan(&plan).unwrap(), 3);
+        assert_eq!(store.items().count(), 3);
+        assert!(store.audit_refs().entities.is_empty());
+    }
+
+    #[test]
+    fn test_remove_refuses_while_referenced() {
+        let mut store = removal_store();
+        let e1 = format!("{OLD_DERIVED}x.test._.e1.v1");
+
+        let Err(StoreError::RemovalBlocked(blockers)) = store.remove(OLD, false) else {
+            panic!("expected RemovalBlocked");
+        };
+        let ids: Vec<&str> = blockers.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, [USES_OLD, OLD_DERIVED]);
+        assert!(blockers.iter().all(|b| b.target == OLD));
+        assert_eq!(store.items().count(), 6);
+
+        assert!(matches!(
+            store.remove("gts.x.test.old.missing.v1~", false),
+            Err(StoreError::EntityNotFound(_))
+        ));
+        assert_eq!(store.remove(&e1, false).unwrap().gts_id.unwrap().id, e1);
+        store.remove(OLD, true).unwrap();
+        assert!(store.get(OLD).is_none());
+    }
+
+    #[test]
+    fn test_remove_matching_ignores_references_inside_the_set() {
+        let mut store = removal_store();
+        let err = store
+            .remove_matching("gts.x.test.old.*", false)
+            .unwrap_err();
+        // The derived schema and its instance match too; the schema with the
+        // x-gts-ref and the instance naming the derived type do not
+        let StoreError::RemovalBlocked(blockers) = &err else {
+            panic!("expected RemovalBlocked, got {err}");
+        };
+        let ids: Vec<&str> = blockers.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, [USES_OLD, &format!("{UNRELATED}x.test._.o1.v1")]);
+        assert_eq!(err.code(), crate::diagnostic::GtsCode::RemovalBlocked);
+
+        let removed = store.remove_matching("gts.x.test.old.*", true).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert_eq!(store.items().count(), 3);
+        assert!(
+            store
+                .remove_matching("gts.x.test.old.*", false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
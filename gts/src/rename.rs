@@ -0,0 +1,656 @@
+//! Namespace renaming / GTS ID migration.
+//!
+//! A [`GtsIdRenamer`] maps IDs matching a `from` pattern onto a `to` pattern:
+//!
+//! - `gts.x.oldpkg.*` → `gts.x.newpkg.*` rewrites every segment (including
+//!   chained ones such as `...v1~x.oldpkg.ns.type.v1~`) whose vendor/package
+//!   prefix matches.
+//! - `gts.a.b.c.d.v1~` → `gts.a.b.c.e.v1~` renames that type and every ID
+//!   chained from it.
+//! - Any other full ID is renamed only on exact match.
+//!
+//! Files are rewritten textually so formatting, key order and comments survive.
+//! Without `rewrite_refs` only each entity's own ID fields (`$id`/`id` and its
+//! `type`) are changed; with it every reference (`$ref`, `x-gts-ref`, plain
+//! string values) is rewritten too.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use thiserror::Error;
+
+use crate::entities::{GtsConfig, GtsEntity};
+use crate::files_reader::GtsFileReader;
+use crate::gts::{GTS_PREFIX, GTS_URI_PREFIX, GtsID};
+use crate::store::GtsStore;
+
+#[derive(Debug, Error)]
+pub enum RenameError {
+    #[error("Invalid rename pattern '{pattern}': {cause}")]
+    InvalidPattern { pattern: String, cause: String },
+    #[error("Failed to rewrite {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchMode {
+    /// `gts.<prefix>.*` without `~`: applied to every segment of a chain.
+    SegmentPrefix,
+    /// Full type ID or chained wildcard: applied to the start of the ID.
+    Prefix,
+    Exact,
+}
+
+#[derive(Debug, Clone)]
+pub struct GtsIdRenamer {
+    from: String,
+    to: String,
+    mode: MatchMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsRenameMapping {
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsRenamedFile {
+    pub path: String,
+    pub replacements: usize,
+}
+
+/// Record of a rename run: every ID mapping applied and every file touched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsRenameManifest {
+    pub from: String,
+    pub to: String,
+    pub rewrite_refs: bool,
+    pub dry_run: bool,
+    pub mappings: Vec<GtsRenameMapping>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<GtsRenamedFile>,
+    /// Renames that would have produced an invalid GTS ID and were skipped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid: Vec<GtsRenameMapping>,
+}
+
+impl GtsRenameManifest {
+    fn new(renamer: &GtsIdRenamer, rewrite_refs: bool, dry_run: bool) -> Self {
+        GtsRenameManifest {
+            from: renamer.source_pattern(),
+            to: renamer.target_pattern(),
+            rewrite_refs,
+            dry_run,
+            mappings: Vec::new(),
+            files: Vec::new(),
+            invalid: Vec::new(),
+        }
+    }
+}
+
+impl GtsIdRenamer {
+    /// Builds a renamer from a pair of patterns. Both must start with `gts.`
+    /// and either both end with `*` or neither does.
+    ///
+    /// # Errors
+    /// Returns `RenameError::InvalidPattern` if the patterns are malformed.
+    pub fn new(from: &str, to: &str) -> Result<Self, RenameError> {
+        let invalid = |pattern: &str, cause: &str| RenameError::InvalidPattern {
+            pattern: pattern.to_owned(),
+            cause: cause.to_owned(),
+        };
+        for pattern in [from, to] {
+            if !pattern.starts_with(GTS_PREFIX) {
+                return Err(invalid(pattern, "must start with 'gts.'"));
+            }
+            if pattern.trim_end_matches('*').contains('*') {
+                return Err(invalid(pattern, "'*' is only allowed at the end"));
+            }
+        }
+        let (from_prefix, to_prefix) = match (from.strip_suffix('*'), to.strip_suffix('*')) {
+            (Some(f), Some(t)) => (f, t),
+            (None, None) => (from, to),
+            _ => return Err(invalid(to, "both patterns must end with '*' or neither")),
+        };
+        let mode = if from.ends_with('*') && !from_prefix.contains('~') {
+            if to_prefix.contains('~') {
+                return Err(invalid(to, "segment prefix cannot be renamed to a chain"));
+            }
+            MatchMode::SegmentPrefix
+        } else if from.ends_with('*') || from.ends_with('~') {
+            MatchMode::Prefix
+        } else {
+            MatchMode::Exact
+        };
+        if mode != MatchMode::SegmentPrefix && !from.ends_with('*') && !GtsID::is_valid(to) {
+            return Err(invalid(to, "not a valid GTS ID"));
+        }
+        Ok(GtsIdRenamer {
+            from: from_prefix.to_owned(),
+            to: to_prefix.to_owned(),
+            mode,
+        })
+    }
+
+    fn source_pattern(&self) -> String {
+        match self.mode {
+            MatchMode::Exact => self.from.clone(),
+            _ if self.from.ends_with('~') && self.to.ends_with('~') => self.from.clone(),
+            _ => format!("{}*", self.from),
+        }
+    }
+
+    fn target_pattern(&self) -> String {
+        match self.mode {
+            MatchMode::Exact => self.to.clone(),
+            _ if self.from.ends_with('~') && self.to.ends_with('~') => self.to.clone(),
+            _ => format!("{}*", self.to),
+        }
+    }
+
+    /// Returns the renamed ID, or `None` if `id` does not match. Accepts plain
+    /// IDs, wildcard patterns and `gts://` URIs.
+    #[must_use]
+    pub fn rename(&self, id: &str) -> Option<String> {
+        if let Some(bare) = id.strip_prefix(GTS_URI_PREFIX) {
+            return self.rename(bare).map(|r| format!("{GTS_URI_PREFIX}{r}"));
+        }
+        match self.mode {
+            MatchMode::Exact => (id == self.from).then(|| self.to.clone()),
+            MatchMode::Prefix => id
+                .strip_prefix(self.from.as_str())
+                .map(|rest| format!("{}{rest}", self.to)),
+            MatchMode::SegmentPrefix => {
+                let body = id.strip_prefix(GTS_PREFIX)?;
+                let from_seg = &self.from[GTS_PREFIX.len()..];
+                let to_seg = &self.to[GTS_PREFIX.len()..];
+                let mut changed = false;
+                let renamed: String = body
+                    .split_inclusive('~')
+                    .map(|seg| match seg.strip_prefix(from_seg) {
+                        Some(rest) => {
+                            changed = true;
+                            format!("{to_seg}{rest}")
+                        }
+                        None => seg.to_owned(),
+                    })
+                    .collect();
+                changed.then(|| format!("{GTS_PREFIX}{renamed}"))
+            }
+        }
+    }
+
+    /// Renames `id` only if the result is still a valid ID (or pattern).
+    fn checked_rename(&self, id: &str, manifest: &mut Mappings) -> Option<String> {
+        let new = self.rename(id)?;
+        let bare = new.strip_prefix(GTS_URI_PREFIX).unwrap_or(&new);
+        let old_bare = id.strip_prefix(GTS_URI_PREFIX).unwrap_or(id);
+        if !bare.contains('*') && GtsID::is_valid(old_bare) && !GtsID::is_valid(bare) {
+            manifest
+                .invalid
+                .insert(old_bare.to_owned(), bare.to_owned());
+            return None;
+        }
+        if !bare.contains('*') {
+            manifest
+                .applied
+                .insert(old_bare.to_owned(), bare.to_owned());
+        }
+        Some(new)
+    }
+}
+
+#[derive(Default)]
+struct Mappings {
+    applied: BTreeMap<String, String>,
+    invalid: BTreeMap<String, String>,
+}
+
+impl Mappings {
+    fn into_manifest(self, manifest: &mut GtsRenameManifest) {
+        let to_vec = |m: BTreeMap<String, String>| {
+            m.into_iter()
+                .map(|(old, new)| GtsRenameMapping { old, new })
+                .collect()
+        };
+        manifest.mappings = to_vec(self.applied);
+        manifest.invalid = to_vec(self.invalid);
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '~' | '*' | '-')
+}
+
+/// Rewrites every GTS ID token in `text` for which `rename` returns a value.
+/// Returns the new text and the number of replacements.
+fn rewrite_text(text: &str, mut rename: impl FnMut(&str) -> Option<String>) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut rest = text;
+    while let Some(pos) = rest.find(GTS_PREFIX) {
+        let at_boundary = rest[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_ascii_alphanumeric() && c != '_');
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let len = rest.find(|c: char| !is_token_char(c)).unwrap_or(rest.len());
+        let token = rest[..len].trim_end_matches('.');
+        match at_boundary.then(|| rename(token)).flatten() {
+            Some(new) => {
+                out.push_str(&new);
+                count += 1;
+            }
+            None => out.push_str(token),
+        }
+        rest = &rest[token.len()..];
+    }
+    out.push_str(rest);
+    (out, count)
+}
+
+/// Top-level fields through which an entity declares its own ID and type.
+fn own_id_fields(cfg: &GtsConfig) -> HashSet<String> {
+    cfg.entity_id_fields
+        .iter()
+        .chain(&cfg.schema_id_fields)
+        .filter(|f| *f != "$schema")
+        .cloned()
+        .collect()
+}
+
+/// IDs the entities of a file declare about themselves.
+fn file_own_ids(content: &Value, fields: &HashSet<String>) -> HashSet<String> {
+    let items: Vec<&Value> = match content {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    items
+        .into_iter()
+        .filter_map(Value::as_object)
+        .flat_map(|obj| fields.iter().filter_map(|f| obj.get(f)?.as_str()))
+        .map(|v| v.strip_prefix(GTS_URI_PREFIX).unwrap_or(v).to_owned())
+        .collect()
+}
+
+/// Renames IDs across every GTS file found under `paths`.
+///
+/// # Errors
+/// Returns `RenameError::Io` if a file cannot be read or written.
+pub fn rename_in_paths(
+    paths: &[String],
+    renamer: &GtsIdRenamer,
+    rewrite_refs: bool,
+    dry_run: bool,
+    cfg: &GtsConfig,
+) -> Result<GtsRenameManifest, RenameError> {
+    let mut manifest = GtsRenameManifest::new(renamer, rewrite_refs, dry_run);
+    let mut mappings = Mappings::default();
+    let mut reader = GtsFileReader::new(paths, Some(cfg.clone()));
+    let fields = own_id_fields(cfg);
+
+    for path in reader.discovered_files() {
+        let io_err = |source| RenameError::Io {
+            path: path.display().to_string(),
+            source,
+        };
+        let text = fs::read_to_string(path).map_err(io_err)?;
+        let own = match GtsFileReader::load_json_file(path) {
+            Ok(content) => file_own_ids(&content, &fields),
+            Err(e) => {
+                tracing::warn!("Skipping unparsable file {}: {e}", path.display());
+                continue;
+            }
+        };
+
+        let (new_text, replacements) = rewrite_text(&text, |token| {
+            if rewrite_refs || own.contains(token) {
+                renamer.checked_rename(token, &mut mappings)
+            } else {
+                None
+            }
+        });
+        if replacements == 0 {
+            continue;
+        }
+        if !dry_run {
+            fs::write(path, new_text).map_err(io_err)?;
+        }
+        manifest.files.push(GtsRenamedFile {
+            path: path.display().to_string(),
+            replacements,
+        });
+    }
+
+    mappings.into_manifest(&mut manifest);
+    Ok(manifest)
+}
+
+fn rewrite_value(
+    value: &Value,
+    rename: &mut impl FnMut(&str) -> Option<String>,
+    only_fields: Option<&HashSet<String>>,
+) -> Value {
+    match value {
+        Value::String(s) if only_fields.is_none() => {
+            let (new, _) = rewrite_text(s, &mut *rename);
+            Value::String(new)
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = match (only_fields, v) {
+                        (Some(fields), Value::String(s)) if fields.contains(k) => {
+                            Value::String(rewrite_text(s, &mut *rename).0)
+                        }
+                        (Some(_), other) => other.clone(),
+                        (None, other) => rewrite_value(other, rename, None),
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(items) if only_fields.is_none() => Value::Array(
+            items
+                .iter()
+                .map(|v| rewrite_value(v, rename, None))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+impl GtsStore {
+    /// Renames IDs of the entities held in the store, re-keying them under
+    /// their new IDs.
+    pub fn rename_ids(
+        &mut self,
+        renamer: &GtsIdRenamer,
+        rewrite_refs: bool,
+        cfg: &GtsConfig,
+    ) -> GtsRenameManifest {
+        let mut manifest = GtsRenameManifest::new(renamer, rewrite_refs, false);
+        let mut mappings = Mappings::default();
+        let entities = std::mem::take(self.entities_mut());
+        let mut labels = std::mem::take(self.all_labels_mut());
+        let own_fields = own_id_fields(cfg);
+
+        for (id, entity) in entities {
+            let content = rewrite_value(
+                &entity.content,
+                &mut |token| renamer.checked_rename(token, &mut mappings),
+                (!rewrite_refs).then_some(&own_fields),
+            );
+            let new_id = renamer
+                .checked_rename(&id, &mut mappings)
+                .unwrap_or_else(|| id.clone());
+            let schema_id = entity.schema_id.as_deref().map(|s| {
+                renamer
+                    .checked_rename(s, &mut mappings)
+                    .unwrap_or_else(|| s.to_owned())
+            });
+
+            let rekeyed = GtsEntity::new(
+                entity.file,
+                entity.list_sequence,
+                &content,
+                Some(cfg),
+                entity
+                    .gts_id
+                    .as_ref()
+                    .and_then(|_| GtsID::new(&new_id).ok()),
+                entity.is_schema,
+                entity.label,
+                Some(entity.validation),
+                schema_id,
+            );
+            if let Some(entity_labels) = labels.remove(&id) {
+                self.all_labels_mut().insert(new_id.clone(), entity_labels);
+            }
+            self.entities_mut().insert(new_id, rekeyed);
+        }
+        self.rebuild_indexes();
+
+        mappings.into_manifest(&mut manifest);
+        manifest
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn renamer() -> GtsIdRenamer {
+        GtsIdRenamer::new("gts.x.oldpkg.*", "gts.x.newpkg.*").unwrap()
+    }
+
+    #[test]
+    fn test_pattern_validation() {
+        assert!(GtsIdRenamer::new("x.oldpkg.*", "gts.x.newpkg.*").is_err());
+        assert!(GtsIdRenamer::new("gts.x.oldpkg.*", "gts.x.newpkg.").is_err());
+        assert!(GtsIdRenamer::new("gts.x.*.old", "gts.x.newpkg.*").is_err());
+        assert!(GtsIdRenamer::new("gts.x.a.b.c.v1~", "not-an-id").is_err());
+        assert!(GtsIdRenamer::new("gts.x.a.b.c.v1~", "gts.x.a.b.d.v1~").is_ok());
+    }
+
+    #[test]
+    fn test_segment_prefix_rename() {
+        let r = renamer();
+        assert_eq!(
+            r.rename("gts.x.oldpkg.ns.order.v1~").as_deref(),
+            Some("gts.x.newpkg.ns.order.v1~")
+        );
+        assert_eq!(
+            r.rename("gts.x.core.events.type.v1~x.oldpkg.ns.placed.v1~")
+                .as_deref(),
+            Some("gts.x.core.events.type.v1~x.newpkg.ns.placed.v1~")
+        );
+        assert_eq!(
+            r.rename("gts://gts.x.oldpkg.ns.order.v1~").as_deref(),
+            Some("gts://gts.x.newpkg.ns.order.v1~")
+        );
+        assert_eq!(
+            r.rename("gts.x.oldpkg.*").as_deref(),
+            Some("gts.x.newpkg.*")
+        );
+        assert!(r.rename("gts.x.other.ns.order.v1~").is_none());
+    }
+
+    #[test]
+    fn test_type_prefix_rename_includes_chains() {
+        let r = GtsIdRenamer::new("gts.x.a.b.order.v1~", "gts.x.a.b.purchase.v1~").unwrap();
+        assert_eq!(
+            r.rename("gts.x.a.b.order.v1~x.c.d.e.v1").as_deref(),
+            Some("gts.x.a.b.purchase.v1~x.c.d.e.v1")
+        );
+        assert!(r.rename("gts.x.a.b.order.v2~").is_none());
+    }
+
+    #[test]
+    fn test_rewrite_text_respects_boundaries() {
+        let r = renamer();
+        let (out, n) = rewrite_text(
+            r#"{"$ref": "gts://gts.x.oldpkg.ns.a.v1~", "note": "see gts.x.oldpkg.ns.b.v1~.", "k": "notgts.x.oldpkg.ns.c.v1~"}"#,
+            |t| r.rename(t),
+        );
+        assert_eq!(n, 2);
+        assert!(out.contains("gts://gts.x.newpkg.ns.a.v1~"));
+        assert!(out.contains("see gts.x.newpkg.ns.b.v1~."));
+        assert!(out.contains("notgts.x.oldpkg.ns.c.v1~"));
+    }
+
+    fn write_fixture(dir: &TempDir) {
+        fs::write(
+            dir.path().join("schema.json"),
+            r#"{
+  "$id": "gts://gts.x.oldpkg.ns.order.v1~",
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "type": "object",
+  "properties": {
+    "customer": {"type": "string", "x-gts-ref": "gts.x.oldpkg.ns.customer.v1~"}
+  }
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("instance.yaml"),
+            "id: gts.x.oldpkg.ns.order.v1~x.shop.orders.o1.v1\ntype: gts.x.oldpkg.ns.order.v1~\ncustomer: gts.x.oldpkg.ns.customer.v1~x.shop.people.c1.v1\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rename_in_paths_own_ids_only() {
+        let dir = TempDir::new().unwrap();
+        write_fixture(&dir);
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+
+        let manifest =
+            rename_in_paths(&paths, &renamer(), false, false, &GtsConfig::default()).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+
+        let schema = fs::read_to_string(dir.path().join("schema.json")).unwrap();
+        assert!(schema.contains("gts://gts.x.newpkg.ns.order.v1~"));
+        assert!(schema.contains("\"x-gts-ref\": \"gts.x.oldpkg.ns.customer.v1~\""));
+        // Formatting is preserved
+        assert!(schema.starts_with("{\n  \"$id\""));
+
+        let instance = fs::read_to_string(dir.path().join("instance.yaml")).unwrap();
+        assert!(instance.contains("id: gts.x.newpkg.ns.order.v1~x.shop.orders.o1.v1"));
+        assert!(instance.contains("type: gts.x.newpkg.ns.order.v1~\n"));
+        assert!(instance.contains("customer: gts.x.oldpkg.ns.customer"));
+    }
+
+    #[test]
+    fn test_rename_in_paths_rewrite_refs_and_manifest() {
+        let dir = TempDir::new().unwrap();
+        write_fixture(&dir);
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+
+        let manifest =
+            rename_in_paths(&paths, &renamer(), true, false, &GtsConfig::default()).unwrap();
+        let schema = fs::read_to_string(dir.path().join("schema.json")).unwrap();
+        assert!(schema.contains("gts.x.newpkg.ns.customer.v1~"));
+        assert!(!schema.contains("oldpkg"));
+
+        let olds: Vec<&str> = manifest.mappings.iter().map(|m| m.old.as_str()).collect();
+        assert!(olds.contains(&"gts.x.oldpkg.ns.order.v1~"));
+        assert!(olds.contains(&"gts.x.oldpkg.ns.customer.v1~x.shop.people.c1.v1"));
+        assert!(manifest.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_rename_in_paths_dry_run_leaves_files() {
+        let dir = TempDir::new().unwrap();
+        write_fixture(&dir);
+        let before = fs::read_to_string(dir.path().join("schema.json")).unwrap();
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+
+        let manifest =
+            rename_in_paths(&paths, &renamer(), true, true, &GtsConfig::default()).unwrap();
+        assert!(manifest.dry_run);
+        assert!(!manifest.mappings.is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("schema.json")).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_store_rename_ids() {
+        let mut store = GtsStore::new(None);
+        let cfg = GtsConfig::default();
+        store
+            .register_schema(
+                "gts.x.oldpkg.ns.order.v1~",
+                &json!({
+                    "$id": "gts://gts.x.oldpkg.ns.order.v1~",
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        let instance = json!({
+            "id": "gts.x.oldpkg.ns.order.v1~x.shop.orders.o1.v1",
+            "type": "gts.x.oldpkg.ns.order.v1~",
+            "parent": "gts.x.oldpkg.ns.order.v1~x.shop.orders.o0.v1"
+        });
+        store
+            .register(GtsEntity::new(
+                None,
+                None,
+                &instance,
+                Some(&cfg),
+                None,
+                false,
+                String::new(),
+                None,
+                None,
+            ))
+            .unwrap();
+
+        let manifest = store.rename_ids(&renamer(), false, &cfg);
+        assert!(store.get("gts.x.newpkg.ns.order.v1~").is_some());
+        assert!(store.get("gts.x.oldpkg.ns.order.v1~").is_none());
+
+        let renamed = store
+            .get("gts.x.newpkg.ns.order.v1~x.shop.orders.o1.v1")
+            .expect("instance re-keyed")
+            .clone();
+        assert_eq!(
+            renamed.schema_id.as_deref(),
+            Some("gts.x.newpkg.ns.order.v1~")
+        );
+        assert_eq!(renamed.content["type"], json!("gts.x.newpkg.ns.order.v1~"));
+        // Without rewrite_refs, references stay untouched
+        assert_eq!(
+            renamed.content["parent"],
+            json!("gts.x.oldpkg.ns.order.v1~x.shop.orders.o0.v1")
+        );
+        assert!(
+            manifest
+                .mappings
+                .iter()
+                .any(|m| m.old == "gts.x.oldpkg.ns.order.v1~")
+        );
+    }
+
+    #[test]
+    fn test_store_rename_ids_moves_labels() {
+        let mut store = GtsStore::new(None);
+        let cfg = GtsConfig::default();
+        store
+            .register_schema(
+                "gts.x.oldpkg.ns.order.v1~",
+                &json!({
+                    "$id": "gts://gts.x.oldpkg.ns.order.v1~",
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        store
+            .label("gts.x.oldpkg.ns.order.v1~", "team", "billing")
+            .unwrap();
+
+        store.rename_ids(&renamer(), false, &cfg);
+        assert_eq!(
+            store
+                .labels("gts.x.newpkg.ns.order.v1~")
+                .get("team")
+                .map(String::as_str),
+            Some("billing")
+        );
+        assert!(store.labels("gts.x.oldpkg.ns.order.v1~").is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+//! Enforcement of [`GtsSandboxLimits`] by the store.
+//!
+//! The limits themselves and the static schema checks live in `gts-core`;
+//! this module adds what needs the validator: regex engine options for
+//! `jsonschema` and the cost estimate behind the step budget.  Violations are
+//! reported as `StoreError::PolicyViolation`.
+
+use jsonschema::{PatternOptions, Regex};
+use serde_json::Value;
+
+pub use gts_core::sandbox::GtsSandboxLimits;
+
+/// Regex engine options for `jsonschema` validators.
+pub(crate) fn pattern_options(limits: &GtsSandboxLimits) -> PatternOptions<Regex> {
+    PatternOptions::regex()
+        .size_limit(limits.regex_size_limit)
+        .dfa_size_limit(limits.regex_dfa_size_limit)
+}
+
+/// Number of JSON values in `value`, including itself.
+pub(crate) fn value_count(value: &Value) -> u64 {
+    match value {
+        Value::Object(map) => 1 + map.values().map(value_count).sum::<u64>(),
+        Value::Array(items) => 1 + items.iter().map(value_count).sum::<u64>(),
+        _ => 1,
+    }
+}
+
+/// Number of keywords in a schema, i.e. object keys at any depth.
+pub(crate) fn keyword_count(schema: &Value) -> u64 {
+    match schema {
+        Value::Object(map) => map.values().map(|v| 1 + keyword_count(v)).sum::<u64>(),
+        Value::Array(items) => items.iter().map(keyword_count).sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::store::{GtsStore, StoreError};
+    use serde_json::json;
+
+    const ITEM: &str = "gts.x.test.sandbox.item.v1~";
+
+    fn schema(properties: &Value) -> Value {
+        json!({
+            "$id": format!("gts://{ITEM}"),
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": properties
+        })
+    }
+
+    #[test]
+    fn test_store_enforces_limits() {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ITEM,
+                &schema(&json!({"tags": {"type": "array", "items": {"enum": [1, 2, 3]}}})),
+            )
+            .unwrap();
+        let item = json!({"tags": [1, 2, 3, 1, 2, 3]});
+        assert!(store.validate_value(ITEM, &item).is_ok());
+
+        store.set_sandbox_limits(Some(GtsSandboxLimits {
+            max_enum_values: 2,
+            ..GtsSandboxLimits::default()
+        }));
+        assert!(matches!(
+            store.validate_value(ITEM, &item),
+            Err(StoreError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            store.validate_schema(ITEM),
+            Err(StoreError::PolicyViolation(_))
+        ));
+
+        store.set_sandbox_limits(Some(GtsSandboxLimits {
+            max_validation_steps: 50,
+            ..GtsSandboxLimits::default()
+        }));
+        assert!(store.validate_value(ITEM, &json!({"tags": [1]})).is_ok());
+        let err = store.validate_value(ITEM, &item).unwrap_err();
+        assert!(err.to_string().contains("validation budget"), "{err}");
+    }
+}
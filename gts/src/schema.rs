@@ -157,6 +157,21 @@ impl GtsSchema for () {
     }
 }
 
+/// Marker for types declared with `#[struct_to_gts_schema]`.
+///
+/// Unlike [`GtsSchema`], which anyone can implement by hand, this trait is
+/// only implemented by the macro (and by `()`, the empty payload).  Structs
+/// declared with `payload_must_be_gts = true` require it of their generic
+/// parameter, so they cannot be instantiated with an ad-hoc payload whose
+/// schema is never generated or registered.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a GTS type",
+    label = "declare `{Self}` with `#[struct_to_gts_schema]` to use it as a GTS payload"
+)]
+pub trait GtsTyped: GtsSchema {}
+
+impl GtsTyped for () {}
+
 /// Private trait for nested GTS struct serialization.
 ///
 /// Nested structs implement this instead of `serde::Serialize` to prevent
@@ -13,6 +13,8 @@
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
+use crate::diagnostic::{GtsCode, GtsDiagnostic};
+
 /// Represents the effective (flattened) schema used for compatibility comparison.
 pub(crate) struct EffectiveSchema {
     pub properties: HashMap<String, Value>,
@@ -84,13 +86,13 @@ pub(crate) fn extract_effective_schema(schema: &Value) -> EffectiveSchema {
 /// - Derived cannot change array `items` type
 ///
 /// Returns an empty `Vec` when the schemas are compatible, otherwise a list of
-/// human-readable error descriptions.
+/// coded, human-readable error descriptions.
 pub(crate) fn validate_schema_compatibility(
     base: &EffectiveSchema,
     derived: &EffectiveSchema,
     base_id: &str,
     derived_id: &str,
-) -> Vec<String> {
+) -> Vec<GtsDiagnostic> {
     let mut errors = Vec::new();
     let base_disallows_additional = matches!(base.additional_properties, Some(Value::Bool(false)));
 
@@ -98,8 +100,11 @@ pub(crate) fn validate_schema_compatibility(
         if let Some(base_prop) = base.properties.get(prop_name) {
             // Property exists in both – check for disabling
             if *derived_prop == Value::Bool(false) {
-                errors.push(format!(
-                    "property '{prop_name}': derived schema '{derived_id}' disables property defined in base '{base_id}'"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::PropertyDisabled,
+                    format!(
+                        "property '{prop_name}': derived schema '{derived_id}' disables property defined in base '{base_id}'"
+                    ),
                 ));
                 continue;
             }
@@ -109,9 +114,12 @@ pub(crate) fn validate_schema_compatibility(
         }
         // New property in derived – check additionalProperties
         else if base_disallows_additional {
-            errors.push(format!(
+            errors.push(GtsDiagnostic::new(
+                GtsCode::PropertyAdded,
+                format!(
                     "property '{prop_name}': derived schema '{derived_id}' adds new property but base '{base_id}' has additionalProperties: false"
-                ));
+                ),
+            ));
         }
     }
 
@@ -120,8 +128,11 @@ pub(crate) fn validate_schema_compatibility(
         let derived_allows_additional =
             !matches!(derived.additional_properties, Some(Value::Bool(false)));
         if derived_allows_additional {
-            errors.push(format!(
-                "derived schema '{derived_id}' loosens additionalProperties from false in base '{base_id}'"
+            errors.push(GtsDiagnostic::new(
+                GtsCode::AdditionalPropertiesLoosened,
+                format!(
+                    "derived schema '{derived_id}' loosens additionalProperties from false in base '{base_id}'"
+                ),
             ));
         }
     }
@@ -132,6 +143,18 @@ pub(crate) fn validate_schema_compatibility(
     errors
 }
 
+/// Checks a single property: returns the ways `derived_prop` loosens
+/// `base_prop` (empty when every value it accepts is accepted by the base).
+pub(crate) fn property_compatibility(
+    base_prop: &Value,
+    derived_prop: &Value,
+    prop_name: &str,
+) -> Vec<GtsDiagnostic> {
+    let mut errors = Vec::new();
+    compare_property_constraints(base_prop, derived_prop, prop_name, &mut errors);
+    errors
+}
+
 // ---------------------------------------------------------------------------
 // Constraint comparison helpers
 // ---------------------------------------------------------------------------
@@ -143,7 +166,7 @@ fn compare_property_constraints(
     base_prop: &Value,
     derived_prop: &Value,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     // If base is not an object schema, it places no constraints to loosen.
     let Some(base_map) = base_prop.as_object() else {
@@ -153,9 +176,12 @@ fn compare_property_constraints(
     // If derived is a boolean `true` schema (or any non-object), it accepts
     // everything and therefore loosens any constraint the base defines.
     let Some(derived_map) = derived_prop.as_object() else {
-        errors.push(format!(
-            "property '{prop_name}': derived replaces schema object with a non-object value, \
-             loosening base constraints"
+        errors.push(GtsDiagnostic::new(
+            GtsCode::PropertyUnconstrained,
+            format!(
+                "property '{prop_name}': derived replaces schema object with a non-object value, \
+                 loosening base constraints"
+            ),
         ));
         return;
     };
@@ -215,7 +241,10 @@ fn compare_property_constraints(
         let nested_errors =
             validate_schema_compatibility(&base_nested, &derived_nested, "base", "derived");
         for err in nested_errors {
-            errors.push(format!("in nested object '{prop_name}': {err}"));
+            errors.push(GtsDiagnostic::new(
+                err.code,
+                format!("in nested object '{prop_name}': {}", err.message),
+            ));
         }
     }
 }
@@ -228,13 +257,16 @@ fn check_type_compatibility(
     base_map: &serde_json::Map<String, Value>,
     derived_map: &serde_json::Map<String, Value>,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let (Some(base_type), Some(derived_type)) = (base_map.get("type"), derived_map.get("type"))
         && base_type != derived_type
     {
-        errors.push(format!(
-            "property '{prop_name}': derived changes type from {base_type} to {derived_type}"
+        errors.push(GtsDiagnostic::new(
+            GtsCode::TypeChanged,
+            format!(
+                "property '{prop_name}': derived changes type from {base_type} to {derived_type}"
+            ),
         ));
     }
 }
@@ -249,18 +281,24 @@ fn check_const_compatibility(
     base_map: &serde_json::Map<String, Value>,
     derived_map: &serde_json::Map<String, Value>,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(base_const) = base_map.get("const") {
         match derived_map.get("const") {
             Some(derived_const) if base_const != derived_const => {
-                errors.push(format!(
-                    "property '{prop_name}': derived redefines const from {base_const} to {derived_const}"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::ConstChanged,
+                    format!(
+                        "property '{prop_name}': derived redefines const from {base_const} to {derived_const}"
+                    ),
                 ));
             }
             None => {
-                errors.push(format!(
-                    "property '{prop_name}': derived omits const constraint ({base_const}) defined in base"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::ConstOmitted,
+                    format!(
+                        "property '{prop_name}': derived omits const constraint ({base_const}) defined in base"
+                    ),
                 ));
             }
             _ => {} // Same const or derived adds tightening
@@ -278,18 +316,24 @@ fn check_pattern_compatibility(
     base_map: &serde_json::Map<String, Value>,
     derived_map: &serde_json::Map<String, Value>,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(base_pat) = base_map.get("pattern") {
         match derived_map.get("pattern") {
             Some(derived_pat) if base_pat != derived_pat => {
-                errors.push(format!(
-                    "property '{prop_name}': derived changes pattern from {base_pat} to {derived_pat}"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::PatternChanged,
+                    format!(
+                        "property '{prop_name}': derived changes pattern from {base_pat} to {derived_pat}"
+                    ),
                 ));
             }
             None => {
-                errors.push(format!(
-                    "property '{prop_name}': derived omits pattern constraint ({base_pat}) defined in base"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::PatternOmitted,
+                    format!(
+                        "property '{prop_name}': derived omits pattern constraint ({base_pat}) defined in base"
+                    ),
                 ));
             }
             _ => {} // Same pattern
@@ -306,15 +350,18 @@ fn check_enum_compatibility(
     base_map: &serde_json::Map<String, Value>,
     derived_map: &serde_json::Map<String, Value>,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(Value::Array(base_enum)) = base_map.get("enum") {
         // Check if derived has enum (subset check)
         if let Some(Value::Array(derived_enum)) = derived_map.get("enum") {
             for val in derived_enum {
                 if !base_enum.contains(val) {
-                    errors.push(format!(
-                        "property '{prop_name}': derived enum contains value {val} not in base enum"
+                    errors.push(GtsDiagnostic::new(
+                        GtsCode::EnumLoosened,
+                        format!(
+                            "property '{prop_name}': derived enum contains value {val} not in base enum"
+                        ),
                     ));
                 }
             }
@@ -323,15 +370,19 @@ fn check_enum_compatibility(
         // Check if derived has const (must be in base enum — tightening from set to single)
         if let Some(derived_const) = derived_map.get("const") {
             if !base_enum.contains(derived_const) {
-                errors.push(format!(
-                    "property '{prop_name}': derived const {derived_const} is not in base enum"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::EnumLoosened,
+                    format!(
+                        "property '{prop_name}': derived const {derived_const} is not in base enum"
+                    ),
                 ));
             }
             return;
         }
         // Neither enum nor const — loosening
-        errors.push(format!(
-            "property '{prop_name}': derived omits enum constraint defined in base"
+        errors.push(GtsDiagnostic::new(
+            GtsCode::EnumLoosened,
+            format!("property '{prop_name}': derived omits enum constraint defined in base"),
         ));
     }
 }
@@ -345,7 +396,7 @@ fn check_items_compatibility(
     base_map: &serde_json::Map<String, Value>,
     derived_map: &serde_json::Map<String, Value>,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(base_items) = base_map.get("items") {
         match derived_map.get("items") {
@@ -355,8 +406,11 @@ fn check_items_compatibility(
                 compare_property_constraints(base_items, derived_items, &items_name, errors);
             }
             None => {
-                errors.push(format!(
-                    "property '{prop_name}': derived omits items constraint defined in base"
+                errors.push(GtsDiagnostic::new(
+                    GtsCode::ItemsOmitted,
+                    format!(
+                        "property '{prop_name}': derived omits items constraint defined in base"
+                    ),
                 ));
             }
         }
@@ -373,7 +427,7 @@ fn check_required_removal(
     derived: &EffectiveSchema,
     base_id: &str,
     derived_id: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     // Only check if derived explicitly declares any required fields
     // (if derived doesn't declare required at all, allOf semantics inherit base's required)
@@ -382,13 +436,21 @@ fn check_required_removal(
     }
     for base_req in &base.required {
         if !derived.required.contains(base_req) {
-            errors.push(format!(
-                "derived schema '{derived_id}' removes required field '{base_req}' defined in base '{base_id}'"
+            errors.push(GtsDiagnostic::new(
+                GtsCode::RequiredRemoved,
+                format!(
+                    "derived schema '{derived_id}' removes required field '{base_req}' defined in base '{base_id}'"
+                ),
             ));
         }
     }
 }
 
+/// The code for loosening bound `keyword`.
+fn bound_code(keyword: &str) -> GtsCode {
+    GtsCode::loosened_bound(keyword).unwrap_or(GtsCode::IncompatibleSchema)
+}
+
 /// Helper: derived upper-bound constraint must be **<=** base.
 /// If base has an upper bound and derived omits it, that's incompatible (loosening).
 fn check_upper_bound(
@@ -396,7 +458,7 @@ fn check_upper_bound(
     derived_map: &serde_json::Map<String, Value>,
     keyword: &str,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(base_val) = base_map.get(keyword) {
         match derived_map.get(keyword) {
@@ -404,14 +466,20 @@ fn check_upper_bound(
                 if let (Some(b), Some(d)) = (base_val.as_f64(), derived_val.as_f64())
                     && d > b
                 {
-                    errors.push(format!(
-                        "property '{prop_name}': derived {keyword} ({d}) exceeds base {keyword} ({b})"
+                    errors.push(GtsDiagnostic::new(
+                        bound_code(keyword),
+                        format!(
+                            "property '{prop_name}': derived {keyword} ({d}) exceeds base {keyword} ({b})"
+                        ),
                     ));
                 }
             }
             None => {
-                errors.push(format!(
-                    "property '{prop_name}': derived omits {keyword} constraint ({base_val}) defined in base"
+                errors.push(GtsDiagnostic::new(
+                    bound_code(keyword),
+                    format!(
+                        "property '{prop_name}': derived omits {keyword} constraint ({base_val}) defined in base"
+                    ),
                 ));
             }
         }
@@ -425,7 +493,7 @@ fn check_lower_bound(
     derived_map: &serde_json::Map<String, Value>,
     keyword: &str,
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     if let Some(base_val) = base_map.get(keyword) {
         match derived_map.get(keyword) {
@@ -433,14 +501,20 @@ fn check_lower_bound(
                 if let (Some(b), Some(d)) = (base_val.as_f64(), derived_val.as_f64())
                     && d < b
                 {
-                    errors.push(format!(
-                        "property '{prop_name}': derived {keyword} ({d}) is less than base {keyword} ({b})"
+                    errors.push(GtsDiagnostic::new(
+                        bound_code(keyword),
+                        format!(
+                            "property '{prop_name}': derived {keyword} ({d}) is less than base {keyword} ({b})"
+                        ),
                     ));
                 }
             }
             None => {
-                errors.push(format!(
-                    "property '{prop_name}': derived omits {keyword} constraint ({base_val}) defined in base"
+                errors.push(GtsDiagnostic::new(
+                    bound_code(keyword),
+                    format!(
+                        "property '{prop_name}': derived omits {keyword} constraint ({base_val}) defined in base"
+                    ),
                 ));
             }
         }
@@ -469,7 +543,7 @@ fn check_enumerated_values_against_base(
     base_map: &serde_json::Map<String, Value>,
     values: &[Value],
     prop_name: &str,
-    errors: &mut Vec<String>,
+    errors: &mut Vec<GtsDiagnostic>,
 ) {
     // Check numeric lower bounds (minimum, minLength, minItems)
     for keyword in &["minimum", "minLength", "minItems"] {
@@ -489,9 +563,12 @@ fn check_enumerated_values_against_base(
                 if let Some(n) = numeric
                     && n < base_val
                 {
-                    errors.push(format!(
-                        "property '{prop_name}': derived const/enum value {val} violates \
-                         base {keyword} ({base_val})"
+                    errors.push(GtsDiagnostic::new(
+                        bound_code(keyword),
+                        format!(
+                            "property '{prop_name}': derived const/enum value {val} violates \
+                             base {keyword} ({base_val})"
+                        ),
                     ));
                 }
             }
@@ -516,9 +593,12 @@ fn check_enumerated_values_against_base(
                 if let Some(n) = numeric
                     && n > base_val
                 {
-                    errors.push(format!(
-                        "property '{prop_name}': derived const/enum value {val} violates \
-                         base {keyword} ({base_val})"
+                    errors.push(GtsDiagnostic::new(
+                        bound_code(keyword),
+                        format!(
+                            "property '{prop_name}': derived const/enum value {val} violates \
+                             base {keyword} ({base_val})"
+                        ),
                     ));
                 }
             }
@@ -972,7 +1052,7 @@ mod tests {
         );
         assert!(
             errs.iter()
-                .any(|e| e.contains("violates") && e.contains("minimum")),
+                .any(|e| e.message.contains("violates") && e.message.contains("minimum")),
             "error should mention minimum violation: {errs:?}"
         );
     }
@@ -0,0 +1,197 @@
+//! Fallback schema resolution for instances of unregistered minor versions.
+//!
+//! Producers can move to a new minor version before the registry catches up:
+//! an instance declaring `gts.x.shop.orders.order.v1.3~` arrives while only
+//! `v1.5~` is registered.  Minor versions are backward compatible, so with
+//! [`GtsSchemaResolution::NearestMinor`] such an instance is validated against
+//! the nearest newer registered minor of the same major instead of failing
+//! with `SchemaNotFound`.  Older minors are never used, as they may not know
+//! fields a newer producer sends.
+
+pub use gts_core::schema_resolution::{GtsSchemaResolution, GtsSchemaSubstitution};
+
+use serde_json::Value;
+
+use crate::gts::GtsID;
+use crate::store::{GtsStore, StoreError};
+
+/// The minor version of `candidate` if it is a schema of the same type as
+/// `requested`, at least as new.
+fn minor_of(requested: &GtsID, candidate: &GtsID) -> Option<u32> {
+    let minor = candidate.gts_id_segments.last()?.ver_minor.unwrap_or(0);
+    let requested_minor = requested.gts_id_segments.last()?.ver_minor.unwrap_or(0);
+    (candidate.is_type() && candidate.same_type_as(requested) && minor >= requested_minor)
+        .then_some(minor)
+}
+
+impl GtsStore {
+    /// The registered schema nearest to `schema_id` that instances of it are
+    /// compatible with: the lowest newer minor version of the same major.
+    /// `None` if `schema_id` is registered itself or has no such version.
+    #[must_use]
+    pub fn nearest_minor_schema(&self, schema_id: &str) -> Option<String> {
+        let requested = GtsID::new(schema_id).ok()?;
+        if self.entities().contains_key(&requested.id) {
+            return None;
+        }
+        self.entities()
+            .iter()
+            .filter(|(_, entity)| entity.is_schema)
+            .filter_map(|(id, _)| {
+                let candidate = GtsID::new(id).ok()?;
+                minor_of(&requested, &candidate).map(|minor| (minor, id))
+            })
+            .min()
+            .map(|(_, id)| id.clone())
+    }
+
+    /// Like [`GtsStore::validate_instance`], but reports the schema that was
+    /// used instead of the declared one under
+    /// [`GtsSchemaResolution::NearestMinor`].
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the instance or its schema is missing, or
+    /// validation fails.
+    pub fn validate_instance_resolved(
+        &mut self,
+        gts_id: &str,
+    ) -> Result<Option<GtsSchemaSubstitution>, StoreError> {
+        let (content, schema_id, substitution) = self.instance_schema(gts_id)?;
+        self.validate_value(&schema_id, &content)?;
+        Ok(substitution)
+    }
+
+    /// The content of instance `gts_id` and the schema to validate it
+    /// against, applying the store's [`GtsSchemaResolution`].
+    pub(crate) fn instance_schema(
+        &mut self,
+        gts_id: &str,
+    ) -> Result<(Value, String, Option<GtsSchemaSubstitution>), StoreError> {
+        let gid = GtsID::new(gts_id).map_err(|_| StoreError::ObjectNotFound(gts_id.to_owned()))?;
+
+        let obj = self
+            .get(&gid.id)
+            .ok_or_else(|| StoreError::ObjectNotFound(gts_id.to_owned()))?;
+
+        let declared = obj
+            .schema_id
+            .clone()
+            .ok_or_else(|| StoreError::SchemaForInstanceNotFound(gid.id.clone()))?;
+        let content = obj.content.clone();
+
+        let substitution = if self.schema_resolution() == GtsSchemaResolution::NearestMinor
+            && self.get(&declared).is_none()
+        {
+            self.nearest_minor_schema(&declared)
+                .map(|resolved| GtsSchemaSubstitution {
+                    requested: declared.clone(),
+                    resolved,
+                })
+        } else {
+            None
+        };
+        let schema_id = substitution
+            .as_ref()
+            .map_or(declared, |s| s.resolved.clone());
+
+        if let Some(s) = &substitution {
+            tracing::warn!(
+                "Schema {} of instance {} is not registered; using {}",
+                s.requested,
+                gts_id,
+                s.resolved
+            );
+        }
+        tracing::info!(
+            "Validating instance {} against schema {}",
+            gts_id,
+            schema_id
+        );
+        Ok((content, schema_id, substitution))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    const V1_3: &str = "gts.x.test.res.order.v1.3~";
+
+    fn store_with(schemas: &[&str]) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for id in schemas {
+            store
+                .register_schema(
+                    id,
+                    &json!({
+                        "$id": format!("gts://{id}"),
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "type": "object",
+                        "required": ["total"]
+                    }),
+                )
+                .unwrap();
+        }
+        let content = json!({"id": format!("{V1_3}x.test._.o1.v1"), "total": 3});
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(V1_3.to_owned()),
+        );
+        store.register(entity).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_nearest_minor_schema_prefers_lowest_newer_minor() {
+        let store = store_with(&[
+            "gts.x.test.res.order.v1.2~",
+            "gts.x.test.res.order.v1.5~",
+            "gts.x.test.res.order.v1.4~",
+            "gts.x.test.res.order.v2.0~",
+            "gts.x.test.res.invoice.v1.9~",
+        ]);
+        assert_eq!(
+            store.nearest_minor_schema(V1_3).as_deref(),
+            Some("gts.x.test.res.order.v1.4~")
+        );
+        assert_eq!(
+            store.nearest_minor_schema("gts.x.test.res.order.v1.6~"),
+            None
+        );
+        assert_eq!(
+            store.nearest_minor_schema("gts.x.test.res.order.v1.2~"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_instance_resolved_is_opt_in() {
+        let instance = format!("{V1_3}x.test._.o1.v1");
+        let mut store = store_with(&["gts.x.test.res.order.v1.5~"]);
+        assert!(matches!(
+            store.validate_instance_resolved(&instance),
+            Err(StoreError::SchemaNotFound(_))
+        ));
+
+        store.set_schema_resolution(GtsSchemaResolution::NearestMinor);
+        let substitution = store.validate_instance_resolved(&instance).unwrap();
+        assert_eq!(
+            substitution,
+            Some(GtsSchemaSubstitution {
+                requested: V1_3.to_owned(),
+                resolved: "gts.x.test.res.order.v1.5~".to_owned(),
+            })
+        );
+        assert!(store.validate_instance(&instance).is_ok());
+    }
+}
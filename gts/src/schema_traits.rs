@@ -23,8 +23,15 @@
 //! When composed via `allOf`, an empty sub-schema does not restrict the
 //! validated object.  This means any trait values are accepted as long as
 //! other sub-schemas in the composition don't reject them.
+//!
+//! **Placeholders:** String trait values may contain `${name}` placeholders
+//! (`"topicRef": "${env}.orders"`).  Validation sees them verbatim;
+//! [`resolve_effective_traits`] substitutes them from [`GtsTraitVars`] and
+//! fails if any is left unresolved, so one corpus can serve several
+//! environments.
 
 use serde_json::Value;
+use std::collections::HashMap;
 
 /// Maximum recursion depth for traversing `allOf` nesting.
 /// Prevents stack overflow on deeply nested or maliciously crafted schemas.
@@ -48,7 +55,7 @@ const MAX_RECURSION_DEPTH: usize = 64;
 /// Returns `Vec<String>` of error messages if trait values don't conform to the
 /// effective trait schema or if traits are provided without trait schema.
 #[cfg(test)]
-pub fn validate_traits_chain(chain_schemas: &[(String, Value)]) -> Result<(), Vec<String>> {
+pub(crate) fn validate_traits_chain(chain_schemas: &[(String, Value)]) -> Result<(), Vec<String>> {
     let mut trait_schemas = Vec::new();
     let mut merged = serde_json::Map::new();
     for (_id, content) in chain_schemas {
@@ -74,7 +81,7 @@ pub fn validate_traits_chain(chain_schemas: &[(String, Value)]) -> Result<(), Ve
 /// Returns `Vec<String>` of error messages if trait values don't conform to the
 /// effective trait schema, if required traits are missing, or if traits exist
 /// without a trait schema in the chain.
-pub fn validate_effective_traits(
+pub(crate) fn validate_effective_traits(
     resolved_trait_schemas: &[Value],
     merged_traits: &Value,
     check_unresolved: bool,
@@ -92,7 +99,39 @@ pub fn validate_effective_traits(
         return Ok(());
     }
 
-    // Validate trait schema integrity
+    check_trait_schemas(resolved_trait_schemas)?;
+    let effective_trait_schema = build_effective_trait_schema(resolved_trait_schemas);
+    let effective_traits = apply_defaults(&effective_trait_schema, merged_traits);
+    validate_traits_against_schema(&effective_trait_schema, &effective_traits, check_unresolved)
+}
+
+/// Builds the effective traits object (merged values plus defaults) with
+/// every `${name}` placeholder substituted from `vars`, and validates it.
+///
+/// # Errors
+/// Returns `Vec<String>` of error messages if a placeholder cannot be
+/// resolved, or for any of the failures of [`validate_effective_traits`].
+pub(crate) fn resolve_effective_traits(
+    resolved_trait_schemas: &[Value],
+    merged_traits: &Value,
+    vars: &GtsTraitVars,
+) -> Result<Value, Vec<String>> {
+    if resolved_trait_schemas.is_empty() {
+        validate_effective_traits(resolved_trait_schemas, merged_traits, true)?;
+        return Ok(Value::Object(serde_json::Map::new()));
+    }
+
+    check_trait_schemas(resolved_trait_schemas)?;
+    let effective_trait_schema = build_effective_trait_schema(resolved_trait_schemas);
+    let defaulted = apply_defaults(&effective_trait_schema, merged_traits);
+    let effective_traits = substitute_placeholders(&defaulted, vars)?;
+    validate_traits_against_schema(&effective_trait_schema, &effective_traits, true)?;
+    Ok(effective_traits)
+}
+
+/// Checks that every trait schema is a compilable JSON Schema without trait
+/// values of its own.
+fn check_trait_schemas(resolved_trait_schemas: &[Value]) -> Result<(), Vec<String>> {
     for (i, ts) in resolved_trait_schemas.iter().enumerate() {
         // x-gts-traits-schema must not contain x-gts-traits
         if let Some(obj) = ts.as_object()
@@ -111,10 +150,120 @@ pub fn validate_effective_traits(
             )]);
         }
     }
+    Ok(())
+}
 
-    let effective_trait_schema = build_effective_trait_schema(resolved_trait_schemas);
-    let effective_traits = apply_defaults(&effective_trait_schema, merged_traits);
-    validate_traits_against_schema(&effective_trait_schema, &effective_traits, check_unresolved)
+// ---------------------------------------------------------------------------
+// Placeholders
+// ---------------------------------------------------------------------------
+
+/// Values for `${name}` placeholders in trait values.
+#[derive(Debug, Clone, Default)]
+pub struct GtsTraitVars {
+    vars: HashMap<String, String>,
+    env: bool,
+}
+
+impl GtsTraitVars {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_var(mut self, name: &str, value: &str) -> Self {
+        self.vars.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Falls back to process environment variables for names without an
+    /// explicit value.
+    #[must_use]
+    pub fn with_env(mut self) -> Self {
+        self.env = true;
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.vars
+            .get(name)
+            .cloned()
+            .or_else(|| self.env.then(|| std::env::var(name).ok()).flatten())
+    }
+}
+
+/// Replaces `${name}` placeholders in every string of `value`.
+///
+/// # Errors
+/// Returns one message per placeholder that has no value or is not closed.
+pub(crate) fn substitute_placeholders(
+    value: &Value,
+    vars: &GtsTraitVars,
+) -> Result<Value, Vec<String>> {
+    let mut errors = Vec::new();
+    let out = substitute_recursive(value, vars, "", &mut errors);
+    if errors.is_empty() {
+        Ok(out)
+    } else {
+        Err(errors)
+    }
+}
+
+fn substitute_recursive(
+    value: &Value,
+    vars: &GtsTraitVars,
+    path: &str,
+    errors: &mut Vec<String>,
+) -> Value {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        Value::String(s) => Value::String(substitute_str(s, vars, path, errors)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| substitute_recursive(v, vars, &child(&i.to_string()), errors))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), substitute_recursive(v, vars, &child(k), errors)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn substitute_str(s: &str, vars: &GtsTraitVars, path: &str, errors: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            errors.push(format!("trait '{path}': unterminated placeholder in '{s}'"));
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = &after[..end];
+        if let Some(value) = vars.get(name) {
+            out.push_str(&value);
+        } else {
+            errors.push(format!(
+                "trait '{path}': placeholder '${{{name}}}' is not resolved"
+            ));
+            out.push_str(&rest[start..start + end + 3]);
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
 }
 
 // ---------------------------------------------------------------------------
@@ -1188,4 +1337,97 @@ mod tests {
             "should report invalid JSON Schema early: {err:?}"
         );
     }
+
+    #[test]
+    fn test_substitute_placeholders() {
+        let vars = GtsTraitVars::new()
+            .with_var("env", "prod")
+            .with_var("region", "eu");
+        let traits = json!({
+            "topicRef": "${env}.orders",
+            "hosts": ["${region}-1.${env}", "static"],
+            "retries": 3
+        });
+        assert_eq!(
+            substitute_placeholders(&traits, &vars).unwrap(),
+            json!({"topicRef": "prod.orders", "hosts": ["eu-1.prod", "static"], "retries": 3})
+        );
+
+        let err = substitute_placeholders(&json!({"a": {"b": "${nope}.x"}, "c": "${env"}), &vars)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            vec![
+                "trait 'a.b': placeholder '${nope}' is not resolved".to_owned(),
+                "trait 'c': unterminated placeholder in '${env'".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trait_vars_env_fallback() {
+        // PATH is set in any environment the tests run in
+        let path = std::env::var("PATH").unwrap();
+        let vars = GtsTraitVars::new().with_env();
+        assert_eq!(
+            substitute_placeholders(&json!("${PATH}"), &vars).unwrap(),
+            json!(path)
+        );
+        assert!(substitute_placeholders(&json!("${PATH}"), &GtsTraitVars::new()).is_err());
+    }
+
+    #[test]
+    fn test_resolve_traits_from_store() {
+        let base = "gts.x.test.traitvars.topic.v1~";
+        let leaf = "gts.x.test.traitvars.topic.v1~x.test.traitvars.orders.v1~";
+        let mut store = crate::store::GtsStore::new(None);
+        store
+            .register_schema(
+                base,
+                &json!({
+                    "$id": format!("gts://{base}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "x-gts-traits-schema": {
+                        "type": "object",
+                        "properties": {
+                            "topicRef": {"type": "string", "pattern": "^(dev|prod)\\."},
+                            "partitions": {"type": "integer", "default": 1}
+                        }
+                    }
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                leaf,
+                &json!({
+                    "$id": format!("gts://{leaf}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [{"$ref": format!("gts://{base}")}],
+                    "x-gts-traits": {"topicRef": "${env}.orders"}
+                }),
+            )
+            .unwrap();
+
+        let traits = store
+            .resolve_traits(leaf, &GtsTraitVars::new().with_var("env", "prod"))
+            .unwrap();
+        assert_eq!(traits, json!({"topicRef": "prod.orders", "partitions": 1}));
+
+        let err = store
+            .resolve_traits(leaf, &GtsTraitVars::new())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("placeholder '${env}' is not resolved"),
+            "{err}"
+        );
+        // Substituted values are validated
+        let err = store
+            .resolve_traits(leaf, &GtsTraitVars::new().with_var("env", "qa"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("trait validation"), "{err}");
+    }
 }
@@ -0,0 +1,264 @@
+//! Signed schema bundles (`signing` feature).
+//!
+//! Services that share a trusted set of schemas exchange them as a
+//! [`GtsSignedBundle`]: a JSON document holding the schemas, the ed25519
+//! public key of the signer and a signature over the schemas.  The receiving
+//! store only registers the schemas if the signature verifies against one of
+//! the keys it trusts.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Signer as _, Verifier as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::gts::{GTS_URI_PREFIX, GtsID, GtsWildcard};
+use crate::store::{GtsStore, StoreError};
+
+/// Value of [`GtsSignedBundle::format`] written by this version.
+pub const SIGNED_BUNDLE_FORMAT: &str = "gts-signed-bundle/v1";
+
+/// Schemas with an ed25519 signature by their publisher.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GtsSignedBundle {
+    pub format: String,
+    /// Schema documents, sorted by ID.
+    pub schemas: Vec<Value>,
+    /// Base64 ed25519 public key of the signer.
+    pub public_key: String,
+    /// Base64 ed25519 signature over the format and the schemas.
+    pub signature: String,
+}
+
+impl GtsSignedBundle {
+    /// Signs `schemas` with `key`.
+    #[must_use]
+    pub fn sign(schemas: Vec<Value>, key: &SigningKey) -> Self {
+        let signature = key.sign(&signed_bytes(SIGNED_BUNDLE_FORMAT, &schemas));
+        GtsSignedBundle {
+            format: SIGNED_BUNDLE_FORMAT.to_owned(),
+            schemas,
+            public_key: encode_key(&key.verifying_key()),
+            signature: BASE64.encode(signature.to_bytes()),
+        }
+    }
+
+    /// Checks that the bundle was signed by one of the `trusted` keys and
+    /// not changed since.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSignature` if the format is unknown, the
+    /// signer is not trusted or the signature does not match.
+    pub fn verify(&self, trusted: &[VerifyingKey]) -> Result<(), StoreError> {
+        if self.format != SIGNED_BUNDLE_FORMAT {
+            return Err(StoreError::InvalidSignature(format!(
+                "unsupported bundle format '{}'",
+                self.format
+            )));
+        }
+        let signer = decode_key(&self.public_key)?;
+        if !trusted.contains(&signer) {
+            return Err(StoreError::InvalidSignature(format!(
+                "bundle signed by untrusted key {}",
+                self.public_key
+            )));
+        }
+        let signature = BASE64
+            .decode(&self.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| StoreError::InvalidSignature("malformed signature".to_owned()))?;
+        signer
+            .verify(&signed_bytes(&self.format, &self.schemas), &signature)
+            .map_err(|_| StoreError::InvalidSignature("signature does not match".to_owned()))
+    }
+}
+
+/// The bytes a bundle signature covers.
+fn signed_bytes(format: &str, schemas: &[Value]) -> Vec<u8> {
+    let mut bytes = format.as_bytes().to_vec();
+    bytes.push(b'\n');
+    bytes.extend(Value::Array(schemas.to_vec()).to_string().into_bytes());
+    bytes
+}
+
+/// Base64 form of a public key, as stored in bundles.
+#[must_use]
+pub fn encode_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.as_bytes())
+}
+
+/// Parses a base64 public key.
+///
+/// # Errors
+/// Returns `StoreError::InvalidSignature` if `key` is not a base64 ed25519
+/// public key.
+pub fn decode_key(key: &str) -> Result<VerifyingKey, StoreError> {
+    BASE64
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| StoreError::InvalidSignature(format!("invalid public key '{key}'")))
+}
+
+/// The schema ID of a bundled schema document.
+fn bundled_schema_id(schema: &Value) -> Result<String, StoreError> {
+    schema
+        .get("$id")
+        .and_then(Value::as_str)
+        .map(|id| id.strip_prefix(GTS_URI_PREFIX).unwrap_or(id).to_owned())
+        .filter(|id| id.ends_with('~'))
+        .ok_or(StoreError::InvalidSchemaId)
+}
+
+impl GtsStore {
+    /// Signs the schemas matching `pattern`, a GTS wildcard such as
+    /// `gts.acme.*` or one exact schema ID, into a bundle.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid GTS
+    /// wildcard.
+    pub fn export_signed_bundle(
+        &self,
+        pattern: &str,
+        key: &SigningKey,
+    ) -> Result<GtsSignedBundle, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let selected = |id: &str| {
+            if pattern.contains('*') {
+                GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(&wildcard))
+            } else {
+                id == wildcard.id
+            }
+        };
+        let mut schemas: Vec<(&String, &Value)> = self
+            .entities()
+            .iter()
+            .filter(|(id, entity)| entity.is_schema && selected(id))
+            .map(|(id, entity)| (id, &entity.content))
+            .collect();
+        schemas.sort_by_key(|(id, _)| *id);
+        let schemas = schemas.into_iter().map(|(_, s)| s.clone()).collect();
+        Ok(GtsSignedBundle::sign(schemas, key))
+    }
+
+    /// Verifies `bundle` against the `trusted` keys and registers its
+    /// schemas in one transaction, returning their IDs.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSignature` if verification fails, before
+    /// anything is registered, or the registration error, after which the
+    /// store is unchanged.
+    pub fn import_signed_bundle(
+        &mut self,
+        bundle: &GtsSignedBundle,
+        trusted: &[VerifyingKey],
+    ) -> Result<Vec<String>, StoreError> {
+        bundle.verify(trusted)?;
+        self.transaction(|tx| {
+            let mut ids = Vec::with_capacity(bundle.schemas.len());
+            for schema in &bundle.schemas {
+                let id = bundled_schema_id(schema)?;
+                tx.register_schema(&id, schema)?;
+                ids.push(id);
+            }
+            Ok(ids)
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.sign.event.v1~";
+    const CHILD: &str = "gts.x.test.sign.event.v1~x.test.sign.paid.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                CHILD,
+                &json!({
+                    "$id": format!("gts://{CHILD}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "allOf": [{"$ref": format!("gts://{BASE}")}]
+                }),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_signed_bundle_round_trip() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let bundle = store()
+            .export_signed_bundle("gts.x.test.sign.*", &key)
+            .unwrap();
+        assert_eq!(bundle.schemas.len(), 2);
+        assert_eq!(
+            store()
+                .export_signed_bundle(BASE, &key)
+                .unwrap()
+                .schemas
+                .len(),
+            1
+        );
+        assert_eq!(decode_key(&bundle.public_key).unwrap(), key.verifying_key());
+
+        // Survives a trip through JSON text
+        let bundle: GtsSignedBundle =
+            serde_json::from_str(&serde_json::to_string_pretty(&bundle).unwrap()).unwrap();
+        let mut target = GtsStore::new(None);
+        let ids = target
+            .import_signed_bundle(&bundle, &[key.verifying_key()])
+            .unwrap();
+        assert_eq!(ids, vec![BASE.to_owned(), CHILD.to_owned()]);
+        assert!(target.entities().contains_key(CHILD));
+    }
+
+    #[test]
+    fn test_signed_bundle_rejects_tampering_and_unknown_keys() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[9; 32]);
+        let bundle = store().export_signed_bundle(BASE, &key).unwrap();
+        let mut target = GtsStore::new(None);
+
+        let err = target
+            .import_signed_bundle(&bundle, &[other.verifying_key()])
+            .unwrap_err();
+        assert!(err.to_string().contains("untrusted"), "{err}");
+
+        let mut tampered = bundle.clone();
+        tampered.schemas[0]["type"] = json!("string");
+        let err = target
+            .import_signed_bundle(&tampered, &[key.verifying_key()])
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        // Re-signing with an untrusted key does not help either
+        let resigned = GtsSignedBundle::sign(tampered.schemas, &other);
+        assert!(
+            target
+                .import_signed_bundle(&resigned, &[key.verifying_key()])
+                .is_err()
+        );
+        assert!(target.entities().is_empty());
+    }
+}
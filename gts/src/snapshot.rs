@@ -0,0 +1,270 @@
+//! Snapshot tests for generated schemas.
+//!
+//! [`gts_schema_snapshot!`](crate::gts_schema_snapshot) compares the schema a
+//! type generates with a file committed next to the tests, so a struct edit
+//! that changes the schema fails the test with a line diff instead of
+//! slipping through.  Run the tests with `GTS_UPDATE_SNAPSHOTS=1` to accept
+//! the new schemas.  Missing snapshots are written on first run, except when
+//! `CI` is set.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::gts::GTS_URI_PREFIX;
+
+/// Environment variable that makes snapshot tests accept the current schemas.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "GTS_UPDATE_SNAPSHOTS";
+
+/// Lines of unchanged context shown around each change in a snapshot diff.
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum GtsSnapshotError {
+    #[error("Schema snapshot {path} is missing; run with {UPDATE_SNAPSHOTS_ENV}=1 to create it")]
+    Missing { path: String },
+    #[error(
+        "Schema snapshot {path} does not match the generated schema; \
+         run with {UPDATE_SNAPSHOTS_ENV}=1 to accept it\n{diff}"
+    )]
+    Mismatch { path: String, diff: String },
+    #[error("Failed to access schema snapshot {path}: {cause}")]
+    Io { path: String, cause: String },
+}
+
+/// What [`compare_schema_snapshot`] did with a matching or new snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtsSnapshotOutcome {
+    Matched,
+    Written,
+}
+
+/// The snapshot file for `schema` in `dir`: `<schema $id>.schema.json`.
+#[must_use]
+pub fn snapshot_path(dir: &Path, schema: &Value) -> PathBuf {
+    let id = schema
+        .get("$id")
+        .and_then(Value::as_str)
+        .map_or("schema", |id| id.strip_prefix(GTS_URI_PREFIX).unwrap_or(id));
+    dir.join(format!("{id}.schema.json"))
+}
+
+/// Compares `schema` with the snapshot at `path`, writing the snapshot
+/// instead when `update` is set or when it is missing and `create` is set.
+///
+/// # Errors
+/// Returns `GtsSnapshotError::Mismatch` with a line diff if the snapshot
+/// differs, `GtsSnapshotError::Missing` if it does not exist and may not be
+/// created, and `GtsSnapshotError::Io` if it cannot be read or written.
+pub fn compare_schema_snapshot(
+    schema: &Value,
+    path: &Path,
+    update: bool,
+    create: bool,
+) -> Result<GtsSnapshotOutcome, GtsSnapshotError> {
+    let display = path.display().to_string();
+    let io_error = |e: std::io::Error| GtsSnapshotError::Io {
+        path: display.clone(),
+        cause: e.to_string(),
+    };
+    let rendered = serde_json::to_string_pretty(schema).map_err(|e| GtsSnapshotError::Io {
+        path: display.clone(),
+        cause: e.to_string(),
+    })? + "\n";
+
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(io_error(e)),
+    };
+    match existing {
+        Some(text) if !update => {
+            if text.replace("\r\n", "\n") == rendered {
+                Ok(GtsSnapshotOutcome::Matched)
+            } else {
+                Err(GtsSnapshotError::Mismatch {
+                    path: display,
+                    diff: line_diff(&text, &rendered),
+                })
+            }
+        }
+        None if !update && !create => Err(GtsSnapshotError::Missing { path: display }),
+        _ => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(io_error)?;
+            }
+            fs::write(path, rendered).map_err(io_error)?;
+            Ok(GtsSnapshotOutcome::Written)
+        }
+    }
+}
+
+/// Checks `schema` against its snapshot in `dir`, honouring
+/// `GTS_UPDATE_SNAPSHOTS` and `CI`. Used by
+/// [`gts_schema_snapshot!`](crate::gts_schema_snapshot).
+///
+/// # Panics
+/// Panics with the diff if the snapshot does not match, or if it is missing
+/// under `CI` or cannot be accessed.
+pub fn assert_schema_snapshot(schema: &Value, dir: &Path) {
+    let update = std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+    let create = std::env::var_os("CI").is_none();
+    let path = snapshot_path(dir, schema);
+    if let Err(e) = compare_schema_snapshot(schema, &path, update, create) {
+        panic!("{e}");
+    }
+}
+
+/// A unified-style diff of two texts, line by line: removed lines start with
+/// `-`, added lines with `+`, and unchanged context with a space.
+#[must_use]
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, _))| *tag != ' ')
+        .map(|(n, _)| n)
+        .collect();
+    let mut out = String::new();
+    let mut last_shown: Option<usize> = None;
+    for (n, (tag, line)) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|c| n + DIFF_CONTEXT >= *c && n <= c + DIFF_CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| n > last + 1) {
+            out.push_str("...\n");
+        }
+        let _ = writeln!(out, "{tag}{line}");
+        last_shown = Some(n);
+    }
+    out
+}
+
+/// Compares the schema of a GTS type (as produced by
+/// [`gts_schema_for!`](crate::gts_schema_for)) with a committed snapshot.
+///
+/// The snapshot lives in `tests/snapshots/<schema $id>.schema.json` under the
+/// calling crate, or in the given directory relative to it.  Set
+/// `GTS_UPDATE_SNAPSHOTS=1` to accept changed schemas.
+///
+/// # Example
+///
+/// ```ignore
+/// #[test]
+/// fn order_schema_is_stable() {
+///     gts::gts_schema_snapshot!(OrderV1);
+///     gts::gts_schema_snapshot!(BaseEventV1<OrderPlacedV1>, "tests/schema_snapshots");
+/// }
+/// ```
+#[macro_export]
+macro_rules! gts_schema_snapshot {
+    ($ty:ty) => {
+        $crate::gts_schema_snapshot!($ty, "tests/snapshots")
+    };
+    ($ty:ty, $dir:expr) => {{
+        let schema = $crate::gts_schema_for!($ty);
+        $crate::snapshot::assert_schema_snapshot(
+            &schema,
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($dir),
+        );
+    }};
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn schema(max: u32) -> Value {
+        json!({
+            "$id": "gts://gts.x.test.snap.order.v1~",
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "total": {"type": "integer", "maximum": max}
+            }
+        })
+    }
+
+    #[test]
+    fn test_snapshot_written_matched_and_diffed() {
+        let dir = TempDir::new().unwrap();
+        let path = snapshot_path(dir.path(), &schema(10));
+        assert!(path.ends_with("gts.x.test.snap.order.v1~.schema.json"));
+
+        assert!(matches!(
+            compare_schema_snapshot(&schema(10), &path, false, false),
+            Err(GtsSnapshotError::Missing { .. })
+        ));
+        assert_eq!(
+            compare_schema_snapshot(&schema(10), &path, false, true).unwrap(),
+            GtsSnapshotOutcome::Written
+        );
+        assert_eq!(
+            compare_schema_snapshot(&schema(10), &path, false, true).unwrap(),
+            GtsSnapshotOutcome::Matched
+        );
+
+        let err = compare_schema_snapshot(&schema(20), &path, false, true).unwrap_err();
+        let GtsSnapshotError::Mismatch { diff, .. } = &err else {
+            panic!("{err}");
+        };
+        assert!(diff.contains("-      \"maximum\": 10"), "{diff}");
+        assert!(diff.contains("+      \"maximum\": 20"), "{diff}");
+        assert!(!diff.contains("\"$id\""), "{diff}");
+
+        assert_eq!(
+            compare_schema_snapshot(&schema(20), &path, true, false).unwrap(),
+            GtsSnapshotOutcome::Written
+        );
+        assert_eq!(
+            compare_schema_snapshot(&schema(20), &path, false, false).unwrap(),
+            GtsSnapshotOutcome::Matched
+        );
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc\n", "a\nx\nc\n"), " a\n-b\n+x\n c\n");
+        assert_eq!(line_diff("same\n", "same\n"), "");
+    }
+}
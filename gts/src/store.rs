@@ -1,20 +1,47 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 use thiserror::Error;
+use web_time::{Instant, SystemTime};
 
-use crate::entities::GtsEntity;
+use crate::changes::{GtsChangeFeed, GtsChangeKind, registration_kind};
+use crate::diagnostic::{GtsCode, GtsDiagnostic};
+use crate::entities::{GtsConfig, GtsEntity, GtsFile};
 use crate::gts::{GTS_URI_PREFIX, GtsID, GtsWildcard};
+use crate::index::GtsStoreIndex;
+use crate::lifecycle::{self, GtsLifecycle, GtsLifecycleState, GtsRetiredSchemaPolicy, STATUS_KEY};
+use crate::metrics::GtsMetricCounters;
+use crate::numbers::{self, GtsNumberPolicy};
+use crate::ownership::PolicyProvider;
+use crate::ref_integrity::{GtsBrokenRef, GtsRefIntegrity};
+use crate::removal::GtsRemovalBlocker;
+use crate::sandbox::{self, GtsSandboxLimits};
 use crate::schema_cast::GtsEntityCastResult;
+use crate::schema_resolution::GtsSchemaResolution;
+use crate::validator_cache::{self, GtsCompiledSchema, GtsValidatorCache};
+use crate::visibility::GtsVisibility;
 
 /// Custom retriever for resolving gts:// URI scheme references in JSON Schema validation
-struct GtsRetriever {
+pub(crate) struct GtsRetriever {
     store: Arc<RwLock<HashMap<String, Value>>>,
 }
 
 impl GtsRetriever {
     fn new(store_map: &HashMap<String, GtsEntity>) -> Self {
+        Self::with_content(store_map, Value::clone)
+    }
+
+    /// Retriever serving schemas with `x-gts-ref` stripped, for validators that
+    /// follow `gts://` references instead of having them inlined.
+    pub(crate) fn stripped(store_map: &HashMap<String, GtsEntity>) -> Self {
+        Self::with_content(store_map, GtsStore::remove_x_gts_ref_fields)
+    }
+
+    fn with_content(
+        store_map: &HashMap<String, GtsEntity>,
+        content: impl Fn(&Value) -> Value,
+    ) -> Self {
         let mut schemas = HashMap::new();
 
         // Pre-populate with all schemas from the store
@@ -22,7 +49,7 @@ impl GtsRetriever {
             if entity.is_schema {
                 // Store with gts:// URI format
                 let uri = format!("{GTS_URI_PREFIX}{id}");
-                schemas.insert(uri, entity.content.clone());
+                schemas.insert(uri, content(&entity.content));
             }
         }
 
@@ -88,12 +115,325 @@ pub enum StoreError {
     ValidationError(String),
     #[error("Invalid $ref: {0}")]
     InvalidRef(String),
+    #[error("Background store task failed: {0}")]
+    AsyncTask(String),
+    #[error(
+        "Schema '{schema_id}' is retired{}",
+        .replacement.as_ref().map(|r| format!("; migrate to '{r}'")).unwrap_or_default()
+    )]
+    SchemaRetired {
+        schema_id: String,
+        replacement: Option<String>,
+    },
+    #[error("Store has no writer")]
+    NoWriter,
+    #[error(transparent)]
+    Write(#[from] GtsWriteError),
+    #[error("Unresolved references: {}", .0.join("; "))]
+    UnresolvedReferences(Vec<String>),
+    #[error("Policy violation: {}", .0.join("; "))]
+    PolicyViolation(Vec<String>),
+    #[error("Registration of '{id}' denied: {reason}")]
+    RegistrationDenied { id: String, reason: String },
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+    #[error("Invalid bundle signature: {0}")]
+    InvalidSignature(String),
+    #[error("Unknown ID alias '@{0}'")]
+    UnknownAlias(String),
+    #[error("ID '{input}' is ambiguous; it matches: {}", .candidates.join(", "))]
+    AmbiguousId {
+        input: String,
+        candidates: Vec<String>,
+    },
+    #[error(
+        "Schema '{schema_id}' is not compatible with base '{base_id}': {}",
+        .problems.iter().map(|p| p.message.as_str()).collect::<Vec<_>>().join("; ")
+    )]
+    IncompatibleSchema {
+        schema_id: String,
+        base_id: String,
+        /// How the schema loosens its base, one `GTS00xx` diagnostic each.
+        problems: Vec<GtsDiagnostic>,
+    },
+    #[error(
+        "Broken references: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    BrokenReference(Vec<GtsBrokenRef>),
+    #[error(
+        "Cannot remove: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    RemovalBlocked(Vec<GtsRemovalBlocker>),
+}
+
+impl StoreError {
+    /// The stable diagnostic code of this error.
+    #[must_use]
+    pub fn code(&self) -> GtsCode {
+        match self {
+            StoreError::ObjectNotFound(_) => GtsCode::ObjectNotFound,
+            StoreError::SchemaNotFound(_) => GtsCode::SchemaNotFound,
+            StoreError::EntityNotFound(_) => GtsCode::EntityNotFound,
+            StoreError::SchemaForInstanceNotFound(_) => GtsCode::SchemaForInstanceNotFound,
+            StoreError::CastFromSchemaNotAllowed(_) => GtsCode::CastFromSchemaNotAllowed,
+            StoreError::InvalidEntity => GtsCode::InvalidEntity,
+            StoreError::InvalidSchemaId => GtsCode::InvalidSchemaId,
+            StoreError::ValidationError(_) => GtsCode::ValidationFailed,
+            StoreError::InvalidRef(_) => GtsCode::InvalidRef,
+            StoreError::AsyncTask(_) => GtsCode::AsyncTask,
+            StoreError::SchemaRetired { .. } => GtsCode::SchemaRetired,
+            StoreError::NoWriter => GtsCode::NoWriter,
+            StoreError::Write(_) => GtsCode::WriteFailed,
+            StoreError::UnresolvedReferences(_) => GtsCode::UnresolvedReferences,
+            StoreError::PolicyViolation(_) => GtsCode::PolicyViolation,
+            StoreError::RegistrationDenied { .. } => GtsCode::RegistrationDenied,
+            StoreError::InvalidBundle(_) => GtsCode::InvalidBundle,
+            StoreError::InvalidSignature(_) => GtsCode::InvalidSignature,
+            StoreError::UnknownAlias(_) => GtsCode::UnknownAlias,
+            StoreError::AmbiguousId { .. } => GtsCode::AmbiguousId,
+            StoreError::IncompatibleSchema { .. } => GtsCode::IncompatibleSchema,
+            StoreError::BrokenReference(_) => GtsCode::BrokenReference,
+            StoreError::RemovalBlocked(_) => GtsCode::RemovalBlocked,
+        }
+    }
+
+    /// The error as coded diagnostics: one per problem for
+    /// [`StoreError::IncompatibleSchema`] and per reference for
+    /// [`StoreError::BrokenReference`], otherwise just the error itself.
+    #[must_use]
+    pub fn diagnostics(&self) -> Vec<GtsDiagnostic> {
+        match self {
+            StoreError::IncompatibleSchema { problems, .. } if !problems.is_empty() => {
+                problems.clone()
+            }
+            StoreError::BrokenReference(broken) if !broken.is_empty() => {
+                broken.iter().map(GtsBrokenRef::diagnostic).collect()
+            }
+            _ => vec![GtsDiagnostic::new(self.code(), self.to_string())],
+        }
+    }
+}
+
+/// How keywords placed next to a `$ref` (its "siblings") are combined with
+/// the referenced schema when the reference is inlined.
+///
+/// Draft-7 ignores siblings of `$ref` entirely; 2019-09 and later apply them
+/// alongside the reference. Inlining has to pick one of these behaviours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefSiblingMerge {
+    /// Draft-7 semantics: siblings are dropped, only the referenced schema is kept.
+    Ignore,
+    /// Shallow merge: each sibling keyword replaces the referenced keyword of
+    /// the same name. Nested constraints of the referenced keyword are lost.
+    Override,
+    /// Permissive deep merge (default): nested objects such as `properties`
+    /// are merged recursively, `required` lists are unioned and `allOf` lists
+    /// are concatenated, so no constraint from either side is silently lost.
+    /// Conflicting scalar keywords resolve to the sibling's value.
+    #[default]
+    Deep,
+}
+
+/// Emitted when a resolved `$ref` carries sibling keywords.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefSiblingWarning {
+    pub ref_uri: String,
+    pub sibling_keys: Vec<String>,
+    pub mode: RefSiblingMerge,
+}
+
+/// Mutable state threaded through a single `$ref` resolution pass.
+struct RefResolution<'a> {
+    visited: std::collections::HashSet<String>,
+    cycle_found: bool,
+    strict_cycles: bool,
+    warnings: Vec<RefSiblingWarning>,
+    /// Documents being resolved, innermost last; local `#/...` refs point
+    /// into the last one.
+    roots: Vec<&'a Value>,
+    /// Local refs being inlined, as `<document $id>#/...`, to stop at
+    /// recursive definitions.
+    local_refs: Vec<String>,
+}
+
+impl<'a> RefResolution<'a> {
+    fn new(strict_cycles: bool, root: &'a Value) -> Self {
+        Self {
+            visited: std::collections::HashSet::new(),
+            cycle_found: false,
+            strict_cycles,
+            warnings: Vec::new(),
+            roots: vec![root],
+            local_refs: Vec::new(),
+        }
+    }
+}
+
+/// Merges one sibling keyword into the resolved schema for [`RefSiblingMerge::Deep`].
+fn deep_merge_keyword(target: &mut serde_json::Map<String, Value>, key: &str, value: Value) {
+    match (target.get_mut(key), value) {
+        (Some(Value::Object(existing)), Value::Object(incoming)) => {
+            for (k, v) in incoming {
+                deep_merge_keyword(existing, &k, v);
+            }
+        }
+        (Some(Value::Array(existing)), Value::Array(incoming))
+            if key == "required" || key == "allOf" =>
+        {
+            for v in incoming {
+                if key == "allOf" || !existing.contains(&v) {
+                    existing.push(v);
+                }
+            }
+        }
+        (_, value) => {
+            target.insert(key.to_owned(), value);
+        }
+    }
 }
 
 pub trait GtsReader: Send {
     fn iter(&mut self) -> Box<dyn Iterator<Item = GtsEntity> + '_>;
     fn read_by_id(&self, entity_id: &str) -> Option<GtsEntity>;
     fn reset(&mut self);
+
+    /// Checks that the storage behind the reader can be reached, e.g. that
+    /// its directories exist or its registry answers.
+    ///
+    /// # Errors
+    /// Returns why the storage cannot be reached.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GtsWriteError {
+    #[error("Entity has no ID to name its file after")]
+    MissingId,
+    #[error("{0}: only JSON files can be written")]
+    UnsupportedFormat(String),
+    #[error("{path}: '{id}' not found in file")]
+    NotInFile { path: String, id: String },
+    #[error("{path}: {cause}")]
+    Io { path: String, cause: String },
+}
+
+/// Persists entities; the write-side counterpart of [`GtsReader`].
+pub trait GtsWriter: Send {
+    /// Writes an instance and returns where it was written.
+    ///
+    /// # Errors
+    /// Returns `GtsWriteError` if the entity cannot be written.
+    fn write_entity(&mut self, entity: &GtsEntity) -> Result<String, GtsWriteError>;
+
+    /// Writes a schema and returns where it was written.
+    ///
+    /// # Errors
+    /// Returns `GtsWriteError` if the schema cannot be written.
+    fn write_schema(&mut self, entity: &GtsEntity) -> Result<String, GtsWriteError>;
+
+    /// Removes an entity from storage.
+    ///
+    /// # Errors
+    /// Returns `GtsWriteError` if the entity cannot be removed.
+    fn delete(&mut self, entity: &GtsEntity) -> Result<(), GtsWriteError>;
+}
+
+/// A parsed query expression: the ID pattern plus bracket filters.
+pub(crate) struct QueryMatcher {
+    base_pattern: String,
+    is_wildcard: bool,
+    wildcard_pattern: Option<GtsWildcard>,
+    exact_gts_id: Option<GtsID>,
+    filters: Vec<Vec<FilterCondition>>,
+    /// Whether a filter asks for the lifecycle status; only then are
+    /// deprecated and retired schemas part of the results.
+    filters_status: bool,
+    today: String,
+}
+
+impl QueryMatcher {
+    pub(crate) fn parse(expr: &str) -> Result<Self, String> {
+        // Parse the query expression
+        let (base, _, filt) = expr.partition('[');
+        let base_pattern = base.trim().to_owned();
+        let is_wildcard = base_pattern.contains('*');
+
+        // Parse filters if present
+        let filter_str = if filt.is_empty() {
+            ""
+        } else {
+            filt.rsplit_once(']').map_or("", |x| x.0)
+        };
+        let filters = GtsStore::parse_query_filters(filter_str);
+        let filters_status = filters.iter().flatten().any(|c| c.key == STATUS_KEY);
+
+        // Validate and create pattern
+        let (wildcard_pattern, exact_gts_id, error) =
+            GtsStore::validate_query_pattern(&base_pattern, is_wildcard);
+        if !error.is_empty() {
+            return Err(error);
+        }
+
+        Ok(Self {
+            base_pattern,
+            is_wildcard,
+            wildcard_pattern,
+            exact_gts_id,
+            filters,
+            filters_status,
+            today: lifecycle::today(),
+        })
+    }
+
+    /// The effective lifecycle status of a schema.
+    fn status(&self, entity: &GtsEntity) -> Option<GtsLifecycleState> {
+        entity
+            .is_schema
+            .then(|| GtsLifecycle::of(&entity.content).effective_state(&self.today))
+    }
+
+    /// Whether query results leave `entity` out: deprecated and retired
+    /// schemas, unless the query filters on `x-gts-status`.
+    pub(crate) fn hides(&self, entity: &GtsEntity) -> bool {
+        !self.filters_status
+            && self
+                .status(entity)
+                .is_some_and(|s| s >= GtsLifecycleState::Deprecated)
+    }
+
+    /// Whether `entity` matches the ID pattern and filters (labels aside).
+    pub(crate) fn matches(&self, entity: &GtsEntity) -> bool {
+        entity.content.is_object()
+            && entity.gts_id.as_ref().is_some_and(|gts_id| {
+                GtsStore::matches_id_pattern(
+                    gts_id,
+                    &self.base_pattern,
+                    self.is_wildcard,
+                    self.wildcard_pattern.as_ref(),
+                    self.exact_gts_id.as_ref(),
+                )
+            })
+            && self.matches_content(entity)
+    }
+
+    /// Filters see a schema's effective lifecycle status under `x-gts-status`.
+    fn matches_content(&self, entity: &GtsEntity) -> bool {
+        match self.status(entity) {
+            Some(status) if self.filters_status => {
+                let mut content = entity.content.clone();
+                if let Some(obj) = content.as_object_mut() {
+                    obj.insert(STATUS_KEY.to_owned(), Value::String(status.to_string()));
+                }
+                GtsStore::matches_filters(&content, &self.filters)
+            }
+            _ => GtsStore::matches_filters(&entity.content, &self.filters),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,9 +445,59 @@ pub struct GtsStoreQueryResult {
     pub results: Vec<Value>,
 }
 
+/// Direction for [`GtsStore::query_sorted`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// One page of results from [`GtsStore::query_page`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsStoreQueryPage {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    pub count: usize,
+    pub page_size: usize,
+    pub results: Vec<Value>,
+    /// Opaque cursor for the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
 pub struct GtsStore {
     by_id: HashMap<String, GtsEntity>,
     reader: Option<Box<dyn GtsReader>>,
+    writer: Option<Box<dyn GtsWriter>>,
+    /// Last-use timestamps per schema ID; `None` while usage tracking is off.
+    usage: Option<HashMap<String, SystemTime>>,
+    /// Operational key-value labels per entity ID, kept apart from the content
+    /// so they survive re-registration and reloads of the entity. They live in
+    /// memory only and go with the entity when it is removed.
+    labels: HashMap<String, BTreeMap<String, String>>,
+    index: GtsStoreIndex,
+    ref_sibling_merge: RefSiblingMerge,
+    number_policy: GtsNumberPolicy,
+    sandbox: Option<GtsSandboxLimits>,
+    schema_resolution: GtsSchemaResolution,
+    ref_integrity: GtsRefIntegrity,
+    retired_schemas: GtsRetiredSchemaPolicy,
+    /// `$schema` assumed when compiling schemas that declare none.
+    dialect: Option<String>,
+    pub(crate) policy: Option<Box<dyn PolicyProvider>>,
+    /// Clearance queries are answered for; `None` while unrestricted.
+    pub(crate) audience: Option<GtsVisibility>,
+    validators: GtsValidatorCache,
+    /// Content fingerprints by entity ID, computed on first request.
+    fingerprints: HashMap<String, String>,
+    metrics: GtsMetricCounters,
+    changes: GtsChangeFeed,
+    /// When the entities were last read from the reader or a watched file.
+    pub(crate) loaded_at: Option<SystemTime>,
+    /// Worker threads for the parallel methods; 0 means rayon's global pool.
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel_threads: usize,
 }
 
 impl GtsStore {
@@ -115,10 +505,31 @@ impl GtsStore {
         let mut store = GtsStore {
             by_id: HashMap::new(),
             reader,
+            writer: None,
+            usage: None,
+            labels: HashMap::new(),
+            index: GtsStoreIndex::default(),
+            ref_sibling_merge: RefSiblingMerge::default(),
+            number_policy: GtsNumberPolicy::default(),
+            sandbox: None,
+            schema_resolution: GtsSchemaResolution::default(),
+            ref_integrity: GtsRefIntegrity::default(),
+            retired_schemas: GtsRetiredSchemaPolicy::default(),
+            dialect: None,
+            policy: None,
+            audience: None,
+            validators: GtsValidatorCache::default(),
+            fingerprints: HashMap::new(),
+            metrics: GtsMetricCounters::default(),
+            changes: GtsChangeFeed::default(),
+            loaded_at: None,
+            #[cfg(feature = "parallel")]
+            parallel_threads: 0,
         };
 
         if store.reader.is_some() {
             store.populate_from_reader();
+            store.apply_index_hints();
         }
 
         tracing::info!("Populated GtsStore with {} entities", store.by_id.len());
@@ -130,27 +541,80 @@ impl GtsStore {
             for entity in reader.iter() {
                 // Use effective_id() which handles both GTS IDs and anonymous instance IDs
                 if let Some(id) = entity.effective_id() {
-                    self.by_id.insert(id, entity);
+                    insert_indexed(&mut self.by_id, &mut self.index, id, entity);
                 }
             }
+            self.loaded_at = Some(SystemTime::now());
         }
     }
 
     /// Registers an entity in the store.
     ///
     /// # Errors
-    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID.
+    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID,
+    /// or `StoreError::RegistrationDenied` if the policy provider refuses
+    /// anonymous registration of it.
     pub fn register(&mut self, entity: GtsEntity) -> Result<(), StoreError> {
+        self.register_as(None, entity)
+    }
+
+    /// Registers an entity in the store on behalf of `principal`, which the
+    /// policy provider may check.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID,
+    /// or `StoreError::RegistrationDenied` if the policy provider refuses.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(principal = ?principal, id = ?entity.effective_id()),
+            err(level = "debug"),
+        )
+    )]
+    pub fn register_as(
+        &mut self,
+        principal: Option<&str>,
+        entity: GtsEntity,
+    ) -> Result<(), StoreError> {
         let id = entity.effective_id().ok_or(StoreError::InvalidEntity)?;
-        self.by_id.insert(id, entity);
+        self.authorize(principal, &id, &entity)?;
+        self.insert_entity(id, entity);
         Ok(())
     }
 
     /// Registers a schema in the store.
     ///
     /// # Errors
-    /// Returns `StoreError::InvalidSchemaId` if the `type_id` doesn't end with '~'.
+    /// Returns `StoreError::InvalidSchemaId` if the `type_id` doesn't end with '~',
+    /// or `StoreError::RegistrationDenied` if the policy provider refuses
+    /// anonymous registration of it.
     pub fn register_schema(&mut self, type_id: &str, schema: &Value) -> Result<(), StoreError> {
+        self.register_schema_as(None, type_id, schema)
+    }
+
+    /// Registers a schema in the store on behalf of `principal`, which the
+    /// policy provider may check.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSchemaId` if the `type_id` doesn't end with '~',
+    /// or `StoreError::RegistrationDenied` if the policy provider refuses.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(principal = ?principal, id = type_id),
+            err(level = "debug"),
+        )
+    )]
+    pub fn register_schema_as(
+        &mut self,
+        principal: Option<&str>,
+        type_id: &str,
+        schema: &Value,
+    ) -> Result<(), StoreError> {
         if !type_id.ends_with('~') {
             return Err(StoreError::InvalidSchemaId);
         }
@@ -167,7 +631,8 @@ impl GtsStore {
             None,
             None,
         );
-        self.by_id.insert(type_id.to_owned(), entity);
+        self.authorize(principal, type_id, &entity)?;
+        self.insert_entity(type_id.to_owned(), entity);
         Ok(())
     }
 
@@ -181,7 +646,7 @@ impl GtsStore {
         if let Some(ref reader) = self.reader
             && let Some(entity) = reader.read_by_id(entity_id)
         {
-            self.by_id.insert(entity_id.to_owned(), entity);
+            self.insert_entity(entity_id.to_owned(), entity);
             return self.by_id.get(entity_id);
         }
 
@@ -199,16 +664,322 @@ impl GtsStore {
         Err(StoreError::SchemaNotFound(type_id.to_owned()))
     }
 
+    /// Sets the writer used by [`GtsStore::persist`] and
+    /// [`GtsStore::delete_persisted`].
+    pub fn set_writer(&mut self, writer: Box<dyn GtsWriter>) {
+        self.writer = Some(writer);
+    }
+
+    /// Writes the stored entity `entity_id` through the writer and returns
+    /// where it was written. An entity registered in memory remembers the
+    /// file it was first written to.
+    ///
+    /// # Errors
+    /// Returns `StoreError::NoWriter` without a writer,
+    /// `StoreError::EntityNotFound` for an unknown ID, or `StoreError::Write`
+    /// if writing fails.
+    pub fn persist(&mut self, entity_id: &str) -> Result<String, StoreError> {
+        if self.writer.is_none() {
+            return Err(StoreError::NoWriter);
+        }
+        let entity = self
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| StoreError::EntityNotFound(entity_id.to_owned()))?;
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(StoreError::NoWriter);
+        };
+        let path = if entity.is_schema {
+            writer.write_schema(&entity)?
+        } else {
+            writer.write_entity(&entity)?
+        };
+
+        if entity.file.is_none()
+            && let Some(stored) = self.by_id.get_mut(entity_id)
+        {
+            let name = std::path::Path::new(&path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            stored.file = Some(GtsFile::new(path.clone(), name, entity.content));
+        }
+        Ok(path)
+    }
+
+    /// Removes the stored entity `entity_id` from storage through the writer.
+    /// The entity stays registered in the store.
+    ///
+    /// # Errors
+    /// Returns `StoreError::NoWriter` without a writer,
+    /// `StoreError::EntityNotFound` for an unknown ID, or `StoreError::Write`
+    /// if deleting fails.
+    pub fn delete_persisted(&mut self, entity_id: &str) -> Result<(), StoreError> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(StoreError::NoWriter);
+        };
+        let entity = self
+            .by_id
+            .get_mut(entity_id)
+            .ok_or_else(|| StoreError::EntityNotFound(entity_id.to_owned()))?;
+        writer.delete(entity)?;
+        let file = entity.file.take();
+        let removed = entity.list_sequence.take();
+
+        // Entities after the removed one in the same list file move up
+        if let (Some(file), Some(removed)) = (file, removed) {
+            for other in self.by_id.values_mut() {
+                if other.file.as_ref().is_some_and(|f| f.path == file.path)
+                    && let Some(idx) = other.list_sequence.as_mut()
+                    && *idx > removed
+                {
+                    *idx -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn items(&self) -> impl Iterator<Item = (&String, &GtsEntity)> {
         self.by_id.iter()
     }
 
+    pub(crate) fn entities(&self) -> &HashMap<String, GtsEntity> {
+        &self.by_id
+    }
+
+    /// Direct access to the entity map for crate-internal bulk operations.
+    /// Marks the query indexes stale; call [`GtsStore::rebuild_indexes`] afterwards.
+    pub(crate) fn entities_mut(&mut self) -> &mut HashMap<String, GtsEntity> {
+        self.index.mark_stale();
+        self.validators.clear();
+        self.fingerprints.clear();
+        &mut self.by_id
+    }
+
+    /// Inserts or replaces an entity, keeping the query indexes and compiled
+    /// validators up to date and telling the change listener.
+    pub(crate) fn insert_entity(&mut self, id: String, entity: GtsEntity) {
+        if id.ends_with('~') {
+            self.validators.invalidate(&id);
+        }
+        self.fingerprints.remove(&id);
+        let change = self.changes.is_listened().then(|| {
+            let kind = registration_kind(self.by_id.get(&id), &entity);
+            (kind, id.clone(), entity.is_schema)
+        });
+        let has_hints = entity.is_schema && !entity.index_hints().is_empty();
+        insert_indexed(&mut self.by_id, &mut self.index, id, entity);
+        if let Some((kind, id, is_schema)) = change {
+            let visibility = self.visibility_of(&id);
+            self.changes.publish(kind, &id, is_schema, visibility);
+        }
+        if has_hints {
+            self.apply_index_hints();
+        }
+    }
+
+    /// Removes an entity, keeping the query indexes and compiled validators
+    /// up to date and telling the change listener.
+    pub(crate) fn remove_entity(&mut self, id: &str) -> Option<GtsEntity> {
+        let visibility = self.changes.is_listened().then(|| self.visibility_of(id));
+        let entity = self.by_id.remove(id)?;
+        self.index.remove(id, &entity);
+        self.fingerprints.remove(id);
+        self.labels.remove(id);
+        if id.ends_with('~') {
+            self.validators.invalidate(id);
+        }
+        if let Some(visibility) = visibility {
+            self.changes
+                .publish(GtsChangeKind::Removed, id, entity.is_schema, visibility);
+        }
+        Some(entity)
+    }
+
+    pub(crate) fn fingerprints(&self) -> &HashMap<String, String> {
+        &self.fingerprints
+    }
+
+    pub(crate) fn fingerprints_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.fingerprints
+    }
+
+    pub(crate) fn change_feed_mut(&mut self) -> &mut GtsChangeFeed {
+        &mut self.changes
+    }
+
+    pub(crate) fn metric_counters(&self) -> &GtsMetricCounters {
+        &self.metrics
+    }
+
+    pub(crate) fn metric_counters_mut(&mut self) -> &mut GtsMetricCounters {
+        &mut self.metrics
+    }
+
+    pub(crate) fn validator_cache(&self) -> &GtsValidatorCache {
+        &self.validators
+    }
+
+    pub(crate) fn validator_cache_mut(&mut self) -> &mut GtsValidatorCache {
+        &mut self.validators
+    }
+
+    pub(crate) fn index(&self) -> &GtsStoreIndex {
+        &self.index
+    }
+
+    pub(crate) fn index_mut(&mut self) -> &mut GtsStoreIndex {
+        &mut self.index
+    }
+
+    pub(crate) fn entities_and_index_mut(
+        &mut self,
+    ) -> (&HashMap<String, GtsEntity>, &mut GtsStoreIndex) {
+        (&self.by_id, &mut self.index)
+    }
+
+    /// When the entities were last read from the reader, or reloaded from a
+    /// watched file; `None` for a store without a reader.
+    #[must_use]
+    pub fn loaded_at(&self) -> Option<SystemTime> {
+        self.loaded_at
+    }
+
+    /// Whether the reader's storage can be reached; `None` for a store
+    /// without a reader.
+    #[must_use]
+    pub fn check_reader(&self) -> Option<Result<(), String>> {
+        self.reader.as_ref().map(|reader| reader.check())
+    }
+
+    /// Enables or disables recording of when each schema was last used to
+    /// validate an instance. Disabling drops all recorded timestamps.
+    pub fn set_usage_tracking(&mut self, enabled: bool) {
+        match (enabled, self.usage.is_some()) {
+            (true, false) => self.usage = Some(HashMap::new()),
+            (false, true) => self.usage = None,
+            _ => {}
+        }
+    }
+
+    #[must_use]
+    pub fn usage_tracking(&self) -> bool {
+        self.usage.is_some()
+    }
+
+    /// Returns when the schema was last used for validation, if usage tracking is enabled.
+    #[must_use]
+    pub fn last_used(&self, schema_id: &str) -> Option<SystemTime> {
+        self.usage.as_ref()?.get(schema_id).copied()
+    }
+
+    /// Sets the label `key` to `value` on an entity, replacing any previous value.
+    ///
+    /// # Errors
+    /// Returns `StoreError::EntityNotFound` if the entity is not in the store.
+    pub fn label(&mut self, entity_id: &str, key: &str, value: &str) -> Result<(), StoreError> {
+        if self.get(entity_id).is_none() {
+            return Err(StoreError::EntityNotFound(entity_id.to_owned()));
+        }
+        self.labels
+            .entry(entity_id.to_owned())
+            .or_default()
+            .insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    /// Removes the label `key` from an entity, returning its previous value.
+    pub fn unlabel(&mut self, entity_id: &str, key: &str) -> Option<String> {
+        let labels = self.labels.get_mut(entity_id)?;
+        let previous = labels.remove(key);
+        if labels.is_empty() {
+            self.labels.remove(entity_id);
+        }
+        previous
+    }
+
+    /// Returns the labels of an entity (empty if it has none).
+    #[must_use]
+    pub fn labels(&self, entity_id: &str) -> BTreeMap<String, String> {
+        self.labels.get(entity_id).cloned().unwrap_or_default()
+    }
+
+    /// Labels of every entity, by entity ID.
+    pub(crate) fn all_labels(&self) -> &HashMap<String, BTreeMap<String, String>> {
+        &self.labels
+    }
+
+    pub(crate) fn all_labels_mut(&mut self) -> &mut HashMap<String, BTreeMap<String, String>> {
+        &mut self.labels
+    }
+
+    pub(crate) fn has_labels(&self, entity_id: &str, wanted: &BTreeMap<String, String>) -> bool {
+        if wanted.is_empty() {
+            return true;
+        }
+        self.labels.get(entity_id).is_some_and(|labels| {
+            wanted
+                .iter()
+                .all(|(k, v)| labels.get(k).is_some_and(|l| l == v))
+        })
+    }
+
+    /// Rejects validation against retired schemas (or only warns, per
+    /// `policy`) and warns about deprecated ones.
+    fn check_lifecycle(
+        schema_id: &str,
+        schema: &Value,
+        policy: GtsRetiredSchemaPolicy,
+    ) -> Result<(), StoreError> {
+        let lifecycle = GtsLifecycle::of(schema);
+        match lifecycle.effective_state(&lifecycle::today()) {
+            GtsLifecycleState::Draft | GtsLifecycleState::Active => Ok(()),
+            GtsLifecycleState::Deprecated => {
+                tracing::warn!(
+                    "Schema {schema_id} is deprecated{}{}",
+                    lifecycle
+                        .retire_on
+                        .as_ref()
+                        .map(|d| format!(" and will be retired on {d}"))
+                        .unwrap_or_default(),
+                    lifecycle
+                        .replacement
+                        .as_ref()
+                        .map(|r| format!("; migrate to {r}"))
+                        .unwrap_or_default()
+                );
+                Ok(())
+            }
+            GtsLifecycleState::Retired if policy == GtsRetiredSchemaPolicy::Warn => {
+                tracing::warn!(
+                    "Schema {schema_id} is retired{}",
+                    lifecycle
+                        .replacement
+                        .as_ref()
+                        .map(|r| format!("; migrate to {r}"))
+                        .unwrap_or_default()
+                );
+                Ok(())
+            }
+            GtsLifecycleState::Retired => Err(StoreError::SchemaRetired {
+                schema_id: schema_id.to_owned(),
+                replacement: lifecycle.replacement,
+            }),
+        }
+    }
+
     /// Resolve all `$ref` references in a JSON Schema by inlining the referenced schemas.
     ///
     /// This method recursively traverses the schema, finds all `$ref` references,
     /// and replaces them with the actual schema content from the store. The result
     /// is a fully inlined schema with no external references.
     ///
+    /// Local JSON Pointer refs (`#/$defs/Address`, `#/definitions/Foo/...`) are
+    /// looked up in the document they appear in, so a base schema's
+    /// definitions still resolve once it is inlined into a derived schema.
+    /// Recursive definitions keep their `$ref`.
+    ///
     /// # Arguments
     ///
     /// * `schema` - The JSON Schema value that may contain `$ref` references
@@ -232,37 +1003,155 @@ impl GtsStore {
     /// assert!(!inlined.to_string().contains("$ref"));
     /// ```
     #[must_use]
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(schema_id = ?schema.get("$id")))
+    )]
     pub fn resolve_schema_refs(&self, schema: &Value) -> Value {
-        let mut visited = std::collections::HashSet::new();
-        let mut cycle_found = false;
-        self.resolve_schema_refs_inner(schema, &mut visited, &mut cycle_found, false)
+        let mut ctx = RefResolution::new(false, schema);
+        self.resolve_schema_refs_inner(schema, &mut ctx)
+    }
+
+    /// Like [`resolve_schema_refs`] but also returns one warning per `$ref`
+    /// that had sibling keywords, describing how they were handled under the
+    /// store's [`RefSiblingMerge`] mode.
+    #[must_use]
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(schema_id = ?schema.get("$id")))
+    )]
+    pub fn resolve_schema_refs_with_warnings(
+        &self,
+        schema: &Value,
+    ) -> (Value, Vec<RefSiblingWarning>) {
+        let mut ctx = RefResolution::new(false, schema);
+        let resolved = self.resolve_schema_refs_inner(schema, &mut ctx);
+        (resolved, ctx.warnings)
+    }
+
+    /// Like [`resolve_schema_refs`] but returns an error if a circular `$ref`
+    /// is detected during resolution.
+    ///
+    /// Uses strict cycle detection: once a `$ref` target is visited it stays
+    /// in the seen-set for the entire resolution pass, so both true circular
+    /// references **and** duplicate `$ref`s (e.g. the same URI twice in
+    /// `allOf`) are flagged.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip_all, fields(schema_id = ?schema.get("$id")))
+    )]
+    pub(crate) fn resolve_schema_refs_checked(&self, schema: &Value) -> Result<Value, String> {
+        let mut ctx = RefResolution::new(true, schema);
+        let resolved = self.resolve_schema_refs_inner(schema, &mut ctx);
+        if ctx.cycle_found {
+            Err("circular $ref detected".to_owned())
+        } else {
+            Ok(resolved)
+        }
+    }
+
+    /// Sets how keywords next to a resolved `$ref` are combined with the
+    /// referenced schema. See [`RefSiblingMerge`].
+    pub fn set_ref_sibling_merge(&mut self, mode: RefSiblingMerge) {
+        if self.ref_sibling_merge != mode {
+            self.validators.clear();
+        }
+        self.ref_sibling_merge = mode;
+    }
+
+    #[must_use]
+    pub fn ref_sibling_merge(&self) -> RefSiblingMerge {
+        self.ref_sibling_merge
+    }
+
+    /// Sets the integer strictness and bignum-string rules applied on top of
+    /// JSON Schema validation. See [`GtsNumberPolicy`].
+    pub fn set_number_policy(&mut self, policy: GtsNumberPolicy) {
+        self.number_policy = policy;
+    }
+
+    #[must_use]
+    pub fn number_policy(&self) -> GtsNumberPolicy {
+        self.number_policy
+    }
+
+    /// Sets the resource limits for validating against untrusted schemas, or
+    /// lifts them with `None`. See [`GtsSandboxLimits`].
+    pub fn set_sandbox_limits(&mut self, limits: Option<GtsSandboxLimits>) {
+        if limits != self.sandbox {
+            self.validators.clear();
+        }
+        self.sandbox = limits;
+    }
+
+    #[must_use]
+    pub fn sandbox_limits(&self) -> Option<GtsSandboxLimits> {
+        self.sandbox
+    }
+
+    /// Sets the `$schema` URI assumed for schemas that declare none; `None`
+    /// leaves the choice to the validator.
+    pub fn set_dialect(&mut self, dialect: Option<String>) {
+        if dialect != self.dialect {
+            self.validators.clear();
+        }
+        self.dialect = dialect;
+    }
+
+    #[must_use]
+    pub fn dialect(&self) -> Option<&str> {
+        self.dialect.as_deref()
+    }
+
+    /// Applies every store setting of `cfg`, with `strict` tightening the
+    /// number policy and ref integrity.
+    pub fn apply_config(&mut self, cfg: &GtsConfig) {
+        self.set_number_policy(cfg.number_policy());
+        self.set_sandbox_limits(cfg.sandbox);
+        self.set_schema_resolution(cfg.schema_resolution);
+        self.set_ref_integrity(cfg.ref_integrity_policy());
+        self.set_retired_schema_policy(cfg.retired_schemas);
+        self.set_namespace_rules(cfg.namespaces.clone());
+        self.set_dialect(cfg.dialect.clone());
+    }
+
+    /// Sets how instances find their schema when the declared version is not
+    /// registered. See [`GtsSchemaResolution`].
+    pub fn set_schema_resolution(&mut self, resolution: GtsSchemaResolution) {
+        self.schema_resolution = resolution;
+    }
+
+    #[must_use]
+    pub fn schema_resolution(&self) -> GtsSchemaResolution {
+        self.schema_resolution
+    }
+
+    /// Sets whether validation also requires `x-gts-ref` targets to exist in
+    /// the store. See [`GtsRefIntegrity`].
+    pub fn set_ref_integrity(&mut self, integrity: GtsRefIntegrity) {
+        self.ref_integrity = integrity;
+    }
+
+    #[must_use]
+    pub fn ref_integrity(&self) -> GtsRefIntegrity {
+        self.ref_integrity
     }
 
-    /// Like [`resolve_schema_refs`] but returns an error if a circular `$ref`
-    /// is detected during resolution.
-    ///
-    /// Uses strict cycle detection: once a `$ref` target is visited it stays
-    /// in the seen-set for the entire resolution pass, so both true circular
-    /// references **and** duplicate `$ref`s (e.g. the same URI twice in
-    /// `allOf`) are flagged.
-    pub(crate) fn resolve_schema_refs_checked(&self, schema: &Value) -> Result<Value, String> {
-        let mut visited = std::collections::HashSet::new();
-        let mut cycle_found = false;
-        let resolved = self.resolve_schema_refs_inner(schema, &mut visited, &mut cycle_found, true);
-        if cycle_found {
-            Err("circular $ref detected".to_owned())
-        } else {
-            Ok(resolved)
-        }
+    /// Sets whether validating against a retired schema fails or only warns.
+    pub fn set_retired_schema_policy(&mut self, policy: GtsRetiredSchemaPolicy) {
+        self.retired_schemas = policy;
+    }
+
+    #[must_use]
+    pub fn retired_schema_policy(&self) -> GtsRetiredSchemaPolicy {
+        self.retired_schemas
     }
 
     #[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
-    fn resolve_schema_refs_inner(
-        &self,
-        schema: &Value,
-        visited: &mut std::collections::HashSet<String>,
-        cycle_found: &mut bool,
-        strict_cycles: bool,
+    fn resolve_schema_refs_inner<'a>(
+        &'a self,
+        schema: &'a Value,
+        ctx: &mut RefResolution<'a>,
     ) -> Value {
         // Recursively resolve $ref references in the schema
         match schema {
@@ -277,21 +1166,8 @@ impl GtsStore {
                         "#/$defs/GtsSchemaId" => {
                             return crate::GtsSchemaId::json_schema_value();
                         }
-                        s if s.starts_with("#/") => {
-                            // Other internal references - keep as-is
-                            let mut new_map = serde_json::Map::new();
-                            for (k, v) in map {
-                                new_map.insert(
-                                    k.clone(),
-                                    self.resolve_schema_refs_inner(
-                                        v,
-                                        visited,
-                                        cycle_found,
-                                        strict_cycles,
-                                    ),
-                                );
-                            }
-                            return Value::Object(new_map);
+                        s if s.starts_with('#') => {
+                            return self.resolve_local_ref(map, s, ctx);
                         }
                         _ => {} // Fall through to external ref handling
                     }
@@ -300,21 +1176,13 @@ impl GtsStore {
                     let canonical_ref = ref_uri.strip_prefix(GTS_URI_PREFIX).unwrap_or(ref_uri);
 
                     // Cycle detection: skip if we've already visited this ref
-                    if visited.contains(canonical_ref) {
+                    if ctx.visited.contains(canonical_ref) {
                         // Circular $ref detected — drop it to avoid infinite loop
-                        *cycle_found = true;
+                        ctx.cycle_found = true;
                         let mut new_map = serde_json::Map::new();
                         for (k, v) in map {
                             if k != "$ref" {
-                                new_map.insert(
-                                    k.clone(),
-                                    self.resolve_schema_refs_inner(
-                                        v,
-                                        visited,
-                                        cycle_found,
-                                        strict_cycles,
-                                    ),
-                                );
+                                new_map.insert(k.clone(), self.resolve_schema_refs_inner(v, ctx));
                             }
                         }
                         if new_map.is_empty() {
@@ -328,16 +1196,14 @@ impl GtsStore {
                         && entity.is_schema
                     {
                         // Mark as visited before recursing
-                        visited.insert(canonical_ref.to_owned());
-                        // Recursively resolve refs in the referenced schema
-                        let mut resolved = self.resolve_schema_refs_inner(
-                            &entity.content,
-                            visited,
-                            cycle_found,
-                            strict_cycles,
-                        );
-                        if !strict_cycles {
-                            visited.remove(canonical_ref);
+                        ctx.visited.insert(canonical_ref.to_owned());
+                        // Recursively resolve refs in the referenced schema, whose
+                        // local refs point into its own document
+                        ctx.roots.push(&entity.content);
+                        let mut resolved = self.resolve_schema_refs_inner(&entity.content, ctx);
+                        ctx.roots.pop();
+                        if !ctx.strict_cycles {
+                            ctx.visited.remove(canonical_ref);
                         }
 
                         // Remove $id and $schema from resolved content to avoid URL resolution issues
@@ -347,50 +1213,11 @@ impl GtsStore {
                             resolved_map.remove("$schema");
                         }
 
-                        // If the original object has only $ref, return the resolved schema
-                        if map.len() == 1 {
-                            return resolved;
-                        }
-
-                        // Otherwise, merge the resolved schema with other properties
-                        if let Value::Object(resolved_map) = resolved {
-                            let mut merged = resolved_map;
-                            for (k, v) in map {
-                                if k != "$ref" {
-                                    merged.insert(
-                                        k.clone(),
-                                        self.resolve_schema_refs_inner(
-                                            v,
-                                            visited,
-                                            cycle_found,
-                                            strict_cycles,
-                                        ),
-                                    );
-                                }
-                            }
-                            return Value::Object(merged);
-                        }
+                        return self.merge_ref_siblings(map, ref_uri, resolved, ctx);
                     }
                     // If we can't resolve, remove the $ref to avoid "relative URL" errors
                     // and keep other properties
-                    let mut new_map = serde_json::Map::new();
-                    for (k, v) in map {
-                        if k != "$ref" {
-                            new_map.insert(
-                                k.clone(),
-                                self.resolve_schema_refs_inner(
-                                    v,
-                                    visited,
-                                    cycle_found,
-                                    strict_cycles,
-                                ),
-                            );
-                        }
-                    }
-                    if !new_map.is_empty() {
-                        return Value::Object(new_map);
-                    }
-                    return schema.clone();
+                    return self.without_ref(map, ctx);
                 }
 
                 // Special handling for allOf arrays - merge $ref resolved schemas
@@ -400,12 +1227,7 @@ impl GtsStore {
                     let mut merged_required: Vec<String> = Vec::new();
 
                     for item in all_of_array {
-                        let resolved_item = self.resolve_schema_refs_inner(
-                            item,
-                            visited,
-                            cycle_found,
-                            strict_cycles,
-                        );
+                        let resolved_item = self.resolve_schema_refs_inner(item, ctx);
 
                         match resolved_item {
                             Value::Object(ref item_map) => {
@@ -467,23 +1289,119 @@ impl GtsStore {
                 // Recursively process all properties
                 let mut new_map = serde_json::Map::new();
                 for (k, v) in map {
-                    new_map.insert(
-                        k.clone(),
-                        self.resolve_schema_refs_inner(v, visited, cycle_found, strict_cycles),
-                    );
+                    new_map.insert(k.clone(), self.resolve_schema_refs_inner(v, ctx));
                 }
                 Value::Object(new_map)
             }
             Value::Array(arr) => Value::Array(
                 arr.iter()
-                    .map(|v| self.resolve_schema_refs_inner(v, visited, cycle_found, strict_cycles))
+                    .map(|v| self.resolve_schema_refs_inner(v, ctx))
                     .collect(),
             ),
             _ => schema.clone(),
         }
     }
 
-    fn remove_x_gts_ref_fields(schema: &Value) -> Value {
+    /// Inlines a local JSON Pointer ref such as `#/$defs/Address` or
+    /// `#/definitions/Foo/properties/bar`, looked up in the document the ref
+    /// appears in.  Recursive definitions and pointers that do not resolve
+    /// keep their `$ref`.
+    fn resolve_local_ref<'a>(
+        &'a self,
+        map: &'a serde_json::Map<String, Value>,
+        ref_uri: &str,
+        ctx: &mut RefResolution<'a>,
+    ) -> Value {
+        let keep = |ctx: &mut RefResolution<'a>| {
+            let mut new_map = serde_json::Map::new();
+            for (k, v) in map {
+                new_map.insert(k.clone(), self.resolve_schema_refs_inner(v, ctx));
+            }
+            Value::Object(new_map)
+        };
+        let Some(&root) = ctx.roots.last() else {
+            return keep(ctx);
+        };
+        let Some(target) = root.pointer(&ref_uri[1..]) else {
+            return keep(ctx);
+        };
+        let key = format!(
+            "{}{ref_uri}",
+            root.get("$id").and_then(Value::as_str).unwrap_or_default()
+        );
+        if ctx.local_refs.contains(&key) {
+            return keep(ctx);
+        }
+        ctx.local_refs.push(key);
+        let resolved = self.resolve_schema_refs_inner(target, ctx);
+        ctx.local_refs.pop();
+        self.merge_ref_siblings(map, ref_uri, resolved, ctx)
+    }
+
+    /// Combines a resolved `$ref` target with the keywords next to the `$ref`
+    /// according to the configured [`RefSiblingMerge`] mode.
+    fn merge_ref_siblings<'a>(
+        &'a self,
+        map: &'a serde_json::Map<String, Value>,
+        ref_uri: &str,
+        resolved: Value,
+        ctx: &mut RefResolution<'a>,
+    ) -> Value {
+        // If the original object has only $ref, return the resolved schema
+        if map.len() == 1 {
+            return resolved;
+        }
+
+        let sibling_keys: Vec<String> = map.keys().filter(|k| *k != "$ref").cloned().collect();
+        tracing::warn!(
+            "$ref '{ref_uri}' has sibling keywords {sibling_keys:?}; applying {:?} merge",
+            self.ref_sibling_merge
+        );
+        ctx.warnings.push(RefSiblingWarning {
+            ref_uri: ref_uri.to_owned(),
+            sibling_keys,
+            mode: self.ref_sibling_merge,
+        });
+
+        if self.ref_sibling_merge == RefSiblingMerge::Ignore {
+            return resolved;
+        }
+        let Value::Object(mut merged) = resolved else {
+            return self.without_ref(map, ctx);
+        };
+        for (k, v) in map {
+            if k != "$ref" {
+                let v = self.resolve_schema_refs_inner(v, ctx);
+                if self.ref_sibling_merge == RefSiblingMerge::Deep {
+                    deep_merge_keyword(&mut merged, k, v);
+                } else {
+                    merged.insert(k.clone(), v);
+                }
+            }
+        }
+        Value::Object(merged)
+    }
+
+    /// The keywords next to a `$ref` that could not be used, resolved; the
+    /// object unchanged if the `$ref` stands alone.
+    fn without_ref<'a>(
+        &'a self,
+        map: &'a serde_json::Map<String, Value>,
+        ctx: &mut RefResolution<'a>,
+    ) -> Value {
+        let mut new_map = serde_json::Map::new();
+        for (k, v) in map {
+            if k != "$ref" {
+                new_map.insert(k.clone(), self.resolve_schema_refs_inner(v, ctx));
+            }
+        }
+        if new_map.is_empty() {
+            return Value::Object(map.clone());
+        }
+        Value::Object(new_map)
+    }
+
+    pub(crate) fn remove_x_gts_ref_fields(schema: &Value) -> Value {
         // Recursively remove x-gts-ref fields from a schema.
         // This is needed because the jsonschema crate doesn't understand x-gts-ref
         // and will fail on JSON Pointer references like "/$id".
@@ -672,6 +1590,13 @@ impl GtsStore {
         // 1. Validate $ref fields - must be local (#...) or gts:// URIs
         Self::validate_schema_refs(&schema_content, "")?;
 
+        if let Some(limits) = self.sandbox {
+            let problems = limits.check_schema(&schema_content);
+            if !problems.is_empty() {
+                return Err(StoreError::PolicyViolation(problems));
+            }
+        }
+
         // 2. Validate x-gts-ref fields (before JSON Schema validation)
         // This ensures we catch invalid GTS IDs in x-gts-ref before the JSON Schema
         // compiler potentially fails on them
@@ -725,7 +1650,8 @@ impl GtsStore {
     /// The heavy lifting is delegated to [`crate::schema_compat`].
     ///
     /// # Errors
-    /// Returns `StoreError::ValidationError` if any derived schema loosens base constraints.
+    /// Returns `StoreError::IncompatibleSchema` if any derived schema loosens base
+    /// constraints, or `StoreError::ValidationError` if a chain schema is missing.
     pub(crate) fn validate_schema_chain(&mut self, gts_id: &str) -> Result<(), StoreError> {
         let gid = GtsID::new(gts_id)
             .map_err(|e| StoreError::ValidationError(format!("Invalid GTS ID: {e}")))?;
@@ -795,12 +1721,11 @@ impl GtsStore {
             );
 
             if !errors.is_empty() {
-                return Err(StoreError::ValidationError(format!(
-                    "Schema '{}' is not compatible with base '{}': {}",
-                    derived_id,
+                return Err(StoreError::IncompatibleSchema {
+                    schema_id: derived_id,
                     base_id,
-                    errors.join("; ")
-                )));
+                    problems: errors,
+                });
             }
         }
 
@@ -809,14 +1734,56 @@ impl GtsStore {
 
     /// OP#13: Validates schema traits across the inheritance chain.
     ///
-    /// Walks the chain from base to leaf, collects `x-gts-traits-schema` and
-    /// `x-gts-traits` from each level's **raw** content (before allOf
-    /// flattening which would drop `x-gts-*` keys), resolves `$ref` inside
-    /// collected trait schemas, then validates.
-    ///
     /// # Errors
     /// Returns `StoreError::ValidationError` if trait validation fails.
     pub(crate) fn validate_schema_traits(&mut self, gts_id: &str) -> Result<(), StoreError> {
+        let (trait_schemas, merged) = self.collect_chain_traits(gts_id)?;
+        crate::schema_traits::validate_effective_traits(&trait_schemas, &merged, true).map_err(
+            |errors| {
+                StoreError::ValidationError(format!(
+                    "Schema '{}' trait validation failed: {}",
+                    gts_id,
+                    errors.join("; ")
+                ))
+            },
+        )
+    }
+
+    /// Returns the effective traits of schema `gts_id`: values merged along
+    /// the inheritance chain, trait schema defaults, and `${name}`
+    /// placeholders substituted from `vars`. The result is validated against
+    /// the effective trait schema.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if a placeholder is unresolved or
+    /// the traits are invalid.
+    pub fn resolve_traits(
+        &mut self,
+        gts_id: &str,
+        vars: &crate::schema_traits::GtsTraitVars,
+    ) -> Result<Value, StoreError> {
+        let (trait_schemas, merged) = self.collect_chain_traits(gts_id)?;
+        crate::schema_traits::resolve_effective_traits(&trait_schemas, &merged, vars).map_err(
+            |errors| {
+                StoreError::ValidationError(format!(
+                    "Schema '{}' trait resolution failed: {}",
+                    gts_id,
+                    errors.join("; ")
+                ))
+            },
+        )
+    }
+
+    /// Walks the chain from base to leaf, collects `x-gts-traits-schema` and
+    /// `x-gts-traits` from each level's **raw** content (before allOf
+    /// flattening which would drop `x-gts-*` keys) and resolves `$ref` inside
+    /// collected trait schemas.
+    ///
+    /// Returns the resolved trait schemas and the merged trait values.
+    pub(crate) fn collect_chain_traits(
+        &mut self,
+        gts_id: &str,
+    ) -> Result<(Vec<Value>, Value), StoreError> {
         let gid = GtsID::new(gts_id)
             .map_err(|e| StoreError::ValidationError(format!("Invalid GTS ID: {e}")))?;
 
@@ -927,16 +1894,10 @@ impl GtsStore {
             resolved_trait_schemas.push(resolved);
         }
 
-        // Delegate to the schema_traits module
-        let merged = serde_json::Value::Object(merged_traits);
-        crate::schema_traits::validate_effective_traits(&resolved_trait_schemas, &merged, true)
-            .map_err(|errors| {
-                StoreError::ValidationError(format!(
-                    "Schema '{}' trait validation failed: {}",
-                    gts_id,
-                    errors.join("; ")
-                ))
-            })
+        Ok((
+            resolved_trait_schemas,
+            serde_json::Value::Object(merged_traits),
+        ))
     }
 
     /// OP#13 entity-level check: ensures the effective trait schema is "closed".
@@ -1012,78 +1973,175 @@ impl GtsStore {
         Ok(())
     }
 
-    /// Validates an instance against its schema.
-    ///
-    /// # Errors
-    /// Returns `StoreError` if validation fails.
-    pub fn validate_instance(&mut self, gts_id: &str) -> Result<(), StoreError> {
-        let gid = GtsID::new(gts_id).map_err(|_| StoreError::ObjectNotFound(gts_id.to_owned()))?;
-
-        let obj = self
-            .get(&gid.id)
-            .ok_or_else(|| StoreError::ObjectNotFound(gts_id.to_owned()))?
-            .clone();
-
-        let schema_id = obj
-            .schema_id
-            .as_ref()
-            .ok_or_else(|| StoreError::SchemaForInstanceNotFound(gid.id.clone()))?
-            .clone();
-
-        let schema = self.get_schema_content(&schema_id)?;
-
-        tracing::info!(
-            "Validating instance {} against schema {}",
-            gts_id,
-            schema_id
-        );
+    /// Returns the compiled validator for `schema_id`, compiling and caching it
+    /// on first use. `instance` is only used to describe compilation errors.
+    fn compiled_schema(
+        &mut self,
+        schema_id: &str,
+        schema: &Value,
+        instance: &Value,
+    ) -> Result<Arc<GtsCompiledSchema>, StoreError> {
+        if let Some(compiled) = self.validators.get(schema_id) {
+            return Ok(compiled);
+        }
+        let started = Instant::now();
 
         // Resolve internal #/ references (like #/$defs/GtsInstanceId) by inlining them
         // This handles the compile-time inlining of GtsInstanceId and GtsSchemaId
-        let schema_with_internal_refs_resolved = self.resolve_schema_refs(&schema);
+        let schema_with_internal_refs_resolved = self.resolve_schema_refs(schema);
 
         // Remove x-gts-ref fields before jsonschema validation.
         // x-gts-ref is a GTS extension unknown to the jsonschema crate; leaving it
         // inside oneOf/anyOf/allOf branches would cause those branches to be treated
         // as empty match-everything schemas, breaking combinator semantics.
-        let schema_with_internal_refs_resolved =
+        let mut schema_with_internal_refs_resolved =
             Self::remove_x_gts_ref_fields(&schema_with_internal_refs_resolved);
+        if let (Some(dialect), Value::Object(map)) =
+            (&self.dialect, &mut schema_with_internal_refs_resolved)
+        {
+            map.entry("$schema")
+                .or_insert_with(|| Value::String(dialect.clone()));
+        }
 
         tracing::debug!(
             "Schema for validation: {}",
             serde_json::to_string_pretty(&schema_with_internal_refs_resolved).unwrap_or_default()
         );
 
+        if let Some(limits) = self.sandbox {
+            let problems = limits.check_schema(&schema_with_internal_refs_resolved);
+            if !problems.is_empty() {
+                return Err(StoreError::PolicyViolation(problems));
+            }
+        }
+
         // Create custom retriever for gts:// URI resolution
         let retriever = GtsRetriever::new(&self.by_id);
 
         // Build validator with custom retriever to handle gts:// references
         // Internal #/ references have already been resolved by resolve_schema_refs
         // The retriever will resolve any $ref to gts:// URIs automatically
-        let validator = jsonschema::options()
-            .with_retriever(retriever)
+        let mut options = jsonschema::options().with_retriever(retriever);
+        if let Some(limits) = self.sandbox {
+            options = options.with_pattern_options(sandbox::pattern_options(&limits));
+        }
+        let validator = options
             .build(&schema_with_internal_refs_resolved)
             .map_err(|e| {
                 tracing::error!("Schema compilation error: {}", e);
                 StoreError::ValidationError(format!(
                     "Invalid schema: {e}\nContent: {}\nSchema: {}",
-                    serde_json::to_string_pretty(&obj.content).unwrap_or_default(),
+                    serde_json::to_string_pretty(instance).unwrap_or_default(),
                     serde_json::to_string_pretty(&schema_with_internal_refs_resolved)
                         .unwrap_or_default()
                 ))
             })?;
 
-        validator.validate(&obj.content).map_err(|_| {
+        let step_budget = self.sandbox.map(|limits| {
+            (
+                sandbox::keyword_count(&schema_with_internal_refs_resolved),
+                limits.max_validation_steps,
+            )
+        });
+        let compiled = Arc::new(GtsCompiledSchema {
+            validator,
+            resolved: schema_with_internal_refs_resolved,
+            schema: schema.clone(),
+            step_budget,
+        });
+        let deps = validator_cache::schema_dependencies(&self.by_id, schema_id, schema);
+        self.validators
+            .insert(schema_id, Arc::clone(&compiled), deps);
+        self.metrics.record_compile(schema_id, started.elapsed());
+        Ok(compiled)
+    }
+
+    /// Validates an instance against its schema, which may be a newer minor
+    /// version than the declared one under the store's
+    /// [`GtsSchemaResolution`].
+    ///
+    /// # Errors
+    /// Returns `StoreError` if validation fails.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
+    pub fn validate_instance(&mut self, gts_id: &str) -> Result<(), StoreError> {
+        self.validate_instance_resolved(gts_id).map(|_| ())
+    }
+
+    /// Validates arbitrary JSON content against a registered schema, e.g. a
+    /// request body that is not stored as an entity.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the schema is missing, retired or validation
+    /// fails, or [`StoreError::BrokenReference`] if `x-gts-ref` targets fail
+    /// the store's [`GtsRefIntegrity`] check.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip(self, content), err(level = "debug"))
+    )]
+    pub fn validate_value(&mut self, schema_id: &str, content: &Value) -> Result<(), StoreError> {
+        let compiled = self.prepare_schema(schema_id, content)?;
+        let started = Instant::now();
+        let result = Self::check_compiled(&compiled, content, self.number_policy)
+            .and_then(|()| self.check_ref_integrity(&compiled.schema, content));
+        self.metrics
+            .record_validation(schema_id, result.is_ok(), started.elapsed());
+        result
+    }
+
+    /// Looks up, lifecycle-checks and compiles a schema for validation.
+    /// `instance` is only used to describe compilation errors.
+    pub(crate) fn prepare_schema(
+        &mut self,
+        schema_id: &str,
+        instance: &Value,
+    ) -> Result<Arc<GtsCompiledSchema>, StoreError> {
+        let schema = self.get_schema_content(schema_id)?;
+        Self::check_lifecycle(schema_id, &schema, self.retired_schemas)?;
+
+        if let Some(usage) = self.usage.as_mut() {
+            usage.insert(schema_id.to_owned(), SystemTime::now());
+        }
+
+        self.compiled_schema(schema_id, &schema, instance)
+    }
+
+    /// Checks content against a compiled schema. Needs no store access, so it
+    /// can run on many threads at once.
+    pub(crate) fn check_compiled(
+        compiled: &GtsCompiledSchema,
+        content: &Value,
+        number_policy: GtsNumberPolicy,
+    ) -> Result<(), StoreError> {
+        let validator = &compiled.validator;
+
+        Self::check_step_budget(compiled, content)?;
+
+        validator.validate(content).map_err(|_| {
             let errors: Vec<String> = validator
-                .iter_errors(&obj.content)
+                .iter_errors(content)
                 .map(|err| err.to_string())
                 .collect();
             StoreError::ValidationError(format!("Validation failed: {}", errors.join(", ")))
         })?;
 
+        let number_errors = numbers::check_numbers(content, &compiled.resolved, number_policy);
+        if !number_errors.is_empty() {
+            let errors: Vec<String> = number_errors
+                .iter()
+                .map(|e| format!("{}: {}", e.path, e.reason))
+                .collect();
+            return Err(StoreError::ValidationError(format!(
+                "Number validation failed: {}",
+                errors.join(", ")
+            )));
+        }
+
         // Validate x-gts-ref constraints
         let validator = crate::x_gts_ref::XGtsRefValidator::new();
-        let x_gts_ref_errors = validator.validate_instance(&obj.content, &schema, "");
+        let x_gts_ref_errors = validator.validate_instance(content, &compiled.schema, "");
 
         if !x_gts_ref_errors.is_empty() {
             let error_messages: Vec<String> = x_gts_ref_errors
@@ -1104,10 +2162,31 @@ impl GtsStore {
         Ok(())
     }
 
+    /// Rejects content that would take longer to validate than the sandbox
+    /// step budget allows.
+    pub(crate) fn check_step_budget(
+        compiled: &GtsCompiledSchema,
+        content: &Value,
+    ) -> Result<(), StoreError> {
+        if let Some((keywords, max_steps)) = compiled.step_budget {
+            let steps = sandbox::value_count(content).saturating_mul(keywords);
+            if steps > max_steps {
+                return Err(StoreError::PolicyViolation(vec![format!(
+                    "validation would take about {steps} steps, over the validation budget of {max_steps}"
+                )]));
+            }
+        }
+        Ok(())
+    }
+
     /// Casts an entity from one schema to another.
     ///
     /// # Errors
     /// Returns `StoreError` if the cast fails.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
     pub fn cast(
         &mut self,
         from_id: &str,
@@ -1148,12 +2227,16 @@ impl GtsStore {
             (schema, schema_id.clone())
         };
 
-        // Create a resolver to handle $ref in schemas
-        // TODO: Implement custom resolver
-        let resolver = None;
+        // Inline `gts://` and local `#/...` refs so that the cast sees every
+        // property, including ones defined in base schemas or under `$defs`
+        let resolve = |mut schema: GtsEntity| {
+            schema.content = self.resolve_schema_refs(&schema.content);
+            schema
+        };
+        let (to_schema, from_schema) = (resolve(to_schema), resolve(from_schema));
 
         from_entity
-            .cast(&to_schema, &from_schema, resolver)
+            .cast(&to_schema, &from_schema, None)
             .map_err(|e| StoreError::SchemaNotFound(e.to_string()))
     }
 
@@ -1295,8 +2378,27 @@ impl GtsStore {
         Value::Object(ret)
     }
 
+    /// Entities matching `expr`, an ID pattern with optional bracket filters.
+    /// Deprecated and retired schemas are left out unless the query filters
+    /// on their status, e.g. `gts.x.*[x-gts-status=deprecated]`.
     #[must_use]
     pub fn query(&self, expr: &str, limit: usize) -> GtsStoreQueryResult {
+        self.query_with_labels(expr, &BTreeMap::new(), limit)
+    }
+
+    /// Like [`GtsStore::query`], but only returns entities carrying all of the
+    /// given labels.
+    #[must_use]
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(level = "debug", skip(self, labels), fields(labels = labels.len()))
+    )]
+    pub fn query_with_labels(
+        &self,
+        expr: &str,
+        labels: &BTreeMap<String, String>,
+        limit: usize,
+    ) -> GtsStoreQueryResult {
         let mut result = GtsStoreQueryResult {
             error: String::new(),
             count: 0,
@@ -1304,79 +2406,183 @@ impl GtsStore {
             results: Vec::new(),
         };
 
-        // Parse the query expression
-        let (base, _, filt) = expr.partition('[');
-        let base_pattern = base.trim();
-        let is_wildcard = base_pattern.contains('*');
-
-        // Parse filters if present
-        let filter_str = if filt.is_empty() {
-            ""
-        } else {
-            filt.rsplit_once(']').map_or("", |x| x.0)
-        };
-        let filters = Self::parse_query_filters(filter_str);
-
-        // Validate and create pattern
-        let (wildcard_pattern, exact_gts_id, error) =
-            Self::validate_query_pattern(base_pattern, is_wildcard);
-        if !error.is_empty() {
-            result.error = error;
-            return result;
+        match self.matching_entities(expr, labels) {
+            Ok(matches) => {
+                result.results = matches
+                    .take(limit)
+                    .map(|(_, entity)| entity.content.clone())
+                    .collect();
+            }
+            Err(error) => result.error = error,
         }
 
-        // Filter entities
-        for entity in self.by_id.values() {
-            if result.results.len() >= limit {
-                break;
-            }
+        result.count = result.results.len();
+        result
+    }
 
-            if !entity.content.is_object() {
-                continue;
-            }
+    /// Like [`GtsStore::query`], but ordered by the value at `field` (a dotted
+    /// path such as `created_at` or `meta.rank`).
+    ///
+    /// Values of different JSON types are grouped in the order null, boolean,
+    /// number, string, array, object; entities without the field always come
+    /// last, whatever the direction. Ties are broken by entity ID so the order
+    /// is deterministic.
+    #[must_use]
+    pub fn query_sorted(
+        &self,
+        expr: &str,
+        field: &str,
+        order: SortOrder,
+        limit: usize,
+    ) -> GtsStoreQueryResult {
+        let mut result = GtsStoreQueryResult {
+            error: String::new(),
+            count: 0,
+            limit,
+            results: Vec::new(),
+        };
 
-            let Some(ref gts_id) = entity.gts_id else {
-                continue;
+        let no_labels = BTreeMap::new();
+        let mut matches: Vec<(&String, &GtsEntity, Option<&Value>)> =
+            match self.matching_entities(expr, &no_labels) {
+                Ok(matches) => matches
+                    .map(|(id, entity)| (id, entity, value_at_path(&entity.content, field)))
+                    .collect(),
+                Err(error) => {
+                    result.error = error;
+                    return result;
+                }
+            };
+        matches.sort_by(|a, b| {
+            let by_value = match (a.2, b.2) {
+                (Some(x), Some(y)) => {
+                    let ord = compare_json(x, y);
+                    if order == SortOrder::Desc {
+                        ord.reverse()
+                    } else {
+                        ord
+                    }
+                }
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
             };
+            by_value.then_with(|| a.0.cmp(b.0))
+        });
+
+        result.results = matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, entity, _)| entity.content.clone())
+            .collect();
+        result.count = result.results.len();
+        result
+    }
 
-            // Check if ID matches the pattern
-            if !Self::matches_id_pattern(
-                gts_id,
-                base_pattern,
-                is_wildcard,
-                wildcard_pattern.as_ref(),
-                exact_gts_id.as_ref(),
-            ) {
-                continue;
-            }
+    /// Returns one page of query results in ascending ID order.
+    ///
+    /// Pass `None` as `cursor` for the first page and the returned
+    /// `next_cursor` for each following page; `next_cursor` is `None` once the
+    /// last page has been returned. Because the cursor encodes a position in
+    /// the ID order rather than an offset, entities added or removed between
+    /// calls never cause results to be skipped or repeated.
+    #[must_use]
+    pub fn query_page(
+        &self,
+        expr: &str,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> GtsStoreQueryPage {
+        let mut page = GtsStoreQueryPage {
+            error: String::new(),
+            count: 0,
+            page_size,
+            results: Vec::new(),
+            next_cursor: None,
+        };
 
-            // Check filters
-            if !Self::matches_filters(&entity.content, &filters) {
-                continue;
+        let after = match cursor.map(decode_cursor).transpose() {
+            Ok(after) => after,
+            Err(error) => {
+                page.error = error;
+                return page;
+            }
+        };
+        let no_labels = BTreeMap::new();
+        let mut matches: Vec<(&String, &GtsEntity)> = match self.matching_entities(expr, &no_labels)
+        {
+            Ok(matches) => matches
+                .filter(|(id, _)| after.as_ref().is_none_or(|after| *id > after))
+                .collect(),
+            Err(error) => {
+                page.error = error;
+                return page;
             }
+        };
+        matches.sort_unstable_by(|a, b| a.0.cmp(b.0));
 
-            result.results.push(entity.content.clone());
+        if matches.len() > page_size
+            && let Some((last_id, _)) = page_size.checked_sub(1).and_then(|i| matches.get(i))
+        {
+            page.next_cursor = Some(encode_cursor(last_id));
         }
-
-        result.count = result.results.len();
-        result
+        page.results = matches
+            .into_iter()
+            .take(page_size)
+            .map(|(_, entity)| entity.content.clone())
+            .collect();
+        page.count = page.results.len();
+        page
     }
 
-    fn parse_query_filters(filter_str: &str) -> HashMap<String, String> {
-        let mut filters = HashMap::new();
-        if filter_str.is_empty() {
-            return filters;
-        }
+    /// Entities matching a query expression (ID pattern plus bracket filters)
+    /// and carrying all of `labels`, in map order.
+    fn matching_entities<'a>(
+        &'a self,
+        expr: &str,
+        labels: &'a BTreeMap<String, String>,
+    ) -> Result<impl Iterator<Item = (&'a String, &'a GtsEntity)> + 'a, String> {
+        let matcher = QueryMatcher::parse(expr)?;
+        let entities = self.query_candidates(&matcher);
+        Ok(entities.filter(move |(id, entity)| {
+            matcher.matches(entity)
+                && !matcher.hides(entity)
+                && self.has_labels(id, labels)
+                && self.is_visible(id)
+        }))
+    }
 
-        let parts: Vec<&str> = filter_str.split(',').map(str::trim).collect();
-        for part in parts {
-            if let Some((k, v)) = part.split_once('=') {
-                let v = v.trim().trim_matches('"').trim_matches('\'');
-                filters.insert(k.trim().to_owned(), v.to_owned());
-            }
+    /// Entities that may match `matcher`: the index candidates when the
+    /// pattern or filters are indexed, every entity otherwise.
+    pub(crate) fn query_candidates<'a>(
+        &'a self,
+        matcher: &QueryMatcher,
+    ) -> Box<dyn Iterator<Item = (&'a String, &'a GtsEntity)> + 'a> {
+        match self
+            .index
+            .candidates(&matcher.base_pattern, &matcher.filters)
+        {
+            Some(ids) => Box::new(
+                ids.into_iter()
+                    .filter_map(move |id| self.by_id.get_key_value(&id)),
+            ),
+            None => Box::new(self.by_id.iter()),
         }
+    }
 
-        filters
+    /// Parses bracket filters into a disjunction of conjunctions: `|` separates
+    /// alternatives, `&` (or `,`) joins conditions that must all hold.
+    fn parse_query_filters(filter_str: &str) -> Vec<Vec<FilterCondition>> {
+        filter_str
+            .split('|')
+            .map(|group| {
+                group
+                    .split(['&', ','])
+                    .filter_map(FilterCondition::parse)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|group| !group.is_empty())
+            .collect()
     }
 
     fn validate_query_pattern(
@@ -1435,29 +2641,195 @@ impl GtsStore {
         }
     }
 
-    fn matches_filters(entity_content: &Value, filters: &HashMap<String, String>) -> bool {
+    fn matches_filters(entity_content: &Value, filters: &[Vec<FilterCondition>]) -> bool {
         if filters.is_empty() {
             return true;
         }
 
-        if let Some(obj) = entity_content.as_object() {
-            for (key, value) in filters {
-                let entity_value = obj.get(key).map_or_else(String::new, ToString::to_string);
+        let Some(obj) = entity_content.as_object() else {
+            return false;
+        };
+        filters
+            .iter()
+            .any(|group| group.iter().all(|condition| condition.matches(obj)))
+    }
+}
 
-                // Support wildcard in filter values
-                if value == "*" {
-                    if entity_value.is_empty() || entity_value == "null" {
-                        return false;
-                    }
-                } else if entity_value != format!("\"{value}\"") && entity_value != *value {
-                    return false;
-                }
-            }
-            true
-        } else {
-            false
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// A single `key<op>value` condition from a query's bracket filter.
+#[derive(Debug, Clone)]
+pub(crate) struct FilterCondition {
+    pub(crate) key: String,
+    pub(crate) op: FilterOp,
+    pub(crate) value: String,
+}
+
+impl FilterCondition {
+    fn parse(part: &str) -> Option<Self> {
+        let part = part.trim();
+        let pos = part.find(['!', '=', '<', '>'])?;
+        let (key, rest) = part.split_at(pos);
+        let (op, len) = match rest.as_bytes() {
+            [b'=', b'=', ..] => (FilterOp::Eq, 2),
+            [b'!', b'=', ..] => (FilterOp::Ne, 2),
+            [b'>', b'=', ..] => (FilterOp::Ge, 2),
+            [b'<', b'=', ..] => (FilterOp::Le, 2),
+            [b'=', ..] => (FilterOp::Eq, 1),
+            [b'>', ..] => (FilterOp::Gt, 1),
+            [b'<', ..] => (FilterOp::Lt, 1),
+            _ => return None,
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
+        }
+        let value = rest[len..].trim().trim_matches('"').trim_matches('\'');
+        Some(FilterCondition {
+            key: key.to_owned(),
+            op,
+            value: value.to_owned(),
+        })
+    }
+
+    fn matches(&self, obj: &serde_json::Map<String, Value>) -> bool {
+        let field = field_value(obj, &self.key);
+        match self.op {
+            FilterOp::Eq => Self::equals(field, &self.value),
+            FilterOp::Ne => !Self::equals(field, &self.value),
+            FilterOp::Gt | FilterOp::Ge | FilterOp::Lt | FilterOp::Le => field
+                .and_then(|f| Self::compare(f, &self.value))
+                .is_some_and(|ord| match self.op {
+                    FilterOp::Gt => ord.is_gt(),
+                    FilterOp::Ge => ord.is_ge(),
+                    FilterOp::Lt => ord.is_lt(),
+                    _ => ord.is_le(),
+                }),
+        }
+    }
+
+    fn equals(field: Option<&Value>, value: &str) -> bool {
+        let entity_value = field.map_or_else(String::new, ToString::to_string);
+
+        // Support wildcard in filter values
+        if value == "*" {
+            return !entity_value.is_empty() && entity_value != "null";
+        }
+        entity_value == format!("\"{value}\"") || entity_value == value
+    }
+
+    /// Numbers compare numerically, and so do strings when both sides are
+    /// numeric (`"10" > "9"`); other strings compare lexicographically
+    /// (which also orders ISO-8601 timestamps).  Anything else is not
+    /// comparable.
+    fn compare(field: &Value, value: &str) -> Option<std::cmp::Ordering> {
+        match field {
+            Value::Number(n) => n.as_f64()?.partial_cmp(&value.parse::<f64>().ok()?),
+            Value::String(s) => match (s.parse::<f64>(), value.parse::<f64>()) {
+                (Ok(field), Ok(value)) => field.partial_cmp(&value),
+                _ => Some(s.as_str().cmp(value)),
+            },
+            _ => None,
+        }
+    }
+}
+
+fn insert_indexed(
+    by_id: &mut HashMap<String, GtsEntity>,
+    index: &mut GtsStoreIndex,
+    id: String,
+    entity: GtsEntity,
+) {
+    if let Some(old) = by_id.remove(&id) {
+        index.remove(&id, &old);
+    }
+    index.insert(&id, &entity);
+    by_id.insert(id, entity);
+}
+
+/// The value of a filter or index key: a top-level field of that exact name,
+/// or else the dotted path it spells (`payload.user_id`).
+pub(crate) fn field_value<'a>(
+    obj: &'a serde_json::Map<String, Value>,
+    key: &str,
+) -> Option<&'a Value> {
+    obj.get(key).or_else(|| {
+        let (head, rest) = key.split_once('.')?;
+        value_at_path(obj.get(head)?, rest)
+    })
+}
+
+/// Follows a dotted path (`a.b.0.c`) through objects and arrays.
+fn value_at_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, key| match current {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+/// Total order over JSON values: by type first, then by value within a type.
+fn compare_json(a: &Value, b: &Value) -> std::cmp::Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
         }
     }
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => x
+            .as_f64()
+            .unwrap_or(f64::NAN)
+            .total_cmp(&y.as_f64().unwrap_or(f64::NAN)),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare_json(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Query cursors are the hex-encoded ID of the last entity on a page.
+fn encode_cursor(id: &str) -> String {
+    use std::fmt::Write;
+    id.bytes().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{b:02x}");
+        out
+    })
+}
+
+fn decode_cursor(cursor: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid query cursor: {cursor}");
+    if !cursor.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| {
+            cursor
+                .get(i..i + 2)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
 }
 
 // Helper trait for string partitioning
@@ -0,0 +1,223 @@
+//! Async facade over [`GtsStore`] for use inside a tokio runtime.
+//!
+//! Every operation that may hit the reader (filesystem, network) or compile a
+//! JSON Schema runs on tokio's blocking thread pool, so callers such as the
+//! HTTP server never stall their async workers on slow lookups.
+
+use serde_json::Value;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::entities::GtsEntity;
+use crate::store::{GtsReader, GtsStore, GtsStoreQueryResult, StoreError};
+
+/// Cloneable, thread-safe async handle to a [`GtsStore`].
+///
+/// Clones share the same underlying store.
+#[derive(Clone)]
+pub struct AsyncGtsStore {
+    inner: Arc<Mutex<GtsStore>>,
+}
+
+impl AsyncGtsStore {
+    /// Creates a new store, populating it from `reader` on the blocking pool.
+    ///
+    /// # Errors
+    /// Returns `StoreError::AsyncTask` if the background task panics or is cancelled.
+    pub async fn new(reader: Option<Box<dyn GtsReader>>) -> Result<Self, StoreError> {
+        let store = tokio::task::spawn_blocking(move || GtsStore::new(reader))
+            .await
+            .map_err(|e| StoreError::AsyncTask(e.to_string()))?;
+        Ok(Self::from_store(store))
+    }
+
+    /// Wraps an already populated store.
+    #[must_use]
+    pub fn from_store(store: GtsStore) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    /// Registers an entity in the store.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID.
+    pub async fn register(&self, entity: GtsEntity) -> Result<(), StoreError> {
+        self.run(move |store| store.register(entity)).await?
+    }
+
+    /// Registers a schema in the store.
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSchemaId` if the `type_id` doesn't end with '~'.
+    pub async fn register_schema(&self, type_id: &str, schema: Value) -> Result<(), StoreError> {
+        let type_id = type_id.to_owned();
+        self.run(move |store| store.register_schema(&type_id, &schema))
+            .await?
+    }
+
+    /// Looks up an entity, falling back to the reader when it is not cached.
+    ///
+    /// # Errors
+    /// Returns `StoreError::AsyncTask` if the background task fails.
+    pub async fn get(&self, entity_id: &str) -> Result<Option<GtsEntity>, StoreError> {
+        let entity_id = entity_id.to_owned();
+        self.run(move |store| store.get(&entity_id).cloned()).await
+    }
+
+    /// Runs a query expression against the store.
+    ///
+    /// # Errors
+    /// Returns `StoreError::AsyncTask` if the background task fails.
+    pub async fn query(&self, expr: &str, limit: usize) -> Result<GtsStoreQueryResult, StoreError> {
+        let expr = expr.to_owned();
+        self.run(move |store| store.query(&expr, limit)).await
+    }
+
+    /// Validates an instance against its schema.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if validation fails.
+    pub async fn validate_instance(&self, gts_id: &str) -> Result<(), StoreError> {
+        let gts_id = gts_id.to_owned();
+        self.run(move |store| store.validate_instance(&gts_id))
+            .await?
+    }
+
+    /// Validates arbitrary JSON content against a registered schema.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the schema is missing or validation fails.
+    pub async fn validate_value(&self, schema_id: &str, content: Value) -> Result<(), StoreError> {
+        let schema_id = schema_id.to_owned();
+        self.run(move |store| store.validate_value(&schema_id, &content))
+            .await?
+    }
+
+    /// Validates a schema against the JSON Schema meta-schema and its base chain.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if validation fails.
+    pub async fn validate_schema(&self, gts_id: &str) -> Result<(), StoreError> {
+        let gts_id = gts_id.to_owned();
+        self.run(move |store| store.validate_schema(&gts_id))
+            .await?
+    }
+
+    /// Runs an arbitrary closure against the store on the blocking pool.
+    ///
+    /// This is the escape hatch for store operations that have no dedicated
+    /// async wrapper.
+    ///
+    /// # Errors
+    /// Returns `StoreError::AsyncTask` if the background task fails.
+    pub async fn run<F, R>(&self, f: F) -> Result<R, StoreError>
+    where
+        F: FnOnce(&mut GtsStore) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut store = lock(&inner);
+            f(&mut store)
+        })
+        .await
+        .map_err(|e| StoreError::AsyncTask(e.to_string()))
+    }
+}
+
+/// A panic in an earlier task must not brick the store for everyone else.
+fn lock(inner: &Mutex<GtsStore>) -> MutexGuard<'_, GtsStore> {
+    inner.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA_ID: &str = "gts.x.test.async.item.v1~";
+
+    fn schema() -> Value {
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$id": format!("gts://{SCHEMA_ID}"),
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": {"type": "string"},
+                "name": {"type": "string"}
+            }
+        })
+    }
+
+    fn instance(id: &str, name: &Value) -> GtsEntity {
+        let content = json!({"id": id, "type": SCHEMA_ID, "name": name});
+        GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&crate::entities::GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(SCHEMA_ID.to_owned()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_register_and_get() {
+        let store = AsyncGtsStore::new(None).await.unwrap();
+        store.register_schema(SCHEMA_ID, schema()).await.unwrap();
+
+        let entity = store.get(SCHEMA_ID).await.unwrap().expect("schema stored");
+        assert!(entity.is_schema);
+        assert!(
+            store
+                .get("gts.x.test.async.missing.v1~")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_schema_rejects_instance_id() {
+        let store = AsyncGtsStore::new(None).await.unwrap();
+        let err = store
+            .register_schema("gts.x.test.async.item.v1.0", schema())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StoreError::InvalidSchemaId));
+    }
+
+    #[tokio::test]
+    async fn test_validate_instance() {
+        let store = AsyncGtsStore::new(None).await.unwrap();
+        store.register_schema(SCHEMA_ID, schema()).await.unwrap();
+
+        let good = format!("{SCHEMA_ID}x.test.async.good.v1.0");
+        let bad = format!("{SCHEMA_ID}x.test.async.bad.v1.0");
+        store.register(instance(&good, &json!("ok"))).await.unwrap();
+        store.register(instance(&bad, &json!(42))).await.unwrap();
+
+        store.validate_instance(&good).await.unwrap();
+        let err = store.validate_instance(&bad).await.unwrap_err();
+        assert!(matches!(err, StoreError::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_query_and_shared_clones() {
+        let store = AsyncGtsStore::new(None).await.unwrap();
+        let clone = store.clone();
+        clone.register_schema(SCHEMA_ID, schema()).await.unwrap();
+
+        let result = store.query("gts.x.test.async.*", 10).await.unwrap();
+        assert_eq!(result.count, 1);
+
+        let count = store.run(|s| s.items().count()).await.unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Point-in-time copies of the registered state of a store.
+//!
+//! [`GtsStore::snapshot`] captures every registered entity together with its
+//! labels; [`GtsStore::restore`] puts the store back to that state, e.g.
+//! after an experimental import or at the end of a test.  Settings such as
+//! the number policy, the reader and the writer are not part of the state
+//! and are left as they are.  Restoring changes only the store in memory,
+//! never the files behind it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::entities::GtsEntity;
+use crate::store::GtsStore;
+
+/// The registered entities and labels of a store at one point in time.
+#[derive(Debug, Clone)]
+pub struct GtsStoreSnapshot {
+    entities: HashMap<String, GtsEntity>,
+    labels: HashMap<String, BTreeMap<String, String>>,
+}
+
+impl GtsStoreSnapshot {
+    /// Number of entities captured.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Whether entity `id` was registered when the snapshot was taken.
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.entities.contains_key(id)
+    }
+}
+
+impl GtsStore {
+    /// Captures every registered entity and its labels.  Entities the reader
+    /// has not loaded yet are not included.
+    #[must_use]
+    pub fn snapshot(&self) -> GtsStoreSnapshot {
+        GtsStoreSnapshot {
+            entities: self.entities().clone(),
+            labels: self.all_labels().clone(),
+        }
+    }
+
+    /// Puts the registered entities and labels back to `snapshot`, dropping
+    /// everything registered since and rebuilding the query indexes.
+    pub fn restore(&mut self, snapshot: &GtsStoreSnapshot) {
+        tracing::info!(
+            "Restoring store snapshot of {} entities ({} registered now)",
+            snapshot.len(),
+            self.entities().len()
+        );
+        self.entities_mut().clone_from(&snapshot.entities);
+        self.all_labels_mut().clone_from(&snapshot.labels);
+        self.rebuild_indexes();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use serde_json::json;
+
+    const ITEM: &str = "gts.x.test.snap.item.v1~";
+
+    fn register_item(store: &mut GtsStore, name: &str) -> String {
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{ITEM}x.test._.{name}.v1"), "name": name}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(ITEM.to_owned()),
+        );
+        let id = entity.gts_id.as_ref().unwrap().id.clone();
+        store.register(entity).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_restore_reverts_registrations_labels_and_schemas() {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ITEM,
+                &json!({
+                    "$id": format!("gts://{ITEM}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}}
+                }),
+            )
+            .unwrap();
+        let kept = register_item(&mut store, "kept");
+        store.label(&kept, "env", "prod").unwrap();
+        let snapshot = store.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let added = register_item(&mut store, "added");
+        store.label(&kept, "env", "dev").unwrap();
+        // A stricter schema that the kept instance fails
+        store
+            .register_schema(
+                ITEM,
+                &json!({
+                    "$id": format!("gts://{ITEM}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "required": ["missing"]
+                }),
+            )
+            .unwrap();
+        assert!(store.validate_instance(&kept).is_err());
+
+        store.restore(&snapshot);
+        assert!(store.get(&added).is_none());
+        assert!(!snapshot.contains(&added));
+        assert_eq!(store.labels(&kept)["env"], "prod");
+        store.validate_instance(&kept).unwrap();
+        assert_eq!(store.query(&format!("{ITEM}*"), 10).count, 1);
+
+        // A snapshot can be restored more than once
+        register_item(&mut store, "again");
+        store.restore(&snapshot);
+        assert_eq!(store.items().count(), 2);
+    }
+}
@@ -2423,6 +2423,134 @@ fn test_validate_schema_integration() {
     assert!(err.contains("must be a local ref") || err.contains("gts://"));
 }
 
+fn store_with_local_defs() -> GtsStore {
+    let mut store = GtsStore::new(None);
+    store
+        .register_schema(
+            "gts.x.test.defs.contact.v1~",
+            &json!({
+                "$id": "gts://gts.x.test.defs.contact.v1~",
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "home": {"$ref": "#/$defs/Address"},
+                    "phone": {"$ref": "#/definitions/Phone/properties/number"}
+                },
+                "$defs": {
+                    "Address": {
+                        "type": "object",
+                        "required": ["street"],
+                        "properties": {
+                            "street": {"type": "string"},
+                            "geo": {"$ref": "#/$defs/Geo"}
+                        }
+                    },
+                    "Geo": {"type": "object", "properties": {"lat": {"type": "number"}}}
+                },
+                "definitions": {
+                    "Phone": {"properties": {"number": {"type": "string", "pattern": "^[0-9+]+$"}}}
+                }
+            }),
+        )
+        .expect("test");
+    store
+        .register_schema(
+            "gts.x.test.defs.contact.v1~x.test.defs.customer.v1~",
+            &json!({
+                "$id": "gts://gts.x.test.defs.contact.v1~x.test.defs.customer.v1~",
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "allOf": [
+                    {"$ref": "gts://gts.x.test.defs.contact.v1~"},
+                    {"type": "object", "properties": {"tier": {"type": "string"}}}
+                ]
+            }),
+        )
+        .expect("test");
+    store
+}
+
+#[test]
+fn test_resolve_schema_refs_inlines_local_defs_of_referenced_schema() {
+    let mut store = store_with_local_defs();
+    let derived = store
+        .get_schema_content("gts.x.test.defs.contact.v1~x.test.defs.customer.v1~")
+        .expect("test");
+
+    let resolved = store.resolve_schema_refs(&derived);
+
+    // The base's #/$defs refs point into the base document, not the derived one
+    let props = &resolved["properties"];
+    assert_eq!(props["home"]["properties"]["street"]["type"], "string");
+    assert_eq!(props["home"]["required"], json!(["street"]));
+    assert_eq!(
+        props["home"]["properties"]["geo"]["properties"]["lat"]["type"],
+        "number"
+    );
+    assert_eq!(props["phone"]["pattern"], "^[0-9+]+$");
+    assert_eq!(props["tier"]["type"], "string");
+    assert!(!resolved.to_string().contains("\"$ref\""));
+}
+
+#[test]
+fn test_resolve_schema_refs_keeps_recursive_local_ref() {
+    let store = GtsStore::new(None);
+    let schema = json!({
+        "type": "object",
+        "properties": {"root": {"$ref": "#/$defs/Node"}},
+        "$defs": {
+            "Node": {
+                "type": "object",
+                "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/Node"}}}
+            }
+        }
+    });
+
+    let resolved = store.resolve_schema_refs(&schema);
+
+    let root = &resolved["properties"]["root"];
+    assert_eq!(root["type"], "object");
+    assert_eq!(
+        root["properties"]["children"]["items"],
+        json!({"$ref": "#/$defs/Node"})
+    );
+}
+
+#[test]
+fn test_validate_instance_checks_local_defs_of_base_schema() {
+    let mut store = store_with_local_defs();
+    let schema_id = "gts.x.test.defs.contact.v1~x.test.defs.customer.v1~";
+    for (suffix, street) in [("good", json!("Main St")), ("bad", json!(42))] {
+        let content = json!({
+            "id": format!("{schema_id}x.test._.{suffix}.v1"),
+            "home": {"street": street},
+            "phone": "+123"
+        });
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        );
+        store.register(entity).expect("test");
+    }
+
+    assert!(
+        store
+            .validate_instance(&format!("{schema_id}x.test._.good.v1"))
+            .is_ok()
+    );
+    assert!(
+        store
+            .validate_instance(&format!("{schema_id}x.test._.bad.v1"))
+            .is_err()
+    );
+}
+
 #[test]
 fn test_resolve_schema_refs_with_gts_uri_prefix() {
     let mut store = GtsStore::new(None);
@@ -3223,6 +3351,15 @@ fn test_op12_loosened_max_length_fails() {
 
     let result = store.validate_schema_chain("gts.x.test12.str.field.v1~x.test12._.loose.v1~");
     assert!(result.is_err(), "Loosened maxLength should fail");
+
+    let err = result.expect_err("test");
+    assert_eq!(err.code(), GtsCode::IncompatibleSchema);
+    let codes: Vec<&str> = err.diagnostics().iter().map(|d| d.code.as_str()).collect();
+    assert_eq!(codes, ["GTS0012"]);
+    assert!(
+        err.to_string()
+            .contains("derived maxLength (256) exceeds base maxLength (128)")
+    );
 }
 
 #[test]
@@ -4346,3 +4483,621 @@ fn test_op13_change_default_in_mid_fails() {
         "Changing default in mid-level should fail, got: {result:?}"
     );
 }
+
+// ---------------------------------------------------------------------------
+// $ref sibling merge semantics
+// ---------------------------------------------------------------------------
+
+fn store_with_sibling_ref_base() -> GtsStore {
+    let mut store = GtsStore::new(None);
+    let base = json!({
+        "$id": "gts://gts.x.testmerge.core.base.v1~",
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["id"],
+        "properties": {
+            "id": {"type": "string"},
+            "name": {"type": "string", "maxLength": 10}
+        }
+    });
+    store
+        .register_schema("gts.x.testmerge.core.base.v1~", &base)
+        .expect("register base");
+    store
+}
+
+fn schema_with_ref_siblings() -> Value {
+    json!({
+        "$ref": "gts://gts.x.testmerge.core.base.v1~",
+        "required": ["name"],
+        "properties": {
+            "name": {"minLength": 1}
+        }
+    })
+}
+
+#[test]
+fn test_ref_sibling_merge_default_is_deep() {
+    let store = store_with_sibling_ref_base();
+    assert_eq!(store.ref_sibling_merge(), RefSiblingMerge::Deep);
+
+    let resolved = store.resolve_schema_refs(&schema_with_ref_siblings());
+
+    // Constraints from both the referenced schema and the siblings survive
+    assert_eq!(resolved["required"], json!(["id", "name"]));
+    assert_eq!(resolved["properties"]["id"], json!({"type": "string"}));
+    assert_eq!(
+        resolved["properties"]["name"],
+        json!({"type": "string", "maxLength": 10, "minLength": 1})
+    );
+}
+
+#[test]
+fn test_ref_sibling_merge_override_replaces_keywords() {
+    let mut store = store_with_sibling_ref_base();
+    store.set_ref_sibling_merge(RefSiblingMerge::Override);
+
+    let resolved = store.resolve_schema_refs(&schema_with_ref_siblings());
+
+    assert_eq!(resolved["required"], json!(["name"]));
+    assert_eq!(
+        resolved["properties"],
+        json!({"name": {"minLength": 1}}),
+        "override mode replaces the whole keyword"
+    );
+    assert_eq!(resolved["type"], json!("object"));
+}
+
+#[test]
+fn test_ref_sibling_merge_ignore_drops_siblings() {
+    let mut store = store_with_sibling_ref_base();
+    store.set_ref_sibling_merge(RefSiblingMerge::Ignore);
+
+    let resolved = store.resolve_schema_refs(&schema_with_ref_siblings());
+
+    assert_eq!(resolved["required"], json!(["id"]));
+    assert_eq!(
+        resolved["properties"]["name"],
+        json!({"type": "string", "maxLength": 10})
+    );
+}
+
+#[test]
+fn test_ref_sibling_merge_deep_concatenates_all_of() {
+    let mut store = GtsStore::new(None);
+    let base = json!({
+        "$id": "gts://gts.x.testmerge.core.combo.v1~",
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "allOf": [{"required": ["a"]}]
+    });
+    store
+        .register_schema("gts.x.testmerge.core.combo.v1~", &base)
+        .expect("register base");
+
+    let resolved = store.resolve_schema_refs(&json!({
+        "$ref": "gts://gts.x.testmerge.core.combo.v1~",
+        "allOf": [{"required": ["b"]}]
+    }));
+    assert_eq!(
+        resolved["allOf"],
+        json!([{"required": ["a"]}, {"required": ["b"]}])
+    );
+}
+
+#[test]
+fn test_ref_sibling_warnings_reported() {
+    let store = store_with_sibling_ref_base();
+
+    let (_, warnings) = store.resolve_schema_refs_with_warnings(&schema_with_ref_siblings());
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].ref_uri, "gts://gts.x.testmerge.core.base.v1~");
+    assert_eq!(warnings[0].sibling_keys, vec!["required", "properties"]);
+    assert_eq!(warnings[0].mode, RefSiblingMerge::Deep);
+
+    // A bare $ref has no siblings and produces no warning
+    let (_, warnings) = store
+        .resolve_schema_refs_with_warnings(&json!({"$ref": "gts://gts.x.testmerge.core.base.v1~"}));
+    assert!(warnings.is_empty());
+}
+
+fn store_with_labeled_schemas() -> GtsStore {
+    let mut store = GtsStore::new(None);
+    for i in 0..3 {
+        let schema_id = format!("gts.vendor.package.namespace.type.v{i}.0~");
+        let schema_content = json!({
+            "$id": schema_id,
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object"
+        });
+        store
+            .register_schema(&schema_id, &schema_content)
+            .expect("test");
+    }
+    store
+        .label("gts.vendor.package.namespace.type.v0.0~", "env", "prod")
+        .expect("label");
+    store
+        .label("gts.vendor.package.namespace.type.v1.0~", "env", "dev")
+        .expect("label");
+    store
+        .label("gts.vendor.package.namespace.type.v1.0~", "tier", "gold")
+        .expect("label");
+    store
+}
+
+#[test]
+fn test_label_and_query_with_labels() {
+    let store = store_with_labeled_schemas();
+
+    let wanted = BTreeMap::from([("env".to_owned(), "prod".to_owned())]);
+    let result = store.query_with_labels("gts.vendor.*", &wanted, 10);
+    assert_eq!(result.count, 1);
+    assert_eq!(
+        result.results[0]["$id"],
+        "gts.vendor.package.namespace.type.v0.0~"
+    );
+
+    let wanted = BTreeMap::from([
+        ("env".to_owned(), "dev".to_owned()),
+        ("tier".to_owned(), "gold".to_owned()),
+    ]);
+    assert_eq!(
+        store.query_with_labels("gts.vendor.*", &wanted, 10).count,
+        1
+    );
+
+    let wanted = BTreeMap::from([("env".to_owned(), "staging".to_owned())]);
+    assert_eq!(
+        store.query_with_labels("gts.vendor.*", &wanted, 10).count,
+        0
+    );
+
+    // No labels requested: behaves like a plain query
+    assert_eq!(
+        store
+            .query_with_labels("gts.vendor.*", &BTreeMap::new(), 10)
+            .count,
+        3
+    );
+}
+
+#[test]
+fn test_labels_survive_reregistration() {
+    let mut store = store_with_labeled_schemas();
+    let schema_id = "gts.vendor.package.namespace.type.v0.0~";
+    store
+        .register_schema(
+            schema_id,
+            &json!({
+                "$id": schema_id,
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "description": "updated"
+            }),
+        )
+        .expect("test");
+
+    assert_eq!(
+        store.labels(schema_id).get("env").map(String::as_str),
+        Some("prod")
+    );
+}
+
+#[test]
+fn test_labels_go_with_a_removed_entity() {
+    let mut store = store_with_labeled_schemas();
+    let schema_id = "gts.vendor.package.namespace.type.v0.0~";
+    let schema = store.get(schema_id).expect("test").content.clone();
+
+    store.remove(schema_id, true).expect("test");
+    store.register_schema(schema_id, &schema).expect("test");
+
+    assert!(store.labels(schema_id).is_empty());
+}
+
+#[test]
+fn test_unlabel_and_missing_entity() {
+    let mut store = store_with_labeled_schemas();
+    let schema_id = "gts.vendor.package.namespace.type.v0.0~";
+
+    assert_eq!(store.unlabel(schema_id, "env"), Some("prod".to_owned()));
+    assert_eq!(store.unlabel(schema_id, "env"), None);
+    assert!(store.labels(schema_id).is_empty());
+
+    assert!(matches!(
+        store.label("gts.vendor.package.namespace.missing.v1~", "env", "prod"),
+        Err(StoreError::EntityNotFound(_))
+    ));
+}
+
+fn store_with_products() -> GtsStore {
+    let mut store = GtsStore::new(None);
+    let cfg = GtsConfig::default();
+    let products = [
+        ("a", 50, "active", "gold", 25, "2024-01-01", "9"),
+        ("b", 150, "active", "silver", 30, "2024-03-01", "10"),
+        ("c", 250, "archived", "gold", 40, "2024-05-01", "2"),
+        ("d", 100, "draft", "bronze", 35, "2024-02-01", "v3"),
+    ];
+    for (name, price, status, tier, age, created, revision) in products {
+        let content = json!({
+            "id": format!("gts.x.shop.catalog.product.v1~x.shop._.{name}.v1"),
+            "price": price,
+            "status": status,
+            "tier": tier,
+            "age": age,
+            "created": format!("{created}T00:00:00Z"),
+            "revision": revision
+        });
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        store.register(entity).expect("test");
+    }
+    store
+}
+
+fn query_names(store: &GtsStore, expr: &str) -> Vec<String> {
+    let result = store.query(expr, 100);
+    assert!(result.error.is_empty(), "{expr}: {}", result.error);
+    let mut names: Vec<String> = result
+        .results
+        .iter()
+        .map(|r| {
+            r["id"]
+                .as_str()
+                .and_then(|id| id.rsplit('.').nth(1))
+                .expect("id")
+                .to_owned()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_query_filter_comparison_operators() {
+    let store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*";
+
+    assert_eq!(
+        query_names(&store, &format!("{base}[price>100]")),
+        ["b", "c"]
+    );
+    assert_eq!(
+        query_names(&store, &format!("{base}[price>=100]")),
+        ["b", "c", "d"]
+    );
+    assert_eq!(query_names(&store, &format!("{base}[age<30]")), ["a"]);
+    assert_eq!(query_names(&store, &format!("{base}[age<=30]")), ["a", "b"]);
+    assert_eq!(
+        query_names(&store, &format!("{base}[status!=archived]")),
+        ["a", "b", "d"]
+    );
+    assert_eq!(
+        query_names(&store, &format!("{base}[created<2024-03-01]")),
+        ["a", "d"]
+    );
+    // Ordering against a non-numeric value never matches a number
+    assert!(query_names(&store, &format!("{base}[price>abc]")).is_empty());
+}
+
+#[test]
+fn test_query_filter_compares_numeric_strings_as_numbers() {
+    let store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*";
+
+    // "10" > "9" numerically; "v3" is not numeric, so it compares as text
+    assert_eq!(
+        query_names(&store, &format!("{base}[revision>9]")),
+        ["b", "d"]
+    );
+    assert_eq!(
+        query_names(&store, &format!("{base}[revision<10]")),
+        ["a", "c"]
+    );
+    assert_eq!(
+        query_names(&store, &format!("{base}[revision>=2]")),
+        ["a", "b", "c", "d"]
+    );
+}
+
+#[test]
+fn test_query_filter_logical_operators() {
+    let store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*";
+
+    assert_eq!(
+        query_names(&store, &format!("{base}[status=active&tier=gold]")),
+        ["a"]
+    );
+    // Comma keeps working as AND
+    assert_eq!(
+        query_names(&store, &format!("{base}[status=active,tier=gold]")),
+        ["a"]
+    );
+    assert_eq!(
+        query_names(&store, &format!("{base}[status=draft|tier=gold]")),
+        ["a", "c", "d"]
+    );
+    // `&` binds tighter than `|`
+    assert_eq!(
+        query_names(
+            &store,
+            &format!("{base}[status=active&price>100|status=draft]")
+        ),
+        ["b", "d"]
+    );
+}
+
+#[test]
+fn test_query_filter_not_equal_matches_missing_field() {
+    let store = store_with_products();
+    assert_eq!(
+        query_names(&store, "gts.x.shop.catalog.product.v1~*[color!=red]").len(),
+        4
+    );
+    assert!(query_names(&store, "gts.x.shop.catalog.product.v1~*[color=*]").is_empty());
+}
+
+#[test]
+fn test_query_page_walks_all_results_in_id_order() {
+    let store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*";
+
+    let mut ids = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let page = store.query_page(base, cursor.as_deref(), 3);
+        assert!(page.error.is_empty());
+        assert_eq!(page.count, page.results.len());
+        ids.extend(
+            page.results
+                .iter()
+                .map(|r| r["id"].as_str().expect("id").to_owned()),
+        );
+        pages += 1;
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, 2);
+    assert_eq!(ids.len(), 4);
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(ids, sorted);
+}
+
+#[test]
+fn test_query_page_is_stable_across_inserts() {
+    let mut store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*[status=active]";
+
+    let first = store.query_page(base, None, 1);
+    assert_eq!(first.count, 1);
+    let cursor = first.next_cursor.expect("more results");
+
+    // An entity sorting before the cursor must not shift the next page
+    let content = json!({
+        "id": "gts.x.shop.catalog.product.v1~x.shop._.0.v1",
+        "status": "active"
+    });
+    let entity = GtsEntity::new(
+        None,
+        None,
+        &content,
+        Some(&GtsConfig::default()),
+        None,
+        false,
+        String::new(),
+        None,
+        None,
+    );
+    store.register(entity).expect("test");
+
+    let second = store.query_page(base, Some(&cursor), 1);
+    assert_eq!(
+        second.results[0]["id"],
+        "gts.x.shop.catalog.product.v1~x.shop._.b.v1"
+    );
+    assert!(second.next_cursor.is_none());
+}
+
+#[test]
+fn test_query_page_invalid_cursor() {
+    let store = store_with_products();
+    let page = store.query_page("gts.x.shop.*", Some("zz"), 10);
+    assert!(page.error.contains("Invalid query cursor"));
+    assert!(page.results.is_empty());
+}
+
+#[test]
+fn test_query_sorted_by_number_asc_and_desc() {
+    let store = store_with_products();
+    let base = "gts.x.shop.catalog.product.v1~*";
+    let prices = |result: GtsStoreQueryResult| -> Vec<String> {
+        result
+            .results
+            .iter()
+            .map(|r| r["price"].to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        prices(store.query_sorted(base, "price", SortOrder::Asc, 10)),
+        ["50", "100", "150", "250"]
+    );
+    assert_eq!(
+        prices(store.query_sorted(base, "price", SortOrder::Desc, 2)),
+        ["250", "150"]
+    );
+}
+
+#[test]
+fn test_query_sorted_missing_and_mixed_types() {
+    let mut store = GtsStore::new(None);
+    let cfg = GtsConfig::default();
+    let values = [
+        ("a", json!({"rank": 3})),
+        ("b", json!({"rank": "high"})),
+        ("c", json!({})),
+        ("d", json!({"rank": 1})),
+        ("e", json!({"rank": null})),
+        ("f", json!({"nested": {"rank": 2}})),
+    ];
+    for (name, extra) in values {
+        let mut content = json!({"id": format!("gts.x.shop.catalog.item.v1~x.shop._.{name}.v1")});
+        if let (Some(obj), Some(meta)) = (content.as_object_mut(), extra.as_object()) {
+            obj.insert("meta".to_owned(), Value::Object(meta.clone()));
+        }
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&cfg),
+            None,
+            false,
+            String::new(),
+            None,
+            None,
+        );
+        store.register(entity).expect("test");
+    }
+
+    let order = |order: SortOrder| -> Vec<String> {
+        store
+            .query_sorted("gts.x.shop.catalog.item.v1~*", "meta.rank", order, 10)
+            .results
+            .iter()
+            .map(|r| {
+                r["id"]
+                    .as_str()
+                    .and_then(|id| id.rsplit('.').nth(1))
+                    .expect("id")
+                    .to_owned()
+            })
+            .collect()
+    };
+
+    // null < numbers < strings; missing fields last, ties by ID
+    assert_eq!(order(SortOrder::Asc), ["e", "d", "a", "b", "c", "f"]);
+    assert_eq!(order(SortOrder::Desc), ["b", "a", "d", "e", "c", "f"]);
+
+    let nested = store.query_sorted(
+        "gts.x.shop.catalog.item.v1~*",
+        "meta.nested.rank",
+        SortOrder::Asc,
+        1,
+    );
+    assert_eq!(
+        nested.results[0]["id"],
+        "gts.x.shop.catalog.item.v1~x.shop._.f.v1"
+    );
+}
+
+fn register_counter_instance(store: &mut GtsStore, schema_id: &str, content: &Value) {
+    let entity = GtsEntity::new(
+        None,
+        None,
+        content,
+        Some(&GtsConfig::default()),
+        None,
+        false,
+        String::new(),
+        None,
+        Some(schema_id.to_owned()),
+    );
+    store.register(entity).expect("register instance");
+}
+
+#[test]
+fn test_number_policy_strict_integers_and_bigint_strings() {
+    let mut store = GtsStore::new(None);
+    let schema_id = "gts.x.test.num.counter.v1~";
+    store
+        .register_schema(
+            schema_id,
+            &json!({
+                "$id": format!("gts://{schema_id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "count": {"type": "integer"},
+                    "sequence_id": {"type": "string", "format": "bigint"}
+                }
+            }),
+        )
+        .expect("register schema");
+    let id = "gts.x.test.num.counter.v1~x.test._.c1.v1";
+    register_counter_instance(
+        &mut store,
+        schema_id,
+        &json!({"id": id, "count": 1.0, "sequence_id": "12x"}),
+    );
+
+    // JSON Schema semantics accept 1.0 as an integer and ignore unknown formats
+    assert!(store.validate_instance(id).is_ok());
+
+    store.set_number_policy(GtsNumberPolicy {
+        strict_integers: true,
+        bignum_as_string: true,
+    });
+    match store.validate_instance(id) {
+        Err(StoreError::ValidationError(msg)) => {
+            assert!(msg.starts_with("Number validation failed"), "{msg}");
+            assert!(msg.contains("/count: 1.0 is not an integer"), "{msg}");
+            assert!(
+                msg.contains("/sequence_id: '12x' is not a valid bigint"),
+                "{msg}"
+            );
+        }
+        other => panic!("expected number validation failure, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_cast_converts_numbers_to_bigint_strings() {
+    let mut store = GtsStore::new(None);
+    let v1 = "gts.x.test.num.event.v1.0~";
+    let v2 = "gts.x.test.num.event.v1.1~";
+    for (id, seq) in [
+        (v1, json!({"type": "integer"})),
+        (v2, json!({"type": "string", "format": "bigint"})),
+    ] {
+        store
+            .register_schema(
+                id,
+                &json!({
+                    "$id": format!("gts://{id}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"seq": seq}
+                }),
+            )
+            .expect("register schema");
+    }
+    let id = "gts.x.test.num.event.v1.0~x.test._.e1.v1";
+    register_counter_instance(
+        &mut store,
+        v1,
+        &json!({"id": id, "seq": 18_446_744_073_709_551_615_u64}),
+    );
+
+    let result = store.cast(id, v2).expect("cast");
+    let casted = result.casted_entity.expect("casted entity");
+    assert_eq!(casted["seq"], "18446744073709551615");
+    assert_eq!(result.changed_properties.len(), 1);
+    assert_eq!(result.changed_properties[0]["path"], "seq");
+}
@@ -0,0 +1,471 @@
+//! A [`GtsReader`] for files too large to load whole.
+//!
+//! [`GtsStreamReader`] finds files like [`GtsFileReader`] but parses JSON
+//! files lazily: the elements of a top-level array go through a
+//! `serde_json` stream deserializer and NDJSON files are read a line at a
+//! time, so only the entity being handed out is in memory.  Nothing is read
+//! until the consumer asks for the next entity.  YAML files cannot be
+//! streamed and are loaded whole.
+//!
+//! With the `encryption` feature, encrypted instances are opened with the
+//! reader's cipher, by default the [`GtsContentCipher::from_env`] key the
+//! store's own reader uses; those that cannot be opened are skipped.
+//!
+//! Entities keep the path and name of their file but not its content.  A
+//! file that fails to parse stops at the failure with a warning; the
+//! entities before it are kept.  An NDJSON line that fails to parse is
+//! skipped with a warning naming the line.
+
+use serde_json::Value;
+use serde_json::de::IoRead;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+#[cfg(feature = "encryption")]
+use crate::encryption::GtsContentCipher;
+use crate::entities::{GtsConfig, GtsEntity, GtsFile};
+use crate::files_reader::GtsFileReader;
+use crate::store::GtsReader;
+
+/// Extensions of the files parsed lazily; the rest are loaded whole.
+const STREAMED_EXTENSIONS: &[&str] = &["json", "jsonc", "gts", "ndjson", "jsonl"];
+
+/// Reads entities one at a time from files and directories.
+pub struct GtsStreamReader {
+    files: GtsFileReader,
+    cfg: GtsConfig,
+}
+
+impl GtsStreamReader {
+    #[must_use]
+    pub fn new(path: &[String], cfg: Option<GtsConfig>) -> Self {
+        let cfg = cfg.unwrap_or_default();
+        let files = GtsFileReader::new(path, Some(cfg.clone()));
+        #[cfg(feature = "encryption")]
+        let files = match GtsContentCipher::from_env(&cfg) {
+            Some(cipher) => files.with_cipher(cipher),
+            None => files,
+        };
+        GtsStreamReader { files, cfg }
+    }
+
+    /// Decrypts instances written with `cipher` instead of the configured
+    /// one; encrypted instances that cannot be decrypted are skipped.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn with_cipher(mut self, cipher: GtsContentCipher) -> Self {
+        self.files = self.files.with_cipher(cipher);
+        self
+    }
+}
+
+impl GtsReader for GtsStreamReader {
+    fn iter(&mut self) -> Box<dyn Iterator<Item = GtsEntity> + '_> {
+        let files = self.files.discovered_files().to_vec();
+        let whole = &self.files;
+        let cfg = &self.cfg;
+        Box::new(files.into_iter().flat_map(
+            move |path| -> Box<dyn Iterator<Item = GtsEntity> + '_> {
+                match FileEntities::open(&path, cfg, whole) {
+                    Some(Ok(entities)) => Box::new(entities),
+                    Some(Err(e)) => {
+                        tracing::warn!("Skipping {}: {e}", path.display());
+                        Box::new(std::iter::empty())
+                    }
+                    None => Box::new(whole.process_file(&path).into_iter()),
+                }
+            },
+        ))
+    }
+
+    fn read_by_id(&self, _entity_id: &str) -> Option<GtsEntity> {
+        None
+    }
+
+    fn reset(&mut self) {
+        self.files.reset();
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.files.check()
+    }
+}
+
+/// Where the documents of a file come from.
+enum Documents {
+    /// NDJSON lines, numbered from zero.
+    Lines(io::Lines<BufReader<File>>),
+    /// The elements of a top-level array, or a single document when
+    /// `sequenced` is false.
+    Values {
+        values: serde_json::StreamDeserializer<'static, IoRead<ArrayElements>, Value>,
+        sequenced: bool,
+    },
+}
+
+/// A document of a file and its sequence number in it.
+type Document = (Value, Option<usize>);
+
+/// The entities of one file, parsed as they are asked for.
+struct FileEntities<'a> {
+    cfg: &'a GtsConfig,
+    /// Opens encrypted documents.
+    #[cfg_attr(not(feature = "encryption"), allow(dead_code))]
+    files: &'a GtsFileReader,
+    path: String,
+    name: String,
+    documents: Documents,
+    sequence: usize,
+    done: bool,
+}
+
+impl<'a> FileEntities<'a> {
+    /// The entities of `path`, or `None` for a file that cannot be streamed.
+    fn open(path: &Path, cfg: &'a GtsConfig, files: &'a GtsFileReader) -> Option<io::Result<Self>> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !STREAMED_EXTENSIONS.contains(&extension.as_str()) {
+            return None;
+        }
+        Some(Self::open_streamed(path, &extension, cfg, files))
+    }
+
+    fn open_streamed(
+        path: &Path,
+        extension: &str,
+        cfg: &'a GtsConfig,
+        files: &'a GtsFileReader,
+    ) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let documents = if matches!(extension, "ndjson" | "jsonl") {
+            Documents::Lines(reader.lines())
+        } else {
+            let sequenced = skip_to_array(&mut reader)?;
+            let values = serde_json::Deserializer::from_reader(ArrayElements {
+                inner: reader,
+                depth: usize::from(sequenced),
+                in_string: false,
+                escaped: false,
+            })
+            .into_iter();
+            Documents::Values { values, sequenced }
+        };
+        Ok(FileEntities {
+            cfg,
+            files,
+            path: path.to_string_lossy().to_string(),
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            documents,
+            sequence: 0,
+            done: false,
+        })
+    }
+
+    /// The next document and its sequence number, skipping blank lines.
+    fn next_document(&mut self) -> Option<Result<Document, String>> {
+        match &mut self.documents {
+            Documents::Lines(lines) => loop {
+                let line = lines.next()?;
+                let number = self.sequence;
+                self.sequence += 1;
+                let line = match line {
+                    Ok(line) => line,
+                    Err(e) => return Some(Err(e.to_string())),
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(doc) => return Some(Ok((doc, Some(number)))),
+                    Err(e) => {
+                        tracing::warn!("Skipping line {} of {}: {e}", number + 1, self.path);
+                    }
+                }
+            },
+            Documents::Values { values, sequenced } => {
+                let sequence = sequenced.then_some(self.sequence);
+                self.sequence += 1;
+                Some(
+                    values
+                        .next()?
+                        .map(|doc| (doc, sequence))
+                        .map_err(|e| e.to_string()),
+                )
+            }
+        }
+    }
+}
+
+impl Iterator for FileEntities<'_> {
+    type Item = GtsEntity;
+
+    fn next(&mut self) -> Option<GtsEntity> {
+        while !self.done {
+            let (content, sequence) = match self.next_document() {
+                Some(Ok(document)) => document,
+                Some(Err(e)) => {
+                    tracing::warn!("Stopped reading {}: {e}", self.path);
+                    self.done = true;
+                    return None;
+                }
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            #[cfg(feature = "encryption")]
+            let Some(content) = self.files.decrypt(Path::new(&self.path), content) else {
+                continue;
+            };
+            let file = GtsFile::new(self.path.clone(), self.name.clone(), Value::Null);
+            let entity = GtsEntity::new(
+                Some(file),
+                sequence,
+                &content,
+                Some(self.cfg),
+                None,
+                false,
+                String::new(),
+                None,
+                None,
+            );
+            if entity.effective_id().is_some() {
+                return Some(entity);
+            }
+            tracing::debug!("- skipped entity from {} (no valid ID)", self.path);
+        }
+        None
+    }
+}
+
+/// Consumes leading whitespace and, if the document is an array, its `[`;
+/// whether it was one.
+fn skip_to_array(reader: &mut impl BufRead) -> io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&first) = buf.first() else {
+            return Ok(false);
+        };
+        let blank = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        if blank > 0 {
+            reader.consume(blank);
+        } else if first == b'[' {
+            reader.consume(1);
+            return Ok(true);
+        } else {
+            return Ok(false);
+        }
+    }
+}
+
+/// Turns the rest of a top-level array into whitespace-separated values by
+/// blanking its commas and closing bracket, which a stream deserializer then
+/// reads one element at a time.  Byte offsets, and so error positions, are
+/// unchanged.
+struct ArrayElements {
+    inner: BufReader<File>,
+    /// Nesting depth, 1 inside the top-level array; 0 once it is closed,
+    /// or from the start for a document that is no array.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Read for ArrayElements {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for byte in &mut buf[..read] {
+            if self.depth == 0 {
+                break;
+            }
+            if self.in_string {
+                match *byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match *byte {
+                b'"' => self.in_string = true,
+                b'[' | b'{' => self.depth += 1,
+                b']' | b'}' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        *byte = b' ';
+                    }
+                }
+                b',' if self.depth == 1 => *byte = b' ',
+                _ => {}
+            }
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn ids(reader: &mut GtsStreamReader) -> Vec<String> {
+        reader
+            .iter()
+            .filter_map(|entity| entity.effective_id())
+            .collect()
+    }
+
+    #[test]
+    fn test_streams_arrays_lines_and_single_documents() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a.json"),
+            r#" [ {"id": "gts.x.test.stream.item.v1~x.test._.one.v1", "tags": ["a,b", "]"]},
+                 {"id": "gts.x.test.stream.item.v1~x.test._.two.v1", "note": "say \"[\""} ] "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("b.ndjson"),
+            "{\"id\": \"gts.x.test.stream.item.v1~x.test._.three.v1\"}\n\n\
+             {\"type\": \"gts.x.test.stream.item.v1~\"}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("c.json"),
+            r#"{"$id": "gts://gts.x.test.stream.item.v1~", "type": "object"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("d.yaml"),
+            "id: gts.x.test.stream.item.v1~x.test._.four.v1\n",
+        )
+        .unwrap();
+
+        let mut reader = GtsStreamReader::new(&[dir.path().to_string_lossy().to_string()], None);
+        let mut found = ids(&mut reader);
+        found.sort();
+        let lines = dir.path().join("b.ndjson").canonicalize().unwrap();
+        let mut expected = vec![
+            format!("{}#2", lines.display()),
+            "gts.x.test.stream.item.v1~".to_owned(),
+            "gts.x.test.stream.item.v1~x.test._.four.v1".to_owned(),
+            "gts.x.test.stream.item.v1~x.test._.one.v1".to_owned(),
+            "gts.x.test.stream.item.v1~x.test._.three.v1".to_owned(),
+            "gts.x.test.stream.item.v1~x.test._.two.v1".to_owned(),
+        ];
+        expected.sort();
+        assert_eq!(found, expected);
+        assert!(reader.check().is_ok());
+    }
+
+    #[test]
+    fn test_stops_a_file_at_its_first_error() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("broken.json");
+        fs::write(
+            &path,
+            "[{\"id\": \"gts.x.test.stream.item.v1~x.test._.one.v1\"},\n {broken}, \
+             {\"id\": \"gts.x.test.stream.item.v1~x.test._.two.v1\"}]",
+        )
+        .unwrap();
+        let mut reader = GtsStreamReader::new(&[path.to_string_lossy().to_string()], None);
+        assert_eq!(
+            ids(&mut reader),
+            ["gts.x.test.stream.item.v1~x.test._.one.v1"]
+        );
+
+        let path = dir.path().join("broken.ndjson");
+        fs::write(
+            &path,
+            "{\"id\": \"gts.x.test.stream.item.v1~x.test._.one.v1\"}\n{broken}\n\
+             {\"id\": \"gts.x.test.stream.item.v1~x.test._.two.v1\"}\n",
+        )
+        .unwrap();
+        let mut reader = GtsStreamReader::new(&[path.to_string_lossy().to_string()], None);
+        assert_eq!(
+            ids(&mut reader),
+            [
+                "gts.x.test.stream.item.v1~x.test._.one.v1",
+                "gts.x.test.stream.item.v1~x.test._.two.v1"
+            ]
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_streams_encrypted_instances() {
+        use crate::encryption::{GtsEnvKeyProvider, GtsKeyProvider};
+        use serde_json::json;
+        use std::sync::Arc;
+
+        struct FixedKey;
+        impl GtsKeyProvider for FixedKey {
+            fn current_key_id(&self) -> String {
+                "k1".to_owned()
+            }
+            fn key(&self, _key_id: &str) -> Result<[u8; 32], String> {
+                Ok([1; 32])
+            }
+        }
+
+        let cipher = GtsContentCipher::new(Arc::new(FixedKey), &GtsConfig::default());
+        let order = |name: &str| {
+            json!({
+                "id": format!("gts.x.test.stream.order.v1~x.test._.{name}.v1"),
+                "email": "ann@example.com"
+            })
+        };
+        let dir = TempDir::new().unwrap();
+        let lines = [order("one"), order("two")]
+            .iter()
+            .map(|doc| cipher.encrypt(doc).unwrap().to_string() + "\n")
+            .collect::<String>();
+        fs::write(dir.path().join("orders.ndjson"), lines).unwrap();
+        let array = json!([cipher.encrypt(&order("three")).unwrap()]);
+        fs::write(dir.path().join("orders.json"), array.to_string()).unwrap();
+
+        let paths = [dir.path().to_string_lossy().to_string()];
+        let mut reader = GtsStreamReader::new(&paths, None).with_cipher(cipher);
+        let mut contents: Vec<Value> = reader.iter().map(|entity| entity.content).collect();
+        contents.sort_by_key(|content| content["id"].to_string());
+        assert_eq!(contents, [order("one"), order("three"), order("two")]);
+
+        // With another key the sealed instances are skipped
+        let other = GtsContentCipher::new(
+            Arc::new(GtsEnvKeyProvider::new("GTS_TEST_STREAM_UNSET_KEY")),
+            &GtsConfig::default(),
+        );
+        let mut locked = GtsStreamReader::new(&paths, None).with_cipher(other);
+        assert_eq!(locked.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_consumers_can_stop_early() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("many.jsonl");
+        let mut lines = String::new();
+        for i in 0..1000 {
+            let _ = writeln!(
+                lines,
+                "{{\"id\": \"gts.x.test.stream.item.v1~x.test._.n{i}.v1\"}}"
+            );
+        }
+        fs::write(&path, lines).unwrap();
+        let mut reader = GtsStreamReader::new(&[path.to_string_lossy().to_string()], None);
+        let first: Vec<_> = reader.iter().take(2).collect();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[1].list_sequence, Some(1));
+        assert_eq!(reader.iter().count(), 1000);
+    }
+}
@@ -0,0 +1,347 @@
+//! Terraform export of infrastructure declared through schema traits.
+//!
+//! A schema whose effective traits (see [`GtsStore::resolve_traits`]) carry a
+//! `topicRef` declares a Kafka topic.  [`GtsStore::export_terraform`] collects
+//! those topics for every schema matching a pattern and renders them as
+//! Terraform JSON `kafka_topic` resources (the `Mongey/kafka` provider), so
+//! they can be applied through an ordinary infrastructure-as-code pipeline.
+//!
+//! Recognised traits:
+//!
+//! - `topicRef` — topic name (required);
+//! - `partitions`, `replicationFactor` — integers, default 1;
+//! - `retention` — ISO 8601 duration (`P30D`, `PT12H`) or milliseconds,
+//!   exported as `retention.ms`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::gts::{GtsID, GtsWildcard};
+use crate::schema_traits::GtsTraitVars;
+use crate::store::{GtsStore, StoreError};
+
+pub const TOPIC_TRAIT: &str = "topicRef";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsKafkaTopic {
+    pub name: String,
+    pub partitions: u64,
+    pub replication_factor: u64,
+    pub retention_ms: Option<u64>,
+    /// Schemas declaring the topic, sorted.
+    pub schema_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsTerraformExport {
+    pub pattern: String,
+    /// Topics ordered by name.
+    pub topics: Vec<GtsKafkaTopic>,
+    /// Schemas whose traits could not be exported, with the reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// Milliseconds in an ISO 8601 duration made of weeks, days, hours, minutes
+/// and seconds. Years and months are rejected as ambiguous.
+fn duration_millis(duration: &str) -> Option<u64> {
+    let rest = duration.strip_prefix('P')?;
+    let (date, time) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut total = 0u64;
+    for (part, units) in [
+        (date, &[('W', 604_800_000u64), ('D', 86_400_000)][..]),
+        (time, &[('H', 3_600_000), ('M', 60_000), ('S', 1_000)][..]),
+    ] {
+        let mut number = String::new();
+        let mut unit_idx = 0;
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            // Units must appear in order, each at most once
+            let offset = units[unit_idx..].iter().position(|(u, _)| *u == c)?;
+            unit_idx += offset;
+            total =
+                total.checked_add(number.parse::<u64>().ok()?.checked_mul(units[unit_idx].1)?)?;
+            unit_idx += 1;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    (rest != "T" && !rest.is_empty()).then_some(total)
+}
+
+fn positive_int(traits: &Value, key: &str) -> Result<u64, String> {
+    match traits.get(key) {
+        None => Ok(1),
+        Some(value) => value
+            .as_u64()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("'{key}' must be a positive integer, got {value}")),
+    }
+}
+
+fn topic_from_traits(schema_id: &str, traits: &Value) -> Result<Option<GtsKafkaTopic>, String> {
+    let Some(name) = traits.get(TOPIC_TRAIT) else {
+        return Ok(None);
+    };
+    let name = name
+        .as_str()
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| format!("'{TOPIC_TRAIT}' must be a non-empty string"))?;
+    let retention_ms = match traits.get("retention") {
+        None => None,
+        Some(Value::Number(n)) => Some(
+            n.as_u64()
+                .ok_or_else(|| format!("'retention' must be a non-negative integer, got {n}"))?,
+        ),
+        Some(Value::String(s)) => Some(
+            duration_millis(s)
+                .ok_or_else(|| format!("'retention' is not a supported duration: '{s}'"))?,
+        ),
+        Some(other) => return Err(format!("'retention' must be a duration, got {other}")),
+    };
+    Ok(Some(GtsKafkaTopic {
+        name: name.to_owned(),
+        partitions: positive_int(traits, "partitions")?,
+        replication_factor: positive_int(traits, "replicationFactor")?,
+        retention_ms,
+        schema_ids: vec![schema_id.to_owned()],
+    }))
+}
+
+/// A Terraform resource name for `topic`: letters, digits and underscores,
+/// not starting with a digit.
+fn resource_name(topic: &str) -> String {
+    let name: String = topic
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("topic_{name}")
+    } else {
+        name
+    }
+}
+
+impl GtsTerraformExport {
+    /// Renders the topics as a Terraform JSON configuration
+    /// (`*.tf.json`).
+    #[must_use]
+    pub fn to_terraform_json(&self) -> Value {
+        let mut resources = Map::new();
+        for topic in &self.topics {
+            let mut resource = json!({
+                "//": format!("Declared by {}", topic.schema_ids.join(", ")),
+                "name": topic.name,
+                "partitions": topic.partitions,
+                "replication_factor": topic.replication_factor,
+            });
+            if let Some(ms) = topic.retention_ms {
+                resource["config"] = json!({"retention.ms": ms.to_string()});
+            }
+            resources.insert(resource_name(&topic.name), resource);
+        }
+        json!({"resource": {"kafka_topic": resources}})
+    }
+}
+
+impl GtsStore {
+    /// Collects the Kafka topics declared by the traits of every schema
+    /// matching `pattern`, substituting trait placeholders from `vars`.
+    ///
+    /// Schemas without a `topicRef` trait value in their chain are skipped.
+    /// Schemas whose traits cannot be resolved, or that declare a topic
+    /// already declared with different settings, are reported in
+    /// `problems` and left out.
+    ///
+    /// # Errors
+    /// Returns `StoreError::ValidationError` if `pattern` is not a valid
+    /// GTS wildcard.
+    pub fn export_terraform(
+        &mut self,
+        pattern: &str,
+        vars: &GtsTraitVars,
+    ) -> Result<GtsTerraformExport, StoreError> {
+        let wildcard = GtsWildcard::new(pattern)
+            .map_err(|e| StoreError::ValidationError(format!("Invalid pattern: {e}")))?;
+        let mut schema_ids: Vec<String> = self
+            .items()
+            .filter(|(id, e)| {
+                e.is_schema && GtsID::new(id).is_ok_and(|gid| gid.wildcard_match(&wildcard))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        schema_ids.sort();
+
+        let mut topics: Vec<GtsKafkaTopic> = Vec::new();
+        let mut problems = Vec::new();
+        for schema_id in schema_ids {
+            // Abstract schemas leave the topic to their descendants
+            let declares_topic = self
+                .collect_chain_traits(&schema_id)
+                .is_ok_and(|(_, traits)| traits.get(TOPIC_TRAIT).is_some());
+            if !declares_topic {
+                continue;
+            }
+            let topic = self
+                .resolve_traits(&schema_id, vars)
+                .map_err(|e| e.to_string())
+                .and_then(|traits| topic_from_traits(&schema_id, &traits));
+            match topic {
+                Ok(Some(topic)) => match topics.iter_mut().find(|t| t.name == topic.name) {
+                    Some(existing)
+                        if (
+                            existing.partitions,
+                            existing.replication_factor,
+                            existing.retention_ms,
+                        ) == (
+                            topic.partitions,
+                            topic.replication_factor,
+                            topic.retention_ms,
+                        ) =>
+                    {
+                        existing.schema_ids.push(schema_id);
+                    }
+                    Some(existing) => problems.push(format!(
+                        "{schema_id}: topic '{}' is declared with different settings by {}",
+                        topic.name,
+                        existing.schema_ids.join(", ")
+                    )),
+                    None => topics.push(topic),
+                },
+                Ok(None) => {}
+                Err(e) => problems.push(format!("{schema_id}: {e}")),
+            }
+        }
+        topics.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(GtsTerraformExport {
+            pattern: pattern.to_owned(),
+            topics,
+            problems,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "gts.x.test.iac.topic.v1~";
+
+    fn derived(name: &str) -> String {
+        format!("{BASE}x.test.iac.{name}.v1~")
+    }
+
+    fn store(leaves: &[(&str, Value)]) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                BASE,
+                &json!({
+                    "$id": format!("gts://{BASE}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "x-gts-traits-schema": {
+                        "type": "object",
+                        "properties": {
+                            "topicRef": {"type": "string"},
+                            "partitions": {"type": "integer", "default": 3},
+                            "retention": {"type": "string", "default": "P7D"}
+                        }
+                    }
+                }),
+            )
+            .unwrap();
+        for (name, traits) in leaves {
+            let id = derived(name);
+            store
+                .register_schema(
+                    &id,
+                    &json!({
+                        "$id": format!("gts://{id}"),
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "allOf": [{"$ref": format!("gts://{BASE}")}],
+                        "x-gts-traits": traits
+                    }),
+                )
+                .unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_duration_millis() {
+        assert_eq!(duration_millis("P30D"), Some(2_592_000_000));
+        assert_eq!(duration_millis("P1W2D"), Some(777_600_000));
+        assert_eq!(duration_millis("PT1H30M"), Some(5_400_000));
+        assert_eq!(duration_millis("P1DT12H"), Some(129_600_000));
+        for bad in ["30D", "P", "PT", "P1M", "P1D2W", "P1", "PT1D"] {
+            assert_eq!(duration_millis(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_export_topics_across_pattern() {
+        let mut store = store(&[
+            (
+                "orders",
+                json!({"topicRef": "${env}.orders", "retention": "P30D"}),
+            ),
+            (
+                "refunds",
+                json!({"topicRef": "${env}.refunds", "partitions": 6}),
+            ),
+            (
+                "audit",
+                json!({"topicRef": "${env}.orders", "retention": "P30D"}),
+            ),
+        ]);
+        let vars = GtsTraitVars::new().with_var("env", "prod");
+        let export = store.export_terraform(&format!("{BASE}*"), &vars).unwrap();
+        assert!(export.problems.is_empty(), "{:?}", export.problems);
+        assert_eq!(export.topics.len(), 2);
+        assert_eq!(
+            export.topics[0].schema_ids,
+            vec![derived("audit"), derived("orders")]
+        );
+
+        let tf = export.to_terraform_json();
+        let orders = &tf["resource"]["kafka_topic"]["prod_orders"];
+        assert_eq!(orders["name"], "prod.orders");
+        assert_eq!(orders["partitions"], 3);
+        assert_eq!(orders["config"]["retention.ms"], "2592000000");
+        let refunds = &tf["resource"]["kafka_topic"]["prod_refunds"];
+        assert_eq!(refunds["partitions"], 6);
+        assert_eq!(refunds["config"]["retention.ms"], "604800000");
+    }
+
+    #[test]
+    fn test_export_reports_problems() {
+        let mut store = store(&[
+            ("orders", json!({"topicRef": "orders"})),
+            ("clash", json!({"topicRef": "orders", "partitions": 12})),
+            ("unset", json!({"topicRef": "${env}.x"})),
+            ("bad", json!({"topicRef": "bad", "retention": "P1M"})),
+        ]);
+        let export = store
+            .export_terraform(&format!("{BASE}*"), &GtsTraitVars::new())
+            .unwrap();
+        assert_eq!(export.topics.len(), 1);
+        assert_eq!(export.problems.len(), 3, "{:?}", export.problems);
+        assert!(export.problems[0].contains("not a supported duration"));
+        assert!(export.problems[1].contains("different settings"));
+        assert!(export.problems[2].contains("not resolved"));
+
+        assert!(
+            store
+                .export_terraform("not a pattern", &GtsTraitVars::new())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Fixtures shared by the unit tests.
+
+use serde_json::{Value, json};
+
+/// A draft-07 object schema with ID `id`, extended (or overridden) by the
+/// keys of `body`.
+pub fn schema(id: &str, body: &Value) -> Value {
+    let mut schema = json!({
+        "$id": format!("gts://{id}"),
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object"
+    });
+    if let (Some(schema), Some(body)) = (schema.as_object_mut(), body.as_object()) {
+        schema.extend(body.clone());
+    }
+    schema
+}
@@ -0,0 +1,310 @@
+//! All-or-nothing batches of registrations.
+//!
+//! [`GtsStore::transaction`] runs a closure against a [`GtsTransaction`].
+//! Registrations apply to the store immediately, so later calls in the batch
+//! (and reads through [`GtsTransaction::store`]) see earlier ones.  When the
+//! closure returns, every `gts://` reference of the schemas registered in the
+//! batch and the schema of every registered instance must exist; otherwise,
+//! or if the closure fails, the store is put back as it was.  Change
+//! listeners hear of the registrations only once the transaction commits.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::entities::GtsEntity;
+use crate::store::{GtsStore, StoreError};
+use crate::validator_cache::schema_dependencies;
+
+/// Registration handle passed to the [`GtsStore::transaction`] closure.
+pub struct GtsTransaction<'a> {
+    store: &'a mut GtsStore,
+    /// Entities replaced by the transaction, by ID; `None` for new IDs.
+    previous: HashMap<String, Option<GtsEntity>>,
+    /// IDs registered, in order of first registration.
+    registered: Vec<String>,
+}
+
+impl GtsTransaction<'_> {
+    /// Registers an entity; see [`GtsStore::register`].
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID.
+    pub fn register(&mut self, entity: GtsEntity) -> Result<(), StoreError> {
+        self.register_as(None, entity)
+    }
+
+    /// Registers an entity on behalf of `principal`; see
+    /// [`GtsStore::register_as`].
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidEntity` if the entity has no effective ID,
+    /// or `StoreError::RegistrationDenied` if the policy provider refuses.
+    pub fn register_as(
+        &mut self,
+        principal: Option<&str>,
+        entity: GtsEntity,
+    ) -> Result<(), StoreError> {
+        let id = entity.effective_id().ok_or(StoreError::InvalidEntity)?;
+        self.remember(&id);
+        self.store.register_as(principal, entity)
+    }
+
+    /// Registers a schema; see [`GtsStore::register_schema`].
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSchemaId` if the `type_id` is not a valid
+    /// schema ID.
+    pub fn register_schema(&mut self, type_id: &str, schema: &Value) -> Result<(), StoreError> {
+        self.register_schema_as(None, type_id, schema)
+    }
+
+    /// Registers a schema on behalf of `principal`; see
+    /// [`GtsStore::register_schema_as`].
+    ///
+    /// # Errors
+    /// Returns `StoreError::InvalidSchemaId` if the `type_id` is not a valid
+    /// schema ID, or `StoreError::RegistrationDenied` if the policy provider
+    /// refuses.
+    pub fn register_schema_as(
+        &mut self,
+        principal: Option<&str>,
+        type_id: &str,
+        schema: &Value,
+    ) -> Result<(), StoreError> {
+        self.remember(type_id);
+        self.store.register_schema_as(principal, type_id, schema)
+    }
+
+    /// The store, including this transaction's registrations so far.
+    #[must_use]
+    pub fn store(&self) -> &GtsStore {
+        self.store
+    }
+
+    fn remember(&mut self, id: &str) {
+        if !self.previous.contains_key(id) {
+            let entity = self.store.entities().get(id).cloned();
+            self.previous.insert(id.to_owned(), entity);
+            self.registered.push(id.to_owned());
+        }
+    }
+
+    /// References from the registered entities that the store cannot satisfy.
+    fn unresolved(&self) -> Vec<String> {
+        let entities = self.store.entities();
+        let mut problems = Vec::new();
+        for id in &self.registered {
+            let Some(entity) = entities.get(id) else {
+                continue;
+            };
+            if entity.is_schema {
+                let mut missing: Vec<String> = schema_dependencies(entities, id, &entity.content)
+                    .into_iter()
+                    .filter(|dep| !entities.contains_key(dep))
+                    .collect();
+                missing.sort_unstable();
+                problems.extend(
+                    missing
+                        .into_iter()
+                        .map(|dep| format!("schema '{id}' references missing '{dep}'")),
+                );
+            } else if let Some(schema_id) = &entity.schema_id
+                && !entities.contains_key(schema_id)
+            {
+                problems.push(format!("instance '{id}' has missing schema '{schema_id}'"));
+            }
+        }
+        problems
+    }
+
+    fn rollback(self) {
+        for (id, entity) in self.previous {
+            match entity {
+                Some(entity) => self.store.insert_entity(id, entity),
+                None => {
+                    self.store.remove_entity(&id);
+                }
+            }
+        }
+    }
+}
+
+impl GtsStore {
+    /// Runs `f` as one transaction: if it fails, or leaves references
+    /// unresolved, every registration it made is rolled back.
+    ///
+    /// # Errors
+    /// Returns the closure's error, or `StoreError::UnresolvedReferences`
+    /// listing the missing schemas.
+    pub fn transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut GtsTransaction<'_>) -> Result<R, StoreError>,
+    ) -> Result<R, StoreError> {
+        let holding = self.change_feed_mut().hold();
+        let mut tx = GtsTransaction {
+            store: self,
+            previous: HashMap::new(),
+            registered: Vec::new(),
+        };
+        let result = f(&mut tx).and_then(|value| {
+            let problems = tx.unresolved();
+            if problems.is_empty() {
+                Ok(value)
+            } else {
+                Err(StoreError::UnresolvedReferences(problems))
+            }
+        });
+        if result.is_err() {
+            tracing::info!(
+                "Rolling back transaction of {} registrations",
+                tx.registered.len()
+            );
+            tx.rollback();
+        }
+        if holding {
+            let changes = self.change_feed_mut();
+            if result.is_ok() {
+                changes.release();
+            } else {
+                changes.discard();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::changes::{GtsChangeEvent, GtsChangeKind, GtsChangeListener};
+    use crate::entities::GtsConfig;
+    use crate::test_fixtures::schema;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    const BASE: &str = "gts.x.test.tx.base.v1~";
+    const DERIVED: &str = "gts.x.test.tx.base.v1~x.test.tx.derived.v1~";
+
+    fn base() -> Value {
+        schema(BASE, &json!({"type": "object"}))
+    }
+
+    fn derived() -> Value {
+        schema(
+            DERIVED,
+            &json!({"allOf": [{"$ref": format!("gts://{BASE}")}, {"type": "object"}]}),
+        )
+    }
+
+    fn instance(name: &str) -> GtsEntity {
+        GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{DERIVED}x.test._.{name}.v1")}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(DERIVED.to_owned()),
+        )
+    }
+
+    #[test]
+    fn test_commit_interdependent_set() {
+        let mut store = GtsStore::new(None);
+        let count = store
+            .transaction(|tx| {
+                // Order does not matter within a transaction
+                tx.register(instance("a"))?;
+                tx.register_schema(DERIVED, &derived())?;
+                tx.register_schema(BASE, &base())?;
+                Ok(tx.store().items().count())
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(
+            store
+                .validate_instance(&format!("{DERIVED}x.test._.a.v1"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_missing_reference_rolls_back() {
+        let mut store = GtsStore::new(None);
+        let err = store
+            .transaction(|tx| {
+                tx.register_schema(DERIVED, &derived())?;
+                tx.register(instance("a"))
+            })
+            .unwrap_err();
+        match err {
+            StoreError::UnresolvedReferences(problems) => {
+                assert_eq!(
+                    problems,
+                    vec![format!("schema '{DERIVED}' references missing '{BASE}'")]
+                );
+            }
+            other => panic!("expected unresolved references, got {other:?}"),
+        }
+        assert_eq!(store.items().count(), 0);
+    }
+
+    #[test]
+    fn test_failed_closure_restores_replaced_entities() {
+        let mut store = GtsStore::new(None);
+        store.register_schema(BASE, &base()).unwrap();
+
+        let closed = schema(
+            BASE,
+            &json!({"type": "object", "additionalProperties": false}),
+        );
+        let err = store
+            .transaction(|tx| {
+                tx.register_schema(BASE, &closed)?;
+                tx.register_schema("not-a-schema", &closed)
+            })
+            .unwrap_err();
+        assert!(matches!(err, StoreError::InvalidSchemaId));
+        assert_eq!(store.get(BASE).unwrap().content, base());
+        assert_eq!(store.items().count(), 1);
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(GtsChangeKind, String)>>);
+
+    impl GtsChangeListener for Recorder {
+        fn on_change(&self, event: &GtsChangeEvent) {
+            self.0.lock().unwrap().push((event.kind, event.id.clone()));
+        }
+    }
+
+    #[test]
+    fn test_listeners_hear_only_committed_changes() {
+        let mut store = GtsStore::new(None);
+        let recorder = Arc::new(Recorder::default());
+        store.add_change_listener(recorder.clone());
+
+        store
+            .transaction(|tx| tx.register_schema(DERIVED, &derived()))
+            .unwrap_err();
+        assert!(recorder.0.lock().unwrap().is_empty());
+
+        store
+            .transaction(|tx| {
+                tx.register_schema(BASE, &base())?;
+                tx.register_schema(DERIVED, &derived())
+            })
+            .unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                (GtsChangeKind::Registered, BASE.to_owned()),
+                (GtsChangeKind::Registered, DERIVED.to_owned()),
+            ]
+        );
+    }
+}
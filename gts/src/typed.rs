@@ -0,0 +1,268 @@
+//! Typed access to store entities.
+//!
+//! [`GtsStore::get_as`] validates an entity and deserializes it into an
+//! application type in one step, so callers never act on content that has
+//! not passed its schema.  [`GtsStore::register_typed`] is the inverse: it
+//! validates a Rust value against its type's GTS schema and registers it.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::entities::{GtsConfig, GtsEntity};
+use crate::schema::GtsSchema;
+use crate::store::{GtsStore, StoreError};
+
+#[derive(Debug, Error)]
+pub enum GtsTypedError {
+    #[error("Entity '{0}' not found in store")]
+    NotFound(String),
+    #[error("Entity '{id}' failed validation: {source}")]
+    Validation {
+        id: String,
+        #[source]
+        source: StoreError,
+    },
+    #[error("Entity '{id}' does not deserialize into the requested type: {source}")]
+    Deserialize {
+        id: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Value of type '{schema_id}' does not serialize to JSON: {source}")]
+    Serialize {
+        schema_id: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Value of type '{schema_id}' could not be registered: {source}")]
+    Register {
+        schema_id: String,
+        #[source]
+        source: StoreError,
+    },
+}
+
+impl GtsStore {
+    /// Validates the entity `id` (an instance against its schema, a schema
+    /// against the meta-schema) and deserializes its content into `T`.
+    ///
+    /// # Errors
+    /// Returns `GtsTypedError::NotFound` if the entity is not in the store,
+    /// `GtsTypedError::Validation` if it fails validation, and
+    /// `GtsTypedError::Deserialize` if its content does not fit `T`.
+    pub fn get_as<T: DeserializeOwned>(&mut self, id: &str) -> Result<T, GtsTypedError> {
+        let entity = self
+            .get(id)
+            .ok_or_else(|| GtsTypedError::NotFound(id.to_owned()))?;
+        let is_schema = entity.is_schema;
+        let content = entity.content.clone();
+
+        let validated = if is_schema {
+            self.validate_schema(id)
+        } else {
+            self.validate_instance(id)
+        };
+        validated.map_err(|source| GtsTypedError::Validation {
+            id: id.to_owned(),
+            source,
+        })?;
+
+        serde_json::from_value(content).map_err(|source| GtsTypedError::Deserialize {
+            id: id.to_owned(),
+            source,
+        })
+    }
+
+    /// Serializes `value`, validates it against `T`'s GTS schema and registers
+    /// it as an instance of that schema. Returns the instance's ID.
+    ///
+    /// If the schema is not in the store yet, `T`'s own schema is registered
+    /// first. Ancestor schemas its `$ref`s point at, and the innermost schema
+    /// of a generic wrapper such as `BaseEventV1<AuditPayloadV1<..>>`, must
+    /// already be registered.
+    ///
+    /// # Errors
+    /// Returns `GtsTypedError::Serialize` if `value` does not serialize,
+    /// `GtsTypedError::Validation` if it fails its schema, and
+    /// `GtsTypedError::Register` if the schema is missing or invalid, or the
+    /// value carries no ID.
+    pub fn register_typed<T: Serialize + GtsSchema>(
+        &mut self,
+        value: &T,
+    ) -> Result<String, GtsTypedError> {
+        let schema_id = T::innermost_schema_id();
+        let register_error = |source| GtsTypedError::Register {
+            schema_id: schema_id.to_owned(),
+            source,
+        };
+        let content = serde_json::to_value(value).map_err(|source| GtsTypedError::Serialize {
+            schema_id: schema_id.to_owned(),
+            source,
+        })?;
+
+        if !self.entities().contains_key(T::SCHEMA_ID) && !T::SCHEMA_ID.is_empty() {
+            tracing::info!("Registering schema {} for typed value", T::SCHEMA_ID);
+            self.register_schema(T::SCHEMA_ID, &T::gts_schema_with_refs_allof())
+                .map_err(register_error)?;
+        }
+        if !self.entities().contains_key(schema_id) {
+            return Err(register_error(StoreError::SchemaNotFound(
+                schema_id.to_owned(),
+            )));
+        }
+
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        );
+        let id = entity
+            .effective_id()
+            .ok_or_else(|| register_error(StoreError::InvalidEntity))?;
+        self.validate_value(schema_id, &content)
+            .map_err(|source| GtsTypedError::Validation {
+                id: id.clone(),
+                source,
+            })?;
+        self.register(entity).map_err(register_error)?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+
+    const NOTE: &str = "gts.x.test.typed.note.v1~";
+
+    #[derive(Serialize)]
+    struct Note {
+        id: String,
+        text: String,
+    }
+
+    impl GtsSchema for Note {
+        const SCHEMA_ID: &'static str = NOTE;
+
+        fn gts_schema_with_refs() -> Value {
+            json!({
+                "$id": format!("gts://{NOTE}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {"text": {"type": "string", "minLength": 1}},
+                "required": ["text"]
+            })
+        }
+    }
+
+    const ORDER: &str = "gts.x.test.typed.order.v1~";
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        id: String,
+        total: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Invoice {
+        number: String,
+    }
+
+    fn store_with(orders: &[Value]) -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ORDER,
+                &json!({
+                    "$id": format!("gts://{ORDER}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"total": {"type": "integer", "minimum": 0}},
+                    "required": ["total"]
+                }),
+            )
+            .unwrap();
+        for content in orders {
+            let entity = GtsEntity::new(
+                None,
+                None,
+                content,
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(ORDER.to_owned()),
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_get_as_validates_then_deserializes() {
+        let ok = format!("{ORDER}x.test._.ok.v1");
+        let bad = format!("{ORDER}x.test._.bad.v1");
+        let mut store = store_with(&[
+            json!({"id": ok, "total": 5}),
+            json!({"id": bad, "total": -1}),
+        ]);
+
+        let order: Order = store.get_as(&ok).unwrap();
+        assert_eq!(
+            order,
+            Order {
+                id: ok.clone(),
+                total: 5
+            }
+        );
+
+        assert!(matches!(
+            store.get_as::<Order>(&bad),
+            Err(GtsTypedError::Validation { .. })
+        ));
+        assert!(matches!(
+            store.get_as::<Invoice>(&ok),
+            Err(GtsTypedError::Deserialize { .. })
+        ));
+        let err = store
+            .get_as::<Order>("gts.x.test.typed.order.v1~x.test._.none.v1")
+            .unwrap_err();
+        assert!(matches!(err, GtsTypedError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_register_typed_registers_schema_and_validates() {
+        let mut store = GtsStore::new(None);
+        let id = format!("{NOTE}x.test._.n1.v1");
+        let note = Note {
+            id: id.clone(),
+            text: "hello".to_owned(),
+        };
+        assert_eq!(store.register_typed(&note).unwrap(), id);
+        assert!(store.get(NOTE).unwrap().is_schema);
+        assert_eq!(store.get(&id).unwrap().schema_id.as_deref(), Some(NOTE));
+
+        let empty = Note {
+            id: format!("{NOTE}x.test._.n2.v1"),
+            text: String::new(),
+        };
+        assert!(matches!(
+            store.register_typed(&empty),
+            Err(GtsTypedError::Validation { .. })
+        ));
+        assert!(store.get(&empty.id).is_none());
+    }
+}
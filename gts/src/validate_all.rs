@@ -0,0 +1,438 @@
+//! Whole-store instance validation.
+//!
+//! [`GtsStore::validate_all`] checks every registered instance against its
+//! schema, compiling each schema once.  With the `parallel` feature,
+//! [`GtsStore::validate_all_parallel`] and [`GtsStore::query_parallel`]
+//! spread the per-entity work across a rayon thread pool sized by
+//! [`GtsStore::set_parallel_threads`]; results are identical to the
+//! sequential versions.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use web_time::Instant;
+
+use crate::entities::GtsEntity;
+use crate::numbers::GtsNumberPolicy;
+use crate::ref_integrity::GtsRefIntegrity;
+use crate::store::GtsStore;
+#[cfg(feature = "parallel")]
+use crate::store::{GtsStoreQueryResult, QueryMatcher};
+use crate::validator_cache::GtsCompiledSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidationFailure {
+    pub id: String,
+    pub schema_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidateAllReport {
+    /// Number of instances checked.
+    pub checked: usize,
+    pub valid: usize,
+    /// Failed instances, ordered by ID.
+    pub failures: Vec<GtsValidationFailure>,
+}
+
+impl GtsValidateAllReport {
+    #[must_use]
+    pub fn ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// An instance to validate: its store key and schema ID.
+struct Job {
+    id: String,
+    schema_id: String,
+}
+
+/// Compiled schemas by ID, or why a schema cannot be used.
+type PreparedSchemas = HashMap<String, Result<Arc<GtsCompiledSchema>, String>>;
+
+fn check_job(
+    entities: &HashMap<String, GtsEntity>,
+    schemas: &PreparedSchemas,
+    number_policy: GtsNumberPolicy,
+    job: &Job,
+) -> Option<GtsValidationFailure> {
+    let error = match (&schemas[&job.schema_id], entities.get(&job.id)) {
+        (Ok(compiled), Some(entity)) => {
+            GtsStore::check_compiled(compiled, &entity.content, number_policy)
+                .err()?
+                .to_string()
+        }
+        (Err(error), _) => error.clone(),
+        (Ok(_), None) => return None,
+    };
+    Some(GtsValidationFailure {
+        id: job.id.clone(),
+        schema_id: job.schema_id.clone(),
+        error,
+    })
+}
+
+impl GtsStore {
+    /// Instances with a schema ID, ordered by ID.
+    fn validation_jobs(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .entities()
+            .iter()
+            .filter(|(_, entity)| !entity.is_schema)
+            .filter_map(|(id, entity)| {
+                Some(Job {
+                    id: id.clone(),
+                    schema_id: entity.schema_id.clone()?,
+                })
+            })
+            .collect();
+        jobs.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        jobs
+    }
+
+    /// Compiles every schema the jobs need, once each.
+    fn prepare_schemas(&mut self, jobs: &[Job]) -> PreparedSchemas {
+        let mut schemas = PreparedSchemas::new();
+        for job in jobs {
+            if schemas.contains_key(&job.schema_id) {
+                continue;
+            }
+            let instance = self
+                .entities()
+                .get(&job.id)
+                .map(|entity| entity.content.clone())
+                .unwrap_or_default();
+            let prepared = self
+                .prepare_schema(&job.schema_id, &instance)
+                .map_err(|e| e.to_string());
+            schemas.insert(job.schema_id.clone(), prepared);
+        }
+        schemas
+    }
+
+    /// Jobs without a failure whose `x-gts-ref` targets fail the store's
+    /// [`GtsRefIntegrity`] check.  Runs sequentially, as targets may have to
+    /// be fetched through the reader.
+    fn integrity_failures(
+        &mut self,
+        schemas: &PreparedSchemas,
+        jobs: &[Job],
+        failed: &[GtsValidationFailure],
+    ) -> Vec<GtsValidationFailure> {
+        let failed: HashSet<&str> = failed.iter().map(|f| f.id.as_str()).collect();
+        let mut failures = Vec::new();
+        for job in jobs.iter().filter(|job| !failed.contains(job.id.as_str())) {
+            let (Some(Ok(compiled)), Some(entity)) =
+                (schemas.get(&job.schema_id), self.entities().get(&job.id))
+            else {
+                continue;
+            };
+            let content = entity.content.clone();
+            if let Err(e) = self.check_ref_integrity(&compiled.schema, &content) {
+                failures.push(GtsValidationFailure {
+                    id: job.id.clone(),
+                    schema_id: job.schema_id.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+        failures
+    }
+
+    fn validate_all_with(
+        &mut self,
+        check: impl FnOnce(
+            &HashMap<String, GtsEntity>,
+            &PreparedSchemas,
+            GtsNumberPolicy,
+            &[Job],
+        ) -> Vec<GtsValidationFailure>,
+    ) -> GtsValidateAllReport {
+        let jobs = self.validation_jobs();
+        let schemas = self.prepare_schemas(&jobs);
+        tracing::info!(
+            "Validating {} instances against {} schemas",
+            jobs.len(),
+            schemas.len()
+        );
+        let started = Instant::now();
+        let mut failures = check(self.entities(), &schemas, self.number_policy(), &jobs);
+        if self.ref_integrity() != GtsRefIntegrity::Off {
+            let integrity = self.integrity_failures(&schemas, &jobs, &failures);
+            failures.extend(integrity);
+            failures.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        }
+        self.record_validations(&jobs, &failures, started.elapsed());
+        GtsValidateAllReport {
+            checked: jobs.len(),
+            valid: jobs.len() - failures.len(),
+            failures,
+        }
+    }
+
+    /// Counts each job in the store metrics, spreading `elapsed` evenly.
+    fn record_validations(
+        &mut self,
+        jobs: &[Job],
+        failures: &[GtsValidationFailure],
+        elapsed: Duration,
+    ) {
+        let failed: HashSet<&str> = failures.iter().map(|f| f.id.as_str()).collect();
+        let each = elapsed / u32::try_from(jobs.len().max(1)).unwrap_or(u32::MAX);
+        let counters = self.metric_counters_mut();
+        for job in jobs {
+            counters.record_validation(&job.schema_id, !failed.contains(job.id.as_str()), each);
+        }
+    }
+
+    /// Validates every registered instance against its schema.
+    ///
+    /// Schemas are not checked; use [`GtsStore::validate_schema`] for those.
+    /// Instances loaded lazily through the reader are only covered once they
+    /// have been fetched.
+    pub fn validate_all(&mut self) -> GtsValidateAllReport {
+        self.validate_all_with_progress(|_, _| {})
+    }
+
+    /// Like [`GtsStore::validate_all`], calling `on_checked(done, total)`
+    /// after each instance.
+    pub fn validate_all_with_progress(
+        &mut self,
+        mut on_checked: impl FnMut(usize, usize),
+    ) -> GtsValidateAllReport {
+        self.validate_all_with(|entities, schemas, policy, jobs| {
+            jobs.iter()
+                .enumerate()
+                .filter_map(|(i, job)| {
+                    let failure = check_job(entities, schemas, policy, job);
+                    on_checked(i + 1, jobs.len());
+                    failure
+                })
+                .collect()
+        })
+    }
+}
+
+/// Runs `op` on a pool of `threads` threads, or rayon's global pool for `0`.
+#[cfg(feature = "parallel")]
+fn in_pool<R: Send>(threads: usize, op: impl FnOnce() -> R + Send) -> R {
+    if threads == 0 {
+        return op();
+    }
+    match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(op),
+        Err(e) => {
+            tracing::warn!("Cannot build a {threads}-thread pool, using the global one: {e}");
+            op()
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl GtsStore {
+    /// Sets the number of threads used by the parallel methods; `0` (the
+    /// default) uses rayon's global pool.
+    pub fn set_parallel_threads(&mut self, threads: usize) {
+        self.parallel_threads = threads;
+    }
+
+    #[must_use]
+    pub fn parallel_threads(&self) -> usize {
+        self.parallel_threads
+    }
+
+    /// Like [`GtsStore::validate_all`], but checks instances in parallel.
+    /// Schemas are still compiled sequentially, once each.
+    pub fn validate_all_parallel(&mut self) -> GtsValidateAllReport {
+        use rayon::prelude::*;
+
+        let threads = self.parallel_threads;
+        self.validate_all_with(|entities, schemas, policy, jobs| {
+            in_pool(threads, || {
+                jobs.par_iter()
+                    .filter_map(|job| check_job(entities, schemas, policy, job))
+                    .collect()
+            })
+        })
+    }
+
+    /// Like [`GtsStore::query`], but matches entities in parallel.
+    ///
+    /// Returns the same results in the same order; unlike `query` it matches
+    /// every candidate before applying `limit`.
+    #[must_use]
+    pub fn query_parallel(&self, expr: &str, limit: usize) -> GtsStoreQueryResult {
+        use rayon::prelude::*;
+
+        let mut result = GtsStoreQueryResult {
+            error: String::new(),
+            count: 0,
+            limit,
+            results: Vec::new(),
+        };
+        match QueryMatcher::parse(expr) {
+            Ok(matcher) => {
+                let candidates: Vec<&GtsEntity> = self
+                    .query_candidates(&matcher)
+                    .map(|(_, entity)| entity)
+                    .collect();
+                let mut found: Vec<&GtsEntity> = in_pool(self.parallel_threads, || {
+                    candidates
+                        .into_par_iter()
+                        .filter(|entity| matcher.matches(entity))
+                        .collect()
+                });
+                found.truncate(limit);
+                result.results = found.into_iter().map(|e| e.content.clone()).collect();
+            }
+            Err(error) => result.error = error,
+        }
+        result.count = result.results.len();
+        result
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use crate::test_fixtures::schema;
+    use serde_json::json;
+
+    const ORDER: &str = "gts.x.test.bulk.order.v1~";
+    const RETIRED: &str = "gts.x.test.bulk.legacy.v1~";
+
+    fn instance(schema_id: &str, name: &str, qty: i64) -> GtsEntity {
+        let id = format!("{schema_id}x.test._.{name}.v1");
+        GtsEntity::new(
+            None,
+            None,
+            &json!({"id": id, "qty": qty}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(schema_id.to_owned()),
+        )
+    }
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                ORDER,
+                &schema(
+                    ORDER,
+                    &json!({"properties": {"qty": {"type": "integer", "minimum": 1}}}),
+                ),
+            )
+            .unwrap();
+        store
+            .register_schema(
+                RETIRED,
+                &schema(
+                    RETIRED,
+                    &json!({
+                        "properties": {"qty": {"type": "integer", "minimum": 1}},
+                        "x-gts-lifecycle": {"state": "retired"}
+                    }),
+                ),
+            )
+            .unwrap();
+        for i in 0..40 {
+            store
+                .register(instance(ORDER, &format!("o{i:02}"), i % 10))
+                .unwrap();
+        }
+        store.register(instance(RETIRED, "old", 1)).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_validate_all_reports_failures_in_id_order() {
+        let mut store = store();
+        let report = store.validate_all();
+        assert_eq!(report.checked, 41);
+        assert_eq!(report.valid, 36);
+        assert!(!report.ok());
+
+        let ids: Vec<&str> = report.failures.iter().map(|f| f.id.as_str()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        assert_eq!(ids, sorted);
+        assert!(
+            report
+                .failures
+                .iter()
+                .any(|f| f.schema_id == RETIRED && f.error.contains("retired"))
+        );
+        // Each schema is compiled once for the whole run
+        assert_eq!(store.validator_cache_stats().misses, 1);
+    }
+
+    #[test]
+    fn test_validate_all_checks_ref_integrity() {
+        let mut store = store();
+        let owned = format!("{ORDER}x.test._.owned.v1");
+        store
+            .register_schema(
+                ORDER,
+                &schema(
+                    ORDER,
+                    &json!({"properties": {"owner": {"type": "string", "x-gts-ref": ORDER}}}),
+                ),
+            )
+            .unwrap();
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": owned, "owner": format!("{ORDER}x.test._.gone.v1")}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(ORDER.to_owned()),
+        );
+        store.register(entity).unwrap();
+        assert!(!store.validate_all().failures.iter().any(|f| f.id == owned));
+
+        store.set_ref_integrity(GtsRefIntegrity::Exists);
+        let report = store.validate_all();
+        let failure = report.failures.iter().find(|f| f.id == owned).unwrap();
+        assert!(failure.error.starts_with("Broken references: /owner: "));
+        assert_eq!(report.valid, report.checked - report.failures.len());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let mut store = store();
+        store.set_parallel_threads(4);
+        assert_eq!(store.parallel_threads(), 4);
+
+        let sequential = store.validate_all();
+        let parallel = store.validate_all_parallel();
+        assert_eq!(
+            serde_json::to_value(&sequential).unwrap(),
+            serde_json::to_value(&parallel).unwrap()
+        );
+
+        for expr in [
+            format!("{ORDER}*"),
+            format!("{ORDER}*[qty=3]"),
+            "bad".to_owned(),
+        ] {
+            let expected = store.query(&expr, 100);
+            let actual = store.query_parallel(&expr, 100);
+            assert_eq!(actual.results, expected.results);
+            assert_eq!(actual.error, expected.error);
+        }
+        assert_eq!(store.query_parallel(&format!("{ORDER}*"), 5).count, 5);
+    }
+}
@@ -0,0 +1,302 @@
+//! Structured validation results.
+//!
+//! [`GtsStore::validate_instance`] reports failures as one message string.
+//! [`GtsStore::validate_instance_report`] runs the same checks but returns a
+//! [`GtsValidationReport`] with one [`GtsValidationIssue`] per error: where in
+//! the instance it is (a JSON pointer), which schema keyword rejected it, and
+//! where that keyword is in the resolved schema.  Unlike `validate_instance`,
+//! which stops at the first failing stage, the report lists JSON Schema,
+//! number-policy, `x-gts-ref` and referential integrity errors together.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::numbers::{self, GtsNumberPolicy};
+use crate::store::{GtsStore, StoreError};
+use crate::validator_cache::GtsCompiledSchema;
+use crate::x_gts_ref::{XGtsRefValidationError, XGtsRefValidator};
+
+/// Keyword reported for `x-gts-ref` errors.
+const X_GTS_REF_KEYWORD: &str = "x-gts-ref";
+
+/// One validation error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtsValidationIssue {
+    /// JSON pointer of the rejected value in the instance; empty for the root.
+    pub instance_path: String,
+    /// JSON pointer of the failing keyword in the `$ref`-resolved schema,
+    /// when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_path: Option<String>,
+    /// The keyword that rejected the value, e.g. `required` or `x-gts-ref`.
+    pub keyword: String,
+    pub message: String,
+}
+
+impl fmt::Display for GtsValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = if self.instance_path.is_empty() {
+            "/"
+        } else {
+            self.instance_path.as_str()
+        };
+        write!(f, "{at}: {} ({})", self.message, self.keyword)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidationReport {
+    /// The schema the content was validated against.
+    pub schema_id: String,
+    pub errors: Vec<GtsValidationIssue>,
+}
+
+impl GtsValidationReport {
+    #[must_use]
+    pub fn ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for GtsValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ok() {
+            return write!(f, "valid against {}", self.schema_id);
+        }
+        write!(f, "invalid against {}", self.schema_id)?;
+        for issue in &self.errors {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `outer.items[1].id` → `/outer/items/1/id`.
+fn dotted_to_pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        let (name, indices) = part.split_once('[').unwrap_or((part, ""));
+        if !name.is_empty() {
+            pointer.push('/');
+            pointer.push_str(&name.replace('~', "~0").replace('/', "~1"));
+        }
+        for index in indices.split('[') {
+            if let Some(index) = index.strip_suffix(']') {
+                pointer.push('/');
+                pointer.push_str(index);
+            }
+        }
+    }
+    pointer
+}
+
+fn x_gts_ref_issue(err: &XGtsRefValidationError) -> GtsValidationIssue {
+    GtsValidationIssue {
+        instance_path: dotted_to_pointer(&err.field_path),
+        schema_path: None,
+        keyword: X_GTS_REF_KEYWORD.to_owned(),
+        message: err.reason.clone(),
+    }
+}
+
+/// Every error `content` has against `compiled`, in the order JSON Schema,
+/// number policy, `x-gts-ref`.
+pub(crate) fn collect_issues(
+    compiled: &GtsCompiledSchema,
+    content: &Value,
+    number_policy: GtsNumberPolicy,
+) -> Vec<GtsValidationIssue> {
+    let mut issues: Vec<GtsValidationIssue> = compiled
+        .validator
+        .iter_errors(content)
+        .map(|err| {
+            let schema_path = err.schema_path().as_str().to_owned();
+            let keyword = schema_path
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .replace("~1", "/")
+                .replace("~0", "~");
+            GtsValidationIssue {
+                instance_path: err.instance_path().as_str().to_owned(),
+                schema_path: Some(schema_path),
+                keyword,
+                message: err.to_string(),
+            }
+        })
+        .collect();
+
+    issues.extend(
+        numbers::check_numbers(content, &compiled.resolved, number_policy)
+            .into_iter()
+            .map(|e| GtsValidationIssue {
+                instance_path: e.path,
+                schema_path: None,
+                keyword: e.keyword,
+                message: e.reason,
+            }),
+    );
+
+    issues.extend(
+        XGtsRefValidator::new()
+            .validate_instance(content, &compiled.schema, "")
+            .iter()
+            .map(x_gts_ref_issue),
+    );
+    issues
+}
+
+impl GtsStore {
+    /// Validates instance `gts_id` like [`GtsStore::validate_instance`], but
+    /// returns every error as a [`GtsValidationIssue`] instead of failing.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the instance or its schema is missing, the
+    /// schema is retired or cannot be compiled, or validation would exceed
+    /// the sandbox budget.
+    pub fn validate_instance_report(
+        &mut self,
+        gts_id: &str,
+    ) -> Result<GtsValidationReport, StoreError> {
+        let (content, schema_id, _) = self.instance_schema(gts_id)?;
+        self.validate_value_report(&schema_id, &content)
+    }
+
+    /// Validates arbitrary JSON content against a registered schema like
+    /// [`GtsStore::validate_value`], returning every error.
+    ///
+    /// # Errors
+    /// Returns `StoreError` if the schema is missing, retired or cannot be
+    /// compiled, or validation would exceed the sandbox budget.
+    pub fn validate_value_report(
+        &mut self,
+        schema_id: &str,
+        content: &Value,
+    ) -> Result<GtsValidationReport, StoreError> {
+        let compiled = self.prepare_schema(schema_id, content)?;
+        Self::check_step_budget(&compiled, content)?;
+        let mut errors = collect_issues(&compiled, content, self.number_policy());
+        errors.extend(
+            self.broken_refs(&compiled.schema, content)
+                .iter()
+                .map(|broken| GtsValidationIssue {
+                    instance_path: broken.path.clone(),
+                    schema_path: None,
+                    keyword: X_GTS_REF_KEYWORD.to_owned(),
+                    message: broken.to_string(),
+                }),
+        );
+        Ok(GtsValidationReport {
+            schema_id: schema_id.to_owned(),
+            errors,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    const SCHEMA: &str = "gts.x.test.report.order.v1~";
+
+    #[test]
+    fn test_dotted_to_pointer() {
+        assert_eq!(dotted_to_pointer(""), "");
+        assert_eq!(dotted_to_pointer("owner"), "/owner");
+        assert_eq!(dotted_to_pointer("outer.items[1].id"), "/outer/items/1/id");
+        assert_eq!(dotted_to_pointer("matrix[0][2]"), "/matrix/0/2");
+    }
+
+    #[test]
+    fn test_validate_instance_report_lists_every_error() {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                SCHEMA,
+                &json!({
+                    "$id": format!("gts://{SCHEMA}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "required": ["id", "total"],
+                    "properties": {
+                        "id": {"type": "string"},
+                        "lines": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {"qty": {"type": "integer", "minimum": 1}}
+                            }
+                        },
+                        "owner": {"type": "string", "x-gts-ref": "gts.x.test.report.user.v1~"}
+                    }
+                }),
+            )
+            .unwrap();
+        let content = json!({
+            "id": format!("{SCHEMA}x.test._.o1.v1"),
+            "lines": [{"qty": 2}, {"qty": 0}],
+            "owner": "gts.x.test.report.team.v1~x.test._.t1.v1"
+        });
+        let entity = GtsEntity::new(
+            None,
+            None,
+            &content,
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(SCHEMA.to_owned()),
+        );
+        let id = entity.gts_id.as_ref().unwrap().id.clone();
+        store.register(entity).unwrap();
+
+        let report = store.validate_instance_report(&id).unwrap();
+        assert!(!report.ok());
+        assert_eq!(report.schema_id, SCHEMA);
+
+        let keywords: Vec<&str> = report.errors.iter().map(|e| e.keyword.as_str()).collect();
+        assert_eq!(keywords, ["required", "minimum", "x-gts-ref"]);
+
+        assert_eq!(report.errors[0].instance_path, "");
+        let minimum = &report.errors[1];
+        assert_eq!(minimum.instance_path, "/lines/1/qty");
+        assert_eq!(
+            minimum.schema_path.as_deref(),
+            Some("/properties/lines/items/properties/qty/minimum")
+        );
+        assert_eq!(report.errors[2].instance_path, "/owner");
+        assert_eq!(report.errors[2].schema_path, None);
+        assert!(report.to_string().contains("/lines/1/qty: "));
+
+        // The legacy string error is unchanged.
+        let err = store.validate_instance(&id).unwrap_err().to_string();
+        assert!(err.contains("Validation failed: "));
+    }
+
+    #[test]
+    fn test_validate_value_report_ok() {
+        let mut store = GtsStore::new(None);
+        store
+            .register_schema(
+                SCHEMA,
+                &json!({
+                    "$id": format!("gts://{SCHEMA}"),
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object"
+                }),
+            )
+            .unwrap();
+        let report = store.validate_value_report(SCHEMA, &json!({})).unwrap();
+        assert!(report.ok());
+        assert!(matches!(
+            store.validate_value_report("gts.x.test.report.missing.v1~", &json!({})),
+            Err(StoreError::SchemaNotFound(_))
+        ));
+    }
+}
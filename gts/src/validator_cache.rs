@@ -0,0 +1,221 @@
+//! Compiled-validator cache for [`GtsStore::validate_instance`].
+//!
+//! Compiling a schema (resolving its `$ref` chain and building the
+//! `jsonschema` validator) costs far more than validating one instance, so
+//! compiled validators are kept per schema ID.  Each entry remembers every
+//! `gts://` schema its chain references; registering, replacing or removing
+//! any of them evicts the entry.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::entities::GtsEntity;
+use crate::references::ref_targets;
+use crate::store::GtsStore;
+
+/// A schema compiled for instance validation.
+pub(crate) struct GtsCompiledSchema {
+    pub(crate) validator: jsonschema::Validator,
+    /// The `$ref`-resolved schema the validator was built from.
+    pub(crate) resolved: Value,
+    /// The schema as registered, for `x-gts-ref` checks.
+    pub(crate) schema: Value,
+    /// Keywords in `resolved` and the step budget per validation, when
+    /// sandbox limits are set.
+    pub(crate) step_budget: Option<(u64, u64)>,
+}
+
+struct CachedSchema {
+    compiled: Arc<GtsCompiledSchema>,
+    /// Schema IDs the entry was compiled from, including its own.
+    deps: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GtsValidatorCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct GtsValidatorCache {
+    entries: HashMap<String, CachedSchema>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl std::fmt::Debug for GtsValidatorCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GtsValidatorCache")
+            .field("entries", &self.entries.len())
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GtsValidatorCache {
+    pub(crate) fn get(&mut self, schema_id: &str) -> Option<Arc<GtsCompiledSchema>> {
+        let compiled = self.entries.get(schema_id).map(|e| Arc::clone(&e.compiled));
+        if compiled.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        compiled
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        schema_id: &str,
+        compiled: Arc<GtsCompiledSchema>,
+        deps: HashSet<String>,
+    ) {
+        self.entries
+            .insert(schema_id.to_owned(), CachedSchema { compiled, deps });
+    }
+
+    /// Evicts every entry compiled from `schema_id`.
+    pub(crate) fn invalidate(&mut self, schema_id: &str) {
+        let before = self.entries.len();
+        self.entries.retain(|_, e| !e.deps.contains(schema_id));
+        self.evictions += (before - self.entries.len()) as u64;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.evictions += self.entries.len() as u64;
+        self.entries.clear();
+    }
+}
+
+/// IDs of all schemas reachable from `schema_id` through `gts://` references,
+/// including `schema_id` itself and references that are not registered yet.
+pub(crate) fn schema_dependencies(
+    entities: &HashMap<String, GtsEntity>,
+    schema_id: &str,
+    schema: &Value,
+) -> HashSet<String> {
+    let mut deps = HashSet::from([schema_id.to_owned()]);
+    let mut pending = ref_targets(schema);
+    while let Some(id) = pending.pop() {
+        if deps.insert(id.clone())
+            && let Some(entity) = entities.get(&id)
+        {
+            pending.extend(ref_targets(&entity.content));
+        }
+    }
+    deps
+}
+
+impl GtsStore {
+    #[must_use]
+    pub fn validator_cache_stats(&self) -> GtsValidatorCacheStats {
+        let cache = self.validator_cache();
+        GtsValidatorCacheStats {
+            entries: cache.entries.len(),
+            hits: cache.hits,
+            misses: cache.misses,
+            evictions: cache.evictions,
+        }
+    }
+
+    /// Drops all compiled validators; they are recompiled on next use.
+    pub fn clear_validator_cache(&mut self) {
+        self.validator_cache_mut().clear();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::GtsConfig;
+    use crate::test_fixtures::schema;
+    use serde_json::json;
+
+    const BASE: &str = "gts.x.test.cache.base.v1~";
+    const DERIVED: &str = "gts.x.test.cache.base.v1~x.test.cache.derived.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        let base = schema(
+            BASE,
+            &json!({"type": "object", "properties": {"n": {"type": "integer", "maximum": 10}}}),
+        );
+        let derived = schema(
+            DERIVED,
+            &json!({"allOf": [{"$ref": format!("gts://{BASE}")}, {"type": "object"}]}),
+        );
+        store.register_schema(BASE, &base).unwrap();
+        store.register_schema(DERIVED, &derived).unwrap();
+        for (name, n) in [("a", 1), ("b", 20)] {
+            let id = format!("{DERIVED}x.test._.{name}.v1");
+            let entity = GtsEntity::new(
+                None,
+                None,
+                &json!({"id": id, "n": n}),
+                Some(&GtsConfig::default()),
+                None,
+                false,
+                String::new(),
+                None,
+                Some(DERIVED.to_owned()),
+            );
+            store.register(entity).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_schema_dependencies_follow_ref_chain() {
+        let store = store();
+        let derived = &store.entities()[DERIVED].content;
+        let deps = schema_dependencies(store.entities(), DERIVED, derived);
+        assert_eq!(deps, HashSet::from([DERIVED.to_owned(), BASE.to_owned()]));
+    }
+
+    #[test]
+    fn test_validator_is_compiled_once_per_schema() {
+        let mut store = store();
+        let a = format!("{DERIVED}x.test._.a.v1");
+        let b = format!("{DERIVED}x.test._.b.v1");
+        for _ in 0..3 {
+            assert!(store.validate_instance(&a).is_ok());
+        }
+        assert!(store.validate_instance(&b).is_err());
+
+        let stats = store.validator_cache_stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 3);
+    }
+
+    #[test]
+    fn test_reregistering_base_schema_invalidates_dependents() {
+        let mut store = store();
+        let b = format!("{DERIVED}x.test._.b.v1");
+        assert!(store.validate_instance(&b).is_err());
+
+        // Relax the base schema: the cached derived validator must not survive
+        let relaxed = schema(
+            BASE,
+            &json!({"type": "object", "properties": {"n": {"type": "integer", "maximum": 100}}}),
+        );
+        store.register_schema(BASE, &relaxed).unwrap();
+        assert_eq!(store.validator_cache_stats().entries, 0);
+        assert_eq!(store.validator_cache_stats().evictions, 1);
+        assert!(store.validate_instance(&b).is_ok());
+
+        // Registering instances leaves compiled schemas alone
+        store.clear_validator_cache();
+        assert!(store.validate_instance(&b).is_ok());
+        let entity = store.get(&b).cloned().unwrap();
+        store.register(entity).unwrap();
+        assert_eq!(store.validator_cache_stats().entries, 1);
+    }
+}
@@ -0,0 +1,195 @@
+//! Schema visibility (`x-gts-visibility`).
+//!
+//! A schema annotated with `"x-gts-visibility": "internal"` (or `partner`)
+//! is only shown to callers with that clearance or higher, together with its
+//! derived schemas and all their instances.  Unannotated schemas are public.
+//! The store itself does not know who is asking: a server sets the caller's
+//! clearance as the store's audience for the duration of a request, and
+//! queries and listings then leave out what the caller may not see.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::store::GtsStore;
+
+/// Schema keyword holding the visibility.
+pub const VISIBILITY_KEY: &str = "x-gts-visibility";
+
+/// Who may see a schema, and the clearance of a caller; each level sees
+/// its own and the lower levels.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum GtsVisibility {
+    #[default]
+    Public,
+    Partner,
+    Internal,
+}
+
+impl GtsVisibility {
+    /// The visibility a schema declares; `None` if it declares none or an
+    /// unknown one.
+    #[must_use]
+    pub fn of(schema: &Value) -> Option<Self> {
+        schema
+            .get(VISIBILITY_KEY)
+            .and_then(Value::as_str)
+            .and_then(|v| v.parse().ok())
+    }
+
+    /// Whether a caller with this clearance may see an entity with
+    /// `visibility`.
+    #[must_use]
+    pub fn can_see(self, visibility: GtsVisibility) -> bool {
+        visibility <= self
+    }
+}
+
+impl FromStr for GtsVisibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "public" => Ok(GtsVisibility::Public),
+            "partner" => Ok(GtsVisibility::Partner),
+            "internal" => Ok(GtsVisibility::Internal),
+            other => Err(format!(
+                "unknown visibility '{other}'; expected public, partner or internal"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for GtsVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GtsVisibility::Public => "public",
+            GtsVisibility::Partner => "partner",
+            GtsVisibility::Internal => "internal",
+        })
+    }
+}
+
+impl GtsStore {
+    /// Restricts queries and listings to what a caller with `audience`
+    /// clearance may see; `None` shows everything.
+    pub fn set_audience(&mut self, audience: Option<GtsVisibility>) {
+        self.audience = audience;
+    }
+
+    #[must_use]
+    pub fn audience(&self) -> Option<GtsVisibility> {
+        self.audience
+    }
+
+    /// The effective visibility of an entity: the most restrictive one
+    /// declared by its schema or the schemas that schema derives from.
+    /// Unknown entities and entities without a schema are public.
+    #[must_use]
+    pub fn visibility_of(&self, id: &str) -> GtsVisibility {
+        let entities = self.entities();
+        let Some(entity) = entities.get(id) else {
+            return GtsVisibility::Public;
+        };
+        let schema_id = if entity.is_schema {
+            id
+        } else {
+            match entity.schema_id.as_deref() {
+                Some(schema_id) => schema_id,
+                None => return GtsVisibility::Public,
+            }
+        };
+        schema_id
+            .match_indices('~')
+            .filter_map(|(end, _)| entities.get(&schema_id[..=end]))
+            .filter_map(|schema| GtsVisibility::of(&schema.content))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Whether the current audience may see the entity `id`.
+    #[must_use]
+    pub fn is_visible(&self, id: &str) -> bool {
+        self.audience
+            .is_none_or(|audience| audience.can_see(self.visibility_of(id)))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::entities::{GtsConfig, GtsEntity};
+    use serde_json::json;
+
+    const PUBLIC: &str = "gts.x.test.vis.event.v1~";
+    const INTERNAL: &str = "gts.x.test.vis.event.v1~x.test.vis.audit.v1~";
+    const PARTNER: &str = "gts.x.test.vis.order.v1~";
+
+    fn store() -> GtsStore {
+        let mut store = GtsStore::new(None);
+        for (id, visibility) in [
+            (PUBLIC, None),
+            (INTERNAL, Some("internal")),
+            (PARTNER, Some("partner")),
+        ] {
+            let mut schema = json!({
+                "$id": format!("gts://{id}"),
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object"
+            });
+            if let Some(visibility) = visibility {
+                schema[VISIBILITY_KEY] = json!(visibility);
+            }
+            store.register_schema(id, &schema).unwrap();
+        }
+        let instance = GtsEntity::new(
+            None,
+            None,
+            &json!({"id": format!("{INTERNAL}x.test._.a1.v1")}),
+            Some(&GtsConfig::default()),
+            None,
+            false,
+            String::new(),
+            None,
+            Some(INTERNAL.to_owned()),
+        );
+        store.register(instance).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_visibility_is_inherited_by_instances() {
+        let store = store();
+        assert_eq!(store.visibility_of(PUBLIC), GtsVisibility::Public);
+        assert_eq!(store.visibility_of(PARTNER), GtsVisibility::Partner);
+        assert_eq!(
+            store.visibility_of(&format!("{INTERNAL}x.test._.a1.v1")),
+            GtsVisibility::Internal
+        );
+        assert_eq!("Partner".parse(), Ok(GtsVisibility::Partner));
+        assert!("secret".parse::<GtsVisibility>().is_err());
+    }
+
+    #[test]
+    fn test_audience_filters_queries() {
+        let mut store = store();
+        let count = |store: &GtsStore| store.query("gts.x.test.vis.*", 100).count;
+        assert_eq!(count(&store), 4);
+
+        store.set_audience(Some(GtsVisibility::Public));
+        assert_eq!(count(&store), 1);
+        assert!(!store.is_visible(INTERNAL));
+
+        store.set_audience(Some(GtsVisibility::Partner));
+        assert_eq!(count(&store), 2);
+
+        store.set_audience(Some(GtsVisibility::Internal));
+        assert_eq!(count(&store), 4);
+    }
+}
@@ -0,0 +1,330 @@
+//! File watching for hot-reloading a [`GtsStore`] from disk.
+//!
+//! [`GtsFileWatcher`] watches directories recursively and turns file-system
+//! notifications into [`GtsWatchEvent`]s on a channel.  Each event already
+//! carries the parsed entities of the changed file, so the consumer only has
+//! to hand it to [`GtsStore::apply_watch_event`] — typically while holding
+//! whatever lock guards its store.
+//!
+//! Files the initial scan skips are skipped here too: those in excluded
+//! directories such as `node_modules`, and those a watched root's
+//! `.gtsignore` ignores.  The `.gtsignore` files are read once, when the
+//! watcher starts.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use thiserror::Error;
+use web_time::SystemTime;
+
+use crate::entities::{GtsConfig, GtsEntity};
+use crate::files_reader::GtsFileReader;
+use crate::gtsignore::GtsIgnore;
+use crate::store::GtsStore;
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("File watcher error: {0}")]
+    Notify(#[from] notify::Error),
+}
+
+/// A change to a GTS file on disk.
+#[derive(Debug, Clone)]
+pub enum GtsWatchEvent {
+    /// A file was created or modified; `entities` is its full new content.
+    FileChanged {
+        path: PathBuf,
+        entities: Vec<GtsEntity>,
+    },
+    /// A file was deleted or moved away.
+    FileRemoved { path: PathBuf },
+    /// The underlying watcher reported an error.
+    Error(String),
+}
+
+/// Effect of a watch event on the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GtsStoreChange {
+    Upserted(String),
+    Removed(String),
+}
+
+/// Watches GTS files and emits [`GtsWatchEvent`]s until dropped.
+pub struct GtsFileWatcher {
+    // Kept alive for as long as events should be delivered.
+    _watcher: RecommendedWatcher,
+    events: Receiver<GtsWatchEvent>,
+}
+
+impl GtsFileWatcher {
+    /// Starts watching `paths` recursively.
+    ///
+    /// # Errors
+    /// Returns `WatchError` if the watcher cannot be created or a path cannot be watched.
+    pub fn new(paths: &[String], cfg: Option<GtsConfig>) -> Result<Self, WatchError> {
+        let (tx, events) = mpsc::channel();
+        let reader = GtsFileReader::new(&[], cfg);
+        let roots: Vec<WatchedRoot> = paths
+            .iter()
+            .map(|path| {
+                let path = PathBuf::from(shellexpand::tilde(path).to_string());
+                let path = path.canonicalize().unwrap_or(path);
+                let ignore = GtsIgnore::load(&path);
+                WatchedRoot { path, ignore }
+            })
+            .collect();
+        let root_paths: Vec<PathBuf> = roots.iter().map(|root| root.path.clone()).collect();
+        let mut watcher =
+            notify::recommended_watcher(move |res| forward(res, &reader, &roots, &tx))?;
+
+        for path in root_paths {
+            watcher.watch(&path, RecursiveMode::Recursive)?;
+            tracing::info!("Watching {} for GTS file changes", path.display());
+        }
+
+        Ok(GtsFileWatcher {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Channel of file change events.
+    #[must_use]
+    pub fn events(&self) -> &Receiver<GtsWatchEvent> {
+        &self.events
+    }
+}
+
+/// A watched directory and the `.gtsignore` at its top.
+struct WatchedRoot {
+    path: PathBuf,
+    ignore: GtsIgnore,
+}
+
+/// Whether the initial scan of `roots` would skip `path`.
+fn is_excluded(roots: &[WatchedRoot], path: &Path) -> bool {
+    roots.iter().any(|root| {
+        path.strip_prefix(&root.path)
+            .is_ok_and(|relative| GtsFileReader::is_excluded(&root.ignore, relative, false))
+    })
+}
+
+fn forward(
+    res: notify::Result<notify::Event>,
+    reader: &GtsFileReader,
+    roots: &[WatchedRoot],
+    tx: &Sender<GtsWatchEvent>,
+) {
+    let event = match res {
+        Ok(event) => event,
+        Err(e) => {
+            let _ = tx.send(GtsWatchEvent::Error(e.to_string()));
+            return;
+        }
+    };
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return;
+    }
+    for path in event.paths {
+        if !GtsFileReader::is_gts_file(&path) || is_excluded(roots, &path) {
+            continue;
+        }
+        let change = if path.is_file() {
+            GtsWatchEvent::FileChanged {
+                entities: reader.process_file(&path),
+                path,
+            }
+        } else {
+            GtsWatchEvent::FileRemoved { path }
+        };
+        tracing::debug!("Watch event: {change:?}");
+        // A closed channel just means nobody is listening any more.
+        let _ = tx.send(change);
+    }
+}
+
+fn is_from_file(entity: &GtsEntity, path: &Path) -> bool {
+    entity
+        .file
+        .as_ref()
+        .is_some_and(|f| Path::new(&f.path) == path)
+}
+
+impl GtsStore {
+    /// Applies a file watch event: entities of a changed file are upserted and
+    /// entities that disappeared from it (or from a removed file) are dropped.
+    pub fn apply_watch_event(&mut self, event: GtsWatchEvent) -> Vec<GtsStoreChange> {
+        let (path, entities) = match event {
+            GtsWatchEvent::FileChanged { path, entities } => (path, entities),
+            GtsWatchEvent::FileRemoved { path } => (path, Vec::new()),
+            GtsWatchEvent::Error(e) => {
+                tracing::warn!("File watcher error: {e}");
+                return Vec::new();
+            }
+        };
+
+        let new_ids: HashSet<String> = entities
+            .iter()
+            .filter_map(GtsEntity::effective_id)
+            .collect();
+        let stale: Vec<String> = self
+            .items()
+            .filter(|(id, entity)| is_from_file(entity, &path) && !new_ids.contains(*id))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut changes = Vec::new();
+        for id in stale {
+            self.remove_entity(&id);
+            changes.push(GtsStoreChange::Removed(id));
+        }
+        for entity in entities {
+            if let Some(id) = entity.effective_id() {
+                self.insert_entity(id.clone(), entity);
+                changes.push(GtsStoreChange::Upserted(id));
+            }
+        }
+        self.loaded_at = Some(SystemTime::now());
+        changes
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    const SCHEMA_ID: &str = "gts.x.test.watch.item.v1~";
+
+    fn schema_json(description: &str) -> String {
+        format!(
+            r#"{{"$id": "gts://{SCHEMA_ID}", "$schema": "http://json-schema.org/draft-07/schema#", "type": "object", "description": "{description}"}}"#
+        )
+    }
+
+    /// Applies events until `done` holds for the store or the timeout expires.
+    fn pump_until(
+        watcher: &GtsFileWatcher,
+        store: &mut GtsStore,
+        done: impl Fn(&mut GtsStore) -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Ok(event) = watcher.events().recv_timeout(Duration::from_millis(100)) {
+                store.apply_watch_event(event);
+            }
+            if done(store) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_watch_add_modify_remove() {
+        let dir = TempDir::new().unwrap();
+        let watcher =
+            GtsFileWatcher::new(&[dir.path().to_string_lossy().to_string()], None).unwrap();
+        let mut store = GtsStore::new(None);
+        let file = dir.path().join("schema.json");
+
+        fs::write(&file, schema_json("first")).unwrap();
+        assert!(pump_until(&watcher, &mut store, |s| s
+            .get(SCHEMA_ID)
+            .is_some()));
+
+        fs::write(&file, schema_json("second")).unwrap();
+        assert!(pump_until(&watcher, &mut store, |s| {
+            s.get(SCHEMA_ID).is_some_and(|e| e.description == "second")
+        }));
+
+        fs::remove_file(&file).unwrap();
+        assert!(pump_until(&watcher, &mut store, |s| s
+            .get(SCHEMA_ID)
+            .is_none()));
+    }
+
+    #[test]
+    fn test_watch_ignores_non_gts_files() {
+        let dir = TempDir::new().unwrap();
+        let watcher =
+            GtsFileWatcher::new(&[dir.path().to_string_lossy().to_string()], None).unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+
+        let event = watcher.events().recv_timeout(Duration::from_millis(500));
+        assert!(event.is_err(), "unexpected event: {event:?}");
+    }
+
+    #[test]
+    fn test_watch_ignores_excluded_directories() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("node_modules")).unwrap();
+        fs::create_dir(dir.path().join("generated")).unwrap();
+        fs::write(dir.path().join(".gtsignore"), "generated/\n").unwrap();
+        let watcher =
+            GtsFileWatcher::new(&[dir.path().to_string_lossy().to_string()], None).unwrap();
+        fs::write(
+            dir.path().join("node_modules").join("schema.json"),
+            schema_json("vendored"),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("generated").join("schema.json"),
+            schema_json("generated"),
+        )
+        .unwrap();
+
+        let event = watcher.events().recv_timeout(Duration::from_millis(500));
+        assert!(event.is_err(), "unexpected event: {event:?}");
+    }
+
+    #[test]
+    fn test_apply_watch_event_drops_entities_missing_from_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("many.json");
+        fs::write(
+            &path,
+            format!(
+                "[{}, {}]",
+                schema_json("a"),
+                schema_json("b").replace("item.v1~", "other.v1~")
+            ),
+        )
+        .unwrap();
+        let reader = GtsFileReader::new(&[], None);
+        let mut store = GtsStore::new(None);
+
+        let changes = store.apply_watch_event(GtsWatchEvent::FileChanged {
+            entities: reader.process_file(&path),
+            path: path.clone(),
+        });
+        assert_eq!(changes.len(), 2);
+
+        fs::write(&path, schema_json("a")).unwrap();
+        let changes = store.apply_watch_event(GtsWatchEvent::FileChanged {
+            entities: reader.process_file(&path),
+            path: path.clone(),
+        });
+        assert!(changes.contains(&GtsStoreChange::Removed(
+            "gts.x.test.watch.other.v1~".to_owned()
+        )));
+        assert!(store.get(SCHEMA_ID).is_some());
+
+        let changes = store.apply_watch_event(GtsWatchEvent::FileRemoved { path });
+        assert_eq!(changes, vec![GtsStoreChange::Removed(SCHEMA_ID.to_owned())]);
+        assert_eq!(store.items().count(), 0);
+    }
+
+    #[test]
+    fn test_watch_missing_path_errors() {
+        let result = GtsFileWatcher::new(&["/nonexistent/gts/watch/path".to_owned()], None);
+        assert!(result.is_err());
+    }
+}
@@ -15,6 +15,8 @@
 /// 2. **Instance Validation**: Validates that instance values match their `x-gts-ref` constraints
 /// 3. **JSON Pointer Resolution**: Supports JSON Pointer references (e.g., `/$id`, `/properties/name`)
 /// 4. **GTS ID Pattern Matching**: Validates GTS IDs and prefix patterns (e.g., `gts.x.y._.z.v1~`)
+/// 5. **Collections**: Arrays of references, via `x-gts-ref` on `items` (or `prefixItems`,
+///    `additionalProperties`) or directly on the array schema
 ///
 /// # Examples
 ///
@@ -69,6 +71,7 @@
 /// The `x-gts-ref` field can contain:
 ///
 /// - **GTS ID Pattern**: A full or prefix GTS identifier (e.g., `gts.x.y._.z.v1~`)
+/// - **GTS Wildcard**: A prefix ending in `*` (e.g., `gts.x.core.events.topic.v1~*`)
 /// - **JSON Pointer**: A reference to another field in the schema (e.g., `/$id`, `/properties/name`)
 ///
 /// ## JSON Pointer Resolution
@@ -89,14 +92,31 @@
 /// ```
 ///
 /// In this case, the `type` field must match the schema's `$id` value.
+///
+/// ## Cardinality
+///
+/// A reference field holding `null` has no reference and passes; make it
+/// mandatory with `required` and a non-nullable `type`, optional with
+/// `"type": ["string", "null"]` or by leaving it out of `required`.  A list of
+/// references is an array whose `items` carry the `x-gts-ref`:
+///
+/// ```json
+/// {
+///   "type": "array",
+///   "minItems": 1,
+///   "items": {"type": "string", "x-gts-ref": "gts.x.core.events.topic.v1~*"}
+/// }
+/// ```
 use serde_json::Value;
 use std::fmt;
 
+use crate::diagnostic::{GtsCode, GtsDiagnostic};
 use crate::gts::GtsID;
 
 /// Error type for x-gts-ref validation failures
 #[derive(Debug, Clone)]
 pub struct XGtsRefValidationError {
+    pub code: GtsCode,
     pub field_path: String,
     pub value: String,
     pub ref_pattern: String,
@@ -105,8 +125,15 @@ pub struct XGtsRefValidationError {
 
 impl XGtsRefValidationError {
     #[must_use]
-    pub fn new(field_path: String, value: String, ref_pattern: String, reason: String) -> Self {
+    pub fn new(
+        code: GtsCode,
+        field_path: String,
+        value: String,
+        ref_pattern: String,
+        reason: String,
+    ) -> Self {
         Self {
+            code,
             field_path,
             value,
             ref_pattern,
@@ -115,6 +142,13 @@ impl XGtsRefValidationError {
     }
 }
 
+impl XGtsRefValidationError {
+    #[must_use]
+    pub fn diagnostic(&self) -> GtsDiagnostic {
+        GtsDiagnostic::new(self.code, self.to_string())
+    }
+}
+
 impl fmt::Display for XGtsRefValidationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -174,13 +208,28 @@ impl XGtsRefValidator {
             return;
         };
 
-        // Check for x-gts-ref constraint
-        if let Some(x_gts_ref) = sch_obj.get("x-gts-ref")
-            && let Some(inst_str) = inst.as_str()
-            && let Some(ref_pattern) = x_gts_ref.as_str()
-            && let Some(error) = self.validate_ref_value(inst_str, ref_pattern, path, root_schema)
-        {
-            errors.push(error);
+        // Check for x-gts-ref constraint. `null` is "no reference": whether a
+        // reference may be left out is up to `type` and `required`.
+        if let Some(ref_pattern) = sch_obj.get("x-gts-ref").and_then(Value::as_str) {
+            match inst {
+                Value::String(value) => {
+                    errors.extend(self.validate_ref_value(value, ref_pattern, path, root_schema));
+                }
+                // On an array schema the constraint applies to every element
+                Value::Array(items) => {
+                    for (idx, item) in items.iter().enumerate() {
+                        if let Some(value) = item.as_str() {
+                            errors.extend(self.validate_ref_value(
+                                value,
+                                ref_pattern,
+                                &format!("{path}[{idx}]"),
+                                root_schema,
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
         }
 
         // Handle oneOf combinator: exactly one branch must match (zero errors)
@@ -195,6 +244,7 @@ impl XGtsRefValidator {
             }
             if matching_count == 0 {
                 errors.push(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefBranch,
                     path.to_owned(),
                     inst.to_string(),
                     String::new(),
@@ -202,6 +252,7 @@ impl XGtsRefValidator {
                 ));
             } else if matching_count > 1 {
                 errors.push(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefBranch,
                     path.to_owned(),
                     inst.to_string(),
                     String::new(),
@@ -219,6 +270,7 @@ impl XGtsRefValidator {
             });
             if !any_match {
                 errors.push(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefBranch,
                     path.to_owned(),
                     inst.to_string(),
                     String::new(),
@@ -234,39 +286,45 @@ impl XGtsRefValidator {
             }
         }
 
-        // Recurse into object properties
-        if let Some(Value::String(type_str)) = sch_obj.get("type") {
-            if type_str == "object" {
-                if let Some(properties) = sch_obj.get("properties")
-                    && let Some(properties_obj) = properties.as_object()
-                    && let Some(inst_obj) = inst.as_object()
-                {
-                    for (prop_name, prop_schema) in properties_obj {
-                        if let Some(prop_value) = inst_obj.get(prop_name) {
-                            let prop_path = if path.is_empty() {
-                                prop_name.clone()
-                            } else {
-                                format!("{path}.{prop_name}")
-                            };
-                            self.visit_instance(
-                                prop_value,
-                                prop_schema,
-                                root_schema,
-                                &prop_path,
-                                errors,
-                            );
-                        }
-                    }
+        match inst {
+            Value::Object(inst_obj) => {
+                let properties = sch_obj.get("properties").and_then(Value::as_object);
+                let additional = sch_obj
+                    .get("additionalProperties")
+                    .filter(|a| a.is_object());
+                for (prop_name, prop_value) in inst_obj {
+                    let Some(prop_schema) =
+                        properties.and_then(|p| p.get(prop_name)).or(additional)
+                    else {
+                        continue;
+                    };
+                    let prop_path = if path.is_empty() {
+                        prop_name.clone()
+                    } else {
+                        format!("{path}.{prop_name}")
+                    };
+                    self.visit_instance(prop_value, prop_schema, root_schema, &prop_path, errors);
                 }
-            } else if type_str == "array"
-                && let Some(items) = sch_obj.get("items")
-                && let Some(inst_arr) = inst.as_array()
-            {
+            }
+            Value::Array(inst_arr) => {
+                // Draft 2020-12 `prefixItems` + `items`, or draft-07 tuple
+                // `items` + `additionalItems`
+                let (tuple, rest) = match (sch_obj.get("prefixItems"), sch_obj.get("items")) {
+                    (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+                    (_, Some(Value::Array(tuple))) => {
+                        (tuple.as_slice(), sch_obj.get("additionalItems"))
+                    }
+                    (_, rest) => (&[][..], rest),
+                };
                 for (idx, item) in inst_arr.iter().enumerate() {
+                    let Some(item_schema) = tuple.get(idx).or(rest) else {
+                        continue;
+                    };
                     let item_path = format!("{path}[{idx}]");
-                    self.visit_instance(item, items, root_schema, &item_path, errors);
+                    self.visit_instance(item, item_schema, root_schema, &item_path, errors);
                 }
             }
+            _ => {}
         }
     }
 
@@ -317,6 +375,7 @@ impl XGtsRefValidator {
                 }
             } else {
                 errors.push(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefInvalidPattern,
                     ref_path,
                     format!("{x_gts_ref:?}"),
                     String::new(),
@@ -363,6 +422,7 @@ impl XGtsRefValidator {
                 Some(resolved) => {
                     if !resolved.starts_with("gts.") {
                         return Some(XGtsRefValidationError::new(
+                            GtsCode::XGtsRefInvalidPattern,
                             field_path.to_owned(),
                             value.to_owned(),
                             ref_pattern.to_owned(),
@@ -375,6 +435,7 @@ impl XGtsRefValidator {
                 }
                 None => {
                     return Some(XGtsRefValidationError::new(
+                        GtsCode::XGtsRefUnresolved,
                         field_path.to_owned(),
                         value.to_owned(),
                         ref_pattern.to_owned(),
@@ -408,6 +469,7 @@ impl XGtsRefValidator {
                 Some(resolved) => {
                     if !GtsID::is_valid(&resolved) {
                         return Some(XGtsRefValidationError::new(
+                            GtsCode::XGtsRefInvalidPattern,
                             field_path.to_owned(),
                             ref_pattern.to_owned(),
                             ref_pattern.to_owned(),
@@ -419,6 +481,7 @@ impl XGtsRefValidator {
                     None
                 }
                 None => Some(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefUnresolved,
                     field_path.to_owned(),
                     ref_pattern.to_owned(),
                     ref_pattern.to_owned(),
@@ -427,6 +490,7 @@ impl XGtsRefValidator {
             }
         } else {
             Some(XGtsRefValidationError::new(
+                GtsCode::XGtsRefInvalidPattern,
                 field_path.to_owned(),
                 ref_pattern.to_owned(),
                 ref_pattern.to_owned(),
@@ -446,11 +510,12 @@ impl XGtsRefValidator {
             return None;
         }
 
-        // Wildcard pattern - validate prefix
+        // Wildcard pattern - validate prefix; `*` may only end the pattern
         if pattern.contains('*') {
-            let prefix = pattern.trim_end_matches('*');
-            if !prefix.starts_with("gts.") {
+            let prefix = pattern.strip_suffix('*').unwrap_or(pattern);
+            if !prefix.starts_with("gts.") || prefix.contains('*') {
                 return Some(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefInvalidPattern,
                     field_path.to_owned(),
                     pattern.to_owned(),
                     pattern.to_owned(),
@@ -463,6 +528,7 @@ impl XGtsRefValidator {
         // Specific GTS ID
         if !GtsID::is_valid(pattern) {
             return Some(XGtsRefValidationError::new(
+                GtsCode::XGtsRefInvalidPattern,
                 field_path.to_owned(),
                 pattern.to_owned(),
                 pattern.to_owned(),
@@ -474,7 +540,7 @@ impl XGtsRefValidator {
     }
 
     /// Validate value matches a GTS pattern
-    fn validate_gts_pattern(
+    pub(crate) fn validate_gts_pattern(
         &self,
         value: &str,
         pattern: &str,
@@ -483,6 +549,7 @@ impl XGtsRefValidator {
         // Validate it's a valid GTS ID
         if !GtsID::is_valid(value) {
             return Some(XGtsRefValidationError::new(
+                GtsCode::XGtsRefInvalidValue,
                 field_path.to_owned(),
                 value.to_owned(),
                 pattern.to_owned(),
@@ -496,6 +563,7 @@ impl XGtsRefValidator {
         } else if let Some(prefix) = pattern.strip_suffix('*') {
             if !value.starts_with(prefix) {
                 return Some(XGtsRefValidationError::new(
+                    GtsCode::XGtsRefMismatch,
                     field_path.to_owned(),
                     value.to_owned(),
                     pattern.to_owned(),
@@ -504,6 +572,7 @@ impl XGtsRefValidator {
             }
         } else if !value.starts_with(pattern) {
             return Some(XGtsRefValidationError::new(
+                GtsCode::XGtsRefMismatch,
                 field_path.to_owned(),
                 value.to_owned(),
                 pattern.to_owned(),
@@ -527,7 +596,7 @@ impl XGtsRefValidator {
     /// The resolved value as a string or None if not found.
     /// Note: For `/$id` references, the `gts://` prefix is stripped from the value
     /// as per GTS specification (relative self-reference should match the $id without the prefix).
-    fn resolve_pointer(schema: &Value, pointer: &str) -> Option<String> {
+    pub(crate) fn resolve_pointer(schema: &Value, pointer: &str) -> Option<String> {
         let path = pointer.trim_start_matches('/');
         if path.is_empty() {
             return None;
@@ -747,6 +816,7 @@ mod tests {
     #[test]
     fn test_validation_error_creation_and_display() {
         let error = XGtsRefValidationError::new(
+            GtsCode::XGtsRefMismatch,
             "test_field".to_owned(),
             "invalid_value".to_owned(),
             "gts.x.*".to_owned(),
@@ -1235,4 +1305,71 @@ mod tests {
         let result = XGtsRefValidator::strip_gts_uri_prefix("gts:/incomplete");
         assert_eq!(result, "gts:/incomplete");
     }
+
+    #[test]
+    fn test_validate_instance_arrays_of_refs() {
+        let validator = XGtsRefValidator::new();
+        let topic = json!({"type": "string", "x-gts-ref": "gts.x.core.events.topic.v1~*"});
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "topics": {"type": "array", "items": topic},
+                "pair": {"prefixItems": [topic, {"type": "string"}]},
+                "by_name": {"type": "object", "additionalProperties": topic},
+                "tags": {"type": "array", "x-gts-ref": "gts.x.core.tags.*"},
+                "parent": {"type": ["string", "null"], "x-gts-ref": "gts.x.core.events.topic.v1~*"}
+            }
+        });
+        let ok = "gts.x.core.events.topic.v1~x.app._.orders.v1";
+        let bad = "gts.x.core.events.queue.v1~x.app._.orders.v1";
+
+        let valid = json!({
+            "topics": [ok, ok],
+            "pair": [ok, "free text"],
+            "by_name": {"orders": ok},
+            "tags": ["gts.x.core.tags.tag.v1~x.app._.hot.v1"],
+            "parent": null
+        });
+        let errors = validator.validate_instance(&valid, &schema, "");
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let invalid = json!({
+            "topics": [ok, bad],
+            "pair": [bad, bad],
+            "by_name": {"orders": bad},
+            "tags": [ok],
+            "parent": bad
+        });
+        let mut paths: Vec<String> = validator
+            .validate_instance(&invalid, &schema, "")
+            .into_iter()
+            .inspect(|e| assert_eq!(e.code, GtsCode::XGtsRefMismatch))
+            .map(|e| e.field_path)
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "by_name.orders",
+                "pair[0]",
+                "parent",
+                "tags[0]",
+                "topics[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_schema_rejects_inner_wildcard() {
+        let validator = XGtsRefValidator::new();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "topic": {"type": "string", "x-gts-ref": "gts.x.*.events.topic.v1~"}
+            }
+        });
+        let errors = validator.validate_schema(&schema, "", None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, GtsCode::XGtsRefInvalidPattern);
+    }
 }
@@ -0,0 +1,31 @@
+//! Pins the signatures of the stable surface in `gts::prelude`.
+//!
+//! This only has to compile: an accidental signature change in one of these
+//! items breaks the build here before it breaks a downstream crate.
+
+use gts::prelude::*;
+use serde_json::Value;
+
+#[test]
+#[allow(clippy::type_complexity)]
+fn test_prelude_signatures() {
+    let _: fn(&str) -> Result<GtsID, GtsError> = GtsID::new;
+    let _: fn(&str) -> bool = GtsID::is_valid;
+    let _: fn(&str) -> Result<GtsWildcard, GtsError> = GtsWildcard::new;
+    let _: fn(&GtsID, &GtsWildcard) -> bool = GtsID::wildcard_match;
+
+    let _: fn(Option<Box<dyn GtsReader>>) -> GtsStore = GtsStore::new;
+    let _: fn(&mut GtsStore, GtsEntity) -> Result<(), StoreError> = GtsStore::register;
+    let _: fn(&mut GtsStore, &str, &Value) -> Result<(), StoreError> = GtsStore::register_schema;
+    let _: fn(&mut GtsStore, &str) -> Result<(), StoreError> = GtsStore::validate_instance;
+    let _: fn(&mut GtsStore, &str) -> Result<(), StoreError> = GtsStore::validate_schema;
+    let _: fn(&GtsStore, &str, usize) -> GtsStoreQueryResult = GtsStore::query;
+    let _: fn(&mut GtsStore) -> GtsValidateAllReport = GtsStore::validate_all;
+
+    let _: fn(Option<Vec<String>>, Option<String>, usize) -> GtsOps = GtsOps::new;
+    let _: fn(&str) -> GtsIdValidationResult = GtsOps::validate_id;
+    let _: fn(&str) -> GtsIdParseResult = GtsOps::parse_id;
+    let _: fn(&mut GtsOps, &str) -> GtsValidationResult = GtsOps::validate_instance;
+
+    let _: fn(&[String], Option<GtsConfig>) -> GtsFileReader = GtsFileReader::new;
+}